@@ -0,0 +1,4 @@
+-- Event payload schema versioning.
+-- Existing rows were written with the original (v1) payload shape.
+
+ALTER TABLE events ADD COLUMN schema_version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,10 @@
+-- Per-API-key quotas, adjustable at runtime via /admin/rate-limits.
+-- NULL limits are unlimited.
+
+CREATE TABLE rate_limits (
+    api_key             TEXT PRIMARY KEY,
+    requests_per_minute BIGINT,
+    swaps_per_day       BIGINT,
+    ws_connections      BIGINT,
+    updated_at          TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,21 @@
+-- Pre-aggregated OHLCV candles, folded from swaps by the candle worker.
+-- Prices are quote per base per the pool's price convention; volumes are
+-- raw token amounts.
+
+CREATE TABLE candles (
+    pool_id       UUID NOT NULL,
+    timeframe     VARCHAR(8) NOT NULL,
+    bucket_start  TIMESTAMPTZ NOT NULL,
+    open          DOUBLE PRECISION NOT NULL,
+    high          DOUBLE PRECISION NOT NULL,
+    low           DOUBLE PRECISION NOT NULL,
+    close         DOUBLE PRECISION NOT NULL,
+    base_volume   NUMERIC(78, 0) NOT NULL,
+    quote_volume  NUMERIC(78, 0) NOT NULL,
+    trades        BIGINT NOT NULL,
+    updated_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (pool_id, timeframe, bucket_start)
+);
+
+-- Cross-pool queries per timeframe (leaderboards, stats)
+CREATE INDEX idx_candles_timeframe_bucket ON candles (timeframe, bucket_start);
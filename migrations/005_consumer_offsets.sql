@@ -0,0 +1,8 @@
+-- Named consumer cursors over the event log, advanced by
+-- POST /api/v1/consumers/{name}/ack.
+
+CREATE TABLE consumer_offsets (
+    name           VARCHAR(64) PRIMARY KEY,
+    last_event_id  BIGINT NOT NULL,
+    updated_at     TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
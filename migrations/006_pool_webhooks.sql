@@ -0,0 +1,15 @@
+-- Webhooks attached to a pool via POST /api/v1/pools/{id}/webhooks.
+-- Empty event_types means every event; min_amount (decimal text) filters
+-- swaps by amount_in and liquidity changes by liquidity_delta.
+
+CREATE TABLE pool_webhooks (
+    id           UUID PRIMARY KEY,
+    pool_id      UUID NOT NULL,
+    url          TEXT NOT NULL,
+    event_types  TEXT[] NOT NULL DEFAULT '{}',
+    min_amount   TEXT,
+    secret       TEXT NOT NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_pool_webhooks_pool_id ON pool_webhooks (pool_id);
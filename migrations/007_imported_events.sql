@@ -0,0 +1,5 @@
+-- Flags events imported from another venue's history via
+-- POST /admin/pools/{id}/events/import. Imported events feed analytics
+-- but are never replayed.
+
+ALTER TABLE events ADD COLUMN imported BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,5 @@
+-- Per-API-key pool creation quotas, adjustable via PUT /admin/rate-limits.
+-- NULL means unlimited.
+
+ALTER TABLE rate_limits ADD COLUMN max_pools BIGINT;
+ALTER TABLE rate_limits ADD COLUMN pool_creations_per_day BIGINT;
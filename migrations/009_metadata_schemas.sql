@@ -0,0 +1,11 @@
+-- JSON Schemas that pool metadata must satisfy, set via
+-- PUT /admin/metadata-schemas/{scope}/{name}. scope is 'tenant' (name is a
+-- client id) or 'pool_type' (name is a pool type).
+
+CREATE TABLE metadata_schemas (
+    scope       TEXT NOT NULL,
+    name        TEXT NOT NULL,
+    schema      JSONB NOT NULL,
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (scope, name)
+);
@@ -0,0 +1,16 @@
+-- First successful response to each Idempotency-Key, replayed to retries of
+-- the same request within IDEMPOTENCY_TTL_SECS. client_id is '' for
+-- requests without an X-Client-Id.
+
+CREATE TABLE idempotency_keys (
+    client_id        TEXT NOT NULL,
+    idempotency_key  TEXT NOT NULL,
+    fingerprint      TEXT NOT NULL,
+    status           SMALLINT NOT NULL,
+    content_type     TEXT,
+    body             BYTEA NOT NULL,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (client_id, idempotency_key)
+);
+
+CREATE INDEX idx_idempotency_keys_created_at ON idempotency_keys (created_at);
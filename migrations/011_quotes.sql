@@ -0,0 +1,26 @@
+-- Quotes served by POST /api/v1/pools/{id}/quote, sampled per
+-- PERSISTENCE_QUOTE_AUDIT_SAMPLE_BPS, for best-execution audits against
+-- the swap_executed events of the same client. Amounts are raw token
+-- amounts; prices are whole-token prices as reported to the client.
+
+CREATE TABLE quotes (
+    id                BIGSERIAL PRIMARY KEY,
+    pool_id           UUID NOT NULL,
+    client_id         TEXT,
+    token_in          TEXT NOT NULL,
+    token_out         TEXT NOT NULL,
+    amount_in         NUMERIC(78, 0) NOT NULL,
+    amount_out        NUMERIC(78, 0) NOT NULL,
+    fee               NUMERIC(78, 0) NOT NULL,
+    spot_price        DOUBLE PRECISION NOT NULL,
+    execution_price   DOUBLE PRECISION NOT NULL,
+    price_impact_bps  INTEGER NOT NULL,
+    pool_version      BIGINT NOT NULL,
+    quoted_at         TIMESTAMPTZ NOT NULL
+);
+
+-- Per-client audits over a time range
+CREATE INDEX idx_quotes_client_quoted_at ON quotes (client_id, quoted_at);
+
+-- Per-pool audits over a time range
+CREATE INDEX idx_quotes_pool_quoted_at ON quotes (pool_id, quoted_at);
@@ -0,0 +1,24 @@
+-- Checkpoints left by event log compaction. They are kept out of the
+-- events table so every row there stays a pool event: consumers,
+-- replicas, and replay never see them. `summary` holds the
+-- CompactionSummary of the deleted range, including its swaps bucketed
+-- by hour, input token, and actor.
+
+CREATE TABLE event_checkpoints (
+    id          BIGSERIAL PRIMARY KEY,
+    pool_id     UUID NOT NULL,
+    range_from  TIMESTAMPTZ NOT NULL,
+    range_to    TIMESTAMPTZ NOT NULL,
+    summary     JSONB NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Per-pool lookups by the end of the compacted range
+CREATE INDEX idx_event_checkpoints_pool_range_to ON event_checkpoints (pool_id, range_to);
+
+-- Move checkpoints written into the event log by earlier versions
+INSERT INTO event_checkpoints (pool_id, range_from, range_to, summary, created_at)
+SELECT pool_id, COALESCE((payload->>'from')::timestamptz, created_at), created_at, payload, created_at
+FROM events WHERE event_type = 'events_compacted' ORDER BY id;
+
+DELETE FROM events WHERE event_type = 'events_compacted';
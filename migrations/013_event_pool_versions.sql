@@ -0,0 +1,5 @@
+-- Pool version each event left its pool at, so replay can skip events a
+-- snapshot already holds, or that were written twice, without relying on
+-- insert timestamps. NULL for events that change no pool state and for
+-- events written before this column existed.
+ALTER TABLE events ADD COLUMN pool_version BIGINT;
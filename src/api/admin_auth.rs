@@ -0,0 +1,104 @@
+//! Admin-only access to the `/admin` routes.
+//!
+//! Every route under `/admin/` can replay, compact, or restore history,
+//! pause pools, rewrite quotas, or start jobs, so it is reserved to the
+//! clients listed in `ADMIN_CLIENT_IDS`. The caller is identified by
+//! `X-Client-Id`, like everywhere else; with no admins configured the
+//! admin routes refuse every request.
+
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+
+use super::client_id::{CLIENT_ID_HEADER, parse_client_id};
+use crate::error::GatewayError;
+use crate::service::PoolService;
+use crate::service::ownership::Manager;
+
+/// Path prefix of the admin routes.
+pub const ADMIN_PREFIX: &str = "/admin/";
+
+/// Axum middleware rejecting requests to admin routes from clients that
+/// are not admins. Other routes pass through.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::Forbidden`] if the request targets an admin
+/// route without an admin client id.
+pub async fn require_admin(
+    State(pool_service): State<Arc<PoolService>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, GatewayError> {
+    if req.uri().path().starts_with(ADMIN_PREFIX) {
+        let client = req
+            .headers()
+            .get(CLIENT_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|raw| parse_client_id(raw).ok());
+        if !pool_service.is_admin(Manager::Client(client.as_deref())) {
+            return Err(GatewayError::Forbidden(format!(
+                "{} is reserved to admin clients",
+                req.uri().path()
+            )));
+        }
+    }
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use axum::Router;
+    use axum::body::Body;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use tower::Service;
+
+    use super::*;
+    use crate::domain::{EventBus, PoolRegistry};
+
+    #[tokio::test]
+    async fn admin_routes_refuse_non_admin_clients() {
+        let pool_service = Arc::new(
+            PoolService::new(Arc::new(PoolRegistry::new()), EventBus::new(16))
+                .with_admins(BTreeSet::from(["root".to_string()])),
+        );
+        let mut app = Router::new()
+            .route("/admin/rate-limits", get(|| async { "quotas" }))
+            .route("/api/v1/pools", get(|| async { "pools" }))
+            .layer(axum::middleware::from_fn_with_state(
+                pool_service,
+                require_admin,
+            ));
+        let mut status = async |path: &str, client: Option<&str>| {
+            let mut req = Request::builder().uri(path);
+            if let Some(client) = client {
+                req = req.header(CLIENT_ID_HEADER, client);
+            }
+            let Ok(req) = req.body(Body::empty()) else {
+                panic!("valid request");
+            };
+            let Ok(resp) = app.call(req).await;
+            resp.status()
+        };
+
+        assert_eq!(
+            status("/admin/rate-limits", Some("desk-1")).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status("/admin/rate-limits", None).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status("/admin/rate-limits", Some("root")).await,
+            StatusCode::OK
+        );
+        assert_eq!(status("/api/v1/pools", None).await, StatusCode::OK);
+    }
+}
@@ -0,0 +1,74 @@
+//! Client identity carried by the `X-Client-Id` header.
+//!
+//! The gateway has no authentication of its own; deployments put it
+//! behind a proxy that authenticates callers and sets `X-Client-Id`.
+//! Commands record the id as the `actor` of the events they cause, which
+//! is what account-scoped WebSocket subscriptions filter on.
+
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+
+use crate::error::GatewayError;
+
+/// Header naming the calling client.
+pub const CLIENT_ID_HEADER: &str = "x-client-id";
+
+/// Longest accepted client id, in bytes.
+pub const MAX_CLIENT_ID_LEN: usize = 128;
+
+/// The calling client's id, if the request carried one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientId(pub Option<String>);
+
+impl ClientId {
+    /// Returns the id as a string slice.
+    #[must_use]
+    pub fn as_deref(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+}
+
+/// Validates a client id: 1 to [`MAX_CLIENT_ID_LEN`] visible ASCII
+/// characters.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] if `raw` is empty, too long,
+/// or contains whitespace or control characters.
+pub fn parse_client_id(raw: &str) -> Result<String, GatewayError> {
+    if raw.is_empty() || raw.len() > MAX_CLIENT_ID_LEN || !raw.bytes().all(|b| b.is_ascii_graphic())
+    {
+        return Err(GatewayError::InvalidRequest(format!(
+            "{CLIENT_ID_HEADER} must be 1-{MAX_CLIENT_ID_LEN} visible ASCII characters"
+        )));
+    }
+    Ok(raw.to_string())
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientId {
+    type Rejection = GatewayError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(CLIENT_ID_HEADER) else {
+            return Ok(Self(None));
+        };
+        let raw = value.to_str().map_err(|_| {
+            GatewayError::InvalidRequest(format!("{CLIENT_ID_HEADER} is not valid ASCII"))
+        })?;
+        parse_client_id(raw).map(|id| Self(Some(id)))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_blank_and_oversized_ids() {
+        assert!(parse_client_id("bot-1").is_ok());
+        assert!(parse_client_id("").is_err());
+        assert!(parse_client_id("has space").is_err());
+        assert!(parse_client_id(&"x".repeat(MAX_CLIENT_ID_LEN + 1)).is_err());
+    }
+}
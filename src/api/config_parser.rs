@@ -0,0 +1,754 @@
+//! Pool configuration parsing.
+//!
+//! Converts the type-specific JSON `config` of a pool creation request
+//! into a typed [`PoolConfigDto`] and then a hydra-amm [`AmmConfig`].
+//! Also used to rebuild pools from stored configs during replay and
+//! recovery.
+
+use hydra_amm::config::{
+    AmmConfig, ClmmConfig, ConstantProductConfig, DynamicConfig, HybridConfig, OrderBookConfig,
+    WeightedConfig,
+};
+use hydra_amm::domain::{
+    Amount, BasisPoints, Decimals, FeeTier, Liquidity, Position, Price, Tick, Token, TokenAddress,
+    TokenPair,
+};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+
+use crate::api::dto::{PoolConfigDto, TokenDto, WeightedTokenDto, parse_number};
+use crate::domain::oracle_bounds::OracleBounds;
+use crate::domain::pool_entry::TokenInfo;
+use crate::domain::pool_operation::TokenSide;
+use crate::error::GatewayError;
+
+/// Parses a pool-type-specific JSON config into an `AmmConfig`.
+///
+/// Returns the config together with its fee tier in basis points.
+///
+/// # Errors
+///
+/// Returns a [`GatewayError`] on invalid or unsupported configuration.
+pub fn parse_pool_config(
+    pool_type: &str,
+    config: &serde_json::Value,
+) -> Result<(AmmConfig, u32), GatewayError> {
+    let typed = parse_config_dto(pool_type, config)?;
+    if let PoolConfigDto::Dynamic(dynamic) = &typed
+        && let Some(reason) =
+            OracleBounds::from_config(config)?.violation(None, dynamic.oracle_price)
+    {
+        return Err(GatewayError::InvalidRequest(format!(
+            "oracle_price {reason}"
+        )));
+    }
+    amm_config(&typed)
+}
+
+/// Parses a pool-type-specific JSON config into its typed form. Keys the
+/// pool type does not take are ignored.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidPoolType`] for an unknown pool type and
+/// [`GatewayError::InvalidRequest`] when the config does not match it.
+pub fn parse_config_dto(
+    pool_type: &str,
+    config: &serde_json::Value,
+) -> Result<PoolConfigDto, GatewayError> {
+    fn typed<T: DeserializeOwned>(config: &serde_json::Value) -> Result<T, GatewayError> {
+        T::deserialize(config)
+            .map_err(|e| GatewayError::InvalidRequest(format!("invalid config: {e}")))
+    }
+    Ok(match pool_type {
+        "constant_product" => PoolConfigDto::ConstantProduct(typed(config)?),
+        "clmm" => PoolConfigDto::Clmm(typed(config)?),
+        "hybrid" => PoolConfigDto::Hybrid(typed(config)?),
+        "weighted" => PoolConfigDto::Weighted(typed(config)?),
+        "dynamic" => PoolConfigDto::Dynamic(typed(config)?),
+        "orderbook" => PoolConfigDto::OrderBook(typed(config)?),
+        other => return Err(GatewayError::InvalidPoolType(other.to_string())),
+    })
+}
+
+/// Reads the field `key` of a config, for checks of single fields.
+fn field<T: DeserializeOwned>(config: &serde_json::Value, key: &str) -> Result<T, GatewayError> {
+    let value = config
+        .get(key)
+        .ok_or_else(|| GatewayError::InvalidRequest(format!("missing {key}")))?;
+    T::deserialize(value).map_err(|e| GatewayError::InvalidRequest(format!("invalid {key}: {e}")))
+}
+
+/// Reads the numeric field `key` of a config, given as a number or a
+/// numeric string.
+fn number_field<T>(config: &serde_json::Value, key: &str) -> Result<T, GatewayError>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let value = config
+        .get(key)
+        .ok_or_else(|| GatewayError::InvalidRequest(format!("missing {key}")))?;
+    parse_number(value).map_err(|e| GatewayError::InvalidRequest(format!("invalid {key}: {e}")))
+}
+
+/// Bounds of the CLMM tick range, matching hydra-amm.
+const MAX_TICK: i32 = 887_272;
+
+/// Expands an `initial_price` in a config into the fields the pool type
+/// actually takes, so clients need not precompute consistent reserves.
+///
+/// The price is quoted like every reported price: in raw units, per the
+/// config's `price_convention` (token B per token A by default).
+///
+/// - `constant_product`: exactly one of `reserve_a`/`reserve_b` is given
+///   and the other is derived.
+/// - `clmm`: `current_tick` is derived.
+/// - `dynamic`: `oracle_price` is set, and a missing reserve is derived
+///   so both sides hold equal value.
+///
+/// The returned config no longer carries `initial_price`; configs
+/// without one are returned unchanged.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] if the price is not a
+/// positive number, the pool type does not support it, the fields it
+/// derives are also given explicitly, or a derived value is out of range.
+pub fn resolve_initial_price(
+    pool_type: &str,
+    mut config: serde_json::Value,
+) -> Result<serde_json::Value, GatewayError> {
+    let Some(raw_price) = config.get("initial_price") else {
+        return Ok(config);
+    };
+    let invalid = |msg: &str| GatewayError::InvalidRequest(format!("initial_price: {msg}"));
+    let price = raw_price
+        .as_f64()
+        .or_else(|| raw_price.as_str().and_then(|s| s.parse().ok()))
+        .filter(|p: &f64| p.is_finite() && *p > 0.0)
+        .ok_or_else(|| invalid("expected a positive number"))?;
+    // Price of token A in token B, whatever the reporting convention.
+    let price = match price_base(&token_infos(pool_type, &config), &config)? {
+        TokenSide::First => price,
+        TokenSide::Second => 1.0 / price,
+    };
+
+    let mut derived = serde_json::Map::new();
+    match pool_type {
+        "constant_product" | "dynamic" => {
+            if pool_type == "dynamic" {
+                if config.get("oracle_price").is_some() {
+                    return Err(invalid("give either initial_price or oracle_price"));
+                }
+                derived.insert("oracle_price".to_string(), serde_json::json!(price));
+            }
+            let given = |key: &str| {
+                config
+                    .get(key)
+                    .map(|_| number_field::<u128>(&config, key))
+                    .transpose()
+            };
+            match (given("reserve_a")?, given("reserve_b")?) {
+                (Some(a), None) => {
+                    derived.insert("reserve_b".to_string(), scaled(a, price)?);
+                }
+                (None, Some(b)) => {
+                    derived.insert("reserve_a".to_string(), scaled(b, 1.0 / price)?);
+                }
+                (Some(_), Some(_)) if pool_type == "dynamic" => {}
+                _ => return Err(invalid("give exactly one of reserve_a and reserve_b")),
+            }
+        }
+        "clmm" => {
+            if config.get("current_tick").is_some() {
+                return Err(invalid("give either initial_price or current_tick"));
+            }
+            let tick = (price.ln() / 1.0001_f64.ln()).round();
+            if tick.abs() > f64::from(MAX_TICK) {
+                return Err(invalid("price is outside the tick range"));
+            }
+            #[allow(clippy::cast_possible_truncation)]
+            derived.insert("current_tick".to_string(), serde_json::json!(tick as i32));
+        }
+        other => return Err(invalid(&format!("not supported for {other} pools"))),
+    }
+    if let Some(fields) = config.as_object_mut() {
+        fields.remove("initial_price");
+        fields.extend(derived);
+    }
+    Ok(config)
+}
+
+/// Scales a reserve by `factor`, returning it string-encoded.
+fn scaled(amount: u128, factor: f64) -> Result<serde_json::Value, GatewayError> {
+    let value = (amount as f64 * factor).round();
+    if !(1.0..u128::MAX as f64).contains(&value) {
+        return Err(GatewayError::InvalidRequest(
+            "initial_price: derived reserve is out of range".to_string(),
+        ));
+    }
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    Ok(serde_json::json!((value as u128).to_string()))
+}
+
+/// Widest spread, in orders of magnitude, between the whole-token amounts
+/// of a new pool's initial reserves.
+const MAX_RESERVE_SPREAD_DECADES: f64 = 15.0;
+
+/// How the reserves of a config are expressed, per its `reserve_units`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReserveUnits {
+    /// Integers in each token's smallest unit (the default).
+    Smallest,
+    /// Decimal amounts of whole tokens.
+    Human,
+    /// Whole tokens when the amount has a fractional part or is below one
+    /// whole token in smallest units; smallest units otherwise.
+    Auto,
+}
+
+/// Converts the reserves of a config to smallest units, per its optional
+/// `reserve_units`:
+///
+/// - `"smallest"` (default): reserves are already in smallest units; a
+///   reserve with a fractional part is rejected as a likely whole-token
+///   amount.
+/// - `"human"`: every reserve is a whole-token amount such as `"1.5"`,
+///   scaled by its token's decimals.
+/// - `"auto"`: each reserve is read as whole tokens when it has a
+///   fractional part or is below one whole token in smallest units.
+///
+/// Applies to `reserve_a`/`reserve_b`, or `reserves` of weighted pools.
+/// The returned config holds smallest units and no longer carries
+/// `reserve_units`.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] for an unknown mode, a pool
+/// type without reserves, or a reserve that cannot be converted.
+pub fn resolve_reserve_units(
+    pool_type: &str,
+    mut config: serde_json::Value,
+) -> Result<serde_json::Value, GatewayError> {
+    let units = match config.get("reserve_units") {
+        None => ReserveUnits::Smallest,
+        Some(mode) => match mode.as_str() {
+            Some("smallest") => ReserveUnits::Smallest,
+            Some("human") => ReserveUnits::Human,
+            Some("auto") => ReserveUnits::Auto,
+            _ => {
+                return Err(GatewayError::InvalidRequest(
+                    "reserve_units: expected smallest, human, or auto".to_string(),
+                ));
+            }
+        },
+    };
+    let decimals_of =
+        |token: Option<&serde_json::Value>| token?.get("decimals")?.as_u64()?.try_into().ok();
+    match pool_type {
+        "constant_product" | "hybrid" | "dynamic" => {
+            for (key, token) in [("reserve_a", "token_a"), ("reserve_b", "token_b")] {
+                let decimals = decimals_of(config.get(token));
+                if let Some(reserve) = config.get_mut(key) {
+                    normalize_reserve(reserve, key, decimals, units)?;
+                }
+            }
+        }
+        "weighted" => {
+            let decimals: Vec<Option<u8>> = config
+                .get("tokens")
+                .and_then(|v| v.as_array())
+                .map(|tokens| tokens.iter().map(|t| decimals_of(Some(t))).collect())
+                .unwrap_or_default();
+            if let Some(reserves) = config.get_mut("reserves").and_then(|v| v.as_array_mut()) {
+                for (i, reserve) in reserves.iter_mut().enumerate() {
+                    let decimals = decimals.get(i).copied().flatten();
+                    normalize_reserve(reserve, &format!("reserves[{i}]"), decimals, units)?;
+                }
+            }
+        }
+        other if config.get("reserve_units").is_some() => {
+            return Err(GatewayError::InvalidRequest(format!(
+                "reserve_units: not supported for {other} pools"
+            )));
+        }
+        _ => {}
+    }
+    if let Some(fields) = config.as_object_mut() {
+        fields.remove("reserve_units");
+    }
+    Ok(config)
+}
+
+/// Rewrites one reserve in smallest units, string-encoded. Values that
+/// are neither strings nor numbers are left for parsing to reject.
+fn normalize_reserve(
+    reserve: &mut serde_json::Value,
+    field: &str,
+    decimals: Option<u8>,
+    units: ReserveUnits,
+) -> Result<(), GatewayError> {
+    let text = match reserve {
+        serde_json::Value::String(s) => s.trim().to_string(),
+        serde_json::Value::Number(n) => n.to_string(),
+        _ => return Ok(()),
+    };
+    let fractional = text.contains('.');
+    let whole_tokens = match units {
+        ReserveUnits::Smallest if fractional => {
+            return Err(GatewayError::InvalidRequest(format!(
+                "{field}: {text} is not in smallest units; set reserve_units to human or auto"
+            )));
+        }
+        ReserveUnits::Smallest => false,
+        ReserveUnits::Human => true,
+        ReserveUnits::Auto => {
+            fractional
+                || decimals
+                    .and_then(|d| 10u128.checked_pow(u32::from(d)))
+                    .zip(text.parse::<u128>().ok())
+                    .is_some_and(|(one, raw)| raw < one)
+        }
+    };
+    if whole_tokens {
+        let decimals = decimals.ok_or_else(|| {
+            GatewayError::InvalidRequest(format!(
+                "{field}: token decimals are needed to read whole tokens"
+            ))
+        })?;
+        let raw = to_smallest_units(&text, decimals).ok_or_else(|| {
+            GatewayError::InvalidRequest(format!(
+                "{field}: {text} is not an amount of a token with {decimals} decimals"
+            ))
+        })?;
+        *reserve = serde_json::json!(raw.to_string());
+    }
+    Ok(())
+}
+
+/// Parses a whole-token decimal such as `"1.5"` into smallest units.
+fn to_smallest_units(text: &str, decimals: u8) -> Option<u128> {
+    let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+    let width = usize::from(decimals);
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > width
+        || !digits(whole)
+        || !digits(fraction)
+    {
+        return None;
+    }
+    let scale = 10u128.checked_pow(u32::from(decimals))?;
+    let whole: u128 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().ok()?
+    };
+    let fraction: u128 = if width == 0 {
+        0
+    } else {
+        format!("{fraction:0<width$}").parse().ok()?
+    };
+    whole.checked_mul(scale)?.checked_add(fraction)
+}
+
+/// Rejects initial reserves whose whole-token amounts are more than
+/// 10^15 apart, which almost always means one was given in the wrong
+/// units (e.g. 1 wei against a million tokens). Empty reserves are not
+/// compared.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] when the spread is too wide.
+pub fn check_reserve_spread(tokens: &[TokenInfo], reserves: &[u128]) -> Result<(), GatewayError> {
+    let magnitudes = reserves
+        .iter()
+        .zip(tokens)
+        .filter(|(reserve, _)| **reserve > 0)
+        .map(|(reserve, token)| (*reserve as f64).log10() - f64::from(token.decimals));
+    let (low, high) = magnitudes.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), m| {
+        (lo.min(m), hi.max(m))
+    });
+    let spread = high - low;
+    if spread > MAX_RESERVE_SPREAD_DECADES {
+        return Err(GatewayError::InvalidRequest(format!(
+            "reserves are {spread:.0} orders of magnitude apart in whole tokens; \
+             check the token decimals and reserve_units"
+        )));
+    }
+    Ok(())
+}
+
+/// A problem found by [`config_issues`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Offending field (`pool_type`, a config key such as `token_a`, or
+    /// `config` for problems spanning several fields).
+    pub field: String,
+    /// What is wrong with it.
+    pub message: String,
+}
+
+impl ConfigIssue {
+    /// Builds an issue from a parse error, dropping the error-kind prefix
+    /// of [`GatewayError::InvalidRequest`].
+    #[must_use]
+    pub fn new(field: &str, err: &GatewayError) -> Self {
+        let message = match err {
+            GatewayError::InvalidRequest(msg) => msg.clone(),
+            other => other.to_string(),
+        };
+        Self {
+            field: field.to_string(),
+            message,
+        }
+    }
+}
+
+/// Checks every top-level field of a config independently and returns
+/// all problems found, rather than stopping at the first like
+/// [`parse_pool_config`].
+///
+/// An empty result does not guarantee the config is accepted: value
+/// combinations (e.g. identical tokens) are only checked by
+/// [`parse_pool_config`] and hydra-amm.
+#[must_use]
+pub fn config_issues(pool_type: &str, config: &serde_json::Value) -> Vec<ConfigIssue> {
+    let (amounts, integers, decimals): (&[&str], &[&str], &[&str]) = match pool_type {
+        "constant_product" => (&["reserve_a", "reserve_b"], &[], &[]),
+        "clmm" => (&[], &["tick_spacing", "current_tick"], &[]),
+        "hybrid" => (&["reserve_a", "reserve_b"], &["amplification"], &[]),
+        "weighted" => (&[], &[], &[]),
+        "dynamic" => (
+            &["reserve_a", "reserve_b"],
+            &[],
+            &["oracle_price", "slippage_coefficient"],
+        ),
+        "orderbook" => (&["tick_size", "lot_size"], &[], &[]),
+        other => {
+            return vec![ConfigIssue::new(
+                "pool_type",
+                &GatewayError::InvalidPoolType(other.to_string()),
+            )];
+        }
+    };
+    if !config.is_object() {
+        return vec![ConfigIssue {
+            field: "config".to_string(),
+            message: "expected an object".to_string(),
+        }];
+    }
+
+    let mut issues = Vec::new();
+    let mut check = |field: &str, result: Result<(), GatewayError>| {
+        if let Err(e) = result {
+            issues.push(ConfigIssue::new(field, &e));
+        }
+    };
+    if pool_type == "weighted" {
+        check(
+            "tokens",
+            field::<Vec<WeightedTokenDto>>(config, "tokens").map(|_| ()),
+        );
+        check(
+            "reserves",
+            field::<Vec<serde_json::Value>>(config, "reserves").and_then(|reserves| {
+                reserves.iter().try_for_each(|r| {
+                    parse_number::<u128>(r)
+                        .map(|_| ())
+                        .map_err(|e| GatewayError::InvalidRequest(format!("invalid reserves: {e}")))
+                })
+            }),
+        );
+    } else {
+        for key in ["token_a", "token_b"] {
+            check(key, field::<TokenDto>(config, key).map(|_| ()));
+        }
+    }
+    check(
+        "fee_bps",
+        number_field::<u32>(config, "fee_bps").map(|_| ()),
+    );
+    for key in amounts {
+        check(key, number_field::<u128>(config, key).map(|_| ()));
+    }
+    for key in integers {
+        check(key, number_field::<i64>(config, key).map(|_| ()));
+    }
+    for key in decimals {
+        check(key, number_field::<f64>(config, key).map(|_| ()));
+    }
+    if pool_type == "dynamic" {
+        check(
+            "oracle_bounds",
+            OracleBounds::from_config(config).map(|_| ()),
+        );
+    }
+    let tokens = token_infos(pool_type, config);
+    check("price_convention", price_base(&tokens, config).map(|_| ()));
+    issues
+}
+
+/// Extracts token metadata from a config, in pool order.
+///
+/// Tokens without an address or decimals are skipped; call
+/// [`parse_pool_config`] first to validate the config.
+#[must_use]
+pub fn token_infos(pool_type: &str, config: &serde_json::Value) -> Vec<TokenInfo> {
+    let values: Vec<&serde_json::Value> = if pool_type == "weighted" {
+        config
+            .get("tokens")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().collect())
+            .unwrap_or_default()
+    } else {
+        ["token_a", "token_b"]
+            .iter()
+            .filter_map(|key| config.get(*key))
+            .collect()
+    };
+    values
+        .into_iter()
+        .filter_map(|t| TokenDto::deserialize(t).ok())
+        .map(|t| TokenInfo {
+            address: t.address,
+            decimals: t.decimals,
+            symbol: t.symbol,
+        })
+        .collect()
+}
+
+/// Extracts the initial reserves declared by a config, in token order.
+///
+/// Returns `None` for pool types whose liquidity is not expressed as
+/// plain reserves (CLMM, order book).
+#[must_use]
+pub fn initial_reserves(pool_type: &str, config: &serde_json::Value) -> Option<Vec<u128>> {
+    match pool_type {
+        "constant_product" | "hybrid" | "dynamic" => Some(vec![
+            number_field(config, "reserve_a").ok()?,
+            number_field(config, "reserve_b").ok()?,
+        ]),
+        "weighted" => config
+            .get("reserves")?
+            .as_array()?
+            .iter()
+            .map(|r| parse_number(r).ok())
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Resolves the optional `price_convention` of a config to the side of
+/// the pair whose price is reported.
+///
+/// `{"base": "<address>", "quote": "<address>"}` quotes prices as quote
+/// per base; either key may be omitted. Without a convention prices are
+/// token B per token A.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] if the convention is not an
+/// object, names a token outside the pool's first two tokens, or gives
+/// the same token as base and quote.
+pub fn price_base(
+    tokens: &[TokenInfo],
+    config: &serde_json::Value,
+) -> Result<TokenSide, GatewayError> {
+    let Some(convention) = config.get("price_convention") else {
+        return Ok(TokenSide::First);
+    };
+    let invalid = |msg: &str| GatewayError::InvalidRequest(format!("price_convention: {msg}"));
+    let convention = convention
+        .as_object()
+        .ok_or_else(|| invalid("expected an object with base and/or quote"))?;
+    let side_of = |key: &str| -> Result<Option<TokenSide>, GatewayError> {
+        let Some(value) = convention.get(key) else {
+            return Ok(None);
+        };
+        let address = value
+            .as_str()
+            .ok_or_else(|| invalid(&format!("{key} must be a token address")))?;
+        [TokenSide::First, TokenSide::Second]
+            .into_iter()
+            .find(|side| {
+                tokens
+                    .get(side.index())
+                    .is_some_and(|t| t.address.eq_ignore_ascii_case(address))
+            })
+            .map(Some)
+            .ok_or_else(|| invalid(&format!("{key} {address} is not a pool token")))
+    };
+    match (side_of("base")?, side_of("quote")?) {
+        (Some(base), Some(quote)) if base == quote => {
+            Err(invalid("base and quote must be different tokens"))
+        }
+        (Some(base), _) => Ok(base),
+        (None, Some(quote)) => Ok(quote.other()),
+        (None, None) => Ok(TokenSide::First),
+    }
+}
+
+/// Converts a token of a config into a hydra-amm token. Addresses are
+/// truncated to 32 bytes.
+fn amm_token(token: &TokenDto) -> Result<Token, GatewayError> {
+    let mut bytes = [0u8; 32];
+    let addr_bytes = token.address.as_bytes();
+    let len = addr_bytes.len().min(32);
+    if let (Some(dst), Some(src)) = (bytes.get_mut(..len), addr_bytes.get(..len)) {
+        dst.copy_from_slice(src);
+    }
+
+    let decimals = Decimals::new(token.decimals)
+        .map_err(|e| GatewayError::InvalidRequest(format!("invalid decimals: {e}")))?;
+
+    Ok(Token::new(TokenAddress::from_bytes(bytes), decimals))
+}
+
+fn fee_tier(fee_bps: u32) -> FeeTier {
+    FeeTier::new(BasisPoints::new(fee_bps))
+}
+
+/// Builds the hydra-amm config of a typed config, with its fee tier in
+/// basis points.
+fn amm_config(config: &PoolConfigDto) -> Result<(AmmConfig, u32), GatewayError> {
+    let pair = |a: &TokenDto, b: &TokenDto| -> Result<TokenPair, GatewayError> {
+        Ok(TokenPair::new(amm_token(a)?, amm_token(b)?)?)
+    };
+    let amm = match config {
+        PoolConfigDto::ConstantProduct(c) => {
+            AmmConfig::ConstantProduct(ConstantProductConfig::new(
+                pair(&c.token_a, &c.token_b)?,
+                fee_tier(c.fee_bps),
+                Amount::new(c.reserve_a),
+                Amount::new(c.reserve_b),
+            )?)
+        }
+        PoolConfigDto::Clmm(c) => {
+            let positions = c
+                .positions
+                .iter()
+                .map(|p| {
+                    Ok(Position::new(
+                        Tick::new(p.lower_tick)?,
+                        Tick::new(p.upper_tick)?,
+                        Liquidity::new(p.liquidity),
+                    )?)
+                })
+                .collect::<Result<Vec<_>, GatewayError>>()?;
+            AmmConfig::Clmm(ClmmConfig::new(
+                pair(&c.token_a, &c.token_b)?,
+                fee_tier(c.fee_bps),
+                c.tick_spacing,
+                Tick::new(c.current_tick)?,
+                positions,
+            )?)
+        }
+        PoolConfigDto::Hybrid(c) => AmmConfig::Hybrid(HybridConfig::new(
+            pair(&c.token_a, &c.token_b)?,
+            fee_tier(c.fee_bps),
+            c.amplification,
+            Amount::new(c.reserve_a),
+            Amount::new(c.reserve_b),
+        )?),
+        PoolConfigDto::Weighted(c) => AmmConfig::Weighted(WeightedConfig::new(
+            c.tokens
+                .iter()
+                .map(|t| amm_token(&t.token))
+                .collect::<Result<_, _>>()?,
+            c.tokens
+                .iter()
+                .map(|t| BasisPoints::new(t.weight))
+                .collect(),
+            fee_tier(c.fee_bps),
+            c.reserves.iter().copied().map(Amount::new).collect(),
+        )?),
+        PoolConfigDto::Dynamic(c) => AmmConfig::Dynamic(DynamicConfig::new(
+            pair(&c.token_a, &c.token_b)?,
+            fee_tier(c.fee_bps),
+            Price::new(c.oracle_price)?,
+            c.slippage_coefficient,
+            Amount::new(c.reserve_a),
+            Amount::new(c.reserve_b),
+        )?),
+        PoolConfigDto::OrderBook(c) => AmmConfig::OrderBook(OrderBookConfig::new(
+            pair(&c.token_a, &c.token_b)?,
+            fee_tier(c.fee_bps),
+            Amount::new(c.tick_size),
+            Amount::new(c.lot_size),
+        )?),
+    };
+    Ok((amm, config.fee_bps()))
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn token(address: &str) -> serde_json::Value {
+        serde_json::json!({"address": address, "decimals": 18})
+    }
+
+    #[test]
+    fn numbers_parse_alike_as_strings_and_numbers() {
+        let config = serde_json::json!({
+            "token_a": token("a"),
+            "token_b": token("b"),
+            "fee_bps": "30",
+            "reserve_a": "340282366920938463463374607431768211455",
+            "reserve_b": 1_000_000,
+            "price_convention": {"base": "b"},
+        });
+        let Ok(PoolConfigDto::ConstantProduct(typed)) =
+            parse_config_dto("constant_product", &config)
+        else {
+            panic!("constant_product config rejected");
+        };
+        assert_eq!(typed.fee_bps, 30);
+        assert_eq!(typed.reserve_a, u128::MAX);
+        assert_eq!(typed.reserve_b, 1_000_000);
+
+        let weighted = serde_json::json!({
+            "tokens": [
+                {"address": "a", "decimals": 18, "weight": 8000},
+                {"address": "b", "decimals": 6, "weight": "2000"},
+            ],
+            "fee_bps": 30,
+            "reserves": ["1000", 2000],
+        });
+        let Ok((_, fee_bps)) = parse_pool_config("weighted", &weighted) else {
+            panic!("weighted config rejected");
+        };
+        assert_eq!(fee_bps, 30);
+        assert_eq!(
+            initial_reserves("weighted", &weighted),
+            Some(vec![1000, 2000])
+        );
+    }
+
+    #[test]
+    fn mismatched_configs_are_rejected() {
+        assert!(matches!(
+            parse_config_dto("curve", &serde_json::json!({})),
+            Err(GatewayError::InvalidPoolType(_))
+        ));
+        let clmm = serde_json::json!({
+            "token_a": token("a"),
+            "token_b": {"address": "b"},
+            "fee_bps": 5,
+            "tick_spacing": "wide",
+            "current_tick": -10,
+        });
+        assert!(matches!(
+            parse_pool_config("clmm", &clmm),
+            Err(GatewayError::InvalidRequest(_))
+        ));
+        let fields: Vec<String> = config_issues("clmm", &clmm)
+            .into_iter()
+            .map(|issue| issue.field)
+            .collect();
+        assert_eq!(fields, ["token_b", "tick_spacing"]);
+    }
+}
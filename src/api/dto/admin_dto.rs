@@ -0,0 +1,1070 @@
+//! Administrative DTOs: event replay, event log browsing, the startup
+//! report, compaction,
+//! rate limits, metadata schemas, snapshots, the quote audit trail,
+//! backtests, background jobs, and WebSocket connections.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::consumer_dto::ConsumerEventDto;
+use crate::domain::PoolId;
+use crate::domain::pool_entry::PoolStatus;
+use crate::persistence::compaction::{CompactionSummary, EventCheckpoint};
+use crate::persistence::models::{MetadataSchemaRecord, SnapshotInfo, StoredQuote};
+use crate::service::backtest::{Backtest, BacktestRun, ParameterOverrides};
+use crate::service::contention::{ContentionStats, OperationSample};
+use crate::service::jobs::{JobInfo, JobKind, JobStatus};
+use crate::service::pool_service::{CapacityUsage, RestoredPool};
+use crate::service::quota::KeyQuota;
+use crate::service::replay::{ReplayedPool, StartupReport};
+use crate::service::stale_pools::StalePool;
+use crate::ws::outbound::ConnectionInfo;
+
+/// Request body for `POST /admin/replay`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReplayRequest {
+    /// Seed pools from the latest snapshot at or before this time. When
+    /// omitted, pools are rebuilt from their creation events.
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    /// Replay events up to and including this time. Defaults to now.
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+    /// Restrict the replay to a single pool.
+    #[serde(default)]
+    pub pool_id: Option<PoolId>,
+}
+
+/// Replayed state of a single pool.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReplayedPoolDto {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Pool type, when the pool could be rebuilt.
+    pub pool_type: Option<String>,
+    /// `"active"`, `"removed"`, or `"failed"`.
+    pub status: String,
+    /// Snapshot the pool was seeded from, if any.
+    pub base_snapshot_at: Option<DateTime<Utc>>,
+    /// Operations re-applied from the event log.
+    pub operations_applied: u64,
+    /// Events for this pool that could not be replayed.
+    pub events_skipped: u64,
+    /// Swaps whose replayed amounts differ from the recorded ones.
+    pub divergences: u64,
+    /// Spot price after replay, per the pool's price convention.
+    pub spot_price: Option<String>,
+    /// Total liquidity after replay (string-encoded).
+    pub total_liquidity: Option<String>,
+    /// Reserves keyed by token address (string-encoded).
+    pub reserves: BTreeMap<String, String>,
+    /// Swaps executed over the pool's lifetime.
+    pub swap_count: u64,
+    /// Why the pool could not be fully rebuilt.
+    pub error: Option<String>,
+}
+
+impl From<ReplayedPool> for ReplayedPoolDto {
+    fn from(p: ReplayedPool) -> Self {
+        Self {
+            pool_id: p.pool_id,
+            pool_type: p.pool_type,
+            status: p.status.to_string(),
+            base_snapshot_at: p.base_snapshot_at,
+            operations_applied: p.operations_applied,
+            events_skipped: p.events_skipped,
+            divergences: p.divergences,
+            spot_price: p.spot_price.map(|v| format!("{v}")),
+            total_liquidity: p.total_liquidity.map(|v| v.to_string()),
+            reserves: p
+                .reserves
+                .into_iter()
+                .map(|(token, amount)| (token, amount.to_string()))
+                .collect(),
+            swap_count: p.swap_count,
+            error: p.error,
+        }
+    }
+}
+
+/// Response body for `POST /admin/replay`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReplayResponse {
+    /// Snapshot cut-off used, if any.
+    pub from: Option<DateTime<Utc>>,
+    /// Event cut-off used.
+    pub to: DateTime<Utc>,
+    /// Stored events examined.
+    pub events_scanned: u64,
+    /// Events that changed pool state.
+    pub events_applied: u64,
+    /// Events that could not be replayed.
+    pub events_skipped: u64,
+    /// Wall-clock time spent replaying, in milliseconds.
+    pub duration_ms: u64,
+    /// Per-pool results.
+    pub pools: Vec<ReplayedPoolDto>,
+}
+
+/// Response body for `GET /admin/startup-report`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StartupReportResponse {
+    /// Whether pools were recovered; `false` when persistence or
+    /// recovery is disabled.
+    pub recovered: bool,
+    /// When recovery started.
+    pub started_at: DateTime<Utc>,
+    /// When the recovered pools were registered.
+    pub completed_at: DateTime<Utc>,
+    /// Wall-clock time spent recovering, in milliseconds.
+    pub duration_ms: u64,
+    /// Snapshots pools were seeded from.
+    pub snapshots_loaded: u64,
+    /// Stored events examined.
+    pub events_scanned: u64,
+    /// Events re-applied to pool state.
+    pub events_replayed: u64,
+    /// Events that could not be replayed.
+    pub events_skipped: u64,
+    /// Id of the last event log row read.
+    pub last_event_id: i64,
+    /// Pools registered after recovery.
+    pub pools_restored: usize,
+    /// Pools whose history ends with their removal.
+    pub pools_removed: usize,
+    /// Pools that could not be rebuilt, listed in `failed_pools`.
+    pub pools_failed: usize,
+    /// Replay results of the pools that could not be rebuilt.
+    pub failed_pools: Vec<ReplayedPoolDto>,
+}
+
+impl From<&StartupReport> for StartupReportResponse {
+    fn from(report: &StartupReport) -> Self {
+        Self {
+            recovered: report.recovered,
+            started_at: report.started_at,
+            completed_at: report.completed_at,
+            duration_ms: u64::try_from(report.duration.as_millis()).unwrap_or(u64::MAX),
+            snapshots_loaded: report.snapshots_loaded,
+            events_scanned: report.replay.events_scanned,
+            events_replayed: report.replay.events_applied,
+            events_skipped: report.replay.events_skipped,
+            last_event_id: report.last_event_id,
+            pools_restored: report.pools_with_status("active"),
+            pools_removed: report.pools_with_status("removed"),
+            pools_failed: report.pools_with_status("failed"),
+            failed_pools: report
+                .replay
+                .pools
+                .iter()
+                .filter(|p| p.status == "failed")
+                .cloned()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+/// Request body for `POST /admin/events/compact`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CompactEventsRequest {
+    /// Pool whose events are compacted.
+    pub pool_id: PoolId,
+    /// Start of the range (inclusive).
+    pub from: DateTime<Utc>,
+    /// End of the range (inclusive). Must be covered by a snapshot.
+    pub to: DateTime<Utc>,
+}
+
+/// Response body for `POST /admin/events/compact`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CompactEventsResponse {
+    /// Pool whose events were compacted.
+    pub pool_id: PoolId,
+    /// Event rows deleted.
+    pub events_deleted: u64,
+    /// ID of the checkpoint row that replaced them.
+    pub checkpoint_id: Option<i64>,
+    /// Aggregates preserved in the checkpoint.
+    pub summary: CompactionSummary,
+}
+
+/// Response body for `POST /admin/pools/:id/events/import`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportEventsResponse {
+    /// Pool the history was imported into.
+    pub pool_id: PoolId,
+    /// Events written to the log.
+    pub events_imported: usize,
+    /// Event log ID assigned to the first imported event.
+    pub first_event_id: i64,
+    /// Event log ID assigned to the last imported event.
+    pub last_event_id: i64,
+    /// Candle buckets updated from the imported swaps.
+    pub candles_updated: usize,
+}
+
+/// Query parameters for `GET /admin/pools/stale`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StalePoolParams {
+    /// Idle days to list pools for (defaults to `STALE_POOL_DAYS`).
+    #[serde(default)]
+    pub idle_days: Option<u32>,
+}
+
+/// Query parameters for `GET /admin/events/tail`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventTailParams {
+    /// Most recent events to print first (default 20, max 1000).
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Keep the response open and stream new events as they happen.
+    #[serde(default)]
+    pub follow: bool,
+    /// Only events of this pool.
+    #[serde(default)]
+    pub pool_id: Option<PoolId>,
+    /// Only events of this type (e.g. `swap_executed`).
+    #[serde(default)]
+    pub event_type: Option<String>,
+}
+
+/// Query parameters for `GET /admin/events`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventLogParams {
+    /// Only events of this pool.
+    #[serde(default)]
+    pub pool_id: Option<PoolId>,
+    /// Only events of this type (e.g. `swap_executed`).
+    #[serde(default)]
+    pub event_type: Option<String>,
+    /// Cursor: only events with a greater ID (default 0, the start of
+    /// the log). Pass the previous page's `next_after`.
+    #[serde(default)]
+    pub after: Option<i64>,
+    /// Maximum number of events (1–1000, default 100).
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+/// Response body for `GET /admin/events`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EventLogResponse {
+    /// Matching events after the cursor, in log order.
+    pub events: Vec<ConsumerEventDto>,
+    /// Cursor of the next page; `null` once the end of the log is reached.
+    pub next_after: Option<i64>,
+}
+
+/// A pool that qualifies for archiving.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StalePoolDto {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Pool type string.
+    pub pool_type: String,
+    /// Last state change (creation if none).
+    pub idle_since: DateTime<Utc>,
+    /// Whole days since `idle_since`.
+    pub idle_days: i64,
+}
+
+impl From<StalePool> for StalePoolDto {
+    fn from(pool: StalePool) -> Self {
+        Self {
+            pool_id: pool.pool_id,
+            pool_type: pool.pool_type,
+            idle_since: pool.idle_since,
+            idle_days: pool.idle_days,
+        }
+    }
+}
+
+/// Response body for `GET /admin/pools/stale`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StalePoolListResponse {
+    /// Idle days the list was computed for.
+    pub idle_days: u32,
+    /// Whether this instance archives stale pools automatically.
+    pub auto_archive: bool,
+    /// Stale pools, idle longest first.
+    pub data: Vec<StalePoolDto>,
+}
+
+/// Query parameters for `GET /admin/rate-limits`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RateLimitParams {
+    /// Only the quota of this API key.
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+/// Request body for `PUT /admin/rate-limits`. Omitted or `null` limits
+/// are unlimited.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateRateLimitRequest {
+    /// API key (the caller's `X-Client-Id`).
+    pub api_key: String,
+    /// Requests per UTC minute.
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    /// Swap executions per UTC day.
+    #[serde(default)]
+    pub swaps_per_day: Option<u32>,
+    /// Concurrently open WebSocket connections.
+    #[serde(default)]
+    pub ws_connections: Option<u32>,
+    /// Live pools created by the key.
+    #[serde(default)]
+    pub max_pools: Option<u32>,
+    /// Pool creations per UTC day.
+    #[serde(default)]
+    pub pool_creations_per_day: Option<u32>,
+}
+
+/// Usage counters of one API key on this instance.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RateLimitUsageDto {
+    /// Requests admitted in the current minute.
+    pub requests_this_minute: u32,
+    /// Swaps admitted in the current day.
+    pub swaps_today: u32,
+    /// Open WebSocket connections.
+    pub ws_connections: u32,
+    /// Live pools created by the key while it had a quota.
+    pub pools: u32,
+    /// Pool creations admitted in the current day.
+    pub pool_creations_today: u32,
+}
+
+/// Quota and current usage of one API key.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RateLimitDto {
+    /// API key (client id).
+    pub api_key: String,
+    /// Requests per UTC minute (`null` = unlimited).
+    pub requests_per_minute: Option<u32>,
+    /// Swap executions per UTC day (`null` = unlimited).
+    pub swaps_per_day: Option<u32>,
+    /// Concurrently open WebSocket connections (`null` = unlimited).
+    pub ws_connections: Option<u32>,
+    /// Live pools created by the key (`null` = unlimited).
+    pub max_pools: Option<u32>,
+    /// Pool creations per UTC day (`null` = unlimited).
+    pub pool_creations_per_day: Option<u32>,
+    /// Current usage.
+    pub usage: RateLimitUsageDto,
+}
+
+impl From<KeyQuota> for RateLimitDto {
+    fn from(q: KeyQuota) -> Self {
+        Self {
+            api_key: q.api_key,
+            requests_per_minute: q.limits.requests_per_minute,
+            swaps_per_day: q.limits.swaps_per_day,
+            ws_connections: q.limits.ws_connections,
+            max_pools: q.limits.max_pools,
+            pool_creations_per_day: q.limits.pool_creations_per_day,
+            usage: RateLimitUsageDto {
+                requests_this_minute: q.usage.requests_this_minute,
+                swaps_today: q.usage.swaps_today,
+                ws_connections: q.usage.ws_connections,
+                pools: q.usage.pools,
+                pool_creations_today: q.usage.pool_creations_today,
+            },
+        }
+    }
+}
+
+/// Response body for `GET /admin/rate-limits`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RateLimitListResponse {
+    /// Configured quotas, ordered by API key.
+    pub rate_limits: Vec<RateLimitDto>,
+}
+
+/// Response body for `PUT /admin/rate-limits`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UpdateRateLimitResponse {
+    /// The quota now in force.
+    pub rate_limit: RateLimitDto,
+    /// Whether the quota was written to the database. When persistence
+    /// is disabled it only lasts until restart.
+    pub persisted: bool,
+}
+
+/// A registered pool metadata schema.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MetadataSchemaDto {
+    /// `tenant` or `pool_type`.
+    pub scope: String,
+    /// Client id or pool type the schema applies to.
+    pub name: String,
+    /// The JSON Schema.
+    #[schema(value_type = Object)]
+    pub schema: serde_json::Value,
+    /// Last change.
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<MetadataSchemaRecord> for MetadataSchemaDto {
+    fn from(r: MetadataSchemaRecord) -> Self {
+        Self {
+            scope: r.scope,
+            name: r.name,
+            schema: r.schema,
+            updated_at: r.updated_at,
+        }
+    }
+}
+
+/// Response body for `GET /admin/metadata-schemas`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MetadataSchemaListResponse {
+    /// Registered schemas, tenant schemas first, then by name.
+    pub schemas: Vec<MetadataSchemaDto>,
+}
+
+/// Response body for `PUT /admin/metadata-schemas/:scope/:name`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UpdateMetadataSchemaResponse {
+    /// The schema now in force.
+    pub schema: MetadataSchemaDto,
+    /// Whether the schema was written to the database. When persistence
+    /// is disabled it only lasts until restart.
+    pub persisted: bool,
+}
+
+/// Response body for `GET /admin/stats`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CapacityStatsResponse {
+    /// Pools in the registry.
+    pub pools: usize,
+    /// Pool limit (`null` = unlimited).
+    pub max_pools: Option<usize>,
+    /// Liquidity positions tracked across all pools.
+    pub positions: usize,
+    /// Positions tracked by the pool holding the most.
+    pub max_positions_in_a_pool: usize,
+    /// Per-pool position limit (`null` = unlimited).
+    pub max_positions_per_pool: Option<usize>,
+    /// Mutations rejected since startup because a pool's write lock was
+    /// not acquired within `POOL_LOCK_TIMEOUT_MS`.
+    pub lock_timeouts: u64,
+}
+
+impl From<CapacityUsage> for CapacityStatsResponse {
+    fn from(usage: CapacityUsage) -> Self {
+        Self {
+            pools: usage.pools,
+            max_pools: usage.limits.max_pools,
+            positions: usage.positions,
+            max_positions_in_a_pool: usage.max_positions_in_a_pool,
+            max_positions_per_pool: usage.limits.max_positions_per_pool,
+            lock_timeouts: usage.lock_timeouts,
+        }
+    }
+}
+
+/// Operation holding a pool's write lock.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LockHolderDto {
+    /// Operation name, e.g. `swap` or `add_liquidity`.
+    pub operation: String,
+    /// When the lock was acquired.
+    pub since: DateTime<Utc>,
+    /// How long the lock has been held so far, in microseconds.
+    pub held_us: u64,
+}
+
+/// Lock timing of one finished operation.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OperationLatencyDto {
+    /// Operation name.
+    pub operation: String,
+    /// Time spent waiting for the lock, in microseconds.
+    pub wait_us: u64,
+    /// Time the lock was held, in microseconds.
+    pub held_us: u64,
+    /// Wait plus hold, in microseconds.
+    pub total_us: u64,
+    /// When the lock was released.
+    pub finished_at: DateTime<Utc>,
+}
+
+impl From<OperationSample> for OperationLatencyDto {
+    fn from(sample: OperationSample) -> Self {
+        Self {
+            operation: sample.operation.to_string(),
+            wait_us: micros(sample.wait),
+            held_us: micros(sample.held),
+            total_us: micros(sample.wait.saturating_add(sample.held)),
+            finished_at: sample.finished_at,
+        }
+    }
+}
+
+/// Response body for `GET /admin/pools/{id}/contention`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PoolContentionResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Operation holding the write lock (`null` when free).
+    pub holder: Option<LockHolderDto>,
+    /// Writers waiting for the lock.
+    pub queued_writers: usize,
+    /// Write lock acquisitions since startup.
+    pub acquisitions: u64,
+    /// Mean wait per acquisition, in microseconds.
+    pub average_wait_us: u64,
+    /// Longest wait of an acquisition, in microseconds.
+    pub max_wait_us: u64,
+    /// Writers rejected after `POOL_LOCK_TIMEOUT_MS`.
+    pub timeouts: u64,
+    /// Most recent operations, newest first.
+    pub recent: Vec<OperationLatencyDto>,
+}
+
+impl PoolContentionResponse {
+    /// Builds the response for `pool_id` as of `now`.
+    #[must_use]
+    pub fn new(pool_id: PoolId, stats: ContentionStats, now: DateTime<Utc>) -> Self {
+        Self {
+            pool_id,
+            holder: stats.holder.map(|holder| LockHolderDto {
+                operation: holder.operation.to_string(),
+                since: holder.since,
+                held_us: (now - holder.since)
+                    .to_std()
+                    .map(micros)
+                    .unwrap_or_default(),
+            }),
+            queued_writers: stats.queued_writers,
+            acquisitions: stats.acquisitions,
+            average_wait_us: micros(stats.average_wait),
+            max_wait_us: micros(stats.max_wait),
+            timeouts: stats.timeouts,
+            recent: stats.recent.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Request body for `PUT /admin/pools/{id}/canary`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetCanaryRequest {
+    /// Share of routed flow the pool may receive, in basis points
+    /// (0–10 000).
+    pub weight_bps: u32,
+}
+
+/// Response body for `PUT` and `DELETE /admin/pools/{id}/canary`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CanaryResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Canary routing weight in basis points (`null` once promoted).
+    pub canary_weight_bps: Option<u32>,
+}
+
+/// Query parameters for `POST /admin/pools/{id}/pause`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PausePoolParams {
+    /// Drain instead of pausing: keep accepting withdrawals, fee
+    /// collection, and order cancellation.
+    #[serde(default)]
+    pub drain: bool,
+}
+
+/// Response body for `POST /admin/pools/{id}/pause` and `/resume`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PoolStatusResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Status before the request.
+    pub previous_status: PoolStatus,
+    /// Status now.
+    pub status: PoolStatus,
+}
+
+/// Query parameters for `GET /admin/pools/{id}/snapshots`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SnapshotListParams {
+    /// Most recent snapshots to list (default 100, max 1000).
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+/// A stored snapshot of a pool.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SnapshotDto {
+    /// Snapshot identifier, for `POST /admin/pools/{id}/restore`.
+    pub id: i64,
+    /// Pool type string.
+    pub pool_type: String,
+    /// When the snapshot was taken.
+    pub snapshot_at: DateTime<Utc>,
+    /// Size of the stored config, state, and metadata in bytes.
+    pub size_bytes: i64,
+}
+
+impl From<SnapshotInfo> for SnapshotDto {
+    fn from(info: SnapshotInfo) -> Self {
+        Self {
+            id: info.id,
+            pool_type: info.pool_type,
+            snapshot_at: info.snapshot_at,
+            size_bytes: info.size_bytes,
+        }
+    }
+}
+
+/// Response body for `GET /admin/pools/{id}/snapshots`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SnapshotListResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Snapshots, newest first.
+    pub snapshots: Vec<SnapshotDto>,
+}
+
+/// A compaction checkpoint, in `GET /admin/pools/{id}/checkpoints`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CheckpointDto {
+    /// Checkpoint identifier.
+    pub id: i64,
+    /// Start of the compacted range (inclusive).
+    pub from: DateTime<Utc>,
+    /// End of the compacted range (inclusive).
+    pub to: DateTime<Utc>,
+    /// Aggregates kept for the range.
+    pub summary: CompactionSummary,
+}
+
+impl From<EventCheckpoint> for CheckpointDto {
+    fn from(checkpoint: EventCheckpoint) -> Self {
+        Self {
+            id: checkpoint.id,
+            from: checkpoint.from,
+            to: checkpoint.to,
+            summary: checkpoint.summary,
+        }
+    }
+}
+
+/// Response body for `GET /admin/pools/{id}/checkpoints`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CheckpointListResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Checkpoints, oldest first.
+    pub checkpoints: Vec<CheckpointDto>,
+}
+
+/// Query parameters for `POST /admin/pools/{id}/restore`.
+#[derive(Debug, Clone, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RestorePoolParams {
+    /// Snapshot to restore, from `GET /admin/pools/{id}/snapshots`.
+    pub snapshot_id: i64,
+}
+
+/// Response body for `POST /admin/pools/{id}/restore`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RestorePoolResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Snapshot the pool was restored from.
+    pub snapshot_id: i64,
+    /// When that snapshot was taken.
+    pub snapshot_at: DateTime<Utc>,
+    /// Snapshot holding the state that was replaced; restore it to undo.
+    pub archived_snapshot_id: i64,
+    /// Swaps executed, as of the snapshot.
+    pub swap_count: u64,
+    /// Spot price after the restore, per the pool's price convention.
+    pub spot_price: Option<String>,
+    /// Tracked reserves by token address (string-encoded), when known.
+    pub reserves: BTreeMap<String, String>,
+}
+
+impl From<RestoredPool> for RestorePoolResponse {
+    fn from(restored: RestoredPool) -> Self {
+        let summary = restored.summary;
+        let reserves = summary
+            .reserves
+            .iter()
+            .flatten()
+            .zip(&summary.tokens)
+            .map(|(amount, token)| (token.address.clone(), amount.to_string()))
+            .collect();
+        Self {
+            pool_id: summary.pool_id,
+            snapshot_id: restored.snapshot_id,
+            snapshot_at: restored.snapshot_at,
+            archived_snapshot_id: restored.archived_snapshot_id,
+            swap_count: summary.swap_count,
+            spot_price: summary.spot_price.map(|p| format!("{p}")),
+            reserves,
+        }
+    }
+}
+
+/// Query parameters for `GET /admin/quotes`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct QuoteAuditParams {
+    /// Only quotes served to this client.
+    #[serde(default)]
+    pub client_id: Option<String>,
+    /// Only quotes of this pool.
+    #[serde(default)]
+    pub pool_id: Option<PoolId>,
+    /// Earliest quote time (inclusive). Defaults to a day before `to`.
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    /// Latest quote time (exclusive). Defaults to now.
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+    /// Maximum quotes returned (1–1000, default 100).
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+/// A quote recorded in the audit trail.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QuoteAuditDto {
+    /// Row identifier.
+    pub id: i64,
+    /// Quoted pool.
+    pub pool_id: PoolId,
+    /// Client the quote was served to.
+    pub client_id: Option<String>,
+    /// Input token address.
+    pub token_in: String,
+    /// Output token address.
+    pub token_out: String,
+    /// Quoted input amount (string-encoded).
+    pub amount_in: String,
+    /// Quoted output amount (string-encoded).
+    pub amount_out: String,
+    /// Quoted fee (string-encoded).
+    pub fee: String,
+    /// Spot price when the quote was computed.
+    pub spot_price: String,
+    /// Quoted execution price in whole `token_out` per whole `token_in`.
+    pub execution_price: String,
+    /// Quoted price impact in basis points.
+    pub price_impact_bps: i32,
+    /// Pool state version the quote was computed against.
+    pub pool_version: u64,
+    /// When the quote was served.
+    pub quoted_at: DateTime<Utc>,
+}
+
+impl From<StoredQuote> for QuoteAuditDto {
+    fn from(stored: StoredQuote) -> Self {
+        let quote = stored.quote;
+        Self {
+            id: stored.id,
+            pool_id: PoolId::from_uuid(quote.pool_id),
+            client_id: quote.client_id,
+            token_in: quote.token_in,
+            token_out: quote.token_out,
+            amount_in: quote.amount_in.to_string(),
+            amount_out: quote.amount_out.to_string(),
+            fee: quote.fee.to_string(),
+            spot_price: format!("{}", quote.spot_price),
+            execution_price: format!("{}", quote.execution_price),
+            price_impact_bps: quote.price_impact_bps,
+            pool_version: quote.pool_version,
+            quoted_at: quote.quoted_at,
+        }
+    }
+}
+
+/// Response body for `GET /admin/quotes`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QuoteAuditListResponse {
+    /// Recorded quotes, newest first.
+    pub quotes: Vec<QuoteAuditDto>,
+}
+
+/// Request body for `POST /admin/pools/{id}/backtest`. At least one
+/// parameter must be overridden.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BacktestRequest {
+    /// Start of the window; the pool is rebuilt as it stood then.
+    pub from: DateTime<Utc>,
+    /// End of the window (inclusive). Defaults to now.
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+    /// Alternative swap fee in basis points.
+    #[serde(default)]
+    pub fee_bps: Option<u32>,
+    /// Alternative amplification coefficient (hybrid pools).
+    #[serde(default)]
+    pub amplification: Option<u64>,
+    /// Alternative tick spacing (CLMM pools).
+    #[serde(default)]
+    pub tick_spacing: Option<u32>,
+}
+
+impl BacktestRequest {
+    /// The parameters to override in the alternative run.
+    #[must_use]
+    pub const fn overrides(&self) -> ParameterOverrides {
+        ParameterOverrides {
+            fee_bps: self.fee_bps,
+            amplification: self.amplification,
+            tick_spacing: self.tick_spacing,
+        }
+    }
+}
+
+/// Outcome of a backtest window under one set of parameters.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BacktestRunDto {
+    /// Swaps that executed.
+    pub swaps_executed: u64,
+    /// Swaps the pool rejected (e.g. not enough liquidity).
+    pub swaps_failed: u64,
+    /// Sum of swap inputs (string-encoded).
+    pub volume: String,
+    /// Fees charged in token A (string-encoded).
+    pub fee_token_a: String,
+    /// Fees charged in token B (string-encoded).
+    pub fee_token_b: String,
+    /// End-of-window value of the reserves minus that of the starting
+    /// reserves, both at the closing price, in token B units. `null` for
+    /// pools whose reserves are not tracked.
+    pub lp_pnl: Option<f64>,
+}
+
+impl From<BacktestRun> for BacktestRunDto {
+    fn from(run: BacktestRun) -> Self {
+        let [fee_a, fee_b] = run.fees;
+        Self {
+            swaps_executed: run.swaps_executed,
+            swaps_failed: run.swaps_failed,
+            volume: run.volume.to_string(),
+            fee_token_a: fee_a.to_string(),
+            fee_token_b: fee_b.to_string(),
+            lp_pnl: run.lp_pnl,
+        }
+    }
+}
+
+/// Response body for `POST /admin/pools/{id}/backtest`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BacktestResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Start of the window.
+    pub from: DateTime<Utc>,
+    /// End of the window.
+    pub to: DateTime<Utc>,
+    /// Swaps recorded in the window.
+    pub swaps_replayed: u64,
+    /// Swap events in the window that could not be read.
+    pub events_skipped: u64,
+    /// The pool's own parameters.
+    pub baseline: BacktestRunDto,
+    /// The overridden parameters.
+    pub alternative: BacktestRunDto,
+}
+
+impl BacktestResponse {
+    /// Builds the response for `backtest` of `pool_id` over `[from, to]`.
+    #[must_use]
+    pub fn new(
+        pool_id: PoolId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        backtest: Backtest,
+    ) -> Self {
+        Self {
+            pool_id,
+            from,
+            to,
+            swaps_replayed: backtest.swaps_replayed,
+            events_skipped: backtest.events_skipped,
+            baseline: backtest.baseline.into(),
+            alternative: backtest.alternative.into(),
+        }
+    }
+}
+
+/// One live WebSocket connection and its outbound queue.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WsConnectionDto {
+    /// Connection identifier, unique within this instance.
+    pub connection_id: uuid::Uuid,
+    /// Client id of the connection, if any.
+    pub client_id: Option<String>,
+    /// When the connection was opened.
+    pub connected_at: DateTime<Utc>,
+    /// Event frames waiting to be written to the client.
+    pub queued_events: usize,
+    /// Event frames queued before the overflow policy applies.
+    pub queue_capacity: usize,
+    /// `drop_oldest` or `disconnect`.
+    pub overflow_policy: String,
+    /// Events dropped since the connection opened.
+    pub dropped_events: u64,
+}
+
+impl From<ConnectionInfo> for WsConnectionDto {
+    fn from(info: ConnectionInfo) -> Self {
+        Self {
+            connection_id: info.id,
+            client_id: info.client_id,
+            connected_at: info.connected_at,
+            queued_events: info.queued_events,
+            queue_capacity: info.capacity,
+            overflow_policy: info.policy.as_str().to_string(),
+            dropped_events: info.dropped_events,
+        }
+    }
+}
+
+/// Response body for `GET /admin/ws/connections`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WsConnectionListResponse {
+    /// Live connections, oldest first.
+    pub connections: Vec<WsConnectionDto>,
+    /// Events dropped across the listed connections.
+    pub dropped_events: u64,
+}
+
+fn micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+/// Request body for `POST /admin/jobs`, tagged by `kind`.
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StartJobRequest {
+    /// Snapshot every pool, optionally only those of one type.
+    SnapshotAll {
+        /// Only pools of this type.
+        #[serde(default)]
+        pool_type: Option<String>,
+    },
+    /// Replay persisted history into a staging registry, as
+    /// `POST /admin/replay` does.
+    Replay {
+        /// Seed pools from the latest snapshot at or before this time.
+        #[serde(default)]
+        from: Option<DateTime<Utc>>,
+        /// Replay events up to and including this time. Defaults to now.
+        #[serde(default)]
+        to: Option<DateTime<Utc>>,
+        /// Restrict the replay to a single pool.
+        #[serde(default)]
+        pool_id: Option<PoolId>,
+    },
+    /// Compact the event log of several pools over one range, as
+    /// `POST /admin/events/compact` does for one.
+    Compaction {
+        /// Pools to compact; every registered pool when empty.
+        #[serde(default)]
+        pool_ids: Vec<PoolId>,
+        /// Start of the range (inclusive).
+        from: DateTime<Utc>,
+        /// End of the range (inclusive).
+        to: DateTime<Utc>,
+    },
+    /// Delete several pools, as `DELETE /pools/:id` does for one.
+    BulkDelete {
+        /// Pools to delete.
+        pool_ids: Vec<PoolId>,
+        /// Delete even while pools are in use.
+        #[serde(default)]
+        force: bool,
+    },
+}
+
+impl StartJobRequest {
+    /// Returns the kind of job this request starts.
+    #[must_use]
+    pub const fn kind(&self) -> JobKind {
+        match self {
+            Self::SnapshotAll { .. } => JobKind::SnapshotAll,
+            Self::Replay { .. } => JobKind::Replay,
+            Self::Compaction { .. } => JobKind::Compaction,
+            Self::BulkDelete { .. } => JobKind::BulkDelete,
+        }
+    }
+}
+
+/// Query parameters for `GET /admin/jobs`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct JobListParams {
+    /// Only jobs in this status.
+    #[serde(default)]
+    pub status: Option<JobStatus>,
+    /// Only jobs of this kind.
+    #[serde(default)]
+    pub kind: Option<JobKind>,
+}
+
+/// Status, progress, and outcome of a background job.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JobDto {
+    /// Job identifier.
+    pub job_id: uuid::Uuid,
+    /// Kind of work.
+    pub kind: JobKind,
+    /// Current status.
+    pub status: JobStatus,
+    /// Steps completed so far.
+    pub completed: u64,
+    /// Total steps, once known.
+    pub total: Option<u64>,
+    /// Whether cancellation was requested.
+    pub cancel_requested: bool,
+    /// Error that stopped the job.
+    pub error: Option<String>,
+    /// Result of a completed job; its shape depends on `kind`.
+    #[schema(value_type = Option<Object>)]
+    pub result: Option<serde_json::Value>,
+    /// When the job was started.
+    pub created_at: DateTime<Utc>,
+    /// When the job finished.
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl From<JobInfo> for JobDto {
+    fn from(job: JobInfo) -> Self {
+        Self {
+            job_id: job.id,
+            kind: job.kind,
+            status: job.status,
+            completed: job.completed,
+            total: job.total,
+            cancel_requested: job.cancel_requested,
+            error: job.error,
+            result: job.result,
+            created_at: job.created_at,
+            finished_at: job.finished_at,
+        }
+    }
+}
+
+/// Response body for `GET /admin/jobs`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JobListResponse {
+    /// Retained jobs, newest first.
+    pub jobs: Vec<JobDto>,
+}
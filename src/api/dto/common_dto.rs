@@ -3,6 +3,9 @@
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
+use crate::domain::pool_entry::TokenInfo;
+use crate::error::ErrorCatalogEntry;
+
 /// Token metadata as provided in pool creation requests.
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct TokenDto {
@@ -15,6 +18,71 @@ pub struct TokenDto {
     pub symbol: String,
 }
 
+impl From<&TokenInfo> for TokenDto {
+    fn from(token: &TokenInfo) -> Self {
+        Self {
+            address: token.address.clone(),
+            decimals: token.decimals,
+            symbol: token.symbol.clone(),
+        }
+    }
+}
+
+/// Unit in which token amounts are reported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Units {
+    /// Smallest-unit integer strings only.
+    #[default]
+    Raw,
+    /// Also fill the `*_decimal` fields, scaled by token decimals.
+    Decimal,
+}
+
+/// `?units=` query parameter for endpoints that report token amounts.
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UnitsParams {
+    /// `raw` (default) or `decimal`. Raw amounts are always returned;
+    /// `decimal` adds decimal-adjusted `*_decimal` fields alongside them.
+    #[serde(default)]
+    #[param(inline)]
+    pub units: Units,
+}
+
+impl UnitsParams {
+    /// Returns `raw` scaled by `decimals` when decimal units were
+    /// requested and the token's decimals are known.
+    #[must_use]
+    pub fn decimal(&self, raw: u128, decimals: Option<u8>) -> Option<String> {
+        match (self.units, decimals) {
+            (Units::Decimal, Some(decimals)) => Some(format_units(raw, decimals)),
+            _ => None,
+        }
+    }
+}
+
+/// Formats a smallest-unit amount as a decimal string with `decimals`
+/// fractional digits, trimming trailing zeros (`1500000`, 6 → `"1.5"`).
+///
+/// The conversion is exact; no floating point is involved.
+#[must_use]
+pub fn format_units(raw: u128, decimals: u8) -> String {
+    let digits = raw.to_string();
+    let scale = usize::from(decimals);
+    if scale == 0 {
+        return digits;
+    }
+    let padded = format!("{digits:0>width$}", width = scale + 1);
+    let (whole, fraction) = padded.split_at(padded.len() - scale);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{whole}.{fraction}")
+    }
+}
+
 /// Pagination query parameters for list endpoints.
 #[derive(Debug, Clone, Deserialize, ToSchema, IntoParams)]
 pub struct PaginationParams {
@@ -56,4 +124,54 @@ impl PaginationParams {
             per_page: self.per_page.clamp(1, 100),
         }
     }
+
+    /// Returns the requested page of `items` with its metadata, after
+    /// clamping.
+    #[must_use]
+    pub fn paginate<T>(&self, items: Vec<T>) -> (Vec<T>, PaginationMeta) {
+        let params = self.clamped();
+        let total = u32::try_from(items.len()).unwrap_or(u32::MAX);
+        let total_pages = total.div_ceil(params.per_page);
+        let start =
+            (params.page.saturating_sub(1) as usize).saturating_mul(params.per_page as usize);
+        let page = items
+            .into_iter()
+            .skip(start)
+            .take(params.per_page as usize)
+            .collect();
+        (
+            page,
+            PaginationMeta {
+                page: params.page,
+                per_page: params.per_page,
+                total,
+                total_pages,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_units_scales_exactly() {
+        assert_eq!(format_units(1_500_000, 6), "1.5");
+        assert_eq!(format_units(42, 6), "0.000042");
+        assert_eq!(format_units(2_000_000, 6), "2");
+        assert_eq!(format_units(0, 18), "0");
+        assert_eq!(format_units(123, 0), "123");
+        assert_eq!(
+            format_units(u128::MAX, 18),
+            "340282366920938463463.374607431768211455"
+        );
+    }
+}
+
+/// Response body for `GET /errors`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorCatalogResponse {
+    /// Every error code, ascending.
+    pub errors: Vec<ErrorCatalogEntry>,
 }
@@ -0,0 +1,78 @@
+//! Event consumer DTOs: named cursors over the event log.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::domain::PoolId;
+use crate::persistence::models::StoredEvent;
+
+/// Query parameters for `GET /consumers/:name/next`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ConsumerNextParams {
+    /// Maximum number of events (1–1000, default 100).
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+/// One event from the log.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConsumerEventDto {
+    /// Event log ID; acknowledge it to advance past this event.
+    pub event_id: i64,
+    /// Pool that emitted the event.
+    pub pool_id: PoolId,
+    /// Event type (e.g. `swap_executed`).
+    pub event_type: String,
+    /// Event as delivered on the WebSocket, upcast to the current schema.
+    #[schema(value_type = Object)]
+    pub payload: serde_json::Value,
+    /// When the event was written to the log (the event's own time for
+    /// imported history).
+    pub created_at: DateTime<Utc>,
+    /// Whether the event was imported from another venue's history.
+    pub imported: bool,
+}
+
+impl From<StoredEvent> for ConsumerEventDto {
+    fn from(event: StoredEvent) -> Self {
+        Self {
+            event_id: event.id,
+            pool_id: PoolId::from_uuid(event.pool_id),
+            event_type: event.event_type,
+            payload: event.payload,
+            created_at: event.created_at,
+            imported: event.imported,
+        }
+    }
+}
+
+/// Response body for `GET /consumers/:name/next`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConsumerBatchResponse {
+    /// Consumer name.
+    pub consumer: String,
+    /// Last acknowledged event ID (`0` before the first ack).
+    pub acked_event_id: i64,
+    /// Events after the acknowledged one, in log order.
+    pub events: Vec<ConsumerEventDto>,
+    /// ID to acknowledge once the batch is processed (`null` when empty).
+    pub next_ack: Option<i64>,
+}
+
+/// Request body for `POST /consumers/:name/ack`.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ConsumerAckRequest {
+    /// Last event ID the consumer has processed.
+    pub last_event_id: i64,
+}
+
+/// Response body for `POST /consumers/:name/ack`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConsumerAckResponse {
+    /// Consumer name.
+    pub consumer: String,
+    /// Acknowledged event ID after the ack; unchanged by a stale ack.
+    pub acked_event_id: i64,
+}
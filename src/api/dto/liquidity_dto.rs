@@ -2,9 +2,11 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
-use crate::domain::PoolId;
+use crate::domain::pool_entry::LiquidityPosition;
+use crate::domain::{LpLedger, PoolId, PositionId};
+use crate::service::pool_service::ClosedPosition;
 
 /// Request body for `POST /pools/:id/liquidity/add`.
 #[derive(Debug, Deserialize, ToSchema)]
@@ -13,6 +15,19 @@ pub struct AddLiquidityRequest {
     pub amount_a: String,
     /// Amount of token B to deposit (string-encoded u128).
     pub amount_b: String,
+    /// Existing position to top up. Omit to open a new position.
+    #[serde(default)]
+    pub position_id: Option<PositionId>,
+    /// Lower tick of a new CLMM position (requires `upper_tick`).
+    #[serde(default)]
+    pub lower_tick: Option<i32>,
+    /// Upper tick of a new CLMM position (requires `lower_tick`).
+    #[serde(default)]
+    pub upper_tick: Option<i32>,
+    /// Provider credited with a new position's LP shares; defaults to
+    /// the caller's client id. Ignored when topping up a position.
+    #[serde(default)]
+    pub owner: Option<String>,
     /// Maximum slippage tolerance (percentage as string, e.g. `"0.5"`).
     #[serde(default)]
     pub slippage_tolerance: Option<String>,
@@ -26,10 +41,19 @@ pub struct AddLiquidityRequest {
 pub struct AddLiquidityResponse {
     /// Pool identifier.
     pub pool_id: PoolId,
+    /// Position credited with the deposit; reference it in later
+    /// withdrawals and fee collections.
+    pub position_id: PositionId,
     /// Token A amount deposited (string-encoded).
     pub amount_a_deposited: String,
     /// Token B amount deposited (string-encoded).
     pub amount_b_deposited: String,
+    /// `amount_a_deposited` in whole tokens; only with `?units=decimal`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_a_deposited_decimal: Option<String>,
+    /// `amount_b_deposited` in whole tokens; only with `?units=decimal`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_b_deposited_decimal: Option<String>,
     /// LP tokens or shares minted (string-encoded).
     pub liquidity_minted: String,
     /// Execution timestamp.
@@ -41,6 +65,15 @@ pub struct AddLiquidityResponse {
 pub struct RemoveLiquidityRequest {
     /// Amount of LP tokens to burn (string-encoded u128).
     pub liquidity_amount: String,
+    /// Position to withdraw from. Omit only to burn liquidity no
+    /// position owns.
+    #[serde(default)]
+    pub position_id: Option<PositionId>,
+    /// Provider withdrawing; defaults to the caller's client id. When
+    /// given, the caller must send a client id equal to it. A position
+    /// with an owner can only be withdrawn by that owner.
+    #[serde(default)]
+    pub owner: Option<String>,
     /// Minimum token A out for slippage protection.
     #[serde(default)]
     pub amount_a_min: Option<String>,
@@ -57,8 +90,17 @@ pub struct RemoveLiquidityRequest {
 pub struct RemoveLiquidityResponse {
     /// Pool identifier.
     pub pool_id: PoolId,
-    /// Tokens returned (string-encoded combined value).
+    /// Position withdrawn from, if any.
+    pub position_id: Option<PositionId>,
+    /// Tokens returned as reported by hydra-amm (string-encoded
+    /// combined value).
     pub amount_returned: String,
+    /// Token A withdrawn (string-encoded). `null` for pool types whose
+    /// reserves the gateway does not track.
+    pub amount_a_returned: Option<String>,
+    /// Token B withdrawn (string-encoded). `null` for pool types whose
+    /// reserves the gateway does not track.
+    pub amount_b_returned: Option<String>,
     /// LP tokens burned (string-encoded).
     pub liquidity_burned: String,
     /// Execution timestamp.
@@ -68,10 +110,14 @@ pub struct RemoveLiquidityResponse {
 /// Request body for `POST /pools/:id/fees/collect`.
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct CollectFeesRequest {
-    /// Position tick range for CLMM pools.
+    /// Position to collect fees for. Omit to name the caller's position
+    /// by `lower_tick`/`upper_tick` instead.
+    #[serde(default)]
+    pub position_id: Option<PositionId>,
+    /// Lower tick of the caller's position (requires `upper_tick`).
     #[serde(default)]
     pub lower_tick: Option<i32>,
-    /// Position upper tick for CLMM pools.
+    /// Upper tick of the caller's position (requires `lower_tick`).
     #[serde(default)]
     pub upper_tick: Option<i32>,
 }
@@ -81,8 +127,188 @@ pub struct CollectFeesRequest {
 pub struct CollectFeesResponse {
     /// Pool identifier.
     pub pool_id: PoolId,
-    /// Fees collected (string-encoded).
+    /// Position the fees were collected for.
+    pub position_id: PositionId,
+    /// Fees collected as reported by hydra-amm (string-encoded).
     pub fees_collected: String,
+    /// Fees collected in token A (string-encoded).
+    pub fee_token_a: String,
+    /// Fees collected in token B (string-encoded).
+    pub fee_token_b: String,
     /// Collection timestamp.
     pub collected_at: DateTime<Utc>,
 }
+
+/// Request body for `POST /pools/:id/positions`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct OpenPositionRequest {
+    /// Amount of token A to deposit (string-encoded u128).
+    pub amount_a: String,
+    /// Amount of token B to deposit (string-encoded u128).
+    pub amount_b: String,
+    /// Lower tick of the position (requires `upper_tick`). Omit both for
+    /// the full range.
+    #[serde(default)]
+    pub lower_tick: Option<i32>,
+    /// Upper tick of the position (requires `lower_tick`).
+    #[serde(default)]
+    pub upper_tick: Option<i32>,
+    /// Transaction deadline (ISO-8601).
+    #[serde(default)]
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+/// Query parameters for `GET /pools/:id/positions`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PositionListParams {
+    /// Only positions opened by this client id.
+    #[serde(default)]
+    pub owner: Option<String>,
+}
+
+/// Response body for `GET /pools/:id/positions/:position_id`, also
+/// returned when a position is opened.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PositionResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Position identifier.
+    pub position_id: PositionId,
+    /// Client id that opened the position, if known.
+    pub owner: Option<String>,
+    /// `open` while the position holds liquidity, `closed` once fully
+    /// withdrawn.
+    #[schema(example = "open")]
+    pub status: &'static str,
+    /// LP units currently held (string-encoded).
+    pub liquidity: String,
+    /// Lower tick, for positions opened with a range.
+    pub lower_tick: Option<i32>,
+    /// Upper tick, for positions opened with a range.
+    pub upper_tick: Option<i32>,
+}
+
+impl PositionResponse {
+    /// Builds the response for `position` in `pool_id`.
+    #[must_use]
+    pub fn new(pool_id: PoolId, position_id: PositionId, position: &LiquidityPosition) -> Self {
+        Self {
+            pool_id,
+            position_id,
+            owner: position.owner.clone(),
+            status: if position.is_open() { "open" } else { "closed" },
+            liquidity: position.liquidity.to_string(),
+            lower_tick: position.range.map(|r| r.lower),
+            upper_tick: position.range.map(|r| r.upper),
+        }
+    }
+}
+
+/// Response body for `GET /pools/:id/positions`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PositionListResponse {
+    /// Positions, open and closed, in id order.
+    pub positions: Vec<PositionResponse>,
+}
+
+/// Response body for `DELETE /pools/:id/positions/:position_id`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClosePositionResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Position closed.
+    pub position_id: PositionId,
+    /// LP units burned (string-encoded); `"0"` if the position was
+    /// already empty.
+    pub liquidity_burned: String,
+    /// Token A withdrawn (string-encoded). `null` for pool types whose
+    /// reserves the gateway does not track.
+    pub amount_a_returned: Option<String>,
+    /// Token B withdrawn (string-encoded). `null` for pool types whose
+    /// reserves the gateway does not track.
+    pub amount_b_returned: Option<String>,
+    /// Fees collected in token A (string-encoded).
+    pub fee_token_a: String,
+    /// Fees collected in token B (string-encoded).
+    pub fee_token_b: String,
+    /// Closing timestamp.
+    pub closed_at: DateTime<Utc>,
+}
+
+impl ClosePositionResponse {
+    /// Builds the response for `position_id` in `pool_id`, closed with
+    /// the payout in `closed`.
+    #[must_use]
+    pub fn new(pool_id: PoolId, position_id: PositionId, closed: &ClosedPosition) -> Self {
+        let [fee_a, fee_b] = closed.fees;
+        Self {
+            pool_id,
+            position_id,
+            liquidity_burned: closed.liquidity_burned.to_string(),
+            amount_a_returned: closed.withdrawn.map(|[a, _]| a.to_string()),
+            amount_b_returned: closed.withdrawn.map(|[_, b]| b.to_string()),
+            fee_token_a: fee_a.to_string(),
+            fee_token_b: fee_b.to_string(),
+            closed_at: Utc::now(),
+        }
+    }
+}
+
+/// LP shares held by one provider.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProviderDto {
+    /// Provider (position owner).
+    pub owner: String,
+    /// LP units held across the provider's positions (string-encoded).
+    pub shares: String,
+    /// Share of the pool's total liquidity, in basis points (rounded
+    /// down).
+    pub share_bps: u32,
+}
+
+/// Response body for `GET /pools/:id/providers`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProviderListResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Total LP units in the pool (string-encoded).
+    pub total_liquidity: String,
+    /// LP units held by no provider: the creation liquidity and
+    /// positions opened without an owner (string-encoded).
+    pub unattributed_liquidity: String,
+    /// Providers, largest holding first.
+    pub providers: Vec<ProviderDto>,
+}
+
+impl ProviderListResponse {
+    /// Builds the response from `ledger` for a pool holding
+    /// `total_liquidity` LP units.
+    #[must_use]
+    pub fn new(pool_id: PoolId, ledger: &LpLedger, total_liquidity: u128) -> Self {
+        let mut providers: Vec<ProviderDto> = ledger
+            .iter()
+            .map(|(owner, shares)| ProviderDto {
+                owner: owner.to_string(),
+                shares: shares.to_string(),
+                share_bps: share_bps(shares, total_liquidity),
+            })
+            .collect();
+        providers.sort_by_key(|p| std::cmp::Reverse(ledger.balance(&p.owner)));
+        Self {
+            pool_id,
+            total_liquidity: total_liquidity.to_string(),
+            unattributed_liquidity: total_liquidity.saturating_sub(ledger.total()).to_string(),
+            providers,
+        }
+    }
+}
+
+/// `shares` of `total` in basis points, rounded down.
+fn share_bps(shares: u128, total: u128) -> u32 {
+    if total == 0 {
+        return 0;
+    }
+    let bps = shares.saturating_mul(10_000) / total;
+    u32::try_from(bps.min(10_000)).unwrap_or(10_000)
+}
@@ -0,0 +1,306 @@
+//! Market data DTOs: slippage curves, depth charts, volatility,
+//! candles, and risk scores.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::domain::PoolId;
+use crate::persistence::models::CandleRecord;
+use crate::service::market_data::{BboSource, DepthLevel};
+use crate::service::risk::{RiskComponent, RiskFactor, RiskLevel};
+
+/// Query parameters for `GET /pools/:id/slippage-curve`.
+#[derive(Debug, Clone, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SlippageCurveParams {
+    /// Address of the input token.
+    pub token_in: String,
+    /// Number of points (1–100, default 20).
+    #[serde(default)]
+    pub points: Option<u32>,
+    /// Largest input size (string-encoded u128). Defaults to the pool's
+    /// reserve of `token_in` when the gateway tracks it.
+    #[serde(default)]
+    pub max_amount: Option<String>,
+}
+
+/// One point on a slippage curve.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SlippageCurvePointDto {
+    /// Input amount (string-encoded).
+    pub amount_in: String,
+    /// Output amount (string-encoded).
+    pub amount_out: String,
+    /// Fee charged (string-encoded).
+    pub fee_charged: String,
+    /// Effective execution price in whole output tokens per whole input
+    /// token.
+    pub execution_price: String,
+    /// Price impact after fees in basis points (negative means less
+    /// output than spot).
+    pub price_impact_bps: i32,
+}
+
+/// Response body for `GET /pools/:id/slippage-curve`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SlippageCurveResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Input token address.
+    pub token_in: String,
+    /// Output token address.
+    pub token_out: String,
+    /// Spot price of `token_in` in `token_out`.
+    pub spot_price: Option<String>,
+    /// Curve points by increasing input size. Stops early at the first
+    /// size the pool cannot fill.
+    pub points: Vec<SlippageCurvePointDto>,
+}
+
+/// Query parameters for `GET /pools/:id/depth-chart`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DepthChartParams {
+    /// Half-width of the chart around the mid price in basis points
+    /// (1–5000, default 500).
+    #[serde(default)]
+    pub range_bps: Option<u32>,
+    /// Levels per side (1–50, default 20).
+    #[serde(default)]
+    pub levels: Option<u32>,
+}
+
+/// One cumulative depth level.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DepthLevelDto {
+    /// Distance from the mid price in basis points.
+    pub offset_bps: u32,
+    /// Base-token price at this level.
+    pub price: String,
+    /// Cumulative base-token amount (string-encoded).
+    pub base_amount: String,
+    /// Cumulative quote-token amount (string-encoded).
+    pub quote_amount: String,
+}
+
+impl From<&DepthLevel> for DepthLevelDto {
+    fn from(level: &DepthLevel) -> Self {
+        Self {
+            offset_bps: level.offset_bps,
+            price: format!("{}", level.price),
+            base_amount: level.base_amount.to_string(),
+            quote_amount: level.quote_amount.to_string(),
+        }
+    }
+}
+
+/// Response body for `GET /pools/:id/depth-chart`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DepthChartResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Base token address (the pool's first token).
+    pub base_token: String,
+    /// Quote token address (the pool's second token).
+    pub quote_token: String,
+    /// Current spot price of base in quote.
+    pub mid_price: String,
+    /// Cumulative liquidity for selling base, nearest level first.
+    pub bids: Vec<DepthLevelDto>,
+    /// Cumulative liquidity for buying base, nearest level first.
+    pub asks: Vec<DepthLevelDto>,
+}
+
+/// Response body for `GET /pools/:id/bbo`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BboResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// `order_book` for resting orders, `curve` for an AMM's marginal
+    /// prices net of its fee.
+    pub source: BboSource,
+    /// Base token address.
+    pub base_token: String,
+    /// Quote token address.
+    pub quote_token: String,
+    /// Highest price the pool buys base at, in quote per base (`null` if
+    /// none).
+    pub best_bid: Option<String>,
+    /// Lowest price the pool sells base at, in quote per base (`null` if
+    /// none).
+    pub best_ask: Option<String>,
+    /// Midpoint of bid and ask (`null` unless both exist).
+    pub mid_price: Option<String>,
+    /// Ask minus bid relative to the mid price, in basis points.
+    pub spread_bps: Option<f64>,
+    /// Pool state version the prices were read at.
+    pub version: u64,
+}
+
+/// Query parameters for `GET /pools/:id/volatility`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct VolatilityParams {
+    /// Look-back window: a count and a unit (`m`, `h`, `d`, `w`), e.g.
+    /// `24h` (default) or `7d`.
+    #[serde(default)]
+    pub window: Option<String>,
+}
+
+/// Response body for `GET /pools/:id/volatility`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VolatilityResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Window as requested.
+    pub window: String,
+    /// Start of the window.
+    pub from: DateTime<Utc>,
+    /// End of the window.
+    pub to: DateTime<Utc>,
+    /// Number of price observations in the window.
+    pub samples: usize,
+    /// Standard deviation of log returns between observations (`null`
+    /// with fewer than three observations).
+    pub return_stddev: Option<String>,
+    /// Annualized realized volatility (`null` with fewer than three
+    /// observations).
+    pub annualized_volatility: Option<String>,
+}
+
+/// Query parameters for `GET /pools/:id/candles`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CandleParams {
+    /// Bucket width: `1m` (default), `5m`, `1h`, or `1d`. Also accepted
+    /// as `interval`.
+    #[serde(default, alias = "interval")]
+    pub timeframe: Option<String>,
+    /// Earliest bucket start (inclusive). Defaults to `limit` buckets
+    /// before `to`.
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    /// Latest bucket start (exclusive). Defaults to now.
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+    /// Maximum candles returned (1–1000, default 500).
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+/// One OHLCV candle.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CandleDto {
+    /// Start of the bucket.
+    pub bucket_start: DateTime<Utc>,
+    /// First trade price (quote per base).
+    pub open: String,
+    /// Highest trade price.
+    pub high: String,
+    /// Lowest trade price.
+    pub low: String,
+    /// Last trade price.
+    pub close: String,
+    /// Base token traded (string-encoded raw amount).
+    pub base_volume: String,
+    /// Quote token traded (string-encoded raw amount).
+    pub quote_volume: String,
+    /// Number of trades; `0` for a bucket with price changes only.
+    pub trades: u64,
+}
+
+impl From<&CandleRecord> for CandleDto {
+    fn from(candle: &CandleRecord) -> Self {
+        Self {
+            bucket_start: candle.bucket_start,
+            open: format!("{}", candle.open),
+            high: format!("{}", candle.high),
+            low: format!("{}", candle.low),
+            close: format!("{}", candle.close),
+            base_volume: candle.base_volume.to_string(),
+            quote_volume: candle.quote_volume.to_string(),
+            trades: candle.trades,
+        }
+    }
+}
+
+/// Response body for `GET /pools/:id/candles`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CandlesResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Bucket width.
+    pub timeframe: String,
+    /// Candles with at least one trade or price change, oldest first.
+    pub candles: Vec<CandleDto>,
+}
+
+/// One factor of a pool risk score.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RiskComponentDto {
+    /// The factor.
+    pub factor: RiskFactor,
+    /// Measured input: whole base tokens for `liquidity_depth`,
+    /// annualized volatility for `volatility`, the Herfindahl index for
+    /// `concentration`, and seconds for `oracle_staleness`.
+    pub value: f64,
+    /// Factor score from 0 (safe) to 100 (risky).
+    pub score: f64,
+    /// Share of the composite score.
+    pub weight: f64,
+}
+
+impl From<&RiskComponent> for RiskComponentDto {
+    fn from(component: &RiskComponent) -> Self {
+        Self {
+            factor: component.factor,
+            value: component.value,
+            score: component.score,
+            weight: component.weight,
+        }
+    }
+}
+
+/// Response body for `GET /pools/:id/risk`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RiskResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Composite score from 0 (safe) to 100 (risky).
+    pub score: f64,
+    /// Band of the score.
+    pub level: RiskLevel,
+    /// Factors that apply to the pool and could be measured.
+    pub components: Vec<RiskComponentDto>,
+    /// When the score was computed.
+    pub computed_at: DateTime<Utc>,
+}
+
+/// Response body for `GET /pools/:id/stats`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PoolStatsResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Quote token address per the pool's price convention; every amount
+    /// below is in its raw units.
+    pub quote_token: String,
+    /// Total value locked: the quote reserve plus the base reserve at the
+    /// spot price (string-encoded). `null` when the gateway does not
+    /// track the pool's reserves or it cannot price.
+    pub tvl: Option<String>,
+    /// Quote volume over the last 24 hours (string-encoded).
+    pub volume_24h: String,
+    /// Quote volume over the last 7 days (string-encoded).
+    pub volume_7d: String,
+    /// Fees net of rebates over the last 24 hours (string-encoded).
+    pub fees_24h: String,
+    /// Swaps over the last 24 hours.
+    pub swap_count_24h: u64,
+    /// Swaps since the pool was created.
+    pub swap_count: u64,
+    /// Distinct clients that swapped over the last 24 hours.
+    pub unique_traders_24h: usize,
+    /// When the statistics were computed.
+    pub computed_at: DateTime<Utc>,
+}
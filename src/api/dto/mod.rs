@@ -3,12 +3,32 @@
 //! All numeric amounts are serialized as JSON strings to prevent
 //! precision loss on u128 values.
 
+pub mod admin_dto;
 pub mod common_dto;
+pub mod consumer_dto;
 pub mod liquidity_dto;
+pub mod market_dto;
+pub mod order_dto;
+pub mod pool_config_dto;
 pub mod pool_dto;
+pub mod rewards_dto;
 pub mod swap_dto;
+pub mod token_dto;
+pub mod trade_dto;
+pub mod usage_dto;
+pub mod webhook_dto;
 
+pub use admin_dto::*;
 pub use common_dto::*;
+pub use consumer_dto::*;
 pub use liquidity_dto::*;
+pub use market_dto::*;
+pub use order_dto::*;
+pub use pool_config_dto::*;
 pub use pool_dto::*;
+pub use rewards_dto::*;
 pub use swap_dto::*;
+pub use token_dto::*;
+pub use trade_dto::*;
+pub use usage_dto::*;
+pub use webhook_dto::*;
@@ -0,0 +1,122 @@
+//! Order book DTOs.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::domain::order_book::{BookDepth, LimitOrder, OrderSide, PriceLevel};
+use crate::domain::{OrderId, PoolId};
+
+/// Request body for `POST /pools/:id/orders`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PlaceOrderRequest {
+    /// `bid` buys token A with token B; `ask` sells token A.
+    pub side: OrderSide,
+    /// Limit price in token B units per token A unit (string-encoded
+    /// u128); a multiple of the pool's `tick_size`.
+    pub price: String,
+    /// Token A units (string-encoded u128); a multiple of the pool's
+    /// `lot_size`.
+    pub quantity: String,
+}
+
+/// A resting or cancelled limit order.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OrderResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Order identifier.
+    pub order_id: OrderId,
+    /// Side of the book.
+    pub side: OrderSide,
+    /// Limit price (string-encoded).
+    pub price: String,
+    /// Token A units (string-encoded).
+    pub quantity: String,
+    /// Client id that placed the order, if any.
+    pub owner: Option<String>,
+    /// When the order was placed or cancelled.
+    pub timestamp: DateTime<Utc>,
+}
+
+impl OrderResponse {
+    /// Builds the response for `order` of `pool_id`.
+    #[must_use]
+    pub fn new(pool_id: PoolId, order_id: OrderId, order: LimitOrder) -> Self {
+        Self {
+            pool_id,
+            order_id,
+            side: order.side,
+            price: order.price.to_string(),
+            quantity: order.quantity.to_string(),
+            owner: order.owner,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+/// Query parameters for `GET /pools/:id/orderbook`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OrderBookParams {
+    /// Price levels per side (1–100, default 20).
+    #[serde(default)]
+    pub levels: Option<u32>,
+}
+
+/// Resting quantity at one price.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PriceLevelDto {
+    /// Level price (string-encoded).
+    pub price: String,
+    /// Token A units resting at the price (string-encoded).
+    pub quantity: String,
+    /// Number of orders at the price.
+    pub orders: usize,
+}
+
+impl From<PriceLevel> for PriceLevelDto {
+    fn from(level: PriceLevel) -> Self {
+        Self {
+            price: level.price.to_string(),
+            quantity: level.quantity.to_string(),
+            orders: level.orders,
+        }
+    }
+}
+
+/// Response body for `GET /pools/:id/orderbook`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OrderBookResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Bid levels, highest price first.
+    pub bids: Vec<PriceLevelDto>,
+    /// Ask levels, lowest price first.
+    pub asks: Vec<PriceLevelDto>,
+    /// Highest bid price, if any.
+    pub best_bid: Option<String>,
+    /// Lowest ask price, if any.
+    pub best_ask: Option<String>,
+    /// Resting orders on both sides, including levels not shown.
+    pub resting_orders: usize,
+    /// Snapshot time.
+    pub timestamp: DateTime<Utc>,
+}
+
+impl OrderBookResponse {
+    /// Builds the response from `depth` of `pool_id`.
+    #[must_use]
+    pub fn new(pool_id: PoolId, depth: BookDepth) -> Self {
+        let best = |levels: &[PriceLevel]| levels.first().map(|l| l.price.to_string());
+        Self {
+            pool_id,
+            best_bid: best(&depth.bids),
+            best_ask: best(&depth.asks),
+            bids: depth.bids.into_iter().map(Into::into).collect(),
+            asks: depth.asks.into_iter().map(Into::into).collect(),
+            resting_orders: depth.resting_orders,
+            timestamp: Utc::now(),
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Typed pool creation configs, one per pool type.
+//!
+//! The `config` of a pool creation request is stored verbatim, so replay
+//! rebuilds a pool from exactly what was accepted; these types describe
+//! its shape and are what [`crate::api::config_parser`] parses it into.
+//! Every number may be given as a JSON number or as a decimal string, so
+//! u128 amounts beyond 2^53 can be written without precision loss.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use utoipa::ToSchema;
+
+use super::common_dto::TokenDto;
+
+/// Pool-type-specific creation config, tagged by `pool_type`.
+///
+/// Mirrors the `pool_type` and `config` fields of a create request.
+/// Besides the fields of each type, a config may carry
+/// `price_convention`, `initial_price`, and `reserve_units` (and dynamic
+/// configs `oracle_bounds`), which are resolved before it is parsed.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(tag = "pool_type", content = "config", rename_all = "snake_case")]
+pub enum PoolConfigDto {
+    /// `x * y = k` pool.
+    ConstantProduct(ConstantProductConfigDto),
+    /// Concentrated-liquidity pool.
+    Clmm(ClmmConfigDto),
+    /// Stable-swap pool.
+    Hybrid(HybridConfigDto),
+    /// Multi-token pool with fixed weights.
+    Weighted(WeightedConfigDto),
+    /// Oracle-anchored pool.
+    Dynamic(DynamicConfigDto),
+    /// Central limit order book.
+    #[serde(rename = "orderbook")]
+    OrderBook(OrderBookConfigDto),
+}
+
+impl PoolConfigDto {
+    /// Fee tier of the pool in basis points.
+    #[must_use]
+    pub fn fee_bps(&self) -> u32 {
+        match self {
+            Self::ConstantProduct(c) => c.fee_bps,
+            Self::Clmm(c) => c.fee_bps,
+            Self::Hybrid(c) => c.fee_bps,
+            Self::Weighted(c) => c.fee_bps,
+            Self::Dynamic(c) => c.fee_bps,
+            Self::OrderBook(c) => c.fee_bps,
+        }
+    }
+}
+
+/// Config of a `constant_product` pool.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ConstantProductConfigDto {
+    /// First token of the pair.
+    pub token_a: TokenDto,
+    /// Second token of the pair.
+    pub token_b: TokenDto,
+    /// Fee tier in basis points.
+    #[serde(deserialize_with = "number")]
+    pub fee_bps: u32,
+    /// Initial reserve of token A, in smallest units.
+    #[serde(deserialize_with = "number")]
+    #[schema(value_type = String)]
+    pub reserve_a: u128,
+    /// Initial reserve of token B, in smallest units.
+    #[serde(deserialize_with = "number")]
+    #[schema(value_type = String)]
+    pub reserve_b: u128,
+}
+
+/// Config of a `clmm` pool.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ClmmConfigDto {
+    /// First token of the pair.
+    pub token_a: TokenDto,
+    /// Second token of the pair.
+    pub token_b: TokenDto,
+    /// Fee tier in basis points.
+    #[serde(deserialize_with = "number")]
+    pub fee_bps: u32,
+    /// Spacing between initializable ticks.
+    #[serde(deserialize_with = "number")]
+    pub tick_spacing: u32,
+    /// Tick of the initial price.
+    #[serde(deserialize_with = "number")]
+    pub current_tick: i32,
+    /// Positions seeded at creation.
+    #[serde(default)]
+    pub positions: Vec<ClmmPositionDto>,
+}
+
+/// A liquidity position seeded into a new `clmm` pool.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ClmmPositionDto {
+    /// Lower bound of the position's tick range.
+    #[serde(deserialize_with = "number")]
+    pub lower_tick: i32,
+    /// Upper bound of the position's tick range.
+    #[serde(deserialize_with = "number")]
+    pub upper_tick: i32,
+    /// Liquidity of the position.
+    #[serde(deserialize_with = "number")]
+    #[schema(value_type = String)]
+    pub liquidity: u128,
+}
+
+/// Config of a `hybrid` pool.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct HybridConfigDto {
+    /// First token of the pair.
+    pub token_a: TokenDto,
+    /// Second token of the pair.
+    pub token_b: TokenDto,
+    /// Fee tier in basis points.
+    #[serde(deserialize_with = "number")]
+    pub fee_bps: u32,
+    /// Amplification coefficient of the stable-swap curve.
+    #[serde(deserialize_with = "number")]
+    pub amplification: u32,
+    /// Initial reserve of token A, in smallest units.
+    #[serde(deserialize_with = "number")]
+    #[schema(value_type = String)]
+    pub reserve_a: u128,
+    /// Initial reserve of token B, in smallest units.
+    #[serde(deserialize_with = "number")]
+    #[schema(value_type = String)]
+    pub reserve_b: u128,
+}
+
+/// Config of a `weighted` pool.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct WeightedConfigDto {
+    /// Pool tokens with their weights, in pool order.
+    pub tokens: Vec<WeightedTokenDto>,
+    /// Fee tier in basis points.
+    #[serde(deserialize_with = "number")]
+    pub fee_bps: u32,
+    /// Initial reserves in smallest units, in token order.
+    #[serde(deserialize_with = "numbers")]
+    #[schema(value_type = Vec<String>)]
+    pub reserves: Vec<u128>,
+}
+
+/// A token of a `weighted` pool.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct WeightedTokenDto {
+    /// The token.
+    #[serde(flatten)]
+    pub token: TokenDto,
+    /// Weight in basis points; the weights of a pool sum to 10000.
+    #[serde(deserialize_with = "number")]
+    pub weight: u32,
+}
+
+/// Config of a `dynamic` pool.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct DynamicConfigDto {
+    /// First token of the pair.
+    pub token_a: TokenDto,
+    /// Second token of the pair.
+    pub token_b: TokenDto,
+    /// Fee tier in basis points.
+    #[serde(deserialize_with = "number")]
+    pub fee_bps: u32,
+    /// Initial oracle price of token A in token B.
+    #[serde(deserialize_with = "number")]
+    pub oracle_price: f64,
+    /// How steeply the price moves away from the oracle with trade size.
+    #[serde(deserialize_with = "number")]
+    pub slippage_coefficient: f64,
+    /// Initial reserve of token A, in smallest units.
+    #[serde(deserialize_with = "number")]
+    #[schema(value_type = String)]
+    pub reserve_a: u128,
+    /// Initial reserve of token B, in smallest units.
+    #[serde(deserialize_with = "number")]
+    #[schema(value_type = String)]
+    pub reserve_b: u128,
+}
+
+/// Config of an `orderbook` pool.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct OrderBookConfigDto {
+    /// Base token.
+    pub token_a: TokenDto,
+    /// Quote token.
+    pub token_b: TokenDto,
+    /// Fee tier in basis points.
+    #[serde(deserialize_with = "number")]
+    pub fee_bps: u32,
+    /// Price increment of orders.
+    #[serde(deserialize_with = "number")]
+    #[schema(value_type = String)]
+    pub tick_size: u128,
+    /// Quantity increment of orders.
+    #[serde(deserialize_with = "number")]
+    #[schema(value_type = String)]
+    pub lot_size: u128,
+}
+
+/// Reads a number given either as a JSON number or as a decimal string.
+///
+/// # Errors
+///
+/// Returns a description of the problem for any other value, or a value
+/// that does not parse as `T`.
+pub fn parse_number<T>(value: &serde_json::Value) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let text = match value {
+        serde_json::Value::String(s) => s.trim().to_string(),
+        serde_json::Value::Number(n) => n.to_string(),
+        other => return Err(format!("expected a number or numeric string, got {other}")),
+    };
+    text.parse()
+        .map_err(|e| format!("invalid number {text}: {e}"))
+}
+
+fn number<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    parse_number(&serde_json::Value::deserialize(deserializer)?).map_err(D::Error::custom)
+}
+
+fn numbers<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    Vec::<serde_json::Value>::deserialize(deserializer)?
+        .iter()
+        .map(parse_number)
+        .collect::<Result<_, _>>()
+        .map_err(D::Error::custom)
+}
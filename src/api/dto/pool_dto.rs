@@ -3,11 +3,17 @@
 use std::collections::HashMap;
 
 use chrono::{DateTime, Utc};
+use hydra_amm::traits::LiquidityPool;
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
 use super::common_dto::{PaginationMeta, TokenDto};
-use crate::domain::PoolId;
+use crate::api::config_parser::ConfigIssue;
+use crate::domain::pool_capabilities::PoolCapabilities;
+use crate::domain::pool_entry::{PoolEntry, PoolStatus, PoolSummary};
+use crate::domain::pool_operation::TokenSide;
+use crate::domain::{PoolFilter, PoolId, PoolOperation, PoolSort};
+use crate::service::pool_service::DeletedPool;
 
 /// Request body for `POST /pools`.
 #[derive(Debug, Deserialize, ToSchema)]
@@ -17,8 +23,78 @@ pub struct CreatePoolRequest {
     /// Optional human-readable name (max 100 chars).
     #[serde(default)]
     pub name: Option<String>,
-    /// Pool-type-specific configuration.
+    /// Pool-type-specific configuration, shaped per `pool_type` as
+    /// described by [`PoolConfigDto`](super::PoolConfigDto). Stored as
+    /// given, so pools replay from exactly what was accepted.
     pub config: serde_json::Value,
+    /// Free-form metadata, checked against the registered metadata
+    /// schemas.
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// Request body for `PATCH /pools/:id`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdatePoolRequest {
+    /// Replacement metadata, checked against the registered metadata
+    /// schemas. `null` clears it.
+    #[schema(value_type = Object)]
+    pub metadata: serde_json::Value,
+}
+
+/// Response body for `PATCH /pools/:id`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PoolMetadataResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Metadata now attached to the pool.
+    #[schema(value_type = Object)]
+    pub metadata: serde_json::Value,
+    /// Server timestamp of the update.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request body for `POST /pools/:id/transfer-ownership`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TransferOwnershipRequest {
+    /// Client id of the new owner.
+    pub new_owner: String,
+}
+
+/// Response body for `POST /pools/:id/transfer-ownership`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OwnershipResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Client id of the new owner.
+    pub owner: String,
+    /// Client id of the previous owner (`null` if the pool was unowned).
+    pub previous_owner: Option<String>,
+    /// Server timestamp of the transfer.
+    pub transferred_at: DateTime<Utc>,
+}
+
+/// Request body for `PUT /pools/:id/oracle-price`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetOraclePriceRequest {
+    /// New oracle price, in the units of the config's `oracle_price`.
+    pub price: f64,
+}
+
+/// Response body for `PUT /pools/:id/oracle-price`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OraclePriceResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Oracle price now in effect.
+    pub oracle_price: String,
+    /// Oracle price before the update.
+    pub previous_price: String,
+    /// Spot price after the update, per the pool's price convention.
+    pub spot_price: Option<String>,
+    /// Server timestamp of the update.
+    pub updated_at: DateTime<Utc>,
 }
 
 /// Response body for `POST /pools` (201 Created).
@@ -32,11 +108,51 @@ pub struct CreatePoolResponse {
     pub name: Option<String>,
     /// Server creation timestamp.
     pub created_at: DateTime<Utc>,
-    /// Pool status.
-    pub status: String,
+    /// Pool status (always `active`).
+    pub status: PoolStatus,
+}
+
+/// One problem reported by `POST /pools/validate`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConfigIssueDto {
+    /// Offending field: `pool_type`, a config key such as `token_a`, or
+    /// `config` when hydra-amm rejects the config as a whole.
+    pub field: String,
+    /// What is wrong with it.
+    pub message: String,
+}
+
+impl From<ConfigIssue> for ConfigIssueDto {
+    fn from(issue: ConfigIssue) -> Self {
+        Self {
+            field: issue.field,
+            message: issue.message,
+        }
+    }
+}
+
+/// Response body for `POST /pools/validate`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ValidatePoolResponse {
+    /// Whether `POST /pools` would accept the request.
+    pub valid: bool,
+    /// Pool type echoed from the request.
+    pub pool_type: String,
+    /// Every problem found; empty when `valid`.
+    pub errors: Vec<ConfigIssueDto>,
+}
+
+/// Which token `spot_price` prices, and in which token.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PriceConventionDto {
+    /// Address of the priced token.
+    pub base: Option<String>,
+    /// Address of the token the price is expressed in.
+    pub quote: Option<String>,
 }
 
-/// Single pool detail for `GET /pools/:id`.
+/// Single pool detail for `GET /pools/:id` and the WebSocket `get_state`
+/// command.
 #[derive(Debug, Serialize, ToSchema)]
 pub struct PoolDetailResponse {
     /// Pool identifier.
@@ -47,20 +163,77 @@ pub struct PoolDetailResponse {
     pub created_at: DateTime<Utc>,
     /// Last update timestamp.
     pub updated_at: DateTime<Utc>,
-    /// Pool status.
-    pub status: String,
-    /// Token metadata.
+    /// Lifecycle status.
+    pub status: PoolStatus,
+    /// When the pool was archived, if it was.
+    pub archived_at: Option<DateTime<Utc>>,
+    /// Canary routing weight in basis points (`null` if promoted).
+    pub canary_weight_bps: Option<u32>,
+    /// Token metadata from the creation config (empty for pools created
+    /// programmatically).
     pub tokens: Vec<TokenDto>,
-    /// Current reserves keyed by token symbol.
+    /// Tracked reserves by token address (string-encoded), when known.
     pub reserves: HashMap<String, String>,
-    /// Current spot price.
-    pub current_price: Option<String>,
-    /// Total liquidity.
+    /// Spot price of the base token in the quote token, if the pool can
+    /// price.
+    pub spot_price: Option<String>,
+    /// Tokens `spot_price` refers to.
+    pub price_convention: PriceConventionDto,
+    /// Total liquidity reported by the pool.
     pub total_liquidity: String,
     /// Fee tier in basis points.
     pub fee_bps: u32,
     /// Number of swaps executed.
     pub swap_count: u64,
+    /// Cumulative swap volume in input-token units.
+    pub total_volume: String,
+    /// Pool metadata (`null` if none).
+    #[schema(value_type = Object)]
+    pub metadata: serde_json::Value,
+    /// Client id of the pool's owner (`null` for unowned pools).
+    pub owner: Option<String>,
+    /// Oracle price a dynamic pool quotes around (`null` for other pool
+    /// types).
+    pub oracle_price: Option<String>,
+    /// Actions the pool type supports.
+    pub capabilities: PoolCapabilities,
+}
+
+impl From<&PoolEntry> for PoolDetailResponse {
+    fn from(entry: &PoolEntry) -> Self {
+        let address = |side: TokenSide| entry.tokens.get(side.index()).map(|t| t.address.clone());
+        let reserves = entry
+            .reserves
+            .iter()
+            .flatten()
+            .zip(&entry.tokens)
+            .map(|(amount, token)| (token.address.clone(), amount.to_string()))
+            .collect();
+        Self {
+            pool_id: entry.pool_id,
+            pool_type: entry.pool_type.clone(),
+            created_at: entry.created_at,
+            updated_at: entry.last_modified_at,
+            status: entry.status,
+            archived_at: entry.archived_at,
+            canary_weight_bps: entry.canary_weight_bps,
+            tokens: entry.tokens.iter().map(TokenDto::from).collect(),
+            reserves,
+            spot_price: entry.spot_price().map(|p| format!("{p}")),
+            price_convention: PriceConventionDto {
+                base: address(entry.price_base),
+                quote: address(entry.price_base.other()),
+            },
+            total_liquidity: entry.pool_box.total_liquidity().get().to_string(),
+            fee_bps: entry.fee_bps,
+            swap_count: entry.swap_count,
+            total_volume: entry.total_volume.to_string(),
+            metadata: entry.metadata.clone(),
+            owner: entry.owner.clone(),
+            oracle_price: entry.current_oracle_price().map(|p| format!("{p}")),
+            capabilities: PoolCapabilities::of(&entry.pool_type),
+        }
+    }
 }
 
 /// Pool summary for list responses.
@@ -76,6 +249,65 @@ pub struct PoolSummaryDto {
     pub fee_bps: u32,
     /// Number of swaps.
     pub swap_count: u64,
+    /// Cumulative swap volume (string-encoded).
+    pub total_volume: String,
+    /// Lifecycle status.
+    pub status: PoolStatus,
+}
+
+/// Filter query parameters for `GET /pools`. All filters are optional
+/// and combine with AND.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PoolListFilter {
+    /// Only pools created at or after this instant (RFC 3339).
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only pools created at or before this instant (RFC 3339).
+    pub created_before: Option<DateTime<Utc>>,
+    /// Only pools with at least this many swaps.
+    pub min_swap_count: Option<u64>,
+    /// Only pools modified (swap or liquidity change) at or after this
+    /// instant (RFC 3339).
+    pub active_since: Option<DateTime<Utc>>,
+    /// Only pools of this type (e.g. `clmm`).
+    pub pool_type: Option<String>,
+    /// Only pools holding the token with this address.
+    pub token: Option<String>,
+    /// Only pools with at least this fee tier, in basis points.
+    pub min_fee_bps: Option<u32>,
+    /// Only pools with at most this fee tier, in basis points.
+    pub max_fee_bps: Option<u32>,
+    /// Only pools in this lifecycle state.
+    pub status: Option<PoolStatus>,
+    /// Order of the list: `created_at` (oldest first, the default),
+    /// `volume`, or `swap_count` (highest first).
+    #[serde(default)]
+    pub sort_by: PoolSort,
+}
+
+impl PoolListFilter {
+    /// The criteria applied while listing pools; the time and activity
+    /// filters are applied by [`Self::matches`].
+    #[must_use]
+    pub fn pool_filter(&self) -> PoolFilter {
+        PoolFilter {
+            pool_type: self.pool_type.clone(),
+            token: self.token.clone(),
+            min_fee_bps: self.min_fee_bps,
+            max_fee_bps: self.max_fee_bps,
+            status: self.status,
+            sort_by: self.sort_by,
+        }
+    }
+
+    /// Returns `true` if `pool` passes every filter that is set.
+    #[must_use]
+    pub fn matches(&self, pool: &PoolSummary) -> bool {
+        self.created_after.is_none_or(|t| pool.created_at >= t)
+            && self.created_before.is_none_or(|t| pool.created_at <= t)
+            && self.min_swap_count.is_none_or(|n| pool.swap_count >= n)
+            && self.active_since.is_none_or(|t| pool.last_modified_at >= t)
+    }
 }
 
 /// Paginated list response for `GET /pools`.
@@ -86,3 +318,188 @@ pub struct PoolListResponse {
     /// Pagination metadata.
     pub pagination: PaginationMeta,
 }
+
+/// Query parameters for `DELETE /pools/:id`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeletePoolParams {
+    /// Respond with the final pool state instead of `204 No Content`.
+    #[serde(default)]
+    pub return_state: bool,
+    /// Delete even while providers hold open positions or other
+    /// references to the pool remain.
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Final state returned by `DELETE /pools/:id?return_state=true`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeletedPoolResponse {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Pool type string.
+    pub pool_type: String,
+    /// Creation timestamp.
+    pub created_at: DateTime<Utc>,
+    /// Timestamp of the last state mutation.
+    pub last_modified_at: DateTime<Utc>,
+    /// Fee tier in basis points.
+    pub fee_bps: u32,
+    /// Number of swaps executed.
+    pub swap_count: u64,
+    /// Cumulative swap volume (string-encoded).
+    pub total_volume: String,
+    /// Total liquidity at deletion (string-encoded).
+    pub total_liquidity: String,
+    /// Spot price at deletion, per the pool's price convention.
+    pub spot_price: Option<String>,
+    /// Pool tokens.
+    pub tokens: Vec<TokenDto>,
+    /// Tracked reserves by token address (string-encoded), when known.
+    pub reserves: HashMap<String, String>,
+    /// Creation config as submitted.
+    pub config: serde_json::Value,
+    /// Operations applied since creation; with `config` they rebuild the
+    /// final state.
+    #[schema(value_type = Vec<Object>)]
+    pub journal: Vec<PoolOperation>,
+    /// Whether deletion was forced.
+    pub forced: bool,
+    /// Id of the archived final snapshot, if persistence saved one.
+    pub archived_snapshot_id: Option<i64>,
+    /// Deletion timestamp.
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl From<DeletedPool> for DeletedPoolResponse {
+    fn from(deleted: DeletedPool) -> Self {
+        let summary = deleted.summary;
+        let reserves = summary
+            .reserves
+            .iter()
+            .flatten()
+            .zip(&summary.tokens)
+            .map(|(amount, token)| (token.address.clone(), amount.to_string()))
+            .collect();
+        Self {
+            pool_id: summary.pool_id,
+            pool_type: summary.pool_type,
+            created_at: summary.created_at,
+            last_modified_at: summary.last_modified_at,
+            fee_bps: summary.fee_bps,
+            swap_count: summary.swap_count,
+            total_volume: deleted.total_volume.to_string(),
+            total_liquidity: summary.total_liquidity.to_string(),
+            spot_price: summary.spot_price.map(|p| format!("{p}")),
+            tokens: summary.tokens.iter().map(TokenDto::from).collect(),
+            reserves,
+            config: deleted.config_json,
+            journal: deleted.journal.to_vec(),
+            forced: deleted.forced,
+            archived_snapshot_id: deleted.archived_snapshot_id,
+            deleted_at: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::service::pool_service::build_entry;
+
+    #[test]
+    fn pool_detail_reports_tracked_state() {
+        let Ok(entry) = build_entry(
+            PoolId::new(),
+            "constant_product",
+            serde_json::json!({
+                "token_a": {"address": "0xaaa", "decimals": 6},
+                "token_b": {"address": "0xbbb", "decimals": 6},
+                "fee_bps": 30,
+                "reserve_a": "1000000",
+                "reserve_b": "4000000",
+                "price_convention": {"base": "0xbbb", "quote": "0xaaa"},
+            }),
+        ) else {
+            panic!("entry build failed");
+        };
+        let detail = PoolDetailResponse::from(&entry);
+
+        assert_eq!(detail.status, PoolStatus::Active);
+        assert_eq!(detail.fee_bps, 30);
+        assert_eq!(detail.tokens.len(), 2);
+        assert_eq!(
+            detail.reserves,
+            HashMap::from([
+                ("0xaaa".to_string(), "1000000".to_string()),
+                ("0xbbb".to_string(), "4000000".to_string()),
+            ])
+        );
+        assert_eq!(detail.price_convention.base.as_deref(), Some("0xbbb"));
+        assert_eq!(detail.price_convention.quote.as_deref(), Some("0xaaa"));
+        let Some(Ok(price)) = detail.spot_price.as_deref().map(str::parse::<f64>) else {
+            panic!("pool should price");
+        };
+        assert!((price - 0.25).abs() < 1e-9, "token B priced in token A");
+        assert_eq!(detail.swap_count, 0);
+        assert_eq!(detail.total_volume, "0");
+        assert!(detail.metadata.is_null());
+    }
+
+    #[test]
+    fn list_filter_matches_creation_and_activity() {
+        let Ok(mut entry) = build_entry(
+            PoolId::new(),
+            "constant_product",
+            serde_json::json!({
+                "token_a": {"address": "0xaaa", "decimals": 6},
+                "token_b": {"address": "0xbbb", "decimals": 6},
+                "fee_bps": 30,
+                "reserve_a": "1000000",
+                "reserve_b": "1000000",
+            }),
+        ) else {
+            panic!("entry build failed");
+        };
+        let day = chrono::Duration::days(1);
+        let created = DateTime::UNIX_EPOCH + day * 10;
+        entry.created_at = created;
+        entry.last_modified_at = created + day * 5;
+        entry.swap_count = 3;
+        let pool = PoolSummary::from(&entry);
+        let matches = |filter: PoolListFilter| filter.matches(&pool);
+
+        assert!(matches(PoolListFilter::default()));
+        assert!(matches(PoolListFilter {
+            created_after: Some(created),
+            created_before: Some(created),
+            ..PoolListFilter::default()
+        }));
+        assert!(!matches(PoolListFilter {
+            created_after: Some(created + day),
+            ..PoolListFilter::default()
+        }));
+        assert!(!matches(PoolListFilter {
+            created_before: Some(created - day),
+            ..PoolListFilter::default()
+        }));
+        assert!(matches(PoolListFilter {
+            min_swap_count: Some(3),
+            active_since: Some(created + day * 5),
+            ..PoolListFilter::default()
+        }));
+        assert!(!matches(PoolListFilter {
+            min_swap_count: Some(4),
+            ..PoolListFilter::default()
+        }));
+        assert!(
+            !matches(PoolListFilter {
+                created_after: Some(created),
+                active_since: Some(created + day * 6),
+                ..PoolListFilter::default()
+            }),
+            "filters combine with AND"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! Liquidity-mining reward DTOs.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::domain::{PoolId, PositionId};
+use crate::service::rewards::{AccruedReward, EmissionSchedule};
+
+/// Rewards accrued to the caller in one pool.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RewardDto {
+    /// Pool the rewards were earned in.
+    pub pool_id: PoolId,
+    /// Address of the reward token.
+    pub reward_token: String,
+    /// Reward units (string-encoded).
+    pub amount: String,
+    /// The caller's positions in the pool.
+    pub position_ids: Vec<PositionId>,
+}
+
+impl From<AccruedReward> for RewardDto {
+    fn from(reward: AccruedReward) -> Self {
+        Self {
+            pool_id: reward.pool_id,
+            reward_token: reward.reward_token,
+            amount: reward.amount.to_string(),
+            position_ids: reward.positions,
+        }
+    }
+}
+
+/// Response body for `GET /accounts/me/rewards`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RewardsResponse {
+    /// Client the rewards belong to.
+    pub client_id: String,
+    /// Unclaimed rewards by pool and reward token.
+    pub rewards: Vec<RewardDto>,
+    /// Time the rewards were computed at.
+    pub as_of: DateTime<Utc>,
+}
+
+/// Response body for `POST /accounts/me/rewards/claim`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClaimRewardsResponse {
+    /// Client the rewards were paid to.
+    pub client_id: String,
+    /// Rewards paid out by pool and reward token; empty if nothing had
+    /// accrued.
+    pub claimed: Vec<RewardDto>,
+    /// Claim timestamp.
+    pub claimed_at: DateTime<Utc>,
+}
+
+/// Request body for `PUT /admin/pools/{id}/rewards`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetEmissionScheduleRequest {
+    /// Address of the reward token.
+    pub reward_token: String,
+    /// Reward units emitted per second across all positions
+    /// (string-encoded u128).
+    pub rate_per_second: String,
+    /// Start of emissions. Defaults to now.
+    #[serde(default)]
+    pub starts_at: Option<DateTime<Utc>>,
+    /// End of emissions. Omit to emit until the schedule is replaced.
+    #[serde(default)]
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+/// A pool's emission schedule.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EmissionScheduleDto {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Address of the reward token.
+    pub reward_token: String,
+    /// Reward units emitted per second (string-encoded).
+    pub rate_per_second: String,
+    /// Start of emissions.
+    pub starts_at: DateTime<Utc>,
+    /// End of emissions, if bounded.
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+impl EmissionScheduleDto {
+    /// Builds the DTO for `schedule` of `pool_id`.
+    #[must_use]
+    pub fn new(pool_id: PoolId, schedule: EmissionSchedule) -> Self {
+        Self {
+            pool_id,
+            reward_token: schedule.reward_token,
+            rate_per_second: schedule.rate_per_second.to_string(),
+            starts_at: schedule.starts_at,
+            ends_at: schedule.ends_at,
+        }
+    }
+}
+
+/// Response body for `GET /admin/rewards`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EmissionScheduleListResponse {
+    /// Configured schedules, by pool ID.
+    pub schedules: Vec<EmissionScheduleDto>,
+}
@@ -1,10 +1,14 @@
 //! Swap and quote DTOs.
 
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use super::UnitsParams;
 use crate::domain::PoolId;
+use crate::service::routing::{RouteHop, RouteLeg, RoutePlan, RouteTotals};
 
 /// Request body for `POST /pools/:id/swap` and `POST /pools/:id/quote`.
 #[derive(Debug, Deserialize, ToSchema)]
@@ -28,6 +32,10 @@ pub struct SwapRequest {
     /// Transaction deadline (ISO-8601).
     #[serde(default)]
     pub deadline: Option<DateTime<Utc>>,
+    /// Execute only if the pool is still at this version, as reported by
+    /// `pool_version` in a quote; otherwise the swap fails with 409.
+    #[serde(default)]
+    pub expected_version: Option<u64>,
 }
 
 /// Response body for `POST /pools/:id/swap`.
@@ -47,13 +55,33 @@ pub struct SwapResponse {
     pub amount_out: String,
     /// Fee charged (string-encoded).
     pub fee_charged: String,
-    /// Effective execution price.
+    /// `amount_in` in whole tokens; only with `?units=decimal`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_in_decimal: Option<String>,
+    /// `amount_out` in whole tokens; only with `?units=decimal`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_out_decimal: Option<String>,
+    /// `fee_charged` in whole tokens; only with `?units=decimal`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_charged_decimal: Option<String>,
+    /// Fee-tier discount applied to this swap, in basis points (0 when
+    /// the caller is in no tier).
+    pub fee_discount_bps: u32,
+    /// Part of `fee_charged` rebated to the caller, in the input token
+    /// (string-encoded).
+    pub fee_rebate: String,
+    /// `fee_rebate` in whole tokens; only with `?units=decimal`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_rebate_decimal: Option<String>,
+    /// Execution price in whole `token_out` per whole `token_in`.
     pub execution_price: String,
     /// Spot price before swap.
     pub spot_price_before: String,
     /// Spot price after swap.
     pub spot_price_after: String,
-    /// Price impact in basis points.
+    /// Deviation of the fee-exclusive execution price from the pre-trade
+    /// spot price in the swap's direction, in basis points (negative
+    /// means less output than spot).
     pub price_impact_bps: i32,
     /// Execution timestamp.
     pub executed_at: DateTime<Utc>,
@@ -74,12 +102,236 @@ pub struct QuoteResponse {
     pub amount_out: String,
     /// Fee amount (string-encoded).
     pub fee_charged: String,
-    /// Effective price.
+    /// `amount_in` in whole tokens; only with `?units=decimal`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_in_decimal: Option<String>,
+    /// `amount_out` in whole tokens; only with `?units=decimal`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_out_decimal: Option<String>,
+    /// `fee_charged` in whole tokens; only with `?units=decimal`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_charged_decimal: Option<String>,
+    /// Execution price in whole `token_out` per whole `token_in`.
     pub execution_price: String,
     /// Current spot price.
     pub spot_price: String,
-    /// Estimated price impact in basis points.
+    /// Deviation of the fee-exclusive execution price from the current
+    /// spot price in the swap's direction, in basis points (negative
+    /// means less output than spot).
     pub price_impact_bps: i32,
     /// Quote timestamp.
     pub quoted_at: DateTime<Utc>,
+    /// Pool state version the quote was computed against; pass it as
+    /// `expected_version` to execute only against the same state.
+    pub pool_version: u64,
+    /// Hint for how long the quote is likely to remain useful. The pool
+    /// version, not this time, decides whether it still holds.
+    pub valid_until: DateTime<Utc>,
+}
+
+/// Request body for `POST /swap/auto`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AutoSwapRequest {
+    /// Address of the input token.
+    pub token_in: String,
+    /// Address of the output token.
+    pub token_out: String,
+    /// Exact input amount (string-encoded u128).
+    pub amount_in: String,
+    /// Minimum total output (string-encoded u128). Execution is refused
+    /// if the quote falls below it.
+    #[serde(default)]
+    pub min_amount_out: Option<String>,
+    /// Maximum hops per leg (1–4, default 3).
+    #[serde(default)]
+    pub max_hops: Option<u8>,
+    /// Allow splitting the input across several paths (default `true`).
+    #[serde(default = "default_allow_split")]
+    pub allow_split: bool,
+    /// Fixed cost charged per hop when ranking routes, in `token_out`
+    /// units (string-encoded u128). Defaults to the gateway's
+    /// `ROUTE_HOP_COST`.
+    #[serde(default)]
+    pub hop_cost: Option<String>,
+    /// Execute the route in the same request (default `false`).
+    #[serde(default)]
+    pub execute: bool,
+}
+
+fn default_allow_split() -> bool {
+    true
+}
+
+/// One swap along a route.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RouteHopDto {
+    /// Pool the hop trades on.
+    pub pool_id: PoolId,
+    /// Input token address.
+    pub token_in: String,
+    /// Output token address.
+    pub token_out: String,
+    /// Amount in (string-encoded).
+    pub amount_in: String,
+    /// Amount out (string-encoded).
+    pub amount_out: String,
+    /// Fee charged in `token_in` units (string-encoded).
+    pub fee_charged: String,
+    /// Spot price of `token_in` in `token_out` before the hop.
+    pub spot_price: Option<String>,
+    /// Price impact after fees in basis points (negative means less
+    /// output than spot).
+    pub price_impact_bps: i32,
+}
+
+impl From<&RouteHop> for RouteHopDto {
+    fn from(hop: &RouteHop) -> Self {
+        Self {
+            pool_id: hop.pool_id,
+            token_in: hop.token_in.clone(),
+            token_out: hop.token_out.clone(),
+            amount_in: hop.amount_in.to_string(),
+            amount_out: hop.amount_out.to_string(),
+            fee_charged: hop.fee.to_string(),
+            spot_price: hop.spot_price.map(|p| format!("{p}")),
+            price_impact_bps: hop.price_impact_bps,
+        }
+    }
+}
+
+/// A path carrying part or all of the input.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RouteLegDto {
+    /// Input routed through this leg (string-encoded).
+    pub amount_in: String,
+    /// Output of the leg (string-encoded).
+    pub amount_out: String,
+    /// Hops in execution order.
+    pub hops: Vec<RouteHopDto>,
+}
+
+impl From<&RouteLeg> for RouteLegDto {
+    fn from(leg: &RouteLeg) -> Self {
+        Self {
+            amount_in: leg.amount_in.to_string(),
+            amount_out: leg.amount_out.to_string(),
+            hops: leg.hops.iter().map(RouteHopDto::from).collect(),
+        }
+    }
+}
+
+/// Aggregate route cost relative to pre-trade spot prices.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RouteTotalsDto {
+    /// Number of hops across all legs.
+    pub hop_count: usize,
+    /// Fees charged per token address (string-encoded).
+    pub fees: BTreeMap<String, String>,
+    /// Output at spot prices with no fees or impact (string-encoded).
+    pub spot_amount_out: Option<String>,
+    /// Share of spot value paid in fees, in basis points.
+    pub fee_bps: i32,
+    /// Price impact after fees in basis points (negative means less
+    /// output than spot).
+    pub price_impact_bps: i32,
+}
+
+impl From<RouteTotals> for RouteTotalsDto {
+    fn from(totals: RouteTotals) -> Self {
+        Self {
+            hop_count: totals.hop_count,
+            fees: totals
+                .fees
+                .into_iter()
+                .map(|(token, fee)| (token, fee.to_string()))
+                .collect(),
+            spot_amount_out: totals.spot_amount_out.map(|v| v.to_string()),
+            fee_bps: totals.fee_bps,
+            price_impact_bps: totals.price_impact_bps,
+        }
+    }
+}
+
+/// A routed swap plan.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RoutePlanDto {
+    /// Input token address.
+    pub token_in: String,
+    /// Output token address.
+    pub token_out: String,
+    /// Total input (string-encoded).
+    pub amount_in: String,
+    /// Total output (string-encoded).
+    pub amount_out: String,
+    /// Execution overhead charged by the cost model, in `token_out`
+    /// units (string-encoded).
+    pub execution_cost: String,
+    /// Output net of the execution cost (string-encoded); routes are
+    /// ranked by this value.
+    pub net_amount_out: String,
+    /// `amount_in` in whole tokens; only with `?units=decimal`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_in_decimal: Option<String>,
+    /// `amount_out` in whole tokens; only with `?units=decimal`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_out_decimal: Option<String>,
+    /// `net_amount_out` in whole tokens; only with `?units=decimal`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub net_amount_out_decimal: Option<String>,
+    /// Whether the input is split across several legs.
+    pub split: bool,
+    /// Legs of the route.
+    pub legs: Vec<RouteLegDto>,
+    /// Aggregate fees and impact.
+    pub totals: RouteTotalsDto,
+}
+
+impl RoutePlanDto {
+    /// Fills the `*_decimal` fields from the input and output token
+    /// decimals when `units` asks for them.
+    #[must_use]
+    pub fn with_units(
+        mut self,
+        plan: &RoutePlan,
+        units: UnitsParams,
+        decimals_in: Option<u8>,
+        decimals_out: Option<u8>,
+    ) -> Self {
+        self.amount_in_decimal = units.decimal(plan.amount_in, decimals_in);
+        self.amount_out_decimal = units.decimal(plan.amount_out, decimals_out);
+        self.net_amount_out_decimal = units.decimal(plan.net_amount_out(), decimals_out);
+        self
+    }
+}
+
+impl From<&RoutePlan> for RoutePlanDto {
+    fn from(plan: &RoutePlan) -> Self {
+        Self {
+            token_in: plan.token_in.clone(),
+            token_out: plan.token_out.clone(),
+            amount_in: plan.amount_in.to_string(),
+            amount_out: plan.amount_out.to_string(),
+            execution_cost: plan.execution_cost.to_string(),
+            net_amount_out: plan.net_amount_out().to_string(),
+            amount_in_decimal: None,
+            amount_out_decimal: None,
+            net_amount_out_decimal: None,
+            split: plan.is_split(),
+            legs: plan.legs.iter().map(RouteLegDto::from).collect(),
+            totals: RouteTotalsDto::from(plan.totals()),
+        }
+    }
+}
+
+/// Response body for `POST /swap/auto`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AutoSwapResponse {
+    /// The chosen route; reflects executed amounts when `executed`.
+    pub route: RoutePlanDto,
+    /// Whether the route was executed.
+    pub executed: bool,
+    /// Swap identifier shared by every hop's events (set when executed).
+    pub swap_id: Option<String>,
+    /// Quote or execution timestamp.
+    pub timestamp: DateTime<Utc>,
 }
@@ -0,0 +1,79 @@
+//! Token-centric DTOs: token listing and pool discovery by token.
+
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::common_dto::{PaginationMeta, TokenDto};
+use crate::domain::PoolId;
+use crate::domain::pool_entry::KnownToken;
+
+/// Query parameters for `GET /tokens`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TokenListParams {
+    /// Only tokens whose symbol starts with this prefix
+    /// (case-insensitive).
+    pub symbol: Option<String>,
+}
+
+/// A distinct token known to the gateway.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct KnownTokenDto {
+    /// Token address.
+    pub address: String,
+    /// Number of decimal places.
+    pub decimals: u8,
+    /// Registered symbol (empty if none was given).
+    pub symbol: String,
+    /// Number of pools holding the token.
+    pub pool_count: u32,
+    /// Sum of the token's reserves across pools (string-encoded).
+    pub total_liquidity: String,
+}
+
+impl From<KnownToken> for KnownTokenDto {
+    fn from(token: KnownToken) -> Self {
+        Self {
+            address: token.address,
+            decimals: token.decimals,
+            symbol: token.symbol,
+            pool_count: token.pool_count,
+            total_liquidity: token.total_liquidity.to_string(),
+        }
+    }
+}
+
+/// Paginated list response for `GET /tokens`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenListResponse {
+    /// Tokens ordered by address.
+    pub data: Vec<KnownTokenDto>,
+    /// Pagination metadata.
+    pub pagination: PaginationMeta,
+}
+
+/// A pool that trades a given token.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenPoolDto {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Pool type string.
+    pub pool_type: String,
+    /// Fee tier in basis points.
+    pub fee_bps: u32,
+    /// Total liquidity (string-encoded).
+    pub total_liquidity: String,
+    /// Spot price per the pool's price convention, if the pool can price.
+    pub spot_price: Option<String>,
+    /// All tokens in the pool.
+    pub tokens: Vec<TokenDto>,
+}
+
+/// Response body for `GET /tokens/:address/pools`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenPoolsResponse {
+    /// The queried token address.
+    pub token: String,
+    /// Matching pools, deepest liquidity first.
+    pub data: Vec<TokenPoolDto>,
+}
@@ -0,0 +1,66 @@
+//! Trade tape DTOs.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::domain::PoolId;
+use crate::service::trade_tape::{Trade, TradeSide};
+
+/// Query parameters for `GET /trades`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TradeListParams {
+    /// Maximum number of trades (1–1000, default 100).
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Only trades on this pool.
+    #[serde(default)]
+    pub pool_id: Option<uuid::Uuid>,
+}
+
+/// One trade on the tape.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TradeDto {
+    /// Command id of the swap.
+    pub trade_id: String,
+    /// Pool the trade executed on.
+    pub pool_id: PoolId,
+    /// Base token address per the pool's price convention.
+    pub base_token: String,
+    /// Quote token address.
+    pub quote_token: String,
+    /// `buy` when the taker bought base, `sell` when they sold it.
+    pub side: TradeSide,
+    /// Execution price in quote per base.
+    pub price: String,
+    /// Base amount traded (string-encoded).
+    pub size: String,
+    /// Quote amount traded (string-encoded).
+    pub quote_size: String,
+    /// Execution time.
+    pub timestamp: DateTime<Utc>,
+}
+
+impl From<&Trade> for TradeDto {
+    fn from(trade: &Trade) -> Self {
+        Self {
+            trade_id: trade.trade_id.clone(),
+            pool_id: trade.pool_id,
+            base_token: trade.base_token.clone(),
+            quote_token: trade.quote_token.clone(),
+            side: trade.side,
+            price: format!("{}", trade.price),
+            size: trade.size.to_string(),
+            quote_size: trade.quote_size.to_string(),
+            timestamp: trade.timestamp,
+        }
+    }
+}
+
+/// Response body for `GET /trades`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TradeListResponse {
+    /// Trades, newest first.
+    pub data: Vec<TradeDto>,
+}
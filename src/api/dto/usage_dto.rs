@@ -0,0 +1,130 @@
+//! Per-API-key usage DTOs.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::service::usage::{UsageBucket, UsageReport, UsageTotals};
+
+/// Query parameters for `GET /admin/usage`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UsageParams {
+    /// Only the usage of this API key.
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Earliest hour (inclusive). Defaults to a day before `to`.
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    /// Latest hour (exclusive). Defaults to now.
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Query parameters for `GET /accounts/me/usage`.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UsageRangeParams {
+    /// Earliest hour (inclusive). Defaults to a day before `to`.
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    /// Latest hour (exclusive). Defaults to now.
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Usage counted over some span of time.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UsageTotalsDto {
+    /// HTTP requests admitted.
+    pub requests: u64,
+    /// Swaps executed.
+    pub swaps: u64,
+    /// Swap input volume by input token address (string-encoded raw
+    /// units).
+    pub swap_volume: BTreeMap<String, String>,
+    /// WebSocket payload bytes sent to the key.
+    pub ws_bytes_sent: u64,
+    /// WebSocket payload bytes received from the key.
+    pub ws_bytes_received: u64,
+}
+
+impl From<UsageTotals> for UsageTotalsDto {
+    fn from(totals: UsageTotals) -> Self {
+        Self {
+            requests: totals.requests,
+            swaps: totals.swaps,
+            swap_volume: totals
+                .swap_volume
+                .into_iter()
+                .map(|(token, amount)| (token, amount.to_string()))
+                .collect(),
+            ws_bytes_sent: totals.ws_bytes_sent,
+            ws_bytes_received: totals.ws_bytes_received,
+        }
+    }
+}
+
+/// Usage of one key in one UTC hour.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UsageBucketDto {
+    /// Start of the hour.
+    pub hour_start: DateTime<Utc>,
+    /// Usage within the hour.
+    #[serde(flatten)]
+    pub totals: UsageTotalsDto,
+}
+
+impl From<UsageBucket> for UsageBucketDto {
+    fn from(bucket: UsageBucket) -> Self {
+        Self {
+            hour_start: bucket.hour_start,
+            totals: bucket.totals.into(),
+        }
+    }
+}
+
+/// Usage of one key over the requested range.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UsageDto {
+    /// API key (client id).
+    pub api_key: String,
+    /// Usage over the whole range.
+    pub totals: UsageTotalsDto,
+    /// Hours with usage, oldest first.
+    pub hourly: Vec<UsageBucketDto>,
+}
+
+impl From<UsageReport> for UsageDto {
+    fn from(report: UsageReport) -> Self {
+        Self {
+            api_key: report.api_key,
+            totals: report.totals.into(),
+            hourly: report.hourly.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Response body for `GET /admin/usage`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UsageListResponse {
+    /// Start of the range.
+    pub from: DateTime<Utc>,
+    /// End of the range.
+    pub to: DateTime<Utc>,
+    /// Keys with usage in the range, ordered by key.
+    pub usage: Vec<UsageDto>,
+}
+
+/// Response body for `GET /accounts/me/usage`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UsageResponse {
+    /// Start of the range.
+    pub from: DateTime<Utc>,
+    /// End of the range.
+    pub to: DateTime<Utc>,
+    /// The caller's usage.
+    pub usage: UsageDto,
+}
@@ -0,0 +1,115 @@
+//! Pool webhook DTOs.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::domain::PoolId;
+use crate::service::webhooks::{DeliveryRecord, Webhook};
+
+/// Request body for `POST /pools/:id/webhooks`.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    /// `http` or `https` URL events are POSTed to.
+    pub url: String,
+    /// Event types to deliver (e.g. `swap_executed`); every type when
+    /// empty or omitted.
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    /// Smallest `amount_in` of a delivered swap and smallest
+    /// `liquidity_delta` of a delivered liquidity change (string-encoded
+    /// u128). Other events are not filtered by size.
+    #[serde(default)]
+    pub min_amount: Option<String>,
+    /// HMAC signing secret (16–256 characters). Generated when omitted.
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+/// A webhook attached to a pool. The secret is only returned on creation.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookDto {
+    /// Webhook identifier.
+    pub webhook_id: uuid::Uuid,
+    /// Pool the webhook is attached to.
+    pub pool_id: PoolId,
+    /// Delivery URL.
+    pub url: String,
+    /// Event types delivered; every type when empty.
+    pub event_types: Vec<String>,
+    /// Size threshold (string-encoded), if any.
+    pub min_amount: Option<String>,
+    /// Creation time.
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&Webhook> for WebhookDto {
+    fn from(webhook: &Webhook) -> Self {
+        Self {
+            webhook_id: webhook.id,
+            pool_id: webhook.pool_id,
+            url: webhook.url.clone(),
+            event_types: webhook.event_types.clone(),
+            min_amount: webhook.min_amount.map(|m| m.to_string()),
+            created_at: webhook.created_at,
+        }
+    }
+}
+
+/// Response body for `POST /pools/:id/webhooks`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreateWebhookResponse {
+    /// The new webhook.
+    pub webhook: WebhookDto,
+    /// Signing secret; store it, it is not shown again.
+    pub secret: String,
+    /// Whether the webhook was saved to the database (it is lost on
+    /// restart otherwise).
+    pub persisted: bool,
+}
+
+/// Response body for `GET /pools/:id/webhooks`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookListResponse {
+    /// Webhooks of the pool, oldest first.
+    pub data: Vec<WebhookDto>,
+}
+
+/// Outcome of one delivery.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookDeliveryDto {
+    /// Event type delivered.
+    pub event_type: String,
+    /// Attempts made.
+    pub attempts: u32,
+    /// HTTP status of the last attempt, if a response arrived.
+    pub status: Option<u16>,
+    /// Error of the last attempt, if it failed.
+    pub error: Option<String>,
+    /// Whether an attempt got a 2xx response.
+    pub delivered: bool,
+    /// When the last attempt finished.
+    pub finished_at: DateTime<Utc>,
+}
+
+impl From<DeliveryRecord> for WebhookDeliveryDto {
+    fn from(record: DeliveryRecord) -> Self {
+        Self {
+            event_type: record.event_type,
+            attempts: record.attempts,
+            status: record.status,
+            error: record.error,
+            delivered: record.delivered,
+            finished_at: record.finished_at,
+        }
+    }
+}
+
+/// Response body for `GET /pools/:id/webhooks/:webhook_id/deliveries`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookDeliveriesResponse {
+    /// Webhook identifier.
+    pub webhook_id: uuid::Uuid,
+    /// Recent deliveries, newest first.
+    pub data: Vec<WebhookDeliveryDto>,
+}
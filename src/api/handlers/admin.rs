@@ -0,0 +1,1602 @@
+//! Administrative handlers: disaster-recovery replay, the startup report,
+//! event log compaction and history import, per-API-key rate limits and
+//! usage, pool metadata schemas, capacity usage, pool snapshots and
+//! restores, the quote audit trail, backtests, liquidity-mining schedules,
+//! background jobs, and an event stream tail.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::body::Body;
+use axum::extract::{Path, Query, State};
+use axum::http::{StatusCode, header};
+use axum::response::IntoResponse;
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, stream};
+use tokio::sync::broadcast;
+
+use crate::api::client_id::{ClientId, parse_client_id};
+use crate::api::dto::{
+    BacktestRequest, BacktestResponse, CanaryResponse, CapacityStatsResponse, CheckpointDto,
+    CheckpointListResponse, CompactEventsRequest, CompactEventsResponse, ConsumerEventDto,
+    EmissionScheduleDto, EmissionScheduleListResponse, EventLogParams, EventLogResponse,
+    EventTailParams, ImportEventsResponse, JobDto, JobListParams, JobListResponse,
+    MetadataSchemaDto, MetadataSchemaListResponse, PausePoolParams, PoolContentionResponse,
+    PoolStatusResponse, QuoteAuditDto, QuoteAuditListResponse, QuoteAuditParams,
+    RateLimitListResponse, RateLimitParams, ReplayRequest, ReplayResponse, RestorePoolParams,
+    RestorePoolResponse, SetCanaryRequest, SetEmissionScheduleRequest, SnapshotDto,
+    SnapshotListParams, SnapshotListResponse, StalePoolDto, StalePoolListResponse, StalePoolParams,
+    StartJobRequest, StartupReportResponse, UpdateMetadataSchemaResponse, UpdateRateLimitRequest,
+    UpdateRateLimitResponse, UsageListResponse, UsageParams, WsConnectionDto,
+    WsConnectionListResponse,
+};
+use crate::api::handlers::usage;
+use crate::app_state::AppState;
+use crate::domain::pool_entry::PoolStatus;
+use crate::domain::{PoolEvent, PoolFilter, PoolId};
+use crate::error::{ErrorResponse, GatewayError};
+use crate::persistence::models::{MetadataSchemaRecord, RateLimitRecord};
+use crate::persistence::postgres::PostgresPersistence;
+use crate::service::PoolService;
+use crate::service::backtest;
+use crate::service::candles::{self, CandleAggregator};
+use crate::service::event_import;
+use crate::service::event_tail::{TAIL_CAPACITY, TailFilter};
+use crate::service::jobs::JobProgress;
+use crate::service::metadata_schema::{self, SchemaScope};
+use crate::service::ownership::Manager;
+use crate::service::quota::QuotaLimits;
+use crate::service::rewards::EmissionSchedule;
+use crate::service::stale_pools::StalePoolPolicy;
+use crate::service::trade_tape::Trade;
+use crate::service::webhooks::EVENT_TYPES;
+use crate::service::{replay, snapshot};
+
+/// `POST /admin/replay` — Rebuild pools from persisted history.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PersistenceUnavailable`] when persistence is
+/// disabled, or [`GatewayError::PersistenceError`] on database failure.
+#[utoipa::path(
+    post,
+    path = "/admin/replay",
+    tag = "Admin",
+    summary = "Replay events into a staging registry",
+    description = "Rebuilds pool state from snapshots and the event log into a throwaway registry and reports the resulting reserves and prices. Live pools are not modified; use this to rehearse disaster recovery.",
+    request_body = ReplayRequest,
+    responses(
+        (status = 200, description = "Replay completed", body = ReplayResponse),
+        (status = 400, description = "Invalid time range", body = ErrorResponse),
+        (status = 503, description = "Persistence unavailable", body = ErrorResponse),
+    )
+)]
+pub async fn replay_events(
+    State(state): State<AppState>,
+    Json(req): Json<ReplayRequest>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let persistence = state
+        .persistence
+        .as_ref()
+        .ok_or(GatewayError::PersistenceUnavailable)?;
+    let to = replay_cutoff(req.from, req.to)?;
+    Ok(Json(
+        run_replay(persistence, req.from, to, req.pool_id).await?,
+    ))
+}
+
+/// Resolves the replay cut-off, defaulting to now.
+fn replay_cutoff(
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<DateTime<Utc>, GatewayError> {
+    let to = to.unwrap_or_else(Utc::now);
+    if from.is_some_and(|from| from > to) {
+        return Err(GatewayError::InvalidRequest(
+            "`from` must not be after `to`".to_string(),
+        ));
+    }
+    Ok(to)
+}
+
+/// Replays history into a staging registry and reports the outcome.
+async fn run_replay(
+    persistence: &PostgresPersistence,
+    from: Option<DateTime<Utc>>,
+    to: DateTime<Utc>,
+    pool_id: Option<PoolId>,
+) -> Result<ReplayResponse, GatewayError> {
+    let started = Instant::now();
+    let (_staging, report) = replay::replay_into_staging(persistence, from, to, pool_id).await?;
+    let duration_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+
+    tracing::info!(
+        pools = report.pools.len(),
+        events = report.events_scanned,
+        duration_ms,
+        "replay rehearsal completed"
+    );
+
+    Ok(ReplayResponse {
+        from,
+        to,
+        events_scanned: report.events_scanned,
+        events_applied: report.events_applied,
+        events_skipped: report.events_skipped,
+        duration_ms,
+        pools: report.pools.into_iter().map(Into::into).collect(),
+    })
+}
+
+/// `GET /admin/startup-report` — What startup recovery restored.
+#[utoipa::path(
+    get,
+    path = "/admin/startup-report",
+    tag = "Admin",
+    summary = "Startup recovery report",
+    description = "Reports how this instance rebuilt its pools at startup: snapshots loaded, events replayed and skipped, pools restored, removed, and failed (with the reason for each failure), and how long recovery took. Replicas report their bootstrap. When persistence or `PERSISTENCE_RECOVER_ON_STARTUP` is off, `recovered` is `false`.",
+    responses(
+        (status = 200, description = "Startup report", body = StartupReportResponse),
+    )
+)]
+pub async fn startup_report(State(state): State<AppState>) -> Json<StartupReportResponse> {
+    Json(StartupReportResponse::from(state.startup_report.as_ref()))
+}
+
+/// `POST /admin/events/compact` — Collapse snapshotted events into a checkpoint.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] if the range is inverted or
+/// not covered by a snapshot, [`GatewayError::PersistenceUnavailable`]
+/// when persistence is disabled, or [`GatewayError::PersistenceError`]
+/// on database failure.
+#[utoipa::path(
+    post,
+    path = "/admin/events/compact",
+    tag = "Admin",
+    summary = "Compact the event log",
+    description = "Deletes a pool's swap, liquidity, fee, and price events in a time range already covered by a snapshot and replaces them with one checkpoint holding volume, fee, and OHLC price aggregates and the swaps bucketed by hour, input token, and actor. Checkpoints are stored apart from the event log; list them with `GET /admin/pools/{id}/checkpoints`. Creation and removal events are kept.",
+    request_body = CompactEventsRequest,
+    responses(
+        (status = 200, description = "Range compacted", body = CompactEventsResponse),
+        (status = 400, description = "Invalid or uncovered range", body = ErrorResponse),
+        (status = 503, description = "Persistence unavailable", body = ErrorResponse),
+    )
+)]
+pub async fn compact_events(
+    State(state): State<AppState>,
+    Json(req): Json<CompactEventsRequest>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let persistence = state
+        .persistence
+        .as_ref()
+        .ok_or(GatewayError::PersistenceUnavailable)?;
+    if req.from > req.to {
+        return Err(GatewayError::InvalidRequest(
+            "`from` must not be after `to`".to_string(),
+        ));
+    }
+
+    let outcome = persistence
+        .compact_events(*req.pool_id.as_uuid(), req.from, req.to)
+        .await?;
+
+    tracing::info!(
+        pool_id = %req.pool_id,
+        deleted = outcome.events_deleted,
+        "event log compacted"
+    );
+
+    Ok(Json(CompactEventsResponse {
+        pool_id: req.pool_id,
+        events_deleted: outcome.events_deleted,
+        checkpoint_id: outcome.checkpoint_id,
+        summary: outcome.summary,
+    }))
+}
+
+/// `POST /admin/pools/:id/events/import` — Import historical events.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+/// [`GatewayError::InvalidRequest`] if any line is invalid,
+/// [`GatewayError::ReadOnlyReplica`] on a replica,
+/// [`GatewayError::PersistenceUnavailable`] when persistence is disabled,
+/// or [`GatewayError::PersistenceError`] on database failure.
+#[utoipa::path(
+    post,
+    path = "/admin/pools/{id}/events/import",
+    tag = "Admin",
+    summary = "Import event history",
+    description = "Appends a migrated pool's history from another venue to the event log. The body is NDJSON, one event per line in the event log's JSON shape: `swap_executed`, `liquidity_changed`, `fees_collected`, or `price_updated` events of this pool, in chronological order and not in the future (up to 10000). The whole import is rejected if any line is invalid, with the offending line numbers. Events keep their timestamps, get new event log IDs, and are flagged as imported: volatility, candles, and event consumers include them, while replay and replicas skip them.",
+    params(("id" = uuid::Uuid, Path, description = "Pool UUID")),
+    request_body(content = String, content_type = "application/x-ndjson", description = "One event per line"),
+    responses(
+        (status = 200, description = "History imported", body = ImportEventsResponse),
+        (status = 400, description = "Invalid events", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+        (status = 503, description = "Persistence unavailable or read-only replica", body = ErrorResponse),
+    )
+)]
+pub async fn import_events(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    body: String,
+) -> Result<impl IntoResponse, GatewayError> {
+    if state.pool_service.is_read_only() {
+        return Err(GatewayError::ReadOnlyReplica);
+    }
+    let persistence = state
+        .persistence
+        .as_ref()
+        .ok_or(GatewayError::PersistenceUnavailable)?;
+    let pool_id = PoolId::from_uuid(id);
+    let entry = state.pool_service.registry().get(pool_id).await?;
+    let events = event_import::parse_ndjson(pool_id, &body, Utc::now())?;
+
+    let ids = persistence.import_events(id, &events).await?;
+    let (Some(&first_event_id), Some(&last_event_id)) = (ids.first(), ids.last()) else {
+        return Err(GatewayError::Internal("import assigned no ids".to_string()));
+    };
+
+    // Fold imported swaps and price changes into candles so charts cover
+    // the history too
+    let mut aggregator = CandleAggregator::default();
+    {
+        let entry = entry.read().await;
+        for event in &events {
+            if let Some(trade) = Trade::from_event(event, &entry) {
+                aggregator.record(&trade);
+            } else if let Some((pool_id, price, at)) = candles::price_mark(event) {
+                aggregator.mark(pool_id, price, at);
+            }
+        }
+    }
+    let candles = aggregator.drain();
+    persistence.upsert_candles(&candles).await?;
+    state.candles.merge(&candles);
+
+    tracing::info!(
+        %pool_id,
+        events = ids.len(),
+        first_event_id,
+        last_event_id,
+        "event history imported"
+    );
+
+    Ok(Json(ImportEventsResponse {
+        pool_id,
+        events_imported: ids.len(),
+        first_event_id,
+        last_event_id,
+        candles_updated: candles.len(),
+    }))
+}
+
+/// `GET /admin/rate-limits` — List per-API-key quotas and usage.
+#[utoipa::path(
+    get,
+    path = "/admin/rate-limits",
+    tag = "Admin",
+    summary = "List rate limits",
+    description = "Returns the quota of every configured API key (requests per minute, swaps per day, concurrent WebSocket connections, live pools, pool creations per day) with this instance's current usage counters. Keys without a quota are unlimited and not listed.",
+    params(RateLimitParams),
+    responses(
+        (status = 200, description = "Configured quotas", body = RateLimitListResponse),
+    )
+)]
+pub async fn list_rate_limits(
+    State(state): State<AppState>,
+    Query(params): Query<RateLimitParams>,
+) -> Json<RateLimitListResponse> {
+    let now = Utc::now();
+    let quotas = match params.api_key {
+        Some(api_key) => state.quotas.report_for(&api_key, now).into_iter().collect(),
+        None => state.quotas.report(now),
+    };
+    Json(RateLimitListResponse {
+        rate_limits: quotas.into_iter().map(Into::into).collect(),
+    })
+}
+
+/// `PUT /admin/rate-limits` — Set the quota of an API key.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] if the API key is malformed,
+/// or [`GatewayError::PersistenceError`] if the quota cannot be saved.
+#[utoipa::path(
+    put,
+    path = "/admin/rate-limits",
+    tag = "Admin",
+    summary = "Set a rate limit",
+    description = "Replaces the quota of one API key and applies it immediately; current usage counters are kept. Omitted or null limits are unlimited. The quota is saved to the database when persistence is enabled.",
+    request_body = UpdateRateLimitRequest,
+    responses(
+        (status = 200, description = "Quota applied", body = UpdateRateLimitResponse),
+        (status = 400, description = "Invalid API key", body = ErrorResponse),
+        (status = 500, description = "Quota could not be saved", body = ErrorResponse),
+    )
+)]
+pub async fn update_rate_limit(
+    State(state): State<AppState>,
+    Json(req): Json<UpdateRateLimitRequest>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let record = RateLimitRecord {
+        api_key: parse_client_id(&req.api_key)?,
+        requests_per_minute: req.requests_per_minute,
+        swaps_per_day: req.swaps_per_day,
+        ws_connections: req.ws_connections,
+        max_pools: req.max_pools,
+        pool_creations_per_day: req.pool_creations_per_day,
+    };
+    let persisted = match state.persistence.as_ref() {
+        Some(persistence) => {
+            persistence.save_rate_limit(&record).await?;
+            true
+        }
+        None => false,
+    };
+    state
+        .quotas
+        .set_limits(&record.api_key, QuotaLimits::from(&record));
+
+    tracing::info!(api_key = %record.api_key, persisted, "rate limit updated");
+
+    let quota = state
+        .quotas
+        .report_for(&record.api_key, Utc::now())
+        .ok_or_else(|| GatewayError::Internal("rate limit vanished".to_string()))?;
+    Ok(Json(UpdateRateLimitResponse {
+        rate_limit: quota.into(),
+        persisted,
+    }))
+}
+
+/// `GET /admin/usage` — Usage per API key.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] if `from` is not before `to`.
+#[utoipa::path(
+    get,
+    path = "/admin/usage",
+    tag = "Admin",
+    summary = "API key usage",
+    description = "Returns each API key's requests, swaps with their input volume per token, and WebSocket bytes in each direction, in hourly buckets whose start falls within [from, to) (default: the last day). Without `key`, lists every key with usage in the range. Usage is counted by the instance that served it and kept for `USAGE_RETENTION_DAYS`.",
+    params(UsageParams),
+    responses(
+        (status = 200, description = "Usage per key", body = UsageListResponse),
+        (status = 400, description = "Invalid time range", body = ErrorResponse),
+    )
+)]
+pub async fn list_usage(
+    State(state): State<AppState>,
+    Query(params): Query<UsageParams>,
+) -> Result<Json<UsageListResponse>, GatewayError> {
+    let (from, to) = usage::usage_range(params.from, params.to)?;
+    let reports = match params.key {
+        Some(key) => vec![state.usage.report(&key, from, to)],
+        None => state.usage.report_all(from, to),
+    };
+    Ok(Json(UsageListResponse {
+        from,
+        to,
+        usage: reports.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// `GET /admin/metadata-schemas` — List pool metadata schemas.
+#[utoipa::path(
+    get,
+    path = "/admin/metadata-schemas",
+    tag = "Admin",
+    summary = "List metadata schemas",
+    description = "Returns every JSON Schema that pool metadata is validated against: tenant schemas (by client id) first, then pool type schemas.",
+    responses(
+        (status = 200, description = "Registered schemas", body = MetadataSchemaListResponse),
+    )
+)]
+pub async fn list_metadata_schemas(
+    State(state): State<AppState>,
+) -> Json<MetadataSchemaListResponse> {
+    Json(MetadataSchemaListResponse {
+        schemas: state
+            .metadata_schemas
+            .list()
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+    })
+}
+
+/// `PUT /admin/metadata-schemas/{scope}/{name}` — Register a pool metadata
+/// schema.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] for an unknown scope, a
+/// malformed client id, or a body that is not a valid JSON Schema, or
+/// [`GatewayError::PersistenceError`] if the schema cannot be saved.
+#[utoipa::path(
+    put,
+    path = "/admin/metadata-schemas/{scope}/{name}",
+    tag = "Admin",
+    summary = "Set a metadata schema",
+    description = "Registers the JSON Schema in the body for a tenant (`scope=tenant`, `name` = client id) or a pool type (`scope=pool_type`), replacing any previous one. Pool metadata set on create or `PATCH /api/v1/pools/{id}` must satisfy both the caller's and the pool type's schema; pools created earlier are not revalidated. The schema is saved to the database when persistence is enabled.",
+    params(
+        ("scope" = SchemaScope, Path, description = "`tenant` or `pool_type`"),
+        ("name" = String, Path, description = "Client id or pool type"),
+    ),
+    request_body(content = Object, description = "JSON Schema"),
+    responses(
+        (status = 200, description = "Schema applied", body = UpdateMetadataSchemaResponse),
+        (status = 400, description = "Invalid scope, name, or schema", body = ErrorResponse),
+        (status = 500, description = "Schema could not be saved", body = ErrorResponse),
+    )
+)]
+pub async fn update_metadata_schema(
+    State(state): State<AppState>,
+    Path((scope, name)): Path<(String, String)>,
+    Json(schema): Json<serde_json::Value>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let scope: SchemaScope = scope.parse()?;
+    let name = match scope {
+        SchemaScope::Tenant => parse_client_id(&name)?,
+        SchemaScope::PoolType => name,
+    };
+    metadata_schema::compile(&schema)?;
+    let record = MetadataSchemaRecord {
+        scope: scope.to_string(),
+        name,
+        schema,
+        updated_at: Utc::now(),
+    };
+    let persisted = match state.persistence.as_ref() {
+        Some(persistence) => {
+            persistence.save_metadata_schema(&record).await?;
+            true
+        }
+        None => false,
+    };
+    state.metadata_schemas.set(
+        scope,
+        &record.name,
+        record.schema.clone(),
+        record.updated_at,
+    )?;
+
+    tracing::info!(%scope, name = %record.name, persisted, "metadata schema updated");
+
+    Ok(Json(UpdateMetadataSchemaResponse {
+        schema: MetadataSchemaDto::from(record),
+        persisted,
+    }))
+}
+
+/// `DELETE /admin/metadata-schemas/{scope}/{name}` — Remove a pool
+/// metadata schema.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] for an unknown scope,
+/// [`GatewayError::MetadataSchemaNotFound`] if no such schema is
+/// registered, or [`GatewayError::PersistenceError`] if it cannot be
+/// deleted from the database.
+#[utoipa::path(
+    delete,
+    path = "/admin/metadata-schemas/{scope}/{name}",
+    tag = "Admin",
+    summary = "Remove a metadata schema",
+    description = "Stops validating pool metadata against the schema and deletes it from the database when persistence is enabled.",
+    params(
+        ("scope" = SchemaScope, Path, description = "`tenant` or `pool_type`"),
+        ("name" = String, Path, description = "Client id or pool type"),
+    ),
+    responses(
+        (status = 204, description = "Schema removed"),
+        (status = 400, description = "Invalid scope", body = ErrorResponse),
+        (status = 404, description = "Schema not found", body = ErrorResponse),
+    )
+)]
+pub async fn delete_metadata_schema(
+    State(state): State<AppState>,
+    Path((scope, name)): Path<(String, String)>,
+) -> Result<StatusCode, GatewayError> {
+    let scope: SchemaScope = scope.parse()?;
+    if let Some(persistence) = state.persistence.as_ref() {
+        persistence
+            .delete_metadata_schema(scope.as_str(), &name)
+            .await?;
+    }
+    if !state.metadata_schemas.remove(scope, &name) {
+        return Err(GatewayError::MetadataSchemaNotFound(format!(
+            "{scope}:{name}"
+        )));
+    }
+    tracing::info!(%scope, %name, "metadata schema removed");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `POST /admin/jobs` — Start a background job.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] for an invalid range or an
+/// empty pool list, or [`GatewayError::PersistenceUnavailable`] when the
+/// job needs persistence and it is disabled.
+#[utoipa::path(
+    post,
+    path = "/admin/jobs",
+    tag = "Admin",
+    summary = "Start a background job",
+    description = "Starts a snapshot-all, replay, compaction, or bulk-delete job and returns at once with its id. Poll `GET /admin/jobs/{id}` for progress and the result. Compaction and bulk-delete failures on individual pools are reported in the result without stopping the job.",
+    request_body = StartJobRequest,
+    responses(
+        (status = 202, description = "Job started", body = JobDto),
+        (status = 400, description = "Invalid job parameters", body = ErrorResponse),
+        (status = 503, description = "Persistence unavailable", body = ErrorResponse),
+    )
+)]
+pub async fn start_job(
+    State(state): State<AppState>,
+    Json(req): Json<StartJobRequest>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let kind = req.kind();
+    let pool_service = Arc::clone(&state.pool_service);
+    let job = match req {
+        StartJobRequest::SnapshotAll { pool_type } => {
+            let db = require_persistence(&state)?;
+            state.jobs.spawn(kind, move |progress| async move {
+                snapshot_all_job(&pool_service, &db, pool_type.as_deref(), &progress).await
+            })
+        }
+        StartJobRequest::Replay { from, to, pool_id } => {
+            let db = require_persistence(&state)?;
+            let to = replay_cutoff(from, to)?;
+            state.jobs.spawn(kind, move |progress| async move {
+                progress.set_total(1);
+                let response = run_replay(&db, from, to, pool_id).await?;
+                progress.advance();
+                to_json(&response)
+            })
+        }
+        StartJobRequest::Compaction { pool_ids, from, to } => {
+            let db = require_persistence(&state)?;
+            if from > to {
+                return Err(GatewayError::InvalidRequest(
+                    "`from` must not be after `to`".to_string(),
+                ));
+            }
+            state.jobs.spawn(kind, move |progress| async move {
+                compaction_job(&pool_service, &db, pool_ids, from, to, &progress).await
+            })
+        }
+        StartJobRequest::BulkDelete { pool_ids, force } => {
+            if pool_ids.is_empty() {
+                return Err(GatewayError::InvalidRequest(
+                    "`pool_ids` must not be empty".to_string(),
+                ));
+            }
+            let db = state.persistence.clone();
+            state.jobs.spawn(kind, move |progress| async move {
+                bulk_delete_job(&pool_service, db.as_ref(), &pool_ids, force, &progress).await
+            })
+        }
+    };
+    Ok((StatusCode::ACCEPTED, Json(JobDto::from(job))))
+}
+
+/// `GET /admin/jobs` — List background jobs.
+#[utoipa::path(
+    get,
+    path = "/admin/jobs",
+    tag = "Admin",
+    summary = "List background jobs",
+    description = "Returns jobs started on this instance, newest first: running jobs and the most recent finished ones.",
+    params(JobListParams),
+    responses(
+        (status = 200, description = "Jobs", body = JobListResponse),
+    )
+)]
+pub async fn list_jobs(
+    State(state): State<AppState>,
+    Query(params): Query<JobListParams>,
+) -> Json<JobListResponse> {
+    let jobs = state
+        .jobs
+        .list()
+        .into_iter()
+        .filter(|job| params.status.is_none_or(|status| job.status == status))
+        .filter(|job| params.kind.is_none_or(|kind| job.kind == kind))
+        .map(Into::into)
+        .collect();
+    Json(JobListResponse { jobs })
+}
+
+/// `GET /admin/jobs/{id}` — Get a background job.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::JobNotFound`] if the job is unknown.
+#[utoipa::path(
+    get,
+    path = "/admin/jobs/{id}",
+    tag = "Admin",
+    summary = "Get a background job",
+    description = "Returns the status, progress, error, and result of one job.",
+    params(("id" = uuid::Uuid, Path, description = "Job UUID")),
+    responses(
+        (status = 200, description = "Job", body = JobDto),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_job(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<JobDto>, GatewayError> {
+    state
+        .jobs
+        .get(id)
+        .map(|job| Json(job.into()))
+        .ok_or(GatewayError::JobNotFound(id))
+}
+
+/// `POST /admin/jobs/{id}/cancel` — Cancel a background job.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::JobNotFound`] if the job is unknown.
+#[utoipa::path(
+    post,
+    path = "/admin/jobs/{id}/cancel",
+    tag = "Admin",
+    summary = "Cancel a background job",
+    description = "Asks a running job to stop. The job finishes its current step (one pool, or a whole replay) and is then marked cancelled; work already done is kept. Finished jobs are returned unchanged.",
+    params(("id" = uuid::Uuid, Path, description = "Job UUID")),
+    responses(
+        (status = 200, description = "Cancellation requested", body = JobDto),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+    )
+)]
+pub async fn cancel_job(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<JobDto>, GatewayError> {
+    Ok(Json(state.jobs.cancel(id)?.into()))
+}
+
+/// `GET /admin/stats` — Pool and position counts against their limits.
+#[utoipa::path(
+    get,
+    path = "/admin/stats",
+    tag = "Admin",
+    summary = "Capacity usage",
+    description = "Returns how many pools the registry holds and how many liquidity positions are tracked, next to the configured `MAX_POOLS` and `MAX_POSITIONS_PER_POOL` limits. Creations beyond a limit are rejected with 422 (code 4004). Also counts mutations rejected with 503 (code 3005) because a pool lock was not acquired within `POOL_LOCK_TIMEOUT_MS`.",
+    responses(
+        (status = 200, description = "Capacity usage", body = CapacityStatsResponse),
+    )
+)]
+pub async fn capacity_stats(State(state): State<AppState>) -> Json<CapacityStatsResponse> {
+    Json(state.pool_service.capacity_usage().await.into())
+}
+
+/// `GET /admin/pools/{id}/contention` — Lock contention on one pool.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist.
+#[utoipa::path(
+    get,
+    path = "/admin/pools/{id}/contention",
+    tag = "Admin",
+    summary = "Pool lock contention",
+    description = "Reports contention on the pool's write lock, taken by every mutation: the operation holding it and for how long, writers queued behind it, acquisitions, average and maximum wait, writers rejected after `POOL_LOCK_TIMEOUT_MS`, and wait and hold times of the last 50 operations. Figures are in microseconds and cover this instance since startup; readers are not counted.",
+    params(("id" = uuid::Uuid, Path, description = "Pool UUID")),
+    responses(
+        (status = 200, description = "Contention statistics", body = PoolContentionResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+    )
+)]
+pub async fn pool_contention(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<PoolContentionResponse>, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    state.pool_service.registry().get(pool_id).await?;
+    let stats = state.pool_service.contention().stats(pool_id);
+    Ok(Json(PoolContentionResponse::new(
+        pool_id,
+        stats,
+        Utc::now(),
+    )))
+}
+
+/// `PUT /admin/pools/{id}/canary` — Mark a pool as a canary.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist, or
+/// [`GatewayError::InvalidRequest`] if the weight exceeds 10 000 bps or
+/// the pool is never routed.
+#[utoipa::path(
+    put,
+    path = "/admin/pools/{id}/canary",
+    tag = "Admin",
+    summary = "Mark a canary pool",
+    description = "Limits the share of smart-router flow (`POST /api/v1/swap/auto`) the pool can receive to `weight_bps`. Each route search admits the pool with that probability, so a new pool configuration is validated on a fraction of real traffic; direct swaps on the pool are unaffected. `0` keeps it out of routing entirely. The weight is kept in pool snapshots.",
+    params(("id" = uuid::Uuid, Path, description = "Pool UUID")),
+    request_body = SetCanaryRequest,
+    responses(
+        (status = 200, description = "Canary weight set", body = CanaryResponse),
+        (status = 400, description = "Invalid weight or unroutable pool", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+    )
+)]
+pub async fn set_canary(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    Json(req): Json<SetCanaryRequest>,
+) -> Result<Json<CanaryResponse>, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    state
+        .pool_service
+        .set_canary_weight(pool_id, Some(req.weight_bps))
+        .await?;
+    Ok(Json(CanaryResponse {
+        pool_id,
+        canary_weight_bps: Some(req.weight_bps),
+    }))
+}
+
+/// `DELETE /admin/pools/{id}/canary` — Promote a canary pool.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist, or
+/// [`GatewayError::InvalidRequest`] if the pool is never routed.
+#[utoipa::path(
+    delete,
+    path = "/admin/pools/{id}/canary",
+    tag = "Admin",
+    summary = "Promote a canary pool",
+    description = "Clears the pool's canary weight, so the smart router considers it for every route.",
+    params(("id" = uuid::Uuid, Path, description = "Pool UUID")),
+    responses(
+        (status = 200, description = "Pool promoted", body = CanaryResponse),
+        (status = 400, description = "Unroutable pool", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+    )
+)]
+pub async fn promote_canary(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<CanaryResponse>, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    state.pool_service.set_canary_weight(pool_id, None).await?;
+    Ok(Json(CanaryResponse {
+        pool_id,
+        canary_weight_bps: None,
+    }))
+}
+
+/// `POST /admin/pools/{id}/pause` — Pause or drain a pool.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+/// [`GatewayError::PoolArchived`] if it is archived, or
+/// [`GatewayError::ReadOnlyReplica`] on a replica.
+#[utoipa::path(
+    post,
+    path = "/admin/pools/{id}/pause",
+    tag = "Admin",
+    summary = "Pause a pool",
+    description = "Halts a pool: swaps, liquidity changes, orders, and oracle updates against it fail with 409 (code 2012) until it is resumed, and the smart router stops considering it. With `drain=true` the pool drains instead: swaps, new liquidity, and new orders are rejected, but providers can still withdraw, collect fees, and cancel orders. Reads and quotes keep working. Emits a PoolStatusChanged event naming the caller, unless the pool was already in that status. The status is kept in pool snapshots.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        PausePoolParams,
+        ("x-client-id" = Option<String>, Header, description = "Calling operator, recorded as the actor of the PoolStatusChanged event"),
+    ),
+    responses(
+        (status = 200, description = "Pool paused or draining", body = PoolStatusResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+        (status = 409, description = "Pool archived", body = ErrorResponse),
+        (status = 503, description = "Read-only replica", body = ErrorResponse),
+    )
+)]
+pub async fn pause_pool(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    Query(params): Query<PausePoolParams>,
+    client: ClientId,
+) -> Result<Json<PoolStatusResponse>, GatewayError> {
+    let status = if params.drain {
+        PoolStatus::Draining
+    } else {
+        PoolStatus::Paused
+    };
+    set_pool_status(&state, id, status, client.as_deref()).await
+}
+
+/// `POST /admin/pools/{id}/resume` — Resume a paused or draining pool.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+/// [`GatewayError::PoolArchived`] if it is archived, or
+/// [`GatewayError::ReadOnlyReplica`] on a replica.
+#[utoipa::path(
+    post,
+    path = "/admin/pools/{id}/resume",
+    tag = "Admin",
+    summary = "Resume a pool",
+    description = "Returns a paused or draining pool to `active`, so it accepts every operation and is routed again. Emits a PoolStatusChanged event naming the caller, unless the pool was already active. Archived pools cannot be resumed.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("x-client-id" = Option<String>, Header, description = "Calling operator, recorded as the actor of the PoolStatusChanged event"),
+    ),
+    responses(
+        (status = 200, description = "Pool active", body = PoolStatusResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+        (status = 409, description = "Pool archived", body = ErrorResponse),
+        (status = 503, description = "Read-only replica", body = ErrorResponse),
+    )
+)]
+pub async fn resume_pool(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    client: ClientId,
+) -> Result<Json<PoolStatusResponse>, GatewayError> {
+    set_pool_status(&state, id, PoolStatus::Active, client.as_deref()).await
+}
+
+/// Moves pool `id` to `status` on behalf of `actor`.
+async fn set_pool_status(
+    state: &AppState,
+    id: uuid::Uuid,
+    status: PoolStatus,
+    actor: Option<&str>,
+) -> Result<Json<PoolStatusResponse>, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    let previous_status = state
+        .pool_service
+        .set_pool_status(pool_id, status, actor)
+        .await?;
+    Ok(Json(PoolStatusResponse {
+        pool_id,
+        previous_status,
+        status,
+    }))
+}
+
+/// `GET /admin/pools/{id}/snapshots` — List a pool's stored snapshots.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] for a `limit` outside
+/// `1..=1000`, [`GatewayError::PersistenceUnavailable`] when persistence
+/// is disabled, or [`GatewayError::PersistenceError`] on database failure.
+#[utoipa::path(
+    get,
+    path = "/admin/pools/{id}/snapshots",
+    tag = "Admin",
+    summary = "List pool snapshots",
+    description = "Lists the snapshots stored for the pool, newest first, with when each was taken and its size. Snapshots of deleted pools are listed too. Pass an `id` to `POST /admin/pools/{id}/restore` to roll the pool back to it.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        SnapshotListParams,
+    ),
+    responses(
+        (status = 200, description = "Stored snapshots", body = SnapshotListResponse),
+        (status = 400, description = "Invalid limit", body = ErrorResponse),
+        (status = 503, description = "Persistence unavailable", body = ErrorResponse),
+    )
+)]
+pub async fn list_snapshots(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    Query(params): Query<SnapshotListParams>,
+) -> Result<Json<SnapshotListResponse>, GatewayError> {
+    let limit = params.limit.unwrap_or(DEFAULT_SNAPSHOT_LIMIT);
+    if !(1..=MAX_SNAPSHOT_LIMIT).contains(&limit) {
+        return Err(GatewayError::InvalidRequest(format!(
+            "limit must be between 1 and {MAX_SNAPSHOT_LIMIT}"
+        )));
+    }
+    let db = require_persistence(&state)?;
+    let snapshots = db.list_snapshots(id, i64::from(limit)).await?;
+    Ok(Json(SnapshotListResponse {
+        pool_id: PoolId::from_uuid(id),
+        snapshots: snapshots.into_iter().map(SnapshotDto::from).collect(),
+    }))
+}
+
+/// `GET /admin/pools/{id}/checkpoints` — List a pool's compaction checkpoints.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PersistenceUnavailable`] when persistence is
+/// disabled, or [`GatewayError::PersistenceError`] on database failure.
+#[utoipa::path(
+    get,
+    path = "/admin/pools/{id}/checkpoints",
+    tag = "Admin",
+    summary = "List compaction checkpoints",
+    description = "Lists the checkpoints left by compacting the pool's event log, oldest first, with the compacted range and the aggregates kept for it.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+    ),
+    responses(
+        (status = 200, description = "Compaction checkpoints", body = CheckpointListResponse),
+        (status = 503, description = "Persistence unavailable", body = ErrorResponse),
+    )
+)]
+pub async fn list_checkpoints(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<CheckpointListResponse>, GatewayError> {
+    let db = require_persistence(&state)?;
+    let checkpoints = db.load_checkpoints(Some(id)).await?;
+    Ok(Json(CheckpointListResponse {
+        pool_id: PoolId::from_uuid(id),
+        checkpoints: checkpoints.into_iter().map(CheckpointDto::from).collect(),
+    }))
+}
+
+/// Snapshots listed by `GET /admin/pools/{id}/snapshots` when no `limit`
+/// is given.
+const DEFAULT_SNAPSHOT_LIMIT: u32 = 100;
+/// Largest `limit` accepted by `GET /admin/pools/{id}/snapshots`.
+const MAX_SNAPSHOT_LIMIT: u32 = 1_000;
+
+/// `POST /admin/pools/{id}/restore` — Restore a pool from a snapshot.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+/// [`GatewayError::SnapshotNotFound`] if the snapshot does not exist,
+/// [`GatewayError::InvalidRequest`] if it belongs to another pool,
+/// [`GatewayError::ReadOnlyReplica`] on a replica,
+/// [`GatewayError::PersistenceUnavailable`] when persistence is disabled,
+/// or [`GatewayError::PersistenceError`] on database failure.
+#[utoipa::path(
+    post,
+    path = "/admin/pools/{id}/restore",
+    tag = "Admin",
+    summary = "Restore a pool from a snapshot",
+    description = "Replaces the live pool's state with snapshot `snapshot_id`: reserves, positions, resting orders, and counters roll back to when the snapshot was taken. The current state is first archived as a new snapshot (its id is returned as `archived_snapshot_id`, so the restore can be undone), then a PoolRestored event naming the caller is emitted. Replicas reload the pool from the same snapshot.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        RestorePoolParams,
+        ("x-client-id" = Option<String>, Header, description = "Calling admin (ADMIN_CLIENT_IDS), recorded as the actor of the PoolRestored event"),
+    ),
+    responses(
+        (status = 200, description = "Pool restored", body = RestorePoolResponse),
+        (status = 400, description = "Snapshot of another pool", body = ErrorResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "Pool or snapshot not found", body = ErrorResponse),
+        (status = 503, description = "Persistence unavailable or read-only replica", body = ErrorResponse),
+    )
+)]
+pub async fn restore_pool(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    Query(params): Query<RestorePoolParams>,
+    client: ClientId,
+) -> Result<Json<RestorePoolResponse>, GatewayError> {
+    let db = require_persistence(&state)?;
+    let snapshot = db
+        .load_snapshot(params.snapshot_id)
+        .await?
+        .ok_or(GatewayError::SnapshotNotFound(params.snapshot_id))?;
+    let restored = state
+        .pool_service
+        .restore_pool(
+            PoolId::from_uuid(id),
+            &snapshot,
+            &db,
+            Manager::Client(client.as_deref()),
+        )
+        .await?;
+    Ok(Json(RestorePoolResponse::from(restored)))
+}
+
+/// `GET /admin/quotes` — List quotes recorded in the audit trail.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] for a `limit` outside
+/// `1..=1000` or `from` not before `to`,
+/// [`GatewayError::PersistenceUnavailable`] when persistence is disabled,
+/// or [`GatewayError::PersistenceError`] on database failure.
+#[utoipa::path(
+    get,
+    path = "/admin/quotes",
+    tag = "Admin",
+    summary = "List audited quotes",
+    description = "Lists quotes recorded by the quote audit trail (`PERSISTENCE_QUOTE_AUDIT_ENABLED`), newest first, optionally for one client and one pool. Compare them with the client's `swap_executed` events to audit best execution. Only sampled quotes are recorded.",
+    params(QuoteAuditParams),
+    responses(
+        (status = 200, description = "Recorded quotes", body = QuoteAuditListResponse),
+        (status = 400, description = "Invalid limit or time range", body = ErrorResponse),
+        (status = 503, description = "Persistence unavailable", body = ErrorResponse),
+    )
+)]
+pub async fn list_quotes(
+    State(state): State<AppState>,
+    Query(params): Query<QuoteAuditParams>,
+) -> Result<Json<QuoteAuditListResponse>, GatewayError> {
+    let limit = params.limit.unwrap_or(DEFAULT_QUOTE_LIMIT);
+    if !(1..=MAX_QUOTE_LIMIT).contains(&limit) {
+        return Err(GatewayError::InvalidRequest(format!(
+            "limit must be between 1 and {MAX_QUOTE_LIMIT}"
+        )));
+    }
+    let to = params.to.unwrap_or_else(Utc::now);
+    let from = params
+        .from
+        .unwrap_or_else(|| to - chrono::Duration::days(1));
+    if from >= to {
+        return Err(GatewayError::InvalidRequest(
+            "from must be before to".to_string(),
+        ));
+    }
+    let db = require_persistence(&state)?;
+    let quotes = db
+        .load_quotes(
+            params.client_id.as_deref(),
+            params.pool_id.map(|id| *id.as_uuid()),
+            from,
+            to,
+            limit,
+        )
+        .await?;
+    Ok(Json(QuoteAuditListResponse {
+        quotes: quotes.into_iter().map(QuoteAuditDto::from).collect(),
+    }))
+}
+
+/// Quotes listed by `GET /admin/quotes` when no `limit` is given.
+const DEFAULT_QUOTE_LIMIT: u32 = 100;
+/// Largest `limit` accepted by `GET /admin/quotes`.
+const MAX_QUOTE_LIMIT: u32 = 1_000;
+
+/// `POST /admin/pools/{id}/backtest` — Backtest a pool against alternative parameters.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PersistenceUnavailable`] when persistence is
+/// disabled, [`GatewayError::InvalidRequest`] for an inverted
+/// window, overrides the pool type does not have, or history that does
+/// not apply under them, and [`GatewayError::PersistenceError`] on
+/// database failure.
+#[utoipa::path(
+    post,
+    path = "/admin/pools/{id}/backtest",
+    tag = "Admin",
+    summary = "Backtest pool parameters",
+    description = "Rebuilds the pool as it stood at `from`, once with its own parameters and once with the overridden `fee_bps`, `amplification` (hybrid), or `tick_spacing` (CLMM), then replays the swaps recorded up to `to` against both sandboxes. Reports each run's executed and rejected swaps, volume, fees per token, and LP PnL against holding. Deposits and withdrawals in the window are not replayed. Live pools are not modified.",
+    params(("id" = uuid::Uuid, Path, description = "Pool UUID")),
+    request_body = BacktestRequest,
+    responses(
+        (status = 200, description = "Backtest completed", body = BacktestResponse),
+        (status = 400, description = "Invalid window or overrides", body = ErrorResponse),
+        (status = 503, description = "Persistence unavailable", body = ErrorResponse),
+    )
+)]
+pub async fn backtest_pool(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    Json(req): Json<BacktestRequest>,
+) -> Result<Json<BacktestResponse>, GatewayError> {
+    let persistence = state
+        .persistence
+        .as_ref()
+        .ok_or(GatewayError::PersistenceUnavailable)?;
+    let pool_id = PoolId::from_uuid(id);
+    let to = replay_cutoff(Some(req.from), req.to)?;
+    let result = backtest::run(persistence, pool_id, req.from, to, req.overrides()).await?;
+    Ok(Json(BacktestResponse::new(pool_id, req.from, to, result)))
+}
+
+/// `PUT /admin/pools/{id}/rewards` — Set a pool's emission schedule.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist, or
+/// [`GatewayError::InvalidRequest`] for an empty reward token, a rate
+/// that is not a positive integer, or an end before the start.
+#[utoipa::path(
+    put,
+    path = "/admin/pools/{id}/rewards",
+    tag = "Admin",
+    summary = "Set an emission schedule",
+    description = "Emits `rate_per_second` units of `reward_token` between `starts_at` (default now) and `ends_at` (open-ended if omitted), shared between the pool's positions in proportion to their liquidity over time. Replaces any previous schedule; rewards accrued under it stay claimable. Schedules and accrued rewards are held in memory.",
+    params(("id" = uuid::Uuid, Path, description = "Pool UUID")),
+    request_body = SetEmissionScheduleRequest,
+    responses(
+        (status = 200, description = "Schedule set", body = EmissionScheduleDto),
+        (status = 400, description = "Invalid schedule", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+    )
+)]
+pub async fn set_reward_schedule(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    Json(req): Json<SetEmissionScheduleRequest>,
+) -> Result<Json<EmissionScheduleDto>, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    state.pool_service.registry().get(pool_id).await?;
+    if req.reward_token.trim().is_empty() {
+        return Err(GatewayError::InvalidRequest(
+            "reward_token must not be empty".to_string(),
+        ));
+    }
+    let rate_per_second = req
+        .rate_per_second
+        .parse::<u128>()
+        .ok()
+        .filter(|rate| *rate > 0)
+        .ok_or_else(|| {
+            GatewayError::InvalidRequest(format!(
+                "rate_per_second must be a positive integer, got {:?}",
+                req.rate_per_second
+            ))
+        })?;
+    let now = Utc::now();
+    let starts_at = req.starts_at.unwrap_or(now);
+    if req.ends_at.is_some_and(|end| end <= starts_at) {
+        return Err(GatewayError::InvalidRequest(
+            "ends_at must be after starts_at".to_string(),
+        ));
+    }
+    let schedule = EmissionSchedule {
+        reward_token: req.reward_token,
+        rate_per_second,
+        starts_at,
+        ends_at: req.ends_at,
+    };
+    state
+        .rewards
+        .set_schedule(pool_id, Some(schedule.clone()), now);
+    Ok(Json(EmissionScheduleDto::new(pool_id, schedule)))
+}
+
+/// `DELETE /admin/pools/{id}/rewards` — Stop a pool's emissions.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist.
+#[utoipa::path(
+    delete,
+    path = "/admin/pools/{id}/rewards",
+    tag = "Admin",
+    summary = "Clear an emission schedule",
+    description = "Stops the pool's emissions from now. Rewards already accrued stay claimable.",
+    params(("id" = uuid::Uuid, Path, description = "Pool UUID")),
+    responses(
+        (status = 204, description = "Schedule cleared"),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+    )
+)]
+pub async fn clear_reward_schedule(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<StatusCode, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    state.pool_service.registry().get(pool_id).await?;
+    state.rewards.set_schedule(pool_id, None, Utc::now());
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `GET /admin/rewards` — Configured emission schedules.
+#[utoipa::path(
+    get,
+    path = "/admin/rewards",
+    tag = "Admin",
+    summary = "List emission schedules",
+    description = "Lists every pool's liquidity-mining emission schedule, including ones that have ended.",
+    responses(
+        (status = 200, description = "Emission schedules", body = EmissionScheduleListResponse),
+    )
+)]
+pub async fn list_reward_schedules(
+    State(state): State<AppState>,
+) -> Json<EmissionScheduleListResponse> {
+    let schedules = state
+        .rewards
+        .schedules()
+        .into_iter()
+        .map(|(pool_id, schedule)| EmissionScheduleDto::new(pool_id, schedule))
+        .collect();
+    Json(EmissionScheduleListResponse { schedules })
+}
+
+/// `GET /admin/ws/connections` — Live WebSocket connections.
+#[utoipa::path(
+    get,
+    path = "/admin/ws/connections",
+    tag = "Admin",
+    summary = "WebSocket connections",
+    description = "Lists this instance's live WebSocket connections with their outbound queues: event frames waiting to be written, the queue capacity (`WS_OUTBOUND_QUEUE`), the overflow policy, and how many events were dropped because the client read too slowly.",
+    responses(
+        (status = 200, description = "Live connections", body = WsConnectionListResponse),
+    )
+)]
+pub async fn ws_connections(State(state): State<AppState>) -> Json<WsConnectionListResponse> {
+    let connections: Vec<WsConnectionDto> = state
+        .ws_connections
+        .list()
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    let dropped_events = connections
+        .iter()
+        .fold(0u64, |sum, c| sum.saturating_add(c.dropped_events));
+    Json(WsConnectionListResponse {
+        connections,
+        dropped_events,
+    })
+}
+
+/// `GET /admin/pools/stale` — Pools that qualify for archiving.
+#[utoipa::path(
+    get,
+    path = "/admin/pools/stale",
+    tag = "Admin",
+    summary = "List stale pools",
+    description = "Lists unarchived pools that hold no liquidity and have not changed for `idle_days` (default `STALE_POOL_DAYS`), idle longest first, for review before archiving. With `STALE_POOL_AUTO_ARCHIVE` enabled, pools stale for `STALE_POOL_DAYS` are archived hourly: each emits a `PoolArchived` event and rejects further operations with 409 (code 2007), but stays readable.",
+    params(StalePoolParams),
+    responses(
+        (status = 200, description = "Stale pools", body = StalePoolListResponse),
+    )
+)]
+pub async fn stale_pools(
+    State(state): State<AppState>,
+    Query(params): Query<StalePoolParams>,
+) -> Json<StalePoolListResponse> {
+    let idle_days = params.idle_days.unwrap_or(state.stale_pools.idle_days);
+    let pools = StalePoolPolicy::candidates(&state.pool_service, Utc::now(), idle_days).await;
+    Json(StalePoolListResponse {
+        idle_days,
+        auto_archive: state.stale_pools.auto_archive,
+        data: pools.into_iter().map(StalePoolDto::from).collect(),
+    })
+}
+
+/// `GET /admin/events` — Page through the persisted event log.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] for a `limit` outside
+/// `1..=1000`, a negative `after`, or an unknown `event_type`,
+/// [`GatewayError::PersistenceUnavailable`] when persistence is disabled,
+/// or [`GatewayError::PersistenceError`] on database failure.
+#[utoipa::path(
+    get,
+    path = "/admin/events",
+    tag = "Admin",
+    summary = "Browse the event log",
+    description = "Lists persisted events in log order, optionally for one pool and one event type, to audit historical activity. Pages are cursor-based: start without `after` and pass each page's `next_after` to get the next one, until it is `null`. Payloads are upcast to the current schema. Compacted ranges are missing; list their checkpoints with `GET /admin/pools/{id}/checkpoints`.",
+    params(EventLogParams),
+    responses(
+        (status = 200, description = "One page of events", body = EventLogResponse),
+        (status = 400, description = "Invalid cursor, limit, or event type", body = ErrorResponse),
+        (status = 503, description = "Persistence unavailable", body = ErrorResponse),
+    )
+)]
+pub async fn list_events(
+    State(state): State<AppState>,
+    Query(params): Query<EventLogParams>,
+) -> Result<Json<EventLogResponse>, GatewayError> {
+    let limit = params.limit.unwrap_or(DEFAULT_EVENT_PAGE);
+    if !(1..=MAX_EVENT_PAGE).contains(&limit) {
+        return Err(GatewayError::InvalidRequest(format!(
+            "limit must be between 1 and {MAX_EVENT_PAGE}"
+        )));
+    }
+    let after = params.after.unwrap_or(0);
+    if after < 0 {
+        return Err(GatewayError::InvalidRequest(
+            "after must not be negative".to_string(),
+        ));
+    }
+    if let Some(event_type) = &params.event_type
+        && !EVENT_TYPES.contains(&event_type.as_str())
+    {
+        return Err(GatewayError::InvalidRequest(format!(
+            "unknown event type: {event_type}"
+        )));
+    }
+    let db = require_persistence(&state)?;
+    let events: Vec<ConsumerEventDto> = db
+        .load_event_page(
+            after,
+            params.pool_id.map(|id| *id.as_uuid()),
+            params.event_type.as_deref(),
+            limit,
+        )
+        .await?
+        .into_iter()
+        .map(ConsumerEventDto::from)
+        .collect();
+    let next_after = if events.len() == usize::try_from(limit).unwrap_or(usize::MAX) {
+        events.last().map(|e| e.event_id)
+    } else {
+        None
+    };
+    Ok(Json(EventLogResponse { events, next_after }))
+}
+
+/// Events listed by `GET /admin/events` when no `limit` is given.
+const DEFAULT_EVENT_PAGE: i64 = 100;
+/// Largest `limit` accepted by `GET /admin/events`.
+const MAX_EVENT_PAGE: i64 = 1_000;
+
+/// `GET /admin/events/tail` — Print recent events, optionally following new ones.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] for a `limit` outside
+/// `1..=1000` or an unknown `event_type`.
+#[utoipa::path(
+    get,
+    path = "/admin/events/tail",
+    tag = "Admin",
+    summary = "Tail the event stream",
+    description = "Streams the most recent events published on the event bus as newline-delimited JSON, oldest first, one event per line. With `follow=true` the response stays open and new events are appended as they happen, like `tail -f`; a `{\"lagged\": n}` line marks events dropped because the client read too slowly. Filter by `pool_id` and `event_type`.",
+    params(EventTailParams),
+    responses(
+        (status = 200, description = "Newline-delimited JSON events", content_type = "application/x-ndjson", body = String),
+        (status = 400, description = "Invalid limit or event type", body = ErrorResponse),
+    )
+)]
+pub async fn tail_events(
+    State(state): State<AppState>,
+    Query(params): Query<EventTailParams>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let limit = params.limit.unwrap_or(DEFAULT_TAIL_LIMIT);
+    if !(1..=TAIL_CAPACITY).contains(&limit) {
+        return Err(GatewayError::InvalidRequest(format!(
+            "limit must be between 1 and {TAIL_CAPACITY}"
+        )));
+    }
+    if let Some(event_type) = &params.event_type
+        && !EVENT_TYPES.contains(&event_type.as_str())
+    {
+        return Err(GatewayError::InvalidRequest(format!(
+            "unknown event type: {event_type}"
+        )));
+    }
+    let filter = TailFilter {
+        pool_id: params.pool_id,
+        event_type: params.event_type,
+    };
+    let (recent, live) = state.event_tail.follow(limit, &filter);
+    let recent = stream::iter(recent).map(|event| ndjson_line(&event));
+    let body = if params.follow {
+        Body::from_stream(recent.chain(follow_events(live, filter)))
+    } else {
+        Body::from_stream(recent)
+    };
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson"),
+            (header::CACHE_CONTROL, "no-cache"),
+        ],
+        body,
+    ))
+}
+
+/// Events printed by `GET /admin/events/tail` when no `limit` is given.
+const DEFAULT_TAIL_LIMIT: usize = 20;
+
+/// Lines for events recorded after the tail started, until the client
+/// disconnects or the bus closes.
+fn follow_events(
+    live: broadcast::Receiver<PoolEvent>,
+    filter: TailFilter,
+) -> impl futures_util::Stream<Item = Result<String, Infallible>> {
+    stream::unfold((live, filter), |(mut live, filter)| async move {
+        loop {
+            let line = match live.recv().await {
+                Ok(event) if filter.matches(&event) => ndjson_line(&event),
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    ndjson_line(&serde_json::json!({ "lagged": n }))
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            };
+            return Some((line, (live, filter)));
+        }
+    })
+}
+
+fn ndjson_line(value: &impl serde::Serialize) -> Result<String, Infallible> {
+    let mut line = serde_json::to_string(value).unwrap_or_default();
+    line.push('\n');
+    Ok(line)
+}
+
+fn require_persistence(state: &AppState) -> Result<PostgresPersistence, GatewayError> {
+    state
+        .persistence
+        .clone()
+        .ok_or(GatewayError::PersistenceUnavailable)
+}
+
+fn to_json(value: &impl serde::Serialize) -> Result<serde_json::Value, GatewayError> {
+    serde_json::to_value(value).map_err(|e| GatewayError::Internal(e.to_string()))
+}
+
+/// Snapshots every pool (of `pool_type`, when given). Programmatic pools
+/// without a creation config cannot be snapshotted and are skipped.
+async fn snapshot_all_job(
+    pool_service: &PoolService,
+    db: &PostgresPersistence,
+    pool_type: Option<&str>,
+    progress: &JobProgress,
+) -> Result<serde_json::Value, GatewayError> {
+    let filter = PoolFilter {
+        pool_type: pool_type.map(str::to_string),
+        ..PoolFilter::default()
+    };
+    let pools = pool_service.list_pools(&filter).await;
+    progress.set_total(pools.len() as u64);
+    let (mut saved, mut skipped, mut failed) = (0u64, 0u64, Vec::new());
+    for pool in pools {
+        progress.checkpoint()?;
+        if let Ok(entry_lock) = pool_service.registry().get(pool.pool_id).await {
+            let entry = entry_lock.read().await;
+            match snapshot::encode(&entry) {
+                Ok(parts) => {
+                    let pool_type = entry.pool_type.clone();
+                    drop(entry);
+                    let result = db
+                        .save_snapshot(
+                            *pool.pool_id.as_uuid(),
+                            &pool_type,
+                            &parts.config_json,
+                            &parts.state_json,
+                            &parts.metadata_json,
+                        )
+                        .await;
+                    match result {
+                        Ok(_) => saved = saved.saturating_add(1),
+                        Err(e) => failed.push(
+                            serde_json::json!({ "pool_id": pool.pool_id, "error": e.to_string() }),
+                        ),
+                    }
+                }
+                Err(_) => skipped = skipped.saturating_add(1),
+            }
+        }
+        progress.advance();
+    }
+    Ok(serde_json::json!({
+        "snapshots_saved": saved,
+        "skipped": skipped,
+        "failed": failed,
+    }))
+}
+
+/// Compacts `[from, to]` for each pool, or every registered pool when
+/// `pool_ids` is empty.
+async fn compaction_job(
+    pool_service: &PoolService,
+    db: &PostgresPersistence,
+    pool_ids: Vec<PoolId>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    progress: &JobProgress,
+) -> Result<serde_json::Value, GatewayError> {
+    let pool_ids = if pool_ids.is_empty() {
+        pool_service
+            .list_pools(&PoolFilter::default())
+            .await
+            .into_iter()
+            .map(|p| p.pool_id)
+            .collect()
+    } else {
+        pool_ids
+    };
+    progress.set_total(pool_ids.len() as u64);
+    let mut events_deleted = 0u64;
+    let mut pools = Vec::with_capacity(pool_ids.len());
+    for pool_id in pool_ids {
+        progress.checkpoint()?;
+        match db.compact_events(*pool_id.as_uuid(), from, to).await {
+            Ok(outcome) => {
+                events_deleted = events_deleted.saturating_add(outcome.events_deleted);
+                pools.push(serde_json::json!({
+                    "pool_id": pool_id,
+                    "events_deleted": outcome.events_deleted,
+                    "checkpoint_id": outcome.checkpoint_id,
+                }));
+            }
+            Err(e) => pools.push(serde_json::json!({ "pool_id": pool_id, "error": e.to_string() })),
+        }
+        progress.advance();
+    }
+    Ok(serde_json::json!({ "events_deleted": events_deleted, "pools": pools }))
+}
+
+/// Deletes each pool, archiving a final snapshot when persistence is
+/// enabled.
+async fn bulk_delete_job(
+    pool_service: &PoolService,
+    db: Option<&PostgresPersistence>,
+    pool_ids: &[PoolId],
+    force: bool,
+    progress: &JobProgress,
+) -> Result<serde_json::Value, GatewayError> {
+    progress.set_total(pool_ids.len() as u64);
+    let (mut deleted, mut failed) = (Vec::new(), Vec::new());
+    for pool_id in pool_ids {
+        progress.checkpoint()?;
+        match pool_service
+            .delete_pool(*pool_id, force, db, Manager::Operator)
+            .await
+        {
+            Ok(pool) => deleted.push(serde_json::json!({
+                "pool_id": pool_id,
+                "archived_snapshot_id": pool.archived_snapshot_id,
+            })),
+            Err(e) => {
+                failed.push(serde_json::json!({ "pool_id": pool_id, "error": e.to_string() }))
+            }
+        }
+        progress.advance();
+    }
+    Ok(serde_json::json!({ "deleted": deleted, "failed": failed }))
+}
+
+/// Admin routes, mounted at the root alongside system endpoints.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/replay", post(replay_events))
+        .route("/admin/startup-report", get(startup_report))
+        .route("/admin/events/compact", post(compact_events))
+        .route("/admin/events", get(list_events))
+        .route("/admin/events/tail", get(tail_events))
+        .route("/admin/pools/{id}/events/import", post(import_events))
+        .route("/admin/pools/stale", get(stale_pools))
+        .route("/admin/pools/{id}/contention", get(pool_contention))
+        .route(
+            "/admin/pools/{id}/canary",
+            put(set_canary).delete(promote_canary),
+        )
+        .route("/admin/pools/{id}/pause", post(pause_pool))
+        .route("/admin/pools/{id}/resume", post(resume_pool))
+        .route("/admin/quotes", get(list_quotes))
+        .route("/admin/pools/{id}/snapshots", get(list_snapshots))
+        .route("/admin/pools/{id}/checkpoints", get(list_checkpoints))
+        .route("/admin/pools/{id}/restore", post(restore_pool))
+        .route("/admin/pools/{id}/backtest", post(backtest_pool))
+        .route(
+            "/admin/pools/{id}/rewards",
+            put(set_reward_schedule).delete(clear_reward_schedule),
+        )
+        .route("/admin/rewards", get(list_reward_schedules))
+        .route(
+            "/admin/rate-limits",
+            get(list_rate_limits).put(update_rate_limit),
+        )
+        .route("/admin/usage", get(list_usage))
+        .route("/admin/metadata-schemas", get(list_metadata_schemas))
+        .route(
+            "/admin/metadata-schemas/{scope}/{name}",
+            put(update_metadata_schema).delete(delete_metadata_schema),
+        )
+        .route("/admin/stats", get(capacity_stats))
+        .route("/admin/ws/connections", get(ws_connections))
+        .route("/admin/jobs", post(start_job).get(list_jobs))
+        .route("/admin/jobs/{id}", get(get_job))
+        .route("/admin/jobs/{id}/cancel", post(cancel_job))
+}
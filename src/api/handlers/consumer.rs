@@ -0,0 +1,269 @@
+//! Event consumer handlers: durable, named cursors over the event log.
+//!
+//! A consumer polls `next` for the events after its last acknowledged ID
+//! and acknowledges the batch once processed. Polling does not move the
+//! cursor, so a consumer that crashes before acking sees the same events
+//! again (at-least-once delivery).
+
+use axum::extract::{Path, Query, State};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+
+use crate::api::dto::{
+    ConsumerAckRequest, ConsumerAckResponse, ConsumerBatchResponse, ConsumerEventDto,
+    ConsumerNextParams,
+};
+use crate::app_state::AppState;
+use crate::error::{ErrorResponse, GatewayError};
+use crate::persistence::postgres::PostgresPersistence;
+
+/// Events returned when no `limit` is given.
+const DEFAULT_BATCH_LIMIT: i64 = 100;
+
+/// Largest accepted batch.
+const MAX_BATCH_LIMIT: i64 = 1_000;
+
+/// Longest accepted consumer name.
+const MAX_NAME_LEN: usize = 64;
+
+/// Accepts names of 1–64 ASCII letters, digits, `-`, `_`, or `.`.
+fn validate_name(name: &str) -> Result<(), GatewayError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(GatewayError::InvalidRequest(format!(
+            "consumer name must be 1-{MAX_NAME_LEN} characters of [A-Za-z0-9._-]"
+        )))
+    }
+}
+
+/// `GET /consumers/:name/next` — Events after the consumer's cursor.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] for a bad name or limit,
+/// [`GatewayError::PersistenceUnavailable`] when persistence is disabled,
+/// or [`GatewayError::PersistenceError`] on database failure.
+#[utoipa::path(
+    get,
+    path = "/api/v1/consumers/{name}/next",
+    tag = "Consumers",
+    summary = "Next events for a consumer",
+    description = "Returns up to `limit` events from the event log after the last ID acknowledged by consumer `name`, in log order. Reading does not move the cursor: acknowledge `next_ack` once the batch is processed, or the same events are returned again. A new consumer starts at the beginning of the log. Compacted ranges are skipped; their checkpoints are listed by `GET /admin/pools/{id}/checkpoints`.",
+    params(
+        ("name" = String, Path, description = "Consumer name"),
+        ConsumerNextParams,
+    ),
+    responses(
+        (status = 200, description = "Next batch of events", body = ConsumerBatchResponse),
+        (status = 400, description = "Invalid name or limit", body = ErrorResponse),
+        (status = 503, description = "Persistence unavailable", body = ErrorResponse),
+    )
+)]
+pub async fn consumer_next(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<ConsumerNextParams>,
+) -> Result<impl IntoResponse, GatewayError> {
+    validate_name(&name)?;
+    let limit = batch_limit(params.limit)?;
+    let persistence = state
+        .persistence
+        .as_ref()
+        .ok_or(GatewayError::PersistenceUnavailable)?;
+    Ok(Json(next_batch(persistence, name, limit).await?))
+}
+
+/// Accepts a batch limit of 1–1000, defaulting to 100.
+fn batch_limit(limit: Option<i64>) -> Result<i64, GatewayError> {
+    let limit = limit.unwrap_or(DEFAULT_BATCH_LIMIT);
+    if !(1..=MAX_BATCH_LIMIT).contains(&limit) {
+        return Err(GatewayError::InvalidRequest(format!(
+            "limit must be between 1 and {MAX_BATCH_LIMIT}"
+        )));
+    }
+    Ok(limit)
+}
+
+/// Loads up to `limit` events after consumer `name`'s cursor.
+async fn next_batch(
+    persistence: &PostgresPersistence,
+    name: String,
+    limit: i64,
+) -> Result<ConsumerBatchResponse, GatewayError> {
+    let acked_event_id = persistence.load_consumer_offset(&name).await?.unwrap_or(0);
+    let events: Vec<ConsumerEventDto> = persistence
+        .load_events_after_id(acked_event_id, limit)
+        .await?
+        .into_iter()
+        .map(ConsumerEventDto::from)
+        .collect();
+    let next_ack = events.last().map(|e| e.event_id);
+    Ok(ConsumerBatchResponse {
+        consumer: name,
+        acked_event_id,
+        events,
+        next_ack,
+    })
+}
+
+/// `POST /consumers/:name/ack` — Advance the consumer's cursor.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] for a bad name or an ID that
+/// is negative or past the newest event,
+/// [`GatewayError::PersistenceUnavailable`] when persistence is disabled,
+/// or [`GatewayError::PersistenceError`] on database failure.
+#[utoipa::path(
+    post,
+    path = "/api/v1/consumers/{name}/ack",
+    tag = "Consumers",
+    summary = "Acknowledge events",
+    description = "Marks every event up to and including `last_event_id` as processed by consumer `name`, creating the consumer on first use. The cursor only moves forward: acknowledging an older ID is a no-op, so retried acks are safe.",
+    params(("name" = String, Path, description = "Consumer name")),
+    request_body = ConsumerAckRequest,
+    responses(
+        (status = 200, description = "Cursor after the ack", body = ConsumerAckResponse),
+        (status = 400, description = "Invalid name or event ID", body = ErrorResponse),
+        (status = 503, description = "Persistence unavailable", body = ErrorResponse),
+    )
+)]
+pub async fn consumer_ack(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<ConsumerAckRequest>,
+) -> Result<impl IntoResponse, GatewayError> {
+    validate_name(&name)?;
+    let persistence = state
+        .persistence
+        .as_ref()
+        .ok_or(GatewayError::PersistenceUnavailable)?;
+    Ok(Json(ack(persistence, name, req.last_event_id).await?))
+}
+
+/// Moves consumer `name`'s cursor forward to `last_event_id`, creating
+/// the consumer on its first ack.
+async fn ack(
+    persistence: &PostgresPersistence,
+    name: String,
+    last_event_id: i64,
+) -> Result<ConsumerAckResponse, GatewayError> {
+    let latest = persistence.latest_event_id().await?.unwrap_or(0);
+    if !(0..=latest).contains(&last_event_id) {
+        return Err(GatewayError::InvalidRequest(format!(
+            "last_event_id must be between 0 and the newest event id ({latest})"
+        )));
+    }
+    let acked_event_id = persistence
+        .ack_consumer_offset(&name, last_event_id)
+        .await?;
+    Ok(ConsumerAckResponse {
+        consumer: name,
+        acked_event_id,
+    })
+}
+
+/// Event consumer routes.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/consumers/{name}/next", get(consumer_next))
+        .route("/consumers/{name}/ack", post(consumer_ack))
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::PoolId;
+    use crate::persistence::postgres::testing;
+
+    #[test]
+    fn names_and_limits_are_validated() {
+        assert!(validate_name("orders-sync.v2_1").is_ok());
+        let too_long = "x".repeat(MAX_NAME_LEN + 1);
+        for name in ["", "has space", "a/b", "é", too_long.as_str()] {
+            assert!(
+                matches!(validate_name(name), Err(GatewayError::InvalidRequest(_))),
+                "{name:?} accepted"
+            );
+        }
+        assert_eq!(batch_limit(None).ok(), Some(DEFAULT_BATCH_LIMIT));
+        assert_eq!(
+            batch_limit(Some(MAX_BATCH_LIMIT)).ok(),
+            Some(MAX_BATCH_LIMIT)
+        );
+        assert!(batch_limit(Some(0)).is_err());
+        assert!(batch_limit(Some(MAX_BATCH_LIMIT + 1)).is_err());
+    }
+
+    #[tokio::test]
+    async fn cursors_start_at_the_log_and_only_move_forward() {
+        let Some(db) = testing::persistence().await else {
+            return;
+        };
+        let name = format!("test-{}", uuid::Uuid::new_v4().simple());
+        let pool_id = PoolId::new();
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let Ok(id) = db
+                .save_event(*pool_id.as_uuid(), "pool_removed", &serde_json::json!({}))
+                .await
+            else {
+                panic!("event not saved");
+            };
+            ids.push(id);
+        }
+        let [first, second, third] = ids.as_slice() else {
+            panic!("expected three events");
+        };
+
+        // Unknown consumers read from the start of the log
+        let Ok(batch) = next_batch(&db, name.clone(), MAX_BATCH_LIMIT).await else {
+            panic!("next failed");
+        };
+        assert_eq!(batch.acked_event_id, 0);
+        assert!(!batch.events.is_empty());
+        assert_eq!(batch.next_ack, batch.events.last().map(|e| e.event_id));
+
+        // The first ack registers the consumer
+        let Ok(acked) = ack(&db, name.clone(), *second).await else {
+            panic!("ack failed");
+        };
+        assert_eq!(acked.acked_event_id, *second);
+        let Ok(batch) = next_batch(&db, name.clone(), MAX_BATCH_LIMIT).await else {
+            panic!("next failed");
+        };
+        assert_eq!(batch.acked_event_id, *second);
+        let mine: Vec<i64> = batch
+            .events
+            .iter()
+            .filter(|e| e.pool_id == pool_id)
+            .map(|e| e.event_id)
+            .collect();
+        assert_eq!(mine, [*third]);
+        assert_eq!(batch.next_ack, batch.events.last().map(|e| e.event_id));
+
+        // Stale and repeated acks leave the cursor where it is
+        let Ok(stale) = ack(&db, name.clone(), *first).await else {
+            panic!("stale ack failed");
+        };
+        assert_eq!(stale.acked_event_id, *second);
+
+        for bad in [-1, third + 1_000_000] {
+            assert!(
+                matches!(
+                    ack(&db, name.clone(), bad).await,
+                    Err(GatewayError::InvalidRequest(_))
+                ),
+                "ack of {bad} accepted"
+            );
+        }
+    }
+}
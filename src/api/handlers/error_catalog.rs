@@ -0,0 +1,30 @@
+//! Error catalog handler.
+
+use axum::routing::get;
+use axum::{Json, Router};
+
+use crate::api::dto::ErrorCatalogResponse;
+use crate::app_state::AppState;
+use crate::error::ERROR_CATALOG;
+
+/// `GET /errors` — List every error code the gateway returns.
+#[utoipa::path(
+    get,
+    path = "/api/v1/errors",
+    tag = "System",
+    summary = "Error catalog",
+    description = "Returns every numeric error code with its name, HTTP status, message template, and whether retrying the same request later may succeed, so clients can map codes without hard-coding the table.",
+    responses(
+        (status = 200, description = "Error catalog, by code", body = ErrorCatalogResponse),
+    )
+)]
+pub async fn list_errors() -> Json<ErrorCatalogResponse> {
+    Json(ErrorCatalogResponse {
+        errors: ERROR_CATALOG.to_vec(),
+    })
+}
+
+/// Error catalog routes.
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/errors", get(list_errors))
+}
@@ -1,63 +1,97 @@
-//! Liquidity operation handlers: add, remove, collect fees.
+//! Liquidity operation handlers: add, remove, collect fees, and the
+//! positions resource.
 
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
 use axum::response::IntoResponse;
-use axum::routing::post;
+use axum::routing::{get, post};
 use axum::{Json, Router};
 use chrono::Utc;
-use hydra_amm::domain::{Amount, Liquidity, LiquidityChange};
+use hydra_amm::domain::{Amount, Liquidity};
 
+use crate::api::client_id::ClientId;
 use crate::api::dto::{
-    AddLiquidityRequest, AddLiquidityResponse, RemoveLiquidityRequest, RemoveLiquidityResponse,
+    AddLiquidityRequest, AddLiquidityResponse, ClosePositionResponse, CollectFeesRequest,
+    CollectFeesResponse, OpenPositionRequest, PositionListParams, PositionListResponse,
+    PositionResponse, ProviderListResponse, RemoveLiquidityRequest, RemoveLiquidityResponse,
+    UnitsParams,
 };
 use crate::app_state::AppState;
-use crate::domain::PoolId;
+use crate::domain::pool_operation::{TickRange, TokenSide};
+use crate::domain::{PoolId, PositionId};
 use crate::error::{ErrorResponse, GatewayError};
+use crate::service::pool_service::check_deadline;
 
 /// `POST /pools/:id/liquidity/add` — Add liquidity to a pool.
 ///
 /// # Errors
 ///
-/// Returns [`GatewayError`] on invalid amounts or missing pool.
+/// Returns [`GatewayError`] on invalid amounts or missing pool, and
+/// [`GatewayError::DeadlineExpired`] once `deadline` has passed.
 #[utoipa::path(
     post,
     path = "/api/v1/pools/{id}/liquidity/add",
     tag = "Liquidity",
     summary = "Add liquidity",
-    description = "Deposits tokens into the pool and mints LP shares.",
+    description = "Deposits tokens into the pool and mints LP shares. Opens a new position \
+                   (returned as `position_id`) unless an existing `position_id` is given. \
+                   Rejected with 422 (code 4006) once `deadline` has passed.",
     params(
         ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("x-client-id" = Option<String>, Header, description = "Calling client; recorded as the event actor"),
+        ("idempotency-key" = Option<String>, Header, description = "Retries with the same key within IDEMPOTENCY_TTL_SECS replay the first successful response instead of executing again"),
+        UnitsParams,
     ),
     request_body = AddLiquidityRequest,
     responses(
         (status = 200, description = "Liquidity added", body = AddLiquidityResponse),
         (status = 400, description = "Invalid request", body = ErrorResponse),
-        (status = 404, description = "Pool not found", body = ErrorResponse),
+        (status = 404, description = "Pool or position not found", body = ErrorResponse),
+        (status = 422, description = "Per-pool position limit reached or deadline passed", body = ErrorResponse),
     )
 )]
 pub async fn add_liquidity(
     State(state): State<AppState>,
     Path(id): Path<uuid::Uuid>,
+    Query(units): Query<UnitsParams>,
+    client: ClientId,
     Json(req): Json<AddLiquidityRequest>,
 ) -> Result<impl IntoResponse, GatewayError> {
     let pool_id = PoolId::from_uuid(id);
+    check_deadline(req.deadline, Utc::now())?;
 
-    let amount_a: u128 = req
-        .amount_a
-        .parse()
-        .map_err(|_| GatewayError::InvalidRequest(format!("invalid amount_a: {}", req.amount_a)))?;
-    let amount_b: u128 = req
-        .amount_b
-        .parse()
-        .map_err(|_| GatewayError::InvalidRequest(format!("invalid amount_b: {}", req.amount_b)))?;
+    let amount_a = parse_amount("amount_a", &req.amount_a)?;
+    let amount_b = parse_amount("amount_b", &req.amount_b)?;
+    let range = tick_range(req.lower_tick, req.upper_tick)?;
 
-    let change = LiquidityChange::add(Amount::new(amount_a), Amount::new(amount_b))?;
-    let minted = state.pool_service.add_liquidity(pool_id, &change).await?;
+    let (position_id, minted) = state
+        .pool_service
+        .add_liquidity(
+            pool_id,
+            Amount::new(amount_a),
+            Amount::new(amount_b),
+            req.position_id,
+            range,
+            req.owner.as_deref(),
+            client.as_deref(),
+        )
+        .await?;
+
+    let entry_lock = state.pool_service.registry().get(pool_id).await?;
+    let entry = entry_lock.read().await;
+    let (decimals_a, decimals_b) = (
+        entry.decimals_of(TokenSide::First),
+        entry.decimals_of(TokenSide::Second),
+    );
+    drop(entry);
 
     Ok(Json(AddLiquidityResponse {
         pool_id,
+        position_id,
         amount_a_deposited: amount_a.to_string(),
         amount_b_deposited: amount_b.to_string(),
+        amount_a_deposited_decimal: units.decimal(amount_a, decimals_a),
+        amount_b_deposited_decimal: units.decimal(amount_b, decimals_b),
         liquidity_minted: minted.get().to_string(),
         executed_at: Utc::now(),
     }))
@@ -67,30 +101,41 @@ pub async fn add_liquidity(
 ///
 /// # Errors
 ///
-/// Returns [`GatewayError`] on invalid amounts, missing pool, or insufficient liquidity.
+/// Returns [`GatewayError`] on invalid amounts, missing pool, or insufficient liquidity,
+/// and [`GatewayError::DeadlineExpired`] once `deadline` has passed.
 #[utoipa::path(
     post,
     path = "/api/v1/pools/{id}/liquidity/remove",
     tag = "Liquidity",
     summary = "Remove liquidity",
-    description = "Burns LP shares and returns the underlying tokens.",
+    description = "Burns LP shares and returns the underlying tokens. Pools with tracked \
+                   reserves report the amount of each token withdrawn. A position with an \
+                   owner can only be withdrawn by that provider (`owner`, defaulting to \
+                   `x-client-id`), up to the shares it holds; a body `owner` must match \
+                   `x-client-id`, which is then required. Rejected with 422 (code 4006) \
+                   once `deadline` has passed.",
     params(
         ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("x-client-id" = Option<String>, Header, description = "Calling client; recorded as the event actor"),
+        ("idempotency-key" = Option<String>, Header, description = "Retries with the same key within IDEMPOTENCY_TTL_SECS replay the first successful response instead of executing again"),
     ),
     request_body = RemoveLiquidityRequest,
     responses(
         (status = 200, description = "Liquidity removed", body = RemoveLiquidityResponse),
         (status = 400, description = "Invalid request", body = ErrorResponse),
-        (status = 404, description = "Pool not found", body = ErrorResponse),
-        (status = 422, description = "Insufficient liquidity", body = ErrorResponse),
+        (status = 403, description = "Position belongs to another provider", body = ErrorResponse),
+        (status = 404, description = "Pool or position not found", body = ErrorResponse),
+        (status = 422, description = "Insufficient liquidity or deadline passed", body = ErrorResponse),
     )
 )]
 pub async fn remove_liquidity(
     State(state): State<AppState>,
     Path(id): Path<uuid::Uuid>,
+    client: ClientId,
     Json(req): Json<RemoveLiquidityRequest>,
 ) -> Result<impl IntoResponse, GatewayError> {
     let pool_id = PoolId::from_uuid(id);
+    check_deadline(req.deadline, Utc::now())?;
 
     let liq_amount: u128 = req.liquidity_amount.parse().map_err(|_| {
         GatewayError::InvalidRequest(format!(
@@ -99,23 +144,381 @@ pub async fn remove_liquidity(
         ))
     })?;
 
-    let change = LiquidityChange::remove(Liquidity::new(liq_amount))?;
-    let returned = state
+    check_withdrawer(req.owner.as_deref(), client.as_deref())?;
+
+    let (returned, amounts) = state
         .pool_service
-        .remove_liquidity(pool_id, &change)
+        .remove_liquidity(
+            pool_id,
+            req.position_id,
+            Liquidity::new(liq_amount),
+            req.owner.as_deref(),
+            client.as_deref(),
+        )
         .await?;
 
     Ok(Json(RemoveLiquidityResponse {
         pool_id,
+        position_id: req.position_id,
         amount_returned: returned.get().to_string(),
+        amount_a_returned: amounts.map(|[a, _]| a.to_string()),
+        amount_b_returned: amounts.map(|[_, b]| b.to_string()),
         liquidity_burned: liq_amount.to_string(),
         executed_at: Utc::now(),
     }))
 }
 
+/// `POST /pools/:id/fees/collect` — Collect fees accrued by a position.
+///
+/// # Errors
+///
+/// Returns [`GatewayError`] if the pool or position is not found, the
+/// request names the position both or neither way, or collection fails.
+#[utoipa::path(
+    post,
+    path = "/api/v1/pools/{id}/fees/collect",
+    tag = "Liquidity",
+    summary = "Collect fees",
+    description = "Collects the fees accrued by a position, named either by `position_id` or, \
+                   for CLMM clients that track positions by range, by `lower_tick` and \
+                   `upper_tick`: the caller's (`x-client-id`) position over that range. The \
+                   position is built from its stored tick range and liquidity. \
+                   `fee_token_a`/`fee_token_b` are the position's current share of the swap \
+                   fees charged in each token since it last collected.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("x-client-id" = Option<String>, Header, description = "Calling client; recorded as the event actor"),
+        ("idempotency-key" = Option<String>, Header, description = "Retries with the same key within IDEMPOTENCY_TTL_SECS replay the first successful response instead of executing again"),
+    ),
+    request_body = CollectFeesRequest,
+    responses(
+        (status = 200, description = "Fees collected", body = CollectFeesResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 403, description = "Position belongs to another provider", body = ErrorResponse),
+        (status = 404, description = "Pool or position not found", body = ErrorResponse),
+    )
+)]
+pub async fn collect_fees(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    client: ClientId,
+    Json(req): Json<CollectFeesRequest>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+
+    let range = tick_range(req.lower_tick, req.upper_tick)?;
+    let position_id = match (req.position_id, range) {
+        (Some(position_id), None) => position_id,
+        (None, Some(range)) => {
+            state
+                .pool_service
+                .find_position(pool_id, range, client.as_deref())
+                .await?
+        }
+        _ => {
+            return Err(GatewayError::InvalidRequest(
+                "give either position_id or lower_tick and upper_tick".to_string(),
+            ));
+        }
+    };
+
+    let (fees, [fee_a, fee_b]) = state
+        .pool_service
+        .collect_fees(pool_id, position_id, client.as_deref())
+        .await?;
+
+    Ok(Json(CollectFeesResponse {
+        pool_id,
+        position_id,
+        fees_collected: fees.get().to_string(),
+        fee_token_a: fee_a.to_string(),
+        fee_token_b: fee_b.to_string(),
+        collected_at: Utc::now(),
+    }))
+}
+
+/// `GET /pools/:id/positions/:position_id` — Get a liquidity position.
+///
+/// # Errors
+///
+/// Returns [`GatewayError`] if the pool or position is not found.
+#[utoipa::path(
+    get,
+    path = "/api/v1/pools/{id}/positions/{position_id}",
+    tag = "Liquidity",
+    summary = "Get position",
+    description = "Returns the position's owner, status, liquidity, and tick range.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("position_id" = uuid::Uuid, Path, description = "Position UUID"),
+    ),
+    responses(
+        (status = 200, description = "Position details", body = PositionResponse),
+        (status = 404, description = "Pool or position not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_position(
+    State(state): State<AppState>,
+    Path((id, position_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    let position_id = PositionId::from_uuid(position_id);
+
+    let position = state
+        .pool_service
+        .get_position(pool_id, position_id)
+        .await?;
+
+    Ok(Json(PositionResponse::new(pool_id, position_id, &position)))
+}
+
+/// `POST /pools/:id/positions` — Open a liquidity position.
+///
+/// # Errors
+///
+/// Returns [`GatewayError`] on invalid amounts or ticks or a missing
+/// pool, [`GatewayError::CapacityExceeded`] at the per-pool position
+/// limit, and [`GatewayError::DeadlineExpired`] once `deadline` has
+/// passed.
+#[utoipa::path(
+    post,
+    path = "/api/v1/pools/{id}/positions",
+    tag = "Liquidity",
+    summary = "Open position",
+    description = "Deposits tokens into a new position over `[lower_tick, upper_tick)` (the \
+                   full range when both are omitted). The calling client is recorded as the \
+                   position's owner. Rejected with 422 (code 4006) once `deadline` has passed.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("x-client-id" = Option<String>, Header, description = "Calling client; recorded as the position owner"),
+    ),
+    request_body = OpenPositionRequest,
+    responses(
+        (status = 201, description = "Position opened", body = PositionResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+        (status = 422, description = "Per-pool position limit reached or deadline passed", body = ErrorResponse),
+    )
+)]
+pub async fn open_position(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    client: ClientId,
+    Json(req): Json<OpenPositionRequest>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    check_deadline(req.deadline, Utc::now())?;
+
+    let amount_a = parse_amount("amount_a", &req.amount_a)?;
+    let amount_b = parse_amount("amount_b", &req.amount_b)?;
+    let range = tick_range(req.lower_tick, req.upper_tick)?;
+
+    let (position_id, _) = state
+        .pool_service
+        .add_liquidity(
+            pool_id,
+            Amount::new(amount_a),
+            Amount::new(amount_b),
+            None,
+            range,
+            None,
+            client.as_deref(),
+        )
+        .await?;
+    let position = state
+        .pool_service
+        .get_position(pool_id, position_id)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(PositionResponse::new(pool_id, position_id, &position)),
+    ))
+}
+
+/// `GET /pools/:id/positions` — List a pool's liquidity positions.
+///
+/// # Errors
+///
+/// Returns [`GatewayError`] if the pool is not found.
+#[utoipa::path(
+    get,
+    path = "/api/v1/pools/{id}/positions",
+    tag = "Liquidity",
+    summary = "List positions",
+    description = "Lists the pool's positions, open and closed, in id order. `owner` keeps \
+                   only the positions opened by that client id.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        PositionListParams,
+    ),
+    responses(
+        (status = 200, description = "Positions", body = PositionListResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+    )
+)]
+pub async fn list_positions(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    Query(params): Query<PositionListParams>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+
+    let positions = state
+        .pool_service
+        .list_positions(pool_id, params.owner.as_deref())
+        .await?;
+
+    Ok(Json(PositionListResponse {
+        positions: positions
+            .iter()
+            .map(|(position_id, position)| PositionResponse::new(pool_id, *position_id, position))
+            .collect(),
+    }))
+}
+
+/// `DELETE /pools/:id/positions/:position_id` — Close a liquidity position.
+///
+/// # Errors
+///
+/// Returns [`GatewayError`] if the pool or position is not found or
+/// the withdrawal fails.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/pools/{id}/positions/{position_id}",
+    tag = "Liquidity",
+    summary = "Close position",
+    description = "Collects the position's fees, then withdraws all the liquidity it still \
+                   holds. The position stays listed with status `closed`; closing it again \
+                   only collects fees.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("position_id" = uuid::Uuid, Path, description = "Position UUID"),
+        ("x-client-id" = Option<String>, Header, description = "Calling client; recorded as the event actor"),
+    ),
+    responses(
+        (status = 200, description = "Position closed", body = ClosePositionResponse),
+        (status = 403, description = "Position belongs to another provider", body = ErrorResponse),
+        (status = 404, description = "Pool or position not found", body = ErrorResponse),
+    )
+)]
+pub async fn close_position(
+    State(state): State<AppState>,
+    Path((id, position_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    client: ClientId,
+) -> Result<impl IntoResponse, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    let position_id = PositionId::from_uuid(position_id);
+
+    let closed = state
+        .pool_service
+        .close_position(pool_id, position_id, client.as_deref())
+        .await?;
+
+    Ok(Json(ClosePositionResponse::new(
+        pool_id,
+        position_id,
+        &closed,
+    )))
+}
+
+/// `GET /pools/:id/providers` — List a pool's liquidity providers.
+///
+/// # Errors
+///
+/// Returns [`GatewayError`] if the pool is not found.
+#[utoipa::path(
+    get,
+    path = "/api/v1/pools/{id}/providers",
+    tag = "Liquidity",
+    summary = "List providers",
+    description = "Lists the LP shares held by each position owner, largest holding first, \
+                   with their share of the pool's total liquidity. Liquidity without an owner \
+                   is reported as `unattributed_liquidity`.",
+    params(("id" = uuid::Uuid, Path, description = "Pool UUID")),
+    responses(
+        (status = 200, description = "Providers", body = ProviderListResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+    )
+)]
+pub async fn list_providers(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    let (ledger, total_liquidity) = state.pool_service.lp_holdings(pool_id).await?;
+    Ok(Json(ProviderListResponse::new(
+        pool_id,
+        &ledger,
+        total_liquidity,
+    )))
+}
+
+/// Parses a string-encoded u128 amount named `field`.
+fn parse_amount(field: &str, raw: &str) -> Result<u128, GatewayError> {
+    raw.parse()
+        .map_err(|_| GatewayError::InvalidRequest(format!("invalid {field}: {raw}")))
+}
+
+/// Builds the tick range from a request's optional ticks, which must be
+/// given together.
+/// Checks that a caller naming `owner` in a withdrawal is that owner.
+/// An anonymous caller cannot withdraw on anyone's behalf.
+fn check_withdrawer(owner: Option<&str>, caller: Option<&str>) -> Result<(), GatewayError> {
+    match (owner, caller) {
+        (Some(owner), Some(caller)) if owner != caller => Err(GatewayError::Forbidden(format!(
+            "client {caller} cannot withdraw as provider {owner}"
+        ))),
+        (Some(owner), None) => Err(GatewayError::Forbidden(format!(
+            "withdrawing as provider {owner} requires x-client-id"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn tick_range(lower: Option<i32>, upper: Option<i32>) -> Result<Option<TickRange>, GatewayError> {
+    match (lower, upper) {
+        (Some(lower), Some(upper)) => Ok(Some(TickRange { lower, upper })),
+        (None, None) => Ok(None),
+        _ => Err(GatewayError::InvalidRequest(
+            "lower_tick and upper_tick must be given together".to_string(),
+        )),
+    }
+}
+
 /// Liquidity routes.
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/pools/{id}/liquidity/add", post(add_liquidity))
         .route("/pools/{id}/liquidity/remove", post(remove_liquidity))
+        .route("/pools/{id}/fees/collect", post(collect_fees))
+        .route(
+            "/pools/{id}/positions",
+            get(list_positions).post(open_position),
+        )
+        .route(
+            "/pools/{id}/positions/{position_id}",
+            get(get_position).delete(close_position),
+        )
+        .route("/pools/{id}/providers", get(list_providers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn withdrawing_as_an_owner_requires_being_that_client() {
+        assert!(check_withdrawer(None, None).is_ok());
+        assert!(check_withdrawer(None, Some("desk-1")).is_ok());
+        assert!(check_withdrawer(Some("desk-1"), Some("desk-1")).is_ok());
+        assert!(matches!(
+            check_withdrawer(Some("desk-1"), Some("desk-2")),
+            Err(GatewayError::Forbidden(_))
+        ));
+        assert!(matches!(
+            check_withdrawer(Some("desk-1"), None),
+            Err(GatewayError::Forbidden(_))
+        ));
+    }
 }
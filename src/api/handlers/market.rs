@@ -0,0 +1,521 @@
+//! Market data handlers: best bid and offer, slippage curves, depth
+//! charts, volatility, candles, risk scores, and TVL and volume
+//! statistics.
+
+use axum::extract::{Path, Query, State};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{Duration, Utc};
+
+use crate::api::client_id::ClientId;
+use crate::api::dto::{
+    BboResponse, CandleDto, CandleParams, CandlesResponse, DepthChartParams, DepthChartResponse,
+    DepthLevelDto, PoolStatsResponse, RiskComponentDto, RiskResponse, SlippageCurveParams,
+    SlippageCurvePointDto, SlippageCurveResponse, VolatilityParams, VolatilityResponse,
+};
+use crate::app_state::AppState;
+use crate::domain::PoolEvent;
+use crate::domain::PoolId;
+use crate::error::{ErrorResponse, GatewayError};
+use crate::service::candles::{self, Timeframe};
+use crate::service::market_data::{
+    self, DEFAULT_CURVE_POINTS, DEFAULT_DEPTH_LEVELS, DEFAULT_DEPTH_RANGE_BPS, MAX_CURVE_POINTS,
+    MAX_DEPTH_LEVELS, MAX_DEPTH_RANGE_BPS,
+};
+use crate::service::pool_stats;
+use crate::service::risk::{self, RiskScore};
+use crate::service::volatility;
+
+/// `GET /pools/:id/bbo` — Best bid, best ask, mid price, and spread.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist.
+#[utoipa::path(
+    get,
+    path = "/api/v1/pools/{id}/bbo",
+    tag = "Market Data",
+    summary = "Best bid and offer",
+    description = "Returns the best bid, best ask, mid price, and spread in basis points of a pool. Order-book pools report their best resting orders (token B per token A). AMM pools report a synthetic quote from the slope of their curve at the current point: the spot price less the fee on the bid, grossed up by it on the ask, per the pool's price convention. Read straight from live state without quoting, so it is cheap to poll; `version` tells whether anything changed since the last poll.",
+    params(("id" = uuid::Uuid, Path, description = "Pool UUID")),
+    responses(
+        (status = 200, description = "Best bid and offer", body = BboResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+    )
+)]
+pub async fn pool_bbo(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<BboResponse>, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    let (bbo, [base_token, quote_token], version) = state.pool_service.bbo(pool_id).await?;
+    let price = |p: Option<f64>| p.map(|p| format!("{p}"));
+    Ok(Json(BboResponse {
+        pool_id,
+        source: bbo.source,
+        base_token,
+        quote_token,
+        best_bid: price(bbo.best_bid),
+        best_ask: price(bbo.best_ask),
+        mid_price: price(bbo.mid_price()),
+        spread_bps: bbo.spread_bps(),
+        version,
+    }))
+}
+
+/// `GET /pools/:id/slippage-curve` — Output and price impact by input size.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist and
+/// [`GatewayError::InvalidRequest`] on bad parameters.
+///
+/// Returns [`GatewayError::RateLimited`] or [`GatewayError::Overloaded`]
+/// when no concurrency slot frees up in time.
+#[utoipa::path(
+    get,
+    path = "/api/v1/pools/{id}/slippage-curve",
+    tag = "Market Data",
+    summary = "Slippage curve",
+    description = "Quotes exact-in swaps of `token_in` at `points` evenly spaced sizes up to `max_amount` on a sandbox copy of the pool, returning output and price impact per size for depth/impact charts. The pool is not modified.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        SlippageCurveParams,
+    ),
+    responses(
+        (status = 200, description = "Slippage curve", body = SlippageCurveResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+        (status = 429, description = "Too many concurrent requests from this client", body = ErrorResponse),
+        (status = 503, description = "Endpoint saturated", body = ErrorResponse),
+    )
+)]
+pub async fn slippage_curve(
+    State(state): State<AppState>,
+    client: ClientId,
+    Path(id): Path<uuid::Uuid>,
+    Query(params): Query<SlippageCurveParams>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let _permit = state
+        .heavy_limiter
+        .acquire("slippage_curve", client.as_deref())
+        .await?;
+    let pool_id = PoolId::from_uuid(id);
+    let points = params.points.unwrap_or(DEFAULT_CURVE_POINTS);
+    if !(1..=MAX_CURVE_POINTS).contains(&points) {
+        return Err(GatewayError::InvalidRequest(format!(
+            "points must be between 1 and {MAX_CURVE_POINTS}"
+        )));
+    }
+
+    let (seed, reserves) = state.pool_service.pool_seed(pool_id).await?;
+    let side = seed.side_of(&params.token_in).ok_or_else(|| {
+        GatewayError::InvalidRequest(format!("token_in {} not found in pool", params.token_in))
+    })?;
+    let max_amount: u128 = match &params.max_amount {
+        Some(max) => max
+            .parse()
+            .map_err(|_| GatewayError::InvalidRequest(format!("invalid max_amount: {max}")))?,
+        None => reserves
+            .as_ref()
+            .and_then(|r| r.get(side.index()).copied())
+            .ok_or_else(|| {
+                GatewayError::InvalidRequest(
+                    "max_amount is required for pools without tracked reserves".to_string(),
+                )
+            })?,
+    };
+    if max_amount == 0 {
+        return Err(GatewayError::InvalidRequest(
+            "max_amount must be positive".to_string(),
+        ));
+    }
+
+    let curve = state
+        .quote_runtime
+        .compute({
+            let seed = seed.clone();
+            move || market_data::slippage_curve(&seed, side, points, max_amount)
+        })
+        .await?;
+    let spot_price = curve
+        .first()
+        .and_then(|p| p.spot_price)
+        .map(|p| format!("{p}"));
+
+    Ok(Json(SlippageCurveResponse {
+        pool_id,
+        token_in: seed.label(side).to_string(),
+        token_out: seed.label(side.other()).to_string(),
+        spot_price,
+        points: curve
+            .iter()
+            .map(|p| SlippageCurvePointDto {
+                amount_in: p.amount_in.to_string(),
+                amount_out: p.amount_out.to_string(),
+                fee_charged: p.fee.to_string(),
+                execution_price: format!("{}", p.execution_price().unwrap_or(0.0)),
+                price_impact_bps: p.price_impact_bps,
+            })
+            .collect(),
+    }))
+}
+
+/// `GET /pools/:id/depth-chart` — Cumulative liquidity around the price.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist and
+/// [`GatewayError::InvalidRequest`] on bad parameters or if the pool
+/// cannot price.
+///
+/// Returns [`GatewayError::RateLimited`] or [`GatewayError::Overloaded`]
+/// when no concurrency slot frees up in time.
+#[utoipa::path(
+    get,
+    path = "/api/v1/pools/{id}/depth-chart",
+    tag = "Market Data",
+    summary = "Depth chart",
+    description = "Returns cumulative buy (asks) and sell (bids) liquidity available within ±`range_bps` of the current price, in `levels` steps per side. Depth is derived from the pool's own pricing on a sandbox copy — along the invariant for curve pools and across ticks for CLMM — so the pool is not modified.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        DepthChartParams,
+    ),
+    responses(
+        (status = 200, description = "Depth chart", body = DepthChartResponse),
+        (status = 400, description = "Invalid parameters or unpriceable pool", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+        (status = 429, description = "Too many concurrent requests from this client", body = ErrorResponse),
+        (status = 503, description = "Endpoint saturated", body = ErrorResponse),
+    )
+)]
+pub async fn depth_chart(
+    State(state): State<AppState>,
+    client: ClientId,
+    Path(id): Path<uuid::Uuid>,
+    Query(params): Query<DepthChartParams>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let _permit = state
+        .heavy_limiter
+        .acquire("depth_chart", client.as_deref())
+        .await?;
+    let pool_id = PoolId::from_uuid(id);
+    let range_bps = params.range_bps.unwrap_or(DEFAULT_DEPTH_RANGE_BPS);
+    if !(1..=MAX_DEPTH_RANGE_BPS).contains(&range_bps) {
+        return Err(GatewayError::InvalidRequest(format!(
+            "range_bps must be between 1 and {MAX_DEPTH_RANGE_BPS}"
+        )));
+    }
+    let levels = params.levels.unwrap_or(DEFAULT_DEPTH_LEVELS);
+    if !(1..=MAX_DEPTH_LEVELS).contains(&levels) {
+        return Err(GatewayError::InvalidRequest(format!(
+            "levels must be between 1 and {MAX_DEPTH_LEVELS}"
+        )));
+    }
+
+    let (seed, _) = state.pool_service.pool_seed(pool_id).await?;
+    // Bisection rebuilds the sandbox many times; keep it off the reactor.
+    let chart = state
+        .quote_runtime
+        .compute({
+            let seed = seed.clone();
+            move || market_data::depth_chart(&seed, range_bps, levels)
+        })
+        .await??;
+
+    Ok(Json(DepthChartResponse {
+        pool_id,
+        base_token: seed.label(chart.base).to_string(),
+        quote_token: seed.label(chart.base.other()).to_string(),
+        mid_price: format!("{}", chart.mid_price),
+        bids: chart.bids.iter().map(DepthLevelDto::from).collect(),
+        asks: chart.asks.iter().map(DepthLevelDto::from).collect(),
+    }))
+}
+
+/// `GET /pools/:id/volatility` — Realized volatility over a window.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] for a malformed window,
+/// [`GatewayError::PersistenceUnavailable`] when persistence is disabled,
+/// or [`GatewayError::PersistenceError`] on database failure.
+///
+/// Returns [`GatewayError::RateLimited`] or [`GatewayError::Overloaded`]
+/// when no concurrency slot frees up in time.
+#[utoipa::path(
+    get,
+    path = "/api/v1/pools/{id}/volatility",
+    tag = "Market Data",
+    summary = "Historical volatility",
+    description = "Computes realized volatility from the pool's persisted `price_updated` events within `window`: the standard deviation of log returns between updates, annualized by the observed update frequency. Compacted ranges contribute no observations.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        VolatilityParams,
+    ),
+    responses(
+        (status = 200, description = "Volatility statistics", body = VolatilityResponse),
+        (status = 400, description = "Invalid window", body = ErrorResponse),
+        (status = 429, description = "Too many concurrent requests from this client", body = ErrorResponse),
+        (status = 503, description = "Persistence unavailable or endpoint saturated", body = ErrorResponse),
+    )
+)]
+pub async fn pool_volatility(
+    State(state): State<AppState>,
+    client: ClientId,
+    Path(id): Path<uuid::Uuid>,
+    Query(params): Query<VolatilityParams>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let _permit = state
+        .heavy_limiter
+        .acquire("volatility", client.as_deref())
+        .await?;
+    let window = params
+        .window
+        .unwrap_or_else(|| volatility::DEFAULT_WINDOW.to_string());
+    let span = volatility::parse_window(&window)?;
+    let persistence = state
+        .persistence
+        .as_ref()
+        .ok_or(GatewayError::PersistenceUnavailable)?;
+
+    let to = Utc::now();
+    let from = to - span;
+    let series: Vec<_> = persistence
+        .load_pool_events_of_type(id, "price_updated", from)
+        .await?
+        .iter()
+        .filter_map(|stored| match stored.to_pool_event().ok()? {
+            PoolEvent::PriceUpdated {
+                new_price,
+                timestamp,
+                ..
+            } => Some((timestamp, new_price.parse::<f64>().ok()?)),
+            _ => None,
+        })
+        .collect();
+    let stats = volatility::realized(&series, span);
+
+    Ok(Json(VolatilityResponse {
+        pool_id: PoolId::from_uuid(id),
+        window,
+        from,
+        to,
+        samples: stats.as_ref().map_or(series.len(), |s| s.samples),
+        return_stddev: stats.as_ref().map(|s| format!("{}", s.return_stddev)),
+        annualized_volatility: stats.as_ref().map(|s| format!("{}", s.annualized)),
+    }))
+}
+
+/// Candles returned when the caller does not specify a limit.
+const DEFAULT_CANDLE_LIMIT: u32 = 500;
+
+/// Largest accepted candle limit.
+const MAX_CANDLE_LIMIT: u32 = 1_000;
+
+/// `GET /pools/:id/candles` — Pre-aggregated OHLCV candles.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] for an unknown timeframe, a
+/// limit out of range, or `from` not before `to`; or
+/// [`GatewayError::PersistenceError`] on database failure.
+#[utoipa::path(
+    get,
+    path = "/api/v1/pools/{id}/candles",
+    tag = "Market Data",
+    summary = "OHLCV candles",
+    description = "Returns candles aggregated from the pool's swaps and price changes, in quote per base per the pool's price convention. Recent candles are served from memory and are current to the last trade; older ones are read from the `candles` table when persistence is enabled. Buckets without trades or price changes are omitted.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        CandleParams,
+    ),
+    responses(
+        (status = 200, description = "Candles, oldest first", body = CandlesResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+    )
+)]
+pub async fn pool_candles(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    Query(params): Query<CandleParams>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let timeframe = Timeframe::parse(
+        params
+            .timeframe
+            .as_deref()
+            .unwrap_or(candles::DEFAULT_TIMEFRAME),
+    )?;
+    let limit = params.limit.unwrap_or(DEFAULT_CANDLE_LIMIT);
+    if !(1..=MAX_CANDLE_LIMIT).contains(&limit) {
+        return Err(GatewayError::InvalidRequest(format!(
+            "limit must be between 1 and {MAX_CANDLE_LIMIT}"
+        )));
+    }
+    let to = params.to.unwrap_or_else(Utc::now);
+    let from = params
+        .from
+        .unwrap_or_else(|| to - Duration::seconds(timeframe.seconds() * i64::from(limit)));
+    if from >= to {
+        return Err(GatewayError::InvalidRequest(
+            "from must be before to".to_string(),
+        ));
+    }
+    let pool_id = PoolId::from_uuid(id);
+
+    // Older buckets than the book holds come from the database
+    let mut rows = Vec::new();
+    let mut held_from = from;
+    if let Some(persistence) = state.persistence.as_ref() {
+        held_from = held_from.max(state.candles.covered_from(pool_id, timeframe));
+        if from < held_from {
+            rows = persistence
+                .load_candles(id, timeframe.as_str(), from, held_from.min(to), limit)
+                .await?;
+        }
+    }
+    let remaining = (limit as usize).saturating_sub(rows.len());
+    rows.extend(
+        state
+            .candles
+            .range(pool_id, timeframe, held_from, to, remaining),
+    );
+
+    Ok(Json(CandlesResponse {
+        pool_id,
+        timeframe: timeframe.as_str().to_string(),
+        candles: rows.iter().map(CandleDto::from).collect(),
+    }))
+}
+
+/// `GET /pools/:id/risk` — Composite risk score with its breakdown.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist.
+///
+/// Returns [`GatewayError::RateLimited`] or [`GatewayError::Overloaded`]
+/// when no concurrency slot frees up in time.
+#[utoipa::path(
+    get,
+    path = "/api/v1/pools/{id}/risk",
+    tag = "Market Data",
+    summary = "Pool risk score",
+    description = "Scores how risky it is to route flow through a pool, from 0 (safe) to 100 (risky), with a `low`/`medium`/`high` band and a breakdown per factor. `liquidity_depth` counts whole base tokens tradeable within ±2% of the current price on the thinner side (log scale, 10^6 tokens scores 0 and one token 100). `volatility` is the annualized realized volatility of the last 24 hours of one-minute candle closes (200% scores 100). `concentration` (CLMM) is the Herfindahl index of open positions' liquidity (a single provider scores 100). `oracle_staleness` (dynamic) is the time since the oracle price was set (an hour scores 100). The composite is the weighted mean of the factors that apply and could be measured.",
+    params(("id" = uuid::Uuid, Path, description = "Pool UUID")),
+    responses(
+        (status = 200, description = "Risk score", body = RiskResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+        (status = 429, description = "Too many concurrent requests from this client", body = ErrorResponse),
+        (status = 503, description = "Endpoint saturated", body = ErrorResponse),
+    )
+)]
+pub async fn pool_risk(
+    State(state): State<AppState>,
+    client: ClientId,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<RiskResponse>, GatewayError> {
+    let _permit = state
+        .heavy_limiter
+        .acquire("risk", client.as_deref())
+        .await?;
+    let pool_id = PoolId::from_uuid(id);
+    let now = Utc::now();
+    let mut inputs = state.pool_service.risk_inputs(pool_id, now).await?;
+
+    // Programmatic pools cannot be sandboxed and are scored without depth;
+    // a pool that cannot price has none.
+    if let Ok((seed, _)) = state.pool_service.pool_seed(pool_id).await {
+        let chart = state
+            .quote_runtime
+            .compute({
+                let seed = seed.clone();
+                move || market_data::depth_chart(&seed, risk::DEPTH_RANGE_BPS, 1)
+            })
+            .await?;
+        inputs.depth_tokens = Some(chart.map_or(0.0, |chart| {
+            risk::depth_tokens(&chart, seed.trade_decimals(chart.base).input)
+        }));
+    }
+    let candles = state.candles.range(
+        pool_id,
+        Timeframe::OneMinute,
+        now - Duration::hours(risk::VOLATILITY_WINDOW_HOURS),
+        now,
+        usize::MAX,
+    );
+    inputs.volatility = risk::candle_volatility(&candles);
+
+    let score = RiskScore::of(&inputs);
+    Ok(Json(RiskResponse {
+        pool_id,
+        score: score.score,
+        level: score.level,
+        components: score
+            .components
+            .iter()
+            .map(RiskComponentDto::from)
+            .collect(),
+        computed_at: now,
+    }))
+}
+
+/// `GET /pools/:id/stats` — TVL, volume, fees, and trader counts.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist.
+#[utoipa::path(
+    get,
+    path = "/api/v1/pools/{id}/stats",
+    tag = "Market Data",
+    summary = "Pool statistics",
+    description = "Returns a pool's total value locked with its trailing 24-hour and 7-day quote volume, 24-hour fee revenue net of rebates, 24-hour and lifetime swap counts, and distinct clients that swapped in the last 24 hours. Amounts are raw units of the quote token per the pool's price convention. Windows are whole UTC hours ending with the current one. TVL is `null` for pools whose reserves the gateway does not track. The windows are rebuilt from the event log at startup when `PERSISTENCE_POOL_STATS_BACKFILL` is set.",
+    params(("id" = uuid::Uuid, Path, description = "Pool UUID")),
+    responses(
+        (status = 200, description = "Pool statistics", body = PoolStatsResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+    )
+)]
+pub async fn pool_stats(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<PoolStatsResponse>, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    let entry_lock = state.pool_service.registry().get(pool_id).await?;
+    let (quote_token, tvl, swap_count) = {
+        let entry = entry_lock.read().await;
+        (
+            entry.token_label(entry.price_base.other()),
+            pool_stats::tvl(&entry),
+            entry.swap_count,
+        )
+    };
+    let now = Utc::now();
+    let activity = state.pool_stats.activity(pool_id, now);
+    Ok(Json(PoolStatsResponse {
+        pool_id,
+        quote_token,
+        tvl: tvl.map(|v| v.to_string()),
+        volume_24h: activity.volume_24h.to_string(),
+        volume_7d: activity.volume_7d.to_string(),
+        fees_24h: activity.fees_24h.to_string(),
+        swap_count_24h: activity.swaps_24h,
+        swap_count,
+        unique_traders_24h: activity.unique_traders_24h,
+        computed_at: now,
+    }))
+}
+
+/// Market data routes.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/pools/{id}/bbo", get(pool_bbo))
+        .route("/pools/{id}/slippage-curve", get(slippage_curve))
+        .route("/pools/{id}/depth-chart", get(depth_chart))
+        .route("/pools/{id}/volatility", get(pool_volatility))
+        .route("/pools/{id}/candles", get(pool_candles))
+        .route("/pools/{id}/risk", get(pool_risk))
+        .route("/pools/{id}/stats", get(pool_stats))
+}
@@ -1,9 +1,19 @@
 //! REST endpoint handlers organized by resource.
 
+pub mod admin;
+pub mod consumer;
+pub mod error_catalog;
 pub mod liquidity;
+pub mod market;
+pub mod orderbook;
 pub mod pool;
+pub mod rewards;
 pub mod swap;
 pub mod system;
+pub mod token;
+pub mod trade;
+pub mod usage;
+pub mod webhook;
 
 use axum::Router;
 
@@ -15,4 +25,13 @@ pub fn routes() -> Router<AppState> {
         .merge(pool::routes())
         .merge(swap::routes())
         .merge(liquidity::routes())
+        .merge(rewards::routes())
+        .merge(usage::routes())
+        .merge(market::routes())
+        .merge(orderbook::routes())
+        .merge(token::routes())
+        .merge(trade::routes())
+        .merge(consumer::routes())
+        .merge(webhook::routes())
+        .merge(error_catalog::routes())
 }
@@ -0,0 +1,158 @@
+//! Order book handlers: placing and cancelling limit orders on
+//! `orderbook` pools, and depth snapshots of their books.
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+
+use crate::api::client_id::ClientId;
+use crate::api::dto::{OrderBookParams, OrderBookResponse, OrderResponse, PlaceOrderRequest};
+use crate::app_state::AppState;
+use crate::domain::order_book::{DEFAULT_BOOK_LEVELS, MAX_BOOK_LEVELS};
+use crate::domain::{OrderId, PoolId};
+use crate::error::{ErrorResponse, GatewayError};
+
+/// `POST /pools/:id/orders` — Place a limit order.
+///
+/// # Errors
+///
+/// Returns [`GatewayError`] if the pool is not found or is not an
+/// order-book pool, or the order is invalid or would cross the book.
+#[utoipa::path(
+    post,
+    path = "/api/v1/pools/{id}/orders",
+    tag = "Order Book",
+    summary = "Place limit order",
+    description = "Rests a post-only limit order on an `orderbook` pool and emits an \
+                   `OrderPlaced` event. The price must be a multiple of the pool's \
+                   `tick_size` and the quantity of its `lot_size`; an order that would cross \
+                   the opposite side of the book is rejected.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("x-client-id" = Option<String>, Header, description = "Calling client; recorded as the order owner"),
+    ),
+    request_body = PlaceOrderRequest,
+    responses(
+        (status = 201, description = "Order placed", body = OrderResponse),
+        (status = 400, description = "Invalid order or not an order-book pool", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+    )
+)]
+pub async fn place_order(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    client: ClientId,
+    Json(req): Json<PlaceOrderRequest>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    let price = parse_amount("price", &req.price)?;
+    let quantity = parse_amount("quantity", &req.quantity)?;
+
+    let (order_id, order) = state
+        .pool_service
+        .place_order(pool_id, req.side, price, quantity, client.as_deref())
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(OrderResponse::new(pool_id, order_id, order)),
+    ))
+}
+
+/// `DELETE /pools/:id/orders/:order_id` — Cancel a resting limit order.
+///
+/// # Errors
+///
+/// Returns [`GatewayError`] if the pool or order is not found.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/pools/{id}/orders/{order_id}",
+    tag = "Order Book",
+    summary = "Cancel limit order",
+    description = "Removes a resting order from the book and emits an `OrderCancelled` event. \
+                   Returns the order as it rested.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("order_id" = uuid::Uuid, Path, description = "Order UUID"),
+        ("x-client-id" = Option<String>, Header, description = "Calling client; recorded as the event actor"),
+    ),
+    responses(
+        (status = 200, description = "Order cancelled", body = OrderResponse),
+        (status = 404, description = "Pool or order not found", body = ErrorResponse),
+    )
+)]
+pub async fn cancel_order(
+    State(state): State<AppState>,
+    Path((id, order_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+    client: ClientId,
+) -> Result<impl IntoResponse, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    let order_id = OrderId::from_uuid(order_id);
+
+    let order = state
+        .pool_service
+        .cancel_order(pool_id, order_id, client.as_deref())
+        .await?;
+
+    Ok(Json(OrderResponse::new(pool_id, order_id, order)))
+}
+
+/// `GET /pools/:id/orderbook` — Depth snapshot of a pool's order book.
+///
+/// # Errors
+///
+/// Returns [`GatewayError`] if the pool is not found or is not an
+/// order-book pool, or `levels` is out of range.
+#[utoipa::path(
+    get,
+    path = "/api/v1/pools/{id}/orderbook",
+    tag = "Order Book",
+    summary = "Order book depth",
+    description = "Aggregates the resting orders of an `orderbook` pool by price: bids \
+                   highest first, asks lowest first, up to `levels` levels per side.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        OrderBookParams,
+    ),
+    responses(
+        (status = 200, description = "Order book snapshot", body = OrderBookResponse),
+        (status = 400, description = "Invalid levels or not an order-book pool", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_orderbook(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    Query(params): Query<OrderBookParams>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    let levels = params.levels.unwrap_or(DEFAULT_BOOK_LEVELS);
+    if !(1..=MAX_BOOK_LEVELS).contains(&levels) {
+        return Err(GatewayError::InvalidRequest(format!(
+            "levels must be between 1 and {MAX_BOOK_LEVELS}"
+        )));
+    }
+
+    let depth = state
+        .pool_service
+        .order_book(pool_id, usize::try_from(levels).unwrap_or(usize::MAX))
+        .await?;
+
+    Ok(Json(OrderBookResponse::new(pool_id, depth)))
+}
+
+/// Parses a string-encoded u128 amount named `field`.
+fn parse_amount(field: &str, raw: &str) -> Result<u128, GatewayError> {
+    raw.parse()
+        .map_err(|_| GatewayError::InvalidRequest(format!("invalid {field}: {raw}")))
+}
+
+/// Order book routes.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/pools/{id}/orders", post(place_order))
+        .route("/pools/{id}/orders/{order_id}", delete(cancel_order))
+        .route("/pools/{id}/orderbook", get(get_orderbook))
+}
@@ -1,120 +1,184 @@
-//! Pool CRUD handlers: create, list, get, delete.
+//! Pool CRUD handlers: create, list, get, update metadata, delete,
+//! transfer ownership, and push oracle prices to dynamic pools.
 
 use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
-use axum::response::IntoResponse;
-use axum::routing::{get, post};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
 use axum::{Json, Router};
 use chrono::Utc;
-use hydra_amm::config::{
-    AmmConfig, ClmmConfig, ConstantProductConfig, DynamicConfig, HybridConfig, OrderBookConfig,
-    WeightedConfig,
-};
-use hydra_amm::domain::{
-    Amount, BasisPoints, Decimals, FeeTier, Position, Price, Tick, Token, TokenAddress, TokenPair,
-};
 
+use crate::api::client_id::{ClientId, parse_client_id};
 use crate::api::dto::{
-    CreatePoolRequest, CreatePoolResponse, PaginationMeta, PaginationParams, PoolListResponse,
-    PoolSummaryDto,
+    ConfigIssueDto, CreatePoolRequest, CreatePoolResponse, DeletePoolParams, DeletedPoolResponse,
+    OraclePriceResponse, OwnershipResponse, PaginationParams, PoolDetailResponse, PoolListFilter,
+    PoolListResponse, PoolMetadataResponse, PoolSummaryDto, SetOraclePriceRequest,
+    TransferOwnershipRequest, UpdatePoolRequest, ValidatePoolResponse,
 };
 use crate::app_state::AppState;
+use crate::cluster::ClusterMembership;
+use crate::domain::PoolId;
+use crate::domain::pool_entry::PoolStatus;
 use crate::error::{ErrorResponse, GatewayError};
+use crate::service::ownership::Manager;
+use crate::service::pool_service;
 
 /// `POST /pools` — Create a new AMM pool.
 ///
 /// # Errors
 ///
-/// Returns [`GatewayError`] on invalid config or unsupported pool type.
+/// Returns [`GatewayError`] on invalid config or unsupported pool type,
+/// [`GatewayError::InvalidMetadata`] when `metadata` violates a registered
+/// schema, and [`GatewayError::PoolQuotaExceeded`] when the caller's pool
+/// quota is used up.
 #[utoipa::path(
     post,
     path = "/api/v1/pools",
     tag = "Pools",
     summary = "Create a new AMM pool",
-    description = "Creates a pool of the specified type with the given configuration. The `pool_type` field selects the AMM variant and `config` holds type-specific parameters.",
+    description = "Creates a pool of the specified type with the given configuration. The `pool_type` field selects the AMM variant and `config` holds type-specific parameters; `fee_bps`, `tick_spacing`, and `amplification` may be left out where the deployment sets a default (see `GET /config/pool-types`). Counts against the caller's pool quotas (live pools and creations per day). Optional `metadata` must satisfy the metadata schemas registered for the caller and for the pool type; violations are listed per field in `fields`. The calling client becomes the pool's owner.",
+    params(
+        ("x-client-id" = Option<String>, Header, description = "Calling client; pool quotas and tenant metadata schemas are applied per client id, and it becomes the pool's owner"),
+    ),
     request_body = CreatePoolRequest,
     responses(
         (status = 201, description = "Pool created successfully", body = CreatePoolResponse),
-        (status = 400, description = "Invalid request or pool type", body = ErrorResponse),
+        (status = 400, description = "Invalid request, pool type, or metadata", body = ErrorResponse),
+        (status = 422, description = "Pool limit reached", body = ErrorResponse),
+        (status = 429, description = "Pool quota exceeded", body = ErrorResponse),
     )
 )]
 pub async fn create_pool(
     State(state): State<AppState>,
+    client: ClientId,
     Json(req): Json<CreatePoolRequest>,
 ) -> Result<impl IntoResponse, GatewayError> {
-    let (config, fee_bps) = parse_pool_config(&req)?;
-
+    let metadata = req.metadata.unwrap_or(serde_json::Value::Null);
+    state
+        .metadata_schemas
+        .validate(client.as_deref(), &req.pool_type, &metadata)?;
+    let permit = state
+        .quotas
+        .reserve_pool_creation(client.as_deref(), Utc::now())?;
+    let pool_id = state
+        .cluster
+        .as_deref()
+        .map_or_else(PoolId::new, ClusterMembership::generate_local_id);
     let pool_id = state
         .pool_service
-        .create_pool(&config, &req.pool_type, fee_bps)
+        .create_pool_from_config(
+            pool_id,
+            &req.pool_type,
+            state.pool_defaults.apply(&req.pool_type, req.config),
+            metadata,
+            client.as_deref(),
+        )
         .await?;
+    permit.commit(pool_id);
 
     let response = CreatePoolResponse {
         pool_id,
         pool_type: req.pool_type,
         name: req.name,
         created_at: Utc::now(),
-        status: "active".to_string(),
+        status: PoolStatus::Active,
     };
 
     Ok((StatusCode::CREATED, Json(response)))
 }
 
-/// `GET /pools` — List all pools with pagination and optional type filter.
+/// `POST /pools/validate` — Validate a pool creation request without
+/// creating the pool.
+///
+/// An invalid config is not an error: problems are reported in the
+/// response body.
+#[utoipa::path(
+    post,
+    path = "/api/v1/pools/validate",
+    tag = "Pools",
+    summary = "Validate a pool config",
+    description = "Dry-runs pool creation: fills in the deployment's config defaults, parses the config, builds the hydra-amm pool, and discards it. Returns every field-level problem at once so forms can be checked before submitting to `POST /pools`. Nothing is registered and no event is emitted.",
+    request_body = CreatePoolRequest,
+    responses(
+        (status = 200, description = "Validation result", body = ValidatePoolResponse),
+    )
+)]
+pub async fn validate_pool(
+    State(state): State<AppState>,
+    Json(req): Json<CreatePoolRequest>,
+) -> Json<ValidatePoolResponse> {
+    let config = state.pool_defaults.apply(&req.pool_type, req.config);
+    let errors = pool_service::validate_config(&req.pool_type, config);
+    Json(ValidatePoolResponse {
+        valid: errors.is_empty(),
+        pool_type: req.pool_type,
+        errors: errors.into_iter().map(ConfigIssueDto::from).collect(),
+    })
+}
+
+/// `GET /pools` — List pools with pagination, optional type, token, fee,
+/// status, creation-date, and activity filters, and a sort order.
 ///
 /// # Errors
 ///
-/// Returns [`GatewayError`] on internal failures.
+/// Returns [`GatewayError::InvalidRequest`] if `created_after` is later
+/// than `created_before` or `min_fee_bps` exceeds `max_fee_bps`.
 #[utoipa::path(
     get,
     path = "/api/v1/pools",
     tag = "Pools",
     summary = "List pools",
-    description = "Returns a paginated list of all pools, optionally filtered by type.",
-    params(PaginationParams),
+    description = "Returns a paginated list of pools. `pool_type`, `token` (address), `min_fee_bps`/`max_fee_bps`, and `status` select pools by type, token, fee tier, and lifecycle state; `created_after`/`created_before` bound the creation time, `min_swap_count` drops pools with fewer swaps, and `active_since` keeps only pools modified since the given instant. `sort_by` orders the list by `created_at` (oldest first, the default), `volume`, or `swap_count` (highest first). In a cluster the list also covers pools owned by peers, from their replicated copies.",
+    params(PaginationParams, PoolListFilter),
     responses(
         (status = 200, description = "Paginated pool list", body = PoolListResponse),
+        (status = 400, description = "Inconsistent filter range", body = ErrorResponse),
     )
 )]
 pub async fn list_pools(
     State(state): State<AppState>,
     Query(params): Query<PaginationParams>,
+    Query(filter): Query<PoolListFilter>,
 ) -> Result<impl IntoResponse, GatewayError> {
-    let params = params.clamped();
-    let summaries = state.pool_service.list_pools(None).await;
+    if let (Some(after), Some(before)) = (filter.created_after, filter.created_before)
+        && after > before
+    {
+        return Err(GatewayError::InvalidRequest(
+            "created_after must not be later than created_before".to_string(),
+        ));
+    }
+    if let (Some(min), Some(max)) = (filter.min_fee_bps, filter.max_fee_bps)
+        && min > max
+    {
+        return Err(GatewayError::InvalidRequest(
+            "min_fee_bps must not exceed max_fee_bps".to_string(),
+        ));
+    }
 
-    let total = summaries.len() as u32;
-    let per_page = params.per_page;
-    let page = params.page;
-    let total_pages = if total == 0 {
-        0
-    } else {
-        total.div_ceil(per_page)
-    };
+    // In a cluster the list includes the local copies of peer pools
+    let pool_filter = filter.pool_filter();
+    let mut summaries = state.pool_service.list_pools(&pool_filter).await;
+    if let Some(reads) = state.peer_reads.as_ref() {
+        summaries.extend(reads.pool_service().list_pools(&pool_filter).await);
+        pool_filter.sort_by.sort(&mut summaries);
+    }
+    summaries.retain(|s| filter.matches(s));
+    let (page, pagination) = params.paginate(summaries);
 
-    let start = ((page - 1) * per_page) as usize;
-    let data: Vec<PoolSummaryDto> = summaries
+    let data: Vec<PoolSummaryDto> = page
         .into_iter()
-        .skip(start)
-        .take(per_page as usize)
         .map(|s| PoolSummaryDto {
             pool_id: s.pool_id,
             pool_type: s.pool_type,
             created_at: s.created_at,
             fee_bps: s.fee_bps,
             swap_count: s.swap_count,
+            total_volume: s.total_volume.to_string(),
+            status: s.status,
         })
         .collect();
 
-    Ok(Json(PoolListResponse {
-        data,
-        pagination: PaginationMeta {
-            page,
-            per_page,
-            total,
-            total_pages,
-        },
-    }))
+    Ok(Json(PoolListResponse { data, pagination }))
 }
 
 /// `GET /pools/:id` — Get pool details.
@@ -127,12 +191,12 @@ pub async fn list_pools(
     path = "/api/v1/pools/{id}",
     tag = "Pools",
     summary = "Get pool details",
-    description = "Returns full details for a single pool including reserves, prices, and metadata.",
+    description = "Returns full details for a single pool: tokens, tracked reserves, spot price and the tokens it refers to, total liquidity, swap count and volume, archive and canary status, and metadata.",
     params(
         ("id" = uuid::Uuid, Path, description = "Pool UUID"),
     ),
     responses(
-        (status = 200, description = "Pool details", body = serde_json::Value),
+        (status = 200, description = "Pool details", body = PoolDetailResponse),
         (status = 404, description = "Pool not found", body = ErrorResponse),
     )
 )]
@@ -140,355 +204,234 @@ pub async fn get_pool(
     State(state): State<AppState>,
     Path(id): Path<uuid::Uuid>,
 ) -> Result<impl IntoResponse, GatewayError> {
-    let pool_id = crate::domain::PoolId::from_uuid(id);
+    let pool_id = PoolId::from_uuid(id);
     let entry_lock = state.pool_service.registry().get(pool_id).await?;
     let entry = entry_lock.read().await;
+    Ok(Json(PoolDetailResponse::from(&*entry)))
+}
 
-    let response = serde_json::json!({
-        "pool_id": entry.pool_id,
-        "pool_type": entry.pool_type,
-        "created_at": entry.created_at.to_rfc3339(),
-        "updated_at": entry.last_modified_at.to_rfc3339(),
-        "status": "active",
-        "fee_bps": entry.fee_bps,
-        "swap_count": entry.swap_count,
-        "total_volume": entry.total_volume.to_string(),
-    });
+/// `PATCH /pools/:id` — Replace a pool's metadata.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+/// [`GatewayError::InvalidMetadata`] when `metadata` violates a registered
+/// schema, [`GatewayError::Forbidden`] if the caller is neither the
+/// pool's owner nor an admin, or [`GatewayError::PoolArchived`] if the
+/// pool is archived.
+#[utoipa::path(
+    patch,
+    path = "/api/v1/pools/{id}",
+    tag = "Pools",
+    summary = "Update pool metadata",
+    description = "Replaces the pool's metadata and emits a PoolMetadataUpdated event. The new metadata must satisfy the metadata schemas registered for the caller and for the pool type; violations are listed per field in `fields`. `null` clears the metadata. Pool state and version are unchanged. Only the pool's owner or an admin may update an owned pool.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("x-client-id" = Option<String>, Header, description = "Calling client; its tenant metadata schema applies, and it must own the pool or be an admin"),
+    ),
+    request_body = UpdatePoolRequest,
+    responses(
+        (status = 200, description = "Metadata updated", body = PoolMetadataResponse),
+        (status = 400, description = "Metadata violates a schema", body = ErrorResponse),
+        (status = 403, description = "Caller is neither the owner nor an admin", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+        (status = 409, description = "Pool archived", body = ErrorResponse),
+    )
+)]
+pub async fn update_pool(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    client: ClientId,
+    Json(req): Json<UpdatePoolRequest>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    let pool_type = {
+        let entry_lock = state.pool_service.registry().get(pool_id).await?;
+        let entry = entry_lock.read().await;
+        entry.pool_type.clone()
+    };
+    state
+        .metadata_schemas
+        .validate(client.as_deref(), &pool_type, &req.metadata)?;
+    state
+        .pool_service
+        .update_metadata(
+            pool_id,
+            req.metadata.clone(),
+            Manager::Client(client.as_deref()),
+        )
+        .await?;
 
-    Ok(Json(response))
+    Ok(Json(PoolMetadataResponse {
+        pool_id,
+        metadata: req.metadata,
+        updated_at: Utc::now(),
+    }))
 }
 
-/// `DELETE /pools/:id` — Remove a pool.
+/// `DELETE /pools/:id` — Remove a pool, optionally returning its final state.
 ///
 /// # Errors
 ///
-/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist.
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+/// [`GatewayError::Forbidden`] if the caller is neither the pool's owner
+/// nor an admin, [`GatewayError::PoolInUse`] if providers hold open
+/// positions and `force` is not set, or
+/// [`GatewayError::PersistenceError`] if archiving fails.
 #[utoipa::path(
     delete,
     path = "/api/v1/pools/{id}",
     tag = "Pools",
     summary = "Delete a pool",
-    description = "Removes a pool and emits a PoolRemoved event.",
+    description = "Removes a pool and emits a PoolRemoved event. Refused with 409 while providers hold open positions, unless `force=true`. Reads already running finish, while operations still waiting for the pool and any later ones get 404; PoolRemoved is emitted once the last in-flight operation lets go of the pool. With persistence enabled a final snapshot is archived before removal. `return_state=true` responds with the final state instead of 204. Only the pool's owner or an admin may delete an owned pool.",
     params(
         ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("x-client-id" = Option<String>, Header, description = "Calling client; must own the pool or be an admin"),
+        DeletePoolParams,
     ),
     responses(
+        (status = 200, description = "Pool deleted; final state returned", body = DeletedPoolResponse),
         (status = 204, description = "Pool deleted"),
+        (status = 403, description = "Caller is neither the owner nor an admin", body = ErrorResponse),
         (status = 404, description = "Pool not found", body = ErrorResponse),
+        (status = 409, description = "Pool still in use", body = ErrorResponse),
     )
 )]
 pub async fn delete_pool(
     State(state): State<AppState>,
     Path(id): Path<uuid::Uuid>,
-) -> Result<impl IntoResponse, GatewayError> {
-    let pool_id = crate::domain::PoolId::from_uuid(id);
-    state.pool_service.remove_pool(pool_id).await?;
-    Ok(StatusCode::NO_CONTENT)
-}
-
-/// Pool management routes.
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        .route("/pools", post(create_pool).get(list_pools))
-        .route("/pools/{id}", get(get_pool).delete(delete_pool))
+    client: ClientId,
+    Query(params): Query<DeletePoolParams>,
+) -> Result<Response, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    let deleted = state
+        .pool_service
+        .delete_pool(
+            pool_id,
+            params.force,
+            state.persistence.as_ref(),
+            Manager::Client(client.as_deref()),
+        )
+        .await?;
+    if params.return_state {
+        Ok(Json(DeletedPoolResponse::from(deleted)).into_response())
+    } else {
+        Ok(StatusCode::NO_CONTENT.into_response())
+    }
 }
 
-// ── Config Parsing Helpers ──────────────────────────────────────────────
-
-/// Parses the `CreatePoolRequest` JSON config into an `AmmConfig`.
+/// `POST /pools/:id/transfer-ownership` — Hand a pool to another client.
 ///
 /// # Errors
 ///
-/// Returns a [`GatewayError`] on invalid or unsupported configuration.
-fn parse_pool_config(req: &CreatePoolRequest) -> Result<(AmmConfig, u32), GatewayError> {
-    match req.pool_type.as_str() {
-        "constant_product" => parse_constant_product(&req.config),
-        "clmm" => parse_clmm(&req.config),
-        "hybrid" => parse_hybrid(&req.config),
-        "weighted" => parse_weighted(&req.config),
-        "dynamic" => parse_dynamic(&req.config),
-        "orderbook" => parse_orderbook(&req.config),
-        other => Err(GatewayError::InvalidPoolType(other.to_string())),
-    }
-}
-
-fn parse_token(val: &serde_json::Value) -> Result<Token, GatewayError> {
-    let address = val
-        .get("address")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| GatewayError::InvalidRequest("missing token address".to_string()))?;
-
-    let decimals = val
-        .get("decimals")
-        .and_then(|v| v.as_u64())
-        .ok_or_else(|| GatewayError::InvalidRequest("missing token decimals".to_string()))?;
-
-    let mut bytes = [0u8; 32];
-    let addr_bytes = address.as_bytes();
-    let len = addr_bytes.len().min(32);
-    if let (Some(dst), Some(src)) = (bytes.get_mut(..len), addr_bytes.get(..len)) {
-        dst.copy_from_slice(src);
-    }
-
-    let decimals = Decimals::new(decimals as u8)
-        .map_err(|e| GatewayError::InvalidRequest(format!("invalid decimals: {e}")))?;
-
-    Ok(Token::new(TokenAddress::from_bytes(bytes), decimals))
-}
-
-fn parse_fee_bps(config: &serde_json::Value) -> Result<(FeeTier, u32), GatewayError> {
-    let bps = config
-        .get("fee_bps")
-        .and_then(|v| v.as_u64())
-        .ok_or_else(|| GatewayError::InvalidRequest("missing fee_bps".to_string()))?;
-    let bps_u32 = bps as u32;
-    Ok((FeeTier::new(BasisPoints::new(bps_u32)), bps_u32))
-}
-
-fn parse_amount_str(val: &serde_json::Value, field: &str) -> Result<Amount, GatewayError> {
-    let s = val
-        .get(field)
-        .and_then(|v| v.as_str().or_else(|| v.as_u64().map(|_| "")))
-        .ok_or_else(|| GatewayError::InvalidRequest(format!("missing {field}")))?;
-
-    // Handle both string and number formats
-    let num: u128 = if s.is_empty() {
-        val.get(field)
-            .and_then(|v| v.as_u64())
-            .map(u128::from)
-            .ok_or_else(|| GatewayError::InvalidRequest(format!("invalid {field}")))?
-    } else {
-        s.parse()
-            .map_err(|_| GatewayError::InvalidRequest(format!("invalid {field}: {s}")))?
-    };
-
-    Ok(Amount::new(num))
-}
-
-fn parse_constant_product(config: &serde_json::Value) -> Result<(AmmConfig, u32), GatewayError> {
-    let token_a = parse_token(
-        config
-            .get("token_a")
-            .ok_or_else(|| GatewayError::InvalidRequest("missing token_a".to_string()))?,
-    )?;
-    let token_b = parse_token(
-        config
-            .get("token_b")
-            .ok_or_else(|| GatewayError::InvalidRequest("missing token_b".to_string()))?,
-    )?;
-    let (fee, fee_bps) = parse_fee_bps(config)?;
-    let reserve_a = parse_amount_str(config, "reserve_a")?;
-    let reserve_b = parse_amount_str(config, "reserve_b")?;
-
-    let pair = TokenPair::new(token_a, token_b)?;
-    let cfg = ConstantProductConfig::new(pair, fee, reserve_a, reserve_b)?;
-    Ok((AmmConfig::ConstantProduct(cfg), fee_bps))
-}
-
-fn parse_clmm(config: &serde_json::Value) -> Result<(AmmConfig, u32), GatewayError> {
-    let token_a = parse_token(
-        config
-            .get("token_a")
-            .ok_or_else(|| GatewayError::InvalidRequest("missing token_a".to_string()))?,
-    )?;
-    let token_b = parse_token(
-        config
-            .get("token_b")
-            .ok_or_else(|| GatewayError::InvalidRequest("missing token_b".to_string()))?,
-    )?;
-    let (fee, fee_bps) = parse_fee_bps(config)?;
-
-    let tick_spacing = config
-        .get("tick_spacing")
-        .and_then(|v| v.as_u64())
-        .ok_or_else(|| GatewayError::InvalidRequest("missing tick_spacing".to_string()))?
-        as u32;
-
-    let current_tick_val = config
-        .get("current_tick")
-        .and_then(|v| v.as_i64())
-        .ok_or_else(|| GatewayError::InvalidRequest("missing current_tick".to_string()))?
-        as i32;
-
-    let current_tick = Tick::new(current_tick_val)?;
-    let pair = TokenPair::new(token_a, token_b)?;
-
-    // Parse optional positions
-    let positions = if let Some(pos_arr) = config.get("positions").and_then(|v| v.as_array()) {
-        let mut result = Vec::with_capacity(pos_arr.len());
-        for p in pos_arr {
-            let lower = p
-                .get("lower_tick")
-                .and_then(|v| v.as_i64())
-                .ok_or_else(|| {
-                    GatewayError::InvalidRequest("missing position lower_tick".to_string())
-                })? as i32;
-            let upper = p
-                .get("upper_tick")
-                .and_then(|v| v.as_i64())
-                .ok_or_else(|| {
-                    GatewayError::InvalidRequest("missing position upper_tick".to_string())
-                })? as i32;
-            let liq = p
-                .get("liquidity")
-                .and_then(|v| v.as_str().or_else(|| v.as_u64().map(|_| "")))
-                .ok_or_else(|| {
-                    GatewayError::InvalidRequest("missing position liquidity".to_string())
-                })?;
-            let liq_val: u128 = if liq.is_empty() {
-                p.get("liquidity")
-                    .and_then(|v| v.as_u64())
-                    .map(u128::from)
-                    .ok_or_else(|| {
-                        GatewayError::InvalidRequest("invalid position liquidity".to_string())
-                    })?
-            } else {
-                liq.parse().map_err(|_| {
-                    GatewayError::InvalidRequest("invalid position liquidity".to_string())
-                })?
-            };
-            let pos = Position::new(
-                Tick::new(lower)?,
-                Tick::new(upper)?,
-                hydra_amm::domain::Liquidity::new(liq_val),
-            )?;
-            result.push(pos);
-        }
-        result
-    } else {
-        vec![]
-    };
-
-    let cfg = ClmmConfig::new(pair, fee, tick_spacing, current_tick, positions)?;
-    Ok((AmmConfig::Clmm(cfg), fee_bps))
-}
-
-fn parse_hybrid(config: &serde_json::Value) -> Result<(AmmConfig, u32), GatewayError> {
-    let token_a = parse_token(
-        config
-            .get("token_a")
-            .ok_or_else(|| GatewayError::InvalidRequest("missing token_a".to_string()))?,
-    )?;
-    let token_b = parse_token(
-        config
-            .get("token_b")
-            .ok_or_else(|| GatewayError::InvalidRequest("missing token_b".to_string()))?,
-    )?;
-    let (fee, fee_bps) = parse_fee_bps(config)?;
-    let amplification = config
-        .get("amplification")
-        .and_then(|v| v.as_u64())
-        .ok_or_else(|| GatewayError::InvalidRequest("missing amplification".to_string()))?
-        as u32;
-    let reserve_a = parse_amount_str(config, "reserve_a")?;
-    let reserve_b = parse_amount_str(config, "reserve_b")?;
-
-    let pair = TokenPair::new(token_a, token_b)?;
-    let cfg = HybridConfig::new(pair, fee, amplification, reserve_a, reserve_b)?;
-    Ok((AmmConfig::Hybrid(cfg), fee_bps))
-}
-
-fn parse_weighted(config: &serde_json::Value) -> Result<(AmmConfig, u32), GatewayError> {
-    let (fee, fee_bps) = parse_fee_bps(config)?;
-
-    let tokens_arr = config
-        .get("tokens")
-        .and_then(|v| v.as_array())
-        .ok_or_else(|| GatewayError::InvalidRequest("missing tokens array".to_string()))?;
-
-    let mut tokens = Vec::with_capacity(tokens_arr.len());
-    let mut weights = Vec::with_capacity(tokens_arr.len());
-    for t in tokens_arr {
-        tokens.push(parse_token(t)?);
-        let w = t
-            .get("weight")
-            .and_then(|v| v.as_u64())
-            .ok_or_else(|| GatewayError::InvalidRequest("missing token weight".to_string()))?
-            as u32;
-        weights.push(BasisPoints::new(w));
-    }
-
-    let reserves_arr = config
-        .get("reserves")
-        .and_then(|v| v.as_array())
-        .ok_or_else(|| GatewayError::InvalidRequest("missing reserves array".to_string()))?;
-
-    let mut balances = Vec::with_capacity(reserves_arr.len());
-    for r in reserves_arr {
-        let s = r
-            .as_str()
-            .ok_or_else(|| GatewayError::InvalidRequest("reserve must be string".to_string()))?;
-        let val: u128 = s
-            .parse()
-            .map_err(|_| GatewayError::InvalidRequest(format!("invalid reserve: {s}")))?;
-        balances.push(Amount::new(val));
-    }
+/// Returns [`GatewayError::InvalidRequest`] if `new_owner` is not a valid
+/// client id, [`GatewayError::PoolNotFound`] if the pool does not exist,
+/// [`GatewayError::Forbidden`] if the caller is neither the pool's owner
+/// nor an admin, or [`GatewayError::PoolArchived`] if the pool is
+/// archived.
+#[utoipa::path(
+    post,
+    path = "/api/v1/pools/{id}/transfer-ownership",
+    tag = "Pools",
+    summary = "Transfer pool ownership",
+    description = "Makes `new_owner` the owner of the pool and emits a PoolOwnershipTransferred event. Only the current owner or an admin may transfer a pool; pools created without a client id can only be assigned an owner by an admin. The previous owner loses the right to manage the pool.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("x-client-id" = Option<String>, Header, description = "Calling client; must own the pool or be an admin"),
+    ),
+    request_body = TransferOwnershipRequest,
+    responses(
+        (status = 200, description = "Ownership transferred", body = OwnershipResponse),
+        (status = 400, description = "Invalid new owner", body = ErrorResponse),
+        (status = 403, description = "Caller is neither the owner nor an admin", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+        (status = 409, description = "Pool archived", body = ErrorResponse),
+    )
+)]
+pub async fn transfer_ownership(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    client: ClientId,
+    Json(req): Json<TransferOwnershipRequest>,
+) -> Result<Json<OwnershipResponse>, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    let new_owner = parse_client_id(&req.new_owner)?;
+    let previous_owner = state
+        .pool_service
+        .transfer_ownership(
+            pool_id,
+            new_owner.clone(),
+            Manager::Client(client.as_deref()),
+        )
+        .await?;
 
-    let cfg = WeightedConfig::new(tokens, weights, fee, balances)?;
-    Ok((AmmConfig::Weighted(cfg), fee_bps))
+    Ok(Json(OwnershipResponse {
+        pool_id,
+        owner: new_owner,
+        previous_owner,
+        transferred_at: Utc::now(),
+    }))
 }
 
-fn parse_dynamic(config: &serde_json::Value) -> Result<(AmmConfig, u32), GatewayError> {
-    let token_a = parse_token(
-        config
-            .get("token_a")
-            .ok_or_else(|| GatewayError::InvalidRequest("missing token_a".to_string()))?,
-    )?;
-    let token_b = parse_token(
-        config
-            .get("token_b")
-            .ok_or_else(|| GatewayError::InvalidRequest("missing token_b".to_string()))?,
-    )?;
-    let (fee, fee_bps) = parse_fee_bps(config)?;
-
-    let oracle_price_val = config
-        .get("oracle_price")
-        .and_then(|v| {
-            v.as_f64()
-                .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
-        })
-        .ok_or_else(|| GatewayError::InvalidRequest("missing oracle_price".to_string()))?;
-    let oracle_price = Price::new(oracle_price_val)?;
-
-    let slippage_coefficient = config
-        .get("slippage_coefficient")
-        .and_then(|v| {
-            v.as_f64()
-                .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
-        })
-        .ok_or_else(|| GatewayError::InvalidRequest("missing slippage_coefficient".to_string()))?;
-
-    let reserve_a = parse_amount_str(config, "reserve_a")?;
-    let reserve_b = parse_amount_str(config, "reserve_b")?;
+/// `PUT /pools/:id/oracle-price` — Push an oracle price to a dynamic pool.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+/// [`GatewayError::Forbidden`] if the caller is neither the pool's owner
+/// nor an admin, [`GatewayError::InvalidRequest`] if it is not a dynamic
+/// pool or the price is not positive, or
+/// [`GatewayError::OraclePriceRejected`] if the price is outside the
+/// pool's oracle bounds.
+#[utoipa::path(
+    put,
+    path = "/api/v1/pools/{id}/oracle-price",
+    tag = "Pools",
+    summary = "Update a dynamic pool's oracle price",
+    description = "Reprices a dynamic pool around `price` and emits OraclePriceUpdated and PriceUpdated events. The pool is rebuilt at its current reserves. Prices outside the `oracle_bounds` of the pool's config (`min_price`, `max_price`, `max_step_pct` from the current price) are rejected with 422 and logged, and the pool keeps its price.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("x-client-id" = Option<String>, Header, description = "Calling client; must own the pool or be an admin"),
+    ),
+    request_body = SetOraclePriceRequest,
+    responses(
+        (status = 200, description = "Oracle price updated", body = OraclePriceResponse),
+        (status = 400, description = "Not a dynamic pool or invalid price", body = ErrorResponse),
+        (status = 403, description = "Caller is neither the owner nor an admin", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+        (status = 422, description = "Price outside the pool's oracle bounds", body = ErrorResponse),
+    )
+)]
+pub async fn set_oracle_price(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    client: ClientId,
+    Json(req): Json<SetOraclePriceRequest>,
+) -> Result<Json<OraclePriceResponse>, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    let update = state
+        .pool_service
+        .set_oracle_price(pool_id, req.price, Manager::Client(client.as_deref()))
+        .await?;
 
-    let pair = TokenPair::new(token_a, token_b)?;
-    let cfg = DynamicConfig::new(
-        pair,
-        fee,
-        oracle_price,
-        slippage_coefficient,
-        reserve_a,
-        reserve_b,
-    )?;
-    Ok((AmmConfig::Dynamic(cfg), fee_bps))
+    Ok(Json(OraclePriceResponse {
+        pool_id,
+        oracle_price: format!("{}", req.price),
+        previous_price: format!("{}", update.previous_price),
+        spot_price: update.spot_price.map(|p| format!("{p}")),
+        updated_at: Utc::now(),
+    }))
 }
 
-fn parse_orderbook(config: &serde_json::Value) -> Result<(AmmConfig, u32), GatewayError> {
-    let token_a = parse_token(
-        config
-            .get("token_a")
-            .ok_or_else(|| GatewayError::InvalidRequest("missing token_a".to_string()))?,
-    )?;
-    let token_b = parse_token(
-        config
-            .get("token_b")
-            .ok_or_else(|| GatewayError::InvalidRequest("missing token_b".to_string()))?,
-    )?;
-    let (fee, fee_bps) = parse_fee_bps(config)?;
-    let tick_size = parse_amount_str(config, "tick_size")?;
-    let lot_size = parse_amount_str(config, "lot_size")?;
-
-    let pair = TokenPair::new(token_a, token_b)?;
-    let cfg = OrderBookConfig::new(pair, fee, tick_size, lot_size)?;
-    Ok((AmmConfig::OrderBook(cfg), fee_bps))
+/// Pool management routes.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/pools", post(create_pool).get(list_pools))
+        .route("/pools/validate", post(validate_pool))
+        .route(
+            "/pools/{id}",
+            get(get_pool).patch(update_pool).delete(delete_pool),
+        )
+        .route("/pools/{id}/transfer-ownership", post(transfer_ownership))
+        .route("/pools/{id}/oracle-price", put(set_oracle_price))
 }
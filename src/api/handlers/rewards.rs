@@ -0,0 +1,99 @@
+//! Liquidity-mining reward handlers for the calling client.
+
+use axum::extract::State;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::Utc;
+
+use crate::api::client_id::{CLIENT_ID_HEADER, ClientId};
+use crate::api::dto::{ClaimRewardsResponse, RewardsResponse};
+use crate::app_state::AppState;
+use crate::error::{ErrorResponse, GatewayError};
+
+/// `GET /accounts/me/rewards` — Rewards accrued to the calling client.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] if the request carries no
+/// client id.
+#[utoipa::path(
+    get,
+    path = "/api/v1/accounts/me/rewards",
+    tag = "Rewards",
+    summary = "Accrued rewards",
+    description = "Returns the unclaimed liquidity-mining rewards of the positions opened by the calling client, by pool and reward token. Each pool's emissions are shared between its positions in proportion to the liquidity they held over time.",
+    params(
+        ("x-client-id" = String, Header, description = "Calling client; positions it opened earn rewards"),
+    ),
+    responses(
+        (status = 200, description = "Accrued rewards", body = RewardsResponse),
+        (status = 400, description = "Missing client id", body = ErrorResponse),
+    )
+)]
+pub async fn my_rewards(
+    State(state): State<AppState>,
+    client: ClientId,
+) -> Result<Json<RewardsResponse>, GatewayError> {
+    let client_id = require_client(client)?;
+    let now = Utc::now();
+    let rewards = state.rewards.accrued(&client_id, now);
+    Ok(Json(RewardsResponse {
+        client_id,
+        rewards: rewards.into_iter().map(Into::into).collect(),
+        as_of: now,
+    }))
+}
+
+/// `POST /accounts/me/rewards/claim` — Claim the calling client's rewards.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] if the request carries no
+/// client id, or [`GatewayError::ReadOnlyReplica`] on a replica.
+#[utoipa::path(
+    post,
+    path = "/api/v1/accounts/me/rewards/claim",
+    tag = "Rewards",
+    summary = "Claim rewards",
+    description = "Pays out every reward accrued to the calling client and emits a RewardsClaimed event per pool and reward token. Returns an empty list if nothing had accrued.",
+    params(
+        ("x-client-id" = String, Header, description = "Calling client; recorded as the event actor"),
+    ),
+    responses(
+        (status = 200, description = "Rewards claimed", body = ClaimRewardsResponse),
+        (status = 400, description = "Missing client id", body = ErrorResponse),
+        (status = 503, description = "Read-only replica", body = ErrorResponse),
+    )
+)]
+pub async fn claim_rewards(
+    State(state): State<AppState>,
+    client: ClientId,
+) -> Result<Json<ClaimRewardsResponse>, GatewayError> {
+    if state.pool_service.is_read_only() {
+        return Err(GatewayError::ReadOnlyReplica);
+    }
+    let client_id = require_client(client)?;
+    let now = Utc::now();
+    let claimed = state.rewards.claim(&client_id, now, &state.pool_service);
+    Ok(Json(ClaimRewardsResponse {
+        client_id,
+        claimed: claimed.into_iter().map(Into::into).collect(),
+        claimed_at: now,
+    }))
+}
+
+/// Rewards are tracked per client, so these endpoints need one.
+fn require_client(client: ClientId) -> Result<String, GatewayError> {
+    client.0.ok_or_else(|| {
+        GatewayError::InvalidRequest(format!(
+            "rewards are tracked per client; send {CLIENT_ID_HEADER}"
+        ))
+    })
+}
+
+/// Reward routes.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/accounts/me/rewards", get(my_rewards))
+        .route("/accounts/me/rewards/claim", post(claim_rewards))
+}
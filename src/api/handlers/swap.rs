@@ -1,95 +1,122 @@
 //! Swap and quote endpoint handlers.
 
-use axum::extract::{Path, State};
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
 use axum::response::IntoResponse;
 use axum::routing::post;
 use axum::{Json, Router};
 use chrono::Utc;
-use hydra_amm::domain::{Amount, SwapSpec, Token, TokenAddress};
+use hydra_amm::domain::{Amount, SwapResult, Token, TokenAddress};
 use hydra_amm::traits::SwapPool;
 
-use crate::api::dto::{QuoteResponse, SwapRequest, SwapResponse};
+use crate::api::client_id::ClientId;
+use crate::api::dto::{
+    AutoSwapRequest, AutoSwapResponse, QuoteResponse, RoutePlanDto, SwapRequest, SwapResponse,
+    Units, UnitsParams,
+};
 use crate::app_state::AppState;
 use crate::domain::PoolId;
+use crate::domain::pool_entry::PoolEntry;
+use crate::domain::pool_operation::{SwapKind, TokenSide};
 use crate::error::{ErrorResponse, GatewayError};
+use crate::persistence::models::QuoteRecord;
+use crate::service::pool_service::{SwapConditions, check_deadline};
+use crate::service::pricing::{self, TradeDecimals};
+use crate::service::routing::{
+    self, CostModel, DEFAULT_MAX_HOPS, FULL_CANARY_WEIGHT_BPS, MAX_HOPS_LIMIT, PerHopCost,
+    RouteOptions, RoutePlan,
+};
+
+/// Seconds after quoting reported as a quote's `valid_until` hint.
+const QUOTE_VALID_SECS: i64 = 10;
 
 /// `POST /pools/:id/swap` — Execute a swap.
 ///
 /// # Errors
 ///
-/// Returns [`GatewayError`] on invalid parameters, missing pool, or insufficient liquidity.
+/// Returns [`GatewayError`] on invalid parameters, missing pool, or insufficient liquidity,
+/// [`GatewayError::DeadlineExpired`] once `deadline` has passed,
+/// [`GatewayError::VersionMismatch`] if `expected_version` is stale,
+/// [`GatewayError::PoolPaused`] if the pool is paused or draining,
+/// [`GatewayError::SlippageExceeded`] if the fill violates `min_amount_out`
+/// or `max_amount_in`, and [`GatewayError::RateLimited`] when the caller's
+/// daily swap quota is used up.
 #[utoipa::path(
     post,
     path = "/api/v1/pools/{id}/swap",
     tag = "Swaps",
     summary = "Execute a swap",
-    description = "Executes a token swap on the specified pool. Supports exact-in and exact-out modes.",
+    description = "Executes a token swap on the specified pool. Supports exact-in and exact-out modes. With `min_amount_out` or `max_amount_in`, the fill is quoted under the pool's lock first and the swap is rejected with 422 (code 4003) without touching the pool if it would violate either bound. A swap whose `deadline` passes before it executes, including while it waits for the pool's lock, is rejected with 422 (code 4006).",
     params(
         ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("x-client-id" = Option<String>, Header, description = "Calling client; recorded as the event actor"),
+        ("idempotency-key" = Option<String>, Header, description = "Retries with the same key within IDEMPOTENCY_TTL_SECS replay the first successful response instead of executing again"),
+        UnitsParams,
     ),
     request_body = SwapRequest,
     responses(
         (status = 200, description = "Swap executed", body = SwapResponse),
         (status = 400, description = "Invalid swap parameters", body = ErrorResponse),
         (status = 404, description = "Pool not found", body = ErrorResponse),
-        (status = 422, description = "Insufficient liquidity", body = ErrorResponse),
+        (status = 409, description = "Pool moved past expected_version, or is paused, draining, or archived", body = ErrorResponse),
+        (status = 422, description = "Insufficient liquidity, slippage bound violated, or deadline passed", body = ErrorResponse),
+        (status = 429, description = "Swap quota exceeded", body = ErrorResponse),
     )
 )]
 pub async fn execute_swap(
     State(state): State<AppState>,
     Path(id): Path<uuid::Uuid>,
+    Query(units): Query<UnitsParams>,
+    client: ClientId,
     Json(req): Json<SwapRequest>,
 ) -> Result<impl IntoResponse, GatewayError> {
     let pool_id = PoolId::from_uuid(id);
-    let (spec, token_in) = parse_swap_request(&state, pool_id, &req).await?;
+    check_deadline(req.deadline, Utc::now())?;
+    let (kind, amount, token_in) = parse_swap_request(&state, pool_id, &req).await?;
+    state.quotas.check_swap(client.as_deref(), Utc::now())?;
 
     let command_id = uuid::Uuid::new_v4().to_string();
 
     // Capture price before
     let entry_lock = state.pool_service.registry().get(pool_id).await?;
     let entry = entry_lock.read().await;
-    let pair = *entry.pool_box.token_pair();
-    let base = pair.first();
-    let quote_tok = pair.second();
-    let price_before = entry
-        .pool_box
-        .spot_price(&base, &quote_tok)
-        .map(|p| p.get())
-        .unwrap_or(0.0);
+    let price_before = entry.spot_price().unwrap_or(0.0);
+    let mid_price = TokenSide::of(entry.pool_box.token_pair(), token_in)
+        .and_then(|side| entry.spot_price_of(side));
+    let (decimals_in, decimals_out) = swap_decimals(&entry, token_in);
     drop(entry);
 
-    let result = state
+    let conditions = SwapConditions {
+        expected_version: req.expected_version,
+        min_amount_out: parse_bound("min_amount_out", req.min_amount_out.as_deref())?,
+        max_amount_in: parse_bound("max_amount_in", req.max_amount_in.as_deref())?,
+        deadline: req.deadline,
+    };
+    let (result, rebate) = state
         .pool_service
-        .execute_swap(pool_id, spec, token_in, &command_id)
+        .execute_swap(
+            pool_id,
+            kind,
+            amount,
+            token_in,
+            conditions,
+            &command_id,
+            client.as_deref(),
+        )
         .await?;
 
     // Capture price after
     let entry_lock = state.pool_service.registry().get(pool_id).await?;
     let entry = entry_lock.read().await;
-    let price_after = entry
-        .pool_box
-        .spot_price(&base, &quote_tok)
-        .map(|p| p.get())
-        .unwrap_or(0.0);
+    let price_after = entry.spot_price().unwrap_or(0.0);
     drop(entry);
 
-    let price_impact_bps = if price_before == 0.0 {
-        0
-    } else {
-        #[allow(clippy::cast_possible_truncation)]
-        {
-            ((price_after - price_before) / price_before * 10_000.0) as i32
-        }
-    };
-
-    let effective_price = if result.amount_in().get() == 0 {
-        "0".to_string()
-    } else {
-        format!(
-            "{}",
-            result.amount_out().get() as f64 / result.amount_in().get() as f64
-        )
+    let decimals = TradeDecimals {
+        input: decimals_in,
+        output: decimals_out,
     };
+    let (effective_price, price_impact_bps) = fill_prices(&result, mid_price, decimals);
 
     Ok(Json(SwapResponse {
         swap_id: command_id,
@@ -99,6 +126,12 @@ pub async fn execute_swap(
         amount_in: result.amount_in().get().to_string(),
         amount_out: result.amount_out().get().to_string(),
         fee_charged: result.fee().get().to_string(),
+        amount_in_decimal: units.decimal(result.amount_in().get(), decimals_in),
+        amount_out_decimal: units.decimal(result.amount_out().get(), decimals_out),
+        fee_charged_decimal: units.decimal(result.fee().get(), decimals_in),
+        fee_discount_bps: rebate.discount_bps,
+        fee_rebate: rebate.amount.to_string(),
+        fee_rebate_decimal: units.decimal(rebate.amount, decimals_in),
         execution_price: effective_price,
         spot_price_before: format!("{price_before}"),
         spot_price_after: format!("{price_after}"),
@@ -117,9 +150,11 @@ pub async fn execute_swap(
     path = "/api/v1/pools/{id}/quote",
     tag = "Swaps",
     summary = "Get swap quote",
-    description = "Returns a price quote for a swap without executing it. The pool state is not modified.",
+    description = "Returns a price quote for a swap without executing it. The quote runs on a sandbox copy of the pool, so the pool state is never modified; pools created without a JSON config are quoted in place under the pool's lock. The response names the pool version it was computed against; executing with `expected_version` set to it fails with 409 instead of filling at a different price if the pool has changed. With the quote audit trail enabled, sampled quotes are recorded with the caller's client id.",
     params(
         ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("x-client-id" = Option<String>, Header, description = "Calling client; recorded with the quote in the quote audit trail"),
+        UnitsParams,
     ),
     request_body = SwapRequest,
     responses(
@@ -131,53 +166,64 @@ pub async fn execute_swap(
 pub async fn quote_swap(
     State(state): State<AppState>,
     Path(id): Path<uuid::Uuid>,
+    Query(units): Query<UnitsParams>,
+    client: ClientId,
     Json(req): Json<SwapRequest>,
 ) -> Result<impl IntoResponse, GatewayError> {
     let pool_id = PoolId::from_uuid(id);
-    let (spec, token_in) = parse_swap_request(&state, pool_id, &req).await?;
+    let (kind, amount, token_in) = parse_swap_request(&state, pool_id, &req).await?;
 
-    // Get current spot price
+    // Get current spot price, reported per the pool's price convention;
+    // impact is measured against the price in the swap's direction.
     let entry_lock = state.pool_service.registry().get(pool_id).await?;
     let entry = entry_lock.read().await;
-    let pair = *entry.pool_box.token_pair();
-    let base = pair.first();
-    let quote_tok = pair.second();
-    let spot_price = entry
-        .pool_box
-        .spot_price(&base, &quote_tok)
-        .map(|p| p.get())
-        .unwrap_or(0.0);
+    let spot_price = entry.spot_price().unwrap_or(0.0);
+    let side_in = TokenSide::of(entry.pool_box.token_pair(), token_in);
+    let mid_price = side_in.and_then(|side| entry.spot_price_of(side));
+    let (decimals_in, decimals_out) = swap_decimals(&entry, token_in);
+    // Read before quoting: if the pool moves in between, the version is
+    // older than the quoted state and execution is safely rejected.
+    let pool_version = entry.version();
     drop(entry);
 
+    let pool_service = Arc::clone(&state.pool_service);
     let result = state
-        .pool_service
-        .quote_swap(pool_id, spec, token_in)
-        .await?;
+        .quote_runtime
+        .run(async move {
+            pool_service
+                .quote_swap(pool_id, kind, amount, token_in)
+                .await
+        })
+        .await??;
 
-    let effective_price = if result.amount_in().get() == 0 {
-        "0".to_string()
-    } else {
-        format!(
-            "{}",
-            result.amount_out().get() as f64 / result.amount_in().get() as f64
-        )
-    };
-
-    let price_after_quote = if spot_price == 0.0 {
-        0.0
-    } else {
-        result.amount_out().get() as f64 / result.amount_in().get() as f64
-    };
-
-    let price_impact_bps = if spot_price == 0.0 {
-        0
-    } else {
-        #[allow(clippy::cast_possible_truncation)]
-        {
-            ((price_after_quote - spot_price) / spot_price * 10_000.0) as i32
-        }
+    let decimals = TradeDecimals {
+        input: decimals_in,
+        output: decimals_out,
     };
+    let (effective_price, price_impact_bps) = fill_prices(&result, mid_price, decimals);
 
+    let quoted_at = Utc::now();
+    if let Some(audit) = &state.quote_audit {
+        audit.record(QuoteRecord {
+            pool_id: id,
+            client_id: client.0,
+            token_in: req.token_in.clone(),
+            token_out: req.token_out.clone(),
+            amount_in: result.amount_in().get(),
+            amount_out: result.amount_out().get(),
+            fee: result.fee().get(),
+            spot_price,
+            execution_price: pricing::execution_price(
+                result.amount_in().get(),
+                result.amount_out().get(),
+                decimals,
+            )
+            .unwrap_or(0.0),
+            price_impact_bps,
+            pool_version,
+            quoted_at,
+        });
+    }
     Ok(Json(QuoteResponse {
         pool_id,
         token_in: req.token_in,
@@ -185,10 +231,152 @@ pub async fn quote_swap(
         amount_in: result.amount_in().get().to_string(),
         amount_out: result.amount_out().get().to_string(),
         fee_charged: result.fee().get().to_string(),
+        amount_in_decimal: units.decimal(result.amount_in().get(), decimals_in),
+        amount_out_decimal: units.decimal(result.amount_out().get(), decimals_out),
+        fee_charged_decimal: units.decimal(result.fee().get(), decimals_in),
         execution_price: effective_price,
         spot_price: format!("{spot_price}"),
         price_impact_bps,
-        quoted_at: Utc::now(),
+        quoted_at,
+        pool_version,
+        valid_until: quoted_at + chrono::Duration::seconds(QUOTE_VALID_SECS),
+    }))
+}
+
+/// `POST /swap/auto` — Find the best route and optionally execute it.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] on bad parameters or when no
+/// route connects the tokens, [`GatewayError::InsufficientLiquidity`] if
+/// no route can fill the amount, [`GatewayError::SlippageExceeded`] if
+/// execution was requested but the quote is below `min_amount_out`, and
+/// [`GatewayError::RateLimited`] when execution would exceed the caller's
+/// daily swap quota.
+#[utoipa::path(
+    post,
+    path = "/api/v1/swap/auto",
+    tag = "Swaps",
+    summary = "Best-route quote and execute",
+    description = "Finds the best route from `token_in` to `token_out` across all local pools (direct or multi-hop, optionally split across pool-disjoint paths) and returns the plan. With `execute=true` the route is re-quoted under locks on every pool involved and executed atomically, provided the output meets `min_amount_out`. Canary pools are only considered in their weighted share of searches.",
+    params(
+        ("x-client-id" = Option<String>, Header, description = "Calling client; recorded as the event actor"),
+        UnitsParams,
+    ),
+    request_body = AutoSwapRequest,
+    responses(
+        (status = 200, description = "Route found (and executed if requested)", body = AutoSwapResponse),
+        (status = 400, description = "Invalid parameters or no route", body = ErrorResponse),
+        (status = 422, description = "Insufficient liquidity or slippage exceeded", body = ErrorResponse),
+        (status = 429, description = "Swap quota exceeded", body = ErrorResponse),
+    )
+)]
+pub async fn auto_swap(
+    State(state): State<AppState>,
+    Query(units): Query<UnitsParams>,
+    client: ClientId,
+    Json(req): Json<AutoSwapRequest>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let amount_in: u128 = req.amount_in.parse().map_err(|_| {
+        GatewayError::InvalidRequest(format!("invalid amount_in: {}", req.amount_in))
+    })?;
+    if amount_in == 0 {
+        return Err(GatewayError::InvalidRequest(
+            "amount_in must be positive".to_string(),
+        ));
+    }
+    let min_amount_out: u128 = match &req.min_amount_out {
+        Some(min) => min
+            .parse()
+            .map_err(|_| GatewayError::InvalidRequest(format!("invalid min_amount_out: {min}")))?,
+        None => 0,
+    };
+    let max_hops = req.max_hops.map_or(DEFAULT_MAX_HOPS, usize::from);
+    if !(1..=MAX_HOPS_LIMIT).contains(&max_hops) {
+        return Err(GatewayError::InvalidRequest(format!(
+            "max_hops must be between 1 and {MAX_HOPS_LIMIT}"
+        )));
+    }
+
+    let hop_cost = match &req.hop_cost {
+        Some(cost) => Some(PerHopCost {
+            per_hop: cost
+                .parse()
+                .map_err(|_| GatewayError::InvalidRequest(format!("invalid hop_cost: {cost}")))?,
+        }),
+        None => None,
+    };
+
+    let mut seeds = state.pool_service.route_seeds().await;
+    let roll = uuid::Uuid::new_v4().as_u128() % u128::from(FULL_CANARY_WEIGHT_BPS);
+    routing::admit_canaries(&mut seeds, u32::try_from(roll).unwrap_or(0));
+    let options = RouteOptions {
+        max_hops,
+        allow_split: req.allow_split,
+    };
+    let cost: Arc<dyn CostModel> = match hop_cost {
+        Some(cost) => Arc::new(cost),
+        None => Arc::clone(&state.route_cost),
+    };
+    let (token_in, token_out) = (req.token_in.clone(), req.token_out.clone());
+    let plan = state
+        .quote_runtime
+        .compute(move || {
+            routing::best_route(
+                &seeds,
+                &token_in,
+                &token_out,
+                amount_in,
+                options,
+                cost.as_ref(),
+            )
+        })
+        .await??;
+
+    let (decimals_in, decimals_out) = if units.units == Units::Raw {
+        (None, None)
+    } else {
+        let known = state.pool_service.known_tokens(None).await;
+        let decimals = |address: &str| {
+            known
+                .iter()
+                .find(|t| t.address.eq_ignore_ascii_case(address))
+                .map(|t| t.decimals)
+        };
+        (decimals(&req.token_in), decimals(&req.token_out))
+    };
+    let route_dto = |plan: &RoutePlan| {
+        RoutePlanDto::from(plan).with_units(plan, units, decimals_in, decimals_out)
+    };
+
+    if !req.execute {
+        return Ok(Json(AutoSwapResponse {
+            route: route_dto(&plan),
+            executed: false,
+            swap_id: None,
+            timestamp: Utc::now(),
+        }));
+    }
+
+    if plan.amount_out < min_amount_out {
+        return Err(GatewayError::SlippageExceeded {
+            bound: "min_amount_out",
+            limit: min_amount_out.to_string(),
+            quoted: plan.amount_out.to_string(),
+        });
+    }
+    state.quotas.check_swap(client.as_deref(), Utc::now())?;
+    let command_id = uuid::Uuid::new_v4().to_string();
+    let executed = state
+        .pool_service
+        .execute_route(&plan, min_amount_out, &command_id, client.as_deref())
+        .await?;
+
+    Ok(Json(AutoSwapResponse {
+        route: route_dto(&executed),
+        executed: true,
+        swap_id: Some(command_id),
+        timestamp: Utc::now(),
     }))
 }
 
@@ -197,27 +385,62 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/pools/{id}/swap", post(execute_swap))
         .route("/pools/{id}/quote", post(quote_swap))
+        .route("/swap/auto", post(auto_swap))
+}
+
+/// Decimals of the input and output tokens of a swap on `entry`.
+fn swap_decimals(entry: &PoolEntry, token_in: Token) -> (Option<u8>, Option<u8>) {
+    TokenSide::of(entry.pool_box.token_pair(), token_in).map_or((None, None), |side| {
+        (entry.decimals_of(side), entry.decimals_of(side.other()))
+    })
+}
+
+/// Execution price (formatted) and price impact of a fill against the
+/// pre-trade `mid_price` in the swap's direction.
+fn fill_prices(
+    result: &SwapResult,
+    mid_price: Option<f64>,
+    decimals: TradeDecimals,
+) -> (String, i32) {
+    let (amount_in, amount_out, fee) = (
+        result.amount_in().get(),
+        result.amount_out().get(),
+        result.fee().get(),
+    );
+    let execution = pricing::execution_price(amount_in, amount_out, decimals).unwrap_or(0.0);
+    let impact = pricing::price_impact_bps(mid_price, amount_in, fee, amount_out, decimals);
+    (format!("{execution}"), impact)
+}
+
+/// Parses a [`SwapRequest`] into a swap kind, fixed amount, and input [`Token`].
+/// Parses an optional slippage bound (string-encoded u128).
+fn parse_bound(field: &str, value: Option<&str>) -> Result<Option<u128>, GatewayError> {
+    value
+        .map(|raw| {
+            raw.parse::<u128>()
+                .map_err(|_| GatewayError::InvalidRequest(format!("invalid {field}: {raw}")))
+        })
+        .transpose()
 }
 
-/// Parses a [`SwapRequest`] into a hydra-amm [`SwapSpec`] and input [`Token`].
 async fn parse_swap_request(
     state: &AppState,
     pool_id: PoolId,
     req: &SwapRequest,
-) -> Result<(SwapSpec, Token), GatewayError> {
+) -> Result<(SwapKind, Amount, Token), GatewayError> {
     // Determine exact-in vs exact-out
-    let spec = match (&req.amount_in, &req.amount_out) {
+    let (kind, amount) = match (&req.amount_in, &req.amount_out) {
         (Some(amt_in), None) => {
             let amount: u128 = amt_in.parse().map_err(|_| {
                 GatewayError::InvalidRequest(format!("invalid amount_in: {amt_in}"))
             })?;
-            SwapSpec::exact_in(Amount::new(amount))?
+            (SwapKind::ExactIn, Amount::new(amount))
         }
         (None, Some(amt_out)) => {
             let amount: u128 = amt_out.parse().map_err(|_| {
                 GatewayError::InvalidRequest(format!("invalid amount_out: {amt_out}"))
             })?;
-            SwapSpec::exact_out(Amount::new(amount))?
+            (SwapKind::ExactOut, Amount::new(amount))
         }
         (Some(_), Some(_)) => {
             return Err(GatewayError::InvalidRequest(
@@ -259,5 +482,8 @@ async fn parse_swap_request(
         )));
     };
 
-    Ok((spec, token_in))
+    // Reject zero and other amounts hydra-amm refuses before locking.
+    kind.spec(amount)?;
+
+    Ok((kind, amount, token_in))
 }
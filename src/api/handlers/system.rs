@@ -1,5 +1,6 @@
 //! System endpoints: health check, pool types, admin.
 
+use axum::extract::State;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::routing::get;
@@ -9,6 +10,8 @@ use serde::Serialize;
 use utoipa::ToSchema;
 
 use crate::app_state::AppState;
+use crate::domain::pool_capabilities::PoolCapabilities;
+use crate::service::pool_defaults::PoolDefaults;
 
 /// Health check response.
 #[derive(Debug, Serialize, ToSchema)]
@@ -47,6 +50,9 @@ struct PoolTypeInfo {
     description: &'static str,
     multi_token: bool,
     tick_based: bool,
+    capabilities: PoolCapabilities,
+    /// Config fields filled in when a create request leaves them out.
+    defaults: PoolDefaults,
 }
 
 /// `GET /config/pool-types` — List supported pool types.
@@ -55,56 +61,120 @@ struct PoolTypeInfo {
     path = "/config/pool-types",
     tag = "System",
     summary = "List supported pool types",
-    description = "Returns metadata for every AMM pool type the gateway can create.",
+    description = "Returns metadata for every AMM pool type the gateway can create, with the config defaults this deployment fills in when a create request leaves them out (`POOL_DEFAULT_*`).",
     responses(
         (status = 200, description = "Pool type catalog", body = Vec<PoolTypeInfo>),
     )
 )]
-pub async fn pool_types_handler() -> impl IntoResponse {
+pub async fn pool_types_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let defaults = state.pool_defaults;
     let types = vec![
         PoolTypeInfo {
             pool_type: "constant_product",
             description: "Uniswap V2 style (x · y = k)",
             multi_token: false,
             tick_based: false,
+            capabilities: PoolCapabilities::of("constant_product"),
+            defaults: defaults.for_type("constant_product"),
         },
         PoolTypeInfo {
             pool_type: "clmm",
             description: "Concentrated Liquidity (Uniswap V3 style)",
             multi_token: false,
             tick_based: true,
+            capabilities: PoolCapabilities::of("clmm"),
+            defaults: defaults.for_type("clmm"),
         },
         PoolTypeInfo {
             pool_type: "hybrid",
             description: "Curve-style StableSwap with amplification",
             multi_token: false,
             tick_based: false,
+            capabilities: PoolCapabilities::of("hybrid"),
+            defaults: defaults.for_type("hybrid"),
         },
         PoolTypeInfo {
             pool_type: "weighted",
             description: "Balancer-style weighted multi-token pools",
             multi_token: true,
             tick_based: false,
+            capabilities: PoolCapabilities::of("weighted"),
+            defaults: defaults.for_type("weighted"),
         },
         PoolTypeInfo {
             pool_type: "dynamic",
             description: "DODO-style Proactive Market Maker (oracle-driven)",
             multi_token: false,
             tick_based: false,
+            capabilities: PoolCapabilities::of("dynamic"),
+            defaults: defaults.for_type("dynamic"),
         },
         PoolTypeInfo {
             pool_type: "orderbook",
             description: "Phoenix-style CLOB + AMM hybrid",
             multi_token: false,
             tick_based: false,
+            capabilities: PoolCapabilities::of("orderbook"),
+            defaults: defaults.for_type("orderbook"),
         },
     ];
     (StatusCode::OK, Json(types))
 }
 
+/// Public key used to verify event attestations.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AttestationKeyResponse {
+    /// Whether emitted events carry attestations.
+    pub enabled: bool,
+    /// Signature algorithm.
+    pub algorithm: &'static str,
+    /// Key id found in each event's `attestation.key_id`.
+    pub key_id: Option<String>,
+    /// Hex-encoded Ed25519 public key.
+    pub public_key: Option<String>,
+}
+
+/// `GET /attestation/key` — Event attestation public key.
+#[utoipa::path(
+    get,
+    path = "/attestation/key",
+    tag = "System",
+    summary = "Event attestation key",
+    description = "Returns the Ed25519 public key and key id that event attestations are signed with. Each attested event carries `attestation.signature`, a hex signature over the event's compact JSON with keys sorted and the `attestation` field removed.",
+    responses(
+        (status = 200, description = "Attestation key", body = AttestationKeyResponse),
+    )
+)]
+pub async fn attestation_key_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let signer = state.pool_service.event_signer();
+    Json(AttestationKeyResponse {
+        enabled: signer.is_some(),
+        algorithm: "ed25519",
+        key_id: signer.map(|s| s.key_id().to_string()),
+        public_key: signer.map(|s| s.public_key_hex()),
+    })
+}
+
+/// `GET /api-docs/asyncapi.json` — AsyncAPI document for the WebSocket protocol.
+#[utoipa::path(
+    get,
+    path = "/api-docs/asyncapi.json",
+    tag = "System",
+    summary = "WebSocket AsyncAPI document",
+    description = "Returns an AsyncAPI 2.6 document describing the `/ws` endpoint: the message envelope, client commands, pool and trade events, job frames, and error codes. Schemas are generated from the gateway's Rust types.",
+    responses(
+        (status = 200, description = "AsyncAPI document", body = Object),
+    )
+)]
+pub async fn asyncapi_handler() -> impl IntoResponse {
+    Json(crate::ws::asyncapi::document())
+}
+
 /// System routes mounted at the root level (not under /api/v1).
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/health", get(health_handler))
         .route("/config/pool-types", get(pool_types_handler))
+        .route("/attestation/key", get(attestation_key_handler))
+        .route("/api-docs/asyncapi.json", get(asyncapi_handler))
 }
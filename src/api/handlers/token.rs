@@ -0,0 +1,96 @@
+//! Token discovery handlers.
+
+use axum::extract::{Path, Query, State};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Json, Router};
+
+use crate::api::dto::{
+    KnownTokenDto, PaginationParams, TokenDto, TokenListParams, TokenListResponse, TokenPoolDto,
+    TokenPoolsResponse,
+};
+use crate::app_state::AppState;
+use crate::error::GatewayError;
+
+/// `GET /tokens` — List every distinct token across pools.
+///
+/// # Errors
+///
+/// Never fails; returns an empty page when no pools exist.
+#[utoipa::path(
+    get,
+    path = "/api/v1/tokens",
+    tag = "Tokens",
+    summary = "List known tokens",
+    description = "Enumerates every distinct token across registered pools with decimals, symbol (if registered), number of pools, and total reserves. Filter by symbol prefix with `symbol`.",
+    params(PaginationParams, TokenListParams),
+    responses(
+        (status = 200, description = "Paginated token list", body = TokenListResponse),
+    )
+)]
+pub async fn list_tokens(
+    State(state): State<AppState>,
+    Query(params): Query<PaginationParams>,
+    Query(filter): Query<TokenListParams>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let tokens = state
+        .pool_service
+        .known_tokens(filter.symbol.as_deref())
+        .await;
+    let (page, pagination) = params.paginate(tokens);
+
+    Ok(Json(TokenListResponse {
+        data: page.into_iter().map(KnownTokenDto::from).collect(),
+        pagination,
+    }))
+}
+
+/// `GET /tokens/:address/pools` — List pools that hold a token.
+///
+/// # Errors
+///
+/// Never fails; an unknown token yields an empty list.
+#[utoipa::path(
+    get,
+    path = "/api/v1/tokens/{address}/pools",
+    tag = "Tokens",
+    summary = "Find pools by token",
+    description = "Lists every pool on this instance that contains the token, with pool type, fee tier, liquidity, and spot price. Addresses are matched case-insensitively.",
+    params(
+        ("address" = String, Path, description = "Token address as given at pool creation"),
+    ),
+    responses(
+        (status = 200, description = "Pools holding the token", body = TokenPoolsResponse),
+    )
+)]
+pub async fn token_pools(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let data = state
+        .pool_service
+        .pools_with_token(&address)
+        .await
+        .into_iter()
+        .map(|p| TokenPoolDto {
+            pool_id: p.pool_id,
+            pool_type: p.pool_type,
+            fee_bps: p.fee_bps,
+            total_liquidity: p.total_liquidity.to_string(),
+            spot_price: p.spot_price.map(|v| format!("{v}")),
+            tokens: p.tokens.iter().map(TokenDto::from).collect(),
+        })
+        .collect();
+
+    Ok(Json(TokenPoolsResponse {
+        token: address,
+        data,
+    }))
+}
+
+/// Token routes.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/tokens", get(list_tokens))
+        .route("/tokens/{address}/pools", get(token_pools))
+}
@@ -0,0 +1,56 @@
+//! Trade tape handlers.
+
+use axum::extract::{Query, State};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Json, Router};
+
+use crate::api::dto::{TradeDto, TradeListParams, TradeListResponse};
+use crate::app_state::AppState;
+use crate::domain::PoolId;
+use crate::error::{ErrorResponse, GatewayError};
+use crate::service::trade_tape::TAPE_CAPACITY;
+
+/// Trades returned when no `limit` is given.
+const DEFAULT_TRADE_LIMIT: usize = 100;
+
+/// `GET /trades` — Recent trades across all pools.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] if `limit` is out of range.
+#[utoipa::path(
+    get,
+    path = "/api/v1/trades",
+    tag = "Market Data",
+    summary = "Recent trades",
+    description = "Returns the most recent swaps on this instance as a trade tape, newest first: price in quote per base (per each pool's price convention), base size, and taker side. The tape keeps the last 1000 trades in memory; filter to one pool with `pool_id`.",
+    params(TradeListParams),
+    responses(
+        (status = 200, description = "Recent trades", body = TradeListResponse),
+        (status = 400, description = "Invalid limit", body = ErrorResponse),
+    )
+)]
+pub async fn list_trades(
+    State(state): State<AppState>,
+    Query(params): Query<TradeListParams>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let limit = params.limit.unwrap_or(DEFAULT_TRADE_LIMIT);
+    if !(1..=TAPE_CAPACITY).contains(&limit) {
+        return Err(GatewayError::InvalidRequest(format!(
+            "limit must be between 1 and {TAPE_CAPACITY}"
+        )));
+    }
+    let trades = state
+        .trade_tape
+        .recent(limit, params.pool_id.map(PoolId::from_uuid));
+
+    Ok(Json(TradeListResponse {
+        data: trades.iter().map(TradeDto::from).collect(),
+    }))
+}
+
+/// Trade tape routes.
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/trades", get(list_trades))
+}
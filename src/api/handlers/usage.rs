@@ -0,0 +1,74 @@
+//! Usage reporting for the calling client.
+
+use axum::extract::{Query, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, Duration, Utc};
+
+use crate::api::client_id::{CLIENT_ID_HEADER, ClientId};
+use crate::api::dto::{UsageRangeParams, UsageResponse};
+use crate::app_state::AppState;
+use crate::error::{ErrorResponse, GatewayError};
+
+/// `GET /accounts/me/usage` — Usage of the calling client.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] if the request carries no
+/// client id, or `from` is not before `to`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/accounts/me/usage",
+    tag = "Usage",
+    summary = "Own usage",
+    description = "Returns the calling client's requests, swaps with their input volume per token, and WebSocket bytes in each direction, in hourly buckets whose start falls within [from, to) (default: the last day). Usage is counted by the instance that served it and kept for `USAGE_RETENTION_DAYS`.",
+    params(
+        UsageRangeParams,
+        ("x-client-id" = String, Header, description = "Calling client; usage is tracked per client"),
+    ),
+    responses(
+        (status = 200, description = "Usage of the caller", body = UsageResponse),
+        (status = 400, description = "Missing client id or invalid time range", body = ErrorResponse),
+    )
+)]
+pub async fn my_usage(
+    State(state): State<AppState>,
+    client: ClientId,
+    Query(params): Query<UsageRangeParams>,
+) -> Result<Json<UsageResponse>, GatewayError> {
+    let client_id = client.0.ok_or_else(|| {
+        GatewayError::InvalidRequest(format!(
+            "usage is tracked per client; send {CLIENT_ID_HEADER}"
+        ))
+    })?;
+    let (from, to) = usage_range(params.from, params.to)?;
+    Ok(Json(UsageResponse {
+        from,
+        to,
+        usage: state.usage.report(&client_id, from, to).into(),
+    }))
+}
+
+/// Resolves a usage range, defaulting to the day before now.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] if `from` is not before `to`.
+pub(crate) fn usage_range(
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), GatewayError> {
+    let to = to.unwrap_or_else(Utc::now);
+    let from = from.unwrap_or_else(|| to - Duration::days(1));
+    if from >= to {
+        return Err(GatewayError::InvalidRequest(
+            "from must be before to".to_string(),
+        ));
+    }
+    Ok((from, to))
+}
+
+/// Usage routes.
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/accounts/me/usage", get(my_usage))
+}
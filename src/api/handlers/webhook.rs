@@ -0,0 +1,264 @@
+//! Pool webhook handlers: attach, list, detach, and delivery logs.
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::{delete, get};
+use axum::{Json, Router};
+use chrono::Utc;
+
+use crate::api::dto::{
+    CreateWebhookRequest, CreateWebhookResponse, WebhookDeliveriesResponse, WebhookDeliveryDto,
+    WebhookDto, WebhookListResponse,
+};
+use crate::app_state::AppState;
+use crate::domain::PoolId;
+use crate::error::{ErrorResponse, GatewayError};
+use crate::service::webhooks::{self, EVENT_TYPES, Webhook};
+
+/// Accepted secret lengths.
+const SECRET_LEN: std::ops::RangeInclusive<usize> = 16..=256;
+
+/// Validates a create request into a webhook for `pool_id`.
+fn build_webhook(pool_id: PoolId, req: CreateWebhookRequest) -> Result<Webhook, GatewayError> {
+    let url = req.url.trim();
+    if !(url.starts_with("http://") || url.starts_with("https://"))
+        || url.parse::<reqwest::Url>().is_err()
+    {
+        return Err(GatewayError::InvalidRequest(format!(
+            "invalid webhook url: {url}"
+        )));
+    }
+    if let Some(unknown) = req
+        .event_types
+        .iter()
+        .find(|t| !EVENT_TYPES.contains(&t.as_str()))
+    {
+        return Err(GatewayError::InvalidRequest(format!(
+            "unknown event type: {unknown}"
+        )));
+    }
+    let min_amount = req
+        .min_amount
+        .as_deref()
+        .map(|m| {
+            m.parse::<u128>()
+                .map_err(|_| GatewayError::InvalidRequest(format!("invalid min_amount: {m}")))
+        })
+        .transpose()?;
+    let secret = match req.secret {
+        Some(secret) if SECRET_LEN.contains(&secret.len()) => secret,
+        Some(_) => {
+            return Err(GatewayError::InvalidRequest(format!(
+                "secret must be {}-{} characters",
+                SECRET_LEN.start(),
+                SECRET_LEN.end()
+            )));
+        }
+        None => webhooks::generate_secret(),
+    };
+    let mut event_types = req.event_types;
+    event_types.sort();
+    event_types.dedup();
+    Ok(Webhook {
+        id: uuid::Uuid::new_v4(),
+        pool_id,
+        url: url.to_string(),
+        event_types,
+        min_amount,
+        secret,
+        created_at: Utc::now(),
+    })
+}
+
+/// `POST /pools/:id/webhooks` — Attach a webhook to a pool.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+/// [`GatewayError::InvalidRequest`] for a bad URL, event type, threshold,
+/// or secret, [`GatewayError::CapacityExceeded`] if the pool has the
+/// maximum number of webhooks, [`GatewayError::ReadOnlyReplica`] on a
+/// replica, or [`GatewayError::PersistenceError`] if saving fails.
+#[utoipa::path(
+    post,
+    path = "/api/v1/pools/{id}/webhooks",
+    tag = "Webhooks",
+    summary = "Attach a webhook",
+    description = "Attaches a webhook to the pool. Matching events are POSTed to `url` in the shape WebSocket clients receive, signed with `X-Hydra-Signature` (hex HMAC-SHA256 of `\"{X-Hydra-Timestamp}\\n{body}\"` under the secret). `event_types` and `min_amount` narrow which events are sent. The secret is returned only in this response. A pool may carry 10 webhooks; they are removed with the pool.",
+    params(("id" = uuid::Uuid, Path, description = "Pool UUID")),
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 201, description = "Webhook attached", body = CreateWebhookResponse),
+        (status = 400, description = "Invalid webhook", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+        (status = 422, description = "Pool has the maximum number of webhooks", body = ErrorResponse),
+        (status = 503, description = "Read-only replica", body = ErrorResponse),
+    )
+)]
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    Json(req): Json<CreateWebhookRequest>,
+) -> Result<impl IntoResponse, GatewayError> {
+    if state.pool_service.is_read_only() {
+        return Err(GatewayError::ReadOnlyReplica);
+    }
+    let pool_id = PoolId::from_uuid(id);
+    state.pool_service.registry().get(pool_id).await?;
+    let webhook = build_webhook(pool_id, req)?;
+
+    state.webhooks.add(webhook.clone())?;
+    let persisted = match state.persistence.as_ref() {
+        Some(persistence) => {
+            if let Err(e) = persistence.save_webhook(&webhook.to_record()).await {
+                state.webhooks.remove(pool_id, webhook.id);
+                return Err(e);
+            }
+            true
+        }
+        None => false,
+    };
+
+    tracing::info!(%pool_id, webhook = %webhook.id, persisted, "webhook attached");
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateWebhookResponse {
+            webhook: WebhookDto::from(&webhook),
+            secret: webhook.secret,
+            persisted,
+        }),
+    ))
+}
+
+/// `GET /pools/:id/webhooks` — List a pool's webhooks.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::PoolNotFound`] if the pool does not exist.
+#[utoipa::path(
+    get,
+    path = "/api/v1/pools/{id}/webhooks",
+    tag = "Webhooks",
+    summary = "List webhooks",
+    description = "Lists the webhooks attached to the pool, oldest first. Secrets are not included.",
+    params(("id" = uuid::Uuid, Path, description = "Pool UUID")),
+    responses(
+        (status = 200, description = "Webhooks of the pool", body = WebhookListResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+    )
+)]
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let pool_id = PoolId::from_uuid(id);
+    state.pool_service.registry().get(pool_id).await?;
+
+    Ok(Json(WebhookListResponse {
+        data: state
+            .webhooks
+            .list(pool_id)
+            .iter()
+            .map(WebhookDto::from)
+            .collect(),
+    }))
+}
+
+/// `DELETE /pools/:id/webhooks/:webhook_id` — Detach a webhook.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::WebhookNotFound`] if the pool has no such
+/// webhook, [`GatewayError::ReadOnlyReplica`] on a replica, or
+/// [`GatewayError::PersistenceError`] if deleting the row fails.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/pools/{id}/webhooks/{webhook_id}",
+    tag = "Webhooks",
+    summary = "Detach a webhook",
+    description = "Detaches the webhook from the pool and discards its delivery log. Deliveries already in flight still complete.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("webhook_id" = uuid::Uuid, Path, description = "Webhook UUID"),
+    ),
+    responses(
+        (status = 204, description = "Webhook detached"),
+        (status = 404, description = "Webhook not found", body = ErrorResponse),
+        (status = 503, description = "Read-only replica", body = ErrorResponse),
+    )
+)]
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Path((id, webhook_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<impl IntoResponse, GatewayError> {
+    if state.pool_service.is_read_only() {
+        return Err(GatewayError::ReadOnlyReplica);
+    }
+    let pool_id = PoolId::from_uuid(id);
+    state
+        .webhooks
+        .remove(pool_id, webhook_id)
+        .ok_or(GatewayError::WebhookNotFound(webhook_id))?;
+    if let Some(persistence) = state.persistence.as_ref() {
+        persistence.delete_webhooks(id, Some(webhook_id)).await?;
+    }
+
+    tracing::info!(%pool_id, webhook = %webhook_id, "webhook detached");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `GET /pools/:id/webhooks/:webhook_id/deliveries` — Recent deliveries.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::WebhookNotFound`] if the pool has no such
+/// webhook.
+#[utoipa::path(
+    get,
+    path = "/api/v1/pools/{id}/webhooks/{webhook_id}/deliveries",
+    tag = "Webhooks",
+    summary = "Webhook delivery log",
+    description = "Returns the last 50 deliveries of the webhook, newest first: attempts made (up to 3), the last HTTP status or error, and whether it was delivered. The log is kept in memory by the instance that delivered.",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Pool UUID"),
+        ("webhook_id" = uuid::Uuid, Path, description = "Webhook UUID"),
+    ),
+    responses(
+        (status = 200, description = "Recent deliveries", body = WebhookDeliveriesResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse),
+    )
+)]
+pub async fn webhook_deliveries(
+    State(state): State<AppState>,
+    Path((id, webhook_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let deliveries = state
+        .webhooks
+        .deliveries(PoolId::from_uuid(id), webhook_id)
+        .ok_or(GatewayError::WebhookNotFound(webhook_id))?;
+
+    Ok(Json(WebhookDeliveriesResponse {
+        webhook_id,
+        data: deliveries
+            .into_iter()
+            .map(WebhookDeliveryDto::from)
+            .collect(),
+    }))
+}
+
+/// Pool webhook routes.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/pools/{id}/webhooks",
+            get(list_webhooks).post(create_webhook),
+        )
+        .route("/pools/{id}/webhooks/{webhook_id}", delete(delete_webhook))
+        .route(
+            "/pools/{id}/webhooks/{webhook_id}/deliveries",
+            get(webhook_deliveries),
+        )
+}
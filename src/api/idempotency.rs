@@ -0,0 +1,163 @@
+//! `Idempotency-Key` handling for swaps and liquidity changes.
+//!
+//! A `POST` to one of [`IDEMPOTENT_ROUTES`] with an `Idempotency-Key`
+//! header runs once per key: retries of the same request get the stored
+//! response back, marked with `Idempotent-Replayed: true`. See
+//! [`crate::service::idempotency`].
+
+use axum::body::Body;
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::{HeaderValue, Method, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::Response;
+use chrono::Utc;
+
+use super::client_id::CLIENT_ID_HEADER;
+use crate::app_state::AppState;
+use crate::error::GatewayError;
+use crate::persistence::models::IdempotencyRecord;
+use crate::service::idempotency::{self, Begin};
+
+/// Header carrying the client-chosen key.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// Header set on replayed responses.
+pub const REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Routes whose `POST` honours `Idempotency-Key`.
+pub const IDEMPOTENT_ROUTES: [&str; 4] = [
+    "/api/v1/pools/{id}/swap",
+    "/api/v1/pools/{id}/liquidity/add",
+    "/api/v1/pools/{id}/liquidity/remove",
+    "/api/v1/pools/{id}/fees/collect",
+];
+
+/// Longest accepted key.
+const MAX_KEY_LEN: usize = 255;
+
+/// Largest request or response body buffered (1 MiB).
+const MAX_BODY: usize = 1024 * 1024;
+
+/// Axum middleware running each idempotent request at most once per key.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] for a malformed key,
+/// [`GatewayError::IdempotencyKeyReused`] if the key was used for a
+/// different request, and [`GatewayError::IdempotencyKeyInProgress`]
+/// while the first request with the key is running.
+pub async fn idempotent_mutations(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, GatewayError> {
+    let idempotent = req.method() == Method::POST
+        && req
+            .extensions()
+            .get::<MatchedPath>()
+            .is_some_and(|route| IDEMPOTENT_ROUTES.contains(&route.as_str()));
+    let Some(key) = req
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .filter(|_| idempotent)
+    else {
+        return Ok(next.run(req).await);
+    };
+    let key = parse_key(key)?;
+    let client_id = req
+        .headers()
+        .get(CLIENT_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let path_and_query = req
+        .uri()
+        .path_and_query()
+        .map_or_else(|| req.uri().path().to_string(), ToString::to_string);
+    let (parts, body) = req.into_parts();
+    let body = axum::body::to_bytes(body, MAX_BODY)
+        .await
+        .map_err(|e| GatewayError::InvalidRequest(format!("unreadable body: {e}")))?;
+    let fingerprint = idempotency::fingerprint(parts.method.as_str(), &path_and_query, &body);
+
+    let store = &state.idempotency;
+    let now = Utc::now();
+    let reservation = match store.begin(&client_id, &key, &fingerprint, now)? {
+        Begin::Replay(record) => return Ok(replay(&record)),
+        Begin::Reserved(reservation) => reservation,
+    };
+    if let Some(db) = store.persistence() {
+        match db
+            .load_idempotency_record(&client_id, &key, store.cutoff(now))
+            .await
+        {
+            Ok(Some(record)) => {
+                let record = reservation.replay(record)?;
+                return Ok(replay(&record));
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(error = %e, "idempotency key lookup failed"),
+        }
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    if !response.status().is_success() {
+        // Dropping the reservation frees the key for a retry
+        return Ok(response);
+    }
+    let (parts, body) = response.into_parts();
+    let body = axum::body::to_bytes(body, MAX_BODY)
+        .await
+        .map_err(|e| GatewayError::Internal(format!("unreadable response: {e}")))?;
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let record = reservation.complete(
+        parts.status.as_u16(),
+        content_type,
+        body.to_vec(),
+        Utc::now(),
+    );
+    if let Some(db) = store.persistence()
+        && let Err(e) = db.save_idempotency_record(&record).await
+    {
+        tracing::warn!(error = %e, "failed to persist idempotency key");
+    }
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+/// Validates an `Idempotency-Key`: 1–255 visible ASCII characters.
+fn parse_key(value: &HeaderValue) -> Result<String, GatewayError> {
+    value
+        .to_str()
+        .ok()
+        .filter(|key| {
+            (1..=MAX_KEY_LEN).contains(&key.len()) && key.bytes().all(|b| b.is_ascii_graphic())
+        })
+        .map(str::to_string)
+        .ok_or_else(|| {
+            GatewayError::InvalidRequest(format!(
+                "Idempotency-Key must be 1-{MAX_KEY_LEN} visible ASCII characters"
+            ))
+        })
+}
+
+/// Rebuilds a stored response.
+fn replay(record: &IdempotencyRecord) -> Response {
+    let mut response = Response::new(Body::from(record.body.clone()));
+    *response.status_mut() = StatusCode::from_u16(record.status).unwrap_or(StatusCode::OK);
+    if let Some(content_type) = record
+        .content_type
+        .as_deref()
+        .and_then(|v| HeaderValue::from_str(v).ok())
+    {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, content_type);
+    }
+    response
+        .headers_mut()
+        .insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
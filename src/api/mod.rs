@@ -2,8 +2,16 @@
 //!
 //! All endpoints are mounted under `/api/v1`.
 
+pub mod admin_auth;
+pub mod client_id;
+pub mod config_parser;
 pub mod dto;
 pub mod handlers;
+pub mod idempotency;
+pub mod numeric;
+pub mod rate_limit;
+pub mod server_timing;
+pub mod signing;
 
 use axum::Router;
 use utoipa::OpenApi;
@@ -25,40 +33,272 @@ use crate::app_state::AppState;
         (name = "Pools", description = "Pool CRUD operations"),
         (name = "Swaps", description = "Token swap execution and quoting"),
         (name = "Liquidity", description = "Liquidity provisioning and withdrawal"),
+        (name = "Order Book", description = "Limit orders and depth snapshots of order-book pools"),
+        (name = "Market Data", description = "Slippage and depth analytics computed on sandbox pools"),
+        (name = "Rewards", description = "Liquidity-mining emissions and reward claims"),
+        (name = "Usage", description = "Per-API-key request, swap, and WebSocket usage"),
+        (name = "Tokens", description = "Token discovery across pools"),
+        (name = "Webhooks", description = "Signed event delivery to URLs attached to a pool"),
+        (name = "Consumers", description = "Durable event-log consumption with named cursors"),
+        (name = "Admin", description = "Operational and disaster-recovery tooling"),
     ),
     paths(
         handlers::system::health_handler,
         handlers::system::pool_types_handler,
+        handlers::system::attestation_key_handler,
+        handlers::system::asyncapi_handler,
+        handlers::error_catalog::list_errors,
         handlers::pool::create_pool,
+        handlers::pool::validate_pool,
         handlers::pool::list_pools,
         handlers::pool::get_pool,
+        handlers::pool::update_pool,
         handlers::pool::delete_pool,
+        handlers::pool::transfer_ownership,
+        handlers::pool::set_oracle_price,
         handlers::swap::execute_swap,
         handlers::swap::quote_swap,
+        handlers::swap::auto_swap,
         handlers::liquidity::add_liquidity,
         handlers::liquidity::remove_liquidity,
+        handlers::liquidity::collect_fees,
+        handlers::liquidity::get_position,
+        handlers::liquidity::open_position,
+        handlers::liquidity::list_positions,
+        handlers::liquidity::close_position,
+        handlers::liquidity::list_providers,
+        handlers::orderbook::place_order,
+        handlers::orderbook::cancel_order,
+        handlers::orderbook::get_orderbook,
+        handlers::rewards::my_rewards,
+        handlers::rewards::claim_rewards,
+        handlers::usage::my_usage,
+        handlers::market::pool_bbo,
+        handlers::market::slippage_curve,
+        handlers::market::depth_chart,
+        handlers::market::pool_volatility,
+        handlers::market::pool_candles,
+        handlers::market::pool_risk,
+        handlers::market::pool_stats,
+        handlers::trade::list_trades,
+        handlers::token::list_tokens,
+        handlers::token::token_pools,
+        handlers::webhook::create_webhook,
+        handlers::webhook::list_webhooks,
+        handlers::webhook::delete_webhook,
+        handlers::webhook::webhook_deliveries,
+        handlers::consumer::consumer_next,
+        handlers::consumer::consumer_ack,
+        handlers::admin::replay_events,
+        handlers::admin::startup_report,
+        handlers::admin::compact_events,
+        handlers::admin::import_events,
+        handlers::admin::list_rate_limits,
+        handlers::admin::update_rate_limit,
+        handlers::admin::list_usage,
+        handlers::admin::list_metadata_schemas,
+        handlers::admin::update_metadata_schema,
+        handlers::admin::delete_metadata_schema,
+        handlers::admin::capacity_stats,
+        handlers::admin::pool_contention,
+        handlers::admin::ws_connections,
+        handlers::admin::set_canary,
+        handlers::admin::promote_canary,
+        handlers::admin::pause_pool,
+        handlers::admin::resume_pool,
+        handlers::admin::list_snapshots,
+        handlers::admin::list_checkpoints,
+        handlers::admin::restore_pool,
+        handlers::admin::list_quotes,
+        handlers::admin::backtest_pool,
+        handlers::admin::set_reward_schedule,
+        handlers::admin::clear_reward_schedule,
+        handlers::admin::list_reward_schedules,
+        handlers::admin::stale_pools,
+        handlers::admin::list_events,
+        handlers::admin::tail_events,
+        handlers::admin::start_job,
+        handlers::admin::list_jobs,
+        handlers::admin::get_job,
+        handlers::admin::cancel_job,
     ),
     components(schemas(
         crate::domain::PoolId,
+        crate::domain::PositionId,
+        crate::domain::OrderId,
+        crate::domain::order_book::OrderSide,
+        crate::domain::pool_entry::PoolStatus,
+        crate::domain::pool_filter::PoolSort,
+        crate::domain::pool_capabilities::PoolCapabilities,
+        crate::service::pool_defaults::PoolDefaults,
         crate::error::ErrorResponse,
         crate::error::ErrorBody,
+        crate::error::FieldError,
+        crate::error::ErrorCatalogEntry,
+        dto::ErrorCatalogResponse,
+        handlers::system::AttestationKeyResponse,
         dto::TokenDto,
         dto::PaginationParams,
+        dto::Units,
+        numeric::NumericMode,
+        dto::UnitsParams,
+        dto::PoolListFilter,
+        dto::DeletePoolParams,
+        dto::DeletedPoolResponse,
         dto::PaginationMeta,
         dto::CreatePoolRequest,
+        dto::PoolConfigDto,
+        dto::ConstantProductConfigDto,
+        dto::ClmmConfigDto,
+        dto::ClmmPositionDto,
+        dto::HybridConfigDto,
+        dto::WeightedConfigDto,
+        dto::WeightedTokenDto,
+        dto::DynamicConfigDto,
+        dto::OrderBookConfigDto,
         dto::CreatePoolResponse,
+        dto::UpdatePoolRequest,
+        dto::PoolMetadataResponse,
+        dto::TransferOwnershipRequest,
+        dto::OwnershipResponse,
+        dto::SetOraclePriceRequest,
+        dto::OraclePriceResponse,
+        dto::ValidatePoolResponse,
+        dto::ConfigIssueDto,
         dto::PoolDetailResponse,
+        dto::PriceConventionDto,
         dto::PoolSummaryDto,
         dto::PoolListResponse,
         dto::SwapRequest,
         dto::SwapResponse,
         dto::QuoteResponse,
+        dto::AutoSwapRequest,
+        dto::AutoSwapResponse,
+        dto::RoutePlanDto,
+        dto::RouteLegDto,
+        dto::RouteHopDto,
+        dto::RouteTotalsDto,
         dto::AddLiquidityRequest,
         dto::AddLiquidityResponse,
         dto::RemoveLiquidityRequest,
         dto::RemoveLiquidityResponse,
         dto::CollectFeesRequest,
         dto::CollectFeesResponse,
+        dto::PositionResponse,
+        dto::OpenPositionRequest,
+        dto::PositionListResponse,
+        dto::ClosePositionResponse,
+        dto::ProviderDto,
+        dto::ProviderListResponse,
+        dto::SlippageCurveParams,
+        dto::SlippageCurvePointDto,
+        dto::SlippageCurveResponse,
+        dto::BboResponse,
+        crate::service::market_data::BboSource,
+        dto::DepthChartParams,
+        dto::DepthLevelDto,
+        dto::DepthChartResponse,
+        dto::VolatilityParams,
+        dto::VolatilityResponse,
+        dto::CandleParams,
+        dto::CandleDto,
+        dto::CandlesResponse,
+        dto::RiskResponse,
+        dto::PoolStatsResponse,
+        dto::RiskComponentDto,
+        crate::service::risk::RiskFactor,
+        crate::service::risk::RiskLevel,
+        dto::TokenListParams,
+        dto::KnownTokenDto,
+        dto::TradeDto,
+        dto::TradeListResponse,
+        crate::service::trade_tape::TradeSide,
+        dto::TokenListResponse,
+        dto::TokenPoolDto,
+        dto::TokenPoolsResponse,
+        dto::CreateWebhookRequest,
+        dto::WebhookDto,
+        dto::CreateWebhookResponse,
+        dto::WebhookListResponse,
+        dto::WebhookDeliveryDto,
+        dto::WebhookDeliveriesResponse,
+        dto::ConsumerNextParams,
+        dto::ConsumerEventDto,
+        dto::ConsumerBatchResponse,
+        dto::ConsumerAckRequest,
+        dto::ConsumerAckResponse,
+        dto::ReplayRequest,
+        dto::ReplayResponse,
+        dto::StartupReportResponse,
+        dto::ReplayedPoolDto,
+        dto::CompactEventsRequest,
+        dto::CompactEventsResponse,
+        dto::ImportEventsResponse,
+        crate::persistence::compaction::CompactionSummary,
+        crate::persistence::compaction::SwapBucket,
+        dto::RateLimitParams,
+        dto::UpdateRateLimitRequest,
+        dto::RateLimitUsageDto,
+        dto::RateLimitDto,
+        dto::CapacityStatsResponse,
+        dto::PoolContentionResponse,
+        dto::LockHolderDto,
+        dto::OperationLatencyDto,
+        dto::WsConnectionListResponse,
+        dto::WsConnectionDto,
+        dto::SetCanaryRequest,
+        dto::CanaryResponse,
+        dto::PausePoolParams,
+        dto::PoolStatusResponse,
+        dto::SnapshotListParams,
+        dto::SnapshotDto,
+        dto::SnapshotListResponse,
+        dto::CheckpointDto,
+        dto::CheckpointListResponse,
+        dto::RestorePoolParams,
+        dto::RestorePoolResponse,
+        dto::QuoteAuditParams,
+        dto::QuoteAuditDto,
+        dto::QuoteAuditListResponse,
+        dto::BacktestRequest,
+        dto::BacktestRunDto,
+        dto::BacktestResponse,
+        dto::PlaceOrderRequest,
+        dto::OrderResponse,
+        dto::OrderBookParams,
+        dto::PriceLevelDto,
+        dto::OrderBookResponse,
+        dto::RewardDto,
+        dto::RewardsResponse,
+        dto::ClaimRewardsResponse,
+        dto::SetEmissionScheduleRequest,
+        dto::EmissionScheduleDto,
+        dto::EmissionScheduleListResponse,
+        dto::EventLogParams,
+        dto::EventLogResponse,
+        dto::EventTailParams,
+        dto::StalePoolParams,
+        dto::StalePoolDto,
+        dto::StalePoolListResponse,
+        dto::RateLimitListResponse,
+        dto::UpdateRateLimitResponse,
+        dto::UsageParams,
+        dto::UsageRangeParams,
+        dto::UsageTotalsDto,
+        dto::UsageBucketDto,
+        dto::UsageDto,
+        dto::UsageListResponse,
+        dto::UsageResponse,
+        dto::MetadataSchemaDto,
+        dto::MetadataSchemaListResponse,
+        dto::UpdateMetadataSchemaResponse,
+        crate::service::metadata_schema::SchemaScope,
+        dto::StartJobRequest,
+        dto::JobListParams,
+        dto::JobDto,
+        dto::JobListResponse,
+        crate::service::jobs::JobKind,
+        crate::service::jobs::JobStatus,
     ))
 )]
 #[derive(Debug)]
@@ -69,4 +309,5 @@ pub fn build_router() -> Router<AppState> {
     Router::new()
         .nest("/api/v1", handlers::routes())
         .merge(handlers::system::routes())
+        .merge(handlers::admin::routes())
 }
@@ -0,0 +1,403 @@
+//! Per-request numeric serialization modes.
+//!
+//! Token amounts are u128 values and are serialized as decimal strings by
+//! default, since JSON numbers lose precision above 2^53. Clients can pick
+//! another representation per request, with a `numbers` query parameter or
+//! an `Accept` profile (`Accept: application/json; numbers=number`); the
+//! query parameter wins when both are present:
+//!
+//! - `string` (default): amounts stay decimal strings.
+//! - `number`: amounts that fit in 2^53 - 1 become JSON numbers; larger
+//!   ones stay strings.
+//! - `object`: amounts become `{"raw": "...", "decimal": "..."}`, with the
+//!   decimal form taken from the `*_decimal` field the endpoint reports
+//!   under `?units=decimal` (`null` where token decimals are unknown).
+//!
+//! The mode is applied to JSON response bodies and WebSocket event frames
+//! after serialization, so every DTO follows it without per-field code.
+//! Only the fields named in [`AMOUNT_FIELDS`] and the values of the
+//! per-token maps in [`AMOUNT_MAP_FIELDS`] are amounts; prices, ratios,
+//! counters, and identifiers keep their type in every mode.
+//! Payloads carrying an event attestation are left untouched, because the
+//! signature covers their string form.
+
+use axum::body::{Body, to_bytes};
+use axum::extract::Request;
+use axum::http::header::{ACCEPT, CONTENT_LENGTH, CONTENT_TYPE};
+use axum::http::{HeaderMap, Uri};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use utoipa::ToSchema;
+
+use crate::error::GatewayError;
+use crate::service::attestation::ATTESTATION_FIELD;
+
+/// Query parameter and `Accept` parameter selecting the mode.
+pub const NUMBERS_PARAM: &str = "numbers";
+
+/// Largest integer a JSON number carries exactly in common clients.
+pub const MAX_SAFE_INTEGER: u128 = (1 << 53) - 1;
+
+/// Suffix of the fields holding an amount's decimal form.
+const DECIMAL_SUFFIX: &str = "_decimal";
+
+/// Fields holding a string-encoded raw amount: token units, LP units, or
+/// reward units. No other field changes type, however its value looks.
+const AMOUNT_FIELDS: [&str; 48] = [
+    "amount",
+    "amount_a",
+    "amount_a_deposited",
+    "amount_a_returned",
+    "amount_b",
+    "amount_b_deposited",
+    "amount_b_returned",
+    "amount_in",
+    "amount_out",
+    "amount_returned",
+    "base_amount",
+    "base_volume",
+    "execution_cost",
+    "fee",
+    "fee_charged",
+    "fee_rebate",
+    "fee_token_a",
+    "fee_token_b",
+    "fees_24h",
+    "fees_collected",
+    "hop_cost",
+    "liquidity",
+    "liquidity_burned",
+    "liquidity_delta",
+    "liquidity_minted",
+    "max_amount",
+    "max_amount_in",
+    "min_amount",
+    "min_amount_out",
+    "net_amount_out",
+    "new_total_liquidity",
+    "quantity",
+    "quote_amount",
+    "quote_size",
+    "quote_volume",
+    "rate_per_second",
+    "reserve_a",
+    "reserve_b",
+    "shares",
+    "size",
+    "spot_amount_out",
+    "total_liquidity",
+    "total_volume",
+    "tvl",
+    "unattributed_liquidity",
+    "volume",
+    "volume_24h",
+    "volume_7d",
+];
+
+/// Fields holding a map from token address to a string-encoded amount.
+const AMOUNT_MAP_FIELDS: [&str; 3] = ["fees", "reserves", "swap_volume"];
+
+/// How token amounts are represented in JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NumericMode {
+    /// Decimal strings (default).
+    #[default]
+    String,
+    /// JSON numbers up to 2^53 - 1, strings beyond.
+    Number,
+    /// `{"raw": "...", "decimal": "..."}` objects.
+    Object,
+}
+
+impl NumericMode {
+    /// Parses a mode name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InvalidRequest`] for an unknown name.
+    pub fn parse(value: &str) -> Result<Self, GatewayError> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "string" => Ok(Self::String),
+            "number" => Ok(Self::Number),
+            "object" => Ok(Self::Object),
+            other => Err(GatewayError::InvalidRequest(format!(
+                "unknown numbers mode '{other}' (expected string, number, or object)"
+            ))),
+        }
+    }
+
+    /// Reads the mode from the `numbers` query parameter, falling back to
+    /// a `numbers` parameter on the `Accept` header.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InvalidRequest`] for an unknown mode.
+    pub fn from_request(uri: &Uri, headers: &HeaderMap) -> Result<Self, GatewayError> {
+        let from_query = uri.query().and_then(|query| {
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix(NUMBERS_PARAM)?.strip_prefix('='))
+        });
+        let from_accept = || {
+            headers
+                .get_all(ACCEPT)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split([',', ';']))
+                .find_map(|param| {
+                    let (key, value) = param.split_once('=')?;
+                    (key.trim() == NUMBERS_PARAM).then(|| value.trim().trim_matches('"'))
+                })
+        };
+        match from_query.or_else(from_accept) {
+            Some(mode) => Self::parse(mode),
+            None => Ok(Self::String),
+        }
+    }
+
+    /// Rewrites the amounts in `value`. A no-op in string mode.
+    pub fn apply(self, value: &mut Value) {
+        if self == Self::String {
+            return;
+        }
+        match value {
+            Value::Object(map) if !map.contains_key(ATTESTATION_FIELD) => self.apply_fields(map),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.apply(item)),
+            _ => {}
+        }
+    }
+
+    fn apply_fields(self, map: &mut Map<String, Value>) {
+        let amounts: Vec<String> = map
+            .iter()
+            .filter(|(key, value)| AMOUNT_FIELDS.contains(&key.as_str()) && is_amount(value))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in amounts {
+            let decimal = if self == Self::Object {
+                map.remove(&format!("{key}{DECIMAL_SUFFIX}"))
+            } else {
+                None
+            };
+            if let Some(value) = map.get_mut(&key) {
+                self.rewrite(value, decimal);
+            }
+        }
+        for (key, value) in map.iter_mut() {
+            match value {
+                Value::Object(amounts) if AMOUNT_MAP_FIELDS.contains(&key.as_str()) => {
+                    for amount in amounts.values_mut().filter(|v| is_amount(v)) {
+                        self.rewrite(amount, None);
+                    }
+                }
+                Value::Object(_) | Value::Array(_) => self.apply(value),
+                _ => {}
+            }
+        }
+    }
+
+    /// Rewrites one amount, with its decimal form for object mode. In
+    /// number mode an amount beyond 2^53 - 1 stays a string.
+    fn rewrite(self, value: &mut Value, decimal: Option<Value>) {
+        let Value::String(raw) = value else {
+            return;
+        };
+        match self {
+            Self::String => {}
+            Self::Number => {
+                if let Ok(n) = raw.parse::<u64>()
+                    && u128::from(n) <= MAX_SAFE_INTEGER
+                {
+                    *value = Value::from(n);
+                }
+            }
+            Self::Object => {
+                let decimal = decimal.unwrap_or(Value::Null);
+                *value = serde_json::json!({ "raw": raw, "decimal": decimal });
+            }
+        }
+    }
+}
+
+/// Returns `true` if `value` is a string-encoded u128.
+fn is_amount(value: &Value) -> bool {
+    let Value::String(s) = value else {
+        return false;
+    };
+    !s.is_empty() && s.len() <= 39 && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Axum middleware applying the requested [`NumericMode`] to JSON
+/// responses. In object mode the request is answered as if it asked for
+/// `?units=decimal`, so the decimal forms are available.
+pub async fn numeric_mode(mut req: Request, next: Next) -> Response {
+    let mode = match NumericMode::from_request(req.uri(), req.headers()) {
+        Ok(mode) => mode,
+        Err(e) => return e.into_response(),
+    };
+    if mode == NumericMode::String {
+        return next.run(req).await;
+    }
+    if mode == NumericMode::Object
+        && let Some(uri) = with_decimal_units(req.uri())
+    {
+        *req.uri_mut() = uri;
+    }
+
+    let response = next.run(req).await;
+    let is_json = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = to_bytes(body, usize::MAX).await else {
+        return GatewayError::Internal("failed to read response body".to_string()).into_response();
+    };
+    let body = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(mut value) => {
+            mode.apply(&mut value);
+            parts.headers.remove(CONTENT_LENGTH);
+            Body::from(serde_json::to_vec(&value).unwrap_or_default())
+        }
+        Err(_) => Body::from(bytes),
+    };
+    Response::from_parts(parts, body)
+}
+
+/// Adds `units=decimal` to `uri` unless it already selects units.
+fn with_decimal_units(uri: &Uri) -> Option<Uri> {
+    let query = uri.query().unwrap_or_default();
+    if query.split('&').any(|pair| pair.starts_with("units=")) {
+        return None;
+    }
+    let path_and_query = if query.is_empty() {
+        format!("{}?units=decimal", uri.path())
+    } else {
+        format!("{}?{query}&units=decimal", uri.path())
+    };
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(path_and_query.parse().ok()?);
+    Uri::from_parts(parts).ok()
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn modes_rewrite_amounts_but_not_identifiers() {
+        let body = serde_json::json!({
+            "swap_id": "123",
+            "amount_in": "1000",
+            "amount_in_decimal": "1",
+            "amount_out": "340282366920938463463374607431768211455",
+            "execution_price": "1.5",
+            "token_in": "42",
+            "hops": [{ "fee_charged": "3" }],
+            "spot_price": "2",
+            "price": "100",
+            "ratio": "1",
+            "sequence": "7",
+            "reserves": { "0xaaa": "500", "0xbbb": "1.5" },
+        });
+
+        let mut numbers = body.clone();
+        NumericMode::Number.apply(&mut numbers);
+        assert_eq!(
+            numbers.pointer("/amount_in"),
+            Some(&serde_json::json!(1000))
+        );
+        assert_eq!(
+            numbers.pointer("/amount_out"),
+            Some(&serde_json::json!(
+                "340282366920938463463374607431768211455"
+            ))
+        );
+        assert_eq!(
+            numbers.pointer("/hops/0/fee_charged"),
+            Some(&serde_json::json!(3))
+        );
+        assert_eq!(numbers.pointer("/swap_id"), Some(&serde_json::json!("123")));
+        assert_eq!(numbers.pointer("/token_in"), Some(&serde_json::json!("42")));
+        assert_eq!(
+            numbers.pointer("/execution_price"),
+            Some(&serde_json::json!("1.5"))
+        );
+        assert_eq!(
+            numbers.pointer("/amount_in_decimal"),
+            Some(&serde_json::json!("1"))
+        );
+        for untouched in ["/spot_price", "/price", "/ratio", "/sequence"] {
+            assert_eq!(
+                numbers.pointer(untouched),
+                body.pointer(untouched),
+                "{untouched} is not an amount"
+            );
+        }
+        assert_eq!(
+            numbers.pointer("/reserves"),
+            Some(&serde_json::json!({ "0xaaa": 500, "0xbbb": "1.5" }))
+        );
+
+        let mut objects = body.clone();
+        NumericMode::Object.apply(&mut objects);
+        assert_eq!(
+            objects.pointer("/amount_in"),
+            Some(&serde_json::json!({ "raw": "1000", "decimal": "1" }))
+        );
+        assert!(objects.get("amount_in_decimal").is_none());
+        assert_eq!(
+            objects.pointer("/hops/0/fee_charged/decimal"),
+            Some(&Value::Null)
+        );
+
+        let mut signed = serde_json::json!({ "amount_in": "1000", "attestation": {} });
+        NumericMode::Number.apply(&mut signed);
+        assert_eq!(
+            signed.pointer("/amount_in"),
+            Some(&serde_json::json!("1000"))
+        );
+    }
+
+    #[test]
+    fn query_parameter_wins_over_accept_profile() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("application/json; numbers=object"),
+        );
+        let Ok(uri) = "/api/v1/pools?numbers=number".parse::<Uri>() else {
+            panic!("invalid uri");
+        };
+        assert!(matches!(
+            NumericMode::from_request(&uri, &headers),
+            Ok(NumericMode::Number)
+        ));
+        let Ok(uri) = "/api/v1/pools".parse::<Uri>() else {
+            panic!("invalid uri");
+        };
+        assert!(matches!(
+            NumericMode::from_request(&uri, &headers),
+            Ok(NumericMode::Object)
+        ));
+        assert!(matches!(
+            NumericMode::from_request(&uri, &HeaderMap::new()),
+            Ok(NumericMode::String)
+        ));
+        assert!(NumericMode::parse("float").is_err());
+
+        let Some(rewritten) = with_decimal_units(&uri) else {
+            panic!("units not added");
+        };
+        assert_eq!(rewritten.query(), Some("units=decimal"));
+    }
+}
@@ -0,0 +1,86 @@
+//! Per-API-key request quota and per-route rate limit enforcement.
+//!
+//! Requests carrying an `X-Client-Id` with a configured quota are counted
+//! against its requests-per-minute limit before reaching any handler, and
+//! every request spends a token from its caller's bucket on the route
+//! when `ROUTE_RATE_LIMITS` covers it. Swap and WebSocket quotas are
+//! enforced where those are handled. Admitted requests with a client id
+//! are also counted in its usage.
+
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use axum::extract::{ConnectInfo, MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use chrono::Utc;
+
+use super::client_id::CLIENT_ID_HEADER;
+use crate::app_state::AppState;
+use crate::cluster::proxy;
+use crate::error::GatewayError;
+use crate::service::route_limits::Caller;
+
+/// Axum middleware rejecting requests over their key's per-minute quota,
+/// and counting the admitted ones in the key's usage.
+///
+/// Requests forwarded by a cluster peer, proven by their hop signature,
+/// were already counted there and pass through.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::RateLimited`] when the quota is used up.
+pub async fn enforce_request_quota(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, GatewayError> {
+    if !proxy::is_peer_hop(state.cluster.as_deref(), &req) {
+        let api_key = req
+            .headers()
+            .get(CLIENT_ID_HEADER)
+            .and_then(|v| v.to_str().ok());
+        let now = Utc::now();
+        state.quotas.check_request(api_key, now)?;
+        if let Some(api_key) = api_key {
+            state.usage.record_request(api_key, now);
+        }
+    }
+    Ok(next.run(req).await)
+}
+
+/// Axum middleware rejecting requests whose caller has used up its
+/// tokens on the matched route.
+///
+/// Callers are keyed by `X-Client-Id`, or by peer address without one.
+/// Requests forwarded by a cluster peer, proven by their hop signature,
+/// were already limited there and pass through.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::RateLimited`] when the caller's bucket is
+/// empty.
+pub async fn enforce_route_limits(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, GatewayError> {
+    let route = req.extensions().get::<MatchedPath>();
+    if let Some(route) = route
+        && !state.route_limits.is_disabled()
+        && !proxy::is_peer_hop(state.cluster.as_deref(), &req)
+    {
+        let api_key = req
+            .headers()
+            .get(CLIENT_ID_HEADER)
+            .and_then(|v| v.to_str().ok());
+        let ip = req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        state
+            .route_limits
+            .check(route.as_str(), Caller::new(api_key, ip), Instant::now())?;
+    }
+    Ok(next.run(req).await)
+}
@@ -0,0 +1,39 @@
+//! `Server-Timing` headers on mutation responses.
+//!
+//! Every non-`GET` request runs inside a [`timing::scope`], and its
+//! response carries a `Server-Timing` header with the time spent acquiring
+//! pool locks, computing in hydra-amm, publishing events, and writing to
+//! the database, plus the total time spent in the gateway. Phases the
+//! request never entered are omitted.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::extract::Request;
+use axum::http::{HeaderValue, Method};
+use axum::middleware::Next;
+use axum::response::Response;
+
+use crate::service::timing::{self, RequestTimings};
+
+/// Response header carrying the phase breakdown.
+pub const SERVER_TIMING_HEADER: &str = "server-timing";
+
+/// Axum middleware adding `Server-Timing` to mutation responses.
+///
+/// Responses that already carry the header (requests forwarded to the
+/// owning cluster peer) keep the owner's breakdown.
+pub async fn server_timing(req: Request, next: Next) -> Response {
+    if matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return next.run(req).await;
+    }
+    let started = Instant::now();
+    let timings = Arc::new(RequestTimings::default());
+    let mut response = timing::scope(Arc::clone(&timings), next.run(req)).await;
+    if !response.headers().contains_key(SERVER_TIMING_HEADER)
+        && let Ok(value) = HeaderValue::from_str(&timings.header_value(started.elapsed()))
+    {
+        response.headers_mut().insert(SERVER_TIMING_HEADER, value);
+    }
+    response
+}
@@ -0,0 +1,360 @@
+//! HMAC-signed requests with replay protection.
+//!
+//! When signing keys are configured, every state-changing request (any
+//! method other than `GET`, `HEAD`, or `OPTIONS`) must carry:
+//!
+//! | Header | Content |
+//! |--------|---------|
+//! | `X-Hydra-Key-Id` | Id of the shared secret used |
+//! | `X-Hydra-Timestamp` | Unix time in seconds |
+//! | `X-Hydra-Nonce` | 8–128 visible ASCII characters, unique per request |
+//! | `X-Hydra-Signature` | Hex HMAC-SHA256 of the canonical string |
+//!
+//! The canonical string is the timestamp, nonce, upper-case method, path
+//! with query, and hex SHA-256 of the body, joined by `\n`. Reads may be
+//! signed too and are then verified the same way.
+//!
+//! Requests whose timestamp is more than the allowed skew away from the
+//! server clock are stale. Each nonce is remembered per key until its
+//! request would be stale anyway, so a captured request cannot be
+//! replayed inside the window either.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, Method};
+use axum::middleware::Next;
+use axum::response::Response;
+use chrono::Utc;
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::app_state::AppState;
+use crate::error::GatewayError;
+
+/// Header naming the signing key.
+pub const KEY_ID_HEADER: &str = "x-hydra-key-id";
+/// Header carrying the request time in Unix seconds.
+pub const TIMESTAMP_HEADER: &str = "x-hydra-timestamp";
+/// Header carrying the per-request nonce.
+pub const NONCE_HEADER: &str = "x-hydra-nonce";
+/// Header carrying the hex HMAC-SHA256 signature.
+pub const SIGNATURE_HEADER: &str = "x-hydra-signature";
+
+/// Maximum request body size buffered for verification (1 MiB).
+const MAX_SIGNED_BODY: usize = 1024 * 1024;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Builds the string a request signature covers.
+#[must_use]
+pub fn canonical_string(
+    timestamp: i64,
+    nonce: &str,
+    method: &str,
+    path_and_query: &str,
+    body: &[u8],
+) -> String {
+    format!(
+        "{timestamp}\n{nonce}\n{}\n{path_and_query}\n{}",
+        method.to_ascii_uppercase(),
+        hex::encode(Sha256::digest(body))
+    )
+}
+
+/// Signs `canonical` with `secret`, returning the lower-case hex tag.
+#[must_use]
+pub fn sign(secret: &[u8], canonical: &str) -> String {
+    let Ok(mut mac) = HmacSha256::new_from_slice(secret) else {
+        return String::new();
+    };
+    mac.update(canonical.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Returns `true` if `signature` is the hex tag [`sign`] gives for
+/// `canonical` under `secret`, compared in constant time.
+#[must_use]
+pub fn is_signed(secret: &[u8], canonical: &str, signature: &str) -> bool {
+    let (Ok(tag), Ok(mut mac)) = (hex::decode(signature), HmacSha256::new_from_slice(secret))
+    else {
+        return false;
+    };
+    mac.update(canonical.as_bytes());
+    mac.verify_slice(&tag).is_ok()
+}
+
+/// Signature fields taken from a request's headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedHeaders {
+    /// Signing key id.
+    pub key_id: String,
+    /// Request time in Unix seconds.
+    pub timestamp: i64,
+    /// Per-request nonce.
+    pub nonce: String,
+    /// Hex HMAC-SHA256 signature.
+    pub signature: String,
+}
+
+impl SignedHeaders {
+    /// Reads the signature headers. Returns `Ok(None)` when the request
+    /// carries none of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::Unauthorized`] if only some are present or
+    /// a value is malformed.
+    pub fn from_headers(headers: &HeaderMap) -> Result<Option<Self>, GatewayError> {
+        let names = [
+            KEY_ID_HEADER,
+            TIMESTAMP_HEADER,
+            NONCE_HEADER,
+            SIGNATURE_HEADER,
+        ];
+        if names.iter().all(|name| !headers.contains_key(*name)) {
+            return Ok(None);
+        }
+        let get = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| GatewayError::Unauthorized(format!("missing or invalid {name}")))
+        };
+        let timestamp = get(TIMESTAMP_HEADER)?.parse().map_err(|_| {
+            GatewayError::Unauthorized(format!("{TIMESTAMP_HEADER} must be Unix seconds"))
+        })?;
+        let nonce = get(NONCE_HEADER)?;
+        if !(8..=128).contains(&nonce.len()) || !nonce.bytes().all(|b| b.is_ascii_graphic()) {
+            return Err(GatewayError::Unauthorized(format!(
+                "{NONCE_HEADER} must be 8-128 visible ASCII characters"
+            )));
+        }
+        Ok(Some(Self {
+            key_id: get(KEY_ID_HEADER)?.to_string(),
+            timestamp,
+            nonce: nonce.to_string(),
+            signature: get(SIGNATURE_HEADER)?.to_ascii_lowercase(),
+        }))
+    }
+}
+
+/// Nonces seen per key, each kept until its request goes stale.
+#[derive(Debug, Default)]
+struct NonceWindow {
+    expiry: HashMap<(String, String), i64>,
+    last_pruned: i64,
+}
+
+impl NonceWindow {
+    /// Records `nonce` for `key_id` until `expires_at`. Returns `false`
+    /// if it is already in the window.
+    fn insert(&mut self, key_id: &str, nonce: &str, expires_at: i64, now: i64) -> bool {
+        if now != self.last_pruned {
+            self.expiry.retain(|_, at| *at >= now);
+            self.last_pruned = now;
+        }
+        let key = (key_id.to_string(), nonce.to_string());
+        if self.expiry.get(&key).is_some_and(|at| *at >= now) {
+            return false;
+        }
+        self.expiry.insert(key, expires_at);
+        true
+    }
+}
+
+/// Verifies signed requests against a set of shared secrets.
+#[derive(Debug)]
+pub struct SignatureVerifier {
+    keys: HashMap<String, Vec<u8>>,
+    max_skew_secs: i64,
+    nonces: Mutex<NonceWindow>,
+}
+
+impl SignatureVerifier {
+    /// Creates a verifier from `id=secret` pairs, comma-separated, with
+    /// the given clock skew tolerance. Returns `None` when no key is
+    /// configured, which disables signing.
+    #[must_use]
+    pub fn from_spec(spec: &str, max_skew_secs: u64) -> Option<Self> {
+        let keys: HashMap<String, Vec<u8>> = spec
+            .split(',')
+            .filter_map(|pair| pair.trim().split_once('='))
+            .filter(|(id, secret)| !id.trim().is_empty() && !secret.is_empty())
+            .map(|(id, secret)| (id.trim().to_string(), secret.as_bytes().to_vec()))
+            .collect();
+        (!keys.is_empty()).then(|| Self {
+            keys,
+            max_skew_secs: i64::try_from(max_skew_secs).unwrap_or(i64::MAX),
+            nonces: Mutex::new(NonceWindow::default()),
+        })
+    }
+
+    /// Checks a signed request received at `now` (Unix seconds) and
+    /// consumes its nonce.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::Unauthorized`] for an unknown key, a stale
+    /// timestamp, a bad signature, or a replayed nonce.
+    pub fn verify(
+        &self,
+        signed: &SignedHeaders,
+        method: &str,
+        path_and_query: &str,
+        body: &[u8],
+        now: i64,
+    ) -> Result<(), GatewayError> {
+        let secret = self
+            .keys
+            .get(&signed.key_id)
+            .ok_or_else(|| GatewayError::Unauthorized("unknown signing key".to_string()))?;
+        if now.abs_diff(signed.timestamp) > self.max_skew_secs.unsigned_abs() {
+            return Err(GatewayError::Unauthorized(
+                "request timestamp outside the allowed window".to_string(),
+            ));
+        }
+        let Ok(tag) = hex::decode(&signed.signature) else {
+            return Err(GatewayError::Unauthorized(
+                "signature is not valid hex".to_string(),
+            ));
+        };
+        let canonical = canonical_string(
+            signed.timestamp,
+            &signed.nonce,
+            method,
+            path_and_query,
+            body,
+        );
+        let mut mac = HmacSha256::new_from_slice(secret)
+            .map_err(|e| GatewayError::Internal(format!("signing key: {e}")))?;
+        mac.update(canonical.as_bytes());
+        mac.verify_slice(&tag)
+            .map_err(|_| GatewayError::Unauthorized("signature mismatch".to_string()))?;
+
+        let expires_at = signed.timestamp.saturating_add(self.max_skew_secs);
+        let mut nonces = self.nonces.lock().unwrap_or_else(PoisonError::into_inner);
+        if !nonces.insert(&signed.key_id, &signed.nonce, expires_at, now) {
+            return Err(GatewayError::Unauthorized("nonce already used".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Axum middleware enforcing request signatures when signing is enabled.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::Unauthorized`] if a state-changing request is
+/// unsigned or any signed request fails verification.
+pub async fn verify_signature(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, GatewayError> {
+    let Some(verifier) = state.signature_verifier.as_deref() else {
+        return Ok(next.run(req).await);
+    };
+    let signed = SignedHeaders::from_headers(req.headers())?;
+    let safe = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    let signed = match signed {
+        Some(signed) => signed,
+        None if safe => return Ok(next.run(req).await),
+        None => {
+            return Err(GatewayError::Unauthorized(
+                "request signature required".to_string(),
+            ));
+        }
+    };
+
+    let path_and_query = req
+        .uri()
+        .path_and_query()
+        .map_or_else(|| req.uri().path().to_string(), ToString::to_string);
+    let (parts, body) = req.into_parts();
+    let body = axum::body::to_bytes(body, MAX_SIGNED_BODY)
+        .await
+        .map_err(|e| GatewayError::InvalidRequest(format!("unreadable body: {e}")))?;
+    verifier.verify(
+        &signed,
+        parts.method.as_str(),
+        &path_and_query,
+        &body,
+        Utc::now().timestamp(),
+    )?;
+
+    Ok(next.run(Request::from_parts(parts, Body::from(body))).await)
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn signed(verifier_secret: &[u8], timestamp: i64, nonce: &str, body: &[u8]) -> SignedHeaders {
+        let canonical = canonical_string(timestamp, nonce, "post", "/api/v1/pools", body);
+        SignedHeaders {
+            key_id: "desk".to_string(),
+            timestamp,
+            nonce: nonce.to_string(),
+            signature: sign(verifier_secret, &canonical),
+        }
+    }
+
+    #[test]
+    fn rejects_tampered_stale_and_replayed_requests() {
+        let Some(verifier) = SignatureVerifier::from_spec("desk=s3cret, bad", 30) else {
+            panic!("expected signing to be enabled");
+        };
+        let now = 1_700_000_000;
+        let body = br#"{"pool_type":"constant_product"}"#;
+
+        let request = signed(b"s3cret", now, "nonce-0001", body);
+        assert!(
+            verifier
+                .verify(&request, "POST", "/api/v1/pools", body, now)
+                .is_ok()
+        );
+        assert!(
+            verifier
+                .verify(&request, "POST", "/api/v1/pools", body, now + 1)
+                .is_err(),
+            "replayed nonce accepted"
+        );
+
+        let fresh = signed(b"s3cret", now, "nonce-0002", body);
+        assert!(
+            verifier
+                .verify(&fresh, "POST", "/api/v1/pools", b"{}", now)
+                .is_err(),
+            "tampered body accepted"
+        );
+
+        let stale = signed(b"s3cret", now - 31, "nonce-0003", body);
+        assert!(
+            verifier
+                .verify(&stale, "POST", "/api/v1/pools", body, now)
+                .is_err()
+        );
+
+        let forged = signed(b"guess", now, "nonce-0004", body);
+        assert!(
+            verifier
+                .verify(&forged, "POST", "/api/v1/pools", body, now)
+                .is_err()
+        );
+
+        assert!(SignatureVerifier::from_spec("", 30).is_none());
+    }
+
+    #[test]
+    fn nonces_expire_with_their_window() {
+        let mut window = NonceWindow::default();
+        assert!(window.insert("desk", "n", 10, 0));
+        assert!(!window.insert("desk", "n", 10, 5));
+        assert!(window.insert("other", "n", 10, 5));
+        assert!(window.insert("desk", "n", 20, 11));
+    }
+}
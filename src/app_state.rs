@@ -2,8 +2,36 @@
 
 use std::sync::Arc;
 
+use crate::api::signing::SignatureVerifier;
+use crate::cluster::{ClusterMembership, PeerReads};
 use crate::domain::EventBus;
+use crate::persistence::postgres::PostgresPersistence;
 use crate::service::PoolService;
+use crate::service::candles::CandleBook;
+use crate::service::concurrency::ConcurrencyLimiter;
+use crate::service::event_tail::EventTail;
+use crate::service::idempotency::IdempotencyStore;
+use crate::service::jobs::JobRegistry;
+use crate::service::metadata_schema::MetadataSchemas;
+use crate::service::pool_defaults::PoolDefaults;
+use crate::service::pool_stats::PoolStatsBook;
+use crate::service::quota::QuotaRegistry;
+use crate::service::quote_audit::QuoteAudit;
+use crate::service::quote_runtime::QuoteRuntime;
+use crate::service::replay::StartupReport;
+use crate::service::rewards::RewardsTracker;
+use crate::service::route_limits::RouteRateLimiter;
+use crate::service::routing::CostModel;
+use crate::service::stale_pools::StalePoolPolicy;
+use crate::service::trade_tape::TradeTape;
+use crate::service::usage::UsageLedger;
+use crate::service::webhooks::WebhookRegistry;
+use crate::ws::drain::DrainAnnouncer;
+use crate::ws::heartbeat::Heartbeat;
+use crate::ws::limits::FrameLimits;
+use crate::ws::outbound::ConnectionRegistry;
+use crate::ws::raw::RawEventFeed;
+use crate::ws::session::SessionRegistry;
 
 /// Shared application state available to all handlers via Axum's
 /// `State` extractor.
@@ -13,4 +41,64 @@ pub struct AppState {
     pub pool_service: Arc<PoolService>,
     /// Event bus for WebSocket subscriptions.
     pub event_bus: EventBus,
+    /// Cluster membership when running in clustered mode.
+    pub cluster: Option<Arc<ClusterMembership>>,
+    /// Local copies of the pools owned by cluster peers, when clustered
+    /// with persistence.
+    pub peer_reads: Option<PeerReads>,
+    /// Event log and snapshot store, when persistence is enabled and the
+    /// database was reachable at startup.
+    pub persistence: Option<PostgresPersistence>,
+    /// Execution cost model used to rank routes.
+    pub route_cost: Arc<dyn CostModel>,
+    /// Recent trades across all pools.
+    pub trade_tape: TradeTape,
+    /// Recent candles of every pool.
+    pub candles: CandleBook,
+    /// Rolling 24-hour and 7-day activity of every pool.
+    pub pool_stats: PoolStatsBook,
+    /// Recent events across all pools, for tailing.
+    pub event_tail: EventTail,
+    /// Per-API-key request, swap, and WebSocket quotas.
+    pub quotas: QuotaRegistry,
+    /// Hourly request, swap, and WebSocket usage per API key.
+    pub usage: UsageLedger,
+    /// Token-bucket limits per route and caller.
+    pub route_limits: RouteRateLimiter,
+    /// Responses to requests sent with an `Idempotency-Key`.
+    pub idempotency: IdempotencyStore,
+    /// Request signature verification, when signing keys are configured.
+    pub signature_verifier: Option<Arc<SignatureVerifier>>,
+    /// Background administrative jobs started on this instance.
+    pub jobs: JobRegistry,
+    /// Concurrency limits for CPU-heavy endpoints.
+    pub heavy_limiter: ConcurrencyLimiter,
+    /// Webhooks attached to pools.
+    pub webhooks: WebhookRegistry,
+    /// Where quote and simulation work runs.
+    pub quote_runtime: QuoteRuntime,
+    /// Recorder of served quotes, when the quote audit trail is enabled.
+    pub quote_audit: Option<QuoteAudit>,
+    /// Resumable WebSocket sessions.
+    pub ws_sessions: SessionRegistry,
+    /// Size and nesting limits of client WebSocket messages.
+    pub ws_limits: FrameLimits,
+    /// Ping interval and idle timeout of WebSocket connections.
+    pub ws_heartbeat: Heartbeat,
+    /// Live WebSocket connections and their outbound queues.
+    pub ws_connections: ConnectionRegistry,
+    /// `server_draining` announcement sent to WebSocket clients.
+    pub ws_drain: DrainAnnouncer,
+    /// Pre-serialized events for WebSocket clients using the `raw` encoding.
+    pub ws_raw_feed: RawEventFeed,
+    /// When pools count as stale and whether they are archived.
+    pub stale_pools: StalePoolPolicy,
+    /// Config fields filled in when a create request leaves them out.
+    pub pool_defaults: PoolDefaults,
+    /// JSON Schemas that pool metadata must satisfy.
+    pub metadata_schemas: MetadataSchemas,
+    /// Liquidity-mining emission schedules and accrued rewards.
+    pub rewards: RewardsTracker,
+    /// What startup recovery restored.
+    pub startup_report: Arc<StartupReport>,
 }
@@ -0,0 +1,197 @@
+//! Cluster peer set and rendezvous-hash pool ownership.
+
+use crate::domain::PoolId;
+
+/// A gateway instance participating in the cluster.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterPeer {
+    /// Stable instance identifier (e.g. the pod name).
+    pub instance_id: String,
+    /// Base URL other instances use to reach this peer
+    /// (e.g. `http://gw-1:3000`).
+    pub base_url: String,
+}
+
+/// Static cluster membership with deterministic pool ownership.
+///
+/// Ownership is computed with rendezvous hashing over FNV-1a, so every
+/// instance configured with the same peer list agrees on the owner of
+/// any pool without communicating. Adding or removing a peer only moves
+/// the pools owned by that peer.
+#[derive(Debug, Clone)]
+pub struct ClusterMembership {
+    local_id: String,
+    peers: Vec<ClusterPeer>,
+    client: reqwest::Client,
+    secret: Option<Vec<u8>>,
+}
+
+impl ClusterMembership {
+    /// Creates a membership view for `local_id` within `peers`.
+    ///
+    /// The local instance is added to the peer set if it is missing.
+    #[must_use]
+    pub fn new(local_id: String, mut peers: Vec<ClusterPeer>) -> Self {
+        if !peers.iter().any(|p| p.instance_id == local_id) {
+            peers.push(ClusterPeer {
+                instance_id: local_id.clone(),
+                base_url: String::new(),
+            });
+        }
+        peers.sort_by(|a, b| a.instance_id.cmp(&b.instance_id));
+        Self {
+            local_id,
+            peers,
+            client: reqwest::Client::new(),
+            secret: None,
+        }
+    }
+
+    /// Sets the secret the instances sign forwarded requests with.
+    #[must_use]
+    pub fn with_secret(mut self, secret: &str) -> Self {
+        self.secret = Some(secret.as_bytes().to_vec());
+        self
+    }
+
+    /// Parses a peer list of the form `id1=http://host1:3000,id2=http://host2:3000`.
+    ///
+    /// Malformed entries are skipped.
+    #[must_use]
+    pub fn parse_peers(spec: &str) -> Vec<ClusterPeer> {
+        spec.split(',')
+            .filter_map(|entry| {
+                let (id, url) = entry.trim().split_once('=')?;
+                let (id, url) = (id.trim(), url.trim().trim_end_matches('/'));
+                if id.is_empty() || url.is_empty() {
+                    return None;
+                }
+                Some(ClusterPeer {
+                    instance_id: id.to_string(),
+                    base_url: url.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns this instance's identifier.
+    #[must_use]
+    pub fn local_id(&self) -> &str {
+        &self.local_id
+    }
+
+    /// Returns all peers, including the local instance.
+    #[must_use]
+    pub fn peers(&self) -> &[ClusterPeer] {
+        &self.peers
+    }
+
+    /// Returns the HTTP client used to forward requests to peers.
+    #[must_use]
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// Returns the secret forwarded requests are signed with, if set.
+    #[must_use]
+    pub fn secret(&self) -> Option<&[u8]> {
+        self.secret.as_deref()
+    }
+
+    /// Returns `true` if `instance_id` names a peer other than this
+    /// instance.
+    #[must_use]
+    pub fn is_peer(&self, instance_id: &str) -> bool {
+        instance_id != self.local_id && self.peers.iter().any(|p| p.instance_id == instance_id)
+    }
+
+    /// Returns the peer that owns `pool_id`.
+    #[must_use]
+    pub fn owner_of(&self, pool_id: PoolId) -> Option<&ClusterPeer> {
+        self.peers
+            .iter()
+            .max_by_key(|peer| rendezvous_score(&peer.instance_id, pool_id))
+    }
+
+    /// Returns `true` if the local instance owns `pool_id`.
+    #[must_use]
+    pub fn is_local(&self, pool_id: PoolId) -> bool {
+        self.owner_of(pool_id)
+            .is_none_or(|owner| owner.instance_id == self.local_id)
+    }
+
+    /// Generates a fresh pool ID owned by the local instance.
+    ///
+    /// Draws random UUIDs until one hashes to this instance; with `n`
+    /// peers this takes `n` draws on average.
+    #[must_use]
+    pub fn generate_local_id(&self) -> PoolId {
+        loop {
+            let id = PoolId::new();
+            if self.is_local(id) {
+                return id;
+            }
+        }
+    }
+}
+
+/// Rendezvous weight of `instance_id` for `pool_id` (FNV-1a 64).
+fn rendezvous_score(instance_id: &str, pool_id: PoolId) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    instance_id
+        .as_bytes()
+        .iter()
+        .chain(pool_id.as_uuid().as_bytes())
+        .fold(FNV_OFFSET, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+        })
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn three_node(local: &str) -> ClusterMembership {
+        let peers = ClusterMembership::parse_peers(
+            "gw-a=http://a:3000, gw-b=http://b:3000/,gw-c=http://c:3000,bogus",
+        );
+        ClusterMembership::new(local.to_string(), peers)
+    }
+
+    #[test]
+    fn parse_peers_skips_malformed_and_trims() {
+        let peers = ClusterMembership::parse_peers("a=http://x/, =http://y,b=,c=http://z");
+        assert_eq!(peers.len(), 2);
+        assert_eq!(peers.first().map(|p| p.base_url.as_str()), Some("http://x"));
+    }
+
+    #[test]
+    fn all_instances_agree_on_owner() {
+        let a = three_node("gw-a");
+        let b = three_node("gw-b");
+        let c = three_node("gw-c");
+        for _ in 0..100 {
+            let id = PoolId::new();
+            let owners = [a.is_local(id), b.is_local(id), c.is_local(id)];
+            assert_eq!(owners.iter().filter(|o| **o).count(), 1);
+        }
+    }
+
+    #[test]
+    fn generated_ids_are_local() {
+        let b = three_node("gw-b");
+        for _ in 0..20 {
+            assert!(b.is_local(b.generate_local_id()));
+        }
+    }
+
+    #[test]
+    fn single_node_owns_everything() {
+        let solo = ClusterMembership::new("solo".to_string(), vec![]);
+        assert!(solo.is_local(PoolId::new()));
+        assert_eq!(solo.peers().len(), 1);
+    }
+}
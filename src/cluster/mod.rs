@@ -0,0 +1,17 @@
+//! Clustering: distributed pool ownership across gateway instances.
+//!
+//! Each pool is owned by exactly one instance, chosen by rendezvous
+//! (highest-random-weight) hashing of the pool ID over the configured
+//! peer set. Instances only mint pool IDs they own, and requests that
+//! mutate a pool owned by another instance are forwarded to its owner.
+//! Reads of such pools are served from local copies replicated through
+//! the shared event log (see [`reads`]), and pool listings include them.
+//! This removes the single-process ceiling on pool count and swap
+//! throughput without any shared coordination state.
+
+pub mod membership;
+pub mod proxy;
+pub mod reads;
+
+pub use membership::{ClusterMembership, ClusterPeer};
+pub use reads::PeerReads;
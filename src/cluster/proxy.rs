@@ -0,0 +1,314 @@
+//! Request forwarding to the instance that owns a pool.
+
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, HeaderName, HeaderValue, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use chrono::Utc;
+
+use super::ClusterMembership;
+use crate::api::signing;
+use crate::app_state::AppState;
+use crate::domain::PoolId;
+use crate::error::GatewayError;
+
+/// Header marking a request that was already forwarded once, preventing
+/// forwarding loops when peers disagree about membership.
+pub const FORWARDED_HEADER: &str = "x-hydra-forwarded-by";
+
+/// Header proving a forwarded request comes from a peer:
+/// `<unix seconds>:<hex HMAC-SHA256>` under the cluster secret, covering
+/// the forwarding instance, the time, the method, and the path.
+pub const HOP_SIGNATURE_HEADER: &str = "x-hydra-hop-signature";
+
+/// Accepted distance in seconds between a hop signature's time and the
+/// local clock.
+const HOP_MAX_SKEW_SECS: u64 = 30;
+
+/// Header naming the instance that owns the pool, on responses to
+/// requests for a pool owned by a peer.
+pub const POOL_OWNER_HEADER: HeaderName = HeaderName::from_static("x-hydra-pool-owner");
+
+/// Maximum request body size buffered for forwarding (1 MiB).
+const MAX_FORWARD_BODY: usize = 1024 * 1024;
+
+/// Hop-by-hop headers (RFC 9110 §7.6.1), which describe one connection
+/// and are never forwarded.
+const HOP_BY_HOP: [HeaderName; 8] = [
+    header::CONNECTION,
+    HeaderName::from_static("keep-alive"),
+    header::PROXY_AUTHENTICATE,
+    header::PROXY_AUTHORIZATION,
+    header::TE,
+    header::TRAILER,
+    header::TRANSFER_ENCODING,
+    header::UPGRADE,
+];
+
+/// Axum middleware forwarding pool-scoped requests to the owning instance.
+///
+/// Requests under `/api/v1/pools/{id}` whose pool is owned by another
+/// peer are proxied to it, minus hop-by-hop headers, unless they are
+/// reads the local copy of the pool can answer (see [`PeerReads`]).
+/// Everything else is handled locally.
+///
+/// [`PeerReads`]: super::PeerReads
+///
+/// # Errors
+///
+/// Returns [`GatewayError::OwnerUnreachable`] if the owning peer cannot
+/// be reached.
+pub async fn forward_to_owner(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, GatewayError> {
+    let Some(cluster) = state.cluster.as_deref() else {
+        return Ok(next.run(req).await);
+    };
+    if req.headers().contains_key(FORWARDED_HEADER) {
+        return Ok(next.run(req).await);
+    }
+    let Some(pool_id) = pool_id_from_path(req.uri().path()) else {
+        return Ok(next.run(req).await);
+    };
+    let Some(owner) = cluster.owner_of(pool_id) else {
+        return Ok(next.run(req).await);
+    };
+    if owner.instance_id == cluster.local_id() || owner.base_url.is_empty() {
+        return Ok(next.run(req).await);
+    }
+    let owner_header = HeaderValue::from_str(&owner.instance_id).ok();
+    let req = match state.peer_reads.as_ref() {
+        Some(reads) => match reads.serve(pool_id, req).await {
+            Ok(mut response) => {
+                if let Some(owner_id) = owner_header {
+                    response.headers_mut().insert(POOL_OWNER_HEADER, owner_id);
+                }
+                return Ok(response);
+            }
+            Err(req) => req,
+        },
+        None => req,
+    };
+
+    let path_and_query = req
+        .uri()
+        .path_and_query()
+        .map_or_else(|| req.uri().path().to_string(), ToString::to_string);
+    let url = format!("{}{path_and_query}", owner.base_url);
+    tracing::debug!(%pool_id, owner = %owner.instance_id, %url, "forwarding to pool owner");
+
+    let (parts, body) = req.into_parts();
+    let body = axum::body::to_bytes(body, MAX_FORWARD_BODY)
+        .await
+        .map_err(|e| GatewayError::InvalidRequest(format!("unreadable body: {e}")))?;
+
+    let mut upstream = cluster
+        .client()
+        .request(parts.method.clone(), &url)
+        .body(body)
+        .header(FORWARDED_HEADER, cluster.local_id());
+    if let Some(secret) = cluster.secret() {
+        let timestamp = Utc::now().timestamp();
+        let canonical = hop_canonical(
+            cluster.local_id(),
+            timestamp,
+            parts.method.as_str(),
+            &path_and_query,
+        );
+        let signature = signing::sign(secret, &canonical);
+        upstream = upstream.header(HOP_SIGNATURE_HEADER, format!("{timestamp}:{signature}"));
+    }
+    for (name, value) in &end_to_end(&parts.headers) {
+        if name != header::HOST
+            && name != header::CONTENT_LENGTH
+            && name != FORWARDED_HEADER
+            && name != HOP_SIGNATURE_HEADER
+        {
+            upstream = upstream.header(name, value);
+        }
+    }
+
+    let resp = upstream
+        .send()
+        .await
+        .map_err(|e| GatewayError::OwnerUnreachable(format!("{}: {e}", owner.instance_id)))?;
+
+    let status = resp.status();
+    let headers = end_to_end(resp.headers());
+    let bytes = resp.bytes().await.map_err(|e| {
+        GatewayError::OwnerUnreachable(format!("{}: response failed: {e}", owner.instance_id))
+    })?;
+
+    let mut response = Body::from(bytes).into_response();
+    *response.status_mut() = status;
+    for (name, value) in &headers {
+        response.headers_mut().insert(name.clone(), value.clone());
+    }
+    if let Some(owner_id) = owner_header {
+        response.headers_mut().insert(POOL_OWNER_HEADER, owner_id);
+    }
+    Ok(response)
+}
+
+/// Returns `true` if `req` was forwarded by a cluster peer, as proven by
+/// a current hop signature under the cluster secret. Such requests were
+/// already rate limited and counted on the peer that received them.
+#[must_use]
+pub fn is_peer_hop(cluster: Option<&ClusterMembership>, req: &Request) -> bool {
+    let Some((cluster, secret)) = cluster.and_then(|c| Some((c, c.secret()?))) else {
+        return false;
+    };
+    let header = |name| req.headers().get(name).and_then(|v| v.to_str().ok());
+    let (Some(from), Some(proof)) = (header(FORWARDED_HEADER), header(HOP_SIGNATURE_HEADER)) else {
+        return false;
+    };
+    let Some((timestamp, signature)) = proof.split_once(':') else {
+        return false;
+    };
+    let Ok(timestamp) = timestamp.parse::<i64>() else {
+        return false;
+    };
+    let path_and_query = req
+        .uri()
+        .path_and_query()
+        .map_or_else(|| req.uri().path(), |pq| pq.as_str());
+    cluster.is_peer(from)
+        && Utc::now().timestamp().abs_diff(timestamp) <= HOP_MAX_SKEW_SECS
+        && signing::is_signed(
+            secret,
+            &hop_canonical(from, timestamp, req.method().as_str(), path_and_query),
+            signature,
+        )
+}
+
+/// Builds the string a hop signature covers.
+fn hop_canonical(from: &str, timestamp: i64, method: &str, path_and_query: &str) -> String {
+    format!("{from}\n{timestamp}\n{method}\n{path_and_query}")
+}
+
+/// Returns `headers` without hop-by-hop headers, including any the
+/// `Connection` header names.
+fn end_to_end(headers: &HeaderMap) -> HeaderMap {
+    let named: Vec<String> = headers
+        .get_all(header::CONNECTION)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|name| name.trim().to_ascii_lowercase())
+        .collect();
+    headers
+        .iter()
+        .filter(|(name, _)| !HOP_BY_HOP.contains(name) && !named.iter().any(|n| n == name.as_str()))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
+}
+
+/// Extracts the pool ID from `/api/v1/pools/{id}[/...]`.
+fn pool_id_from_path(path: &str) -> Option<PoolId> {
+    let rest = path.strip_prefix("/api/v1/pools/")?;
+    let segment = rest.split('/').next()?;
+    segment.parse::<uuid::Uuid>().ok().map(PoolId::from_uuid)
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pool_id_from_pool_scoped_paths() {
+        let id = PoolId::new();
+        assert_eq!(pool_id_from_path(&format!("/api/v1/pools/{id}")), Some(id));
+        assert_eq!(
+            pool_id_from_path(&format!("/api/v1/pools/{id}/swap")),
+            Some(id)
+        );
+        assert_eq!(pool_id_from_path("/api/v1/pools"), None);
+        assert_eq!(pool_id_from_path("/api/v1/pools/validate"), None);
+        assert_eq!(pool_id_from_path("/health"), None);
+    }
+
+    #[test]
+    fn hop_by_hop_headers_are_not_forwarded() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONNECTION,
+            HeaderValue::from_static("close, x-trace"),
+        );
+        headers.insert(
+            header::TRANSFER_ENCODING,
+            HeaderValue::from_static("chunked"),
+        );
+        headers.insert("keep-alive", HeaderValue::from_static("timeout=5"));
+        headers.insert("x-trace", HeaderValue::from_static("1"));
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        headers.insert("x-client-id", HeaderValue::from_static("desk-1"));
+
+        let forwarded = end_to_end(&headers);
+        assert_eq!(forwarded.len(), 2);
+        assert!(forwarded.contains_key(header::CONTENT_TYPE));
+        assert!(forwarded.contains_key("x-client-id"));
+    }
+
+    #[test]
+    fn only_signed_hops_from_peers_are_trusted() {
+        let peers = ClusterMembership::parse_peers("gw-0=http://gw-0,gw-1=http://gw-1");
+        let cluster = ClusterMembership::new("gw-0".to_string(), peers).with_secret("s3cret");
+        let path = format!("/api/v1/pools/{}/swap", PoolId::new());
+        let hop = |from: &str, secret: &str, timestamp: i64, path: &str| {
+            let signature = signing::sign(
+                secret.as_bytes(),
+                &hop_canonical(from, timestamp, "POST", path),
+            );
+            let Ok(req) = Request::post(path)
+                .header(FORWARDED_HEADER, from)
+                .header(HOP_SIGNATURE_HEADER, format!("{timestamp}:{signature}"))
+                .body(Body::empty())
+            else {
+                panic!("invalid request");
+            };
+            req
+        };
+        let now = Utc::now().timestamp();
+
+        assert!(is_peer_hop(
+            Some(&cluster),
+            &hop("gw-1", "s3cret", now, &path)
+        ));
+        assert!(!is_peer_hop(
+            Some(&cluster),
+            &hop("gw-1", "guess", now, &path)
+        ));
+        assert!(!is_peer_hop(
+            Some(&cluster),
+            &hop("gw-9", "s3cret", now, &path)
+        ));
+        assert!(!is_peer_hop(
+            Some(&cluster),
+            &hop("gw-0", "s3cret", now, &path)
+        ));
+        assert!(!is_peer_hop(
+            Some(&cluster),
+            &hop("gw-1", "s3cret", now - 120, &path)
+        ));
+        let mut moved = hop("gw-1", "s3cret", now, &path);
+        *moved.uri_mut() = axum::http::Uri::from_static("/api/v1/pools");
+        assert!(!is_peer_hop(Some(&cluster), &moved));
+
+        let Ok(unsigned) = Request::post(path.as_str())
+            .header(FORWARDED_HEADER, "gw-1")
+            .body(Body::empty())
+        else {
+            panic!("invalid request");
+        };
+        assert!(!is_peer_hop(Some(&cluster), &unsigned));
+        assert!(!is_peer_hop(None, &hop("gw-1", "s3cret", now, &path)));
+    }
+}
@@ -0,0 +1,119 @@
+//! Reads of pools owned by other instances, served from local copies.
+//!
+//! Every instance keeps read-only copies of the pools its peers own, fed
+//! from the shared event log by a [`ReplicaFollower`] limited to peer
+//! pools. Reads answered from a pool's own state are served from that
+//! copy instead of being forwarded, so they keep working (slightly
+//! behind, by the replication lag) while the owner is busy or down.
+//! Reads that depend on per-instance books, like candles and statistics,
+//! are still forwarded.
+//!
+//! [`ReplicaFollower`]: crate::service::replica::ReplicaFollower
+
+use std::sync::Arc;
+
+use axum::Router;
+use axum::extract::Request;
+use axum::http::Method;
+use axum::response::Response;
+use tower::Service;
+
+use crate::domain::PoolId;
+use crate::service::PoolService;
+
+/// Sub-resources of `/api/v1/pools/{id}` whose `GET` is answered from the
+/// pool entry alone (`""` is the pool itself).
+const REPLICATED_READS: [&str; 6] = [
+    "",
+    "positions",
+    "providers",
+    "bbo",
+    "slippage-curve",
+    "depth-chart",
+];
+
+/// Local copies of peer pools and the router serving reads from them.
+#[derive(Debug, Clone)]
+pub struct PeerReads {
+    pool_service: Arc<PoolService>,
+    router: Router,
+}
+
+impl PeerReads {
+    /// Serves reads through `router`, whose handlers see `pool_service`
+    /// (the read-only copies of peer pools).
+    #[must_use]
+    pub const fn new(pool_service: Arc<PoolService>, router: Router) -> Self {
+        Self {
+            pool_service,
+            router,
+        }
+    }
+
+    /// Returns the service holding the copies of peer pools.
+    #[must_use]
+    pub const fn pool_service(&self) -> &Arc<PoolService> {
+        &self.pool_service
+    }
+
+    /// Answers `req` for `pool_id` from the local copy, or gives the
+    /// request back if it is not a replicated read or the pool has not
+    /// been replicated yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `req` unchanged when it must be forwarded to the owner.
+    pub async fn serve(&self, pool_id: PoolId, req: Request) -> Result<Response, Request> {
+        if !is_replicated_read(req.method(), req.uri().path())
+            || self.pool_service.registry().get(pool_id).await.is_err()
+        {
+            return Err(req);
+        }
+        let mut router = self.router.clone();
+        let Ok(response) = router.call(req).await;
+        Ok(response)
+    }
+}
+
+/// Returns `true` for `GET` requests to a pool sub-resource listed in
+/// [`REPLICATED_READS`], or to a single position.
+fn is_replicated_read(method: &Method, path: &str) -> bool {
+    if method != Method::GET {
+        return false;
+    }
+    let Some(rest) = path.strip_prefix("/api/v1/pools/") else {
+        return false;
+    };
+    let resource = rest
+        .split_once('/')
+        .map_or("", |(_, resource)| resource.trim_end_matches('/'));
+    REPLICATED_READS.contains(&resource)
+        || resource
+            .strip_prefix("positions/")
+            .is_some_and(|position| !position.contains('/'))
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_pool_state_reads_are_replicated() {
+        let id = PoolId::new();
+        let read =
+            |path: &str| is_replicated_read(&Method::GET, &format!("/api/v1/pools/{id}{path}"));
+        assert!(read(""));
+        assert!(read("/positions"));
+        assert!(read(&format!("/positions/{}", uuid::Uuid::new_v4())));
+        assert!(read("/providers"));
+        assert!(read("/depth-chart"));
+        assert!(!read("/candles"), "candles live in per-instance books");
+        assert!(!read("/stats"));
+        assert!(!read("/orderbook"));
+        assert!(!is_replicated_read(
+            &Method::POST,
+            &format!("/api/v1/pools/{id}/positions")
+        ));
+    }
+}
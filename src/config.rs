@@ -32,14 +32,204 @@ pub struct GatewayConfig {
     /// Seconds between automatic pool snapshots.
     pub snapshot_interval_secs: u64,
 
+    /// Mutation-count snapshot policy, e.g. `clmm=500,*=5000` (see
+    /// [`crate::service::snapshot_policy::SnapshotPolicy::parse`]). Empty
+    /// disables it.
+    pub snapshot_every_mutations: String,
+
     /// Whether to append events to the event log.
     pub event_log_enabled: bool,
 
+    /// Most events the event log writer inserts per transaction.
+    pub event_log_batch_size: usize,
+
+    /// Events waiting for the event log writer while the database is
+    /// slow; beyond that, mutations are refused until it catches up.
+    pub event_log_buffer: usize,
+
+    /// Whether to rebuild pools from snapshots and the event log at
+    /// startup.
+    pub recover_on_startup: bool,
+
+    /// Whether to pre-aggregate swaps into the `candles` table.
+    pub candles_enabled: bool,
+
+    /// How often the candle worker flushes to the database, in ms.
+    pub candle_flush_interval_ms: u64,
+
+    /// Recent candles kept in memory per pool and timeframe (0 disables).
+    pub candle_memory_buckets: usize,
+
+    /// Whether to backfill pool statistics from the last week of logged
+    /// swaps at startup.
+    pub pool_stats_backfill: bool,
+
+    /// Whether to record served quotes in the `quotes` audit table.
+    pub quote_audit_enabled: bool,
+
+    /// Share of quotes recorded, in basis points (10 000 = all).
+    pub quote_audit_sample_bps: u32,
+
+    /// Comma-separated client ids whose quotes are recorded; empty
+    /// records every client's.
+    pub quote_audit_clients: String,
+
     /// Delete snapshots older than this many days (0 = never).
     pub cleanup_after_days: u64,
 
     /// Capacity of the EventBus broadcast channel.
     pub event_bus_capacity: usize,
+
+    /// Bind the listener with `SO_REUSEPORT` so a replacement process can
+    /// start on the same address before this one exits.
+    pub reuse_port: bool,
+
+    /// Path of the PID file a replacement process writes to make this one
+    /// drain during a zero-downtime restart.
+    pub pid_file: Option<String>,
+
+    /// Seconds to wait for in-flight requests and WebSocket connections
+    /// to finish after a shutdown signal before exiting.
+    pub shutdown_grace_secs: u64,
+
+    /// Seconds a disconnected WebSocket session stays resumable.
+    pub ws_session_ttl_secs: u64,
+
+    /// File through which WebSocket sessions are handed to the
+    /// replacement process during a zero-downtime restart.
+    pub ws_session_handover_file: Option<String>,
+
+    /// Seconds between announcing a shutdown to WebSocket clients and
+    /// starting to drain (0 disables the announcement).
+    pub ws_drain_notice_secs: u64,
+
+    /// Where WebSocket clients are told to reconnect during a drain.
+    pub ws_drain_alternate_host: Option<String>,
+
+    /// Largest WebSocket message accepted from a client, in bytes.
+    pub ws_max_message_bytes: usize,
+
+    /// Deepest JSON nesting accepted in a client WebSocket message.
+    pub ws_max_json_depth: usize,
+
+    /// Event frames queued per WebSocket connection for a slow client.
+    pub ws_outbound_queue: usize,
+
+    /// Disconnect a WebSocket client whose outbound queue is full, instead
+    /// of dropping its oldest queued events.
+    pub ws_outbound_disconnect_on_overflow: bool,
+
+    /// Seconds between pings sent to each WebSocket client (0 disables
+    /// them).
+    pub ws_ping_interval_secs: u64,
+
+    /// Seconds a WebSocket client may send nothing, pongs included, before
+    /// it is disconnected (0 disables the timeout).
+    pub ws_idle_timeout_secs: u64,
+
+    /// Identifier of this instance within the cluster.
+    pub cluster_instance_id: String,
+
+    /// Cluster peers as `id=base_url` pairs, comma-separated. Empty
+    /// disables clustering.
+    pub cluster_peers: String,
+
+    /// Secret shared by the cluster's instances, signing the requests
+    /// they forward to each other. Required when `cluster_peers` is set.
+    pub cluster_secret: Option<String>,
+
+    /// Fixed cost per hop charged when ranking routes, in output-token
+    /// units (0 ranks routes on raw output).
+    pub route_hop_cost: u128,
+
+    /// Request signing keys as `id=secret` pairs, comma-separated. Empty
+    /// disables signature checks.
+    pub request_signing_keys: String,
+
+    /// Accepted distance in seconds between a signed request's timestamp
+    /// and the server clock; also how long nonces are remembered.
+    pub request_signing_max_skew_secs: u64,
+
+    /// Hex-encoded Ed25519 seed used to attest emitted events. `None`
+    /// disables attestations.
+    pub event_signing_key: Option<String>,
+
+    /// Key id published alongside event attestations.
+    pub event_signing_key_id: String,
+
+    /// Run as a read-only replica that follows the primary's event log.
+    pub replica_mode: bool,
+
+    /// How often a replica polls the event log, in milliseconds.
+    pub replica_poll_interval_ms: u64,
+
+    /// Concurrent requests allowed on each CPU-heavy endpoint.
+    pub heavy_max_concurrent: usize,
+
+    /// Concurrent requests one API key may run on each CPU-heavy
+    /// endpoint (0 disables the per-key limit).
+    pub heavy_max_per_key: usize,
+
+    /// How long a heavy request waits for a slot before it is rejected,
+    /// in milliseconds.
+    pub heavy_queue_timeout_ms: u64,
+
+    /// Worker threads of the dedicated quote and simulation runtime (0
+    /// runs that work on the shared runtime).
+    pub quote_runtime_threads: usize,
+
+    /// Most pools the registry may hold (0 means unlimited).
+    pub max_pools: usize,
+
+    /// Most liquidity positions tracked per pool (0 means unlimited).
+    pub max_positions_per_pool: usize,
+
+    /// How long a mutation waits for a pool's write lock before it is
+    /// rejected, in milliseconds (0 waits indefinitely).
+    pub pool_lock_timeout_ms: u64,
+
+    /// Days without a state change after which a pool holding no
+    /// liquidity is stale.
+    pub stale_pool_days: u32,
+
+    /// Whether stale pools are archived automatically.
+    pub stale_pool_auto_archive: bool,
+
+    /// Fee tier in basis points for create requests without `fee_bps`.
+    pub pool_default_fee_bps: Option<u32>,
+
+    /// Tick spacing for `clmm` create requests without `tick_spacing`.
+    pub pool_default_tick_spacing: Option<u32>,
+
+    /// Amplification for `hybrid` create requests without `amplification`.
+    pub pool_default_amplification: Option<u32>,
+
+    /// Swap fee tiers as `volume=bps` pairs, e.g. `1000000=5,50000000=10`
+    /// (empty disables fee rebates).
+    pub fee_tiers: String,
+
+    /// Comma-separated client ids that may manage every pool, whoever
+    /// owns it.
+    pub admin_client_ids: String,
+
+    /// Token-bucket limits per route, e.g. `/swap=10/s,/quote=100/s,*=1200/m`
+    /// (see [`crate::service::route_limits::RouteRateLimiter::parse`]).
+    /// Empty disables them.
+    pub route_rate_limits: String,
+
+    /// Seconds a response to an `Idempotency-Key` is replayed to retries.
+    pub idempotency_ttl_secs: u64,
+
+    /// Idempotency keys kept in memory; the least recently used beyond
+    /// this are dropped.
+    pub idempotency_cache_size: usize,
+
+    /// Whether to also store idempotency keys in Postgres (when
+    /// persistence is enabled).
+    pub idempotency_persist: bool,
+
+    /// Days of hourly per-API-key usage kept in memory.
+    pub usage_retention_days: u32,
 }
 
 impl GatewayConfig {
@@ -68,11 +258,86 @@ impl GatewayConfig {
 
         let persistence_enabled = parse_env_bool("PERSISTENCE_ENABLED", true);
         let snapshot_interval_secs = parse_env("PERSISTENCE_SNAPSHOT_INTERVAL_SECS", 60);
+        let snapshot_every_mutations =
+            std::env::var("PERSISTENCE_SNAPSHOT_EVERY_MUTATIONS").unwrap_or_default();
         let event_log_enabled = parse_env_bool("PERSISTENCE_EVENT_LOG_ENABLED", true);
+        let event_log_batch_size = parse_env("PERSISTENCE_EVENT_LOG_BATCH_SIZE", 100);
+        let event_log_buffer = parse_env("PERSISTENCE_EVENT_LOG_BUFFER", 100_000);
+        let recover_on_startup = parse_env_bool("PERSISTENCE_RECOVER_ON_STARTUP", true);
+        let candles_enabled = parse_env_bool("PERSISTENCE_CANDLES_ENABLED", true);
+        let candle_flush_interval_ms = parse_env("PERSISTENCE_CANDLE_FLUSH_INTERVAL_MS", 1_000);
+        let candle_memory_buckets = parse_env("CANDLE_MEMORY_BUCKETS", 500);
+        let pool_stats_backfill = parse_env_bool("PERSISTENCE_POOL_STATS_BACKFILL", true);
+        let quote_audit_enabled = parse_env_bool("PERSISTENCE_QUOTE_AUDIT_ENABLED", false);
+        let quote_audit_sample_bps = parse_env("PERSISTENCE_QUOTE_AUDIT_SAMPLE_BPS", 10_000);
+        let quote_audit_clients =
+            std::env::var("PERSISTENCE_QUOTE_AUDIT_CLIENTS").unwrap_or_default();
         let cleanup_after_days = parse_env("PERSISTENCE_CLEANUP_AFTER_DAYS", 30);
 
         let event_bus_capacity = parse_env("EVENT_BUS_CAPACITY", 10_000);
 
+        let reuse_port = parse_env_bool("LISTEN_REUSE_PORT", false);
+        let pid_file = std::env::var("PID_FILE").ok().filter(|s| !s.is_empty());
+        let shutdown_grace_secs = parse_env("SHUTDOWN_GRACE_SECS", 30);
+        let ws_session_ttl_secs = parse_env("WS_SESSION_TTL_SECS", 300);
+        let ws_session_handover_file = std::env::var("WS_SESSION_HANDOVER_FILE")
+            .ok()
+            .filter(|s| !s.is_empty());
+        let ws_drain_notice_secs = parse_env("WS_DRAIN_NOTICE_SECS", 0);
+        let ws_drain_alternate_host = std::env::var("WS_DRAIN_ALTERNATE_HOST")
+            .ok()
+            .filter(|s| !s.is_empty());
+        let ws_max_message_bytes = parse_env("WS_MAX_MESSAGE_BYTES", 65_536);
+        let ws_max_json_depth = parse_env("WS_MAX_JSON_DEPTH", 16);
+        let ws_outbound_queue = parse_env("WS_OUTBOUND_QUEUE", 1024);
+        let ws_outbound_disconnect_on_overflow =
+            parse_env_bool("WS_OUTBOUND_DISCONNECT_ON_OVERFLOW", false);
+        let ws_ping_interval_secs = parse_env("WS_PING_INTERVAL_SECS", 30);
+        let ws_idle_timeout_secs = parse_env("WS_IDLE_TIMEOUT_SECS", 90);
+
+        let cluster_instance_id =
+            std::env::var("CLUSTER_INSTANCE_ID").unwrap_or_else(|_| "gateway-0".to_string());
+        let cluster_peers = std::env::var("CLUSTER_PEERS").unwrap_or_default();
+        let cluster_secret = std::env::var("CLUSTER_SECRET")
+            .ok()
+            .filter(|s| !s.is_empty());
+
+        let route_hop_cost = parse_env("ROUTE_HOP_COST", 0);
+
+        let request_signing_keys = std::env::var("REQUEST_SIGNING_KEYS").unwrap_or_default();
+        let request_signing_max_skew_secs = parse_env("REQUEST_SIGNING_MAX_SKEW_SECS", 300);
+
+        let event_signing_key = std::env::var("EVENT_SIGNING_KEY")
+            .ok()
+            .filter(|s| !s.is_empty());
+        let event_signing_key_id =
+            std::env::var("EVENT_SIGNING_KEY_ID").unwrap_or_else(|_| "gateway".to_string());
+
+        let replica_mode = parse_env_bool("REPLICA_MODE", false);
+        let replica_poll_interval_ms = parse_env("REPLICA_POLL_INTERVAL_MS", 500);
+
+        let heavy_max_concurrent = parse_env("HEAVY_MAX_CONCURRENT", 8);
+        let heavy_max_per_key = parse_env("HEAVY_MAX_PER_KEY", 2);
+        let heavy_queue_timeout_ms = parse_env("HEAVY_QUEUE_TIMEOUT_MS", 1_000);
+        let quote_runtime_threads = parse_env("QUOTE_RUNTIME_THREADS", 0);
+
+        let max_pools = parse_env("MAX_POOLS", 10_000);
+        let max_positions_per_pool = parse_env("MAX_POSITIONS_PER_POOL", 10_000);
+        let pool_lock_timeout_ms = parse_env("POOL_LOCK_TIMEOUT_MS", 5_000);
+
+        let stale_pool_days = parse_env("STALE_POOL_DAYS", 30);
+        let stale_pool_auto_archive = parse_env_bool("STALE_POOL_AUTO_ARCHIVE", false);
+        let pool_default_fee_bps = parse_env_opt("POOL_DEFAULT_FEE_BPS");
+        let pool_default_tick_spacing = parse_env_opt("POOL_DEFAULT_TICK_SPACING");
+        let pool_default_amplification = parse_env_opt("POOL_DEFAULT_AMPLIFICATION");
+        let fee_tiers = std::env::var("FEE_TIERS").unwrap_or_default();
+        let admin_client_ids = std::env::var("ADMIN_CLIENT_IDS").unwrap_or_default();
+        let route_rate_limits = std::env::var("ROUTE_RATE_LIMITS").unwrap_or_default();
+        let idempotency_ttl_secs = parse_env("IDEMPOTENCY_TTL_SECS", 86_400);
+        let idempotency_cache_size = parse_env("IDEMPOTENCY_CACHE_SIZE", 10_000);
+        let idempotency_persist = parse_env_bool("IDEMPOTENCY_PERSIST", true);
+        let usage_retention_days = parse_env("USAGE_RETENTION_DAYS", 30);
+
         Ok(Self {
             listen_addr,
             database_url,
@@ -81,9 +346,62 @@ impl GatewayConfig {
             database_connect_timeout_secs,
             persistence_enabled,
             snapshot_interval_secs,
+            snapshot_every_mutations,
             event_log_enabled,
+            event_log_batch_size,
+            event_log_buffer,
+            recover_on_startup,
+            candles_enabled,
+            candle_flush_interval_ms,
+            candle_memory_buckets,
+            pool_stats_backfill,
+            quote_audit_enabled,
+            quote_audit_sample_bps,
+            quote_audit_clients,
             cleanup_after_days,
             event_bus_capacity,
+            reuse_port,
+            pid_file,
+            shutdown_grace_secs,
+            ws_session_ttl_secs,
+            ws_session_handover_file,
+            ws_drain_notice_secs,
+            ws_drain_alternate_host,
+            ws_max_message_bytes,
+            ws_max_json_depth,
+            ws_outbound_queue,
+            ws_outbound_disconnect_on_overflow,
+            ws_ping_interval_secs,
+            ws_idle_timeout_secs,
+            cluster_instance_id,
+            cluster_peers,
+            cluster_secret,
+            route_hop_cost,
+            request_signing_keys,
+            request_signing_max_skew_secs,
+            event_signing_key,
+            event_signing_key_id,
+            replica_mode,
+            replica_poll_interval_ms,
+            heavy_max_concurrent,
+            heavy_max_per_key,
+            heavy_queue_timeout_ms,
+            quote_runtime_threads,
+            max_pools,
+            max_positions_per_pool,
+            pool_lock_timeout_ms,
+            stale_pool_days,
+            stale_pool_auto_archive,
+            pool_default_fee_bps,
+            pool_default_tick_spacing,
+            pool_default_amplification,
+            fee_tiers,
+            admin_client_ids,
+            route_rate_limits,
+            idempotency_ttl_secs,
+            idempotency_cache_size,
+            idempotency_persist,
+            usage_retention_days,
         })
     }
 }
@@ -97,6 +415,12 @@ fn parse_env<T: std::str::FromStr>(key: &str, default: T) -> T {
         .unwrap_or(default)
 }
 
+/// Parses an optional environment variable as `T`, returning `None` when
+/// it is missing or invalid.
+fn parse_env_opt<T: std::str::FromStr>(key: &str) -> Option<T> {
+    std::env::var(key).ok().and_then(|v| v.parse().ok())
+}
+
 /// Parses an environment variable as a boolean. Accepts `"true"`, `"1"`,
 /// `"false"`, `"0"` (case-insensitive). Returns `default` otherwise.
 fn parse_env_bool(key: &str, default: bool) -> bool {
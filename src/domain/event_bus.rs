@@ -5,6 +5,7 @@
 //! connections subscribe to receive filtered events.
 
 use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
 
 use super::PoolEvent;
 
@@ -42,6 +43,26 @@ impl EventBus {
         self.sender.subscribe()
     }
 
+    /// Starts a task republishing every event of this bus on `target`.
+    /// The task ends when this bus closes.
+    pub fn relay(&self, target: &Self) -> JoinHandle<()> {
+        let mut events = self.subscribe();
+        let target = target.clone();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        target.publish(event);
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(lagged = n, "event relay lagged behind event bus");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
     /// Returns the current number of active receivers.
     #[must_use]
     pub fn receiver_count(&self) -> usize {
@@ -63,6 +84,9 @@ mod tests {
             token_a: "0xaaa".to_string(),
             token_b: "0xbbb".to_string(),
             fee_tier: 30,
+            config: serde_json::Value::Null,
+            metadata: serde_json::Value::Null,
+            owner: None,
             timestamp: Utc::now(),
         }
     }
@@ -110,6 +134,25 @@ mod tests {
         assert_eq!(e1.pool_id(), e2.pool_id());
     }
 
+    #[tokio::test]
+    async fn relay_republishes_events_on_the_target() {
+        let (source, target) = (EventBus::new(100), EventBus::new(100));
+        let mut rx = target.subscribe();
+        let relay = source.relay(&target);
+
+        let id = PoolId::new();
+        source.publish(make_event(id));
+        let Ok(event) = rx.recv().await else {
+            panic!("expected a relayed event");
+        };
+        assert_eq!(event.pool_id(), id);
+
+        drop(source);
+        let Ok(()) = relay.await else {
+            panic!("relay panicked");
+        };
+    }
+
     #[test]
     fn receiver_count_tracks_subscribers() {
         let bus = EventBus::new(100);
@@ -0,0 +1,182 @@
+//! The operation journal of a pool.
+//!
+//! A pool's journal only grows, and it is captured whole for snapshots,
+//! route and quote sandboxes, and backtests. [`Journal`] seals its
+//! operations into immutable checkpoints shared behind `Arc`s after each
+//! scheduled snapshot and every [`CHECKPOINT_OPS`] operations, so a
+//! capture copies at most that many operations plus a few pointers,
+//! however long the pool's history. Checkpoints are merged with their
+//! older neighbour once it is no larger, which keeps their number
+//! logarithmic in the journal's length.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::PoolOperation;
+
+/// Operations kept outside a checkpoint before they are sealed.
+pub const CHECKPOINT_OPS: usize = 256;
+
+/// Every mutation applied to a pool since creation, in order.
+#[derive(Debug, Clone, Default)]
+pub struct Journal {
+    checkpoints: Vec<Arc<[PoolOperation]>>,
+    tail: Vec<PoolOperation>,
+}
+
+impl Journal {
+    /// Creates an empty journal.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `op`, sealing a checkpoint every [`CHECKPOINT_OPS`]
+    /// operations.
+    pub fn push(&mut self, op: PoolOperation) {
+        self.tail.push(op);
+        if self.tail.len() >= CHECKPOINT_OPS {
+            self.checkpoint();
+        }
+    }
+
+    /// Seals the operations appended since the last checkpoint.
+    pub fn checkpoint(&mut self) {
+        if self.tail.is_empty() {
+            return;
+        }
+        let mut sealed = std::mem::take(&mut self.tail);
+        while let Some(older) = self.checkpoints.last()
+            && older.len() <= sealed.len()
+        {
+            let mut merged = older.to_vec();
+            merged.append(&mut sealed);
+            sealed = merged;
+            self.checkpoints.pop();
+        }
+        self.checkpoints.push(sealed.into());
+    }
+
+    /// Number of operations journaled.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.segments().map(<[PoolOperation]>::len).sum()
+    }
+
+    /// Returns `true` if nothing has been journaled.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tail.is_empty() && self.checkpoints.is_empty()
+    }
+
+    /// Iterates over every operation, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &PoolOperation> {
+        self.since(0)
+    }
+
+    /// Iterates over the operations from position `index` on, skipping
+    /// whole checkpoints before it.
+    pub fn since(&self, index: usize) -> impl Iterator<Item = &PoolOperation> {
+        let mut skip = index;
+        self.segments().flat_map(move |segment| {
+            let rest = segment.get(skip.min(segment.len())..).unwrap_or_default();
+            skip = skip.saturating_sub(segment.len());
+            rest.iter()
+        })
+    }
+
+    /// Copies the operations out.
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<PoolOperation> {
+        self.iter().cloned().collect()
+    }
+
+    fn segments(&self) -> impl Iterator<Item = &[PoolOperation]> {
+        self.checkpoints
+            .iter()
+            .map(|checkpoint| &**checkpoint)
+            .chain(std::iter::once(self.tail.as_slice()))
+    }
+}
+
+impl PartialEq for Journal {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl From<Vec<PoolOperation>> for Journal {
+    fn from(ops: Vec<PoolOperation>) -> Self {
+        let mut journal = Self {
+            checkpoints: Vec::new(),
+            tail: ops,
+        };
+        journal.checkpoint();
+        journal
+    }
+}
+
+impl Serialize for Journal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for Journal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<PoolOperation>::deserialize(deserializer).map(Self::from)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::pool_operation::{SwapKind, TokenSide};
+
+    fn swap(amount: usize) -> PoolOperation {
+        PoolOperation::Swap {
+            token_in: TokenSide::First,
+            kind: SwapKind::ExactIn,
+            amount: amount.to_string(),
+        }
+    }
+
+    #[test]
+    fn captures_share_checkpoints_and_keep_the_order() {
+        let ops: Vec<PoolOperation> = (0..10 * CHECKPOINT_OPS + 7).map(swap).collect();
+        let mut journal = Journal::new();
+        for op in &ops {
+            journal.push(op.clone());
+        }
+        assert_eq!(journal.len(), ops.len());
+        assert_eq!(journal.to_vec(), ops);
+        assert!(
+            journal.checkpoints.len() <= 4,
+            "{} checkpoints",
+            journal.checkpoints.len()
+        );
+
+        let captured = journal.clone();
+        assert!(
+            captured
+                .checkpoints
+                .iter()
+                .zip(&journal.checkpoints)
+                .all(|(a, b)| Arc::ptr_eq(a, b))
+        );
+        let from = 3 * CHECKPOINT_OPS + 1;
+        assert!(journal.since(from).eq(ops.get(from..).unwrap_or_default()));
+        assert_eq!(journal.since(ops.len() + 1).count(), 0);
+
+        let Ok(json) = serde_json::to_value(&journal) else {
+            panic!("journal did not serialize");
+        };
+        assert_eq!(serde_json::to_value(&ops).ok(), Some(json.clone()));
+        let Ok(decoded) = serde_json::from_value::<Journal>(json) else {
+            panic!("journal did not deserialize");
+        };
+        assert_eq!(decoded, journal);
+    }
+}
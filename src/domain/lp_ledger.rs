@@ -0,0 +1,123 @@
+//! LP share balances per provider.
+//!
+//! The ledger sums the LP units of every position by its owner, so a
+//! provider's holding in a pool is one lookup rather than a scan of the
+//! [`PositionRegistry`](super::PositionRegistry). Like the registry it
+//! lives in the [`PoolEntry`](super::PoolEntry) and is rebuilt from the
+//! operation journal. Liquidity held by positions without an owner, and
+//! the pool's creation liquidity, is not attributed to any provider.
+
+use std::collections::BTreeMap;
+
+use crate::error::GatewayError;
+
+/// LP units held by each provider of one pool.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LpLedger {
+    balances: BTreeMap<String, u128>,
+}
+
+impl LpLedger {
+    /// Creates an empty ledger.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Credits `units` minted to `owner`.
+    pub fn credit(&mut self, owner: &str, units: u128) {
+        if units == 0 {
+            return;
+        }
+        let balance = self.balances.entry(owner.to_string()).or_default();
+        *balance = balance.saturating_add(units);
+    }
+
+    /// Debits `units` burned by `owner`, forgetting providers left with
+    /// nothing.
+    pub fn debit(&mut self, owner: &str, units: u128) {
+        if let Some(balance) = self.balances.get_mut(owner) {
+            *balance = balance.saturating_sub(units);
+            if *balance == 0 {
+                self.balances.remove(owner);
+            }
+        }
+    }
+
+    /// LP units held by `owner`.
+    #[must_use]
+    pub fn balance(&self, owner: &str) -> u128 {
+        self.balances.get(owner).copied().unwrap_or(0)
+    }
+
+    /// Checks that `owner` holds at least `units`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InsufficientLiquidity`] if it holds fewer.
+    pub fn ensure_holds(&self, owner: &str, units: u128) -> Result<(), GatewayError> {
+        if self.balance(owner) < units {
+            return Err(GatewayError::InsufficientLiquidity);
+        }
+        Ok(())
+    }
+
+    /// Iterates over every provider holding units, in owner order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u128)> {
+        self.balances
+            .iter()
+            .map(|(owner, units)| (owner.as_str(), *units))
+    }
+
+    /// Number of providers holding units.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.balances.len()
+    }
+
+    /// Returns `true` if no provider holds units.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.balances.is_empty()
+    }
+
+    /// LP units attributed to providers.
+    #[must_use]
+    pub fn total(&self) -> u128 {
+        self.balances
+            .values()
+            .fold(0u128, |acc, units| acc.saturating_add(*units))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn balances_follow_credits_and_debits() {
+        let mut ledger = LpLedger::new();
+        ledger.credit("desk-1", 100);
+        ledger.credit("desk-2", 40);
+        ledger.credit("desk-1", 50);
+        ledger.credit("desk-3", 0);
+        assert_eq!(ledger.balance("desk-1"), 150);
+        assert_eq!(ledger.total(), 190);
+        assert_eq!(ledger.len(), 2);
+        assert!(ledger.ensure_holds("desk-2", 40).is_ok());
+        assert!(matches!(
+            ledger.ensure_holds("desk-2", 41),
+            Err(GatewayError::InsufficientLiquidity)
+        ));
+        assert!(ledger.ensure_holds("nobody", 1).is_err());
+
+        ledger.debit("desk-2", 40);
+        ledger.debit("nobody", 10);
+        assert_eq!(
+            ledger.iter().collect::<Vec<_>>(),
+            [("desk-1", 150)],
+            "emptied providers are forgotten"
+        );
+    }
+}
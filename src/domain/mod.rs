@@ -2,16 +2,37 @@
 //!
 //! This module contains the server-side domain model including pool
 //! identity, pool entries with metadata, the event bus for broadcasting
-//! state changes, and the pool registry for concurrent pool storage.
+//! state changes, the pool registry for concurrent pool storage, the
+//! per-pool registry of liquidity positions and ledger of LP shares, the
+//! resting limit orders of order-book pools, the oracle price bounds of
+//! dynamic pools, and the capabilities of each pool type.
 
 pub mod event_bus;
+pub mod journal;
+pub mod lp_ledger;
+pub mod oracle_bounds;
+pub mod order_book;
+pub mod order_id;
+pub mod pool_capabilities;
 pub mod pool_entry;
 pub mod pool_event;
+pub mod pool_filter;
 pub mod pool_id;
+pub mod pool_operation;
 pub mod pool_registry;
+pub mod position_id;
+pub mod position_registry;
 
 pub use event_bus::EventBus;
+pub use journal::Journal;
+pub use lp_ledger::LpLedger;
+pub use order_book::OrderBook;
+pub use order_id::OrderId;
 pub use pool_entry::PoolEntry;
 pub use pool_event::PoolEvent;
+pub use pool_filter::{PoolFilter, PoolSort};
 pub use pool_id::PoolId;
-pub use pool_registry::PoolRegistry;
+pub use pool_operation::PoolOperation;
+pub use pool_registry::{PoolRegistry, Tombstone};
+pub use position_id::PositionId;
+pub use position_registry::PositionRegistry;
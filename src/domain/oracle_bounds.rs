@@ -0,0 +1,142 @@
+//! Sanity bounds on the oracle price of dynamic pools.
+//!
+//! A dynamic pool quotes around the price its oracle feed pushes, so one
+//! bad update would reprice it instantly. The optional `oracle_bounds`
+//! object of a dynamic pool's creation config limits the prices it
+//! accepts, in the same units as `oracle_price`:
+//!
+//! ```json
+//! "oracle_bounds": { "min_price": 0.5, "max_price": 2.0, "max_step_pct": 5.0 }
+//! ```
+//!
+//! Every field is optional; an update outside them is rejected with
+//! [`GatewayError::OraclePriceRejected`] and the pool keeps its price.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::GatewayError;
+
+/// Prices a dynamic pool's oracle may move to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OracleBounds {
+    /// Lowest acceptable price.
+    #[serde(default)]
+    pub min_price: Option<f64>,
+    /// Highest acceptable price.
+    #[serde(default)]
+    pub max_price: Option<f64>,
+    /// Largest move from the current price in one update, in percent.
+    #[serde(default)]
+    pub max_step_pct: Option<f64>,
+}
+
+impl OracleBounds {
+    /// Reads the `oracle_bounds` of a creation config; a config without
+    /// one is unbounded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InvalidRequest`] if the bounds are
+    /// malformed, not positive, or `min_price` exceeds `max_price`.
+    pub fn from_config(config: &serde_json::Value) -> Result<Self, GatewayError> {
+        let Some(raw) = config.get("oracle_bounds").filter(|v| !v.is_null()) else {
+            return Ok(Self::default());
+        };
+        let bounds: Self = serde_json::from_value(raw.clone())
+            .map_err(|e| GatewayError::InvalidRequest(format!("invalid oracle_bounds: {e}")))?;
+        for (field, value) in [
+            ("min_price", bounds.min_price),
+            ("max_price", bounds.max_price),
+            ("max_step_pct", bounds.max_step_pct),
+        ] {
+            if value.is_some_and(|v| !v.is_finite() || v <= 0.0) {
+                return Err(GatewayError::InvalidRequest(format!(
+                    "oracle_bounds.{field} must be positive"
+                )));
+            }
+        }
+        if let (Some(min), Some(max)) = (bounds.min_price, bounds.max_price)
+            && min > max
+        {
+            return Err(GatewayError::InvalidRequest(
+                "oracle_bounds.min_price exceeds max_price".to_string(),
+            ));
+        }
+        Ok(bounds)
+    }
+
+    /// Returns why `proposed` is out of bounds, or `None` if it is
+    /// acceptable. The step limit applies only when `current` is given.
+    #[must_use]
+    pub fn violation(&self, current: Option<f64>, proposed: f64) -> Option<String> {
+        if let Some(min) = self.min_price
+            && proposed < min
+        {
+            return Some(format!("{proposed} is below min_price {min}"));
+        }
+        if let Some(max) = self.max_price
+            && proposed > max
+        {
+            return Some(format!("{proposed} is above max_price {max}"));
+        }
+        if let (Some(max_step), Some(current)) = (self.max_step_pct, current)
+            && current > 0.0
+        {
+            let step_pct = (proposed - current).abs() / current * 100.0;
+            if step_pct > max_step {
+                return Some(format!(
+                    "move from {current} to {proposed} is {step_pct:.2}%, above max_step_pct {max_step}"
+                ));
+            }
+        }
+        None
+    }
+
+    /// Checks an update from `current` to `proposed`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::OraclePriceRejected`] if `proposed` is
+    /// out of bounds.
+    pub fn check(&self, current: f64, proposed: f64) -> Result<(), GatewayError> {
+        match self.violation(Some(current), proposed) {
+            Some(reason) => Err(GatewayError::OraclePriceRejected(reason)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_prices_outside_range_and_large_steps() {
+        let config = serde_json::json!({
+            "oracle_bounds": {"min_price": 0.5, "max_price": 2.0, "max_step_pct": 10.0}
+        });
+        let Ok(bounds) = OracleBounds::from_config(&config) else {
+            panic!("valid bounds rejected");
+        };
+        assert!(bounds.check(1.0, 1.05).is_ok());
+        assert!(bounds.check(1.0, 1.2).is_err());
+        assert!(bounds.check(0.52, 0.49).is_err());
+        assert!(bounds.check(1.95, 2.01).is_err());
+        assert!(bounds.violation(None, 1.9).is_none());
+
+        let Ok(unbounded) = OracleBounds::from_config(&serde_json::json!({})) else {
+            panic!("missing bounds rejected");
+        };
+        assert!(unbounded.check(1.0, 1_000.0).is_ok());
+
+        for bad in [
+            serde_json::json!({"oracle_bounds": {"min_price": 3.0, "max_price": 2.0}}),
+            serde_json::json!({"oracle_bounds": {"max_step_pct": -1.0}}),
+            serde_json::json!({"oracle_bounds": {"max_step": 5.0}}),
+        ] {
+            assert!(OracleBounds::from_config(&bad).is_err());
+        }
+    }
+}
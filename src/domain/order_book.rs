@@ -0,0 +1,247 @@
+//! Resting limit orders of an order-book pool.
+//!
+//! Orders are placed post-only: one that would cross the opposite side
+//! of the book is rejected rather than matched. The book lives in the
+//! [`PoolEntry`](super::PoolEntry) and is rebuilt from the operation
+//! journal, so resting orders survive snapshots and replay.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::OrderId;
+use crate::error::GatewayError;
+
+/// Price levels per side returned when the caller does not ask.
+pub const DEFAULT_BOOK_LEVELS: u32 = 20;
+
+/// Most price levels per side a depth snapshot returns.
+pub const MAX_BOOK_LEVELS: u32 = 100;
+
+/// Side of the book an order rests on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderSide {
+    /// Buys token A with token B.
+    Bid,
+    /// Sells token A for token B.
+    Ask,
+}
+
+impl OrderSide {
+    /// Event and API representation.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Bid => "bid",
+            Self::Ask => "ask",
+        }
+    }
+}
+
+/// A resting limit order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LimitOrder {
+    /// Side of the book.
+    pub side: OrderSide,
+    /// Limit price in token B units per token A unit; a multiple of the
+    /// pool's `tick_size`.
+    pub price: u128,
+    /// Token A units; a multiple of the pool's `lot_size`.
+    pub quantity: u128,
+    /// Client id that placed the order, if the request carried one.
+    pub owner: Option<String>,
+}
+
+/// Aggregated orders at one price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceLevel {
+    /// Level price.
+    pub price: u128,
+    /// Token A units resting at the price.
+    pub quantity: u128,
+    /// Number of orders at the price.
+    pub orders: usize,
+}
+
+/// Aggregated view of both sides of a book.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BookDepth {
+    /// Bid levels, highest price first.
+    pub bids: Vec<PriceLevel>,
+    /// Ask levels, lowest price first.
+    pub asks: Vec<PriceLevel>,
+    /// Resting orders on both sides, including levels beyond the cut.
+    pub resting_orders: usize,
+}
+
+/// Resting orders of one pool, by id.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderBook {
+    orders: BTreeMap<OrderId, LimitOrder>,
+}
+
+impl OrderBook {
+    /// Creates an empty book.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of resting orders.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// Returns `true` if no order is resting.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    /// Returns the order `id`.
+    #[must_use]
+    pub fn get(&self, id: &OrderId) -> Option<&LimitOrder> {
+        self.orders.get(id)
+    }
+
+    /// Iterates over resting orders in id order.
+    pub fn iter(&self) -> impl Iterator<Item = (&OrderId, &LimitOrder)> {
+        self.orders.iter()
+    }
+
+    /// Highest bid price.
+    #[must_use]
+    pub fn best_bid(&self) -> Option<u128> {
+        self.prices(OrderSide::Bid).max()
+    }
+
+    /// Lowest ask price.
+    #[must_use]
+    pub fn best_ask(&self) -> Option<u128> {
+        self.prices(OrderSide::Ask).min()
+    }
+
+    /// Rests `order` under `id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InvalidRequest`] if `id` is already
+    /// resting or the order would cross the opposite side.
+    pub fn place(&mut self, id: OrderId, order: LimitOrder) -> Result<(), GatewayError> {
+        if self.orders.contains_key(&id) {
+            return Err(GatewayError::InvalidRequest(format!(
+                "order {id} is already resting"
+            )));
+        }
+        let crosses = match order.side {
+            OrderSide::Bid => self.best_ask().is_some_and(|ask| order.price >= ask),
+            OrderSide::Ask => self.best_bid().is_some_and(|bid| order.price <= bid),
+        };
+        if crosses {
+            return Err(GatewayError::InvalidRequest(format!(
+                "{} at {} would cross the book; orders are post-only",
+                order.side.as_str(),
+                order.price
+            )));
+        }
+        self.orders.insert(id, order);
+        Ok(())
+    }
+
+    /// Removes and returns the order `id`.
+    pub fn cancel(&mut self, id: &OrderId) -> Option<LimitOrder> {
+        self.orders.remove(id)
+    }
+
+    /// Aggregates `side` by price, best first, up to `levels` levels.
+    #[must_use]
+    pub fn depth(&self, side: OrderSide, levels: usize) -> Vec<PriceLevel> {
+        let mut by_price: BTreeMap<u128, PriceLevel> = BTreeMap::new();
+        for order in self.orders.values().filter(|o| o.side == side) {
+            let level = by_price.entry(order.price).or_insert(PriceLevel {
+                price: order.price,
+                quantity: 0,
+                orders: 0,
+            });
+            level.quantity = level.quantity.saturating_add(order.quantity);
+            level.orders = level.orders.saturating_add(1);
+        }
+        let levels_iter = by_price.into_values();
+        match side {
+            OrderSide::Bid => levels_iter.rev().take(levels).collect(),
+            OrderSide::Ask => levels_iter.take(levels).collect(),
+        }
+    }
+
+    /// Aggregates both sides, up to `levels` levels each.
+    #[must_use]
+    pub fn snapshot(&self, levels: usize) -> BookDepth {
+        BookDepth {
+            bids: self.depth(OrderSide::Bid, levels),
+            asks: self.depth(OrderSide::Ask, levels),
+            resting_orders: self.len(),
+        }
+    }
+
+    fn prices(&self, side: OrderSide) -> impl Iterator<Item = u128> + '_ {
+        self.orders
+            .values()
+            .filter(move |o| o.side == side)
+            .map(|o| o.price)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn order(side: OrderSide, price: u128, quantity: u128) -> LimitOrder {
+        LimitOrder {
+            side,
+            price,
+            quantity,
+            owner: None,
+        }
+    }
+
+    #[test]
+    fn depth_aggregates_levels_best_first_and_rejects_crossing() {
+        let mut book = OrderBook::new();
+        for (side, price, quantity) in [
+            (OrderSide::Bid, 99, 10),
+            (OrderSide::Bid, 98, 5),
+            (OrderSide::Bid, 99, 7),
+            (OrderSide::Ask, 101, 3),
+            (OrderSide::Ask, 103, 4),
+        ] {
+            let Ok(()) = book.place(OrderId::new(), order(side, price, quantity)) else {
+                panic!("non-crossing order rejected");
+            };
+        }
+        assert_eq!((book.best_bid(), book.best_ask()), (Some(99), Some(101)));
+
+        let bids = book.depth(OrderSide::Bid, 10);
+        assert_eq!(
+            bids.iter()
+                .map(|l| (l.price, l.quantity, l.orders))
+                .collect::<Vec<_>>(),
+            vec![(99, 17, 2), (98, 5, 1)]
+        );
+        let asks = book.depth(OrderSide::Ask, 1);
+        assert_eq!(asks.iter().map(|l| l.price).collect::<Vec<_>>(), vec![101]);
+
+        assert!(
+            book.place(OrderId::new(), order(OrderSide::Bid, 101, 1))
+                .is_err()
+        );
+        assert!(
+            book.place(OrderId::new(), order(OrderSide::Ask, 99, 1))
+                .is_err()
+        );
+        assert_eq!(book.len(), 5);
+    }
+}
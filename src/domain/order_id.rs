@@ -0,0 +1,57 @@
+//! Type-safe limit order identifier.
+//!
+//! [`OrderId`] names a resting order on an order-book pool: it is
+//! assigned when the order is placed and referenced to cancel it.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Unique identifier for a limit order.
+///
+/// Wraps a UUID v4. Orders are scoped to the pool they were placed in;
+/// the same id is never valid in another pool.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
+#[serde(transparent)]
+pub struct OrderId(uuid::Uuid);
+
+impl OrderId {
+    /// Creates a new random `OrderId` (UUID v4).
+    #[must_use]
+    pub fn new() -> Self {
+        Self(uuid::Uuid::new_v4())
+    }
+
+    /// Creates an `OrderId` from an existing [`uuid::Uuid`].
+    #[must_use]
+    pub const fn from_uuid(uuid: uuid::Uuid) -> Self {
+        Self(uuid)
+    }
+
+    /// Returns the inner [`uuid::Uuid`].
+    #[must_use]
+    pub const fn as_uuid(&self) -> &uuid::Uuid {
+        &self.0
+    }
+}
+
+impl Default for OrderId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for OrderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<uuid::Uuid> for OrderId {
+    fn from(uuid: uuid::Uuid) -> Self {
+        Self(uuid)
+    }
+}
@@ -0,0 +1,72 @@
+//! What each pool type supports.
+//!
+//! [`PoolCapabilities`] tells generic clients which actions a pool
+//! accepts, so they can enable or disable them without knowing every
+//! pool type. It is derived from the pool type alone.
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Actions supported by a pool type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct PoolCapabilities {
+    /// Liquidity is added as a position over a tick range (`range` on
+    /// `liquidity/add`).
+    pub supports_positions: bool,
+    /// Limit orders can be placed and cancelled.
+    pub supports_orders: bool,
+    /// Liquidity can be added in one token only.
+    pub supports_single_sided_liquidity: bool,
+    /// The fee tier can be changed after creation. No pool type allows
+    /// it yet.
+    pub supports_fee_update: bool,
+    /// The pool may hold more than two tokens.
+    pub multi_token: bool,
+}
+
+impl PoolCapabilities {
+    /// Capabilities of `pool_type`; an unknown type supports nothing.
+    #[must_use]
+    pub fn of(pool_type: &str) -> Self {
+        let none = Self::default();
+        match pool_type {
+            "clmm" => Self {
+                supports_positions: true,
+                supports_single_sided_liquidity: true,
+                ..none
+            },
+            "weighted" => Self {
+                supports_single_sided_liquidity: true,
+                multi_token: true,
+                ..none
+            },
+            "dynamic" => Self {
+                supports_single_sided_liquidity: true,
+                ..none
+            },
+            "orderbook" => Self {
+                supports_orders: true,
+                ..none
+            },
+            _ => none,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capabilities_follow_pool_type() {
+        assert!(PoolCapabilities::of("orderbook").supports_orders);
+        assert!(PoolCapabilities::of("clmm").supports_positions);
+        assert!(PoolCapabilities::of("weighted").multi_token);
+        assert_eq!(
+            PoolCapabilities::of("constant_product"),
+            PoolCapabilities::default()
+        );
+        assert!(!PoolCapabilities::of("dynamic").supports_orders);
+    }
+}
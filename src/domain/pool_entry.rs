@@ -1,10 +1,87 @@
 //! Pool entry combining hydra-amm pool with server-side metadata.
 
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
+use hydra_amm::domain::{Amount, Liquidity, Position, SwapResult, Tick};
+use hydra_amm::factory::DefaultPoolFactory;
 use hydra_amm::pools::PoolBox;
-use serde::Serialize;
+use hydra_amm::traits::{LiquidityPool, SwapPool};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use super::PoolId;
+use super::journal::Journal;
+use super::lp_ledger::LpLedger;
+use super::oracle_bounds::OracleBounds;
+use super::order_book::{LimitOrder, OrderBook};
+use super::pool_operation::{PoolOperation, TickRange, TokenSide, parse_u128};
+pub use super::position_registry::LiquidityPosition;
+use super::position_registry::PositionRegistry;
+use crate::api::config_parser;
+use crate::error::GatewayError;
+
+/// Token metadata as supplied at pool creation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenInfo {
+    /// Token address exactly as given in the creation request.
+    pub address: String,
+    /// Number of decimal places.
+    pub decimals: u8,
+    /// Human-readable symbol (may be empty).
+    #[serde(default)]
+    pub symbol: String,
+}
+
+/// Lifecycle state of a pool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolStatus {
+    /// Accepts every operation.
+    #[default]
+    Active,
+    /// Halted by an operator; rejects every operation until resumed.
+    Paused,
+    /// Winding down: rejects swaps, new liquidity, and new orders, but
+    /// lets providers withdraw, collect fees, and cancel orders.
+    Draining,
+    /// Archived for inactivity; rejects every operation for good.
+    Archived,
+}
+
+impl PoolStatus {
+    /// Returns the status as its wire string.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Paused => "paused",
+            Self::Draining => "draining",
+            Self::Archived => "archived",
+        }
+    }
+
+    /// Returns `true` if a pool in this status accepts `op`.
+    #[must_use]
+    pub const fn admits(self, op: &PoolOperation) -> bool {
+        match self {
+            Self::Active => true,
+            Self::Draining => matches!(
+                op,
+                PoolOperation::RemoveLiquidity { .. }
+                    | PoolOperation::CollectFees { .. }
+                    | PoolOperation::CancelOrder { .. }
+            ),
+            Self::Paused | Self::Archived => false,
+        }
+    }
+}
+
+impl std::fmt::Display for PoolStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 /// Aggregate wrapping a hydra-amm [`PoolBox`] with gateway metadata.
 ///
@@ -36,6 +113,108 @@ pub struct PoolEntry {
 
     /// Fee tier in basis points (immutable after creation).
     pub fee_bps: u32,
+
+    /// Type-specific creation config as submitted (`Null` when the pool
+    /// was created programmatically from an `AmmConfig`).
+    pub config_json: serde_json::Value,
+
+    /// Token metadata from the creation config, in pool order.
+    pub tokens: Vec<TokenInfo>,
+
+    /// Gateway-tracked reserves in token order, for pool types whose
+    /// config declares them.
+    pub reserves: Option<Vec<u128>>,
+
+    /// Every mutation applied since creation, in order.
+    pub journal: Journal,
+
+    /// Versions used up before the journal started: non-zero once the
+    /// pool has been restored from a snapshot, so versions keep growing
+    /// across restores.
+    pub version_base: u64,
+
+    /// LP units minted through liquidity additions and not yet burned.
+    /// Non-zero means providers still hold open positions.
+    pub provided_liquidity: u128,
+
+    /// Side whose price is reported by [`Self::spot_price`], from the
+    /// creation config's `price_convention` (token A by default).
+    pub price_base: TokenSide,
+
+    /// Liquidity positions opened through the gateway, by id. Positions
+    /// stay listed after being fully withdrawn so fees can still be
+    /// collected.
+    pub positions: PositionRegistry,
+
+    /// LP units held by each position owner.
+    pub lp_ledger: LpLedger,
+
+    /// Resting limit orders, for order-book pools.
+    pub orders: OrderBook,
+
+    /// Latest oracle price pushed to a dynamic pool; `None` until the
+    /// first update, when the config's `oracle_price` applies.
+    pub oracle_price: Option<f64>,
+
+    /// When the oracle price was last set; `None` until the first update.
+    pub oracle_updated_at: Option<DateTime<Utc>>,
+
+    /// Swap fees charged since creation, per token in pool order. Each
+    /// swap's fee is charged in its input token.
+    pub fees_accrued: [u128; 2],
+
+    /// Lifecycle state; only active pools accept every operation.
+    pub status: PoolStatus,
+
+    /// When the pool was archived, if it was.
+    pub archived_at: Option<DateTime<Utc>>,
+
+    /// Share of routed flow, in basis points, the smart router may send
+    /// to this pool while it is a canary. `None` once promoted.
+    pub canary_weight_bps: Option<u32>,
+
+    /// Client that owns the pool: its creator, or whoever ownership was
+    /// last transferred to. `None` for pools created without a client id.
+    pub owner: Option<String>,
+
+    /// Free-form metadata attached by the pool's operators (`null` if
+    /// none). Validated against the registered metadata schemas.
+    pub metadata: serde_json::Value,
+
+    /// Set when the pool is removed. The entry is then a tombstone that
+    /// refuses new operations until its last handle is dropped.
+    pub removed: bool,
+}
+
+/// Result of applying a [`PoolOperation`] to an entry.
+#[derive(Debug)]
+pub enum OperationOutcome {
+    /// A swap executed.
+    Swap(SwapResult),
+    /// Liquidity was deposited; holds the LP units minted.
+    LiquidityAdded(Amount),
+    /// Liquidity was withdrawn.
+    LiquidityRemoved {
+        /// Amount hydra-amm reports as returned.
+        returned: Amount,
+        /// Tokens withdrawn in pool order, for pools with tracked
+        /// reserves.
+        amounts: Option<[u128; 2]>,
+    },
+    /// A position's fees were collected.
+    FeesCollected {
+        /// Amount hydra-amm reports as collected.
+        collected: Amount,
+        /// The position's share of the swap fees charged since its last
+        /// collection, per token in pool order.
+        amounts: [u128; 2],
+    },
+    /// A limit order was rested.
+    OrderPlaced(LimitOrder),
+    /// A resting limit order was cancelled; holds the order removed.
+    OrderCancelled(LimitOrder),
+    /// A dynamic pool was repriced; holds the previous oracle price.
+    OracleUpdated(f64),
 }
 
 impl PoolEntry {
@@ -52,10 +231,424 @@ impl PoolEntry {
             swap_count: 0,
             total_volume: 0,
             fee_bps,
+            config_json: serde_json::Value::Null,
+            tokens: Vec::new(),
+            reserves: None,
+            journal: Journal::new(),
+            version_base: 0,
+            provided_liquidity: 0,
+            price_base: TokenSide::First,
+            positions: PositionRegistry::new(),
+            lp_ledger: LpLedger::new(),
+            orders: OrderBook::new(),
+            oracle_price: None,
+            oracle_updated_at: None,
+            fees_accrued: [0; 2],
+            status: PoolStatus::Active,
+            archived_at: None,
+            canary_weight_bps: None,
+            owner: None,
+            metadata: serde_json::Value::Null,
+            removed: false,
+        }
+    }
+
+    /// Attaches the creation config, token metadata, and initial reserves.
+    #[must_use]
+    pub fn with_definition(
+        mut self,
+        config_json: serde_json::Value,
+        tokens: Vec<TokenInfo>,
+        reserves: Option<Vec<u128>>,
+    ) -> Self {
+        self.config_json = config_json;
+        self.tokens = tokens;
+        self.reserves = reserves;
+        self
+    }
+
+    /// Checks that the pool is active and so accepts swaps.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PoolArchived`] for an archived pool, or
+    /// [`GatewayError::PoolPaused`] for a paused or draining one.
+    pub fn ensure_tradable(&self) -> Result<(), GatewayError> {
+        match self.status {
+            PoolStatus::Active => Ok(()),
+            _ => Err(self.status_error()),
+        }
+    }
+
+    /// Error rejecting an operation the pool's status does not admit.
+    fn status_error(&self) -> GatewayError {
+        let pool_id = *self.pool_id.as_uuid();
+        match self.status {
+            PoolStatus::Archived => GatewayError::PoolArchived(pool_id),
+            status => GatewayError::PoolPaused {
+                pool_id,
+                status: status.as_str(),
+            },
+        }
+    }
+
+    /// Returns the pool's state version: the number of mutations applied
+    /// since creation, plus one per restore. It changes exactly when pool
+    /// state does and never goes back, so quotes can name the state they
+    /// were computed against and logged events the state they produced.
+    #[must_use]
+    pub fn version(&self) -> u64 {
+        u64::try_from(self.journal.len())
+            .unwrap_or(u64::MAX)
+            .saturating_add(self.version_base)
+    }
+
+    /// Returns the spot price of the base token in the quote token per
+    /// the pool's price convention, or `None` when the pool cannot
+    /// currently price (e.g. an empty order book).
+    #[must_use]
+    pub fn spot_price(&self) -> Option<f64> {
+        self.spot_price_of(self.price_base)
+    }
+
+    /// Returns the spot price of the `side` token in units of the other
+    /// token.
+    #[must_use]
+    pub fn spot_price_of(&self, side: TokenSide) -> Option<f64> {
+        let pair = self.pool_box.token_pair();
+        self.pool_box
+            .spot_price(&side.token(pair), &side.other().token(pair))
+            .ok()
+            .map(|p| p.get())
+    }
+
+    /// Returns the address label for one side of the pair.
+    ///
+    /// Uses the creation-time address string when known, falling back to
+    /// the debug form of the hydra-amm address.
+    #[must_use]
+    pub fn token_label(&self, side: TokenSide) -> String {
+        self.tokens.get(side.index()).map_or_else(
+            || format!("{:?}", side.token(self.pool_box.token_pair()).address()),
+            |t| t.address.clone(),
+        )
+    }
+
+    /// Returns the decimals of one side of the pair, if the creation
+    /// config declared them.
+    #[must_use]
+    pub fn decimals_of(&self, side: TokenSide) -> Option<u8> {
+        self.tokens.get(side.index()).map(|t| t.decimals)
+    }
+
+    /// Resolves an address label (as returned by [`Self::token_label`])
+    /// to a side of the pair.
+    #[must_use]
+    pub fn side_of_label(&self, label: &str) -> Option<TokenSide> {
+        [TokenSide::First, TokenSide::Second]
+            .into_iter()
+            .find(|side| self.token_label(*side) == label)
+    }
+
+    /// Liquidity not held by any tracked position: the creation
+    /// liquidity plus deposits made before positions were tracked.
+    #[must_use]
+    pub fn unowned_liquidity(&self) -> u128 {
+        let owned = self.positions.total_liquidity();
+        self.pool_box.total_liquidity().get().saturating_sub(owned)
+    }
+
+    /// Applies `op` to the pool, updating metadata, tracked reserves, and
+    /// the journal.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PoolArchived`] if the pool is archived,
+    /// [`GatewayError::PoolPaused`] if it is paused or draining and its
+    /// status does not admit `op` (see [`PoolStatus::admits`]), or a
+    /// [`GatewayError`] if hydra-amm rejects the operation; the entry is
+    /// left unchanged in every case.
+    pub fn apply(&mut self, op: &PoolOperation) -> Result<OperationOutcome, GatewayError> {
+        if !self.status.admits(op) {
+            return Err(self.status_error());
+        }
+        let outcome = match op {
+            PoolOperation::Swap {
+                token_in,
+                kind,
+                amount,
+            } => {
+                let spec = kind.spec(Amount::new(parse_u128(amount)?))?;
+                let token = token_in.token(self.pool_box.token_pair());
+                let result = self.pool_box.swap(spec, token)?;
+                self.swap_count = self.swap_count.saturating_add(1);
+                self.total_volume = self.total_volume.saturating_add(result.amount_in().get());
+                adjust(&mut self.fees_accrued, token_in.index(), |f| {
+                    f.saturating_add(result.fee().get())
+                });
+                if let Some(reserves) = self.reserves.as_mut() {
+                    adjust(reserves, token_in.index(), |r| {
+                        r.saturating_add(result.amount_in().get())
+                    });
+                    adjust(reserves, token_in.other().index(), |r| {
+                        r.saturating_sub(result.amount_out().get())
+                    });
+                }
+                OperationOutcome::Swap(result)
+            }
+            PoolOperation::AddLiquidity {
+                amount_a,
+                amount_b,
+                position_id,
+                range,
+                owner,
+            } => {
+                let (a, b) = (parse_u128(amount_a)?, parse_u128(amount_b)?);
+                let change = op.liquidity_change()?.ok_or_else(|| {
+                    GatewayError::Internal("add operation without change".to_string())
+                })?;
+                let minted = self.pool_box.add_liquidity(&change)?;
+                if let Some(reserves) = self.reserves.as_mut() {
+                    adjust(reserves, 0, |r| r.saturating_add(a));
+                    adjust(reserves, 1, |r| r.saturating_add(b));
+                }
+                self.provided_liquidity = self.provided_liquidity.saturating_add(minted.get());
+                if let Some(id) = position_id {
+                    let position =
+                        self.positions
+                            .open(*id, owner.clone(), *range, self.fees_accrued);
+                    position.liquidity = position.liquidity.saturating_add(minted.get());
+                    if let Some(holder) = &position.owner {
+                        self.lp_ledger.credit(holder, minted.get());
+                    }
+                }
+                OperationOutcome::LiquidityAdded(minted)
+            }
+            PoolOperation::RemoveLiquidity {
+                liquidity,
+                position_id,
+            } => {
+                let burned = parse_u128(liquidity)?;
+                let available = match position_id {
+                    Some(id) => {
+                        self.positions
+                            .get(id)
+                            .ok_or(GatewayError::PositionNotFound(*self.pool_id.as_uuid()))?
+                            .liquidity
+                    }
+                    None => self.unowned_liquidity(),
+                };
+                if burned > available {
+                    return Err(GatewayError::InsufficientLiquidity);
+                }
+                let change = op.liquidity_change()?.ok_or_else(|| {
+                    GatewayError::Internal("remove operation without change".to_string())
+                })?;
+                let total_before = self.pool_box.total_liquidity().get();
+                let returned = self.pool_box.remove_liquidity(&change)?;
+                // Withdrawals are proportional to the share burned.
+                let amounts = self.reserves.as_mut().map(|reserves| {
+                    let mut withdrawn = [0; 2];
+                    for (i, r) in reserves.iter_mut().enumerate() {
+                        let out = pro_rata(*r, burned, total_before);
+                        *r = r.saturating_sub(out);
+                        if let Some(w) = withdrawn.get_mut(i) {
+                            *w = out;
+                        }
+                    }
+                    withdrawn
+                });
+                self.provided_liquidity = self.provided_liquidity.saturating_sub(burned);
+                if let Some(position) = position_id.and_then(|id| self.positions.get_mut(&id)) {
+                    position.liquidity = position.liquidity.saturating_sub(burned);
+                    if let Some(holder) = &position.owner {
+                        self.lp_ledger.debit(holder, burned);
+                    }
+                }
+                OperationOutcome::LiquidityRemoved { returned, amounts }
+            }
+            PoolOperation::CollectFees { position_id } => {
+                let held = self
+                    .positions
+                    .get(position_id)
+                    .cloned()
+                    .ok_or(GatewayError::PositionNotFound(*self.pool_id.as_uuid()))?;
+                let range = held.range.unwrap_or(TickRange::FULL);
+                let position = Position::new(
+                    Tick::new(range.lower)?,
+                    Tick::new(range.upper)?,
+                    Liquidity::new(held.liquidity),
+                )?;
+                let total = self.pool_box.total_liquidity().get();
+                let collected = self.pool_box.collect_fees(&position)?;
+                // The position earns its current share of the fees charged
+                // since it last collected.
+                let mut amounts = [0; 2];
+                for ((owed, accrued), checkpoint) in amounts
+                    .iter_mut()
+                    .zip(self.fees_accrued)
+                    .zip(held.fee_checkpoint)
+                {
+                    *owed = pro_rata(accrued.saturating_sub(checkpoint), held.liquidity, total);
+                }
+                if let Some(position) = self.positions.get_mut(position_id) {
+                    position.fee_checkpoint = self.fees_accrued;
+                }
+                OperationOutcome::FeesCollected { collected, amounts }
+            }
+            PoolOperation::PlaceOrder {
+                order_id,
+                side,
+                price,
+                quantity,
+                owner,
+            } => {
+                let order = LimitOrder {
+                    side: *side,
+                    price: parse_u128(price)?,
+                    quantity: parse_u128(quantity)?,
+                    owner: owner.clone(),
+                };
+                self.check_order(&order)?;
+                self.orders.place(*order_id, order.clone())?;
+                OperationOutcome::OrderPlaced(order)
+            }
+            PoolOperation::CancelOrder { order_id } => {
+                let order = self
+                    .orders
+                    .cancel(order_id)
+                    .ok_or(GatewayError::OrderNotFound(*self.pool_id.as_uuid()))?;
+                OperationOutcome::OrderCancelled(order)
+            }
+            PoolOperation::SetOraclePrice { price } => {
+                OperationOutcome::OracleUpdated(self.reprice(price)?)
+            }
+        };
+        self.journal.push(op.clone());
+        self.last_modified_at = Utc::now();
+        Ok(outcome)
+    }
+
+    /// Returns the order book's `tick_size` and `lot_size` from the
+    /// creation config, for order-book pools that recorded them.
+    #[must_use]
+    pub fn order_increments(&self) -> Option<(u128, u128)> {
+        let field = |name: &str| {
+            let value = self.config_json.get(name)?;
+            value
+                .as_str()
+                .and_then(|s| s.parse().ok())
+                .or_else(|| value.as_u64().map(u128::from))
+        };
+        Some((field("tick_size")?, field("lot_size")?))
+    }
+
+    /// Returns the oracle price a dynamic pool currently quotes around.
+    #[must_use]
+    pub fn current_oracle_price(&self) -> Option<f64> {
+        self.oracle_price.or_else(|| {
+            let value = self.config_json.get("oracle_price")?;
+            value
+                .as_f64()
+                .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+        })
+    }
+
+    /// Moves a dynamic pool to the oracle price `price` and returns the
+    /// previous one.
+    ///
+    /// hydra-amm cannot change the price of a live pool, so the pool is
+    /// rebuilt from its creation config at the new price and its current
+    /// reserves. Updates outside the config's [`OracleBounds`] are
+    /// rejected and leave the pool untouched.
+    fn reprice(&mut self, price: &str) -> Result<f64, GatewayError> {
+        if self.pool_type != "dynamic" {
+            return Err(GatewayError::InvalidRequest(format!(
+                "{} pools have no oracle price",
+                self.pool_type
+            )));
+        }
+        let proposed = price
+            .parse::<f64>()
+            .ok()
+            .filter(|p| p.is_finite() && *p > 0.0)
+            .ok_or_else(|| {
+                GatewayError::InvalidRequest(format!("oracle price must be positive: {price}"))
+            })?;
+        let (Some(previous), Some(&[reserve_a, reserve_b])) =
+            (self.current_oracle_price(), self.reserves.as_deref())
+        else {
+            return Err(GatewayError::InvalidRequest(format!(
+                "pool {} has no creation config to reprice",
+                self.pool_id
+            )));
+        };
+        OracleBounds::from_config(&self.config_json)?.check(previous, proposed)?;
+
+        let mut config = self.config_json.clone();
+        if let Some(object) = config.as_object_mut() {
+            object.insert("oracle_price".to_string(), proposed.into());
+            object.insert("reserve_a".to_string(), reserve_a.to_string().into());
+            object.insert("reserve_b".to_string(), reserve_b.to_string().into());
+        }
+        let (amm_config, _) = config_parser::parse_pool_config(&self.pool_type, &config)?;
+        self.pool_box = DefaultPoolFactory::create(&amm_config)?;
+        self.oracle_price = Some(proposed);
+        self.oracle_updated_at = Some(Utc::now());
+        Ok(previous)
+    }
+
+    /// Checks that `order` can rest on this pool: an order-book pool,
+    /// a non-zero price and quantity, and multiples of the tick and lot
+    /// sizes.
+    fn check_order(&self, order: &LimitOrder) -> Result<(), GatewayError> {
+        if self.pool_type != "orderbook" {
+            return Err(GatewayError::InvalidRequest(format!(
+                "{} pools have no order book",
+                self.pool_type
+            )));
+        }
+        if order.price == 0 || order.quantity == 0 {
+            return Err(GatewayError::InvalidRequest(
+                "price and quantity must be positive".to_string(),
+            ));
+        }
+        if let Some((tick_size, lot_size)) = self.order_increments() {
+            if tick_size > 0 && !order.price.is_multiple_of(tick_size) {
+                return Err(GatewayError::InvalidRequest(format!(
+                    "price must be a multiple of tick_size {tick_size}"
+                )));
+            }
+            if lot_size > 0 && !order.quantity.is_multiple_of(lot_size) {
+                return Err(GatewayError::InvalidRequest(format!(
+                    "quantity must be a multiple of lot_size {lot_size}"
+                )));
+            }
         }
+        Ok(())
     }
 }
 
+/// Applies `f` to `reserves[index]` if present.
+fn adjust(reserves: &mut [u128], index: usize, f: impl FnOnce(u128) -> u128) {
+    if let Some(r) = reserves.get_mut(index) {
+        *r = f(*r);
+    }
+}
+
+/// Computes `value * part / whole` without overflowing u128.
+#[must_use]
+pub fn pro_rata(value: u128, part: u128, whole: u128) -> u128 {
+    if whole == 0 {
+        return 0;
+    }
+    let part = part.min(whole);
+    // Split into quotient and remainder so the product never overflows.
+    let whole_units = (value / whole).saturating_mul(part);
+    let remainder = (value % whole).checked_mul(part).map_or(0, |r| r / whole);
+    whole_units.saturating_add(remainder)
+}
+
 /// Lightweight summary of a pool for list endpoints.
 #[derive(Debug, Clone, Serialize)]
 pub struct PoolSummary {
@@ -65,10 +658,45 @@ pub struct PoolSummary {
     pub pool_type: String,
     /// Creation timestamp.
     pub created_at: DateTime<Utc>,
+    /// Timestamp of the last state mutation.
+    pub last_modified_at: DateTime<Utc>,
     /// Fee tier in basis points.
     pub fee_bps: u32,
     /// Number of swaps executed.
     pub swap_count: u64,
+    /// Cumulative swap volume in base token smallest units.
+    pub total_volume: u128,
+    /// Token metadata from the creation config.
+    pub tokens: Vec<TokenInfo>,
+    /// Total liquidity reported by hydra-amm.
+    pub total_liquidity: u128,
+    /// Spot price of the base token in the quote token per the pool's
+    /// price convention, if the pool can price.
+    pub spot_price: Option<f64>,
+    /// Side of the pair that `spot_price` prices.
+    pub price_base: TokenSide,
+    /// Tracked reserves in token order (`None` for pool types whose
+    /// reserves the gateway does not track).
+    pub reserves: Option<Vec<u128>>,
+    /// Lifecycle state.
+    pub status: PoolStatus,
+    /// When the pool was archived, if it was.
+    pub archived_at: Option<DateTime<Utc>>,
+    /// Canary routing weight in basis points (`None` if promoted).
+    pub canary_weight_bps: Option<u32>,
+    /// Pool metadata (`null` if none).
+    pub metadata: serde_json::Value,
+}
+
+impl PoolSummary {
+    /// Returns `true` if the pool holds a token with `address`
+    /// (compared case-insensitively).
+    #[must_use]
+    pub fn has_token(&self, address: &str) -> bool {
+        self.tokens
+            .iter()
+            .any(|t| t.address.eq_ignore_ascii_case(address))
+    }
 }
 
 impl From<&PoolEntry> for PoolSummary {
@@ -77,8 +705,267 @@ impl From<&PoolEntry> for PoolSummary {
             pool_id: entry.pool_id,
             pool_type: entry.pool_type.clone(),
             created_at: entry.created_at,
+            last_modified_at: entry.last_modified_at,
             fee_bps: entry.fee_bps,
             swap_count: entry.swap_count,
+            total_volume: entry.total_volume,
+            tokens: entry.tokens.clone(),
+            total_liquidity: entry.pool_box.total_liquidity().get(),
+            spot_price: entry.spot_price(),
+            price_base: entry.price_base,
+            reserves: entry.reserves.clone(),
+            status: entry.status,
+            archived_at: entry.archived_at,
+            canary_weight_bps: entry.canary_weight_bps,
+            metadata: entry.metadata.clone(),
+        }
+    }
+}
+
+/// A distinct token across all registered pools.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KnownToken {
+    /// Token address as first seen.
+    pub address: String,
+    /// Number of decimal places.
+    pub decimals: u8,
+    /// First non-empty symbol registered for the token (may be empty).
+    pub symbol: String,
+    /// Number of pools holding the token.
+    pub pool_count: u32,
+    /// Sum of the token's tracked reserves across its pools.
+    pub total_liquidity: u128,
+}
+
+/// Collapses the tokens of `pools` into one [`KnownToken`] per address
+/// (compared case-insensitively), ordered by address.
+#[must_use]
+pub fn known_tokens(pools: &[PoolSummary]) -> Vec<KnownToken> {
+    let mut by_address: BTreeMap<String, KnownToken> = BTreeMap::new();
+    for pool in pools {
+        for (index, token) in pool.tokens.iter().enumerate() {
+            let reserve = pool
+                .reserves
+                .as_ref()
+                .and_then(|r| r.get(index))
+                .copied()
+                .unwrap_or(0);
+            let known = by_address
+                .entry(token.address.to_ascii_lowercase())
+                .or_insert_with(|| KnownToken {
+                    address: token.address.clone(),
+                    decimals: token.decimals,
+                    symbol: String::new(),
+                    pool_count: 0,
+                    total_liquidity: 0,
+                });
+            if known.symbol.is_empty() {
+                known.symbol.clone_from(&token.symbol);
+            }
+            known.pool_count = known.pool_count.saturating_add(1);
+            known.total_liquidity = known.total_liquidity.saturating_add(reserve);
+        }
+    }
+    by_address.into_values().collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::PositionId;
+    use crate::domain::pool_operation::SwapKind;
+    use hydra_amm::config::{AmmConfig, ConstantProductConfig};
+    use hydra_amm::domain::{BasisPoints, Decimals, FeeTier, Token, TokenAddress, TokenPair};
+    use hydra_amm::factory::DefaultPoolFactory;
+
+    fn make_entry() -> PoolEntry {
+        let Ok(d6) = Decimals::new(6) else {
+            panic!("valid decimals");
+        };
+        let tok_a = Token::new(TokenAddress::from_bytes([1u8; 32]), d6);
+        let tok_b = Token::new(TokenAddress::from_bytes([2u8; 32]), d6);
+        let Ok(pair) = TokenPair::new(tok_a, tok_b) else {
+            panic!("valid pair");
+        };
+        let fee = FeeTier::new(BasisPoints::new(30));
+        let Ok(cfg) =
+            ConstantProductConfig::new(pair, fee, Amount::new(1_000_000), Amount::new(1_000_000))
+        else {
+            panic!("valid config");
+        };
+        let Ok(pool_box) = DefaultPoolFactory::create(&AmmConfig::ConstantProduct(cfg)) else {
+            panic!("pool creation failed");
+        };
+        PoolEntry::new(PoolId::new(), pool_box, "constant_product".to_string(), 30).with_definition(
+            serde_json::Value::Null,
+            Vec::new(),
+            Some(vec![1_000_000, 1_000_000]),
+        )
+    }
+
+    #[test]
+    fn apply_swap_tracks_reserves_and_journal() {
+        let mut entry = make_entry();
+        let op = PoolOperation::Swap {
+            token_in: TokenSide::First,
+            kind: SwapKind::ExactIn,
+            amount: "1000".to_string(),
+        };
+        let Ok(OperationOutcome::Swap(result)) = entry.apply(&op) else {
+            panic!("swap failed");
+        };
+        assert_eq!(entry.swap_count, 1);
+        assert_eq!(entry.journal.to_vec(), vec![op]);
+        let Some(reserves) = entry.reserves.as_deref() else {
+            panic!("reserves tracked");
+        };
+        assert_eq!(reserves, [1_001_000, 1_000_000 - result.amount_out().get()]);
+    }
+
+    #[test]
+    fn failed_operation_is_not_journaled() {
+        let mut entry = make_entry();
+        let op = PoolOperation::RemoveLiquidity {
+            liquidity: "0".to_string(),
+            position_id: None,
+        };
+        assert!(entry.apply(&op).is_err());
+        assert!(entry.journal.is_empty());
+    }
+
+    #[test]
+    fn positions_limit_what_can_be_withdrawn() {
+        let mut entry = make_entry();
+        let id = PositionId::new();
+        let add = PoolOperation::AddLiquidity {
+            amount_a: "1000".to_string(),
+            amount_b: "1000".to_string(),
+            position_id: Some(id),
+            range: None,
+            owner: None,
+        };
+        let Ok(OperationOutcome::LiquidityAdded(minted)) = entry.apply(&add) else {
+            panic!("add failed");
+        };
+        let Some(position) = entry.positions.get(&id) else {
+            panic!("position tracked");
+        };
+        assert_eq!(position.liquidity, minted.get());
+
+        let remove = |liquidity: u128, position_id| PoolOperation::RemoveLiquidity {
+            liquidity: liquidity.to_string(),
+            position_id,
+        };
+        assert!(matches!(
+            entry.apply(&remove(minted.get() + 1, Some(id))),
+            Err(GatewayError::InsufficientLiquidity)
+        ));
+        assert!(matches!(
+            entry.apply(&remove(1, Some(PositionId::new()))),
+            Err(GatewayError::PositionNotFound(_))
+        ));
+        let Ok(_) = entry.apply(&remove(minted.get(), Some(id))) else {
+            panic!("remove failed");
+        };
+        assert_eq!(entry.positions.get(&id).map(|p| p.liquidity), Some(0));
+    }
+
+    #[test]
+    fn fees_and_withdrawals_are_reported_per_token() {
+        let mut entry = make_entry();
+        let id = PositionId::new();
+        let Ok(OperationOutcome::LiquidityAdded(minted)) =
+            entry.apply(&PoolOperation::AddLiquidity {
+                amount_a: "1000000".to_string(),
+                amount_b: "1000000".to_string(),
+                position_id: Some(id),
+                range: None,
+                owner: None,
+            })
+        else {
+            panic!("add failed");
+        };
+        for (side, amount) in [(TokenSide::First, "10000"), (TokenSide::Second, "20000")] {
+            let Ok(_) = entry.apply(&PoolOperation::Swap {
+                token_in: side,
+                kind: SwapKind::ExactIn,
+                amount: amount.to_string(),
+            }) else {
+                panic!("swap failed");
+            };
         }
+        assert_eq!(entry.fees_accrued, [30, 60]);
+
+        let total = entry.pool_box.total_liquidity().get();
+        let collect = PoolOperation::CollectFees { position_id: id };
+        let Ok(OperationOutcome::FeesCollected { amounts, .. }) = entry.apply(&collect) else {
+            panic!("collect failed");
+        };
+        assert_eq!(
+            amounts,
+            [
+                pro_rata(30, minted.get(), total),
+                pro_rata(60, minted.get(), total)
+            ]
+        );
+        let Ok(OperationOutcome::FeesCollected { amounts, .. }) = entry.apply(&collect) else {
+            panic!("collect failed");
+        };
+        assert_eq!(amounts, [0, 0], "fees are only reported once");
+
+        let before = entry.reserves.clone().unwrap_or_default();
+        let Ok(OperationOutcome::LiquidityRemoved {
+            amounts: Some([a, b]),
+            ..
+        }) = entry.apply(&PoolOperation::RemoveLiquidity {
+            liquidity: minted.get().to_string(),
+            position_id: Some(id),
+        })
+        else {
+            panic!("remove failed");
+        };
+        let after = entry.reserves.clone().unwrap_or_default();
+        let deltas: Vec<u128> = before.iter().zip(&after).map(|(x, y)| x - y).collect();
+        assert_eq!(deltas, [a, b]);
+        assert!(a > 0 && b > 0);
+    }
+
+    #[test]
+    fn known_tokens_merges_addresses_across_pools() {
+        let summary = |tokens: [(&str, &str); 2], reserves: Option<Vec<u128>>| PoolSummary {
+            tokens: tokens
+                .iter()
+                .map(|(address, symbol)| TokenInfo {
+                    address: (*address).to_string(),
+                    decimals: 6,
+                    symbol: (*symbol).to_string(),
+                })
+                .collect(),
+            reserves,
+            ..PoolSummary::from(&make_entry())
+        };
+        let pools = vec![
+            summary([("0xAAA", ""), ("0xbbb", "B")], Some(vec![10, 20])),
+            summary([("0xaaa", "A"), ("0xccc", "C")], Some(vec![5, 7])),
+            summary([("0xaaa", "A2"), ("0xbbb", "B")], None),
+        ];
+
+        let tokens = known_tokens(&pools);
+        assert_eq!(tokens.len(), 3);
+        let Some(a) = tokens.first() else {
+            panic!("missing token");
+        };
+        assert_eq!(a.address, "0xAAA");
+        assert_eq!(a.symbol, "A");
+        assert_eq!(a.pool_count, 3);
+        assert_eq!(a.total_liquidity, 15);
+    }
+
+    #[test]
+    fn pro_rata_handles_large_values() {
+        assert_eq!(pro_rata(100, 1, 4), 25);
+        assert_eq!(pro_rata(u128::MAX, 2, 2), u128::MAX);
+        assert_eq!(pro_rata(10, 1, 0), 0);
     }
 }
@@ -5,12 +5,22 @@
 //! to the PostgreSQL event log.
 
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-use super::PoolId;
+use super::order_book::OrderSide;
+use super::pool_entry::PoolStatus;
+use super::pool_operation::{SwapKind, TickRange};
+use super::{OrderId, PoolId, PositionId};
+
+/// Current schema version of serialized [`PoolEvent`] payloads.
+///
+/// Bump this whenever a variant's fields change and register an upcaster
+/// in [`crate::persistence::upcast`] for the previous version.
+pub const EVENT_SCHEMA_VERSION: u32 = 7;
 
 /// Reason why a price update occurred.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum PriceChangeReason {
     /// Price changed due to a swap execution.
@@ -19,10 +29,12 @@ pub enum PriceChangeReason {
     LiquidityAdded,
     /// Price changed due to liquidity being removed.
     LiquidityRemoved,
+    /// Price changed due to an oracle update on a dynamic pool.
+    OracleUpdated,
 }
 
 /// Type of liquidity change.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum LiquidityChangeType {
     /// Liquidity was added to the pool.
@@ -35,7 +47,7 @@ pub enum LiquidityChangeType {
 ///
 /// All `Decimal`-like amounts are stored as `String` to preserve u128
 /// precision when serialized to JSON.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(tag = "event_type", rename_all = "snake_case")]
 pub enum PoolEvent {
     /// Emitted when a new pool is created.
@@ -50,6 +62,16 @@ pub enum PoolEvent {
         token_b: String,
         /// Fee tier in basis points.
         fee_tier: u32,
+        /// Type-specific creation config, enough to rebuild the pool on
+        /// replay (`null` for pools created from a raw `AmmConfig`).
+        #[schema(value_type = Object)]
+        config: serde_json::Value,
+        /// Free-form pool metadata supplied at creation (`null` if none).
+        #[schema(value_type = Object)]
+        metadata: serde_json::Value,
+        /// Client that created the pool and owns it, from the
+        /// `X-Client-Id` header (`None` for unowned pools).
+        owner: Option<String>,
         /// Creation timestamp.
         timestamp: DateTime<Utc>,
     },
@@ -62,6 +84,75 @@ pub enum PoolEvent {
         timestamp: DateTime<Utc>,
     },
 
+    /// Emitted when a pool's metadata is replaced.
+    PoolMetadataUpdated {
+        /// Pool identifier.
+        pool_id: PoolId,
+        /// New metadata (`null` clears it).
+        #[schema(value_type = Object)]
+        metadata: serde_json::Value,
+        /// Client that issued the command, from the `X-Client-Id` header.
+        actor: Option<String>,
+        /// Update timestamp.
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Emitted when a pool's ownership passes to another client.
+    PoolOwnershipTransferred {
+        /// Pool identifier.
+        pool_id: PoolId,
+        /// Owner before the transfer (`None` if the pool was unowned).
+        previous_owner: Option<String>,
+        /// New owner.
+        new_owner: String,
+        /// Client that issued the command, from the `X-Client-Id` header.
+        actor: Option<String>,
+        /// Transfer timestamp.
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Emitted when an idle pool is archived. Archived pools stay
+    /// readable but accept no further operations.
+    PoolArchived {
+        /// Pool identifier.
+        pool_id: PoolId,
+        /// Last state change before archiving (creation if none).
+        idle_since: DateTime<Utc>,
+        /// Archive timestamp.
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Emitted when an operator pauses, drains, or resumes a pool.
+    PoolStatusChanged {
+        /// Pool identifier.
+        pool_id: PoolId,
+        /// Status before the change.
+        previous_status: PoolStatus,
+        /// Status after the change.
+        status: PoolStatus,
+        /// Client that issued the command, from the `X-Client-Id` header.
+        actor: Option<String>,
+        /// Change timestamp.
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Emitted when an admin replaces a pool's state with a stored
+    /// snapshot. The replaced state is archived as a snapshot first.
+    PoolRestored {
+        /// Pool identifier.
+        pool_id: PoolId,
+        /// Snapshot the pool was restored from.
+        snapshot_id: i64,
+        /// When that snapshot was taken.
+        snapshot_at: DateTime<Utc>,
+        /// Snapshot holding the state that was replaced.
+        archived_snapshot_id: i64,
+        /// Client that issued the command, from the `X-Client-Id` header.
+        actor: Option<String>,
+        /// Restore timestamp.
+        timestamp: DateTime<Utc>,
+    },
+
     /// Emitted after a successful swap.
     SwapExecuted {
         /// Pool identifier.
@@ -74,10 +165,21 @@ pub enum PoolEvent {
         amount_out: String,
         /// Fee charged (string-encoded u128).
         fee: String,
+        /// Part of `fee` paid back to the actor under the fee-tier
+        /// program (string-encoded u128, in the input token). Defaults to
+        /// `"0"` so imported history from other venues may omit it.
+        #[serde(default = "no_rebate")]
+        fee_rebate: String,
         /// New spot price after swap.
         new_price: String,
         /// Price change in basis points.
         price_change_bps: i32,
+        /// Input token address.
+        token_in: String,
+        /// Whether `amount_in` or `amount_out` was the fixed amount.
+        swap_kind: SwapKind,
+        /// Client that issued the command, from the `X-Client-Id` header.
+        actor: Option<String>,
         /// Execution timestamp.
         timestamp: DateTime<Utc>,
     },
@@ -88,12 +190,23 @@ pub enum PoolEvent {
         pool_id: PoolId,
         /// Whether liquidity was added or removed.
         change_type: LiquidityChangeType,
-        /// Amount of token A involved.
+        /// Amount of token A deposited or withdrawn. For withdrawals from
+        /// pool types without tracked reserves this is the amount
+        /// hydra-amm reports and `amount_b` is `"0"`.
         amount_a: String,
-        /// Amount of token B involved.
+        /// Amount of token B deposited or withdrawn.
         amount_b: String,
         /// New total liquidity after the change.
         new_total_liquidity: String,
+        /// LP units minted (add) or burned (remove).
+        liquidity_delta: String,
+        /// Position credited or debited, if the change was made through
+        /// one.
+        position_id: Option<PositionId>,
+        /// Tick range of the position (set when an add opened it).
+        range: Option<TickRange>,
+        /// Client that issued the command, from the `X-Client-Id` header.
+        actor: Option<String>,
         /// Timestamp of the change.
         timestamp: DateTime<Utc>,
     },
@@ -106,10 +219,81 @@ pub enum PoolEvent {
         fee_token_a: String,
         /// Fees collected in token B.
         fee_token_b: String,
+        /// Position the fees were collected for.
+        position_id: Option<PositionId>,
+        /// Client that issued the command, from the `X-Client-Id` header.
+        actor: Option<String>,
         /// Collection timestamp.
         timestamp: DateTime<Utc>,
     },
 
+    /// Emitted when a client claims the liquidity-mining rewards its
+    /// positions earned in the pool.
+    RewardsClaimed {
+        /// Pool identifier.
+        pool_id: PoolId,
+        /// Address of the reward token.
+        reward_token: String,
+        /// Reward units paid out.
+        amount: String,
+        /// Positions the rewards were earned by.
+        position_ids: Vec<PositionId>,
+        /// Client that claimed, from the `X-Client-Id` header.
+        actor: Option<String>,
+        /// Claim timestamp.
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Emitted when a limit order rests on an order-book pool.
+    OrderPlaced {
+        /// Pool identifier.
+        pool_id: PoolId,
+        /// Id assigned to the order.
+        order_id: OrderId,
+        /// Side of the book.
+        side: OrderSide,
+        /// Limit price in token B units per token A unit.
+        price: String,
+        /// Token A units.
+        quantity: String,
+        /// Client that placed the order, from the `X-Client-Id` header.
+        actor: Option<String>,
+        /// Placement timestamp.
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Emitted when a resting limit order is cancelled.
+    OrderCancelled {
+        /// Pool identifier.
+        pool_id: PoolId,
+        /// Order cancelled.
+        order_id: OrderId,
+        /// Side of the book.
+        side: OrderSide,
+        /// Limit price of the order.
+        price: String,
+        /// Token A units removed from the book.
+        quantity: String,
+        /// Client that cancelled the order, from the `X-Client-Id` header.
+        actor: Option<String>,
+        /// Cancellation timestamp.
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Emitted when a dynamic pool accepts a new oracle price.
+    OraclePriceUpdated {
+        /// Pool identifier.
+        pool_id: PoolId,
+        /// Oracle price before the update.
+        old_price: String,
+        /// Oracle price after the update.
+        new_price: String,
+        /// Client that pushed the price, from the `X-Client-Id` header.
+        actor: Option<String>,
+        /// Update timestamp.
+        timestamp: DateTime<Utc>,
+    },
+
     /// Emitted after any operation that modifies the pool price.
     PriceUpdated {
         /// Pool identifier.
@@ -134,27 +318,94 @@ impl PoolEvent {
         match self {
             Self::PoolCreated { pool_id, .. }
             | Self::PoolRemoved { pool_id, .. }
+            | Self::PoolArchived { pool_id, .. }
+            | Self::PoolRestored { pool_id, .. }
+            | Self::PoolStatusChanged { pool_id, .. }
+            | Self::PoolMetadataUpdated { pool_id, .. }
+            | Self::PoolOwnershipTransferred { pool_id, .. }
             | Self::SwapExecuted { pool_id, .. }
             | Self::LiquidityChanged { pool_id, .. }
             | Self::FeesCollected { pool_id, .. }
+            | Self::RewardsClaimed { pool_id, .. }
+            | Self::OrderPlaced { pool_id, .. }
+            | Self::OrderCancelled { pool_id, .. }
+            | Self::OraclePriceUpdated { pool_id, .. }
             | Self::PriceUpdated { pool_id, .. } => *pool_id,
         }
     }
 
+    /// Returns the client whose command caused this event, if recorded.
+    #[must_use]
+    pub fn actor(&self) -> Option<&str> {
+        match self {
+            Self::SwapExecuted { actor, .. }
+            | Self::PoolMetadataUpdated { actor, .. }
+            | Self::PoolOwnershipTransferred { actor, .. }
+            | Self::PoolRestored { actor, .. }
+            | Self::PoolStatusChanged { actor, .. }
+            | Self::PoolCreated { owner: actor, .. }
+            | Self::LiquidityChanged { actor, .. }
+            | Self::FeesCollected { actor, .. }
+            | Self::RewardsClaimed { actor, .. }
+            | Self::OrderPlaced { actor, .. }
+            | Self::OrderCancelled { actor, .. }
+            | Self::OraclePriceUpdated { actor, .. } => actor.as_deref(),
+            Self::PoolRemoved { .. } | Self::PoolArchived { .. } | Self::PriceUpdated { .. } => {
+                None
+            }
+        }
+    }
+
+    /// Returns when the event happened.
+    #[must_use]
+    pub const fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            Self::PoolCreated { timestamp, .. }
+            | Self::PoolRemoved { timestamp, .. }
+            | Self::PoolArchived { timestamp, .. }
+            | Self::PoolRestored { timestamp, .. }
+            | Self::PoolStatusChanged { timestamp, .. }
+            | Self::PoolMetadataUpdated { timestamp, .. }
+            | Self::PoolOwnershipTransferred { timestamp, .. }
+            | Self::SwapExecuted { timestamp, .. }
+            | Self::LiquidityChanged { timestamp, .. }
+            | Self::FeesCollected { timestamp, .. }
+            | Self::RewardsClaimed { timestamp, .. }
+            | Self::OrderPlaced { timestamp, .. }
+            | Self::OrderCancelled { timestamp, .. }
+            | Self::OraclePriceUpdated { timestamp, .. }
+            | Self::PriceUpdated { timestamp, .. } => *timestamp,
+        }
+    }
+
     /// Returns the event type as a static string slice.
     #[must_use]
     pub const fn event_type_str(&self) -> &'static str {
         match self {
             Self::PoolCreated { .. } => "pool_created",
             Self::PoolRemoved { .. } => "pool_removed",
+            Self::PoolArchived { .. } => "pool_archived",
+            Self::PoolRestored { .. } => "pool_restored",
+            Self::PoolStatusChanged { .. } => "pool_status_changed",
+            Self::PoolMetadataUpdated { .. } => "pool_metadata_updated",
+            Self::PoolOwnershipTransferred { .. } => "pool_ownership_transferred",
             Self::SwapExecuted { .. } => "swap_executed",
             Self::LiquidityChanged { .. } => "liquidity_changed",
             Self::FeesCollected { .. } => "fees_collected",
+            Self::RewardsClaimed { .. } => "rewards_claimed",
+            Self::OrderPlaced { .. } => "order_placed",
+            Self::OrderCancelled { .. } => "order_cancelled",
+            Self::OraclePriceUpdated { .. } => "oracle_price_updated",
             Self::PriceUpdated { .. } => "price_updated",
         }
     }
 }
 
+/// Default `fee_rebate` of swaps that predate or lack the fee program.
+fn no_rebate() -> String {
+    "0".to_string()
+}
+
 #[cfg(test)]
 #[allow(clippy::panic)]
 mod tests {
@@ -168,6 +419,9 @@ mod tests {
             token_a: "0xaaa".to_string(),
             token_b: "0xbbb".to_string(),
             fee_tier: 30,
+            config: serde_json::Value::Null,
+            metadata: serde_json::Value::Null,
+            owner: None,
             timestamp: Utc::now(),
         };
         assert_eq!(event.event_type_str(), "pool_created");
@@ -181,8 +435,12 @@ mod tests {
             amount_in: "1000".to_string(),
             amount_out: "990".to_string(),
             fee: "3".to_string(),
+            fee_rebate: "0".to_string(),
             new_price: "0.99".to_string(),
             price_change_bps: -10,
+            token_in: "0xaaa".to_string(),
+            swap_kind: SwapKind::ExactIn,
+            actor: None,
             timestamp: Utc::now(),
         };
         let json = serde_json::to_string(&event);
@@ -192,6 +450,31 @@ mod tests {
         assert!(json_str.contains("1000"));
     }
 
+    #[test]
+    fn event_round_trips_through_json() {
+        let event = PoolEvent::LiquidityChanged {
+            pool_id: PoolId::new(),
+            change_type: LiquidityChangeType::Remove,
+            amount_a: "10".to_string(),
+            amount_b: "20".to_string(),
+            new_total_liquidity: "100".to_string(),
+            liquidity_delta: "5".to_string(),
+            position_id: Some(PositionId::new()),
+            range: None,
+            actor: Some("bot-1".to_string()),
+            timestamp: Utc::now(),
+        };
+        let Ok(value) = serde_json::to_value(&event) else {
+            panic!("serialize failed");
+        };
+        let Ok(back) = serde_json::from_value::<PoolEvent>(value) else {
+            panic!("deserialize failed");
+        };
+        assert_eq!(back.event_type_str(), "liquidity_changed");
+        assert_eq!(back.pool_id(), event.pool_id());
+        assert_eq!(back.actor(), Some("bot-1"));
+    }
+
     #[test]
     fn pool_id_accessor() {
         let id = PoolId::new();
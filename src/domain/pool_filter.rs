@@ -0,0 +1,81 @@
+//! Selection and ordering of pool listings.
+//!
+//! [`PoolFilter`] picks the pools a listing returns by type, token, fee
+//! tier, and lifecycle status, and orders them per [`PoolSort`]. The
+//! default filter returns every pool, oldest first.
+
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use super::pool_entry::{PoolEntry, PoolStatus, PoolSummary};
+
+/// Order of a pool listing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolSort {
+    /// Oldest pools first.
+    #[default]
+    CreatedAt,
+    /// Largest cumulative swap volume first.
+    Volume,
+    /// Most swaps first.
+    SwapCount,
+}
+
+impl PoolSort {
+    /// Sorts `pools` in this order. Ties keep creation order.
+    pub fn sort(self, pools: &mut [PoolSummary]) {
+        pools.sort_by_key(|p| (p.created_at, p.pool_id));
+        match self {
+            Self::CreatedAt => {}
+            Self::Volume => pools.sort_by_key(|p| std::cmp::Reverse(p.total_volume)),
+            Self::SwapCount => pools.sort_by_key(|p| std::cmp::Reverse(p.swap_count)),
+        }
+    }
+}
+
+/// Criteria of a pool listing; every criterion that is set must hold.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoolFilter {
+    /// Only pools of this type.
+    pub pool_type: Option<String>,
+    /// Only pools holding the token with this address (compared
+    /// case-insensitively).
+    pub token: Option<String>,
+    /// Only pools with at least this fee tier, in basis points.
+    pub min_fee_bps: Option<u32>,
+    /// Only pools with at most this fee tier, in basis points.
+    pub max_fee_bps: Option<u32>,
+    /// Only pools in this lifecycle state.
+    pub status: Option<PoolStatus>,
+    /// Order of the listing.
+    pub sort_by: PoolSort,
+}
+
+impl PoolFilter {
+    /// A filter keeping the pools of `pool_type`.
+    #[must_use]
+    pub fn of_type(pool_type: &str) -> Self {
+        Self {
+            pool_type: Some(pool_type.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Returns `true` if `entry` meets every criterion that is set.
+    #[must_use]
+    pub fn matches(&self, entry: &PoolEntry) -> bool {
+        self.pool_type
+            .as_deref()
+            .is_none_or(|t| entry.pool_type == t)
+            && self.token.as_deref().is_none_or(|address| {
+                entry
+                    .tokens
+                    .iter()
+                    .any(|t| t.address.eq_ignore_ascii_case(address))
+            })
+            && self.min_fee_bps.is_none_or(|min| entry.fee_bps >= min)
+            && self.max_fee_bps.is_none_or(|max| entry.fee_bps <= max)
+            && self.status.is_none_or(|s| entry.status == s)
+    }
+}
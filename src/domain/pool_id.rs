@@ -13,7 +13,9 @@ use utoipa::ToSchema;
 /// Wraps a UUID v4. Generated once at pool creation time and immutable
 /// thereafter. Used as the dictionary key in [`super::PoolRegistry`],
 /// event discriminator, and WebSocket subscription target.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
 #[serde(transparent)]
 pub struct PoolId(uuid::Uuid);
 
@@ -0,0 +1,272 @@
+//! Replayable pool mutations.
+//!
+//! `PoolBox` can be neither cloned nor serialized, so the gateway keeps
+//! its own record of every state-changing call as a [`PoolOperation`].
+//! Rebuilding a pool from its creation config and re-applying its
+//! operations in order reproduces the live state exactly; this is what
+//! snapshots, event replay, and recovery are built on.
+
+use hydra_amm::domain::{Amount, Liquidity, LiquidityChange, SwapSpec, Token, TokenPair};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::order_book::OrderSide;
+use super::{OrderId, PositionId};
+use crate::error::GatewayError;
+
+/// Which token of the pool's pair an operation refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenSide {
+    /// The pair's first token (token A).
+    First,
+    /// The pair's second token (token B).
+    Second,
+}
+
+impl TokenSide {
+    /// Resolves `token` against `pair`, returning `None` if it belongs to
+    /// neither side.
+    #[must_use]
+    pub fn of(pair: &TokenPair, token: Token) -> Option<Self> {
+        if token == pair.first() {
+            Some(Self::First)
+        } else if token == pair.second() {
+            Some(Self::Second)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the token on this side of `pair`.
+    #[must_use]
+    pub fn token(self, pair: &TokenPair) -> Token {
+        match self {
+            Self::First => pair.first(),
+            Self::Second => pair.second(),
+        }
+    }
+
+    /// Returns the opposite side.
+    #[must_use]
+    pub const fn other(self) -> Self {
+        match self {
+            Self::First => Self::Second,
+            Self::Second => Self::First,
+        }
+    }
+
+    /// Index of this side in a two-element reserve vector.
+    #[must_use]
+    pub const fn index(self) -> usize {
+        match self {
+            Self::First => 0,
+            Self::Second => 1,
+        }
+    }
+}
+
+/// Whether a swap fixes its input or its output amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapKind {
+    /// The input amount is fixed.
+    #[default]
+    ExactIn,
+    /// The output amount is fixed.
+    ExactOut,
+}
+
+impl SwapKind {
+    /// Builds the hydra-amm [`SwapSpec`] for `amount`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::AmmError`] if hydra-amm rejects the amount.
+    pub fn spec(self, amount: Amount) -> Result<SwapSpec, GatewayError> {
+        Ok(match self {
+            Self::ExactIn => SwapSpec::exact_in(amount)?,
+            Self::ExactOut => SwapSpec::exact_out(amount)?,
+        })
+    }
+}
+
+/// Tick range of a concentrated-liquidity position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TickRange {
+    /// Lower tick (inclusive).
+    pub lower: i32,
+    /// Upper tick (exclusive).
+    pub upper: i32,
+}
+
+impl TickRange {
+    /// Widest range hydra-amm accepts, used for positions opened without
+    /// an explicit range.
+    pub const FULL: Self = Self {
+        lower: -887_272,
+        upper: 887_272,
+    };
+}
+
+/// A state-changing call on a pool, in a form that can be re-applied.
+///
+/// Amounts are string-encoded u128 values, matching the event payloads.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PoolOperation {
+    /// A swap of `amount` (input or output depending on `kind`).
+    Swap {
+        /// Side of the input token.
+        token_in: TokenSide,
+        /// Exact-in or exact-out.
+        kind: SwapKind,
+        /// The fixed amount.
+        amount: String,
+    },
+    /// A two-sided deposit.
+    AddLiquidity {
+        /// Token A deposited.
+        amount_a: String,
+        /// Token B deposited.
+        amount_b: String,
+        /// Position credited with the minted units. Absent for deposits
+        /// journaled before positions were tracked.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        position_id: Option<PositionId>,
+        /// Tick range recorded when the deposit opened the position.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        range: Option<TickRange>,
+        /// Client id recorded as the owner when the deposit opened the
+        /// position.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        owner: Option<String>,
+    },
+    /// A withdrawal burning `liquidity` LP units.
+    RemoveLiquidity {
+        /// LP units burned.
+        liquidity: String,
+        /// Position debited. Absent when burning liquidity no position
+        /// owns (creation liquidity or pre-position deposits).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        position_id: Option<PositionId>,
+    },
+    /// Collection of the fees accrued by a position.
+    CollectFees {
+        /// Position collected for.
+        position_id: PositionId,
+    },
+    /// A limit order rested on an order-book pool.
+    PlaceOrder {
+        /// Id assigned to the order.
+        order_id: OrderId,
+        /// Side of the book.
+        side: OrderSide,
+        /// Limit price in token B units per token A unit.
+        price: String,
+        /// Token A units.
+        quantity: String,
+        /// Client id that placed the order.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        owner: Option<String>,
+    },
+    /// Cancellation of a resting limit order.
+    CancelOrder {
+        /// Order cancelled.
+        order_id: OrderId,
+    },
+    /// A new oracle price pushed to a dynamic pool.
+    SetOraclePrice {
+        /// Oracle price, in the units of the config's `oracle_price`.
+        price: String,
+    },
+}
+
+impl PoolOperation {
+    /// Builds the hydra-amm [`LiquidityChange`] for a liquidity operation.
+    ///
+    /// Returns `Ok(None)` for swaps, fee collections, orders, and oracle
+    /// updates.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::Internal`] if a stored amount does not
+    /// parse, or [`GatewayError::AmmError`] if hydra-amm rejects it.
+    pub fn liquidity_change(&self) -> Result<Option<LiquidityChange>, GatewayError> {
+        match self {
+            Self::Swap { .. }
+            | Self::CollectFees { .. }
+            | Self::PlaceOrder { .. }
+            | Self::CancelOrder { .. }
+            | Self::SetOraclePrice { .. } => Ok(None),
+            Self::AddLiquidity {
+                amount_a, amount_b, ..
+            } => Ok(Some(LiquidityChange::add(
+                Amount::new(parse_u128(amount_a)?),
+                Amount::new(parse_u128(amount_b)?),
+            )?)),
+            Self::RemoveLiquidity { liquidity, .. } => Ok(Some(LiquidityChange::remove(
+                Liquidity::new(parse_u128(liquidity)?),
+            )?)),
+        }
+    }
+}
+
+/// Parses a string-encoded u128 stored in an operation or event.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::Internal`] if `s` is not a valid u128.
+pub fn parse_u128(s: &str) -> Result<u128, GatewayError> {
+    s.parse()
+        .map_err(|_| GatewayError::Internal(format!("invalid stored amount: {s}")))
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operation_round_trips_through_json() {
+        let op = PoolOperation::Swap {
+            token_in: TokenSide::Second,
+            kind: SwapKind::ExactOut,
+            amount: "1000".to_string(),
+        };
+        let Ok(value) = serde_json::to_value(&op) else {
+            panic!("serialize failed");
+        };
+        assert_eq!(value.get("op"), Some(&serde_json::json!("swap")));
+        assert_eq!(value.get("kind"), Some(&serde_json::json!("exact_out")));
+        let Ok(back) = serde_json::from_value::<PoolOperation>(value) else {
+            panic!("deserialize failed");
+        };
+        assert_eq!(back, op);
+    }
+
+    #[test]
+    fn liquidity_change_rejects_bad_amounts() {
+        let op = PoolOperation::RemoveLiquidity {
+            liquidity: "not-a-number".to_string(),
+            position_id: None,
+        };
+        assert!(op.liquidity_change().is_err());
+    }
+
+    #[test]
+    fn journal_without_position_ids_still_parses() {
+        let value = serde_json::json!({"op": "add_liquidity", "amount_a": "1", "amount_b": "2"});
+        let Ok(op) = serde_json::from_value::<PoolOperation>(value) else {
+            panic!("deserialize failed");
+        };
+        let PoolOperation::AddLiquidity {
+            position_id, range, ..
+        } = op
+        else {
+            panic!("expected add");
+        };
+        assert!(position_id.is_none());
+        assert!(range.is_none());
+    }
+}
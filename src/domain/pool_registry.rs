@@ -4,14 +4,21 @@
 //! entry is individually protected by a [`tokio::sync::RwLock`]. This
 //! allows concurrent reads on the same pool and concurrent writes on
 //! different pools.
+//!
+//! Removing a pool leaves a [`Tombstone`]: the entry is detached so new
+//! lookups fail, and marked removed so operations that fetched it before
+//! the removal are refused once they take its write lock. Its state is
+//! reaped when the last outstanding handle is dropped.
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use tokio::sync::RwLock;
 
 use super::PoolId;
 use super::pool_entry::{PoolEntry, PoolSummary};
+use super::pool_filter::PoolFilter;
 use crate::error::GatewayError;
 
 /// Central store for all active AMM pools.
@@ -56,6 +63,34 @@ impl PoolRegistry {
         Ok(pool_id)
     }
 
+    /// Inserts a new pool entry unless the registry already holds
+    /// `max_pools` pools.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::CapacityExceeded`] if the registry is full
+    /// and [`GatewayError::InvalidRequest`] if the ID already exists.
+    pub async fn insert_within(
+        &self,
+        entry: PoolEntry,
+        max_pools: usize,
+    ) -> Result<PoolId, GatewayError> {
+        let pool_id = entry.pool_id;
+        let mut map = self.pools.write().await;
+        if map.len() >= max_pools {
+            return Err(GatewayError::CapacityExceeded(format!(
+                "pool limit of {max_pools} reached"
+            )));
+        }
+        if map.contains_key(&pool_id) {
+            return Err(GatewayError::InvalidRequest(format!(
+                "pool {pool_id} already exists"
+            )));
+        }
+        map.insert(pool_id, Arc::new(RwLock::new(entry)));
+        Ok(pool_id)
+    }
+
     /// Returns a shared reference to the pool entry behind a per-pool lock.
     ///
     /// # Errors
@@ -69,43 +104,82 @@ impl PoolRegistry {
             .ok_or(GatewayError::PoolNotFound(*pool_id.as_uuid()))
     }
 
-    /// Removes a pool from the registry, returning its entry.
+    /// Removes a pool from the registry, leaving a tombstone.
+    ///
+    /// Waits for in-flight holders of the write lock, then marks the
+    /// entry removed. Succeeds however many handles to the entry remain;
+    /// reap the tombstone to get the final state once they are gone.
     ///
     /// # Errors
     ///
     /// Returns [`GatewayError::PoolNotFound`] if no pool with the given ID
     /// exists.
-    pub async fn remove(&self, pool_id: PoolId) -> Result<PoolEntry, GatewayError> {
-        let mut map = self.pools.write().await;
-        let arc = map
+    pub async fn remove(&self, pool_id: PoolId) -> Result<Tombstone, GatewayError> {
+        let entry = self.detach(pool_id).await?;
+        entry.write().await.removed = true;
+        Ok(Tombstone::new(pool_id, entry))
+    }
+
+    /// Removes a pool from the registry regardless of outstanding
+    /// references, returning its handle. In-flight holders keep working
+    /// on the detached entry; new lookups fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PoolNotFound`] if no pool with the given ID
+    /// exists.
+    pub async fn detach(&self, pool_id: PoolId) -> Result<Arc<RwLock<PoolEntry>>, GatewayError> {
+        self.pools
+            .write()
+            .await
             .remove(&pool_id)
-            .ok_or(GatewayError::PoolNotFound(*pool_id.as_uuid()))?;
-        // Unwrap the Arc — we just removed it so we hold the only strong ref
-        // after the map write lock is released. Use `try_unwrap` to be safe.
-        let entry = Arc::try_unwrap(arc)
-            .map_err(|_| {
-                GatewayError::Internal("pool entry still referenced elsewhere".to_string())
-            })?
-            .into_inner();
-        Ok(entry)
-    }
-
-    /// Returns summaries of all pools, optionally filtered by pool type.
-    pub async fn list(&self, pool_type_filter: Option<&str>) -> Vec<PoolSummary> {
+            .ok_or(GatewayError::PoolNotFound(*pool_id.as_uuid()))
+    }
+
+    /// Removes every pool whose ID fails `keep`, returning how many were
+    /// removed.
+    pub async fn retain(&self, keep: impl Fn(PoolId) -> bool) -> usize {
+        let ids: Vec<PoolId> = self
+            .pools
+            .read()
+            .await
+            .keys()
+            .copied()
+            .filter(|id| !keep(*id))
+            .collect();
+        let mut removed = 0;
+        for pool_id in ids {
+            if self.remove(pool_id).await.is_ok() {
+                removed += 1;
+            }
+        }
+        removed
+    }
+
+    /// Puts a detached handle back under `pool_id`.
+    pub async fn reattach(&self, pool_id: PoolId, entry: Arc<RwLock<PoolEntry>>) {
+        self.pools.write().await.insert(pool_id, entry);
+    }
+
+    /// Returns summaries of the pools matching `filter`, in its order.
+    pub async fn list(&self, filter: &PoolFilter) -> Vec<PoolSummary> {
         let map = self.pools.read().await;
         let mut summaries = Vec::with_capacity(map.len());
         for entry_lock in map.values() {
             let entry = entry_lock.read().await;
-            if let Some(filter) = pool_type_filter
-                && entry.pool_type != filter
-            {
-                continue;
+            if filter.matches(&entry) {
+                summaries.push(PoolSummary::from(&*entry));
             }
-            summaries.push(PoolSummary::from(&*entry));
         }
+        filter.sort_by.sort(&mut summaries);
         summaries
     }
 
+    /// Returns handles to every pool entry.
+    pub async fn entries(&self) -> Vec<Arc<RwLock<PoolEntry>>> {
+        self.pools.read().await.values().cloned().collect()
+    }
+
     /// Returns the number of pools in the registry.
     pub async fn len(&self) -> usize {
         self.pools.read().await.len()
@@ -123,6 +197,57 @@ impl Default for PoolRegistry {
     }
 }
 
+/// How often [`Tombstone::reap`] checks for outstanding handles.
+const REAP_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A removed pool whose entry may still be referenced by operations that
+/// fetched it before the removal.
+#[derive(Debug)]
+pub struct Tombstone {
+    pool_id: PoolId,
+    entry: Arc<RwLock<PoolEntry>>,
+}
+
+impl Tombstone {
+    /// Wraps the detached `entry` of `pool_id`, which the caller has
+    /// marked removed.
+    #[must_use]
+    pub const fn new(pool_id: PoolId, entry: Arc<RwLock<PoolEntry>>) -> Self {
+        Self { pool_id, entry }
+    }
+
+    /// Returns the removed pool's ID.
+    #[must_use]
+    pub const fn pool_id(&self) -> PoolId {
+        self.pool_id
+    }
+
+    /// Returns the final state if no other handle to the entry remains,
+    /// or the tombstone back otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns `self` while the entry is still referenced elsewhere.
+    pub fn try_reap(self) -> Result<PoolEntry, Self> {
+        let pool_id = self.pool_id;
+        Arc::try_unwrap(self.entry)
+            .map(RwLock::into_inner)
+            .map_err(|entry| Self { pool_id, entry })
+    }
+
+    /// Waits until every other handle to the entry is dropped and returns
+    /// the final state.
+    pub async fn reap(mut self) -> PoolEntry {
+        loop {
+            match self.try_reap() {
+                Ok(entry) => return entry,
+                Err(tombstone) => self = tombstone,
+            }
+            tokio::time::sleep(REAP_INTERVAL).await;
+        }
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::panic)]
 mod tests {
@@ -186,14 +311,57 @@ mod tests {
         let id = entry.pool_id;
 
         let _ = registry.insert(entry).await;
-        let removed = registry.remove(id).await;
-        assert!(removed.is_ok());
+        let Ok(tombstone) = registry.remove(id).await else {
+            panic!("remove failed");
+        };
+        assert!(tombstone.try_reap().is_ok_and(|entry| entry.removed));
 
         // Now it should be gone
         let result = registry.get(id).await;
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn retain_removes_rejected_pools() {
+        let registry = PoolRegistry::new();
+        let kept = make_pool_entry();
+        let dropped = make_pool_entry();
+        let (kept_id, dropped_id) = (kept.pool_id, dropped.pool_id);
+        let _ = registry.insert(kept).await;
+        let _ = registry.insert(dropped).await;
+
+        assert_eq!(registry.retain(|id| id == kept_id).await, 1);
+        assert!(registry.get(kept_id).await.is_ok());
+        assert!(registry.get(dropped_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn remove_tombstones_entries_still_in_use() {
+        let registry = PoolRegistry::new();
+        let entry = make_pool_entry();
+        let id = entry.pool_id;
+        let _ = registry.insert(entry).await;
+        let Ok(in_flight) = registry.get(id).await else {
+            panic!("pool not found");
+        };
+
+        let Ok(tombstone) = registry.remove(id).await else {
+            panic!("remove refused while the pool is in use");
+        };
+        assert!(registry.get(id).await.is_err());
+        assert!(in_flight.read().await.removed);
+        let Err(tombstone) = tombstone.try_reap() else {
+            panic!("reaped while still referenced");
+        };
+
+        let reaper = tokio::spawn(tombstone.reap());
+        drop(in_flight);
+        let Ok(reaped) = reaper.await else {
+            panic!("reaper failed");
+        };
+        assert_eq!(reaped.pool_id, id);
+    }
+
     #[tokio::test]
     async fn remove_nonexistent_returns_error() {
         let registry = PoolRegistry::new();
@@ -207,7 +375,7 @@ mod tests {
         let _ = registry.insert(make_pool_entry()).await;
         let _ = registry.insert(make_pool_entry()).await;
 
-        let list = registry.list(None).await;
+        let list = registry.list(&PoolFilter::default()).await;
         assert_eq!(list.len(), 2);
     }
 
@@ -216,13 +384,77 @@ mod tests {
         let registry = PoolRegistry::new();
         let _ = registry.insert(make_pool_entry()).await;
 
-        let matched = registry.list(Some("constant_product")).await;
+        let matched = registry
+            .list(&PoolFilter::of_type("constant_product"))
+            .await;
         assert_eq!(matched.len(), 1);
 
-        let unmatched = registry.list(Some("clmm")).await;
+        let unmatched = registry.list(&PoolFilter::of_type("clmm")).await;
         assert!(unmatched.is_empty());
     }
 
+    #[tokio::test]
+    async fn list_filters_by_token_fee_and_status_and_sorts() {
+        use crate::domain::pool_entry::{PoolStatus, TokenInfo};
+        use crate::domain::pool_filter::PoolSort;
+
+        let registry = PoolRegistry::new();
+        let mut ids = Vec::new();
+        for (fee_bps, volume, swaps) in [(5, 300, 1), (30, 100, 9), (100, 200, 4)] {
+            let mut entry = make_pool_entry();
+            entry.fee_bps = fee_bps;
+            entry.total_volume = volume;
+            entry.swap_count = swaps;
+            entry.tokens = vec![TokenInfo {
+                address: format!("0xFEE{fee_bps}"),
+                decimals: 6,
+                symbol: String::new(),
+            }];
+            ids.push(entry.pool_id);
+            let _ = registry.insert(entry).await;
+        }
+        let Some(&paused) = ids.get(2) else {
+            panic!("missing pool");
+        };
+        if let Ok(entry) = registry.get(paused).await {
+            entry.write().await.status = PoolStatus::Paused;
+        }
+
+        let listed = |filter: PoolFilter| {
+            let registry = &registry;
+            async move {
+                registry
+                    .list(&filter)
+                    .await
+                    .into_iter()
+                    .map(|p| p.fee_bps)
+                    .collect::<Vec<_>>()
+            }
+        };
+        let fee_range = PoolFilter {
+            min_fee_bps: Some(10),
+            max_fee_bps: Some(100),
+            sort_by: PoolSort::Volume,
+            ..PoolFilter::default()
+        };
+        assert_eq!(listed(fee_range).await, [100, 30]);
+        let by_swaps = PoolFilter {
+            sort_by: PoolSort::SwapCount,
+            ..PoolFilter::default()
+        };
+        assert_eq!(listed(by_swaps).await, [30, 100, 5]);
+        let token = PoolFilter {
+            token: Some("0xfee30".to_string()),
+            ..PoolFilter::default()
+        };
+        assert_eq!(listed(token).await, [30]);
+        let active = PoolFilter {
+            status: Some(PoolStatus::Active),
+            ..PoolFilter::default()
+        };
+        assert_eq!(listed(active).await.len(), 2);
+    }
+
     #[tokio::test]
     async fn len_and_is_empty() {
         let registry = PoolRegistry::new();
@@ -0,0 +1,77 @@
+//! Type-safe liquidity position identifier.
+//!
+//! [`PositionId`] names a provider's stake in a pool, much like an LP
+//! NFT: it is minted by the first deposit and referenced by later
+//! top-ups, withdrawals, and fee collections.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Unique identifier for a liquidity position.
+///
+/// Wraps a UUID v4. Positions are scoped to the pool they were opened
+/// in; the same id is never valid in another pool.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
+#[serde(transparent)]
+pub struct PositionId(uuid::Uuid);
+
+impl PositionId {
+    /// Creates a new random `PositionId` (UUID v4).
+    #[must_use]
+    pub fn new() -> Self {
+        Self(uuid::Uuid::new_v4())
+    }
+
+    /// Creates a `PositionId` from an existing [`uuid::Uuid`].
+    #[must_use]
+    pub const fn from_uuid(uuid: uuid::Uuid) -> Self {
+        Self(uuid)
+    }
+
+    /// Returns the inner [`uuid::Uuid`].
+    #[must_use]
+    pub const fn as_uuid(&self) -> &uuid::Uuid {
+        &self.0
+    }
+}
+
+impl Default for PositionId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for PositionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<uuid::Uuid> for PositionId {
+    fn from(uuid: uuid::Uuid) -> Self {
+        Self(uuid)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_as_bare_uuid() {
+        let id = PositionId::new();
+        let Ok(json) = serde_json::to_string(&id) else {
+            panic!("serialization failed");
+        };
+        assert_eq!(json, format!("\"{id}\""));
+        let Ok(back) = serde_json::from_str::<PositionId>(&json) else {
+            panic!("deserialization failed");
+        };
+        assert_eq!(back, id);
+    }
+}
@@ -0,0 +1,148 @@
+//! Liquidity positions of a pool.
+//!
+//! Every deposit made through the gateway opens or tops up a position,
+//! which records the client that opened it, its tick range, the LP units
+//! it holds, and its fee checkpoint. The registry lives in the
+//! [`PoolEntry`](super::PoolEntry) and is rebuilt from the operation
+//! journal, so positions survive snapshots and replay. A fully withdrawn
+//! position stays listed as closed.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::PositionId;
+use super::pool_operation::TickRange;
+
+/// A provider's stake in a pool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LiquidityPosition {
+    /// Client id that opened the position, if the request carried one.
+    pub owner: Option<String>,
+    /// LP units currently held.
+    pub liquidity: u128,
+    /// Tick range recorded when the position was opened.
+    pub range: Option<TickRange>,
+    /// The pool's [`PoolEntry::fees_accrued`] when the position last
+    /// collected fees (or was opened).
+    ///
+    /// [`PoolEntry::fees_accrued`]: super::PoolEntry::fees_accrued
+    pub fee_checkpoint: [u128; 2],
+}
+
+impl LiquidityPosition {
+    /// Returns `true` while the position holds liquidity.
+    #[must_use]
+    pub const fn is_open(&self) -> bool {
+        self.liquidity > 0
+    }
+}
+
+/// Positions of one pool, by id.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PositionRegistry {
+    positions: BTreeMap<PositionId, LiquidityPosition>,
+}
+
+impl PositionRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of positions, open or closed.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns `true` if no position was ever opened.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Returns `true` if `id` is a position of this pool.
+    #[must_use]
+    pub fn contains(&self, id: &PositionId) -> bool {
+        self.positions.contains_key(id)
+    }
+
+    /// Returns the position `id`.
+    #[must_use]
+    pub fn get(&self, id: &PositionId) -> Option<&LiquidityPosition> {
+        self.positions.get(id)
+    }
+
+    /// Returns the position `id` for an update.
+    pub fn get_mut(&mut self, id: &PositionId) -> Option<&mut LiquidityPosition> {
+        self.positions.get_mut(id)
+    }
+
+    /// Returns the position `id`, opening it empty for `owner` over
+    /// `range` with `fee_checkpoint` if it does not exist yet.
+    pub fn open(
+        &mut self,
+        id: PositionId,
+        owner: Option<String>,
+        range: Option<TickRange>,
+        fee_checkpoint: [u128; 2],
+    ) -> &mut LiquidityPosition {
+        self.positions.entry(id).or_insert(LiquidityPosition {
+            owner,
+            liquidity: 0,
+            range,
+            fee_checkpoint,
+        })
+    }
+
+    /// Iterates over every position in id order.
+    pub fn iter(&self) -> impl Iterator<Item = (&PositionId, &LiquidityPosition)> {
+        self.positions.iter()
+    }
+
+    /// Iterates over every position in id order.
+    pub fn values(&self) -> impl Iterator<Item = &LiquidityPosition> {
+        self.positions.values()
+    }
+
+    /// LP units held across all positions.
+    #[must_use]
+    pub fn total_liquidity(&self) -> u128 {
+        self.values()
+            .fold(0u128, |acc, p| acc.saturating_add(p.liquidity))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_keeps_the_first_owner_and_range() {
+        let mut positions = PositionRegistry::new();
+        let id = PositionId::new();
+        let range = Some(TickRange {
+            lower: -60,
+            upper: 60,
+        });
+        positions
+            .open(id, Some("desk-1".to_string()), range, [0; 2])
+            .liquidity += 100;
+        positions
+            .open(id, Some("desk-2".to_string()), None, [5; 2])
+            .liquidity += 50;
+
+        let Some(position) = positions.get(&id) else {
+            panic!("position tracked");
+        };
+        assert_eq!(position.owner.as_deref(), Some("desk-1"));
+        assert_eq!(position.range, range);
+        assert_eq!(position.fee_checkpoint, [0; 2]);
+        assert_eq!(positions.total_liquidity(), 150);
+        assert!(position.is_open());
+        assert_eq!(positions.len(), 1);
+    }
+}
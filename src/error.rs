@@ -5,6 +5,7 @@
 
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 use utoipa::ToSchema;
 
@@ -16,7 +17,7 @@ use utoipa::ToSchema;
 ///   "error": {
 ///     "code": 1001,
 ///     "message": "Invalid price: must be positive",
-///     "details": null
+///     "retryable": false
 ///   }
 /// }
 /// ```
@@ -36,6 +37,271 @@ pub struct ErrorBody {
     /// Optional additional details.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<String>,
+    /// Field-level problems, for errors that have them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<FieldError>>,
+    /// Whether the same request may succeed if retried later.
+    pub retryable: bool,
+    /// How long to wait before retrying, when the server knows.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
+}
+
+/// A problem with one field of a request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct FieldError {
+    /// JSON pointer to the offending value (`""` for the whole value).
+    pub field: String,
+    /// What is wrong with it.
+    pub message: String,
+    /// Schema that rejected it (e.g. `pool_type:constant_product`).
+    pub schema: String,
+}
+
+/// One entry of the error catalog served at `GET /api/v1/errors`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ErrorCatalogEntry {
+    /// Numeric error code.
+    pub code: u32,
+    /// Stable snake_case name of the error.
+    pub name: &'static str,
+    /// HTTP status the error is returned with.
+    pub status: u16,
+    /// Message template; `{...}` parts are filled in per occurrence.
+    pub message: &'static str,
+    /// Whether the same request may succeed if retried later.
+    pub retryable: bool,
+}
+
+/// Every error the gateway returns, by code. Must agree with
+/// [`GatewayError::error_code`] and [`GatewayError::status_code`]; a test
+/// checks every variant against it.
+pub const ERROR_CATALOG: [ErrorCatalogEntry; 35] = [
+    entry(401, "unauthorized", 401, "unauthorized: {reason}", false),
+    entry(403, "forbidden", 403, "forbidden: {reason}", false),
+    entry(
+        429,
+        "rate_limited",
+        429,
+        "rate limit exceeded; retry after {retry_after_ms} ms",
+        true,
+    ),
+    entry(
+        1001,
+        "invalid_request",
+        400,
+        "invalid request: {reason}",
+        false,
+    ),
+    entry(
+        1002,
+        "invalid_pool_type",
+        400,
+        "invalid pool type: {pool_type}",
+        false,
+    ),
+    entry(1003, "amm_error", 400, "amm error: {reason}", false),
+    entry(
+        1004,
+        "invalid_metadata",
+        400,
+        "invalid metadata: {count} schema violation(s)",
+        false,
+    ),
+    entry(
+        1005,
+        "idempotency_key_reused",
+        400,
+        "idempotency key {key} was used for a different request",
+        false,
+    ),
+    entry(
+        2001,
+        "pool_not_found",
+        404,
+        "pool not found: {pool_id}",
+        false,
+    ),
+    entry(
+        2002,
+        "position_not_found",
+        404,
+        "position not found in pool {pool_id}",
+        false,
+    ),
+    entry(2003, "pool_in_use", 409, "pool in use: {reason}", false),
+    entry(
+        2004,
+        "version_mismatch",
+        409,
+        "pool state changed: expected version {expected}, found {actual}",
+        false,
+    ),
+    entry(2005, "job_not_found", 404, "job not found: {job_id}", false),
+    entry(
+        2006,
+        "webhook_not_found",
+        404,
+        "webhook not found: {webhook_id}",
+        false,
+    ),
+    entry(
+        2007,
+        "pool_archived",
+        409,
+        "pool {pool_id} is archived",
+        false,
+    ),
+    entry(
+        2008,
+        "metadata_schema_not_found",
+        404,
+        "metadata schema not found: {scope}:{name}",
+        false,
+    ),
+    entry(
+        2009,
+        "order_not_found",
+        404,
+        "order not found in pool {pool_id}",
+        false,
+    ),
+    entry(
+        2010,
+        "idempotency_key_in_progress",
+        409,
+        "a request with idempotency key {key} is still in progress",
+        true,
+    ),
+    entry(
+        2011,
+        "snapshot_not_found",
+        404,
+        "snapshot not found: {snapshot_id}",
+        false,
+    ),
+    entry(
+        2012,
+        "pool_paused",
+        409,
+        "pool {pool_id} is {status}",
+        false,
+    ),
+    entry(3000, "internal", 500, "internal error: {reason}", false),
+    entry(
+        3001,
+        "persistence_error",
+        500,
+        "persistence error: {reason}",
+        true,
+    ),
+    entry(
+        3002,
+        "persistence_unavailable",
+        503,
+        "persistence unavailable",
+        false,
+    ),
+    entry(
+        3003,
+        "read_only_replica",
+        503,
+        "read-only replica: send mutations to the primary",
+        false,
+    ),
+    entry(
+        3004,
+        "overloaded",
+        503,
+        "server busy; retry after {retry_after_ms} ms",
+        true,
+    ),
+    entry(
+        3005,
+        "lock_timeout",
+        503,
+        "pool {pool_id} is busy; retry after {retry_after_ms} ms",
+        true,
+    ),
+    entry(
+        3006,
+        "owner_unreachable",
+        502,
+        "pool owner unreachable: {reason}",
+        true,
+    ),
+    entry(
+        3007,
+        "event_log_backlog",
+        503,
+        "event log behind; retry after {retry_after_ms} ms",
+        true,
+    ),
+    entry(
+        4001,
+        "insufficient_liquidity",
+        422,
+        "insufficient liquidity in pool",
+        false,
+    ),
+    entry(
+        4002,
+        "insufficient_balance",
+        422,
+        "insufficient balance: {reason}",
+        false,
+    ),
+    entry(
+        4003,
+        "slippage_exceeded",
+        422,
+        "slippage exceeded: {bound} {limit}, quoted {quoted}",
+        false,
+    ),
+    entry(
+        4004,
+        "capacity_exceeded",
+        422,
+        "capacity exceeded: {reason}",
+        false,
+    ),
+    entry(
+        4005,
+        "pool_quota_exceeded",
+        429,
+        "pool quota exceeded: {reason}",
+        false,
+    ),
+    entry(
+        4006,
+        "deadline_expired",
+        422,
+        "deadline {deadline} has passed",
+        false,
+    ),
+    entry(
+        4007,
+        "oracle_price_rejected",
+        422,
+        "oracle price rejected: {reason}",
+        false,
+    ),
+];
+
+const fn entry(
+    code: u32,
+    name: &'static str,
+    status: u16,
+    message: &'static str,
+    retryable: bool,
+) -> ErrorCatalogEntry {
+    ErrorCatalogEntry {
+        code,
+        name,
+        status,
+        message,
+        retryable,
+    }
 }
 
 /// Server-side error enum with HTTP status code mapping.
@@ -46,7 +312,7 @@ pub struct ErrorBody {
 /// |-----------|-----------------|----------------------------|
 /// | 1000–1999 | Validation      | 400 Bad Request            |
 /// | 2000–2999 | State/Not Found | 404 Not Found / 409 Conflict |
-/// | 3000–3999 | Server          | 500 Internal Server Error  |
+/// | 3000–3999 | Server          | 500 Internal Server Error / 502 Bad Gateway / 503 Service Unavailable |
 /// | 4000–4999 | Pool-Specific   | 422 Unprocessable Entity   |
 #[derive(Debug, thiserror::Error)]
 pub enum GatewayError {
@@ -54,10 +320,35 @@ pub enum GatewayError {
     #[error("pool not found: {0}")]
     PoolNotFound(uuid::Uuid),
 
+    /// The pool cannot be removed while it is still in use.
+    #[error("pool in use: {0}")]
+    PoolInUse(String),
+
+    /// The pool changed since the state a quote was computed against.
+    #[error("pool state changed: expected version {expected}, found {actual}")]
+    VersionMismatch {
+        /// Version the caller expected.
+        expected: u64,
+        /// Current pool version.
+        actual: u64,
+    },
+
     /// Request validation failed.
     #[error("invalid request: {0}")]
     InvalidRequest(String),
 
+    /// Pool metadata does not satisfy a registered schema.
+    #[error("invalid metadata: {} schema violation(s)", .0.len())]
+    InvalidMetadata(Vec<FieldError>),
+
+    /// An `Idempotency-Key` was sent again with a different request.
+    #[error("idempotency key {0} was used for a different request")]
+    IdempotencyKeyReused(String),
+
+    /// The first request with an `Idempotency-Key` has not finished yet.
+    #[error("a request with idempotency key {0} is still in progress")]
+    IdempotencyKeyInProgress(String),
+
     /// Pool does not have enough liquidity for the operation.
     #[error("insufficient liquidity in pool")]
     InsufficientLiquidity,
@@ -66,10 +357,74 @@ pub enum GatewayError {
     #[error("insufficient balance: {0}")]
     InsufficientBalance(String),
 
+    /// The swap would fill outside the caller's slippage bound: output
+    /// below `min_amount_out` or input above `max_amount_in`.
+    #[error("slippage exceeded: {bound} {limit}, quoted {quoted}")]
+    SlippageExceeded {
+        /// Bound that was violated (`min_amount_out` or `max_amount_in`).
+        bound: &'static str,
+        /// Caller's bound (string-encoded).
+        limit: String,
+        /// Quoted amount (string-encoded).
+        quoted: String,
+    },
+
+    /// The operation's `deadline` passed before it could execute.
+    #[error("deadline {deadline} has passed")]
+    DeadlineExpired {
+        /// Caller's deadline.
+        deadline: DateTime<Utc>,
+    },
+
+    /// An oracle price update fell outside the pool's oracle bounds.
+    #[error("oracle price rejected: {0}")]
+    OraclePriceRejected(String),
+
+    /// A pool or position limit would be exceeded.
+    #[error("capacity exceeded: {0}")]
+    CapacityExceeded(String),
+
+    /// The caller's API key may not create another pool right now.
+    #[error("pool quota exceeded: {0}")]
+    PoolQuotaExceeded(String),
+
+    /// The pool is archived and accepts no further operations.
+    #[error("pool {0} is archived")]
+    PoolArchived(uuid::Uuid),
+
+    /// The pool is paused or draining and does not accept the operation.
+    #[error("pool {pool_id} is {status}")]
+    PoolPaused {
+        /// Pool that rejected the operation.
+        pool_id: uuid::Uuid,
+        /// Its status: `paused` or `draining`.
+        status: &'static str,
+    },
+
     /// Liquidity position not found.
     #[error("position not found in pool {0}")]
     PositionNotFound(uuid::Uuid),
 
+    /// Resting limit order not found.
+    #[error("order not found in pool {0}")]
+    OrderNotFound(uuid::Uuid),
+
+    /// Background job not found.
+    #[error("job not found: {0}")]
+    JobNotFound(uuid::Uuid),
+
+    /// Pool webhook not found.
+    #[error("webhook not found: {0}")]
+    WebhookNotFound(uuid::Uuid),
+
+    /// Metadata schema not found, as `scope:name`.
+    #[error("metadata schema not found: {0}")]
+    MetadataSchemaNotFound(String),
+
+    /// Pool snapshot not found.
+    #[error("snapshot not found: {0}")]
+    SnapshotNotFound(i64),
+
     /// Error propagated from the hydra-amm computation engine.
     #[error("amm error: {0}")]
     AmmError(#[from] hydra_amm::error::AmmError),
@@ -78,6 +433,14 @@ pub enum GatewayError {
     #[error("persistence error: {0}")]
     PersistenceError(String),
 
+    /// The persistence layer is disabled or not connected.
+    #[error("persistence unavailable")]
+    PersistenceUnavailable,
+
+    /// This instance is a read-only replica; mutations go to the primary.
+    #[error("read-only replica: send mutations to the primary")]
+    ReadOnlyReplica,
+
     /// Client exceeded rate limit.
     #[error("rate limit exceeded; retry after {retry_after_ms} ms")]
     RateLimited {
@@ -85,6 +448,42 @@ pub enum GatewayError {
         retry_after_ms: u64,
     },
 
+    /// Heavy endpoint saturated; the request waited too long for a slot.
+    #[error("server busy; retry after {retry_after_ms} ms")]
+    Overloaded {
+        /// Milliseconds until the client may retry.
+        retry_after_ms: u64,
+    },
+
+    /// A pool's write lock was not acquired within the lock timeout.
+    #[error("pool {pool_id} is busy; retry after {retry_after_ms} ms")]
+    LockTimeout {
+        /// Pool whose lock timed out.
+        pool_id: uuid::Uuid,
+        /// Milliseconds until the client may retry.
+        retry_after_ms: u64,
+    },
+
+    /// The cluster peer owning a pool could not be reached.
+    #[error("pool owner unreachable: {0}")]
+    OwnerUnreachable(String),
+
+    /// Too many events are waiting to be written to the event log, so
+    /// mutations are refused until it catches up.
+    #[error("event log behind; retry after {retry_after_ms} ms")]
+    EventLogBacklog {
+        /// Milliseconds until the client may retry.
+        retry_after_ms: u64,
+    },
+
+    /// Request signature missing, invalid, stale, or replayed.
+    #[error("unauthorized: {0}")]
+    Unauthorized(String),
+
+    /// Caller is identified but may not perform the operation.
+    #[error("forbidden: {0}")]
+    Forbidden(String),
+
     /// Unsupported or invalid pool type string.
     #[error("invalid pool type: {0}")]
     InvalidPoolType(String),
@@ -101,30 +500,106 @@ impl GatewayError {
         match self {
             Self::InvalidRequest(_) => 1001,
             Self::InvalidPoolType(_) => 1002,
+            Self::InvalidMetadata(_) => 1004,
+            Self::IdempotencyKeyReused(_) => 1005,
             Self::PoolNotFound(_) => 2001,
             Self::PositionNotFound(_) => 2002,
+            Self::PoolInUse(_) => 2003,
+            Self::VersionMismatch { .. } => 2004,
+            Self::JobNotFound(_) => 2005,
+            Self::WebhookNotFound(_) => 2006,
+            Self::PoolArchived(_) => 2007,
+            Self::MetadataSchemaNotFound(_) => 2008,
+            Self::OrderNotFound(_) => 2009,
+            Self::IdempotencyKeyInProgress(_) => 2010,
+            Self::SnapshotNotFound(_) => 2011,
+            Self::PoolPaused { .. } => 2012,
             Self::InsufficientLiquidity => 4001,
             Self::InsufficientBalance(_) => 4002,
+            Self::SlippageExceeded { .. } => 4003,
+            Self::CapacityExceeded(_) => 4004,
+            Self::PoolQuotaExceeded(_) => 4005,
+            Self::DeadlineExpired { .. } => 4006,
+            Self::OraclePriceRejected(_) => 4007,
             Self::AmmError(_) => 1003,
             Self::PersistenceError(_) => 3001,
+            Self::PersistenceUnavailable => 3002,
+            Self::ReadOnlyReplica => 3003,
+            Self::Overloaded { .. } => 3004,
+            Self::LockTimeout { .. } => 3005,
+            Self::OwnerUnreachable(_) => 3006,
+            Self::EventLogBacklog { .. } => 3007,
             Self::RateLimited { .. } => 429,
+            Self::Unauthorized(_) => 401,
+            Self::Forbidden(_) => 403,
             Self::Internal(_) => 3000,
         }
     }
 
+    /// Returns this error's entry in [`ERROR_CATALOG`].
+    #[must_use]
+    pub fn catalog_entry(&self) -> Option<&'static ErrorCatalogEntry> {
+        let code = self.error_code();
+        ERROR_CATALOG.iter().find(|entry| entry.code == code)
+    }
+
+    /// Returns `true` if the same request may succeed if retried later.
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        self.catalog_entry().is_some_and(|entry| entry.retryable)
+    }
+
+    /// Returns how long the caller should wait before retrying, for
+    /// errors that know it (rate limits, saturated endpoints, busy pools,
+    /// and a lagging event log).
+    #[must_use]
+    pub const fn retry_after_ms(&self) -> Option<u64> {
+        match self {
+            Self::RateLimited { retry_after_ms }
+            | Self::Overloaded { retry_after_ms }
+            | Self::LockTimeout { retry_after_ms, .. }
+            | Self::EventLogBacklog { retry_after_ms } => Some(*retry_after_ms),
+            _ => None,
+        }
+    }
+
     /// Returns the HTTP status code for this variant.
     #[must_use]
     pub const fn status_code(&self) -> StatusCode {
         match self {
-            Self::InvalidRequest(_) | Self::InvalidPoolType(_) | Self::AmmError(_) => {
-                StatusCode::BAD_REQUEST
-            }
-            Self::PoolNotFound(_) | Self::PositionNotFound(_) => StatusCode::NOT_FOUND,
-            Self::InsufficientLiquidity | Self::InsufficientBalance(_) => {
-                StatusCode::UNPROCESSABLE_ENTITY
-            }
+            Self::InvalidRequest(_)
+            | Self::InvalidPoolType(_)
+            | Self::InvalidMetadata(_)
+            | Self::IdempotencyKeyReused(_)
+            | Self::AmmError(_) => StatusCode::BAD_REQUEST,
+            Self::PoolNotFound(_)
+            | Self::PositionNotFound(_)
+            | Self::OrderNotFound(_)
+            | Self::JobNotFound(_)
+            | Self::WebhookNotFound(_)
+            | Self::MetadataSchemaNotFound(_)
+            | Self::SnapshotNotFound(_) => StatusCode::NOT_FOUND,
+            Self::PoolInUse(_)
+            | Self::VersionMismatch { .. }
+            | Self::PoolArchived(_)
+            | Self::PoolPaused { .. }
+            | Self::IdempotencyKeyInProgress(_) => StatusCode::CONFLICT,
+            Self::InsufficientLiquidity
+            | Self::InsufficientBalance(_)
+            | Self::SlippageExceeded { .. }
+            | Self::DeadlineExpired { .. }
+            | Self::OraclePriceRejected(_)
+            | Self::CapacityExceeded(_) => StatusCode::UNPROCESSABLE_ENTITY,
             Self::PersistenceError(_) | Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
-            Self::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            Self::PersistenceUnavailable
+            | Self::ReadOnlyReplica
+            | Self::Overloaded { .. }
+            | Self::LockTimeout { .. }
+            | Self::EventLogBacklog { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            Self::OwnerUnreachable(_) => StatusCode::BAD_GATEWAY,
+            Self::RateLimited { .. } | Self::PoolQuotaExceeded(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
         }
     }
 }
@@ -132,11 +607,18 @@ impl GatewayError {
 impl IntoResponse for GatewayError {
     fn into_response(self) -> Response {
         let status = self.status_code();
+        let fields = match &self {
+            Self::InvalidMetadata(fields) => Some(fields.clone()),
+            _ => None,
+        };
         let body = ErrorResponse {
             error: ErrorBody {
                 code: self.error_code(),
                 message: self.to_string(),
                 details: None,
+                fields,
+                retryable: self.is_retryable(),
+                retry_after_ms: self.retry_after_ms(),
             },
         };
         let mut response = axum::Json(body).into_response();
@@ -144,3 +626,122 @@ impl IntoResponse for GatewayError {
         response
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalog_matches_every_variant() {
+        let id = uuid::Uuid::nil();
+        let text = || String::new();
+        let samples = [
+            GatewayError::PoolNotFound(id),
+            GatewayError::PoolInUse(text()),
+            GatewayError::VersionMismatch {
+                expected: 1,
+                actual: 2,
+            },
+            GatewayError::InvalidRequest(text()),
+            GatewayError::InvalidMetadata(Vec::new()),
+            GatewayError::IdempotencyKeyReused(text()),
+            GatewayError::IdempotencyKeyInProgress(text()),
+            GatewayError::InsufficientLiquidity,
+            GatewayError::InsufficientBalance(text()),
+            GatewayError::SlippageExceeded {
+                bound: "min_amount_out",
+                limit: text(),
+                quoted: text(),
+            },
+            GatewayError::DeadlineExpired {
+                deadline: DateTime::UNIX_EPOCH,
+            },
+            GatewayError::CapacityExceeded(text()),
+            GatewayError::OraclePriceRejected(text()),
+            GatewayError::PoolQuotaExceeded(text()),
+            GatewayError::PoolArchived(id),
+            GatewayError::PoolPaused {
+                pool_id: id,
+                status: "paused",
+            },
+            GatewayError::PositionNotFound(id),
+            GatewayError::JobNotFound(id),
+            GatewayError::WebhookNotFound(id),
+            GatewayError::MetadataSchemaNotFound(text()),
+            GatewayError::OrderNotFound(id),
+            GatewayError::SnapshotNotFound(1),
+            GatewayError::PersistenceError(text()),
+            GatewayError::PersistenceUnavailable,
+            GatewayError::ReadOnlyReplica,
+            GatewayError::RateLimited { retry_after_ms: 1 },
+            GatewayError::Overloaded { retry_after_ms: 1 },
+            GatewayError::LockTimeout {
+                pool_id: id,
+                retry_after_ms: 1,
+            },
+            GatewayError::Unauthorized(text()),
+            GatewayError::Forbidden(text()),
+            GatewayError::InvalidPoolType(text()),
+            GatewayError::OwnerUnreachable(text()),
+            GatewayError::EventLogBacklog { retry_after_ms: 1 },
+            GatewayError::Internal(text()),
+        ];
+        for error in &samples {
+            let Some(entry) = error.catalog_entry() else {
+                panic!("{error:?} missing from the catalog");
+            };
+            assert_eq!(entry.status, error.status_code().as_u16(), "{error:?}");
+        }
+        // Every variant but AmmError (not constructible here) is sampled
+        assert_eq!(samples.len() + 1, ERROR_CATALOG.len());
+        assert!(ERROR_CATALOG.windows(2).all(|w| match w {
+            [a, b] => a.code < b.code,
+            _ => true,
+        }));
+        assert!(GatewayError::PersistenceError(text()).is_retryable());
+        assert!(!GatewayError::PersistenceUnavailable.is_retryable());
+        assert!(!GatewayError::PoolNotFound(id).is_retryable());
+    }
+
+    async fn error_body(error: GatewayError) -> serde_json::Value {
+        let Ok(bytes) = axum::body::to_bytes(error.into_response().into_body(), usize::MAX).await
+        else {
+            panic!("error body unreadable");
+        };
+        let Ok(body) = serde_json::from_slice::<serde_json::Value>(&bytes) else {
+            panic!("error body is not JSON");
+        };
+        body
+    }
+
+    #[tokio::test]
+    async fn error_responses_report_retryability_and_delay() {
+        let body = error_body(GatewayError::RateLimited {
+            retry_after_ms: 250,
+        })
+        .await;
+        assert_eq!(
+            body.pointer("/error/retryable"),
+            Some(&serde_json::json!(true))
+        );
+        assert_eq!(
+            body.pointer("/error/retry_after_ms"),
+            Some(&serde_json::json!(250))
+        );
+
+        let body = error_body(GatewayError::PersistenceError(String::new())).await;
+        assert_eq!(
+            body.pointer("/error/retryable"),
+            Some(&serde_json::json!(true))
+        );
+        assert!(body.pointer("/error/retry_after_ms").is_none());
+
+        let body = error_body(GatewayError::PoolNotFound(uuid::Uuid::nil())).await;
+        assert_eq!(
+            body.pointer("/error/retryable"),
+            Some(&serde_json::json!(false))
+        );
+        assert!(body.pointer("/error/retry_after_ms").is_none());
+    }
+}
@@ -25,9 +25,13 @@
 
 pub mod api;
 pub mod app_state;
+pub mod cluster;
 pub mod config;
 pub mod domain;
 pub mod error;
+pub mod loadtest;
 pub mod persistence;
+pub mod proto;
+pub mod server;
 pub mod service;
 pub mod ws;
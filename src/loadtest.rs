@@ -0,0 +1,486 @@
+//! Load generation for `hydra-gateway loadtest`.
+//!
+//! The subcommand runs the gateway in-process on a loopback port, without
+//! persistence, and drives it over real HTTP and WebSocket connections:
+//! it creates `--pools` constant-product pools, then `--concurrency`
+//! workers send swaps and quotes (`--quote-percent` of them quotes) for
+//! `--duration-secs`, while `--ws-clients` WebSocket clients subscribed
+//! to every pool receive the resulting events. The report gives the
+//! throughput and latency percentiles of each workload, and for
+//! WebSocket clients the lag between an event's timestamp and its
+//! arrival.
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::config::GatewayConfig;
+use crate::ws::messages::{WsMessage, WsMessageType};
+
+/// Error of a load test that could not run.
+pub type LoadTestError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Reserve of each token in a load-test pool.
+const POOL_RESERVE: &str = "1000000000000000";
+
+/// Input amount of each swap and quote.
+const TRADE_AMOUNT: &str = "1000000";
+
+/// Workload of a load test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadTestOptions {
+    /// Pools created before the workload starts.
+    pub pools: usize,
+    /// Concurrent HTTP workers.
+    pub concurrency: usize,
+    /// How long the workload runs.
+    pub duration: Duration,
+    /// Share of requests that are quotes rather than swaps, in percent.
+    pub quote_percent: u32,
+    /// WebSocket clients subscribed to every pool.
+    pub ws_clients: usize,
+}
+
+impl Default for LoadTestOptions {
+    fn default() -> Self {
+        Self {
+            pools: 10,
+            concurrency: 32,
+            duration: Duration::from_secs(30),
+            quote_percent: 50,
+            ws_clients: 4,
+        }
+    }
+}
+
+impl LoadTestOptions {
+    /// Parses `--pools`, `--concurrency`, `--duration-secs`,
+    /// `--quote-percent`, and `--ws-clients`, each followed by a number;
+    /// omitted options keep their defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message naming an unknown option, a missing or invalid
+    /// value, or a workload without pools or workers.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or_else(|| format!("{flag} needs a value"))?;
+            let number = |value: &str| -> Result<u64, String> {
+                value
+                    .parse()
+                    .map_err(|_| format!("{flag}: {value} is not a number"))
+            };
+            let count =
+                |value: &str| number(value).map(|n| usize::try_from(n).unwrap_or(usize::MAX));
+            match flag.as_str() {
+                "--pools" => options.pools = count(&value)?,
+                "--concurrency" => options.concurrency = count(&value)?,
+                "--duration-secs" => options.duration = Duration::from_secs(number(&value)?),
+                "--quote-percent" => {
+                    options.quote_percent = u32::try_from(number(&value)?)
+                        .ok()
+                        .filter(|p| *p <= 100)
+                        .ok_or_else(|| format!("{flag}: expected 0 to 100"))?;
+                }
+                "--ws-clients" => options.ws_clients = count(&value)?,
+                other => return Err(format!("unknown loadtest option {other}")),
+            }
+        }
+        if options.pools == 0 || options.concurrency == 0 {
+            return Err("--pools and --concurrency must be at least 1".to_string());
+        }
+        Ok(options)
+    }
+}
+
+/// Adapts `config` for a load test: in memory, on a loopback port, with
+/// nothing that would throttle or redirect the workload.
+#[must_use]
+pub fn gateway_config(mut config: GatewayConfig) -> GatewayConfig {
+    config.listen_addr = SocketAddr::from(([127, 0, 0, 1], 0));
+    config.reuse_port = false;
+    config.pid_file = None;
+    config.persistence_enabled = false;
+    config.replica_mode = false;
+    config.cluster_peers.clear();
+    config.request_signing_keys.clear();
+    config.route_rate_limits.clear();
+    config.ws_session_handover_file = None;
+    config.ws_drain_notice_secs = 0;
+    config
+}
+
+/// Latency distribution of one workload.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencyStats {
+    /// Successful operations.
+    pub count: usize,
+    /// Failed operations, not included in the percentiles.
+    pub errors: u64,
+    /// Median latency.
+    pub p50: Duration,
+    /// 90th percentile latency.
+    pub p90: Duration,
+    /// 99th percentile latency.
+    pub p99: Duration,
+    /// Slowest operation.
+    pub max: Duration,
+}
+
+impl LatencyStats {
+    /// Summarizes `samples` by nearest-rank percentiles.
+    #[must_use]
+    pub fn from_samples(mut samples: Vec<Duration>, errors: u64) -> Self {
+        samples.sort_unstable();
+        let percentile = |p: f64| {
+            #[allow(
+                clippy::cast_possible_truncation,
+                clippy::cast_sign_loss,
+                clippy::cast_precision_loss
+            )]
+            let rank = (p * samples.len() as f64).ceil() as usize;
+            samples
+                .get(rank.saturating_sub(1))
+                .copied()
+                .unwrap_or_default()
+        };
+        Self {
+            count: samples.len(),
+            errors,
+            p50: percentile(0.50),
+            p90: percentile(0.90),
+            p99: percentile(0.99),
+            max: samples.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// Results of a load test.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadReport {
+    /// Workload that was run.
+    pub options: LoadTestOptions,
+    /// How long the workload actually ran.
+    pub elapsed: Duration,
+    /// `POST /pools/{id}/swap` round trips.
+    pub swaps: LatencyStats,
+    /// `POST /pools/{id}/quote` round trips.
+    pub quotes: LatencyStats,
+    /// Event timestamp to arrival at a WebSocket client.
+    pub ws_events: LatencyStats,
+}
+
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.elapsed.as_secs_f64().max(f64::EPSILON);
+        let ms = |d: Duration| d.as_secs_f64() * 1_000.0;
+        writeln!(
+            f,
+            "{} pools, {} workers, {} ws clients, {:.1}s",
+            self.options.pools, self.options.concurrency, self.options.ws_clients, secs
+        )?;
+        writeln!(
+            f,
+            "{:<10} {:>9} {:>7} {:>10} {:>9} {:>9} {:>9} {:>9}",
+            "workload", "count", "errors", "per sec", "p50 ms", "p90 ms", "p99 ms", "max ms"
+        )?;
+        for (name, stats) in [
+            ("swap", self.swaps),
+            ("quote", self.quotes),
+            ("ws event", self.ws_events),
+        ] {
+            #[allow(clippy::cast_precision_loss)]
+            let rate = stats.count as f64 / secs;
+            writeln!(
+                f,
+                "{name:<10} {:>9} {:>7} {rate:>10.1} {:>9.2} {:>9.2} {:>9.2} {:>9.2}",
+                stats.count,
+                stats.errors,
+                ms(stats.p50),
+                ms(stats.p90),
+                ms(stats.p99),
+                ms(stats.max)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A pool created for the workload.
+#[derive(Debug)]
+struct LoadPool {
+    id: String,
+    tokens: [String; 2],
+}
+
+/// Latencies collected by one worker.
+#[derive(Debug, Default)]
+struct WorkerSamples {
+    swaps: Vec<Duration>,
+    swap_errors: u64,
+    quotes: Vec<Duration>,
+    quote_errors: u64,
+}
+
+/// Runs `options` against the gateway listening on `addr`.
+///
+/// # Errors
+///
+/// Returns the error if the pools cannot be created or a WebSocket client
+/// cannot subscribe. Failed swaps and quotes are counted, not returned.
+pub async fn run(addr: SocketAddr, options: LoadTestOptions) -> Result<LoadReport, LoadTestError> {
+    let base = format!("http://{addr}/api/v1");
+    let client = reqwest::Client::new();
+    let mut pools = Vec::with_capacity(options.pools);
+    for n in 0..options.pools {
+        pools.push(create_pool(&client, &base, n).await?);
+    }
+    let pools = Arc::new(pools);
+
+    let mut subscribers = Vec::with_capacity(options.ws_clients);
+    for _ in 0..options.ws_clients {
+        subscribers.push(subscribe(addr).await?);
+    }
+
+    let started = Instant::now();
+    let deadline = started + options.duration;
+    let listeners: Vec<_> = subscribers
+        .into_iter()
+        .map(|socket| tokio::spawn(listen(socket, deadline)))
+        .collect();
+    let workers: Vec<_> = (0..options.concurrency)
+        .map(|worker| {
+            tokio::spawn(drive(
+                client.clone(),
+                base.clone(),
+                Arc::clone(&pools),
+                worker,
+                options.quote_percent,
+                deadline,
+            ))
+        })
+        .collect();
+
+    let mut samples = WorkerSamples::default();
+    for worker in workers {
+        let done = worker.await?;
+        samples.swaps.extend(done.swaps);
+        samples.quotes.extend(done.quotes);
+        samples.swap_errors += done.swap_errors;
+        samples.quote_errors += done.quote_errors;
+    }
+    let elapsed = started.elapsed();
+    let mut lags = Vec::new();
+    for listener in listeners {
+        lags.extend(listener.await?);
+    }
+
+    Ok(LoadReport {
+        options,
+        elapsed,
+        swaps: LatencyStats::from_samples(samples.swaps, samples.swap_errors),
+        quotes: LatencyStats::from_samples(samples.quotes, samples.quote_errors),
+        ws_events: LatencyStats::from_samples(lags, 0),
+    })
+}
+
+/// Creates the `n`th load-test pool.
+async fn create_pool(
+    client: &reqwest::Client,
+    base: &str,
+    n: usize,
+) -> Result<LoadPool, LoadTestError> {
+    let tokens = [format!("load{n}a"), format!("load{n}b")];
+    let [token_a, token_b] = &tokens;
+    let response = client
+        .post(format!("{base}/pools"))
+        .json(&serde_json::json!({
+            "pool_type": "constant_product",
+            "name": format!("loadtest-{n}"),
+            "config": {
+                "token_a": {"address": token_a, "decimals": 6},
+                "token_b": {"address": token_b, "decimals": 6},
+                "fee_bps": 30,
+                "reserve_a": POOL_RESERVE,
+                "reserve_b": POOL_RESERVE,
+            },
+        }))
+        .send()
+        .await?
+        .error_for_status()?;
+    let body: serde_json::Value = response.json().await?;
+    let id = body
+        .get("pool_id")
+        .and_then(|v| v.as_str())
+        .ok_or("pool creation returned no pool_id")?
+        .to_string();
+    Ok(LoadPool { id, tokens })
+}
+
+/// Sends swaps and quotes until `deadline`, spreading them over the pools
+/// and alternating their direction.
+async fn drive(
+    client: reqwest::Client,
+    base: String,
+    pools: Arc<Vec<LoadPool>>,
+    worker: usize,
+    quote_percent: u32,
+    deadline: Instant,
+) -> WorkerSamples {
+    let mut samples = WorkerSamples::default();
+    let mut n: usize = 0;
+    while Instant::now() < deadline {
+        let Some(pool) = pools.get(worker.wrapping_add(n) % pools.len().max(1)) else {
+            break;
+        };
+        let quote = u32::try_from(n % 100).unwrap_or(0) < quote_percent;
+        let [token_a, token_b] = &pool.tokens;
+        let (token_in, token_out) = if n.is_multiple_of(2) {
+            (token_a, token_b)
+        } else {
+            (token_b, token_a)
+        };
+        let action = if quote { "quote" } else { "swap" };
+        let sent = Instant::now();
+        let result = client
+            .post(format!("{base}/pools/{}/{action}", pool.id))
+            .json(&serde_json::json!({
+                "token_in": token_in,
+                "token_out": token_out,
+                "amount_in": TRADE_AMOUNT,
+            }))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status);
+        let ok = match result {
+            Ok(response) => response.bytes().await.is_ok(),
+            Err(_) => false,
+        };
+        let latency = sent.elapsed();
+        match (quote, ok) {
+            (true, true) => samples.quotes.push(latency),
+            (true, false) => samples.quote_errors += 1,
+            (false, true) => samples.swaps.push(latency),
+            (false, false) => samples.swap_errors += 1,
+        }
+        n = n.wrapping_add(1);
+    }
+    samples
+}
+
+type WsClient =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Opens a WebSocket connection subscribed to every pool.
+async fn subscribe(addr: SocketAddr) -> Result<WsClient, LoadTestError> {
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/ws")).await?;
+    let command = WsMessage {
+        id: "loadtest-subscribe".to_string(),
+        msg_type: WsMessageType::Command,
+        timestamp: Utc::now(),
+        seq: None,
+        payload: serde_json::json!({"command": "subscribe", "pool_ids": ["*"]}),
+    };
+    socket
+        .send(Message::text(serde_json::to_string(&command)?))
+        .await?;
+    // Wait for the confirmation so no event is missed
+    while let Some(frame) = socket.next().await {
+        if let Message::Text(text) = frame?
+            && serde_json::from_str::<WsMessage>(&text)
+                .is_ok_and(|msg| msg.msg_type == WsMessageType::Response)
+        {
+            return Ok(socket);
+        }
+    }
+    Err("WebSocket closed before the subscription was confirmed".into())
+}
+
+/// Receives events until `deadline`, returning the lag of each.
+async fn listen(mut socket: WsClient, deadline: Instant) -> Vec<Duration> {
+    let mut lags = Vec::new();
+    loop {
+        let frame = tokio::select! {
+            () = tokio::time::sleep_until(deadline) => break,
+            frame = socket.next() => frame,
+        };
+        let Some(Ok(frame)) = frame else {
+            break;
+        };
+        let Message::Text(text) = frame else {
+            continue;
+        };
+        let Ok(msg) = serde_json::from_str::<WsMessage>(&text) else {
+            continue;
+        };
+        let emitted = msg
+            .payload
+            .get("timestamp")
+            .and_then(|v| serde_json::from_value::<DateTime<Utc>>(v.clone()).ok());
+        if msg.msg_type == WsMessageType::Event
+            && let Some(lag) = emitted.and_then(|at| (Utc::now() - at).to_std().ok())
+        {
+            lags.push(lag);
+        }
+    }
+    let _ = socket.close(None).await;
+    lags
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn options_parse_and_latencies_summarize() {
+        let args = [
+            "--pools",
+            "3",
+            "--quote-percent",
+            "80",
+            "--duration-secs",
+            "5",
+        ];
+        let Ok(options) = LoadTestOptions::parse(args.map(str::to_string)) else {
+            panic!("options not parsed");
+        };
+        assert_eq!(
+            options,
+            LoadTestOptions {
+                pools: 3,
+                quote_percent: 80,
+                duration: Duration::from_secs(5),
+                ..LoadTestOptions::default()
+            }
+        );
+        assert!(LoadTestOptions::parse(["--quote-percent", "101"].map(str::to_string)).is_err());
+        assert!(LoadTestOptions::parse(["--pools"].map(str::to_string)).is_err());
+        assert!(LoadTestOptions::parse(["--threads", "2"].map(str::to_string)).is_err());
+
+        let samples = (1..=100).rev().map(Duration::from_millis).collect();
+        let stats = LatencyStats::from_samples(samples, 2);
+        assert_eq!(
+            (stats.count, stats.errors, stats.p50, stats.p99, stats.max),
+            (
+                100,
+                2,
+                Duration::from_millis(50),
+                Duration::from_millis(99),
+                Duration::from_millis(100)
+            )
+        );
+        assert_eq!(
+            LatencyStats::from_samples(Vec::new(), 0),
+            LatencyStats::default()
+        );
+    }
+}
@@ -2,7 +2,9 @@
 //!
 //! Starts the Axum HTTP server with REST and WebSocket endpoints.
 
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::Router;
 use axum::routing::get;
@@ -17,11 +19,49 @@ use utoipa_swagger_ui::SwaggerUi;
 use hydra_gateway::api;
 #[cfg(feature = "swagger-ui")]
 use hydra_gateway::api::ApiDoc;
+use hydra_gateway::api::signing::SignatureVerifier;
 use hydra_gateway::app_state::AppState;
+use hydra_gateway::cluster::{self, ClusterMembership, PeerReads};
 use hydra_gateway::config::GatewayConfig;
 use hydra_gateway::domain::{EventBus, PoolRegistry};
+use hydra_gateway::loadtest::{self, LoadTestOptions};
+use hydra_gateway::persistence::postgres::PostgresPersistence;
+use hydra_gateway::server;
 use hydra_gateway::service::PoolService;
+use hydra_gateway::service::attestation::EventSigner;
+use hydra_gateway::service::candles::{self, CandleBook, Timeframe};
+use hydra_gateway::service::concurrency::ConcurrencyLimiter;
+use hydra_gateway::service::event_log::{self, EventLogOptions};
+use hydra_gateway::service::event_tail::EventTail;
+use hydra_gateway::service::fee_program::FeeProgram;
+use hydra_gateway::service::idempotency::IdempotencyStore;
+use hydra_gateway::service::jobs::JobRegistry;
+use hydra_gateway::service::metadata_schema::MetadataSchemas;
+use hydra_gateway::service::ownership;
+use hydra_gateway::service::pool_defaults::PoolDefaults;
+use hydra_gateway::service::pool_service::CapacityLimits;
+use hydra_gateway::service::pool_stats::{self, PoolStatsBook};
+use hydra_gateway::service::quota::QuotaRegistry;
+use hydra_gateway::service::quote_audit::{QuoteAudit, QuoteSampler};
+use hydra_gateway::service::quote_runtime::QuoteRuntime;
+use hydra_gateway::service::replay::{self, StartupReport};
+use hydra_gateway::service::replica::ReplicaFollower;
+use hydra_gateway::service::rewards::RewardsTracker;
+use hydra_gateway::service::route_limits::RouteRateLimiter;
+use hydra_gateway::service::routing::PerHopCost;
+use hydra_gateway::service::snapshot_policy::SnapshotPolicy;
+use hydra_gateway::service::snapshot_scheduler::SnapshotScheduler;
+use hydra_gateway::service::stale_pools::StalePoolPolicy;
+use hydra_gateway::service::trade_tape::TradeTape;
+use hydra_gateway::service::usage::UsageLedger;
+use hydra_gateway::service::webhooks::WebhookRegistry;
+use hydra_gateway::ws::drain::DrainAnnouncer;
 use hydra_gateway::ws::handler::ws_handler;
+use hydra_gateway::ws::heartbeat::Heartbeat;
+use hydra_gateway::ws::limits::FrameLimits;
+use hydra_gateway::ws::outbound::{ConnectionRegistry, OutboundOptions, OverflowPolicy};
+use hydra_gateway::ws::raw::RawEventFeed;
+use hydra_gateway::ws::session::SessionRegistry;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -34,6 +74,54 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Load configuration
     let config = GatewayConfig::from_env()?;
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        None => run(config, None, server::shutdown_signal()).await,
+        Some("loadtest") => load_test(config, LoadTestOptions::parse(args)?).await,
+        Some(other) => {
+            Err(format!("unknown command {other}; expected no command or loadtest").into())
+        }
+    }
+}
+
+/// Runs the gateway in memory on a loopback port, drives the load test
+/// against it, and prints the report.
+async fn load_test(
+    config: GatewayConfig,
+    options: LoadTestOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (listening_tx, listening_rx) = tokio::sync::oneshot::channel();
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    let gateway = run(
+        loadtest::gateway_config(config),
+        Some(listening_tx),
+        async move {
+            let _ = stop_rx.await;
+        },
+    );
+    let driver = async move {
+        let report = match listening_rx.await {
+            Ok(addr) => loadtest::run(addr, options)
+                .await
+                .map_err(|e| e as Box<dyn std::error::Error>),
+            Err(_) => Err("gateway did not start".into()),
+        };
+        let _ = stop_tx.send(());
+        report
+    };
+    let (served, report) = tokio::join!(gateway, driver);
+    served?;
+    println!("{}", report?);
+    Ok(())
+}
+
+/// Runs the gateway until `shutdown` resolves, reporting the bound
+/// address on `listening` once it accepts connections.
+async fn run(
+    config: GatewayConfig,
+    listening: Option<tokio::sync::oneshot::Sender<SocketAddr>>,
+    shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+) -> Result<(), Box<dyn std::error::Error>> {
     tracing::info!(addr = %config.listen_addr, "starting hydra-gateway");
 
     // Build domain layer
@@ -41,14 +129,494 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let event_bus = EventBus::new(config.event_bus_capacity);
 
     // Build service layer
-    let pool_service = Arc::new(PoolService::new(registry, event_bus.clone()));
+    let mut pool_service =
+        PoolService::new(registry, event_bus.clone()).with_capacity_limits(CapacityLimits {
+            max_pools: (config.max_pools > 0).then_some(config.max_pools),
+            max_positions_per_pool: (config.max_positions_per_pool > 0)
+                .then_some(config.max_positions_per_pool),
+        });
+    if config.pool_lock_timeout_ms > 0 {
+        pool_service =
+            pool_service.with_lock_timeout(Duration::from_millis(config.pool_lock_timeout_ms));
+    }
+    if let Some(seed) = config.event_signing_key.as_deref() {
+        let signer = EventSigner::from_hex_seed(&config.event_signing_key_id, seed)?;
+        tracing::info!(
+            key_id = signer.key_id(),
+            public_key = %signer.public_key_hex(),
+            "event attestations enabled"
+        );
+        pool_service = pool_service.with_event_signer(signer);
+    }
+    if config.replica_mode {
+        pool_service = pool_service.with_read_only();
+    }
+    let fee_program = Some(FeeProgram::parse(&config.fee_tiers))
+        .filter(|program| !program.is_disabled())
+        .map(Arc::new);
+    if let Some(program) = fee_program.as_ref() {
+        tracing::info!(tiers = program.tiers().len(), "swap fee tiers enabled");
+        pool_service = pool_service.with_fee_program(Arc::clone(program));
+    }
+    let admins = ownership::parse_admins(&config.admin_client_ids);
+    if !admins.is_empty() {
+        tracing::info!(admins = admins.len(), "pool admin clients configured");
+        pool_service = pool_service.with_admins(admins);
+    }
+
+    // Persistence (optional: the gateway keeps serving from memory when
+    // the database is unreachable)
+    let persistence = if config.persistence_enabled {
+        match PostgresPersistence::connect(&config).await {
+            Ok(p) => {
+                tracing::info!("persistence connected");
+                Some(p)
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "persistence unavailable, continuing without it");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Hand emitted events to the event log writer, started below
+    let mut event_log_queue = None;
+    if persistence.is_some() && config.event_log_enabled && !config.replica_mode {
+        let (sink, queue) = event_log::channel(EventLogOptions {
+            batch_size: config.event_log_batch_size,
+            buffer: config.event_log_buffer,
+        });
+        pool_service = pool_service.with_event_log(sink);
+        event_log_queue = Some(queue);
+    }
+    let pool_service = Arc::new(pool_service);
+
+    // Trade tape, fed from the event bus
+    let trade_tape = TradeTape::new();
+    trade_tape.spawn(Arc::clone(&pool_service));
+
+    // Event tail for `GET /admin/events/tail`
+    let event_tail = EventTail::new();
+    event_tail.spawn(&event_bus);
+
+    // Cluster membership (disabled when no peers are configured)
+    let cluster = if config.cluster_peers.is_empty() {
+        None
+    } else {
+        let Some(secret) = config.cluster_secret.as_deref() else {
+            return Err("CLUSTER_PEERS requires CLUSTER_SECRET".into());
+        };
+        let peers = ClusterMembership::parse_peers(&config.cluster_peers);
+        let membership =
+            ClusterMembership::new(config.cluster_instance_id.clone(), peers).with_secret(secret);
+        tracing::info!(
+            instance = membership.local_id(),
+            peers = membership.peers().len(),
+            "cluster mode enabled"
+        );
+        Some(Arc::new(membership))
+    };
+
+    // Events WebSocket clients see: this instance's, plus those of the
+    // copies of peer pools followed below. Instance-local books keep to
+    // the instance's own bus.
+    let follows_peers = cluster.is_some() && persistence.is_some() && !config.replica_mode;
+    let ws_event_bus = if follows_peers {
+        let ws_event_bus = EventBus::new(config.event_bus_capacity);
+        event_bus.relay(&ws_event_bus);
+        ws_event_bus
+    } else {
+        event_bus.clone()
+    };
+
+    // Shared serialization for WebSocket clients using the raw encoding
+    let ws_raw_feed = RawEventFeed::new(config.event_bus_capacity);
+    ws_raw_feed.spawn(&ws_event_bus);
+
+    // Take over from the previous process, which drains once the PID file
+    // names us
+    let ws_sessions = SessionRegistry::new(Duration::from_secs(config.ws_session_ttl_secs));
+    let mut owned_pid_file = None;
+    let mut previous_process = None;
+    let mut process_lock = None;
+    if let Some(pid_file) = config.pid_file.as_deref() {
+        let path = std::path::PathBuf::from(pid_file);
+        match server::ProcessLock::open(&path) {
+            Ok(lock) => process_lock = Some(Arc::new(lock)),
+            Err(e) => tracing::warn!(pid_file, error = %e, "failed to open process lock file"),
+        }
+        match server::take_over_pid_file(&path) {
+            Ok(previous) => {
+                if let Some(previous) = previous {
+                    if let Some(handover) = config.ws_session_handover_file.as_deref() {
+                        ws_sessions.expect_handover(
+                            std::path::PathBuf::from(handover),
+                            Duration::from_secs(config.shutdown_grace_secs),
+                        );
+                    }
+                    tracing::info!(previous, "taking over from previous process");
+                    previous_process = Some(previous);
+                }
+                owned_pid_file = Some(path);
+            }
+            Err(e) => tracing::warn!(pid_file, error = %e, "failed to write pid file"),
+        }
+    }
+
+    // Rebuild the pools this instance had before it restarted, after the
+    // previous process has written its final snapshots
+    let mut startup_report = StartupReport::skipped(chrono::Utc::now());
+    if let Some(db) = persistence.as_ref()
+        && config.recover_on_startup
+        && !config.replica_mode
+    {
+        if let (Some(pid_file), Some(previous)) = (owned_pid_file.as_deref(), previous_process) {
+            let timeout =
+                Duration::from_secs(config.ws_drain_notice_secs + config.shutdown_grace_secs)
+                    + server::SNAPSHOT_MARGIN;
+            if !server::wait_drained(pid_file, previous, process_lock.as_deref(), timeout).await {
+                tracing::warn!(
+                    previous,
+                    "previous process did not finish draining; restoring what it persisted so far"
+                );
+            }
+        }
+        startup_report = replay::recover(db, pool_service.registry()).await?;
+        startup_report.log();
+        // Peer pools are followed as read-only copies instead
+        if let Some(cluster) = cluster.as_deref() {
+            pool_service
+                .registry()
+                .retain(|pool_id| cluster.is_local(pool_id))
+                .await;
+        }
+    }
+
+    // Hold the process lock once the previous process has exited, so the
+    // next one can tell when this one has
+    if let Some(lock) = process_lock.clone() {
+        tokio::spawn(async move { lock.acquire().await });
+    }
+
+    // A replica follows the primary's event log instead of writing its own
+    if config.replica_mode {
+        let Some(db) = persistence.clone() else {
+            return Err("REPLICA_MODE requires a reachable database".into());
+        };
+        let mut follower = ReplicaFollower::new(
+            Arc::clone(&pool_service),
+            db,
+            Duration::from_millis(config.replica_poll_interval_ms),
+        );
+        startup_report = follower.bootstrap().await?;
+        startup_report.log();
+        follower.spawn();
+        tracing::info!("read-only replica mode enabled");
+    }
+
+    // Append emitted events to the event log
+    if let (Some(db), Some(queue)) = (persistence.clone(), event_log_queue) {
+        event_log::spawn(Arc::clone(&pool_service), db, queue);
+    }
+
+    // Pre-aggregate swaps into candles
+    if let Some(db) = persistence.clone()
+        && config.candles_enabled
+        && !config.replica_mode
+    {
+        candles::spawn(
+            &trade_tape,
+            &event_bus,
+            db,
+            Duration::from_millis(config.candle_flush_interval_ms.max(1)),
+        );
+    }
+
+    // Recent candles in memory, hydrated from the database
+    let now = chrono::Utc::now();
+    let candle_book = CandleBook::new(config.candle_memory_buckets, now);
+    if let Some(db) = persistence
+        .as_ref()
+        .filter(|_| config.candle_memory_buckets > 0)
+    {
+        for timeframe in Timeframe::ALL {
+            let since = candle_book.hydration_start(timeframe, now);
+            match db.load_candles_since(timeframe.as_str(), since).await {
+                Ok(rows) => candle_book.hydrate(timeframe, since, rows),
+                Err(e) => tracing::warn!(
+                    timeframe = timeframe.as_str(),
+                    error = %e,
+                    "candle hydration failed; older candles are read from the database"
+                ),
+            }
+        }
+    }
+    candle_book.spawn(&trade_tape, &event_bus);
+
+    // Rolling pool statistics, backfilled from the last week of swaps
+    let pool_stats = PoolStatsBook::new();
+    if let Some(db) = persistence.as_ref().filter(|_| config.pool_stats_backfill) {
+        let since = now - chrono::Duration::days(pool_stats::WINDOW_DAYS);
+        match db.load_events_of_type("swap_executed", since).await {
+            Ok(events) => {
+                let swaps = pool_stats.backfill(pool_service.registry(), &events).await;
+                tracing::info!(swaps, "pool stats backfilled");
+            }
+            Err(e) => tracing::warn!(error = %e, "pool stats backfill failed"),
+        }
+        match db.load_checkpoints(None).await {
+            Ok(checkpoints) => {
+                let swaps = pool_stats
+                    .backfill_checkpoints(pool_service.registry(), &checkpoints, since)
+                    .await;
+                tracing::info!(swaps, "pool stats backfilled from compacted ranges");
+            }
+            Err(e) => tracing::warn!(error = %e, "pool stats checkpoint backfill failed"),
+        }
+    }
+    pool_stats.spawn(Arc::clone(&pool_service));
+
+    // Record served quotes for best-execution audits
+    let quote_audit = persistence
+        .clone()
+        .filter(|_| config.quote_audit_enabled)
+        .map(|db| {
+            let sampler =
+                QuoteSampler::new(config.quote_audit_sample_bps, &config.quote_audit_clients);
+            tracing::info!(
+                sample_bps = config.quote_audit_sample_bps,
+                "quote audit trail enabled"
+            );
+            QuoteAudit::spawn(db, sampler).0
+        });
+
+    // Snapshot every pool on a fixed interval
+    if let Some(db) = persistence.clone()
+        && !config.replica_mode
+        && SnapshotScheduler::new(Duration::from_secs(config.snapshot_interval_secs))
+            .spawn(Arc::clone(&pool_service), db)
+            .is_some()
+    {
+        tracing::info!(
+            interval_secs = config.snapshot_interval_secs,
+            "periodic snapshots enabled"
+        );
+    }
+
+    // Snapshot hot pools after every N mutations
+    let snapshot_policy = SnapshotPolicy::parse(&config.snapshot_every_mutations);
+    if let Some(db) = persistence.clone()
+        && !snapshot_policy.is_disabled()
+        && !config.replica_mode
+    {
+        snapshot_policy.spawn(Arc::clone(&pool_service), db);
+    }
+
+    // Archive pools left empty and idle, when enabled
+    let stale_pools = StalePoolPolicy {
+        idle_days: config.stale_pool_days,
+        auto_archive: config.stale_pool_auto_archive && !config.replica_mode,
+    };
+    if stale_pools.spawn(Arc::clone(&pool_service)).is_some() {
+        tracing::info!(
+            idle_days = stale_pools.idle_days,
+            "stale pool auto-archiving enabled"
+        );
+    }
+
+    // Seed fee-tier volumes from the last 30 days of swaps
+    if let (Some(program), Some(db)) = (fee_program.as_ref(), persistence.as_ref()) {
+        match db
+            .load_swap_volumes(FeeProgram::window_start(chrono::Utc::now()))
+            .await
+        {
+            Ok(volumes) => {
+                tracing::info!(accounts = program.seed(&volumes), "swap volumes loaded");
+            }
+            Err(e) => tracing::warn!(error = %e, "failed to load swap volumes"),
+        }
+    }
+
+    // Per-API-key quotas, restored from the database when available
+    let quotas = QuotaRegistry::new();
+    if let Some(db) = persistence.as_ref() {
+        match db.load_rate_limits().await {
+            Ok(records) => {
+                tracing::info!(keys = records.len(), "rate limits loaded");
+                quotas.load(&records);
+            }
+            Err(e) => tracing::warn!(error = %e, "failed to load rate limits"),
+        }
+    }
+    quotas.track_pool_removals(pool_service.event_bus());
+
+    // Per-API-key usage, counted per instance
+    let usage = UsageLedger::new(config.usage_retention_days);
+    usage.track_swaps(pool_service.event_bus());
+
+    // Per-route token buckets (disabled when no limits are configured)
+    let route_limits = RouteRateLimiter::parse(&config.route_rate_limits);
+    if !route_limits.is_disabled() {
+        tracing::info!(spec = %config.route_rate_limits, "route rate limits enabled");
+    }
+
+    // Responses to Idempotency-Key requests, also kept in the database when enabled
+    let idempotency = IdempotencyStore::new(
+        config.idempotency_cache_size,
+        Duration::from_secs(config.idempotency_ttl_secs),
+        persistence.clone().filter(|_| config.idempotency_persist),
+    );
+    idempotency.spawn_expiry();
+
+    // Pool webhooks, restored from the database and delivered by the primary
+    let webhooks = WebhookRegistry::new();
+    if let Some(db) = persistence.as_ref() {
+        match db.load_webhooks().await {
+            Ok(records) => {
+                tracing::info!(webhooks = records.len(), "webhooks loaded");
+                webhooks.load(&records);
+            }
+            Err(e) => tracing::warn!(error = %e, "failed to load webhooks"),
+        }
+    }
+    if !config.replica_mode {
+        webhooks.spawn(Arc::clone(&pool_service), persistence.clone());
+    }
+
+    // Liquidity-mining rewards, seeded from the recovered positions
+    let rewards = RewardsTracker::new();
+    rewards.spawn(Arc::clone(&pool_service));
+
+    // Pool metadata schemas, restored from the database when available
+    let metadata_schemas = MetadataSchemas::new();
+    if let Some(db) = persistence.as_ref() {
+        match db.load_metadata_schemas().await {
+            Ok(records) => {
+                tracing::info!(schemas = records.len(), "metadata schemas loaded");
+                for skipped in metadata_schemas.load(&records) {
+                    tracing::warn!(schema = %skipped, "metadata schema skipped");
+                }
+            }
+            Err(e) => tracing::warn!(error = %e, "failed to load metadata schemas"),
+        }
+    }
+
+    // Signed requests (disabled when no signing keys are configured)
+    let signature_verifier = SignatureVerifier::from_spec(
+        &config.request_signing_keys,
+        config.request_signing_max_skew_secs,
+    )
+    .map(Arc::new);
+    if signature_verifier.is_some() {
+        tracing::info!("request signing enabled");
+    }
+
+    // Quote and simulation work, optionally isolated on its own runtime
+    let quote_runtime = QuoteRuntime::dedicated(config.quote_runtime_threads)?;
+    if quote_runtime.is_dedicated() {
+        tracing::info!(
+            threads = config.quote_runtime_threads,
+            "dedicated quote runtime started"
+        );
+    }
+
+    let ws_drain = DrainAnnouncer::new();
+    let ws_connections = ConnectionRegistry::new(OutboundOptions {
+        capacity: config.ws_outbound_queue,
+        policy: if config.ws_outbound_disconnect_on_overflow {
+            OverflowPolicy::Disconnect
+        } else {
+            OverflowPolicy::DropOldest
+        },
+    })
+    .with_usage(usage.clone());
 
     // Build application state
-    let app_state = AppState {
-        pool_service,
-        event_bus,
+    let mut app_state = AppState {
+        pool_service: Arc::clone(&pool_service),
+        event_bus: ws_event_bus,
+        cluster,
+        peer_reads: None,
+        persistence: persistence.clone(),
+        route_cost: Arc::new(PerHopCost {
+            per_hop: config.route_hop_cost,
+        }),
+        trade_tape,
+        candles: candle_book,
+        pool_stats,
+        event_tail,
+        quotas,
+        usage,
+        route_limits,
+        idempotency,
+        signature_verifier,
+        jobs: JobRegistry::new(),
+        heavy_limiter: ConcurrencyLimiter::new(
+            config.heavy_max_concurrent,
+            config.heavy_max_per_key,
+            Duration::from_millis(config.heavy_queue_timeout_ms),
+        ),
+        webhooks,
+        quote_runtime,
+        quote_audit,
+        ws_sessions: ws_sessions.clone(),
+        ws_limits: FrameLimits {
+            max_message_bytes: config.ws_max_message_bytes,
+            max_json_depth: config.ws_max_json_depth,
+        },
+        ws_heartbeat: Heartbeat::from_secs(
+            config.ws_ping_interval_secs,
+            config.ws_idle_timeout_secs,
+        ),
+        ws_connections: ws_connections.clone(),
+        ws_drain: ws_drain.clone(),
+        ws_raw_feed,
+        stale_pools,
+        pool_defaults: PoolDefaults {
+            fee_bps: config.pool_default_fee_bps,
+            tick_spacing: config.pool_default_tick_spacing,
+            amplification: config.pool_default_amplification,
+        },
+        metadata_schemas,
+        rewards,
+        startup_report: Arc::new(startup_report),
     };
 
+    // Copies of peer pools, serving reads of pools this instance does not own
+    if let (Some(cluster), Some(db)) = (app_state.cluster.as_ref(), persistence.clone())
+        && follows_peers
+    {
+        let copies = Arc::new(
+            PoolService::new(
+                Arc::new(PoolRegistry::new()),
+                EventBus::new(config.event_bus_capacity),
+            )
+            .with_read_only(),
+        );
+        copies.event_bus().relay(&app_state.event_bus);
+        let mut follower = ReplicaFollower::new(
+            Arc::clone(&copies),
+            db,
+            Duration::from_millis(config.replica_poll_interval_ms),
+        )
+        .peer_pools_only(Arc::clone(cluster));
+        let report = follower.bootstrap().await?;
+        tracing::info!(
+            pools = copies.registry().len().await,
+            last_event_id = report.last_event_id,
+            "following peer pools"
+        );
+        follower.spawn();
+        let peer_state = AppState {
+            pool_service: Arc::clone(&copies),
+            ..app_state.clone()
+        };
+        let router = handler_layers(api::build_router(), &peer_state).with_state(peer_state);
+        app_state.peer_reads = Some(PeerReads::new(copies, router));
+    }
+
     // Build router
     let app = Router::new()
         .merge(api::build_router())
@@ -58,16 +626,132 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let app =
         app.merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()));
 
-    let app = app
+    let app = handler_layers(app, &app_state)
+        .layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            cluster::proxy::forward_to_owner,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            Arc::clone(&app_state.pool_service),
+            api::admin_auth::require_admin,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            api::signing::verify_signature,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            api::rate_limit::enforce_request_quota,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            api::rate_limit::enforce_route_limits,
+        ))
+        .layer(axum::middleware::from_fn(api::server_timing::server_timing))
         .layer(TraceLayer::new_for_http())
         .layer(CorsLayer::permissive())
         .with_state(app_state);
 
-    // Start server
-    let listener = tokio::net::TcpListener::bind(config.listen_addr).await?;
-    tracing::info!(addr = %config.listen_addr, "server listening");
+    // Shut down on a signal, or once a replacement process takes over
+    let (shutdown_tx, mut shutdown_rx) = tokio::sync::watch::channel(false);
+    let superseded = owned_pid_file.clone();
+    tokio::spawn(async move {
+        let superseded = async {
+            match superseded {
+                Some(pid_file) => server::superseded(pid_file).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            () = shutdown => {}
+            () = superseded => {}
+        }
+        let _ = shutdown_tx.send(true);
+    });
 
-    axum::serve(listener, app).await?;
+    // Bind only once pools are restored; until then the previous process
+    // keeps the address
+    let listener = server::bind_listener(config.listen_addr, config.reuse_port)?;
+    let local_addr = listener.local_addr()?;
+    tracing::info!(addr = %local_addr, reuse_port = config.reuse_port, "server listening");
+    if let Some(listening) = listening {
+        let _ = listening.send(local_addr);
+    }
+    let (drain_tx, drain_rx) = tokio::sync::watch::channel(false);
+    let handover_file = config.ws_session_handover_file.clone();
+    let drain_notice = Duration::from_secs(config.ws_drain_notice_secs);
+    let drain_alternate_host = config.ws_drain_alternate_host.clone();
+    let serve = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(async move {
+        let _ = shutdown_rx.wait_for(|requested| *requested).await;
+        if !drain_notice.is_zero() {
+            let notice = ws_drain.announce(drain_notice, drain_alternate_host);
+            tracing::info!(deadline = %notice.deadline, "announced drain to ws clients");
+            tokio::time::sleep(drain_notice).await;
+        }
+        if let Some(path) = handover_file {
+            match ws_sessions.hand_over(std::path::Path::new(&path)).await {
+                Ok(count) => tracing::info!(sessions = count, "ws sessions handed over"),
+                Err(e) => tracing::warn!(error = %e, "failed to hand over ws sessions"),
+            }
+        } else {
+            ws_sessions.close_all();
+        }
+        let _ = drain_tx.send(true);
+    });
 
+    tokio::select! {
+        result = serve.into_future() => result?,
+        () = server::drain_deadline(drain_rx, Duration::from_secs(config.shutdown_grace_secs)) => {
+            tracing::warn!(
+                grace_secs = config.shutdown_grace_secs,
+                "drain grace period elapsed, exiting with open connections"
+            );
+        }
+    }
+
+    // Let WebSocket connections write their close frames
+    let open = ws_connections.wait_closed().await;
+    if open > 0 {
+        tracing::warn!(connections = open, "exiting with open ws connections");
+    }
+
+    // Nothing mutates pools any more: snapshot them so the next start
+    // replays no events
+    if let Some(db) = persistence.as_ref()
+        && !config.replica_mode
+    {
+        let round = SnapshotScheduler::run_once(&pool_service, db).await;
+        tracing::info!(
+            saved = round.saved,
+            skipped = round.skipped,
+            failed = round.failed.len(),
+            "final snapshot written"
+        );
+    }
+
+    // Tell a replacement process it may restore from our snapshots
+    if let Some(pid_file) = owned_pid_file.as_deref()
+        && let Err(e) = server::mark_drained(pid_file)
+    {
+        tracing::warn!(error = %e, "failed to write drained marker");
+    }
+
+    tracing::info!("server stopped");
     Ok(())
 }
+
+/// Layers between pool-owner forwarding and the handlers, shared by the
+/// main router and the router serving reads of peer pools so both answer
+/// alike.
+fn handler_layers(router: Router<AppState>, state: &AppState) -> Router<AppState> {
+    router
+        .layer(axum::middleware::from_fn(api::numeric::numeric_mode))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            api::idempotency::idempotent_mutations,
+        ))
+}
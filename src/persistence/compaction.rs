@@ -0,0 +1,342 @@
+//! Event log compaction.
+//!
+//! Once a snapshot covers a time range, the individual swap, liquidity,
+//! fee, and price events inside it are no longer needed to rebuild the
+//! pool. Compaction replaces them with a single [`EventCheckpoint`] in
+//! the `event_checkpoints` table, whose [`CompactionSummary`] keeps the
+//! volume, fee, and price aggregates while storage is reclaimed. Swaps are
+//! kept as hourly [`SwapBucket`]s, so the pool statistics and fee-tier
+//! volumes loaded at startup still count them. Checkpoints never appear
+//! in the event log itself.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::models::StoredEvent;
+use crate::domain::pool_event::LiquidityChangeType;
+use crate::domain::pool_operation::SwapKind;
+use crate::domain::{PoolEvent, PoolId};
+
+/// Event types that compaction may delete. Lifecycle events
+/// (`pool_created`, `pool_removed`, `pool_archived`,
+/// `pool_metadata_updated`) are always kept.
+pub const COMPACTABLE_EVENT_TYPES: [&str; 4] = [
+    "swap_executed",
+    "liquidity_changed",
+    "fees_collected",
+    "price_updated",
+];
+
+/// Aggregates preserved for a compacted range. Amounts are
+/// string-encoded u128 values; prices are decimal strings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CompactionSummary {
+    /// Start of the compacted range (inclusive).
+    pub from: Option<DateTime<Utc>>,
+    /// End of the compacted range (inclusive).
+    pub to: Option<DateTime<Utc>>,
+    /// Number of rows replaced by this checkpoint.
+    pub events_compacted: u64,
+    /// Swaps executed in the range.
+    pub swap_count: u64,
+    /// Sum of swap input amounts.
+    pub volume_in: String,
+    /// Sum of swap output amounts.
+    pub volume_out: String,
+    /// Sum of swap fees.
+    pub fees: String,
+    /// Sum of fee-tier rebates, included in `fees`.
+    #[serde(default)]
+    pub fee_rebates: String,
+    /// Number of liquidity deposits.
+    pub liquidity_adds: u64,
+    /// Number of liquidity withdrawals.
+    pub liquidity_removes: u64,
+    /// Price before the first price change in the range.
+    pub open_price: Option<String>,
+    /// Highest price observed in the range.
+    pub high_price: Option<String>,
+    /// Lowest price observed in the range.
+    pub low_price: Option<String>,
+    /// Price after the last price change in the range.
+    pub close_price: Option<String>,
+    /// Swaps in the range by UTC hour, input token, and actor.
+    #[serde(default)]
+    pub swaps: Vec<SwapBucket>,
+}
+
+/// Swaps of one UTC hour with the same input token and actor, summed.
+/// Amounts are string-encoded u128 values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct SwapBucket {
+    /// Start of the hour.
+    pub hour: DateTime<Utc>,
+    /// Input token of the swaps.
+    pub token_in: String,
+    /// Client that swapped, if known.
+    pub actor: Option<String>,
+    /// Number of swaps.
+    pub swaps: u64,
+    /// Sum of input amounts.
+    pub amount_in: String,
+    /// Sum of output amounts.
+    pub amount_out: String,
+    /// Sum of fees.
+    pub fee: String,
+    /// Sum of fee-tier rebates, included in `fee`.
+    pub fee_rebate: String,
+}
+
+/// A checkpoint row from the `event_checkpoints` table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventCheckpoint {
+    /// Auto-increment row ID.
+    pub id: i64,
+    /// Pool whose events were compacted.
+    pub pool_id: Uuid,
+    /// Start of the compacted range (inclusive).
+    pub from: DateTime<Utc>,
+    /// End of the compacted range (inclusive).
+    pub to: DateTime<Utc>,
+    /// Aggregates of the deleted events.
+    pub summary: CompactionSummary,
+}
+
+/// Running sums of one [`SwapBucket`].
+#[derive(Default)]
+struct BucketSums {
+    swaps: u64,
+    amount_in: u128,
+    amount_out: u128,
+    fee: u128,
+    fee_rebate: u128,
+}
+
+/// Builds the summary for `events`, which must be in log order.
+///
+/// Events whose payload does not decode are counted but otherwise
+/// ignored.
+#[must_use]
+pub fn summarize(events: &[StoredEvent]) -> CompactionSummary {
+    let mut summary = CompactionSummary::default();
+    let (mut volume_in, mut volume_out, mut fees, mut rebates) = (0u128, 0u128, 0u128, 0u128);
+    let mut high: Option<f64> = None;
+    let mut low: Option<f64> = None;
+    let mut buckets: BTreeMap<(DateTime<Utc>, String, Option<String>), BucketSums> =
+        BTreeMap::new();
+
+    for stored in events {
+        summary.events_compacted = summary.events_compacted.saturating_add(1);
+        summary.from = Some(
+            summary
+                .from
+                .map_or(stored.created_at, |f| f.min(stored.created_at)),
+        );
+        summary.to = Some(
+            summary
+                .to
+                .map_or(stored.created_at, |t| t.max(stored.created_at)),
+        );
+
+        let Ok(event) = stored.to_pool_event() else {
+            continue;
+        };
+        match event {
+            PoolEvent::SwapExecuted {
+                amount_in,
+                amount_out,
+                fee,
+                fee_rebate,
+                token_in,
+                actor,
+                timestamp,
+                ..
+            } => {
+                let (amount_in, amount_out) = (
+                    amount_in.parse().unwrap_or(0),
+                    amount_out.parse().unwrap_or(0),
+                );
+                let (fee, fee_rebate) = (fee.parse().unwrap_or(0), fee_rebate.parse().unwrap_or(0));
+                summary.swap_count = summary.swap_count.saturating_add(1);
+                volume_in = volume_in.saturating_add(amount_in);
+                volume_out = volume_out.saturating_add(amount_out);
+                fees = fees.saturating_add(fee);
+                rebates = rebates.saturating_add(fee_rebate);
+
+                let hour = timestamp
+                    .duration_trunc(TimeDelta::hours(1))
+                    .unwrap_or(timestamp);
+                let bucket = buckets.entry((hour, token_in, actor)).or_default();
+                bucket.swaps = bucket.swaps.saturating_add(1);
+                bucket.amount_in = bucket.amount_in.saturating_add(amount_in);
+                bucket.amount_out = bucket.amount_out.saturating_add(amount_out);
+                bucket.fee = bucket.fee.saturating_add(fee);
+                bucket.fee_rebate = bucket.fee_rebate.saturating_add(fee_rebate);
+            }
+            PoolEvent::LiquidityChanged { change_type, .. } => match change_type {
+                LiquidityChangeType::Add => {
+                    summary.liquidity_adds = summary.liquidity_adds.saturating_add(1);
+                }
+                LiquidityChangeType::Remove => {
+                    summary.liquidity_removes = summary.liquidity_removes.saturating_add(1);
+                }
+            },
+            PoolEvent::PriceUpdated {
+                old_price,
+                new_price,
+                ..
+            } => {
+                if summary.open_price.is_none() {
+                    summary.open_price = Some(old_price.clone());
+                    observe(&old_price, &mut high, &mut low);
+                }
+                observe(&new_price, &mut high, &mut low);
+                summary.close_price = Some(new_price);
+            }
+            PoolEvent::PoolCreated { .. }
+            | PoolEvent::PoolRemoved { .. }
+            | PoolEvent::PoolArchived { .. }
+            | PoolEvent::PoolRestored { .. }
+            | PoolEvent::PoolStatusChanged { .. }
+            | PoolEvent::PoolMetadataUpdated { .. }
+            | PoolEvent::PoolOwnershipTransferred { .. }
+            | PoolEvent::FeesCollected { .. }
+            | PoolEvent::RewardsClaimed { .. }
+            | PoolEvent::OrderPlaced { .. }
+            | PoolEvent::OrderCancelled { .. }
+            | PoolEvent::OraclePriceUpdated { .. } => {}
+        }
+    }
+
+    summary.volume_in = volume_in.to_string();
+    summary.volume_out = volume_out.to_string();
+    summary.fees = fees.to_string();
+    summary.fee_rebates = rebates.to_string();
+    summary.high_price = high.map(|p| format!("{p}"));
+    summary.low_price = low.map(|p| format!("{p}"));
+    summary.swaps = buckets
+        .into_iter()
+        .map(|((hour, token_in, actor), sums)| SwapBucket {
+            hour,
+            token_in,
+            actor,
+            swaps: sums.swaps,
+            amount_in: sums.amount_in.to_string(),
+            amount_out: sums.amount_out.to_string(),
+            fee: sums.fee.to_string(),
+            fee_rebate: sums.fee_rebate.to_string(),
+        })
+        .collect();
+    summary
+}
+
+impl SwapBucket {
+    /// The bucket as one `swap_executed` event of `pool_id` at the start
+    /// of its hour, for readers that fold swaps one event at a time.
+    #[must_use]
+    pub fn to_event(&self, pool_id: PoolId) -> PoolEvent {
+        PoolEvent::SwapExecuted {
+            pool_id,
+            command_id: String::new(),
+            amount_in: self.amount_in.clone(),
+            amount_out: self.amount_out.clone(),
+            fee: self.fee.clone(),
+            fee_rebate: self.fee_rebate.clone(),
+            new_price: String::new(),
+            price_change_bps: 0,
+            token_in: self.token_in.clone(),
+            swap_kind: SwapKind::ExactIn,
+            actor: self.actor.clone(),
+            timestamp: self.hour,
+        }
+    }
+}
+
+/// Folds a decimal price string into the running high/low.
+fn observe(price: &str, high: &mut Option<f64>, low: &mut Option<f64>) {
+    let Ok(p) = price.parse::<f64>() else {
+        return;
+    };
+    *high = Some(high.map_or(p, |h| h.max(p)));
+    *low = Some(low.map_or(p, |l| l.min(p)));
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::pool_event::{EVENT_SCHEMA_VERSION, PriceChangeReason};
+
+    fn stored(id: i64, event: &PoolEvent) -> StoredEvent {
+        let Ok(payload) = serde_json::to_value(event) else {
+            panic!("serialize failed");
+        };
+        StoredEvent {
+            id,
+            pool_id: *event.pool_id().as_uuid(),
+            event_type: event.event_type_str().to_string(),
+            schema_version: i32::try_from(EVENT_SCHEMA_VERSION).unwrap_or(i32::MAX),
+            payload,
+            created_at: Utc::now(),
+            imported: false,
+            pool_version: None,
+        }
+    }
+
+    #[test]
+    fn summarizes_swaps_and_prices() {
+        let pool_id = PoolId::new();
+        let swap = PoolEvent::SwapExecuted {
+            pool_id,
+            command_id: "c".to_string(),
+            amount_in: "100".to_string(),
+            amount_out: "90".to_string(),
+            fee: "1".to_string(),
+            fee_rebate: "0".to_string(),
+            new_price: "0.9".to_string(),
+            price_change_bps: -1000,
+            token_in: "0xaaa".to_string(),
+            swap_kind: SwapKind::ExactIn,
+            actor: None,
+            timestamp: Utc::now(),
+        };
+        let price = |old: &str, new: &str| PoolEvent::PriceUpdated {
+            pool_id,
+            old_price: old.to_string(),
+            new_price: new.to_string(),
+            price_change_bps: 0,
+            reason: PriceChangeReason::SwapExecuted,
+            timestamp: Utc::now(),
+        };
+        let events = vec![
+            stored(1, &swap),
+            stored(2, &price("1.0", "0.9")),
+            stored(3, &swap),
+            stored(4, &price("0.9", "1.2")),
+        ];
+
+        let summary = summarize(&events);
+        assert_eq!(summary.events_compacted, 4);
+        assert_eq!(summary.swap_count, 2);
+        assert_eq!(summary.volume_in, "200");
+        assert_eq!(summary.fees, "2");
+        assert_eq!(summary.fee_rebates, "0");
+        assert_eq!(summary.open_price.as_deref(), Some("1.0"));
+        assert_eq!(summary.close_price.as_deref(), Some("1.2"));
+        assert_eq!(summary.high_price.as_deref(), Some("1.2"));
+        assert_eq!(summary.low_price.as_deref(), Some("0.9"));
+        let Some(bucket) = summary.swaps.first() else {
+            panic!("swaps are bucketed");
+        };
+        assert_eq!(summary.swaps.len(), 1, "same hour, token, and actor");
+        assert_eq!((bucket.swaps, bucket.amount_in.as_str()), (2, "200"));
+        assert!(matches!(
+            bucket.to_event(pool_id),
+            PoolEvent::SwapExecuted { amount_out, .. } if amount_out == "180"
+        ));
+    }
+}
@@ -4,5 +4,7 @@
 //! events and periodic state snapshots. The concrete implementation
 //! uses `sqlx::PgPool` for async PostgreSQL access.
 
+pub mod compaction;
 pub mod models;
 pub mod postgres;
+pub mod upcast;
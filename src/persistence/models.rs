@@ -4,6 +4,9 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::domain::PoolEvent;
+use crate::error::GatewayError;
+
 /// A stored event row from the `events` table.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoredEvent {
@@ -13,10 +16,49 @@ pub struct StoredEvent {
     pub pool_id: Uuid,
     /// Event type discriminator (e.g. `"swap_executed"`).
     pub event_type: String,
+    /// Payload schema version the event was written with.
+    pub schema_version: i32,
     /// JSONB payload with event-specific data.
     pub payload: serde_json::Value,
-    /// Server-side creation timestamp.
+    /// Server-side creation timestamp; the event's own timestamp for
+    /// imported events.
     pub created_at: DateTime<Utc>,
+    /// Whether the event was imported from another venue's history rather
+    /// than produced by this gateway. Imported events are never replayed.
+    #[serde(default)]
+    pub imported: bool,
+    /// Pool version after the mutation the event records; `None` for
+    /// imported events and those logged before versions were recorded.
+    #[serde(default)]
+    pub pool_version: Option<i64>,
+}
+
+impl StoredEvent {
+    /// Deserializes the payload into a domain [`PoolEvent`].
+    ///
+    /// The payload should already be upcast to the current schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PersistenceError`] if the payload does not
+    /// match the current `PoolEvent` shape.
+    pub fn to_pool_event(&self) -> Result<PoolEvent, GatewayError> {
+        serde_json::from_value(self.payload.clone())
+            .map_err(|e| GatewayError::PersistenceError(format!("event {} payload: {e}", self.id)))
+    }
+}
+
+/// An event to append to the `events` table.
+#[derive(Debug, Clone)]
+pub struct NewEvent {
+    /// Pool that generated the event.
+    pub pool_id: Uuid,
+    /// Event type string.
+    pub event_type: &'static str,
+    /// Event payload as published.
+    pub payload: serde_json::Value,
+    /// Pool version after the mutation, when the event records one.
+    pub pool_version: Option<i64>,
 }
 
 /// A pool snapshot row from the `pool_snapshots` table.
@@ -37,3 +79,153 @@ pub struct PoolSnapshot {
     /// Snapshot timestamp.
     pub snapshot_at: DateTime<Utc>,
 }
+
+/// A `pool_snapshots` row without its contents, for listings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    /// Auto-increment row ID.
+    pub id: i64,
+    /// Pool type string.
+    pub pool_type: String,
+    /// Snapshot timestamp.
+    pub snapshot_at: DateTime<Utc>,
+    /// Size of the config, state, and metadata JSON in bytes.
+    pub size_bytes: i64,
+}
+
+/// A per-API-key quota row from the `rate_limits` table. `None` limits
+/// are unlimited.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitRecord {
+    /// API key (client id) the quota applies to.
+    pub api_key: String,
+    /// Requests per minute.
+    pub requests_per_minute: Option<u32>,
+    /// Swap executions per day.
+    pub swaps_per_day: Option<u32>,
+    /// Concurrently open WebSocket connections.
+    pub ws_connections: Option<u32>,
+    /// Live pools created by the key.
+    pub max_pools: Option<u32>,
+    /// Pool creations per day.
+    pub pool_creations_per_day: Option<u32>,
+}
+
+/// A webhook row from the `pool_webhooks` table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookRecord {
+    /// Webhook identifier.
+    pub id: Uuid,
+    /// Pool the webhook is attached to.
+    pub pool_id: Uuid,
+    /// Delivery URL.
+    pub url: String,
+    /// Event types delivered; empty means every type.
+    pub event_types: Vec<String>,
+    /// Size threshold (decimal u128), if any.
+    pub min_amount: Option<String>,
+    /// HMAC signing secret.
+    pub secret: String,
+    /// Creation time.
+    pub created_at: DateTime<Utc>,
+}
+
+/// A JSON Schema row from the `metadata_schemas` table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetadataSchemaRecord {
+    /// `tenant` or `pool_type`.
+    pub scope: String,
+    /// Client id or pool type the schema applies to.
+    pub name: String,
+    /// The JSON Schema document.
+    pub schema: serde_json::Value,
+    /// Last time the schema was set.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A stored response from the `idempotency_keys` table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    /// Client id of the request (`""` without one).
+    pub client_id: String,
+    /// The `Idempotency-Key` header value.
+    pub idempotency_key: String,
+    /// Hex SHA-256 of the request method, path, and body.
+    pub fingerprint: String,
+    /// HTTP status of the response.
+    pub status: u16,
+    /// `Content-Type` of the response, if any.
+    pub content_type: Option<String>,
+    /// Response body.
+    pub body: Vec<u8>,
+    /// When the first request completed.
+    pub created_at: DateTime<Utc>,
+}
+
+/// One OHLCV candle row from the `candles` table.
+///
+/// Rows written by the candle worker are deltas: prices are merged with
+/// `GREATEST`/`LEAST`, the close is replaced, and volumes and trade
+/// counts are added to the stored values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CandleRecord {
+    /// Pool the candle belongs to.
+    pub pool_id: Uuid,
+    /// Timeframe label (`1m`, `5m`, `1h`, `1d`).
+    pub timeframe: String,
+    /// Start of the bucket.
+    pub bucket_start: DateTime<Utc>,
+    /// First trade price in the bucket.
+    pub open: f64,
+    /// Highest trade price.
+    pub high: f64,
+    /// Lowest trade price.
+    pub low: f64,
+    /// Last trade price.
+    pub close: f64,
+    /// Base token traded (raw units).
+    pub base_volume: u128,
+    /// Quote token traded (raw units).
+    pub quote_volume: u128,
+    /// Number of trades.
+    pub trades: u64,
+}
+
+/// A quote served to a client, for the `quotes` audit table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuoteRecord {
+    /// Quoted pool.
+    pub pool_id: Uuid,
+    /// Client the quote was served to, from `X-Client-Id`.
+    pub client_id: Option<String>,
+    /// Input token address.
+    pub token_in: String,
+    /// Output token address.
+    pub token_out: String,
+    /// Quoted input amount (raw units).
+    pub amount_in: u128,
+    /// Quoted output amount (raw units).
+    pub amount_out: u128,
+    /// Quoted fee (raw units of the input token).
+    pub fee: u128,
+    /// Spot price when the quote was computed.
+    pub spot_price: f64,
+    /// Quoted execution price in whole `token_out` per whole `token_in`.
+    pub execution_price: f64,
+    /// Quoted price impact in basis points.
+    pub price_impact_bps: i32,
+    /// Pool state version the quote was computed against.
+    pub pool_version: u64,
+    /// When the quote was served.
+    pub quoted_at: DateTime<Utc>,
+}
+
+/// A row of the `quotes` table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredQuote {
+    /// Auto-increment row ID.
+    pub id: i64,
+    /// The quote.
+    #[serde(flatten)]
+    pub quote: QuoteRecord,
+}
@@ -1,26 +1,173 @@
 //! PostgreSQL implementation of the persistence layer.
 
+use std::sync::Arc;
+use std::time::Duration;
+
 use chrono::{DateTime, Utc};
 use sqlx::PgPool;
+use sqlx::postgres::PgPoolOptions;
 use uuid::Uuid;
 
-use super::models::{PoolSnapshot, StoredEvent};
+use super::compaction::{self, COMPACTABLE_EVENT_TYPES, CompactionSummary, EventCheckpoint};
+use super::models::{
+    CandleRecord, IdempotencyRecord, MetadataSchemaRecord, NewEvent, PoolSnapshot, QuoteRecord,
+    RateLimitRecord, SnapshotInfo, StoredEvent, StoredQuote, WebhookRecord,
+};
+use super::upcast::UpcasterRegistry;
+use crate::config::GatewayConfig;
+use crate::domain::PoolEvent;
+use crate::domain::pool_event::EVENT_SCHEMA_VERSION;
 use crate::error::GatewayError;
 
+/// Raw `events` row: `(id, pool_id, event_type, schema_version, payload, created_at, imported,
+/// pool_version)`.
+type EventRow = (
+    i64,
+    Uuid,
+    String,
+    i32,
+    serde_json::Value,
+    DateTime<Utc>,
+    bool,
+    Option<i64>,
+);
+
+/// Raw `event_checkpoints` row: `(id, pool_id, range_from, range_to, summary)`.
+type CheckpointRow = (i64, Uuid, DateTime<Utc>, DateTime<Utc>, serde_json::Value);
+
+/// Raw `pool_snapshots` row, in column order.
+type SnapshotRow = (
+    i64,
+    Uuid,
+    String,
+    serde_json::Value,
+    serde_json::Value,
+    serde_json::Value,
+    DateTime<Utc>,
+);
+
+/// Raw `rate_limits` row, in column order.
+type RateLimitRow = (
+    String,
+    Option<i64>,
+    Option<i64>,
+    Option<i64>,
+    Option<i64>,
+    Option<i64>,
+);
+
+/// Raw `pool_webhooks` row, in column order.
+type WebhookRow = (
+    Uuid,
+    Uuid,
+    String,
+    Vec<String>,
+    Option<String>,
+    String,
+    DateTime<Utc>,
+);
+
+/// Raw `idempotency_keys` row, in column order.
+type IdempotencyRow = (
+    String,
+    String,
+    String,
+    i16,
+    Option<String>,
+    Vec<u8>,
+    DateTime<Utc>,
+);
+
+/// Raw `candles` row, in column order, with volumes cast to text.
+type CandleRow = (
+    Uuid,
+    String,
+    DateTime<Utc>,
+    f64,
+    f64,
+    f64,
+    f64,
+    String,
+    String,
+    i64,
+);
+
+/// Raw `quotes` row, in column order.
+type QuoteRow = (
+    i64,
+    Uuid,
+    Option<String>,
+    String,
+    String,
+    String,
+    String,
+    String,
+    f64,
+    f64,
+    i32,
+    i64,
+    DateTime<Utc>,
+);
+
+/// Result of [`PostgresPersistence::compact_events`].
+#[derive(Debug, Clone)]
+pub struct CompactionOutcome {
+    /// Event rows deleted.
+    pub events_deleted: u64,
+    /// ID of the checkpoint row, if anything was compacted.
+    pub checkpoint_id: Option<i64>,
+    /// Aggregates preserved in the checkpoint.
+    pub summary: CompactionSummary,
+}
+
 /// PostgreSQL-backed persistence layer using `sqlx::PgPool`.
 #[derive(Debug, Clone)]
 pub struct PostgresPersistence {
     pool: PgPool,
+    upcasters: Arc<UpcasterRegistry>,
 }
 
 impl PostgresPersistence {
     /// Creates a new persistence layer with the given connection pool.
     #[must_use]
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            upcasters: Arc::new(UpcasterRegistry::default()),
+        }
     }
 
-    /// Appends an event to the event log.
+    /// Connects to the database described by `config` and applies any
+    /// pending migrations.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] if the connection or
+    /// a migration fails.
+    pub async fn connect(config: &GatewayConfig) -> Result<Self, GatewayError> {
+        let pool = PgPoolOptions::new()
+            .max_connections(config.database_max_connections)
+            .min_connections(config.database_min_connections)
+            .acquire_timeout(Duration::from_secs(config.database_connect_timeout_secs))
+            .connect(&config.database_url)
+            .await
+            .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+        Ok(Self::new(pool))
+    }
+
+    /// Replaces the upcaster registry applied to loaded events.
+    #[must_use]
+    pub fn with_upcasters(mut self, upcasters: UpcasterRegistry) -> Self {
+        self.upcasters = Arc::new(upcasters);
+        self
+    }
+
+    /// Appends an event to the event log, tagged with the current
+    /// [`EVENT_SCHEMA_VERSION`].
     ///
     /// # Errors
     ///
@@ -31,19 +178,93 @@ impl PostgresPersistence {
         event_type: &str,
         payload: &serde_json::Value,
     ) -> Result<i64, GatewayError> {
+        let db_err = |e: sqlx::Error| GatewayError::PersistenceError(e.to_string());
+        let mut tx = self.pool.begin().await.map_err(db_err)?;
+        lock_event_log(&mut tx).await?;
         let row = sqlx::query_scalar::<_, i64>(
-            "INSERT INTO events (pool_id, event_type, payload) VALUES ($1, $2, $3) RETURNING id",
+            "INSERT INTO events (pool_id, event_type, payload, schema_version) \
+             VALUES ($1, $2, $3, $4) RETURNING id",
         )
         .bind(pool_id)
         .bind(event_type)
         .bind(payload)
-        .fetch_one(&self.pool)
+        .bind(i32::try_from(EVENT_SCHEMA_VERSION).unwrap_or(i32::MAX))
+        .fetch_one(&mut *tx)
         .await
-        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+        .map_err(db_err)?;
+        tx.commit().await.map_err(db_err)?;
 
         Ok(row)
     }
 
+    /// Appends `events` to the event log in one transaction, in order, at
+    /// the current [`EVENT_SCHEMA_VERSION`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure;
+    /// nothing is written in that case.
+    pub async fn save_events(&self, events: &[NewEvent]) -> Result<(), GatewayError> {
+        let db_err = |e: sqlx::Error| GatewayError::PersistenceError(e.to_string());
+        let mut tx = self.pool.begin().await.map_err(db_err)?;
+        lock_event_log(&mut tx).await?;
+        for event in events {
+            sqlx::query(
+                "INSERT INTO events (pool_id, event_type, payload, schema_version, pool_version) \
+                 VALUES ($1, $2, $3, $4, $5)",
+            )
+            .bind(event.pool_id)
+            .bind(event.event_type)
+            .bind(&event.payload)
+            .bind(i32::try_from(EVENT_SCHEMA_VERSION).unwrap_or(i32::MAX))
+            .bind(event.pool_version)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_err)?;
+        }
+        tx.commit().await.map_err(db_err)?;
+        Ok(())
+    }
+
+    /// Appends historical `events` of `pool_id` to the event log in one
+    /// transaction, flagged as imported and stamped with their own
+    /// timestamps. Returns the assigned IDs in input order.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure;
+    /// nothing is written in that case.
+    pub async fn import_events(
+        &self,
+        pool_id: Uuid,
+        events: &[PoolEvent],
+    ) -> Result<Vec<i64>, GatewayError> {
+        let db_err = |e: sqlx::Error| GatewayError::PersistenceError(e.to_string());
+        let mut tx = self.pool.begin().await.map_err(db_err)?;
+        lock_event_log(&mut tx).await?;
+        let mut ids = Vec::with_capacity(events.len());
+        for event in events {
+            let payload = serde_json::to_value(event)
+                .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+            let id = sqlx::query_scalar::<_, i64>(
+                "INSERT INTO events (pool_id, event_type, payload, schema_version, created_at, imported) \
+                 VALUES ($1, $2, $3, $4, $5, TRUE) RETURNING id",
+            )
+            .bind(pool_id)
+            .bind(event.event_type_str())
+            .bind(&payload)
+            .bind(i32::try_from(EVENT_SCHEMA_VERSION).unwrap_or(i32::MAX))
+            .bind(event.timestamp())
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(db_err)?;
+            ids.push(id);
+        }
+        tx.commit().await.map_err(db_err)?;
+
+        Ok(ids)
+    }
+
     /// Saves a pool state snapshot.
     ///
     /// # Errors
@@ -79,7 +300,7 @@ impl PostgresPersistence {
     ///
     /// Returns a [`GatewayError::PersistenceError`] on database failure.
     pub async fn load_latest_snapshots(&self) -> Result<Vec<PoolSnapshot>, GatewayError> {
-        let rows = sqlx::query_as::<_, (i64, Uuid, String, serde_json::Value, serde_json::Value, serde_json::Value, DateTime<Utc>)>(
+        let rows = sqlx::query_as::<_, SnapshotRow>(
             "SELECT DISTINCT ON (pool_id) id, pool_id, pool_type, config_json, state_json, metadata_json, snapshot_at \
              FROM pool_snapshots ORDER BY pool_id, snapshot_at DESC",
         )
@@ -87,37 +308,102 @@ impl PostgresPersistence {
         .await
         .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
 
+        Ok(rows.into_iter().map(snapshot_from_row).collect())
+    }
+
+    /// Loads, for each pool, the latest snapshot taken at or before `at`,
+    /// optionally restricted to one pool.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn load_snapshots_at(
+        &self,
+        at: DateTime<Utc>,
+        pool_id: Option<Uuid>,
+    ) -> Result<Vec<PoolSnapshot>, GatewayError> {
+        let rows = sqlx::query_as::<_, SnapshotRow>(
+            "SELECT DISTINCT ON (pool_id) id, pool_id, pool_type, config_json, state_json, metadata_json, snapshot_at \
+             FROM pool_snapshots WHERE snapshot_at <= $1 AND ($2::uuid IS NULL OR pool_id = $2) \
+             ORDER BY pool_id, snapshot_at DESC",
+        )
+        .bind(at)
+        .bind(pool_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        Ok(rows.into_iter().map(snapshot_from_row).collect())
+    }
+
+    /// Lists up to `limit` snapshots of a pool, newest first, without
+    /// their contents.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn list_snapshots(
+        &self,
+        pool_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<SnapshotInfo>, GatewayError> {
+        let rows = sqlx::query_as::<_, (i64, String, DateTime<Utc>, i64)>(
+            "SELECT id, pool_type, snapshot_at, \
+             (octet_length(config_json::text) + octet_length(state_json::text) \
+             + octet_length(metadata_json::text))::bigint \
+             FROM pool_snapshots WHERE pool_id = $1 ORDER BY snapshot_at DESC, id DESC LIMIT $2",
+        )
+        .bind(pool_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
         Ok(rows
             .into_iter()
-            .map(
-                |(id, pool_id, pool_type, config_json, state_json, metadata_json, snapshot_at)| {
-                    PoolSnapshot {
-                        id,
-                        pool_id,
-                        pool_type,
-                        config_json,
-                        state_json,
-                        metadata_json,
-                        snapshot_at,
-                    }
-                },
-            )
+            .map(|(id, pool_type, snapshot_at, size_bytes)| SnapshotInfo {
+                id,
+                pool_type,
+                snapshot_at,
+                size_bytes,
+            })
             .collect())
     }
 
-    /// Loads events after the given timestamp, optionally filtered by pool ID.
+    /// Loads one snapshot by id.
     ///
     /// # Errors
     ///
     /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn load_snapshot(&self, id: i64) -> Result<Option<PoolSnapshot>, GatewayError> {
+        let row = sqlx::query_as::<_, SnapshotRow>(
+            "SELECT id, pool_id, pool_type, config_json, state_json, metadata_json, snapshot_at \
+             FROM pool_snapshots WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        Ok(row.map(snapshot_from_row))
+    }
+
+    /// Loads events after the given timestamp, optionally filtered by pool ID.
+    ///
+    /// Payloads are upcast to the current schema version.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure
+    /// or if a payload cannot be upcast.
     pub async fn load_events_after(
         &self,
         after: DateTime<Utc>,
         pool_id: Option<Uuid>,
     ) -> Result<Vec<StoredEvent>, GatewayError> {
         let rows = if let Some(pid) = pool_id {
-            sqlx::query_as::<_, (i64, Uuid, String, serde_json::Value, DateTime<Utc>)>(
-                "SELECT id, pool_id, event_type, payload, created_at FROM events \
+            sqlx::query_as::<_, EventRow>(
+                "SELECT id, pool_id, event_type, schema_version, payload, created_at, imported, pool_version FROM events \
                  WHERE created_at > $1 AND pool_id = $2 ORDER BY created_at ASC",
             )
             .bind(after)
@@ -125,8 +411,8 @@ impl PostgresPersistence {
             .fetch_all(&self.pool)
             .await
         } else {
-            sqlx::query_as::<_, (i64, Uuid, String, serde_json::Value, DateTime<Utc>)>(
-                "SELECT id, pool_id, event_type, payload, created_at FROM events \
+            sqlx::query_as::<_, EventRow>(
+                "SELECT id, pool_id, event_type, schema_version, payload, created_at, imported, pool_version FROM events \
                  WHERE created_at > $1 ORDER BY created_at ASC",
             )
             .bind(after)
@@ -135,18 +421,338 @@ impl PostgresPersistence {
         }
         .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
 
-        Ok(rows
-            .into_iter()
+        self.upcast_rows(rows)
+    }
+
+    /// Loads events written up to and including `until`, in log order,
+    /// optionally filtered by pool ID.
+    ///
+    /// Payloads are upcast to the current schema version.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure
+    /// or if a payload cannot be upcast.
+    pub async fn load_events_until(
+        &self,
+        until: DateTime<Utc>,
+        pool_id: Option<Uuid>,
+    ) -> Result<Vec<StoredEvent>, GatewayError> {
+        let rows = sqlx::query_as::<_, EventRow>(
+            "SELECT id, pool_id, event_type, schema_version, payload, created_at, imported, pool_version FROM events \
+             WHERE created_at <= $1 AND ($2::uuid IS NULL OR pool_id = $2) ORDER BY id ASC",
+        )
+        .bind(until)
+        .bind(pool_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        self.upcast_rows(rows)
+    }
+
+    /// Loads up to `limit` events with a row ID greater than `after_id`,
+    /// in ID order. Used to tail the log.
+    ///
+    /// Events are inserted under [`lock_event_log`], so a row only
+    /// becomes visible once every row with a lower ID is committed or
+    /// rolled back: tailing by ID never skips a row committed late.
+    ///
+    /// Payloads are upcast to the current schema version.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure
+    /// or if a payload cannot be upcast.
+    pub async fn load_events_after_id(
+        &self,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<StoredEvent>, GatewayError> {
+        let rows = sqlx::query_as::<_, EventRow>(
+            "SELECT id, pool_id, event_type, schema_version, payload, created_at, imported, pool_version FROM events \
+             WHERE id > $1 ORDER BY id ASC LIMIT $2",
+        )
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        self.upcast_rows(rows)
+    }
+
+    /// Loads up to `limit` events with a row ID greater than `after_id`,
+    /// in ID order, optionally only those of one pool and one type.
+    /// Used to page through the log.
+    ///
+    /// Payloads are upcast to the current schema version.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure
+    /// or if a payload cannot be upcast.
+    pub async fn load_event_page(
+        &self,
+        after_id: i64,
+        pool_id: Option<Uuid>,
+        event_type: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<StoredEvent>, GatewayError> {
+        let rows = sqlx::query_as::<_, EventRow>(
+            "SELECT id, pool_id, event_type, schema_version, payload, created_at, imported, pool_version FROM events \
+             WHERE id > $1 AND ($2::uuid IS NULL OR pool_id = $2) \
+             AND ($3::text IS NULL OR event_type = $3) ORDER BY id ASC LIMIT $4",
+        )
+        .bind(after_id)
+        .bind(pool_id)
+        .bind(event_type)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        self.upcast_rows(rows)
+    }
+
+    /// Loads one pool's events of a single type written at or after
+    /// `since`, in log order.
+    ///
+    /// Payloads are upcast to the current schema version.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure
+    /// or if a payload cannot be upcast.
+    pub async fn load_pool_events_of_type(
+        &self,
+        pool_id: Uuid,
+        event_type: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<StoredEvent>, GatewayError> {
+        let rows = sqlx::query_as::<_, EventRow>(
+            "SELECT id, pool_id, event_type, schema_version, payload, created_at, imported, pool_version FROM events \
+             WHERE pool_id = $1 AND event_type = $2 AND created_at >= $3 ORDER BY id ASC",
+        )
+        .bind(pool_id)
+        .bind(event_type)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        self.upcast_rows(rows)
+    }
+
+    /// Loads every pool's events of a single type written at or after
+    /// `since`, in log order.
+    ///
+    /// Payloads are upcast to the current schema version.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure
+    /// or if a payload cannot be upcast.
+    pub async fn load_events_of_type(
+        &self,
+        event_type: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<StoredEvent>, GatewayError> {
+        let rows = sqlx::query_as::<_, EventRow>(
+            "SELECT id, pool_id, event_type, schema_version, payload, created_at, imported, pool_version FROM events \
+             WHERE event_type = $1 AND created_at >= $2 ORDER BY id ASC",
+        )
+        .bind(event_type)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        self.upcast_rows(rows)
+    }
+
+    /// Sums swap input amounts per actor and UTC day since `since`, for
+    /// seeding the fee-tier program. Swaps folded into compaction
+    /// checkpoints count from their hourly buckets. Anonymous and imported
+    /// swaps are left out; sums are decimal strings.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn load_swap_volumes(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(String, DateTime<Utc>, String)>, GatewayError> {
+        sqlx::query_as::<_, (String, DateTime<Utc>, String)>(
+            "SELECT actor, date_trunc('day', at, 'UTC'), SUM(amount)::text FROM ( \
+             SELECT payload->>'actor' AS actor, created_at AS at, \
+             (payload->>'amount_in')::numeric AS amount FROM events \
+             WHERE event_type = 'swap_executed' AND NOT imported \
+             UNION ALL \
+             SELECT bucket->>'actor', (bucket->>'hour')::timestamptz, \
+             (bucket->>'amount_in')::numeric \
+             FROM event_checkpoints, jsonb_array_elements(summary->'swaps') AS bucket \
+             WHERE range_to >= $1 \
+             ) swaps WHERE actor IS NOT NULL AND at >= $1 GROUP BY 1, 2",
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))
+    }
+
+    /// Replaces the compactable events of `pool_id` in `[from, to]` with a
+    /// single `event_checkpoints` row carrying their [`CompactionSummary`].
+    ///
+    /// The range must be covered by a snapshot taken at or after `to`, so
+    /// the pool can still be rebuilt without the deleted events. Runs in
+    /// one transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InvalidRequest`] if no covering snapshot
+    /// exists, or a [`GatewayError::PersistenceError`] on database
+    /// failure.
+    pub async fn compact_events(
+        &self,
+        pool_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<CompactionOutcome, GatewayError> {
+        let db_err = |e: sqlx::Error| GatewayError::PersistenceError(e.to_string());
+        let mut tx = self.pool.begin().await.map_err(db_err)?;
+
+        let covered = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM pool_snapshots WHERE pool_id = $1 AND snapshot_at >= $2)",
+        )
+        .bind(pool_id)
+        .bind(to)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_err)?;
+        if !covered {
+            return Err(GatewayError::InvalidRequest(format!(
+                "no snapshot of pool {pool_id} at or after {to}; take one before compacting"
+            )));
+        }
+
+        let rows = sqlx::query_as::<_, EventRow>(
+            "SELECT id, pool_id, event_type, schema_version, payload, created_at, imported, pool_version FROM events \
+             WHERE pool_id = $1 AND created_at >= $2 AND created_at <= $3 \
+             AND event_type = ANY($4) ORDER BY id ASC FOR UPDATE",
+        )
+        .bind(pool_id)
+        .bind(from)
+        .bind(to)
+        .bind(&COMPACTABLE_EVENT_TYPES[..])
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db_err)?;
+        if rows.is_empty() {
+            return Ok(CompactionOutcome {
+                events_deleted: 0,
+                checkpoint_id: None,
+                summary: CompactionSummary::default(),
+            });
+        }
+
+        let events = self.upcast_rows(rows)?;
+        let ids: Vec<i64> = events.iter().map(|e| e.id).collect();
+        let summary = compaction::summarize(&events);
+        let payload =
+            serde_json::to_value(&summary).map_err(|e| GatewayError::Internal(e.to_string()))?;
+
+        let deleted = sqlx::query("DELETE FROM events WHERE id = ANY($1)")
+            .bind(&ids)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_err)?
+            .rows_affected();
+
+        let checkpoint_id = sqlx::query_scalar::<_, i64>(
+            "INSERT INTO event_checkpoints (pool_id, range_from, range_to, summary) \
+             VALUES ($1, $2, $3, $4) RETURNING id",
+        )
+        .bind(pool_id)
+        .bind(from)
+        .bind(to)
+        .bind(&payload)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_err)?;
+
+        tx.commit().await.map_err(db_err)?;
+
+        Ok(CompactionOutcome {
+            events_deleted: deleted,
+            checkpoint_id: Some(checkpoint_id),
+            summary,
+        })
+    }
+
+    /// Loads the compaction checkpoints of every pool, or of `pool_id`,
+    /// in the order they were written.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure
+    /// or if a summary cannot be decoded.
+    pub async fn load_checkpoints(
+        &self,
+        pool_id: Option<Uuid>,
+    ) -> Result<Vec<EventCheckpoint>, GatewayError> {
+        let rows = sqlx::query_as::<_, CheckpointRow>(
+            "SELECT id, pool_id, range_from, range_to, summary FROM event_checkpoints \
+             WHERE ($1::uuid IS NULL OR pool_id = $1) ORDER BY id ASC",
+        )
+        .bind(pool_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        rows.into_iter()
+            .map(|(id, pool_id, from, to, summary)| {
+                let summary = serde_json::from_value(summary).map_err(|e| {
+                    GatewayError::PersistenceError(format!("checkpoint {id} summary: {e}"))
+                })?;
+                Ok(EventCheckpoint {
+                    id,
+                    pool_id,
+                    from,
+                    to,
+                    summary,
+                })
+            })
+            .collect()
+    }
+
+    /// Converts raw event rows into upcast [`StoredEvent`]s.
+    fn upcast_rows(&self, rows: Vec<EventRow>) -> Result<Vec<StoredEvent>, GatewayError> {
+        rows.into_iter()
             .map(
-                |(id, pool_id, event_type, payload, created_at)| StoredEvent {
+                |(
                     id,
                     pool_id,
                     event_type,
+                    schema_version,
                     payload,
                     created_at,
+                    imported,
+                    pool_version,
+                )| {
+                    self.upcasters.upcast(StoredEvent {
+                        id,
+                        pool_id,
+                        event_type,
+                        schema_version,
+                        payload,
+                        created_at,
+                        imported,
+                        pool_version,
+                    })
                 },
             )
-            .collect())
+            .collect()
     }
 
     /// Deletes snapshots older than the given number of days.
@@ -166,4 +772,778 @@ impl PostgresPersistence {
 
         Ok(result.rows_affected())
     }
+
+    /// Loads every per-API-key quota.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn load_rate_limits(&self) -> Result<Vec<RateLimitRecord>, GatewayError> {
+        let rows = sqlx::query_as::<_, RateLimitRow>(
+            "SELECT api_key, requests_per_minute, swaps_per_day, ws_connections, \
+             max_pools, pool_creations_per_day \
+             FROM rate_limits ORDER BY api_key",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        let limit = |v: Option<i64>| v.map(|n| u32::try_from(n).unwrap_or(u32::MAX));
+        Ok(rows
+            .into_iter()
+            .map(
+                |(
+                    api_key,
+                    requests_per_minute,
+                    swaps_per_day,
+                    ws_connections,
+                    max_pools,
+                    pool_creations_per_day,
+                )| RateLimitRecord {
+                    api_key,
+                    requests_per_minute: limit(requests_per_minute),
+                    swaps_per_day: limit(swaps_per_day),
+                    ws_connections: limit(ws_connections),
+                    max_pools: limit(max_pools),
+                    pool_creations_per_day: limit(pool_creations_per_day),
+                },
+            )
+            .collect())
+    }
+
+    /// Inserts or replaces the quota of `record.api_key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn save_rate_limit(&self, record: &RateLimitRecord) -> Result<(), GatewayError> {
+        sqlx::query(
+            "INSERT INTO rate_limits (api_key, requests_per_minute, swaps_per_day, ws_connections, \
+             max_pools, pool_creations_per_day, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, NOW()) \
+             ON CONFLICT (api_key) DO UPDATE SET \
+             requests_per_minute = EXCLUDED.requests_per_minute, \
+             swaps_per_day = EXCLUDED.swaps_per_day, \
+             ws_connections = EXCLUDED.ws_connections, \
+             max_pools = EXCLUDED.max_pools, \
+             pool_creations_per_day = EXCLUDED.pool_creations_per_day, \
+             updated_at = EXCLUDED.updated_at",
+        )
+        .bind(&record.api_key)
+        .bind(record.requests_per_minute.map(i64::from))
+        .bind(record.swaps_per_day.map(i64::from))
+        .bind(record.ws_connections.map(i64::from))
+        .bind(record.max_pools.map(i64::from))
+        .bind(record.pool_creations_per_day.map(i64::from))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Returns the ID of the newest event in the log, or `None` when the
+    /// log is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn latest_event_id(&self) -> Result<Option<i64>, GatewayError> {
+        sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(id) FROM events")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| GatewayError::PersistenceError(e.to_string()))
+    }
+
+    /// Returns the last event ID acknowledged by consumer `name`, or
+    /// `None` for a consumer that has never acknowledged.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn load_consumer_offset(&self, name: &str) -> Result<Option<i64>, GatewayError> {
+        sqlx::query_scalar::<_, i64>("SELECT last_event_id FROM consumer_offsets WHERE name = $1")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| GatewayError::PersistenceError(e.to_string()))
+    }
+
+    /// Advances consumer `name` to `last_event_id` and returns its offset.
+    /// The offset never moves backwards, so a stale or repeated ack is a
+    /// no-op.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn ack_consumer_offset(
+        &self,
+        name: &str,
+        last_event_id: i64,
+    ) -> Result<i64, GatewayError> {
+        sqlx::query_scalar::<_, i64>(
+            "INSERT INTO consumer_offsets (name, last_event_id, updated_at) \
+             VALUES ($1, $2, NOW()) \
+             ON CONFLICT (name) DO UPDATE SET \
+             last_event_id = GREATEST(consumer_offsets.last_event_id, EXCLUDED.last_event_id), \
+             updated_at = EXCLUDED.updated_at \
+             RETURNING last_event_id",
+        )
+        .bind(name)
+        .bind(last_event_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))
+    }
+
+    /// Loads every pool webhook, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn load_webhooks(&self) -> Result<Vec<WebhookRecord>, GatewayError> {
+        let rows = sqlx::query_as::<_, WebhookRow>(
+            "SELECT id, pool_id, url, event_types, min_amount, secret, created_at \
+             FROM pool_webhooks ORDER BY created_at ASC",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(id, pool_id, url, event_types, min_amount, secret, created_at)| WebhookRecord {
+                    id,
+                    pool_id,
+                    url,
+                    event_types,
+                    min_amount,
+                    secret,
+                    created_at,
+                },
+            )
+            .collect())
+    }
+
+    /// Inserts a pool webhook.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn save_webhook(&self, record: &WebhookRecord) -> Result<(), GatewayError> {
+        sqlx::query(
+            "INSERT INTO pool_webhooks (id, pool_id, url, event_types, min_amount, secret, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(record.id)
+        .bind(record.pool_id)
+        .bind(&record.url)
+        .bind(&record.event_types)
+        .bind(&record.min_amount)
+        .bind(&record.secret)
+        .bind(record.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Deletes webhook `id`, or every webhook of `pool_id` when `id` is
+    /// `None`. Returns the number of rows deleted.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn delete_webhooks(
+        &self,
+        pool_id: Uuid,
+        id: Option<Uuid>,
+    ) -> Result<u64, GatewayError> {
+        let result = sqlx::query(
+            "DELETE FROM pool_webhooks WHERE pool_id = $1 AND ($2::uuid IS NULL OR id = $2)",
+        )
+        .bind(pool_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Loads every pool metadata schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn load_metadata_schemas(&self) -> Result<Vec<MetadataSchemaRecord>, GatewayError> {
+        let rows = sqlx::query_as::<_, (String, String, serde_json::Value, DateTime<Utc>)>(
+            "SELECT scope, name, schema, updated_at FROM metadata_schemas ORDER BY scope, name",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(scope, name, schema, updated_at)| MetadataSchemaRecord {
+                scope,
+                name,
+                schema,
+                updated_at,
+            })
+            .collect())
+    }
+
+    /// Inserts or replaces a pool metadata schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn save_metadata_schema(
+        &self,
+        record: &MetadataSchemaRecord,
+    ) -> Result<(), GatewayError> {
+        sqlx::query(
+            "INSERT INTO metadata_schemas (scope, name, schema, updated_at) \
+             VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (scope, name) DO UPDATE SET \
+             schema = EXCLUDED.schema, updated_at = EXCLUDED.updated_at",
+        )
+        .bind(&record.scope)
+        .bind(&record.name)
+        .bind(&record.schema)
+        .bind(record.updated_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Deletes a pool metadata schema. Returns `true` if it existed.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn delete_metadata_schema(
+        &self,
+        scope: &str,
+        name: &str,
+    ) -> Result<bool, GatewayError> {
+        let result = sqlx::query("DELETE FROM metadata_schemas WHERE scope = $1 AND name = $2")
+            .bind(scope)
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Loads the response stored for `idempotency_key` of `client_id`, if
+    /// it was stored at or after `since`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn load_idempotency_record(
+        &self,
+        client_id: &str,
+        idempotency_key: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Option<IdempotencyRecord>, GatewayError> {
+        let row = sqlx::query_as::<_, IdempotencyRow>(
+            "SELECT client_id, idempotency_key, fingerprint, status, content_type, body, \
+             created_at FROM idempotency_keys \
+             WHERE client_id = $1 AND idempotency_key = $2 AND created_at >= $3",
+        )
+        .bind(client_id)
+        .bind(idempotency_key)
+        .bind(since)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        Ok(row.map(
+            |(client_id, idempotency_key, fingerprint, status, content_type, body, created_at)| {
+                IdempotencyRecord {
+                    client_id,
+                    idempotency_key,
+                    fingerprint,
+                    status: u16::try_from(status).unwrap_or_default(),
+                    content_type,
+                    body,
+                    created_at,
+                }
+            },
+        ))
+    }
+
+    /// Inserts the response to an idempotent request, replacing an expired
+    /// one stored under the same key.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn save_idempotency_record(
+        &self,
+        record: &IdempotencyRecord,
+    ) -> Result<(), GatewayError> {
+        sqlx::query(
+            "INSERT INTO idempotency_keys (client_id, idempotency_key, fingerprint, status, \
+             content_type, body, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7) \
+             ON CONFLICT (client_id, idempotency_key) DO UPDATE SET \
+             fingerprint = EXCLUDED.fingerprint, status = EXCLUDED.status, \
+             content_type = EXCLUDED.content_type, body = EXCLUDED.body, \
+             created_at = EXCLUDED.created_at",
+        )
+        .bind(&record.client_id)
+        .bind(&record.idempotency_key)
+        .bind(&record.fingerprint)
+        .bind(i16::try_from(record.status).unwrap_or(i16::MAX))
+        .bind(&record.content_type)
+        .bind(&record.body)
+        .bind(record.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Deletes idempotency records stored before `before`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn delete_idempotency_records_before(
+        &self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, GatewayError> {
+        let result = sqlx::query("DELETE FROM idempotency_keys WHERE created_at < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Merges candle deltas into the `candles` table in one transaction.
+    ///
+    /// A new bucket is inserted as is. For an existing bucket the open is
+    /// kept, the high and low are widened, the close is replaced, and
+    /// volumes and trade counts are added.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure;
+    /// nothing is written in that case.
+    pub async fn upsert_candles(&self, candles: &[CandleRecord]) -> Result<(), GatewayError> {
+        let db_err = |e: sqlx::Error| GatewayError::PersistenceError(e.to_string());
+        let mut tx = self.pool.begin().await.map_err(db_err)?;
+        for candle in candles {
+            sqlx::query(
+                "INSERT INTO candles (pool_id, timeframe, bucket_start, open, high, low, close, \
+                 base_volume, quote_volume, trades, updated_at) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8::NUMERIC, $9::NUMERIC, $10, NOW()) \
+                 ON CONFLICT (pool_id, timeframe, bucket_start) DO UPDATE SET \
+                 high = GREATEST(candles.high, EXCLUDED.high), \
+                 low = LEAST(candles.low, EXCLUDED.low), \
+                 close = EXCLUDED.close, \
+                 base_volume = candles.base_volume + EXCLUDED.base_volume, \
+                 quote_volume = candles.quote_volume + EXCLUDED.quote_volume, \
+                 trades = candles.trades + EXCLUDED.trades, \
+                 updated_at = EXCLUDED.updated_at",
+            )
+            .bind(candle.pool_id)
+            .bind(&candle.timeframe)
+            .bind(candle.bucket_start)
+            .bind(candle.open)
+            .bind(candle.high)
+            .bind(candle.low)
+            .bind(candle.close)
+            .bind(candle.base_volume.to_string())
+            .bind(candle.quote_volume.to_string())
+            .bind(i64::try_from(candle.trades).unwrap_or(i64::MAX))
+            .execute(&mut *tx)
+            .await
+            .map_err(db_err)?;
+        }
+        tx.commit().await.map_err(db_err)?;
+
+        Ok(())
+    }
+
+    /// Loads up to `limit` of a pool's candles for `timeframe` starting
+    /// in `[from, to)`, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn load_candles(
+        &self,
+        pool_id: Uuid,
+        timeframe: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: u32,
+    ) -> Result<Vec<CandleRecord>, GatewayError> {
+        let rows = sqlx::query_as::<_, CandleRow>(
+            "SELECT pool_id, timeframe, bucket_start, open, high, low, close, \
+             base_volume::TEXT, quote_volume::TEXT, trades FROM candles \
+             WHERE pool_id = $1 AND timeframe = $2 AND bucket_start >= $3 AND bucket_start < $4 \
+             ORDER BY bucket_start ASC LIMIT $5",
+        )
+        .bind(pool_id)
+        .bind(timeframe)
+        .bind(from)
+        .bind(to)
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        Ok(rows.into_iter().map(candle_from_row).collect())
+    }
+
+    /// Loads every pool's candles for `timeframe` starting at or after
+    /// `since`, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn load_candles_since(
+        &self,
+        timeframe: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<CandleRecord>, GatewayError> {
+        let rows = sqlx::query_as::<_, CandleRow>(
+            "SELECT pool_id, timeframe, bucket_start, open, high, low, close, \
+             base_volume::TEXT, quote_volume::TEXT, trades FROM candles \
+             WHERE timeframe = $1 AND bucket_start >= $2 \
+             ORDER BY bucket_start ASC",
+        )
+        .bind(timeframe)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        Ok(rows.into_iter().map(candle_from_row).collect())
+    }
+
+    /// Appends served quotes to the `quotes` audit table in one
+    /// transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure;
+    /// nothing is written in that case.
+    pub async fn save_quotes(&self, quotes: &[QuoteRecord]) -> Result<(), GatewayError> {
+        let db_err = |e: sqlx::Error| GatewayError::PersistenceError(e.to_string());
+        let mut tx = self.pool.begin().await.map_err(db_err)?;
+        for quote in quotes {
+            sqlx::query(
+                "INSERT INTO quotes (pool_id, client_id, token_in, token_out, amount_in, amount_out, \
+                 fee, spot_price, execution_price, price_impact_bps, pool_version, quoted_at) \
+                 VALUES ($1, $2, $3, $4, $5::NUMERIC, $6::NUMERIC, $7::NUMERIC, $8, $9, $10, $11, $12)",
+            )
+            .bind(quote.pool_id)
+            .bind(quote.client_id.as_deref())
+            .bind(&quote.token_in)
+            .bind(&quote.token_out)
+            .bind(quote.amount_in.to_string())
+            .bind(quote.amount_out.to_string())
+            .bind(quote.fee.to_string())
+            .bind(quote.spot_price)
+            .bind(quote.execution_price)
+            .bind(quote.price_impact_bps)
+            .bind(i64::try_from(quote.pool_version).unwrap_or(i64::MAX))
+            .bind(quote.quoted_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_err)?;
+        }
+        tx.commit().await.map_err(db_err)?;
+        Ok(())
+    }
+
+    /// Loads up to `limit` quotes served in `[from, to)`, newest first,
+    /// optionally restricted to one client and one pool.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] on database failure.
+    pub async fn load_quotes(
+        &self,
+        client_id: Option<&str>,
+        pool_id: Option<Uuid>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: u32,
+    ) -> Result<Vec<StoredQuote>, GatewayError> {
+        let rows = sqlx::query_as::<_, QuoteRow>(
+            "SELECT id, pool_id, client_id, token_in, token_out, amount_in::TEXT, amount_out::TEXT, \
+             fee::TEXT, spot_price, execution_price, price_impact_bps, pool_version, quoted_at \
+             FROM quotes WHERE ($1::TEXT IS NULL OR client_id = $1) \
+             AND ($2::UUID IS NULL OR pool_id = $2) AND quoted_at >= $3 AND quoted_at < $4 \
+             ORDER BY quoted_at DESC, id DESC LIMIT $5",
+        )
+        .bind(client_id)
+        .bind(pool_id)
+        .bind(from)
+        .bind(to)
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+
+        Ok(rows.into_iter().map(quote_from_row).collect())
+    }
+}
+
+/// Advisory lock key serializing inserts into `events`.
+const EVENT_LOG_LOCK: i64 = 0x6879_6472_615f_6576;
+
+/// Takes the event log's insert lock for the rest of the transaction, so
+/// event row IDs are committed in order: a row only becomes visible once
+/// every row with a lower ID is committed or rolled back.
+async fn lock_event_log(conn: &mut sqlx::PgConnection) -> Result<(), GatewayError> {
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(EVENT_LOG_LOCK)
+        .execute(conn)
+        .await
+        .map_err(|e| GatewayError::PersistenceError(e.to_string()))?;
+    Ok(())
+}
+
+/// Maps a raw snapshot row into a [`PoolSnapshot`].
+fn snapshot_from_row(
+    (id, pool_id, pool_type, config_json, state_json, metadata_json, snapshot_at): SnapshotRow,
+) -> PoolSnapshot {
+    PoolSnapshot {
+        id,
+        pool_id,
+        pool_type,
+        config_json,
+        state_json,
+        metadata_json,
+        snapshot_at,
+    }
+}
+
+/// Maps a raw candle row into a [`CandleRecord`]. Volumes that do not fit
+/// a `u128` read as `u128::MAX`.
+fn candle_from_row(
+    (pool_id, timeframe, bucket_start, open, high, low, close, base_volume, quote_volume, trades): CandleRow,
+) -> CandleRecord {
+    let volume = |v: String| v.parse().unwrap_or(u128::MAX);
+    CandleRecord {
+        pool_id,
+        timeframe,
+        bucket_start,
+        open,
+        high,
+        low,
+        close,
+        base_volume: volume(base_volume),
+        quote_volume: volume(quote_volume),
+        trades: u64::try_from(trades).unwrap_or(0),
+    }
+}
+
+/// Maps a raw quote row into a [`StoredQuote`]. Amounts that do not fit a
+/// `u128` read as `u128::MAX`.
+fn quote_from_row(
+    (
+        id,
+        pool_id,
+        client_id,
+        token_in,
+        token_out,
+        amount_in,
+        amount_out,
+        fee,
+        spot_price,
+        execution_price,
+        price_impact_bps,
+        pool_version,
+        quoted_at,
+    ): QuoteRow,
+) -> StoredQuote {
+    let amount = |v: String| v.parse().unwrap_or(u128::MAX);
+    StoredQuote {
+        id,
+        quote: QuoteRecord {
+            pool_id,
+            client_id,
+            token_in,
+            token_out,
+            amount_in: amount(amount_in),
+            amount_out: amount(amount_out),
+            fee: amount(fee),
+            spot_price,
+            execution_price,
+            price_impact_bps,
+            pool_version: u64::try_from(pool_version).unwrap_or(0),
+            quoted_at,
+        },
+    }
+}
+
+/// Database access for tests.
+#[cfg(test)]
+#[allow(clippy::panic)]
+pub(crate) mod testing {
+    use super::*;
+
+    /// Connects to `TEST_DATABASE_URL` and applies the migrations, or
+    /// returns `None` when it is unset so database tests are skipped.
+    pub(crate) async fn persistence() -> Option<PostgresPersistence> {
+        let url = std::env::var("TEST_DATABASE_URL").ok()?;
+        let Ok(pool) = PgPoolOptions::new().max_connections(4).connect(&url).await else {
+            panic!("TEST_DATABASE_URL is unreachable");
+        };
+        if let Err(e) = sqlx::migrate!("./migrations").run(&pool).await {
+            panic!("migrations failed: {e}");
+        }
+        Some(PostgresPersistence::new(pool))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::PoolId;
+
+    #[tokio::test]
+    async fn tailing_waits_for_rows_committed_late() {
+        let Some(db) = testing::persistence().await else {
+            return;
+        };
+        let pool_id = PoolId::new();
+        let Ok(payload) = serde_json::to_value(PoolEvent::PoolRemoved {
+            pool_id,
+            timestamp: Utc::now(),
+        }) else {
+            panic!("event did not serialize");
+        };
+
+        // An insert still uncommitted holds the lower ID
+        let Ok(mut held) = db.pool.begin().await else {
+            panic!("transaction did not start");
+        };
+        let Ok(()) = lock_event_log(&mut held).await else {
+            panic!("lock not taken");
+        };
+        let Ok(early) = sqlx::query_scalar::<_, i64>(
+            "INSERT INTO events (pool_id, event_type, payload, schema_version) \
+             VALUES ($1, 'pool_removed', $2, $3) RETURNING id",
+        )
+        .bind(pool_id.as_uuid())
+        .bind(&payload)
+        .bind(i32::try_from(EVENT_SCHEMA_VERSION).unwrap_or(i32::MAX))
+        .fetch_one(&mut *held)
+        .await
+        else {
+            panic!("insert failed");
+        };
+        let writer = tokio::spawn({
+            let db = db.clone();
+            let payload = payload.clone();
+            async move {
+                db.save_event(*pool_id.as_uuid(), "pool_removed", &payload)
+                    .await
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(
+            !writer.is_finished(),
+            "a later insert waits for the earlier one"
+        );
+        let Ok(()) = held.commit().await else {
+            panic!("commit failed");
+        };
+        let Ok(Ok(late)) = writer.await else {
+            panic!("later insert failed");
+        };
+        assert!(late > early);
+
+        let Ok(tail) = db.load_events_after_id(early - 1, 1_000).await else {
+            panic!("tail failed");
+        };
+        let ids: Vec<i64> = tail
+            .iter()
+            .filter(|event| event.pool_id == *pool_id.as_uuid())
+            .map(|event| event.id)
+            .collect();
+        assert_eq!(ids, [early, late]);
+    }
+
+    #[tokio::test]
+    async fn event_pages_filter_by_pool_and_type() {
+        let Some(db) = testing::persistence().await else {
+            return;
+        };
+        let (a, b) = (PoolId::new(), PoolId::new());
+        let events = [
+            PoolEvent::PoolArchived {
+                pool_id: a,
+                idle_since: Utc::now(),
+                timestamp: Utc::now(),
+            },
+            PoolEvent::PoolRemoved {
+                pool_id: a,
+                timestamp: Utc::now(),
+            },
+            PoolEvent::PoolRemoved {
+                pool_id: b,
+                timestamp: Utc::now(),
+            },
+        ];
+        let mut ids = Vec::new();
+        for event in &events {
+            let Ok(payload) = serde_json::to_value(event) else {
+                panic!("event did not serialize");
+            };
+            let Ok(id) = db
+                .save_event(*event.pool_id().as_uuid(), event.event_type_str(), &payload)
+                .await
+            else {
+                panic!("insert failed");
+            };
+            ids.push(id);
+        }
+        let [archived, removed_a, removed_b] = ids[..] else {
+            panic!("three events saved");
+        };
+        let page = |after, pool_id: Option<PoolId>, event_type, limit| {
+            db.load_event_page(after, pool_id.map(|p| *p.as_uuid()), event_type, limit)
+        };
+        let ids_of = |events: Vec<StoredEvent>| -> Vec<i64> {
+            events
+                .iter()
+                .filter(|e| e.pool_id == *a.as_uuid() || e.pool_id == *b.as_uuid())
+                .map(|e| e.id)
+                .collect()
+        };
+
+        let Ok(first) = page(archived - 1, Some(a), None, 1).await else {
+            panic!("page failed");
+        };
+        assert_eq!(ids_of(first), [archived]);
+        let Ok(next) = page(archived, Some(a), None, 10).await else {
+            panic!("page failed");
+        };
+        assert_eq!(ids_of(next), [removed_a], "the cursor skips what was read");
+        let Ok(removals) = page(archived - 1, None, Some("pool_removed"), 1_000).await else {
+            panic!("page failed");
+        };
+        assert_eq!(ids_of(removals), [removed_a, removed_b]);
+    }
 }
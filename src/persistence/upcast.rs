@@ -0,0 +1,283 @@
+//! Event payload upcasting.
+//!
+//! Stored events carry the `schema_version` they were written with. When
+//! `PoolEvent` fields change, register an upcaster that migrates payloads
+//! from version `n` to `n + 1`; [`UpcasterRegistry::upcast`] chains them
+//! so historical events always deserialize with the current shape.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::models::StoredEvent;
+use crate::domain::pool_event::EVENT_SCHEMA_VERSION;
+use crate::error::GatewayError;
+
+/// Migrates a payload of a given event type one schema version forward.
+pub type UpcastFn =
+    Box<dyn Fn(&str, serde_json::Value) -> Result<serde_json::Value, String> + Send + Sync>;
+
+/// Ordered set of single-step payload migrations.
+pub struct UpcasterRegistry {
+    target_version: u32,
+    steps: BTreeMap<u32, UpcastFn>,
+}
+
+impl UpcasterRegistry {
+    /// Creates an empty registry that upcasts to `target_version`.
+    #[must_use]
+    pub fn new(target_version: u32) -> Self {
+        Self {
+            target_version,
+            steps: BTreeMap::new(),
+        }
+    }
+
+    /// Registers the migration from `from_version` to `from_version + 1`.
+    ///
+    /// Replaces any previously registered step for the same version.
+    pub fn register(&mut self, from_version: u32, step: UpcastFn) {
+        self.steps.insert(from_version, step);
+    }
+
+    /// Returns the schema version events are upcast to.
+    #[must_use]
+    pub fn target_version(&self) -> u32 {
+        self.target_version
+    }
+
+    /// Migrates `event` to the target schema version.
+    ///
+    /// Events already at (or above) the target version are returned
+    /// unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PersistenceError`] if a required step is
+    /// missing or a step rejects the payload.
+    pub fn upcast(&self, mut event: StoredEvent) -> Result<StoredEvent, GatewayError> {
+        let mut version = u32::try_from(event.schema_version).unwrap_or(0);
+        while version < self.target_version {
+            let step = self.steps.get(&version).ok_or_else(|| {
+                GatewayError::PersistenceError(format!(
+                    "no upcaster from schema v{version} for event {}",
+                    event.id
+                ))
+            })?;
+            event.payload = step(&event.event_type, event.payload).map_err(|e| {
+                GatewayError::PersistenceError(format!(
+                    "upcasting event {} from v{version}: {e}",
+                    event.id
+                ))
+            })?;
+            version = version.saturating_add(1);
+        }
+        event.schema_version = i32::try_from(version).unwrap_or(i32::MAX);
+        Ok(event)
+    }
+}
+
+impl Default for UpcasterRegistry {
+    /// Registry targeting [`EVENT_SCHEMA_VERSION`] with all built-in steps.
+    fn default() -> Self {
+        let mut registry = Self::new(EVENT_SCHEMA_VERSION);
+        registry.register(1, Box::new(v1_to_v2));
+        registry.register(2, Box::new(v2_to_v3));
+        registry.register(3, Box::new(v3_to_v4));
+        registry.register(4, Box::new(v4_to_v5));
+        registry.register(5, Box::new(v5_to_v6));
+        registry.register(6, Box::new(v6_to_v7));
+        registry
+    }
+}
+
+/// v1 → v2: adds the fields needed to replay events.
+///
+/// Version 1 events did not record the creation config, swap direction,
+/// or LP delta, so they are filled with values replay treats as unknown.
+fn v1_to_v2(event_type: &str, mut payload: serde_json::Value) -> Result<serde_json::Value, String> {
+    let obj = payload
+        .as_object_mut()
+        .ok_or_else(|| "payload is not an object".to_string())?;
+    match event_type {
+        "pool_created" => {
+            obj.entry("config").or_insert(serde_json::Value::Null);
+        }
+        "swap_executed" => {
+            obj.entry("token_in").or_insert_with(|| "".into());
+            obj.entry("swap_kind").or_insert_with(|| "exact_in".into());
+        }
+        "liquidity_changed" => {
+            obj.entry("liquidity_delta").or_insert_with(|| "0".into());
+        }
+        _ => {}
+    }
+    Ok(payload)
+}
+
+/// v2 → v3: adds position references to liquidity and fee events.
+///
+/// Changes recorded before positions existed belong to no position.
+fn v2_to_v3(event_type: &str, mut payload: serde_json::Value) -> Result<serde_json::Value, String> {
+    let obj = payload
+        .as_object_mut()
+        .ok_or_else(|| "payload is not an object".to_string())?;
+    match event_type {
+        "liquidity_changed" => {
+            obj.entry("position_id").or_insert(serde_json::Value::Null);
+            obj.entry("range").or_insert(serde_json::Value::Null);
+        }
+        "fees_collected" => {
+            obj.entry("position_id").or_insert(serde_json::Value::Null);
+        }
+        _ => {}
+    }
+    Ok(payload)
+}
+
+/// v3 → v4: records which client caused command-driven events.
+///
+/// Earlier events carry no actor.
+fn v3_to_v4(event_type: &str, mut payload: serde_json::Value) -> Result<serde_json::Value, String> {
+    let obj = payload
+        .as_object_mut()
+        .ok_or_else(|| "payload is not an object".to_string())?;
+    if matches!(
+        event_type,
+        "swap_executed" | "liquidity_changed" | "fees_collected"
+    ) {
+        obj.entry("actor").or_insert(serde_json::Value::Null);
+    }
+    Ok(payload)
+}
+
+/// v4 → v5: records fee-tier rebates on swaps.
+///
+/// Swaps before the fee-tier program earned no rebate.
+fn v4_to_v5(event_type: &str, mut payload: serde_json::Value) -> Result<serde_json::Value, String> {
+    let obj = payload
+        .as_object_mut()
+        .ok_or_else(|| "payload is not an object".to_string())?;
+    if event_type == "swap_executed" {
+        obj.entry("fee_rebate").or_insert_with(|| "0".into());
+    }
+    Ok(payload)
+}
+
+/// v5 → v6: records pool metadata on creation.
+///
+/// Pools created before metadata existed have none.
+fn v5_to_v6(event_type: &str, mut payload: serde_json::Value) -> Result<serde_json::Value, String> {
+    let obj = payload
+        .as_object_mut()
+        .ok_or_else(|| "payload is not an object".to_string())?;
+    if event_type == "pool_created" {
+        obj.entry("metadata").or_insert(serde_json::Value::Null);
+    }
+    Ok(payload)
+}
+
+/// v6 → v7: records the owner on creation.
+///
+/// Pools created before ownership existed are unowned.
+fn v6_to_v7(event_type: &str, mut payload: serde_json::Value) -> Result<serde_json::Value, String> {
+    let obj = payload
+        .as_object_mut()
+        .ok_or_else(|| "payload is not an object".to_string())?;
+    if event_type == "pool_created" {
+        obj.entry("owner").or_insert(serde_json::Value::Null);
+    }
+    Ok(payload)
+}
+
+impl fmt::Debug for UpcasterRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UpcasterRegistry")
+            .field("target_version", &self.target_version)
+            .field("steps", &self.steps.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn stored(version: i32, payload: serde_json::Value) -> StoredEvent {
+        StoredEvent {
+            id: 7,
+            pool_id: uuid::Uuid::new_v4(),
+            event_type: "swap_executed".to_string(),
+            schema_version: version,
+            payload,
+            created_at: Utc::now(),
+            imported: false,
+            pool_version: None,
+        }
+    }
+
+    #[test]
+    fn current_version_is_unchanged() {
+        let registry = UpcasterRegistry::default();
+        let version = i32::try_from(EVENT_SCHEMA_VERSION).unwrap_or(i32::MAX);
+        let event = stored(version, serde_json::json!({"a": 1}));
+        let Ok(out) = registry.upcast(event) else {
+            panic!("upcast failed");
+        };
+        assert_eq!(out.payload, serde_json::json!({"a": 1}));
+    }
+
+    #[test]
+    fn chains_steps_to_target() {
+        let mut registry = UpcasterRegistry::new(3);
+        registry.register(
+            1,
+            Box::new(|_, mut p| {
+                if let Some(obj) = p.as_object_mut() {
+                    obj.insert("fee".to_string(), serde_json::json!("0"));
+                }
+                Ok(p)
+            }),
+        );
+        registry.register(
+            2,
+            Box::new(|event_type, mut p| {
+                if let Some(obj) = p.as_object_mut() {
+                    obj.insert("kind".to_string(), serde_json::json!(event_type));
+                }
+                Ok(p)
+            }),
+        );
+
+        let Ok(out) = registry.upcast(stored(1, serde_json::json!({}))) else {
+            panic!("upcast failed");
+        };
+        assert_eq!(out.schema_version, 3);
+        assert_eq!(out.payload.get("fee"), Some(&serde_json::json!("0")));
+        assert_eq!(
+            out.payload.get("kind"),
+            Some(&serde_json::json!("swap_executed"))
+        );
+    }
+
+    #[test]
+    fn v1_swap_gains_replay_fields() {
+        let registry = UpcasterRegistry::default();
+        let Ok(out) = registry.upcast(stored(1, serde_json::json!({"amount_in": "5"}))) else {
+            panic!("upcast failed");
+        };
+        assert_eq!(out.payload.get("token_in"), Some(&serde_json::json!("")));
+        assert_eq!(
+            out.payload.get("swap_kind"),
+            Some(&serde_json::json!("exact_in"))
+        );
+        assert_eq!(out.payload.get("fee_rebate"), Some(&serde_json::json!("0")));
+    }
+
+    #[test]
+    fn missing_step_is_an_error() {
+        let registry = UpcasterRegistry::new(2);
+        assert!(registry.upcast(stored(1, serde_json::json!({}))).is_err());
+    }
+}
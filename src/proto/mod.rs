@@ -0,0 +1,680 @@
+//! Protobuf encoding for pool events.
+//!
+//! The versioned wire schema lives in `proto/pool_events.proto`; the
+//! matching Rust types are in [`v1`]. [`encode_event`] converts a domain
+//! [`PoolEvent`] into its binary form for consumers that need a stable
+//! cross-language contract instead of serde JSON.
+
+pub mod v1;
+
+use prost::Message;
+
+use crate::domain::PoolEvent;
+use crate::domain::order_book::OrderSide;
+use crate::domain::pool_entry::PoolStatus;
+use crate::domain::pool_event::{LiquidityChangeType, PriceChangeReason};
+use crate::domain::pool_operation::SwapKind;
+
+pub use crate::domain::pool_event::EVENT_SCHEMA_VERSION;
+
+impl From<&PoolEvent> for v1::PoolEvent {
+    fn from(event: &PoolEvent) -> Self {
+        use v1::pool_event::Event;
+
+        let (timestamp, payload) = match event {
+            PoolEvent::PoolCreated {
+                pool_type,
+                token_a,
+                token_b,
+                fee_tier,
+                config,
+                metadata,
+                timestamp,
+                ..
+            } => (
+                timestamp,
+                Event::PoolCreated(v1::PoolCreated {
+                    pool_type: pool_type.clone(),
+                    token_a: token_a.clone(),
+                    token_b: token_b.clone(),
+                    fee_tier: *fee_tier,
+                    config_json: config.to_string(),
+                    metadata_json: metadata.to_string(),
+                }),
+            ),
+            PoolEvent::PoolOwnershipTransferred {
+                previous_owner,
+                new_owner,
+                timestamp,
+                ..
+            } => (
+                timestamp,
+                Event::PoolOwnershipTransferred(v1::PoolOwnershipTransferred {
+                    previous_owner: previous_owner.clone().unwrap_or_default(),
+                    new_owner: new_owner.clone(),
+                }),
+            ),
+            PoolEvent::PoolMetadataUpdated {
+                metadata,
+                timestamp,
+                ..
+            } => (
+                timestamp,
+                Event::PoolMetadataUpdated(v1::PoolMetadataUpdated {
+                    metadata_json: metadata.to_string(),
+                }),
+            ),
+            PoolEvent::PoolRestored {
+                snapshot_id,
+                snapshot_at,
+                archived_snapshot_id,
+                timestamp,
+                ..
+            } => (
+                timestamp,
+                Event::PoolRestored(v1::PoolRestored {
+                    snapshot_id: *snapshot_id,
+                    snapshot_at_micros: snapshot_at.timestamp_micros(),
+                    archived_snapshot_id: *archived_snapshot_id,
+                }),
+            ),
+            PoolEvent::PoolStatusChanged {
+                previous_status,
+                status,
+                timestamp,
+                ..
+            } => (
+                timestamp,
+                Event::PoolStatusChanged(v1::PoolStatusChanged {
+                    previous_status: pool_status(*previous_status),
+                    status: pool_status(*status),
+                }),
+            ),
+            PoolEvent::PoolRemoved { timestamp, .. } => {
+                (timestamp, Event::PoolRemoved(v1::PoolRemoved {}))
+            }
+            PoolEvent::PoolArchived {
+                idle_since,
+                timestamp,
+                ..
+            } => (
+                timestamp,
+                Event::PoolArchived(v1::PoolArchived {
+                    idle_since_micros: idle_since.timestamp_micros(),
+                }),
+            ),
+            PoolEvent::SwapExecuted {
+                command_id,
+                amount_in,
+                amount_out,
+                fee,
+                fee_rebate,
+                new_price,
+                price_change_bps,
+                token_in,
+                swap_kind,
+                timestamp,
+                ..
+            } => (
+                timestamp,
+                Event::SwapExecuted(v1::SwapExecuted {
+                    command_id: command_id.clone(),
+                    amount_in: amount_in.clone(),
+                    amount_out: amount_out.clone(),
+                    fee: fee.clone(),
+                    new_price: new_price.clone(),
+                    price_change_bps: *price_change_bps,
+                    token_in: token_in.clone(),
+                    swap_kind: match swap_kind {
+                        SwapKind::ExactIn => v1::SwapKind::ExactIn,
+                        SwapKind::ExactOut => v1::SwapKind::ExactOut,
+                    } as i32,
+                    fee_rebate: fee_rebate.clone(),
+                }),
+            ),
+            PoolEvent::LiquidityChanged {
+                change_type,
+                amount_a,
+                amount_b,
+                new_total_liquidity,
+                liquidity_delta,
+                position_id,
+                timestamp,
+                ..
+            } => (
+                timestamp,
+                Event::LiquidityChanged(v1::LiquidityChanged {
+                    change_type: match change_type {
+                        LiquidityChangeType::Add => v1::LiquidityChangeType::Add,
+                        LiquidityChangeType::Remove => v1::LiquidityChangeType::Remove,
+                    } as i32,
+                    amount_a: amount_a.clone(),
+                    amount_b: amount_b.clone(),
+                    new_total_liquidity: new_total_liquidity.clone(),
+                    liquidity_delta: liquidity_delta.clone(),
+                    position_id: position_id.map(|id| id.to_string()).unwrap_or_default(),
+                }),
+            ),
+            PoolEvent::FeesCollected {
+                fee_token_a,
+                fee_token_b,
+                position_id,
+                timestamp,
+                ..
+            } => (
+                timestamp,
+                Event::FeesCollected(v1::FeesCollected {
+                    fee_token_a: fee_token_a.clone(),
+                    fee_token_b: fee_token_b.clone(),
+                    position_id: position_id.map(|id| id.to_string()).unwrap_or_default(),
+                }),
+            ),
+            PoolEvent::RewardsClaimed {
+                reward_token,
+                amount,
+                position_ids,
+                timestamp,
+                ..
+            } => (
+                timestamp,
+                Event::RewardsClaimed(v1::RewardsClaimed {
+                    reward_token: reward_token.clone(),
+                    amount: amount.clone(),
+                    position_ids: position_ids.iter().map(ToString::to_string).collect(),
+                }),
+            ),
+            PoolEvent::OrderPlaced {
+                order_id,
+                side,
+                price,
+                quantity,
+                timestamp,
+                ..
+            } => (
+                timestamp,
+                Event::OrderPlaced(v1::OrderPlaced {
+                    order_id: order_id.to_string(),
+                    side: order_side(*side),
+                    price: price.clone(),
+                    quantity: quantity.clone(),
+                }),
+            ),
+            PoolEvent::OrderCancelled {
+                order_id,
+                side,
+                price,
+                quantity,
+                timestamp,
+                ..
+            } => (
+                timestamp,
+                Event::OrderCancelled(v1::OrderCancelled {
+                    order_id: order_id.to_string(),
+                    side: order_side(*side),
+                    price: price.clone(),
+                    quantity: quantity.clone(),
+                }),
+            ),
+            PoolEvent::OraclePriceUpdated {
+                old_price,
+                new_price,
+                timestamp,
+                ..
+            } => (
+                timestamp,
+                Event::OraclePriceUpdated(v1::OraclePriceUpdated {
+                    old_price: old_price.clone(),
+                    new_price: new_price.clone(),
+                }),
+            ),
+            PoolEvent::PriceUpdated {
+                old_price,
+                new_price,
+                price_change_bps,
+                reason,
+                timestamp,
+                ..
+            } => (
+                timestamp,
+                Event::PriceUpdated(v1::PriceUpdated {
+                    old_price: old_price.clone(),
+                    new_price: new_price.clone(),
+                    price_change_bps: *price_change_bps,
+                    reason: match reason {
+                        PriceChangeReason::SwapExecuted => v1::PriceChangeReason::SwapExecuted,
+                        PriceChangeReason::LiquidityAdded => v1::PriceChangeReason::LiquidityAdded,
+                        PriceChangeReason::LiquidityRemoved => {
+                            v1::PriceChangeReason::LiquidityRemoved
+                        }
+                        PriceChangeReason::OracleUpdated => v1::PriceChangeReason::OracleUpdated,
+                    } as i32,
+                }),
+            ),
+        };
+
+        Self {
+            schema_version: EVENT_SCHEMA_VERSION,
+            pool_id: event.pool_id().to_string(),
+            timestamp_micros: timestamp.timestamp_micros(),
+            actor: event.actor().unwrap_or_default().to_string(),
+            event: Some(payload),
+        }
+    }
+}
+
+/// Maps an order side to its protobuf enum value.
+fn order_side(side: OrderSide) -> i32 {
+    let side = match side {
+        OrderSide::Bid => v1::OrderSide::Bid,
+        OrderSide::Ask => v1::OrderSide::Ask,
+    };
+    side as i32
+}
+
+/// Maps a pool status to its protobuf enum value.
+fn pool_status(status: PoolStatus) -> i32 {
+    let status = match status {
+        PoolStatus::Active => v1::PoolStatus::Active,
+        PoolStatus::Paused => v1::PoolStatus::Paused,
+        PoolStatus::Draining => v1::PoolStatus::Draining,
+        PoolStatus::Archived => v1::PoolStatus::Archived,
+    };
+    status as i32
+}
+
+/// Encodes a domain event as a `hydra.gateway.events.v1.PoolEvent` message.
+#[must_use]
+pub fn encode_event(event: &PoolEvent) -> Vec<u8> {
+    v1::PoolEvent::from(event).encode_to_vec()
+}
+
+/// Decodes a protobuf-encoded event.
+///
+/// # Errors
+///
+/// Returns [`prost::DecodeError`] if `bytes` is not a valid
+/// `hydra.gateway.events.v1.PoolEvent`.
+pub fn decode_event(bytes: &[u8]) -> Result<v1::PoolEvent, prost::DecodeError> {
+    v1::PoolEvent::decode(bytes)
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::*;
+    use crate::domain::PoolId;
+    use chrono::Utc;
+
+    #[test]
+    fn swap_event_round_trips() {
+        let pool_id = PoolId::new();
+        let event = PoolEvent::SwapExecuted {
+            pool_id,
+            command_id: "cmd-1".to_string(),
+            amount_in: "340282366920938463463374607431768211455".to_string(),
+            amount_out: "990".to_string(),
+            fee: "3".to_string(),
+            fee_rebate: "0".to_string(),
+            new_price: "0.99".to_string(),
+            price_change_bps: -10,
+            token_in: "0xaaa".to_string(),
+            swap_kind: SwapKind::ExactOut,
+            actor: Some("bot-1".to_string()),
+            timestamp: Utc::now(),
+        };
+
+        let bytes = encode_event(&event);
+        let Ok(decoded) = decode_event(&bytes) else {
+            panic!("decode failed");
+        };
+        assert_eq!(decoded.schema_version, EVENT_SCHEMA_VERSION);
+        assert_eq!(decoded.pool_id, pool_id.to_string());
+        assert_eq!(decoded.actor, "bot-1");
+        let Some(v1::pool_event::Event::SwapExecuted(swap)) = decoded.event else {
+            panic!("expected swap payload");
+        };
+        assert_eq!(swap.amount_in, "340282366920938463463374607431768211455");
+        assert_eq!(swap.price_change_bps, -10);
+        assert_eq!(swap.swap_kind, v1::SwapKind::ExactOut as i32);
+    }
+
+    #[test]
+    fn restore_event_round_trips() {
+        let snapshot_at = Utc::now();
+        let event = PoolEvent::PoolRestored {
+            pool_id: PoolId::new(),
+            snapshot_id: 7,
+            snapshot_at,
+            archived_snapshot_id: 9,
+            actor: Some("admin".to_string()),
+            timestamp: Utc::now(),
+        };
+
+        let Ok(decoded) = decode_event(&encode_event(&event)) else {
+            panic!("decode failed");
+        };
+        let Some(v1::pool_event::Event::PoolRestored(restored)) = decoded.event else {
+            panic!("expected restore payload");
+        };
+        assert_eq!(restored.snapshot_id, 7);
+        assert_eq!(restored.snapshot_at_micros, snapshot_at.timestamp_micros());
+        assert_eq!(restored.archived_snapshot_id, 9);
+    }
+
+    /// Wire tag of each `event` variant. The match is exhaustive, so a new
+    /// variant must be given its tag here.
+    fn oneof_tag(event: &v1::pool_event::Event) -> u32 {
+        use v1::pool_event::Event;
+        match event {
+            Event::PoolCreated(_) => 10,
+            Event::PoolRemoved(_) => 11,
+            Event::SwapExecuted(_) => 12,
+            Event::LiquidityChanged(_) => 13,
+            Event::FeesCollected(_) => 14,
+            Event::PriceUpdated(_) => 15,
+            Event::PoolArchived(_) => 16,
+            Event::PoolMetadataUpdated(_) => 17,
+            Event::RewardsClaimed(_) => 18,
+            Event::OrderPlaced(_) => 19,
+            Event::OrderCancelled(_) => 20,
+            Event::PoolOwnershipTransferred(_) => 21,
+            Event::OraclePriceUpdated(_) => 22,
+            Event::PoolRestored(_) => 23,
+            Event::PoolStatusChanged(_) => 24,
+        }
+    }
+
+    /// One payload of every `event` variant, in tag order.
+    fn every_event() -> Vec<v1::pool_event::Event> {
+        use v1::pool_event::Event;
+        vec![
+            Event::PoolCreated(v1::PoolCreated::default()),
+            Event::PoolRemoved(v1::PoolRemoved::default()),
+            Event::SwapExecuted(v1::SwapExecuted::default()),
+            Event::LiquidityChanged(v1::LiquidityChanged::default()),
+            Event::FeesCollected(v1::FeesCollected::default()),
+            Event::PriceUpdated(v1::PriceUpdated::default()),
+            Event::PoolArchived(v1::PoolArchived::default()),
+            Event::PoolMetadataUpdated(v1::PoolMetadataUpdated::default()),
+            Event::RewardsClaimed(v1::RewardsClaimed::default()),
+            Event::OrderPlaced(v1::OrderPlaced::default()),
+            Event::OrderCancelled(v1::OrderCancelled::default()),
+            Event::PoolOwnershipTransferred(v1::PoolOwnershipTransferred::default()),
+            Event::OraclePriceUpdated(v1::OraclePriceUpdated::default()),
+            Event::PoolRestored(v1::PoolRestored::default()),
+            Event::PoolStatusChanged(v1::PoolStatusChanged::default()),
+        ]
+    }
+
+    #[test]
+    fn every_oneof_variant_round_trips() {
+        let events = every_event();
+        let tags: Vec<u32> = events.iter().map(oneof_tag).collect();
+        let Some(&last) = tags.last() else {
+            panic!("no samples");
+        };
+        assert_eq!(
+            tags,
+            (10..=last).collect::<Vec<_>>(),
+            "every variant has one sample, in tag order"
+        );
+
+        for event in events {
+            let msg = v1::PoolEvent {
+                pool_id: "pool".to_string(),
+                event: Some(event),
+                ..v1::PoolEvent::default()
+            };
+            let Ok(decoded) = decode_event(&msg.encode_to_vec()) else {
+                panic!("decode failed");
+            };
+            assert_eq!(
+                decoded,
+                msg,
+                "tag {} is not registered on the oneof",
+                msg.event.as_ref().map_or(0, oneof_tag)
+            );
+        }
+    }
+
+    #[test]
+    fn enums_map_to_wire_values() {
+        let event = PoolEvent::PriceUpdated {
+            pool_id: PoolId::new(),
+            old_price: "1".to_string(),
+            new_price: "2".to_string(),
+            price_change_bps: 10_000,
+            reason: PriceChangeReason::LiquidityRemoved,
+            timestamp: Utc::now(),
+        };
+        let msg = v1::PoolEvent::from(&event);
+        let Some(v1::pool_event::Event::PriceUpdated(p)) = msg.event else {
+            panic!("expected price payload");
+        };
+        assert_eq!(p.reason, v1::PriceChangeReason::LiquidityRemoved as i32);
+    }
+
+    /// A schema as one line per field (`Message.field = tag type`), oneof
+    /// member (`Message.oneof.member = tag Type`), and enum value
+    /// (`Enum.VALUE = number`).
+    type Schema = BTreeSet<String>;
+
+    /// What a `.proto` line belongs to.
+    enum ProtoScope<'a> {
+        Message(&'a str),
+        Enum(&'a str),
+        Oneof(&'a str, &'a str),
+    }
+
+    /// `PoolCreated` → `pool_created`.
+    fn snake_case(name: &str) -> String {
+        let mut out = String::new();
+        for (i, c) in name.chars().enumerate() {
+            if c.is_ascii_uppercase() && i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        }
+        out
+    }
+
+    /// Reads the schema out of a `.proto` file.
+    fn proto_schema(source: &str) -> Schema {
+        let mut schema = Schema::new();
+        let mut scopes: Vec<ProtoScope<'_>> = Vec::new();
+        for line in source.lines() {
+            let line = line.split("//").next().unwrap_or("").trim();
+            if line.starts_with('}') {
+                scopes.pop();
+                continue;
+            }
+            let words: Vec<&str> = line.trim_end_matches(';').split_whitespace().collect();
+            let opens = line.ends_with('{');
+            match (words.as_slice(), scopes.last()) {
+                (["message", name, ..], _) if opens => scopes.push(ProtoScope::Message(name)),
+                (["enum", name, ..], _) if opens => scopes.push(ProtoScope::Enum(name)),
+                (["oneof", name, ..], Some(&ProtoScope::Message(message))) if opens => {
+                    scopes.push(ProtoScope::Oneof(message, name));
+                }
+                ([value, "=", number], Some(&ProtoScope::Enum(name))) => {
+                    let prefix = format!("{}_", snake_case(name).to_ascii_uppercase());
+                    let value = value.strip_prefix(&prefix).unwrap_or(value);
+                    schema.insert(format!("{name}.{value} = {number}"));
+                }
+                ([ty, field, "=", tag], Some(&ProtoScope::Message(message))) => {
+                    schema.insert(format!("{message}.{field} = {tag} {ty}"));
+                }
+                (["repeated", ty, field, "=", tag], Some(&ProtoScope::Message(message))) => {
+                    schema.insert(format!("{message}.{field} = {tag} repeated {ty}"));
+                }
+                ([ty, member, "=", tag], Some(&ProtoScope::Oneof(message, oneof))) => {
+                    schema.insert(format!("{message}.{oneof}.{member} = {tag} {ty}"));
+                }
+                _ => {}
+            }
+        }
+        schema
+    }
+
+    /// Value of `key = "..."` in a `#[prost(...)]` attribute.
+    fn attr_value<'a>(attr: &'a str, key: &str) -> Option<&'a str> {
+        let (_, rest) = attr.split_once(&format!("{key} = \""))?;
+        rest.split_once('"').map(|(value, _)| value)
+    }
+
+    /// Parses a `tags = "10, 11"` list.
+    fn tag_list(tags: &str) -> Vec<u32> {
+        tags.split(',')
+            .filter_map(|t| t.trim().parse().ok())
+            .collect()
+    }
+
+    /// What a line of the Rust mirror belongs to.
+    enum MirrorScope {
+        None,
+        Message(String),
+        Enum(String),
+        Oneof(String),
+    }
+
+    /// Reads the schema out of the Rust mirror, checking on the way that
+    /// each oneof's `tags` list names exactly its variants' tags.
+    fn mirror_schema(source: &str) -> Schema {
+        let mut schema = Schema::new();
+        let mut module = "";
+        let mut derive = "";
+        let mut scope = MirrorScope::None;
+        let mut attr = String::new();
+        let mut prost_attr: Option<String> = None;
+        // Oneof path → (`Message.field`, tags listed on the field).
+        let mut oneof_fields: BTreeMap<String, (String, Vec<u32>)> = BTreeMap::new();
+        // Oneof path → (variant, payload type, tag).
+        let mut oneof_variants: BTreeMap<String, Vec<(String, String, u32)>> = BTreeMap::new();
+
+        for line in source.lines().map(str::trim) {
+            if line.starts_with("#[prost(") || !attr.is_empty() {
+                attr.push_str(line);
+                if attr.ends_with(")]") {
+                    prost_attr = Some(std::mem::take(&mut attr));
+                }
+                continue;
+            }
+            if let Some(name) = line.strip_prefix("pub mod ") {
+                module = name.trim_end_matches(" {");
+            } else if line.starts_with("#[derive(") {
+                derive = line;
+            } else if let Some(name) = line.strip_prefix("pub struct ") {
+                let name = name.trim_end_matches(" {}").trim_end_matches(" {");
+                scope = MirrorScope::Message(name.to_string());
+            } else if let Some(name) = line.strip_prefix("pub enum ") {
+                let name = name.trim_end_matches(" {");
+                scope = if derive.contains("Oneof") {
+                    MirrorScope::Oneof(format!("{module}::{name}"))
+                } else {
+                    MirrorScope::Enum(name.to_string())
+                };
+            } else if let MirrorScope::Enum(name) = &scope
+                && let Some((value, number)) = line.trim_end_matches(',').split_once(" = ")
+            {
+                let value = snake_case(value).to_ascii_uppercase();
+                schema.insert(format!("{name}.{value} = {number}"));
+            } else if let Some(attr) = prost_attr.take() {
+                match &scope {
+                    MirrorScope::Message(message) => {
+                        let Some((field, _)) = line.trim_start_matches("pub ").split_once(':')
+                        else {
+                            panic!("no field after {attr}");
+                        };
+                        if let (Some(path), Some(tags)) =
+                            (attr_value(&attr, "oneof"), attr_value(&attr, "tags"))
+                        {
+                            oneof_fields.insert(
+                                path.to_string(),
+                                (format!("{message}.{field}"), tag_list(tags)),
+                            );
+                            continue;
+                        }
+                        let Some(tag) = attr_value(&attr, "tag") else {
+                            panic!("no tag in {attr}");
+                        };
+                        let ty = attr_value(&attr, "enumeration").unwrap_or_else(|| {
+                            let body = attr.trim_start_matches("#[prost(");
+                            body.split(',').next().unwrap_or("")
+                        });
+                        let repeated = if attr.contains("repeated") {
+                            "repeated "
+                        } else {
+                            ""
+                        };
+                        schema.insert(format!("{message}.{field} = {tag} {repeated}{ty}"));
+                    }
+                    MirrorScope::Oneof(path) => {
+                        let Some((variant, ty)) = line.trim_end_matches("),").split_once('(')
+                        else {
+                            panic!("no variant after {attr}");
+                        };
+                        let Some(tag) = attr_value(&attr, "tag").and_then(|t| t.parse().ok())
+                        else {
+                            panic!("no tag in {attr}");
+                        };
+                        oneof_variants.entry(path.clone()).or_default().push((
+                            snake_case(variant),
+                            ty.trim_start_matches("super::").to_string(),
+                            tag,
+                        ));
+                    }
+                    MirrorScope::Enum(_) | MirrorScope::None => panic!("stray {attr}"),
+                }
+            }
+        }
+
+        for (path, (field, tags)) in oneof_fields {
+            let variants = oneof_variants.remove(&path).unwrap_or_default();
+            let variant_tags: Vec<u32> = variants.iter().map(|(_, _, tag)| *tag).collect();
+            assert_eq!(
+                tags, variant_tags,
+                "{field}: the oneof tags list must name every variant's tag"
+            );
+            for (member, ty, tag) in variants {
+                schema.insert(format!("{field}.{member} = {tag} {ty}"));
+            }
+        }
+        assert!(oneof_variants.is_empty(), "oneof enum without a field");
+        schema
+    }
+
+    #[test]
+    fn mirror_matches_proto_schema() {
+        let proto = proto_schema(include_str!("../../proto/pool_events.proto"));
+        let mirror = mirror_schema(include_str!("v1.rs"));
+        assert!(
+            proto.contains("SwapExecuted.amount_in = 2 string")
+                && proto.contains("PoolEvent.event.pool_created = 10 PoolCreated")
+                && proto.contains("SwapKind.EXACT_IN = 1"),
+            "the .proto parser found no schema"
+        );
+
+        let missing: Vec<_> = proto.difference(&mirror).collect();
+        let extra: Vec<_> = mirror.difference(&proto).collect();
+        assert!(
+            missing.is_empty() && extra.is_empty(),
+            "src/proto/v1.rs is out of step with proto/pool_events.proto\n\
+             missing from the mirror: {missing:#?}\nnot in the .proto: {extra:#?}"
+        );
+
+        let proto_tags: Vec<u32> = proto
+            .iter()
+            .filter(|line| line.starts_with("PoolEvent.event."))
+            .filter_map(|line| line.split(' ').nth(2)?.parse().ok())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let sample_tags: Vec<u32> = every_event().iter().map(oneof_tag).collect();
+        assert_eq!(
+            proto_tags, sample_tags,
+            "every oneof member needs a round-trip sample"
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! Message types for `hydra.gateway.events.v1`.
+//!
+//! Mirrors `proto/pool_events.proto` field-for-field (same names, tags,
+//! and scalar types). Any change to the `.proto` file must be reflected
+//! here with identical tag numbers; the `mirror_matches_proto_schema` test
+//! fails until it is.
+
+/// Envelope carrying exactly one pool event.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PoolEvent {
+    /// Payload schema version (matches the event log `schema_version`).
+    #[prost(uint32, tag = "1")]
+    pub schema_version: u32,
+    /// Pool UUID (hyphenated, lowercase).
+    #[prost(string, tag = "2")]
+    pub pool_id: String,
+    /// Event timestamp in microseconds since the Unix epoch.
+    #[prost(int64, tag = "3")]
+    pub timestamp_micros: i64,
+    /// Client whose command caused the event; empty if none.
+    #[prost(string, tag = "4")]
+    pub actor: String,
+    /// The event payload.
+    #[prost(
+        oneof = "pool_event::Event",
+        tags = "10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24"
+    )]
+    pub event: Option<pool_event::Event>,
+}
+
+/// Nested types for [`PoolEvent`].
+pub mod pool_event {
+    /// The `event` oneof of [`super::PoolEvent`].
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Event {
+        /// A pool was created.
+        #[prost(message, tag = "10")]
+        PoolCreated(super::PoolCreated),
+        /// A pool was removed.
+        #[prost(message, tag = "11")]
+        PoolRemoved(super::PoolRemoved),
+        /// A swap was executed.
+        #[prost(message, tag = "12")]
+        SwapExecuted(super::SwapExecuted),
+        /// Liquidity was added or removed.
+        #[prost(message, tag = "13")]
+        LiquidityChanged(super::LiquidityChanged),
+        /// Fees were collected.
+        #[prost(message, tag = "14")]
+        FeesCollected(super::FeesCollected),
+        /// The pool price changed.
+        #[prost(message, tag = "15")]
+        PriceUpdated(super::PriceUpdated),
+        /// An idle pool was archived.
+        #[prost(message, tag = "16")]
+        PoolArchived(super::PoolArchived),
+        /// The pool's metadata was replaced.
+        #[prost(message, tag = "17")]
+        PoolMetadataUpdated(super::PoolMetadataUpdated),
+        /// Liquidity-mining rewards were claimed.
+        #[prost(message, tag = "18")]
+        RewardsClaimed(super::RewardsClaimed),
+        /// A limit order rested on the book.
+        #[prost(message, tag = "19")]
+        OrderPlaced(super::OrderPlaced),
+        /// A resting limit order was cancelled.
+        #[prost(message, tag = "20")]
+        OrderCancelled(super::OrderCancelled),
+        /// The pool's ownership was transferred.
+        #[prost(message, tag = "21")]
+        PoolOwnershipTransferred(super::PoolOwnershipTransferred),
+        /// A dynamic pool accepted a new oracle price.
+        #[prost(message, tag = "22")]
+        OraclePriceUpdated(super::OraclePriceUpdated),
+        /// The pool was restored from a snapshot.
+        #[prost(message, tag = "23")]
+        PoolRestored(super::PoolRestored),
+        /// The pool was paused, drained, or resumed.
+        #[prost(message, tag = "24")]
+        PoolStatusChanged(super::PoolStatusChanged),
+    }
+}
+
+/// Payload of a pool creation event.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PoolCreated {
+    /// Pool type string (e.g. `"constant_product"`).
+    #[prost(string, tag = "1")]
+    pub pool_type: String,
+    /// First token address.
+    #[prost(string, tag = "2")]
+    pub token_a: String,
+    /// Second token address.
+    #[prost(string, tag = "3")]
+    pub token_b: String,
+    /// Fee tier in basis points.
+    #[prost(uint32, tag = "4")]
+    pub fee_tier: u32,
+    /// Type-specific creation config as JSON.
+    #[prost(string, tag = "5")]
+    pub config_json: String,
+    /// Pool metadata as JSON (`null` if none).
+    #[prost(string, tag = "6")]
+    pub metadata_json: String,
+}
+
+/// Payload of a pool removal event (no fields).
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PoolRemoved {}
+
+/// Payload of a pool archival event.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PoolArchived {
+    /// Last state change before archiving, in microseconds since the Unix
+    /// epoch.
+    #[prost(int64, tag = "1")]
+    pub idle_since_micros: i64,
+}
+
+/// Payload of a pool metadata update.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PoolMetadataUpdated {
+    /// New metadata as JSON (`null` clears it).
+    #[prost(string, tag = "1")]
+    pub metadata_json: String,
+}
+
+/// Payload of a pool ownership transfer.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PoolOwnershipTransferred {
+    /// Owner before the transfer; empty if the pool was unowned.
+    #[prost(string, tag = "1")]
+    pub previous_owner: String,
+    /// New owner.
+    #[prost(string, tag = "2")]
+    pub new_owner: String,
+}
+
+/// Whether a swap fixed its input or its output amount.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum SwapKind {
+    /// Default value; never emitted.
+    Unspecified = 0,
+    /// The input amount was fixed.
+    ExactIn = 1,
+    /// The output amount was fixed.
+    ExactOut = 2,
+}
+
+/// Payload of a swap execution event.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SwapExecuted {
+    /// Client-provided command ID for correlation.
+    #[prost(string, tag = "1")]
+    pub command_id: String,
+    /// Input amount (decimal u128).
+    #[prost(string, tag = "2")]
+    pub amount_in: String,
+    /// Output amount (decimal u128).
+    #[prost(string, tag = "3")]
+    pub amount_out: String,
+    /// Fee charged (decimal u128).
+    #[prost(string, tag = "4")]
+    pub fee: String,
+    /// New spot price after the swap.
+    #[prost(string, tag = "5")]
+    pub new_price: String,
+    /// Price change in basis points.
+    #[prost(sint32, tag = "6")]
+    pub price_change_bps: i32,
+    /// Input token address.
+    #[prost(string, tag = "7")]
+    pub token_in: String,
+    /// Exact-in or exact-out.
+    #[prost(enumeration = "SwapKind", tag = "8")]
+    pub swap_kind: i32,
+    /// Fee-tier rebate paid back out of `fee` (decimal u128).
+    #[prost(string, tag = "9")]
+    pub fee_rebate: String,
+}
+
+/// Direction of a liquidity change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum LiquidityChangeType {
+    /// Default value; never emitted.
+    Unspecified = 0,
+    /// Liquidity was added.
+    Add = 1,
+    /// Liquidity was removed.
+    Remove = 2,
+}
+
+/// Payload of a liquidity change event.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct LiquidityChanged {
+    /// Whether liquidity was added or removed.
+    #[prost(enumeration = "LiquidityChangeType", tag = "1")]
+    pub change_type: i32,
+    /// Amount of token A involved (decimal u128).
+    #[prost(string, tag = "2")]
+    pub amount_a: String,
+    /// Amount of token B involved (decimal u128).
+    #[prost(string, tag = "3")]
+    pub amount_b: String,
+    /// New total liquidity after the change (decimal u128).
+    #[prost(string, tag = "4")]
+    pub new_total_liquidity: String,
+    /// LP units minted (add) or burned (remove) (decimal u128).
+    #[prost(string, tag = "5")]
+    pub liquidity_delta: String,
+    /// Position credited or debited; empty if none.
+    #[prost(string, tag = "6")]
+    pub position_id: String,
+}
+
+/// Payload of a fee collection event.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct FeesCollected {
+    /// Fees collected in token A (decimal u128).
+    #[prost(string, tag = "1")]
+    pub fee_token_a: String,
+    /// Fees collected in token B (decimal u128).
+    #[prost(string, tag = "2")]
+    pub fee_token_b: String,
+    /// Position the fees were collected for; empty if none.
+    #[prost(string, tag = "3")]
+    pub position_id: String,
+}
+
+/// Payload of a rewards claim.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct RewardsClaimed {
+    /// Address of the reward token.
+    #[prost(string, tag = "1")]
+    pub reward_token: String,
+    /// Reward units paid out (decimal u128).
+    #[prost(string, tag = "2")]
+    pub amount: String,
+    /// Positions the rewards were earned by.
+    #[prost(string, repeated, tag = "3")]
+    pub position_ids: Vec<String>,
+}
+
+/// Side of the order book.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum OrderSide {
+    /// Default value; never emitted.
+    Unspecified = 0,
+    /// Buys token A with token B.
+    Bid = 1,
+    /// Sells token A for token B.
+    Ask = 2,
+}
+
+/// Payload of a limit order placement.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OrderPlaced {
+    /// Order identifier.
+    #[prost(string, tag = "1")]
+    pub order_id: String,
+    /// Side of the book.
+    #[prost(enumeration = "OrderSide", tag = "2")]
+    pub side: i32,
+    /// Limit price (decimal u128).
+    #[prost(string, tag = "3")]
+    pub price: String,
+    /// Token A units rested (decimal u128).
+    #[prost(string, tag = "4")]
+    pub quantity: String,
+}
+
+/// Payload of a limit order cancellation.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OrderCancelled {
+    /// Order identifier.
+    #[prost(string, tag = "1")]
+    pub order_id: String,
+    /// Side of the book.
+    #[prost(enumeration = "OrderSide", tag = "2")]
+    pub side: i32,
+    /// Limit price (decimal u128).
+    #[prost(string, tag = "3")]
+    pub price: String,
+    /// Token A units removed from the book (decimal u128).
+    #[prost(string, tag = "4")]
+    pub quantity: String,
+}
+
+/// Why a price update occurred.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum PriceChangeReason {
+    /// Default value; never emitted.
+    Unspecified = 0,
+    /// Price changed due to a swap.
+    SwapExecuted = 1,
+    /// Price changed due to liquidity being added.
+    LiquidityAdded = 2,
+    /// Price changed due to liquidity being removed.
+    LiquidityRemoved = 3,
+    /// Price changed due to an oracle update.
+    OracleUpdated = 4,
+}
+
+/// Payload of an oracle price update.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OraclePriceUpdated {
+    /// Oracle price before the update.
+    #[prost(string, tag = "1")]
+    pub old_price: String,
+    /// Oracle price after the update.
+    #[prost(string, tag = "2")]
+    pub new_price: String,
+}
+
+/// Payload of a pool restore.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PoolRestored {
+    /// Snapshot the pool was restored from.
+    #[prost(int64, tag = "1")]
+    pub snapshot_id: i64,
+    /// When that snapshot was taken, in microseconds since the Unix epoch.
+    #[prost(int64, tag = "2")]
+    pub snapshot_at_micros: i64,
+    /// Snapshot holding the replaced state.
+    #[prost(int64, tag = "3")]
+    pub archived_snapshot_id: i64,
+}
+
+/// Lifecycle state of a pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum PoolStatus {
+    /// Default value; never emitted.
+    Unspecified = 0,
+    /// Accepts every operation.
+    Active = 1,
+    /// Rejects every operation until resumed.
+    Paused = 2,
+    /// Accepts only withdrawals, fee collection, and order cancellation.
+    Draining = 3,
+    /// Archived for inactivity.
+    Archived = 4,
+}
+
+/// Payload of a pool status change.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PoolStatusChanged {
+    /// Status before the change.
+    #[prost(enumeration = "PoolStatus", tag = "1")]
+    pub previous_status: i32,
+    /// Status after the change.
+    #[prost(enumeration = "PoolStatus", tag = "2")]
+    pub status: i32,
+}
+
+/// Payload of a price update event.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PriceUpdated {
+    /// Spot price before the operation.
+    #[prost(string, tag = "1")]
+    pub old_price: String,
+    /// Spot price after the operation.
+    #[prost(string, tag = "2")]
+    pub new_price: String,
+    /// Price change in basis points.
+    #[prost(sint32, tag = "3")]
+    pub price_change_bps: i32,
+    /// Why the price changed.
+    #[prost(enumeration = "PriceChangeReason", tag = "4")]
+    pub reason: i32,
+}
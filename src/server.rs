@@ -0,0 +1,314 @@
+//! Listener binding, shutdown signalling, and process handover.
+//!
+//! Supports zero-downtime restarts: with `LISTEN_REUSE_PORT=true` a new
+//! gateway process binds the same address (`SO_REUSEPORT`) while the old
+//! process is still serving, and records itself in `PID_FILE`. The old
+//! process watches that file: once it names another process it stops
+//! accepting connections, drains in-flight requests within the grace
+//! period, writes its final snapshots, and leaves a drained marker next to
+//! the PID file. The new process waits for that marker before restoring
+//! pools from the snapshots, and only binds the address once they are
+//! restored, so it never holds connections it is not ready to accept.
+//!
+//! Each process holds a lock on a file next to the PID file while it
+//! runs. The lock is released when the process exits, however it exits,
+//! so a replacement can tell the previous process is gone even when its
+//! PID has been reused.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tokio::net::{TcpListener, TcpSocket};
+
+/// Backlog passed to `listen(2)` for the HTTP listener.
+const LISTEN_BACKLOG: u32 = 1024;
+
+/// How often the PID file and drained marker are polled during a handover.
+const HANDOVER_POLL: Duration = Duration::from_millis(200);
+
+/// Time allowed on top of the drain period for the previous process to
+/// write its final snapshots.
+pub const SNAPSHOT_MARGIN: Duration = Duration::from_secs(30);
+
+/// Binds the HTTP listener, optionally with `SO_REUSEPORT`.
+///
+/// # Errors
+///
+/// Returns an I/O error if the socket cannot be created, configured, or
+/// bound to `addr`.
+pub fn bind_listener(addr: SocketAddr, reuse_port: bool) -> std::io::Result<TcpListener> {
+    let socket = if addr.is_ipv4() {
+        TcpSocket::new_v4()?
+    } else {
+        TcpSocket::new_v6()?
+    };
+    socket.set_reuseaddr(true)?;
+    #[cfg(unix)]
+    if reuse_port {
+        socket.set_reuseport(true)?;
+    }
+    #[cfg(not(unix))]
+    if reuse_port {
+        tracing::warn!("LISTEN_REUSE_PORT is only supported on unix; ignoring");
+    }
+    socket.bind(addr)?;
+    socket.listen(LISTEN_BACKLOG)
+}
+
+/// Reads the PID recorded in `path`, if any.
+fn read_pid(path: &Path) -> Option<u32> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|s| s.trim().parse::<u32>().ok())
+}
+
+/// Records this process in `pid_file`, returning the PID of the process
+/// it replaces (if the file named one other than ours).
+///
+/// # Errors
+///
+/// Returns an I/O error if the file cannot be written.
+pub fn take_over_pid_file(pid_file: &Path) -> std::io::Result<Option<u32>> {
+    let own_pid = std::process::id();
+    let previous = read_pid(pid_file).filter(|pid| *pid != own_pid);
+    std::fs::write(pid_file, own_pid.to_string())?;
+    Ok(previous)
+}
+
+/// Resolves once `pid_file` names a process other than this one, i.e. a
+/// replacement has taken over and this process should drain.
+pub async fn superseded(pid_file: PathBuf) {
+    let own_pid = std::process::id();
+    loop {
+        if read_pid(&pid_file).is_some_and(|pid| pid != own_pid) {
+            tracing::info!("replaced by a new process, draining connections");
+            return;
+        }
+        tokio::time::sleep(HANDOVER_POLL).await;
+    }
+}
+
+/// Path of the marker a drained process leaves next to `pid_file`.
+fn drained_marker(pid_file: &Path) -> PathBuf {
+    let mut marker = pid_file.as_os_str().to_owned();
+    marker.push(".drained");
+    PathBuf::from(marker)
+}
+
+/// Records that this process has drained and written its final
+/// snapshots, so a replacement may restore from them.
+///
+/// # Errors
+///
+/// Returns an I/O error if the marker cannot be written.
+pub fn mark_drained(pid_file: &Path) -> std::io::Result<()> {
+    std::fs::write(drained_marker(pid_file), std::process::id().to_string())
+}
+
+/// Lock a gateway process holds on a file next to its PID file for as
+/// long as it runs.
+#[derive(Debug)]
+pub struct ProcessLock {
+    file: File,
+}
+
+impl ProcessLock {
+    /// Opens the lock file next to `pid_file`, without locking it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if the file cannot be opened or created.
+    pub fn open(pid_file: &Path) -> std::io::Result<Self> {
+        let mut path = pid_file.as_os_str().to_owned();
+        path.push(".lock");
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(PathBuf::from(path))?;
+        Ok(Self { file })
+    }
+
+    /// Takes the lock unless another process holds it. Returns whether
+    /// this process holds it now.
+    #[must_use]
+    pub fn try_acquire(&self) -> bool {
+        match self.file.try_lock() {
+            Ok(()) => true,
+            Err(TryLockError::WouldBlock) => false,
+            Err(TryLockError::Error(e)) => {
+                tracing::warn!(error = %e, "failed to lock the process lock file");
+                false
+            }
+        }
+    }
+
+    /// Resolves once this process holds the lock, i.e. the process that
+    /// held it before has exited.
+    pub async fn acquire(&self) {
+        while !self.try_acquire() {
+            tokio::time::sleep(HANDOVER_POLL).await;
+        }
+    }
+}
+
+/// Waits until the process `previous` has marked itself drained or has
+/// exited, giving up after `timeout`. Whether it exited is read from
+/// `lock`, which this process then holds. Returns whether it finished.
+pub async fn wait_drained(
+    pid_file: &Path,
+    previous: u32,
+    lock: Option<&ProcessLock>,
+    timeout: Duration,
+) -> bool {
+    let marker = drained_marker(pid_file);
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if read_pid(&marker) == Some(previous) || lock.is_some_and(ProcessLock::try_acquire) {
+            return true;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(HANDOVER_POLL).await;
+    }
+}
+
+/// Resolves when the process receives `SIGTERM` or Ctrl-C.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!(error = %e, "failed to listen for ctrl-c");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sig) => {
+                sig.recv().await;
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
+    tracing::info!("shutdown signal received, draining connections");
+}
+
+/// Resolves `grace` after `drain_started` flips to `true`.
+///
+/// Used to bound how long in-flight requests and long-lived WebSocket
+/// connections may keep the old process alive during a handover.
+pub async fn drain_deadline(
+    mut drain_started: tokio::sync::watch::Receiver<bool>,
+    grace: Duration,
+) {
+    if drain_started.wait_for(|started| *started).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+    tokio::time::sleep(grace).await;
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn bind_listener_with_reuse_port_allows_second_bind() {
+        let Ok(addr) = "127.0.0.1:0".parse::<SocketAddr>() else {
+            panic!("valid addr");
+        };
+        let Ok(first) = bind_listener(addr, true) else {
+            panic!("first bind failed");
+        };
+        let Ok(addr) = first.local_addr() else {
+            panic!("no local addr");
+        };
+        let second = bind_listener(addr, true);
+        assert!(second.is_ok());
+    }
+
+    #[test]
+    fn take_over_pid_file_returns_previous_pid() {
+        let path = std::env::temp_dir().join(format!("hydra-gw-{}.pid", uuid::Uuid::new_v4()));
+        let _ = std::fs::write(&path, "4242");
+
+        let previous = take_over_pid_file(&path);
+        assert!(matches!(previous, Ok(Some(4242))));
+
+        // Second takeover by the same process reports no predecessor.
+        let again = take_over_pid_file(&path);
+        assert!(matches!(again, Ok(None)));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn superseded_resolves_once_another_process_takes_over() {
+        let path = std::env::temp_dir().join(format!("hydra-gw-{}.pid", uuid::Uuid::new_v4()));
+        let _ = std::fs::write(&path, std::process::id().to_string());
+
+        let watcher = tokio::spawn(superseded(path.clone()));
+        tokio::time::sleep(HANDOVER_POLL * 2).await;
+        assert!(!watcher.is_finished(), "our own pid is not a takeover");
+
+        let _ = std::fs::write(&path, "4242");
+        let finished = tokio::time::timeout(HANDOVER_POLL * 5, watcher).await;
+        assert!(matches!(finished, Ok(Ok(()))));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn wait_drained_sees_the_previous_process_marker() {
+        let path = std::env::temp_dir().join(format!("hydra-gw-{}.pid", uuid::Uuid::new_v4()));
+        let own_pid = std::process::id();
+
+        // A marker left by some other process does not count.
+        let _ = std::fs::write(drained_marker(&path), "4242");
+        assert!(!wait_drained(&path, own_pid, None, HANDOVER_POLL).await);
+
+        assert!(mark_drained(&path).is_ok());
+        assert!(wait_drained(&path, own_pid, None, HANDOVER_POLL).await);
+
+        let _ = std::fs::remove_file(drained_marker(&path));
+    }
+
+    #[tokio::test]
+    async fn wait_drained_sees_the_previous_process_exit() {
+        let path = std::env::temp_dir().join(format!("hydra-gw-{}.pid", uuid::Uuid::new_v4()));
+        let (Ok(previous), Ok(ours)) = (ProcessLock::open(&path), ProcessLock::open(&path)) else {
+            panic!("lock file not opened");
+        };
+        assert!(previous.try_acquire());
+
+        // Still running, whatever its PID now names.
+        assert!(!wait_drained(&path, 4242, Some(&ours), HANDOVER_POLL).await);
+
+        drop(previous);
+        assert!(wait_drained(&path, 4242, Some(&ours), HANDOVER_POLL).await);
+        let Ok(next) = ProcessLock::open(&path) else {
+            panic!("lock file not opened");
+        };
+        assert!(!next.try_acquire(), "the lock passed to this process");
+
+        let mut lock_file = path.as_os_str().to_owned();
+        lock_file.push(".lock");
+        let _ = std::fs::remove_file(PathBuf::from(lock_file));
+    }
+}
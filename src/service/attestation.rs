@@ -0,0 +1,191 @@
+//! Ed25519 attestations over emitted events.
+//!
+//! When an event signing key is configured, every event serialized to
+//! JSON — WebSocket frames and the event log — carries an `attestation`
+//! object holding the key id and a hex Ed25519 signature. The signature
+//! covers the compact JSON encoding of the event with object keys sorted
+//! and the `attestation` field removed, so consumers can verify it after
+//! re-parsing the payload with any JSON library.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::domain::PoolEvent;
+use crate::error::GatewayError;
+
+/// Name of the payload field holding the attestation.
+pub const ATTESTATION_FIELD: &str = "attestation";
+
+/// Signature attached to a serialized event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Attestation {
+    /// Id of the key that signed the event.
+    pub key_id: String,
+    /// Hex Ed25519 signature over the canonical payload.
+    pub signature: String,
+}
+
+/// Signs events with the gateway's Ed25519 key.
+#[derive(Debug, Clone)]
+pub struct EventSigner {
+    key_id: String,
+    key: SigningKey,
+}
+
+impl EventSigner {
+    /// Creates a signer from a hex-encoded 32-byte Ed25519 seed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InvalidRequest`] if the seed is not 64 hex
+    /// characters.
+    pub fn from_hex_seed(key_id: &str, seed_hex: &str) -> Result<Self, GatewayError> {
+        let seed: [u8; 32] = hex::decode(seed_hex.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| {
+                GatewayError::InvalidRequest(
+                    "event signing key must be a hex-encoded 32-byte seed".to_string(),
+                )
+            })?;
+        Ok(Self {
+            key_id: key_id.to_string(),
+            key: SigningKey::from_bytes(&seed),
+        })
+    }
+
+    /// Returns the key id attached to attestations.
+    #[must_use]
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Returns the hex-encoded public key consumers verify against.
+    #[must_use]
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.key.verifying_key().as_bytes())
+    }
+
+    /// Signs a JSON payload (any existing attestation is ignored).
+    #[must_use]
+    pub fn attest(&self, payload: &Value) -> Attestation {
+        let signature = self.key.sign(canonical_payload(payload).as_bytes());
+        Attestation {
+            key_id: self.key_id.clone(),
+            signature: hex::encode(signature.to_bytes()),
+        }
+    }
+}
+
+/// Serializes `event` to JSON, attaching an attestation when a signer is
+/// given.
+#[must_use]
+pub fn event_payload(event: &PoolEvent, signer: Option<&EventSigner>) -> Value {
+    let mut payload = serde_json::to_value(event).unwrap_or_default();
+    if let Some(signer) = signer {
+        let attestation = signer.attest(&payload);
+        if let Value::Object(map) = &mut payload {
+            map.insert(
+                ATTESTATION_FIELD.to_string(),
+                serde_json::to_value(attestation).unwrap_or_default(),
+            );
+        }
+    }
+    payload
+}
+
+/// Checks the attestation on `payload` against a hex public key.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] if the payload has no
+/// attestation, the key or signature is malformed, or the signature does
+/// not match.
+pub fn verify_payload(payload: &Value, public_key_hex: &str) -> Result<(), GatewayError> {
+    let invalid = |msg: &str| GatewayError::InvalidRequest(msg.to_string());
+    let public_key: [u8; 32] = hex::decode(public_key_hex)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| invalid("public key must be 32 hex-encoded bytes"))?;
+    let key = VerifyingKey::from_bytes(&public_key).map_err(|_| invalid("invalid public key"))?;
+    let signature: [u8; 64] = payload
+        .get(ATTESTATION_FIELD)
+        .and_then(|a| a.get("signature"))
+        .and_then(Value::as_str)
+        .and_then(|s| hex::decode(s).ok())
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| invalid("missing or malformed attestation"))?;
+    key.verify(
+        canonical_payload(payload).as_bytes(),
+        &Signature::from_bytes(&signature),
+    )
+    .map_err(|_| invalid("attestation does not match payload"))
+}
+
+/// Compact JSON of `payload` with sorted keys and no attestation.
+fn canonical_payload(payload: &Value) -> String {
+    let mut payload = sorted(payload);
+    if let Value::Object(map) = &mut payload {
+        map.remove(ATTESTATION_FIELD);
+    }
+    payload.to_string()
+}
+
+/// Rebuilds `value` with every object's keys inserted in sorted order.
+fn sorted(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let mut out = Map::new();
+            for key in keys {
+                if let Some(v) = map.get(key) {
+                    out.insert(key.clone(), sorted(v));
+                }
+            }
+            Value::Object(out)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(sorted).collect()),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::PoolId;
+    use chrono::Utc;
+
+    #[test]
+    fn attested_events_verify_and_detect_tampering() {
+        let Ok(signer) = EventSigner::from_hex_seed("gw-1", &"07".repeat(32)) else {
+            panic!("valid seed rejected");
+        };
+        let event = PoolEvent::PoolRemoved {
+            pool_id: PoolId::new(),
+            timestamp: Utc::now(),
+        };
+
+        let mut payload = event_payload(&event, Some(&signer));
+        assert_eq!(
+            payload
+                .pointer("/attestation/key_id")
+                .and_then(Value::as_str),
+            Some("gw-1")
+        );
+        assert!(verify_payload(&payload, &signer.public_key_hex()).is_ok());
+
+        if let Value::Object(map) = &mut payload {
+            map.insert(
+                "pool_id".to_string(),
+                Value::String(PoolId::new().to_string()),
+            );
+        }
+        assert!(verify_payload(&payload, &signer.public_key_hex()).is_err());
+
+        assert!(event_payload(&event, None).get(ATTESTATION_FIELD).is_none());
+        assert!(EventSigner::from_hex_seed("gw-1", "abcd").is_err());
+    }
+}
@@ -0,0 +1,675 @@
+//! Pre-aggregated OHLCV candles.
+//!
+//! A background task folds every trade from the [`TradeTape`] into 1m
+//! candles and their 5m, 1h, and 1d roll-ups in memory, and flushes the
+//! changed buckets to the `candles` table on an interval. Each flush
+//! writes deltas that the database merges into the stored rows, so chart
+//! queries read finished candles instead of scanning the event log.
+//!
+//! Prices are the trade tape's quote-per-base execution prices; volumes
+//! are raw base and quote amounts. Price moves without a trade (liquidity
+//! changes and oracle updates, from `price_updated` events) are folded in
+//! as price marks that extend the range and set the close but add no
+//! volume. Only the primary writes candles; replicas serve them from the
+//! shared database.
+//!
+//! A [`CandleBook`] keeps the most recent candles of every pool in
+//! memory as well, hydrated from the database at startup, so recent
+//! candles are served without a query and without waiting for a flush.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use super::trade_tape::{Trade, TradeTape};
+use crate::domain::pool_event::PriceChangeReason;
+use crate::domain::{EventBus, PoolEvent, PoolId};
+use crate::error::GatewayError;
+use crate::persistence::models::CandleRecord;
+use crate::persistence::postgres::PostgresPersistence;
+
+/// Timeframe used when the caller does not specify one.
+pub const DEFAULT_TIMEFRAME: &str = "1m";
+
+/// Candle bucket widths the worker maintains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Timeframe {
+    /// One minute.
+    OneMinute,
+    /// Five minutes.
+    FiveMinutes,
+    /// One hour.
+    OneHour,
+    /// One day (UTC).
+    OneDay,
+}
+
+impl Timeframe {
+    /// Every maintained timeframe, finest first.
+    pub const ALL: [Self; 4] = [
+        Self::OneMinute,
+        Self::FiveMinutes,
+        Self::OneHour,
+        Self::OneDay,
+    ];
+
+    /// Label stored in the `timeframe` column.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::OneMinute => "1m",
+            Self::FiveMinutes => "5m",
+            Self::OneHour => "1h",
+            Self::OneDay => "1d",
+        }
+    }
+
+    /// Bucket width in seconds.
+    #[must_use]
+    pub const fn seconds(self) -> i64 {
+        match self {
+            Self::OneMinute => 60,
+            Self::FiveMinutes => 300,
+            Self::OneHour => 3_600,
+            Self::OneDay => 86_400,
+        }
+    }
+
+    /// Parses a label such as `5m`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InvalidRequest`] for an unsupported label.
+    pub fn parse(label: &str) -> Result<Self, GatewayError> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.as_str() == label)
+            .ok_or_else(|| {
+                GatewayError::InvalidRequest(format!(
+                    "invalid timeframe: {label} (expected 1m, 5m, 1h, or 1d)"
+                ))
+            })
+    }
+
+    /// Start of the bucket containing `at`.
+    #[must_use]
+    pub fn bucket_start(self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let width = self.seconds();
+        let start = at.timestamp().div_euclid(width) * width;
+        DateTime::from_timestamp(start, 0).unwrap_or(at)
+    }
+}
+
+/// Candle key: pool, timeframe label, and bucket start.
+type CandleKey = (Uuid, &'static str, DateTime<Utc>);
+
+/// A price observed on a pool: a trade, or a mark without volume.
+#[derive(Debug, Clone, Copy)]
+struct Observation {
+    pool_id: Uuid,
+    at: DateTime<Utc>,
+    price: f64,
+    base_volume: u128,
+    quote_volume: u128,
+    trades: u64,
+}
+
+impl Observation {
+    fn trade(trade: &Trade) -> Option<Self> {
+        (trade.price.is_finite() && trade.price > 0.0).then(|| Self {
+            pool_id: *trade.pool_id.as_uuid(),
+            at: trade.timestamp,
+            price: trade.price,
+            base_volume: trade.size,
+            quote_volume: trade.quote_size,
+            trades: 1,
+        })
+    }
+
+    fn mark(pool_id: PoolId, price: f64, at: DateTime<Utc>) -> Option<Self> {
+        (price.is_finite() && price > 0.0).then(|| Self {
+            pool_id: *pool_id.as_uuid(),
+            at,
+            price,
+            base_volume: 0,
+            quote_volume: 0,
+            trades: 0,
+        })
+    }
+
+    /// The observation as a one-entry candle of `timeframe`.
+    fn delta(self, timeframe: Timeframe) -> CandleRecord {
+        CandleRecord {
+            pool_id: self.pool_id,
+            timeframe: timeframe.as_str().to_string(),
+            bucket_start: timeframe.bucket_start(self.at),
+            open: self.price,
+            high: self.price,
+            low: self.price,
+            close: self.price,
+            base_volume: self.base_volume,
+            quote_volume: self.quote_volume,
+            trades: self.trades,
+        }
+    }
+}
+
+/// Folds `later`, a delta of the same bucket, into `candle`.
+fn fold(candle: &mut CandleRecord, later: &CandleRecord) {
+    candle.high = candle.high.max(later.high);
+    candle.low = candle.low.min(later.low);
+    candle.close = later.close;
+    candle.base_volume = candle.base_volume.saturating_add(later.base_volume);
+    candle.quote_volume = candle.quote_volume.saturating_add(later.quote_volume);
+    candle.trades = candle.trades.saturating_add(later.trades);
+}
+
+/// Pool, price, and time of a `price_updated` event not caused by a swap;
+/// swaps reach the candles as trades instead.
+#[must_use]
+pub fn price_mark(event: &PoolEvent) -> Option<(PoolId, f64, DateTime<Utc>)> {
+    match event {
+        PoolEvent::PriceUpdated {
+            pool_id,
+            new_price,
+            reason,
+            timestamp,
+            ..
+        } if !matches!(reason, PriceChangeReason::SwapExecuted) => {
+            Some((*pool_id, new_price.parse().ok()?, *timestamp))
+        }
+        _ => None,
+    }
+}
+
+/// Candle deltas accumulated since the last flush.
+#[derive(Debug, Default)]
+pub struct CandleAggregator {
+    pending: HashMap<CandleKey, CandleRecord>,
+}
+
+impl CandleAggregator {
+    /// Folds `trade` into its bucket of every timeframe. Trades without a
+    /// positive finite price are ignored.
+    pub fn record(&mut self, trade: &Trade) {
+        if let Some(observation) = Observation::trade(trade) {
+            self.observe(observation);
+        }
+    }
+
+    /// Folds a price mark of `pool_id` into its bucket of every
+    /// timeframe. Prices that are not positive and finite are ignored.
+    pub fn mark(&mut self, pool_id: PoolId, price: f64, at: DateTime<Utc>) {
+        if let Some(observation) = Observation::mark(pool_id, price, at) {
+            self.observe(observation);
+        }
+    }
+
+    fn observe(&mut self, observation: Observation) {
+        for timeframe in Timeframe::ALL {
+            let delta = observation.delta(timeframe);
+            self.merge(
+                (observation.pool_id, timeframe.as_str(), delta.bucket_start),
+                delta,
+            );
+        }
+    }
+
+    /// Whether there is nothing to flush.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Takes the accumulated deltas, oldest bucket first.
+    pub fn drain(&mut self) -> Vec<CandleRecord> {
+        let mut candles: Vec<_> = self.pending.drain().map(|(_, c)| c).collect();
+        candles.sort_by_key(|c| c.bucket_start);
+        candles
+    }
+
+    /// Puts back deltas that failed to flush, ahead of anything recorded
+    /// since.
+    pub fn restore(&mut self, candles: Vec<CandleRecord>) {
+        let newer = std::mem::take(&mut self.pending);
+        for candle in candles {
+            let Ok(timeframe) = Timeframe::parse(&candle.timeframe) else {
+                continue;
+            };
+            self.merge(
+                (candle.pool_id, timeframe.as_str(), candle.bucket_start),
+                candle,
+            );
+        }
+        for (key, candle) in newer {
+            self.merge(key, candle);
+        }
+    }
+
+    fn merge(&mut self, key: CandleKey, later: CandleRecord) {
+        match self.pending.get_mut(&key) {
+            Some(candle) => fold(candle, &later),
+            None => {
+                self.pending.insert(key, later);
+            }
+        }
+    }
+}
+
+/// Recent candles of every pool, kept in memory.
+///
+/// Each pool and timeframe keeps its latest `capacity` buckets. The book
+/// is complete for every bucket from [`Self::covered_from`] on; older
+/// candles are only in the database.
+#[derive(Debug, Clone)]
+pub struct CandleBook {
+    capacity: usize,
+    state: Arc<RwLock<BookState>>,
+}
+
+#[derive(Debug)]
+struct BookState {
+    series: HashMap<(Uuid, Timeframe), VecDeque<CandleRecord>>,
+    /// First bucket of each timeframe the book saw every trade of.
+    since: HashMap<Timeframe, DateTime<Utc>>,
+}
+
+impl CandleBook {
+    /// Creates a book keeping `capacity` buckets per pool and timeframe,
+    /// complete from the bucket after the one containing `now`. A
+    /// capacity of zero keeps nothing.
+    #[must_use]
+    pub fn new(capacity: usize, now: DateTime<Utc>) -> Self {
+        let since = Timeframe::ALL
+            .into_iter()
+            .map(|t| {
+                let next = t.bucket_start(now) + chrono::Duration::seconds(t.seconds());
+                (t, next)
+            })
+            .collect();
+        Self {
+            capacity,
+            state: Arc::new(RwLock::new(BookState {
+                series: HashMap::new(),
+                since,
+            })),
+        }
+    }
+
+    /// Start of the oldest bucket of `timeframe` the book can hold as of
+    /// `now`; hydrate from there.
+    #[must_use]
+    pub fn hydration_start(&self, timeframe: Timeframe, now: DateTime<Utc>) -> DateTime<Utc> {
+        let buckets = i64::try_from(self.capacity.saturating_sub(1)).unwrap_or(i64::MAX);
+        let span = timeframe.seconds().saturating_mul(buckets);
+        timeframe
+            .bucket_start(now)
+            .checked_sub_signed(chrono::Duration::seconds(span))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC)
+    }
+
+    /// Loads every stored candle of `timeframe` starting at or after
+    /// `since`, making the book complete from there. Call before the book
+    /// is fed.
+    pub fn hydrate(&self, timeframe: Timeframe, since: DateTime<Utc>, candles: Vec<CandleRecord>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.write().unwrap_or_else(PoisonError::into_inner);
+        state.since.insert(timeframe, since);
+        for candle in candles {
+            state.apply(timeframe, candle, self.capacity);
+        }
+    }
+
+    /// Folds `trade` into the pool's candles.
+    pub fn record(&self, trade: &Trade) {
+        if let Some(observation) = Observation::trade(trade) {
+            self.observe(observation);
+        }
+    }
+
+    /// Folds a price mark into the pool's candles.
+    pub fn mark(&self, pool_id: PoolId, price: f64, at: DateTime<Utc>) {
+        if let Some(observation) = Observation::mark(pool_id, price, at) {
+            self.observe(observation);
+        }
+    }
+
+    /// Folds candle deltas, such as those of imported history, into the
+    /// book. Buckets older than the book covers are skipped.
+    pub fn merge(&self, candles: &[CandleRecord]) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.write().unwrap_or_else(PoisonError::into_inner);
+        for candle in candles {
+            if let Ok(timeframe) = Timeframe::parse(&candle.timeframe) {
+                state.apply(timeframe, candle.clone(), self.capacity);
+            }
+        }
+    }
+
+    fn observe(&self, observation: Observation) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.write().unwrap_or_else(PoisonError::into_inner);
+        for timeframe in Timeframe::ALL {
+            state.apply(timeframe, observation.delta(timeframe), self.capacity);
+        }
+    }
+
+    /// First bucket from which the book holds every candle of `pool_id`
+    /// and `timeframe`.
+    #[must_use]
+    pub fn covered_from(&self, pool_id: PoolId, timeframe: Timeframe) -> DateTime<Utc> {
+        if self.capacity == 0 {
+            return DateTime::<Utc>::MAX_UTC;
+        }
+        let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
+        let since = state.since_of(timeframe);
+        match state.series.get(&(*pool_id.as_uuid(), timeframe)) {
+            Some(ring) if ring.len() >= self.capacity => ring
+                .front()
+                .map_or(since, |oldest| oldest.bucket_start.max(since)),
+            _ => since,
+        }
+    }
+
+    /// Up to `limit` held candles of `pool_id` and `timeframe` starting in
+    /// `[from, to)`, oldest first.
+    #[must_use]
+    pub fn range(
+        &self,
+        pool_id: PoolId,
+        timeframe: Timeframe,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: usize,
+    ) -> Vec<CandleRecord> {
+        let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
+        state
+            .series
+            .get(&(*pool_id.as_uuid(), timeframe))
+            .map(|ring| {
+                ring.iter()
+                    .filter(|c| c.bucket_start >= from && c.bucket_start < to)
+                    .take(limit)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Starts a task feeding the book with trades from `tape` and price
+    /// marks from `bus`. The task ends when either closes.
+    pub fn spawn(&self, tape: &TradeTape, bus: &EventBus) -> JoinHandle<()> {
+        let book = self.clone();
+        let mut trades = tape.subscribe();
+        let mut events = bus.subscribe();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    received = trades.recv() => match received {
+                        Ok(trade) => book.record(&trade),
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            tracing::warn!(lagged = n, "candle book lagged behind trade tape");
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    received = events.recv() => match received {
+                        Ok(event) => {
+                            if let Some((pool_id, price, at)) = price_mark(&event) {
+                                book.mark(pool_id, price, at);
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            tracing::warn!(lagged = n, "candle book lagged behind event bus");
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                }
+            }
+        })
+    }
+}
+
+impl BookState {
+    fn since_of(&self, timeframe: Timeframe) -> DateTime<Utc> {
+        self.since
+            .get(&timeframe)
+            .copied()
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+
+    /// Folds `delta` into its bucket, keeping at most `capacity` buckets.
+    fn apply(&mut self, timeframe: Timeframe, delta: CandleRecord, capacity: usize) {
+        if delta.bucket_start < self.since_of(timeframe) {
+            return;
+        }
+        let ring = self.series.entry((delta.pool_id, timeframe)).or_default();
+        match ring.binary_search_by_key(&delta.bucket_start, |c| c.bucket_start) {
+            Ok(i) => {
+                if let Some(candle) = ring.get_mut(i) {
+                    fold(candle, &delta);
+                }
+            }
+            // Older than everything held by a full ring: already evicted
+            Err(0) if ring.len() >= capacity => {}
+            Err(i) => {
+                ring.insert(i, delta);
+                while ring.len() > capacity {
+                    ring.pop_front();
+                }
+            }
+        }
+    }
+}
+
+/// Starts a task that aggregates trades from `tape` and price marks from
+/// `bus`, and writes candles to `db` every `flush_interval`. The task
+/// runs for the life of the tape.
+pub fn spawn(
+    tape: &TradeTape,
+    bus: &EventBus,
+    db: PostgresPersistence,
+    flush_interval: Duration,
+) -> JoinHandle<()> {
+    let mut trades = tape.subscribe();
+    let mut events = bus.subscribe();
+    tokio::spawn(async move {
+        let mut aggregator = CandleAggregator::default();
+        let mut ticker = tokio::time::interval(flush_interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                received = trades.recv() => match received {
+                    Ok(trade) => aggregator.record(&trade),
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(lagged = n, "candle worker lagged behind trade tape");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {
+                        flush(&mut aggregator, &db).await;
+                        break;
+                    }
+                },
+                received = events.recv() => match received {
+                    Ok(event) => {
+                        if let Some((pool_id, price, at)) = price_mark(&event) {
+                            aggregator.mark(pool_id, price, at);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(lagged = n, "candle worker lagged behind event bus");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {
+                        flush(&mut aggregator, &db).await;
+                        break;
+                    }
+                },
+                _ = ticker.tick() => flush(&mut aggregator, &db).await,
+            }
+        }
+    })
+}
+
+/// Writes pending deltas, keeping them for the next tick on failure.
+async fn flush(aggregator: &mut CandleAggregator, db: &PostgresPersistence) {
+    if aggregator.is_empty() {
+        return;
+    }
+    let candles = aggregator.drain();
+    if let Err(e) = db.upsert_candles(&candles).await {
+        tracing::warn!(candles = candles.len(), error = %e, "candle flush failed");
+        aggregator.restore(candles);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::PoolId;
+    use crate::service::trade_tape::TradeSide;
+
+    fn trade(pool_id: PoolId, at: &str, price: f64, size: u128) -> Trade {
+        let Ok(timestamp) = at.parse::<DateTime<Utc>>() else {
+            panic!("bad timestamp");
+        };
+        Trade {
+            trade_id: "cmd".to_string(),
+            pool_id,
+            base_token: "weth".to_string(),
+            quote_token: "usdc".to_string(),
+            side: TradeSide::Buy,
+            price,
+            size,
+            quote_size: size * 2,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn trades_fold_into_every_timeframe() {
+        let pool_id = PoolId::new();
+        let mut aggregator = CandleAggregator::default();
+        aggregator.record(&trade(pool_id, "2026-01-01T00:00:10Z", 2.0, 10));
+        aggregator.record(&trade(pool_id, "2026-01-01T00:00:40Z", 3.0, 5));
+        aggregator.record(&trade(pool_id, "2026-01-01T00:01:05Z", 1.5, 1));
+
+        let candles = aggregator.drain();
+        assert!(aggregator.is_empty());
+        let find = |timeframe: &str, minute: u32| {
+            candles.iter().find(|c| {
+                c.timeframe == timeframe
+                    && c.bucket_start.timestamp() == i64::from(minute) * 60 + 1_767_225_600
+            })
+        };
+
+        let Some(first) = find("1m", 0) else {
+            panic!("missing first minute");
+        };
+        assert_eq!(
+            (first.open, first.high, first.low, first.close),
+            (2.0, 3.0, 2.0, 3.0)
+        );
+        assert_eq!(
+            (first.base_volume, first.quote_volume, first.trades),
+            (15, 30, 2)
+        );
+        assert!(find("1m", 1).is_some_and(|c| c.trades == 1 && c.open == 1.5));
+
+        for timeframe in ["5m", "1h", "1d"] {
+            let Some(rollup) = find(timeframe, 0) else {
+                panic!("missing {timeframe} roll-up");
+            };
+            assert_eq!(
+                (rollup.open, rollup.high, rollup.low, rollup.close),
+                (2.0, 3.0, 1.5, 1.5)
+            );
+            assert_eq!((rollup.base_volume, rollup.trades), (16, 3));
+        }
+
+        // A failed flush is retried together with newer trades.
+        aggregator.record(&trade(pool_id, "2026-01-01T00:01:30Z", 4.0, 2));
+        aggregator.restore(candles);
+        let Some(minute) = aggregator
+            .drain()
+            .into_iter()
+            .find(|c| c.timeframe == "1m" && c.bucket_start.timestamp() == 1_767_225_660)
+        else {
+            panic!("restored minute not merged");
+        };
+        assert_eq!(
+            (minute.open, minute.close, minute.base_volume, minute.trades),
+            (1.5, 4.0, 3, 2)
+        );
+    }
+
+    #[test]
+    fn book_keeps_recent_buckets_it_covers() {
+        let pool_id = PoolId::new();
+        let Ok(start) = "2026-01-01T00:00:30Z".parse::<DateTime<Utc>>() else {
+            panic!("bad timestamp");
+        };
+        let book = CandleBook::new(2, start);
+        let minute = |m: i64| start - chrono::Duration::seconds(30) + chrono::Duration::minutes(m);
+        assert_eq!(book.covered_from(pool_id, Timeframe::OneMinute), minute(1));
+
+        // Hydration covers older buckets and drops those before its start.
+        let since = book.hydration_start(Timeframe::OneMinute, start);
+        assert_eq!(since, minute(-1));
+        let mut stored = CandleAggregator::default();
+        stored.record(&trade(pool_id, "2025-12-31T23:58:10Z", 1.0, 1));
+        stored.record(&trade(pool_id, "2025-12-31T23:59:10Z", 2.0, 4));
+        let rows = stored
+            .drain()
+            .into_iter()
+            .filter(|c| c.timeframe == "1m")
+            .collect();
+        book.hydrate(Timeframe::OneMinute, since, rows);
+        assert_eq!(book.covered_from(pool_id, Timeframe::OneMinute), minute(-1));
+
+        // A mark moves the price without adding a trade.
+        book.record(&trade(pool_id, "2026-01-01T00:00:40Z", 2.5, 2));
+        book.mark(pool_id, 3.5, minute(0) + chrono::Duration::seconds(50));
+        let held = book.range(pool_id, Timeframe::OneMinute, minute(-5), minute(5), 10);
+        let summary: Vec<_> = held
+            .iter()
+            .map(|c| (c.bucket_start, c.high, c.close, c.trades))
+            .collect();
+        assert_eq!(
+            summary,
+            [(minute(-1), 2.0, 2.0, 1), (minute(0), 3.5, 3.5, 1)]
+        );
+
+        // A new bucket evicts the oldest, moving coverage forward.
+        book.record(&trade(pool_id, "2026-01-01T00:01:05Z", 3.0, 1));
+        assert_eq!(book.covered_from(pool_id, Timeframe::OneMinute), minute(0));
+        let held = book.range(pool_id, Timeframe::OneMinute, minute(-5), minute(5), 1);
+        assert_eq!(held.first().map(|c| c.bucket_start), Some(minute(0)));
+    }
+
+    #[test]
+    fn timeframes_parse_and_bucket() {
+        assert_eq!(Timeframe::parse("1h").ok(), Some(Timeframe::OneHour));
+        assert!(Timeframe::parse("2h").is_err());
+        let Ok(at) = "2026-03-04T05:06:07Z".parse::<DateTime<Utc>>() else {
+            panic!("bad timestamp");
+        };
+        assert_eq!(
+            Timeframe::FiveMinutes.bucket_start(at).to_rfc3339(),
+            "2026-03-04T05:05:00+00:00"
+        );
+        assert_eq!(
+            Timeframe::OneDay.bucket_start(at).to_rfc3339(),
+            "2026-03-04T00:00:00+00:00"
+        );
+    }
+}
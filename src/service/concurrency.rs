@@ -0,0 +1,159 @@
+//! Concurrency limits for CPU-heavy read endpoints.
+//!
+//! Slippage curves and depth charts simulate many swaps per request, and
+//! volatility series scan the event log. Left unbounded, a burst of them
+//! competes with swaps for the runtime's worker threads. Each heavy route
+//! gets its own [`ConcurrencyLimiter`] slot pool, and each API key
+//! (`X-Client-Id`) may hold only a few of a route's slots at once so one
+//! caller cannot starve the others.
+//!
+//! A request that cannot get a slot waits up to the queue timeout. If its
+//! own key is at its limit it is answered with
+//! [`GatewayError::RateLimited`] (429); if the route as a whole is
+//! saturated it is answered with [`GatewayError::Overloaded`] (503). Both
+//! carry a retry hint. Anonymous requests are bounded by the route limit
+//! only.
+//!
+//! Limits are per instance.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::error::GatewayError;
+
+/// Per-key semaphores retained before idle ones are dropped.
+const MAX_IDLE_KEYS: usize = 1_024;
+
+/// Semaphores keyed by heavy route and API key.
+type KeySemaphores = HashMap<(&'static str, String), Arc<Semaphore>>;
+
+/// Slot held while a heavy request runs; released on drop.
+#[derive(Debug)]
+pub struct HeavyPermit {
+    _route: OwnedSemaphorePermit,
+    _key: Option<OwnedSemaphorePermit>,
+}
+
+/// Per-route and per-key slot pools for heavy endpoints.
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimiter {
+    per_route: usize,
+    per_key: usize,
+    queue_timeout: Duration,
+    routes: Arc<Mutex<HashMap<&'static str, Arc<Semaphore>>>>,
+    keys: Arc<Mutex<KeySemaphores>>,
+}
+
+impl ConcurrencyLimiter {
+    /// Creates a limiter allowing `per_route` concurrent requests on each
+    /// heavy route, at most `per_key` of them from one API key (0 means no
+    /// per-key limit), each waiting at most `queue_timeout` for a slot.
+    #[must_use]
+    pub fn new(per_route: usize, per_key: usize, queue_timeout: Duration) -> Self {
+        Self {
+            per_route: per_route.max(1),
+            per_key,
+            queue_timeout,
+            routes: Arc::default(),
+            keys: Arc::default(),
+        }
+    }
+
+    /// Waits for a slot on `route` for the caller `key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::RateLimited`] if `key` still holds all of
+    /// its slots on `route` after the queue timeout, and
+    /// [`GatewayError::Overloaded`] if the route is still saturated.
+    pub async fn acquire(
+        &self,
+        route: &'static str,
+        key: Option<&str>,
+    ) -> Result<HeavyPermit, GatewayError> {
+        let retry_after_ms = u64::try_from(self.queue_timeout.as_millis()).unwrap_or(u64::MAX);
+        let deadline = tokio::time::Instant::now() + self.queue_timeout;
+
+        let key_permit = match key.filter(|_| self.per_key > 0) {
+            Some(key) => {
+                let semaphore = self.key_semaphore(route, key);
+                match tokio::time::timeout_at(deadline, semaphore.acquire_owned()).await {
+                    Ok(Ok(permit)) => Some(permit),
+                    _ => {
+                        tracing::debug!(route, key, "heavy request rejected: key at limit");
+                        return Err(GatewayError::RateLimited { retry_after_ms });
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let semaphore = self.route_semaphore(route);
+        match tokio::time::timeout_at(deadline, semaphore.acquire_owned()).await {
+            Ok(Ok(permit)) => Ok(HeavyPermit {
+                _route: permit,
+                _key: key_permit,
+            }),
+            _ => {
+                tracing::debug!(route, "heavy request rejected: route saturated");
+                Err(GatewayError::Overloaded { retry_after_ms })
+            }
+        }
+    }
+
+    fn route_semaphore(&self, route: &'static str) -> Arc<Semaphore> {
+        let mut routes = self.routes.lock().unwrap_or_else(PoisonError::into_inner);
+        Arc::clone(
+            routes
+                .entry(route)
+                .or_insert_with(|| Arc::new(Semaphore::new(self.per_route))),
+        )
+    }
+
+    fn key_semaphore(&self, route: &'static str, key: &str) -> Arc<Semaphore> {
+        let mut keys = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        if keys.len() >= MAX_IDLE_KEYS {
+            // Semaphores referenced only by the map have no holders or waiters.
+            keys.retain(|_, semaphore| Arc::strong_count(semaphore) > 1);
+        }
+        Arc::clone(
+            keys.entry((route, key.to_string()))
+                .or_insert_with(|| Arc::new(Semaphore::new(self.per_key))),
+        )
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn keys_and_routes_are_limited_independently() {
+        let limiter = ConcurrencyLimiter::new(2, 1, Duration::from_millis(20));
+
+        let Ok(first) = limiter.acquire("curve", Some("alice")).await else {
+            panic!("first request rejected");
+        };
+        assert!(matches!(
+            limiter.acquire("curve", Some("alice")).await,
+            Err(GatewayError::RateLimited { .. })
+        ));
+        // Another route has its own slots.
+        assert!(limiter.acquire("depth", Some("alice")).await.is_ok());
+
+        let Ok(_second) = limiter.acquire("curve", Some("bob")).await else {
+            panic!("other key rejected");
+        };
+        assert!(matches!(
+            limiter.acquire("curve", None).await,
+            Err(GatewayError::Overloaded { .. })
+        ));
+
+        drop(first);
+        assert!(limiter.acquire("curve", Some("alice")).await.is_ok());
+    }
+}
@@ -0,0 +1,296 @@
+//! Per-pool lock contention statistics.
+//!
+//! Every mutation takes its pool's write lock through [`PoolService`],
+//! which reports here how many writers are queued, which operation holds
+//! the lock, how long writers waited, and how long recent operations took
+//! end to end. `GET /admin/pools/{id}/contention` serves the figures to
+//! diagnose hot pools. Readers (listings, quotes, snapshots) are not
+//! tracked.
+//!
+//! [`PoolService`]: super::PoolService
+
+use std::collections::{HashMap, VecDeque};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::RwLockWriteGuard;
+
+use crate::domain::PoolId;
+use crate::domain::pool_entry::PoolEntry;
+
+/// Operations kept per pool for recent latencies.
+pub const RECENT_OPERATIONS: usize = 50;
+
+/// The operation holding a pool's write lock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockHolder {
+    /// Operation name, e.g. `swap`.
+    pub operation: &'static str,
+    /// When the lock was acquired.
+    pub since: DateTime<Utc>,
+}
+
+/// Timing of one finished operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationSample {
+    /// Operation name.
+    pub operation: &'static str,
+    /// Time spent waiting for the lock.
+    pub wait: Duration,
+    /// Time the lock was held.
+    pub held: Duration,
+    /// When the lock was released.
+    pub finished_at: DateTime<Utc>,
+}
+
+/// Contention figures of one pool.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentionStats {
+    /// Operation holding the write lock, if any.
+    pub holder: Option<LockHolder>,
+    /// Writers waiting for the lock.
+    pub queued_writers: usize,
+    /// Write lock acquisitions since startup.
+    pub acquisitions: u64,
+    /// Mean wait per acquisition.
+    pub average_wait: Duration,
+    /// Longest wait of an acquisition.
+    pub max_wait: Duration,
+    /// Writers that gave up after the lock timeout.
+    pub timeouts: u64,
+    /// Most recent operations, newest first.
+    pub recent: Vec<OperationSample>,
+}
+
+#[derive(Debug, Default)]
+struct PoolContention {
+    holder: Option<LockHolder>,
+    queued: usize,
+    acquisitions: u64,
+    total_wait: Duration,
+    max_wait: Duration,
+    timeouts: u64,
+    recent: VecDeque<OperationSample>,
+}
+
+/// Shared contention table, keyed by pool.
+#[derive(Debug, Clone, Default)]
+pub struct ContentionTracker {
+    pools: Arc<Mutex<HashMap<PoolId, PoolContention>>>,
+}
+
+impl ContentionTracker {
+    /// Creates an empty tracker.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_pool<T>(&self, pool_id: PoolId, f: impl FnOnce(&mut PoolContention) -> T) -> T {
+        let mut pools = self.pools.lock().unwrap_or_else(PoisonError::into_inner);
+        f(pools.entry(pool_id).or_default())
+    }
+
+    /// Records a writer starting to wait for `pool_id`. It leaves the
+    /// queue when the returned ticket is acquired or dropped.
+    #[must_use]
+    pub fn enqueue(&self, pool_id: PoolId, operation: &'static str) -> QueueTicket {
+        self.with_pool(pool_id, |pool| pool.queued = pool.queued.saturating_add(1));
+        QueueTicket {
+            tracker: self.clone(),
+            pool_id,
+            operation,
+            started: Instant::now(),
+            done: false,
+        }
+    }
+
+    /// Returns the figures of `pool_id`; all zero if it saw no writers.
+    #[must_use]
+    pub fn stats(&self, pool_id: PoolId) -> ContentionStats {
+        let pools = self.pools.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(pool) = pools.get(&pool_id) else {
+            return ContentionStats::default();
+        };
+        let average_wait = u32::try_from(pool.acquisitions)
+            .ok()
+            .and_then(|n| pool.total_wait.checked_div(n))
+            .unwrap_or_default();
+        ContentionStats {
+            holder: pool.holder.clone(),
+            queued_writers: pool.queued,
+            acquisitions: pool.acquisitions,
+            average_wait,
+            max_wait: pool.max_wait,
+            timeouts: pool.timeouts,
+            recent: pool.recent.iter().rev().cloned().collect(),
+        }
+    }
+
+    /// Drops the figures of a deleted pool.
+    pub fn forget(&self, pool_id: PoolId) {
+        self.pools
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&pool_id);
+    }
+}
+
+/// A writer waiting for a pool's lock.
+#[derive(Debug)]
+pub struct QueueTicket {
+    tracker: ContentionTracker,
+    pool_id: PoolId,
+    operation: &'static str,
+    started: Instant,
+    done: bool,
+}
+
+impl QueueTicket {
+    /// Records that the lock was acquired as `guard`, which then tracks
+    /// how long it is held.
+    pub fn acquired(mut self, guard: RwLockWriteGuard<'_, PoolEntry>) -> TrackedWriteGuard<'_> {
+        self.done = true;
+        let wait = self.started.elapsed();
+        let operation = self.operation;
+        self.tracker.with_pool(self.pool_id, |pool| {
+            pool.queued = pool.queued.saturating_sub(1);
+            pool.acquisitions = pool.acquisitions.saturating_add(1);
+            pool.total_wait = pool.total_wait.saturating_add(wait);
+            pool.max_wait = pool.max_wait.max(wait);
+            pool.holder = Some(LockHolder {
+                operation,
+                since: Utc::now(),
+            });
+        });
+        TrackedWriteGuard {
+            guard,
+            _release: Release {
+                tracker: self.tracker.clone(),
+                pool_id: self.pool_id,
+                operation,
+                wait,
+                acquired: Instant::now(),
+            },
+        }
+    }
+
+    /// Records that the writer gave up after the lock timeout.
+    pub fn timed_out(mut self) {
+        self.done = true;
+        self.tracker.with_pool(self.pool_id, |pool| {
+            pool.queued = pool.queued.saturating_sub(1);
+            pool.timeouts = pool.timeouts.saturating_add(1);
+        });
+    }
+}
+
+impl Drop for QueueTicket {
+    fn drop(&mut self) {
+        if !self.done {
+            self.tracker.with_pool(self.pool_id, |pool| {
+                pool.queued = pool.queued.saturating_sub(1);
+            });
+        }
+    }
+}
+
+/// A pool write lock whose release is recorded as an operation sample.
+#[derive(Debug)]
+pub struct TrackedWriteGuard<'a> {
+    guard: RwLockWriteGuard<'a, PoolEntry>,
+    // Dropped after `guard`, so the sample covers the whole hold
+    _release: Release,
+}
+
+impl Deref for TrackedWriteGuard<'_> {
+    type Target = PoolEntry;
+
+    fn deref(&self) -> &PoolEntry {
+        &self.guard
+    }
+}
+
+impl DerefMut for TrackedWriteGuard<'_> {
+    fn deref_mut(&mut self) -> &mut PoolEntry {
+        &mut self.guard
+    }
+}
+
+#[derive(Debug)]
+struct Release {
+    tracker: ContentionTracker,
+    pool_id: PoolId,
+    operation: &'static str,
+    wait: Duration,
+    acquired: Instant,
+}
+
+impl Drop for Release {
+    fn drop(&mut self) {
+        let sample = OperationSample {
+            operation: self.operation,
+            wait: self.wait,
+            held: self.acquired.elapsed(),
+            finished_at: Utc::now(),
+        };
+        self.tracker.with_pool(self.pool_id, |pool| {
+            pool.holder = None;
+            if pool.recent.len() >= RECENT_OPERATIONS {
+                pool.recent.pop_front();
+            }
+            pool.recent.push_back(sample);
+        });
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::service::pool_service::build_entry;
+    use tokio::sync::RwLock;
+
+    #[tokio::test]
+    async fn tracks_queue_holder_and_recent_operations() {
+        let config = serde_json::json!({
+            "token_a": {"address": "0xaaa", "decimals": 6},
+            "token_b": {"address": "0xbbb", "decimals": 6},
+            "fee_bps": 30,
+            "reserve_a": "1000000",
+            "reserve_b": "1000000",
+        });
+        let pool_id = PoolId::new();
+        let Ok(entry) = build_entry(pool_id, "constant_product", config) else {
+            panic!("pool creation failed");
+        };
+        let lock = RwLock::new(entry);
+        let tracker = ContentionTracker::new();
+
+        let first = tracker.enqueue(pool_id, "swap");
+        let waiting = tracker.enqueue(pool_id, "add_liquidity");
+        assert_eq!(tracker.stats(pool_id).queued_writers, 2);
+
+        let guard = first.acquired(lock.write().await);
+        let stats = tracker.stats(pool_id);
+        assert_eq!(stats.queued_writers, 1);
+        assert_eq!(stats.acquisitions, 1);
+        assert_eq!(stats.holder.map(|h| h.operation), Some("swap"));
+
+        waiting.timed_out();
+        drop(guard);
+        let abandoned = tracker.enqueue(pool_id, "swap");
+        drop(abandoned);
+
+        let stats = tracker.stats(pool_id);
+        assert_eq!(stats.queued_writers, 0);
+        assert_eq!(stats.timeouts, 1);
+        assert!(stats.holder.is_none());
+        assert_eq!(stats.recent.len(), 1);
+
+        tracker.forget(pool_id);
+        assert_eq!(tracker.stats(pool_id), ContentionStats::default());
+    }
+}
@@ -0,0 +1,24 @@
+//! In-process callbacks on pool mutations.
+//!
+//! Applications embedding the gateway register an [`EventHook`] on
+//! [`super::PoolService`] to keep their own caches, risk engines, or
+//! compliance checks in step with pool state without polling the
+//! WebSocket feed. Hooks see every event the service emits, including
+//! events a replica applies from its primary, and run before the event
+//! reaches the [`crate::domain::EventBus`].
+
+use std::fmt;
+
+use crate::domain::PoolEvent;
+
+/// Synchronous observer of pool events.
+///
+/// Hooks run in registration order on the task that made the mutation,
+/// after the pool state has changed and before the event is published.
+/// They cannot veto the mutation, and they hold up every subscriber of
+/// the bus while they run, so they must not block; hand slow work off to
+/// a task of your own.
+pub trait EventHook: fmt::Debug + Send + Sync {
+    /// Called with each event before it is published.
+    fn on_event(&self, event: &PoolEvent);
+}
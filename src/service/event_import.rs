@@ -0,0 +1,227 @@
+//! Validation of historical events imported into a pool's event log.
+//!
+//! Pools migrated from another venue can bring their trade history so
+//! volatility, candles, and event consumers cover the time before the
+//! migration. The history arrives as NDJSON, one `PoolEvent` per line in
+//! the shape the event log stores. Only analytics events (swaps, liquidity
+//! changes, fee collections, price updates) are accepted; they must belong
+//! to the target pool, carry well-formed amounts, be in chronological
+//! order, and not lie in the future.
+//!
+//! Imported events are stored with their own timestamps and flagged as
+//! imported. Replay and replicas skip them: the pool's state already
+//! reflects that history.
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::{PoolEvent, PoolId};
+use crate::error::GatewayError;
+
+/// Events accepted in one import.
+pub const MAX_IMPORT_EVENTS: usize = 10_000;
+
+/// Invalid lines reported in one error before the rest are elided.
+const MAX_REPORTED_ERRORS: usize = 10;
+
+/// Event types that may be imported.
+pub const IMPORTABLE_EVENT_TYPES: [&str; 4] = [
+    "swap_executed",
+    "liquidity_changed",
+    "fees_collected",
+    "price_updated",
+];
+
+/// Parses and validates an NDJSON import for `pool_id`. Blank lines are
+/// skipped.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] listing the invalid lines (by
+/// 1-based line number), or if the import is empty or holds more than
+/// [`MAX_IMPORT_EVENTS`] events. Nothing is accepted unless every line is
+/// valid.
+pub fn parse_ndjson(
+    pool_id: PoolId,
+    body: &str,
+    now: DateTime<Utc>,
+) -> Result<Vec<PoolEvent>, GatewayError> {
+    let mut events = Vec::new();
+    let mut errors = Vec::new();
+    let mut previous: Option<DateTime<Utc>> = None;
+    for (index, line) in body.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if events.len() + errors.len() >= MAX_IMPORT_EVENTS {
+            return Err(GatewayError::InvalidRequest(format!(
+                "import exceeds {MAX_IMPORT_EVENTS} events"
+            )));
+        }
+        let line_no = index + 1;
+        match validate_line(pool_id, line, now, previous) {
+            Ok(event) => {
+                previous = Some(event.timestamp());
+                events.push(event);
+            }
+            Err(message) => errors.push(format!("line {line_no}: {message}")),
+        }
+    }
+    if !errors.is_empty() {
+        let total = errors.len();
+        errors.truncate(MAX_REPORTED_ERRORS);
+        if total > MAX_REPORTED_ERRORS {
+            errors.push(format!("and {} more", total - MAX_REPORTED_ERRORS));
+        }
+        return Err(GatewayError::InvalidRequest(format!(
+            "invalid import: {}",
+            errors.join("; ")
+        )));
+    }
+    if events.is_empty() {
+        return Err(GatewayError::InvalidRequest(
+            "import contains no events".to_string(),
+        ));
+    }
+    Ok(events)
+}
+
+/// Validates one line, given the timestamp of the previous valid event.
+fn validate_line(
+    pool_id: PoolId,
+    line: &str,
+    now: DateTime<Utc>,
+    previous: Option<DateTime<Utc>>,
+) -> Result<PoolEvent, String> {
+    let event: PoolEvent = serde_json::from_str(line).map_err(|e| e.to_string())?;
+    let event_type = event.event_type_str();
+    if !IMPORTABLE_EVENT_TYPES.contains(&event_type) {
+        return Err(format!("{event_type} events cannot be imported"));
+    }
+    if event.pool_id() != pool_id {
+        return Err(format!("event belongs to pool {}", event.pool_id()));
+    }
+    let timestamp = event.timestamp();
+    if timestamp > now {
+        return Err("timestamp is in the future".to_string());
+    }
+    if previous.is_some_and(|p| timestamp < p) {
+        return Err("events must be in chronological order".to_string());
+    }
+    let amounts: Vec<(&str, &str)> = match &event {
+        PoolEvent::SwapExecuted {
+            amount_in,
+            amount_out,
+            fee,
+            ..
+        } => vec![
+            ("amount_in", amount_in),
+            ("amount_out", amount_out),
+            ("fee", fee),
+        ],
+        PoolEvent::LiquidityChanged {
+            amount_a,
+            amount_b,
+            new_total_liquidity,
+            liquidity_delta,
+            ..
+        } => vec![
+            ("amount_a", amount_a),
+            ("amount_b", amount_b),
+            ("new_total_liquidity", new_total_liquidity),
+            ("liquidity_delta", liquidity_delta),
+        ],
+        PoolEvent::FeesCollected {
+            fee_token_a,
+            fee_token_b,
+            ..
+        } => vec![("fee_token_a", fee_token_a), ("fee_token_b", fee_token_b)],
+        _ => Vec::new(),
+    };
+    if let Some((field, _)) = amounts.iter().find(|(_, v)| v.parse::<u128>().is_err()) {
+        return Err(format!("{field} is not an unsigned integer"));
+    }
+    if let PoolEvent::PriceUpdated { new_price, .. } = &event
+        && !new_price
+            .parse::<f64>()
+            .is_ok_and(|p| p.is_finite() && p > 0.0)
+    {
+        return Err("new_price is not a positive number".to_string());
+    }
+    Ok(event)
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn swap_line(pool_id: PoolId, at: &str, amount_in: &str) -> String {
+        serde_json::json!({
+            "event_type": "swap_executed",
+            "pool_id": pool_id,
+            "command_id": "legacy-1",
+            "amount_in": amount_in,
+            "amount_out": "990",
+            "fee": "3",
+            "new_price": "1.01",
+            "price_change_bps": 1,
+            "token_in": "usdc",
+            "swap_kind": "exact_in",
+            "actor": null,
+            "timestamp": at,
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn valid_history_is_accepted_in_order() {
+        let pool_id = PoolId::new();
+        let body = format!(
+            "{}\n\n{}\n",
+            swap_line(pool_id, "2025-01-01T00:00:00Z", "1000"),
+            swap_line(pool_id, "2025-01-01T00:05:00Z", "2000"),
+        );
+        let Ok(events) = parse_ndjson(pool_id, &body, Utc::now()) else {
+            panic!("valid import rejected");
+        };
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.pool_id() == pool_id));
+    }
+
+    #[test]
+    fn invalid_lines_are_reported_by_number() {
+        let pool_id = PoolId::new();
+        let body = [
+            swap_line(pool_id, "2025-01-01T00:05:00Z", "1000"),
+            swap_line(pool_id, "2025-01-01T00:00:00Z", "1000"),
+            swap_line(PoolId::new(), "2025-01-01T00:06:00Z", "1000"),
+            swap_line(pool_id, "2025-01-01T00:07:00Z", "-5"),
+            swap_line(pool_id, "2999-01-01T00:00:00Z", "1000"),
+            format!(
+                r#"{{"event_type":"pool_removed","pool_id":"{pool_id}","timestamp":"2025-01-02T00:00:00Z"}}"#
+            ),
+            "not json".to_string(),
+        ]
+        .join("\n");
+        let Err(GatewayError::InvalidRequest(message)) = parse_ndjson(pool_id, &body, Utc::now())
+        else {
+            panic!("invalid import accepted");
+        };
+        for expected in [
+            "line 2: events must be in chronological order",
+            "line 3: event belongs to pool",
+            "line 4: amount_in is not an unsigned integer",
+            "line 5: timestamp is in the future",
+            "line 6: pool_removed events cannot be imported",
+            "line 7:",
+        ] {
+            assert!(
+                message.contains(expected),
+                "missing {expected:?} in {message}"
+            );
+        }
+        assert!(!message.contains("line 1:"));
+        assert!(parse_ndjson(pool_id, "\n", Utc::now()).is_err());
+    }
+}
@@ -0,0 +1,313 @@
+//! Event log writer.
+//!
+//! Appends every event the pool service emits to the persistent event
+//! log, in the same JSON shape WebSocket clients receive (including the
+//! attestation, when event signing is enabled), together with the version
+//! a mutation left its pool at. Replay relies on those versions to apply
+//! each mutation exactly once.
+//!
+//! Writing never holds up the operations that emit events. The pool
+//! service hands each event to an [`EventLogSink`], and a single writer
+//! inserts queued events in batches of up to `batch_size` per
+//! transaction. A failed batch is retried with backoff until it is
+//! written, so no event is ever left out of the log; a batch whose commit
+//! was lost may then be written twice, which replay tolerates because it
+//! skips versions it has already applied. While the database is behind,
+//! up to `buffer` events wait in the queue; beyond that the pool service
+//! refuses mutations with [`GatewayError::EventLogBacklog`] until the
+//! writer catches up. A single writer keeps row ids in emission order,
+//! which replicas rely on.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use super::PoolService;
+use crate::domain::PoolEvent;
+use crate::error::GatewayError;
+use crate::persistence::models::NewEvent;
+use crate::persistence::postgres::PostgresPersistence;
+
+/// Delay before the first retry; doubled on each further one.
+const RETRY_BACKOFF: Duration = Duration::from_millis(200);
+
+/// Longest delay between retries of a batch.
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Retry hint sent with mutations refused while the log is behind.
+const BACKLOG_RETRY_AFTER_MS: u64 = 1_000;
+
+/// An emitted event and the version it left its pool at, if it changed
+/// the pool's state.
+type Queued = (PoolEvent, Option<u64>);
+
+/// Batching and buffering of the event log writer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventLogOptions {
+    /// Most events inserted per transaction.
+    pub batch_size: usize,
+    /// Events queued while the writer is behind before mutations are
+    /// refused.
+    pub buffer: usize,
+}
+
+/// The pool service's end of the event log queue.
+#[derive(Debug, Clone)]
+pub struct EventLogSink {
+    tx: mpsc::UnboundedSender<Queued>,
+    pending: Arc<AtomicUsize>,
+    capacity: usize,
+}
+
+/// The writer's end of the event log queue.
+#[derive(Debug)]
+pub struct EventLogQueue {
+    rx: mpsc::UnboundedReceiver<Queued>,
+    pending: Arc<AtomicUsize>,
+    batch_size: usize,
+}
+
+/// Creates the queue between the pool service and the event log writer.
+#[must_use]
+pub fn channel(options: EventLogOptions) -> (EventLogSink, EventLogQueue) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let pending = Arc::new(AtomicUsize::new(0));
+    let sink = EventLogSink {
+        tx,
+        pending: Arc::clone(&pending),
+        capacity: options.buffer.max(1),
+    };
+    let queue = EventLogQueue {
+        rx,
+        pending,
+        batch_size: options.batch_size.max(1),
+    };
+    (sink, queue)
+}
+
+impl EventLogSink {
+    /// Checks that the queue has room for another mutation's events.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::EventLogBacklog`] while `buffer` events
+    /// are waiting to be written.
+    pub fn ensure_room(&self) -> Result<(), GatewayError> {
+        if self.pending() >= self.capacity {
+            return Err(GatewayError::EventLogBacklog {
+                retry_after_ms: BACKLOG_RETRY_AFTER_MS,
+            });
+        }
+        Ok(())
+    }
+
+    /// Queues `event`, which left its pool at `version` when it changed
+    /// the pool's state. An event queued after the writer stopped stays
+    /// counted as pending, so mutations are soon refused rather than
+    /// going unlogged.
+    pub fn push(&self, event: &PoolEvent, version: Option<u64>) {
+        self.pending.fetch_add(1, Ordering::AcqRel);
+        if self.tx.send((event.clone(), version)).is_err() {
+            tracing::error!(
+                pool_id = %event.pool_id(),
+                event_type = event.event_type_str(),
+                "event log writer stopped; event not logged"
+            );
+        }
+    }
+
+    /// Events queued and not written yet.
+    #[must_use]
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::Acquire)
+    }
+}
+
+/// Starts the writer draining `queue` into `db`. It ends once every sink
+/// is dropped and the queue is written.
+pub fn spawn(
+    pool_service: Arc<PoolService>,
+    db: PostgresPersistence,
+    queue: EventLogQueue,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        write(queue, |event| pool_service.event_payload(event), &db).await;
+    })
+}
+
+/// Where the writer stores batches of events.
+trait EventStore {
+    /// Appends `batch` in one transaction.
+    fn save(&self, batch: &[NewEvent]) -> impl Future<Output = Result<(), GatewayError>> + Send;
+}
+
+impl EventStore for PostgresPersistence {
+    fn save(&self, batch: &[NewEvent]) -> impl Future<Output = Result<(), GatewayError>> + Send {
+        self.save_events(batch)
+    }
+}
+
+/// Writes queued events in batches to `store` until the queue closes.
+async fn write<P, S>(mut queue: EventLogQueue, payload: P, store: &S)
+where
+    P: Fn(&PoolEvent) -> serde_json::Value,
+    S: EventStore,
+{
+    let mut events = Vec::with_capacity(queue.batch_size);
+    while queue.rx.recv_many(&mut events, queue.batch_size).await > 0 {
+        let batch: Vec<NewEvent> = events
+            .drain(..)
+            .map(|(event, version)| NewEvent {
+                pool_id: *event.pool_id().as_uuid(),
+                event_type: event.event_type_str(),
+                payload: payload(&event),
+                pool_version: version.map(|v| i64::try_from(v).unwrap_or(i64::MAX)),
+            })
+            .collect();
+        save_until_written(store, &batch).await;
+        queue.pending.fetch_sub(batch.len(), Ordering::AcqRel);
+    }
+}
+
+/// Saves `batch`, retrying with backoff until it is written.
+async fn save_until_written<S: EventStore>(store: &S, batch: &[NewEvent]) {
+    let mut backoff = RETRY_BACKOFF;
+    let mut attempt: u32 = 1;
+    while let Err(e) = store.save(batch).await {
+        tracing::warn!(attempt, events = batch.len(), error = %e, "event log write failed; retrying");
+        tokio::time::sleep(backoff).await;
+        backoff = backoff.saturating_mul(2).min(MAX_RETRY_BACKOFF);
+        attempt = attempt.saturating_add(1);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use std::sync::{Mutex, PoisonError};
+
+    use hydra_amm::domain::Amount;
+    use hydra_amm::traits::SwapPool;
+
+    use super::*;
+    use crate::domain::pool_operation::SwapKind;
+    use crate::domain::{EventBus, PoolRegistry};
+    use crate::service::pool_service::SwapConditions;
+
+    /// Store failing its first `failures` saves.
+    #[derive(Default)]
+    struct FlakyStore {
+        failures: AtomicUsize,
+        rows: Mutex<Vec<NewEvent>>,
+    }
+
+    impl EventStore for FlakyStore {
+        fn save(
+            &self,
+            batch: &[NewEvent],
+        ) -> impl Future<Output = Result<(), GatewayError>> + Send {
+            let result = if self.failures.load(Ordering::Acquire) > 0 {
+                self.failures.fetch_sub(1, Ordering::AcqRel);
+                Err(GatewayError::PersistenceError(
+                    "connection reset".to_string(),
+                ))
+            } else {
+                self.rows
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .extend_from_slice(batch);
+                Ok(())
+            };
+            std::future::ready(result)
+        }
+    }
+
+    #[tokio::test]
+    async fn backlog_refuses_mutations_until_every_event_is_written() {
+        let (sink, queue) = channel(EventLogOptions {
+            batch_size: 8,
+            buffer: 1,
+        });
+        let service = PoolService::new(Arc::new(PoolRegistry::new()), EventBus::new(16))
+            .with_event_log(sink.clone());
+        let Ok(pool_id) = service
+            .create_pool_from_config(
+                crate::domain::PoolId::new(),
+                "constant_product",
+                serde_json::json!({
+                    "token_a": {"address": "0xaaa", "decimals": 6},
+                    "token_b": {"address": "0xbbb", "decimals": 6},
+                    "fee_bps": 30,
+                    "reserve_a": "1000000",
+                    "reserve_b": "1000000",
+                }),
+                serde_json::Value::Null,
+                None,
+            )
+            .await
+        else {
+            panic!("pool creation failed");
+        };
+        let Ok(entry_lock) = service.registry().get(pool_id).await else {
+            panic!("pool not found");
+        };
+        let token = entry_lock.read().await.pool_box.token_pair().first();
+        let swap = || {
+            service.execute_swap(
+                pool_id,
+                SwapKind::ExactIn,
+                Amount::new(1000),
+                token,
+                SwapConditions::default(),
+                "cmd",
+                None,
+            )
+        };
+
+        let Err(GatewayError::EventLogBacklog { .. }) = swap().await else {
+            panic!("mutation accepted with a full event log queue");
+        };
+        assert_eq!(entry_lock.read().await.version(), 0);
+
+        let store = Arc::new(FlakyStore {
+            failures: AtomicUsize::new(1),
+            ..FlakyStore::default()
+        });
+        let writer = tokio::spawn({
+            let store = Arc::clone(&store);
+            async move {
+                let payload = |event: &PoolEvent| serde_json::json!(event.event_type_str());
+                write(queue, payload, &*store).await;
+            }
+        });
+        for _ in 0..200 {
+            if sink.pending() == 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(swap().await.is_ok(), "the writer caught up");
+
+        drop((service, sink));
+        let Ok(()) = writer.await else {
+            panic!("writer panicked");
+        };
+        let rows = store.rows.lock().unwrap_or_else(PoisonError::into_inner);
+        let logged: Vec<_> = rows
+            .iter()
+            .map(|row| (row.event_type, row.pool_version))
+            .collect();
+        assert_eq!(
+            logged,
+            [
+                ("pool_created", Some(0)),
+                ("swap_executed", Some(1)),
+                ("price_updated", None),
+            ],
+            "a failed batch is retried, never dropped"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Recent events across all pools, for `tail -f`-style debugging.
+//!
+//! A background task keeps the most recent events published on the bus
+//! in memory and rebroadcasts them, so `GET /admin/events/tail` can print
+//! the last few and then follow new ones without a WebSocket client.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::domain::{EventBus, PoolEvent, PoolId};
+
+/// Number of events retained by the tail.
+pub const TAIL_CAPACITY: usize = 1_000;
+
+/// Which events a tail shows; unset fields match everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TailFilter {
+    /// Only events of this pool.
+    pub pool_id: Option<PoolId>,
+    /// Only events of this type (e.g. `swap_executed`).
+    pub event_type: Option<String>,
+}
+
+impl TailFilter {
+    /// Returns `true` if `event` passes the filter.
+    #[must_use]
+    pub fn matches(&self, event: &PoolEvent) -> bool {
+        self.pool_id.is_none_or(|id| event.pool_id() == id)
+            && self
+                .event_type
+                .as_deref()
+                .is_none_or(|t| event.event_type_str() == t)
+    }
+}
+
+/// Shared handle to the event tail.
+#[derive(Debug, Clone)]
+pub struct EventTail {
+    recent: Arc<Mutex<VecDeque<PoolEvent>>>,
+    live: broadcast::Sender<PoolEvent>,
+}
+
+impl Default for EventTail {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventTail {
+    /// Creates an empty tail. Call [`Self::spawn`] to start feeding it.
+    #[must_use]
+    pub fn new() -> Self {
+        let (live, _) = broadcast::channel(TAIL_CAPACITY);
+        Self {
+            recent: Arc::new(Mutex::new(VecDeque::with_capacity(TAIL_CAPACITY))),
+            live,
+        }
+    }
+
+    /// Starts a task that records every event published on `event_bus`.
+    /// The task ends when the bus closes.
+    pub fn spawn(&self, event_bus: &EventBus) -> JoinHandle<()> {
+        let tail = self.clone();
+        let mut events = event_bus.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => tail.record(event),
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(lagged = n, "event tail lagged behind event bus");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Appends an event, evicting the oldest beyond [`TAIL_CAPACITY`].
+    pub fn record(&self, event: PoolEvent) {
+        let mut recent = self.recent.lock().unwrap_or_else(PoisonError::into_inner);
+        if recent.len() == TAIL_CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(event.clone());
+        // Sent under the lock so `follow` sees each event exactly once.
+        let _ = self.live.send(event);
+    }
+
+    /// Returns up to `limit` most recent events matching `filter`, oldest
+    /// first, together with a receiver for the events recorded after
+    /// them.
+    #[must_use]
+    pub fn follow(
+        &self,
+        limit: usize,
+        filter: &TailFilter,
+    ) -> (Vec<PoolEvent>, broadcast::Receiver<PoolEvent>) {
+        let recent = self.recent.lock().unwrap_or_else(PoisonError::into_inner);
+        let live = self.live.subscribe();
+        let mut events: Vec<PoolEvent> = recent
+            .iter()
+            .rev()
+            .filter(|e| filter.matches(e))
+            .take(limit)
+            .cloned()
+            .collect();
+        drop(recent);
+        events.reverse();
+        (events, live)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    #[test]
+    fn follow_returns_recent_then_live_events() {
+        let tail = EventTail::new();
+        let pool = PoolId::new();
+        let removed = |pool_id| PoolEvent::PoolRemoved {
+            pool_id,
+            timestamp: Utc::now(),
+        };
+        tail.record(removed(pool));
+        tail.record(removed(PoolId::new()));
+        tail.record(removed(pool));
+
+        let filter = TailFilter {
+            pool_id: Some(pool),
+            event_type: None,
+        };
+        let (recent, mut live) = tail.follow(10, &filter);
+        assert_eq!(recent.len(), 2);
+        assert!(recent.iter().all(|e| e.pool_id() == pool));
+        assert_eq!(tail.follow(1, &TailFilter::default()).0.len(), 1);
+
+        tail.record(removed(pool));
+        let Ok(event) = live.try_recv() else {
+            panic!("expected a live event");
+        };
+        assert!(filter.matches(&event));
+        let only_swaps = TailFilter {
+            pool_id: None,
+            event_type: Some("swap_executed".to_string()),
+        };
+        assert!(!only_swaps.matches(&event));
+    }
+}
@@ -0,0 +1,205 @@
+//! Volume-tiered swap fee rebates.
+//!
+//! Accounts (identified by `X-Client-Id`) that traded enough in the last
+//! 30 days get a discount of some basis points off the pool fee. hydra-amm
+//! still charges the full fee, so the discount is paid back as a rebate:
+//! it is reported with the swap and recorded on the `swap_executed` event,
+//! separately from the fee, so fee analytics can tell the two apart.
+//!
+//! Volume is the sum of swap input amounts in smallest units, the same
+//! measure as a pool's `total_volume`. It is counted per UTC day and can
+//! be seeded from the event log at startup.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Mutex, PoisonError};
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::pool_entry::pro_rata;
+
+/// Days of volume that decide an account's tier.
+pub const VOLUME_WINDOW_DAYS: i64 = 30;
+
+/// Discount granted from a 30-day volume upward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeTier {
+    /// Smallest 30-day volume that qualifies.
+    pub min_volume: u128,
+    /// Discount off the pool fee, in basis points.
+    pub discount_bps: u32,
+}
+
+/// Rebate granted on one swap.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeRebate {
+    /// Discount applied, in basis points (capped at the pool fee).
+    pub discount_bps: u32,
+    /// Part of the fee paid back, in the input token's smallest units.
+    pub amount: u128,
+}
+
+/// Fee tiers and the per-account volume that selects them.
+#[derive(Debug, Default)]
+pub struct FeeProgram {
+    /// Tiers by ascending `min_volume`.
+    tiers: Vec<FeeTier>,
+    /// Volume per account per UTC day index.
+    volumes: Mutex<HashMap<String, BTreeMap<i64, u128>>>,
+}
+
+impl FeeProgram {
+    /// Parses tiers of the form `1000000=5,50000000=10`: a 30-day volume
+    /// and the discount in basis points it earns. An empty spec disables
+    /// the program.
+    ///
+    /// Malformed entries are skipped.
+    #[must_use]
+    pub fn parse(spec: &str) -> Self {
+        let mut tiers: Vec<FeeTier> = spec
+            .split(',')
+            .filter_map(|entry| {
+                let (volume, bps) = entry.split_once('=')?;
+                Some(FeeTier {
+                    min_volume: volume.trim().parse().ok()?,
+                    discount_bps: bps.trim().parse().ok()?,
+                })
+            })
+            .filter(|tier| tier.discount_bps > 0)
+            .collect();
+        tiers.sort_by_key(|tier| tier.min_volume);
+        Self {
+            tiers,
+            volumes: Mutex::default(),
+        }
+    }
+
+    /// Returns `true` if no tier grants a discount.
+    #[must_use]
+    pub fn is_disabled(&self) -> bool {
+        self.tiers.is_empty()
+    }
+
+    /// Returns the tiers by ascending volume.
+    #[must_use]
+    pub fn tiers(&self) -> &[FeeTier] {
+        &self.tiers
+    }
+
+    /// Returns the volume `account` traded in the 30 days ending at `now`.
+    #[must_use]
+    pub fn volume(&self, account: &str, now: DateTime<Utc>) -> u128 {
+        let first_day = day_index(now) - (VOLUME_WINDOW_DAYS - 1);
+        let volumes = self.volumes.lock().unwrap_or_else(PoisonError::into_inner);
+        volumes.get(account).map_or(0, |days| {
+            days.range(first_day..)
+                .fold(0u128, |acc, (_, v)| acc.saturating_add(*v))
+        })
+    }
+
+    /// Returns the discount `account` currently earns, in basis points.
+    #[must_use]
+    pub fn discount_bps(&self, account: &str, now: DateTime<Utc>) -> u32 {
+        let volume = self.volume(account, now);
+        self.tiers
+            .iter()
+            .rev()
+            .find(|tier| volume >= tier.min_volume)
+            .map_or(0, |tier| tier.discount_bps)
+    }
+
+    /// Computes the rebate on a swap by `account` that was charged `fee`
+    /// at `fee_bps`. Anonymous swaps get none.
+    #[must_use]
+    pub fn rebate(
+        &self,
+        account: Option<&str>,
+        fee: u128,
+        fee_bps: u32,
+        now: DateTime<Utc>,
+    ) -> FeeRebate {
+        let Some(account) = account else {
+            return FeeRebate::default();
+        };
+        let discount_bps = self.discount_bps(account, now).min(fee_bps);
+        if discount_bps == 0 {
+            return FeeRebate::default();
+        }
+        FeeRebate {
+            discount_bps,
+            amount: pro_rata(fee, u128::from(discount_bps), u128::from(fee_bps)),
+        }
+    }
+
+    /// Adds `amount` to the volume `account` traded at `at`, dropping
+    /// days that left the window.
+    pub fn record(&self, account: &str, amount: u128, at: DateTime<Utc>) {
+        let day = day_index(at);
+        let mut volumes = self.volumes.lock().unwrap_or_else(PoisonError::into_inner);
+        let days = volumes.entry(account.to_string()).or_default();
+        let slot = days.entry(day).or_insert(0);
+        *slot = slot.saturating_add(amount);
+        let first_day = day_index(Utc::now()) - (VOLUME_WINDOW_DAYS - 1);
+        days.retain(|d, _| *d >= first_day);
+    }
+
+    /// Records per-day volumes loaded from the event log (see
+    /// `PostgresPersistence::load_swap_volumes`). Unparseable sums are
+    /// skipped.
+    ///
+    /// Returns the number of accounts seeded.
+    pub fn seed(&self, volumes: &[(String, DateTime<Utc>, String)]) -> usize {
+        let mut accounts = HashSet::new();
+        for (account, day, amount) in volumes {
+            if let Ok(amount) = amount.parse::<u128>() {
+                self.record(account, amount, *day);
+                accounts.insert(account.as_str());
+            }
+        }
+        accounts.len()
+    }
+
+    /// Start of the volume window ending at `now`, for loading volumes.
+    #[must_use]
+    pub fn window_start(now: DateTime<Utc>) -> DateTime<Utc> {
+        now - chrono::Duration::days(VOLUME_WINDOW_DAYS)
+    }
+}
+
+/// Index of the UTC day containing `at`.
+fn day_index(at: DateTime<Utc>) -> i64 {
+    at.timestamp().div_euclid(86_400)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volume_selects_the_highest_qualifying_tier() {
+        let program = FeeProgram::parse("5000=10, 1000=5, bogus, 9000=0");
+        assert_eq!(program.tiers().len(), 2);
+        let now = Utc::now();
+
+        program.record("bot", 800, now);
+        assert_eq!(program.discount_bps("bot", now), 0);
+        program.record("bot", 300, now - chrono::Duration::days(3));
+        assert_eq!(program.discount_bps("bot", now), 5);
+        // Volume older than the window does not count
+        program.record("bot", 10_000, now - chrono::Duration::days(40));
+        assert_eq!(program.volume("bot", now), 1_100);
+
+        program.record("bot", 4_000, now);
+        let rebate = program.rebate(Some("bot"), 300, 30, now);
+        assert_eq!(
+            rebate,
+            FeeRebate {
+                discount_bps: 10,
+                amount: 100
+            }
+        );
+        // The discount never exceeds the pool fee
+        assert_eq!(program.rebate(Some("bot"), 50, 5, now).amount, 50);
+        assert_eq!(program.rebate(None, 300, 30, now), FeeRebate::default());
+        assert!(FeeProgram::parse("").is_disabled());
+    }
+}
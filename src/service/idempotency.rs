@@ -0,0 +1,377 @@
+//! Idempotency keys for retried mutations.
+//!
+//! A swap that times out on the client's side may still have executed, so
+//! retrying it blindly can execute it twice. A request carrying an
+//! `Idempotency-Key` has its first successful response stored, and
+//! retries with the same key within the TTL get that response replayed
+//! instead of running again. Keys are scoped per client id; a key sent
+//! again with a different request (method, path, or body) is rejected,
+//! and so is one whose first request is still running.
+//!
+//! Responses live in a bounded in-memory LRU and, when persistence is
+//! enabled, in the `idempotency_keys` table, so retries survive restarts.
+//! Only successful responses are stored: a failed request changed nothing
+//! and may simply be retried.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use tokio::task::JoinHandle;
+
+use crate::error::GatewayError;
+use crate::persistence::models::IdempotencyRecord;
+use crate::persistence::postgres::PostgresPersistence;
+
+/// How often expired keys are dropped from memory and the database.
+const EXPIRY_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Client id and key.
+type Scope = (String, String);
+
+/// Hex SHA-256 identifying a request by method, path with query, and body.
+#[must_use]
+pub fn fingerprint(method: &str, path_and_query: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(path_and_query.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+#[derive(Debug)]
+enum Slot {
+    /// The first request is still running.
+    Pending,
+    /// The first request succeeded with this response.
+    Done(Arc<IdempotencyRecord>),
+}
+
+#[derive(Debug)]
+struct Entry {
+    slot: Slot,
+    /// When the request started (pending) or completed (done).
+    since: DateTime<Utc>,
+    tick: u64,
+}
+
+/// Entries by scope, with their recency.
+#[derive(Debug, Default)]
+struct Lru {
+    entries: HashMap<Scope, Entry>,
+    order: BTreeMap<u64, Scope>,
+    next_tick: u64,
+}
+
+impl Lru {
+    fn bump(&mut self) -> u64 {
+        self.next_tick = self.next_tick.wrapping_add(1);
+        self.next_tick
+    }
+
+    fn touch(&mut self, scope: &Scope) {
+        let tick = self.bump();
+        if let Some(entry) = self.entries.get_mut(scope) {
+            self.order.remove(&entry.tick);
+            entry.tick = tick;
+            self.order.insert(tick, scope.clone());
+        }
+    }
+
+    fn insert(&mut self, scope: Scope, slot: Slot, since: DateTime<Utc>, capacity: usize) {
+        self.remove(&scope);
+        while self.entries.len() >= capacity {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+        let tick = self.bump();
+        self.order.insert(tick, scope.clone());
+        self.entries.insert(scope, Entry { slot, since, tick });
+    }
+
+    fn remove(&mut self, scope: &Scope) {
+        if let Some(entry) = self.entries.remove(scope) {
+            self.order.remove(&entry.tick);
+        }
+    }
+}
+
+/// Outcome of [`IdempotencyStore::begin`].
+#[derive(Debug)]
+pub enum Begin {
+    /// First use of the key: run the request and report through the
+    /// reservation.
+    Reserved(Reservation),
+    /// The key already succeeded with the same request: replay this.
+    Replay(Arc<IdempotencyRecord>),
+}
+
+/// Stored responses by client id and key.
+#[derive(Debug, Clone)]
+pub struct IdempotencyStore {
+    lru: Arc<Mutex<Lru>>,
+    capacity: usize,
+    ttl: Duration,
+    persistence: Option<PostgresPersistence>,
+}
+
+impl IdempotencyStore {
+    /// Creates a store keeping up to `capacity` keys in memory for `ttl`,
+    /// and in `persistence` when given.
+    #[must_use]
+    pub fn new(capacity: usize, ttl: Duration, persistence: Option<PostgresPersistence>) -> Self {
+        Self {
+            lru: Arc::default(),
+            capacity: capacity.max(1),
+            ttl,
+            persistence,
+        }
+    }
+
+    /// Database the responses are also stored in, if any.
+    #[must_use]
+    pub fn persistence(&self) -> Option<&PostgresPersistence> {
+        self.persistence.as_ref()
+    }
+
+    /// Oldest time a response may have been stored at and still replay.
+    #[must_use]
+    pub fn cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        chrono::Duration::from_std(self.ttl)
+            .ok()
+            .and_then(|ttl| now.checked_sub_signed(ttl))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC)
+    }
+
+    /// Looks up `key` of `client_id` for a request with `fingerprint`,
+    /// reserving it if it is unused.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::IdempotencyKeyReused`] if the key belongs
+    /// to a different request, and
+    /// [`GatewayError::IdempotencyKeyInProgress`] if its first request
+    /// has not finished.
+    pub fn begin(
+        &self,
+        client_id: &str,
+        key: &str,
+        fingerprint: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Begin, GatewayError> {
+        let scope = (client_id.to_string(), key.to_string());
+        let cutoff = self.cutoff(now);
+        let mut lru = self.lru.lock().unwrap_or_else(PoisonError::into_inner);
+        let existing = match lru.entries.get(&scope) {
+            Some(entry) if entry.since >= cutoff => Some(&entry.slot),
+            _ => None,
+        };
+        match existing {
+            Some(Slot::Done(record)) if record.fingerprint == fingerprint => {
+                let record = Arc::clone(record);
+                lru.touch(&scope);
+                Ok(Begin::Replay(record))
+            }
+            Some(Slot::Done(_)) => Err(GatewayError::IdempotencyKeyReused(key.to_string())),
+            Some(Slot::Pending) => Err(GatewayError::IdempotencyKeyInProgress(key.to_string())),
+            None => {
+                lru.insert(scope.clone(), Slot::Pending, now, self.capacity);
+                Ok(Begin::Reserved(Reservation {
+                    store: self.clone(),
+                    scope,
+                    fingerprint: fingerprint.to_string(),
+                    finished: false,
+                }))
+            }
+        }
+    }
+
+    /// Drops expired keys from memory, returning how many were dropped.
+    pub fn prune(&self, now: DateTime<Utc>) -> usize {
+        let cutoff = self.cutoff(now);
+        let mut lru = self.lru.lock().unwrap_or_else(PoisonError::into_inner);
+        let expired: Vec<Scope> = lru
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.since < cutoff)
+            .map(|(scope, _)| scope.clone())
+            .collect();
+        for scope in &expired {
+            lru.remove(scope);
+        }
+        expired.len()
+    }
+
+    /// Starts a task dropping expired keys from memory and the database.
+    pub fn spawn_expiry(&self) -> JoinHandle<()> {
+        let store = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(EXPIRY_INTERVAL);
+            loop {
+                ticker.tick().await;
+                let now = Utc::now();
+                store.prune(now);
+                if let Some(db) = store.persistence() {
+                    match db
+                        .delete_idempotency_records_before(store.cutoff(now))
+                        .await
+                    {
+                        Ok(0) => {}
+                        Ok(n) => tracing::debug!(deleted = n, "expired idempotency keys deleted"),
+                        Err(e) => tracing::warn!(error = %e, "idempotency key cleanup failed"),
+                    }
+                }
+            }
+        })
+    }
+
+    fn release(&self, scope: &Scope) {
+        let mut lru = self.lru.lock().unwrap_or_else(PoisonError::into_inner);
+        if matches!(
+            lru.entries.get(scope).map(|entry| &entry.slot),
+            Some(Slot::Pending)
+        ) {
+            lru.remove(scope);
+        }
+    }
+}
+
+/// A key held by the request running under it. Dropping it without
+/// completing frees the key for a retry.
+#[derive(Debug)]
+pub struct Reservation {
+    store: IdempotencyStore,
+    scope: Scope,
+    fingerprint: String,
+    finished: bool,
+}
+
+impl Reservation {
+    /// Stores the successful response of the request.
+    pub fn complete(
+        mut self,
+        status: u16,
+        content_type: Option<String>,
+        body: Vec<u8>,
+        now: DateTime<Utc>,
+    ) -> Arc<IdempotencyRecord> {
+        let record = IdempotencyRecord {
+            client_id: self.scope.0.clone(),
+            idempotency_key: self.scope.1.clone(),
+            fingerprint: self.fingerprint.clone(),
+            status,
+            content_type,
+            body,
+            created_at: now,
+        };
+        self.finish(record)
+    }
+
+    /// Adopts a response stored earlier (e.g. in the database before a
+    /// restart) and returns it for replay.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::IdempotencyKeyReused`] if it answered a
+    /// different request.
+    pub fn replay(
+        mut self,
+        record: IdempotencyRecord,
+    ) -> Result<Arc<IdempotencyRecord>, GatewayError> {
+        if record.fingerprint != self.fingerprint {
+            let key = record.idempotency_key.clone();
+            self.finish(record);
+            return Err(GatewayError::IdempotencyKeyReused(key));
+        }
+        Ok(self.finish(record))
+    }
+
+    fn finish(&mut self, record: IdempotencyRecord) -> Arc<IdempotencyRecord> {
+        self.finished = true;
+        let since = record.created_at;
+        let record = Arc::new(record);
+        let mut lru = self
+            .store
+            .lru
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        lru.insert(
+            self.scope.clone(),
+            Slot::Done(Arc::clone(&record)),
+            since,
+            self.store.capacity,
+        );
+        record
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.store.release(&self.scope);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replays_first_success_and_rejects_conflicts() {
+        let store = IdempotencyStore::new(2, Duration::from_secs(60), None);
+        let now = Utc::now();
+        let swap = fingerprint("POST", "/api/v1/pools/1/swap", b"{\"amount\":\"10\"}");
+        let other = fingerprint("POST", "/api/v1/pools/1/swap", b"{\"amount\":\"11\"}");
+
+        let Ok(Begin::Reserved(first)) = store.begin("desk", "k1", &swap, now) else {
+            panic!("unused key not reserved");
+        };
+        assert!(matches!(
+            store.begin("desk", "k1", &swap, now),
+            Err(GatewayError::IdempotencyKeyInProgress(_))
+        ));
+        // Keys are scoped per client.
+        assert!(matches!(
+            store.begin("other", "k1", &swap, now),
+            Ok(Begin::Reserved(_))
+        ));
+
+        first.complete(200, None, b"ok".to_vec(), now);
+        let Ok(Begin::Replay(record)) = store.begin("desk", "k1", &swap, now) else {
+            panic!("completed key not replayed");
+        };
+        assert_eq!(
+            (record.status, record.body.as_slice()),
+            (200, b"ok".as_slice())
+        );
+        assert!(matches!(
+            store.begin("desk", "k1", &other, now),
+            Err(GatewayError::IdempotencyKeyReused(_))
+        ));
+
+        // A failed request frees its key.
+        let Ok(Begin::Reserved(failed)) = store.begin("desk", "k2", &swap, now) else {
+            panic!("unused key not reserved");
+        };
+        drop(failed);
+        assert!(matches!(
+            store.begin("desk", "k2", &other, now),
+            Ok(Begin::Reserved(_))
+        ));
+
+        // Expired responses are forgotten.
+        let later = now + chrono::Duration::seconds(61);
+        assert!(matches!(
+            store.begin("desk", "k1", &other, later),
+            Ok(Begin::Reserved(_))
+        ));
+    }
+}
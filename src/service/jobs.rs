@@ -0,0 +1,338 @@
+//! Background jobs for long-running administrative tasks.
+//!
+//! Snapshotting every pool, replaying history, compacting the event log,
+//! or deleting many pools can take far longer than an HTTP request should
+//! stay open. [`JobRegistry::spawn`] runs such a task on its own tokio task
+//! and records its status, progress, result, and error so callers can poll
+//! it by id.
+//!
+//! Cancellation is cooperative: a cancelled job stops at its next
+//! [`JobProgress::checkpoint`], so a step already under way (one pool's
+//! snapshot, one deletion, a whole replay) runs to completion first.
+//!
+//! Jobs live in memory on the instance that started them. The most recent
+//! [`MAX_FINISHED_JOBS`] finished jobs are kept for inspection.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::error::GatewayError;
+
+/// Finished jobs retained before the oldest are forgotten.
+pub const MAX_FINISHED_JOBS: usize = 100;
+
+/// Kind of work a job performs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Snapshot every pool.
+    SnapshotAll,
+    /// Replay persisted history into a staging registry.
+    Replay,
+    /// Compact the event log of several pools.
+    Compaction,
+    /// Delete several pools.
+    BulkDelete,
+}
+
+/// Lifecycle state of a job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// The job is executing.
+    Running,
+    /// The job finished and produced a result.
+    Completed,
+    /// The job stopped with an error.
+    Failed,
+    /// The job stopped after a cancellation request.
+    Cancelled,
+}
+
+impl JobStatus {
+    /// Returns `true` once the job can no longer change.
+    #[must_use]
+    pub const fn is_finished(self) -> bool {
+        !matches!(self, Self::Running)
+    }
+}
+
+/// Point-in-time view of a job.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobInfo {
+    /// Job identifier.
+    pub id: Uuid,
+    /// Kind of work.
+    pub kind: JobKind,
+    /// Current status.
+    pub status: JobStatus,
+    /// Steps completed so far.
+    pub completed: u64,
+    /// Total steps, once known.
+    pub total: Option<u64>,
+    /// Whether cancellation was requested.
+    pub cancel_requested: bool,
+    /// Error that stopped the job.
+    pub error: Option<String>,
+    /// Result of a completed job.
+    pub result: Option<serde_json::Value>,
+    /// When the job was started.
+    pub created_at: DateTime<Utc>,
+    /// When the job finished.
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug)]
+struct JobEntry {
+    info: JobInfo,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Shared table of background jobs.
+#[derive(Debug, Clone, Default)]
+pub struct JobRegistry {
+    jobs: Arc<Mutex<HashMap<Uuid, JobEntry>>>,
+}
+
+impl JobRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts `work` as a job of `kind` and returns its initial state.
+    ///
+    /// `work` receives a [`JobProgress`] to report progress and observe
+    /// cancellation; its output becomes the job's result or error.
+    pub fn spawn<F, Fut>(&self, kind: JobKind, work: F) -> JobInfo
+    where
+        F: FnOnce(JobProgress) -> Fut,
+        Fut: Future<Output = Result<serde_json::Value, GatewayError>> + Send + 'static,
+    {
+        let id = Uuid::new_v4();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let info = JobInfo {
+            id,
+            kind,
+            status: JobStatus::Running,
+            completed: 0,
+            total: None,
+            cancel_requested: false,
+            error: None,
+            result: None,
+            created_at: Utc::now(),
+            finished_at: None,
+        };
+        self.lock().insert(
+            id,
+            JobEntry {
+                info: info.clone(),
+                cancel: Arc::clone(&cancel),
+            },
+        );
+
+        let progress = JobProgress {
+            id,
+            registry: self.clone(),
+            cancel,
+        };
+        let registry = self.clone();
+        let fut = work(progress);
+        tokio::spawn(async move {
+            let outcome = fut.await;
+            registry.finish(id, outcome);
+        });
+        tracing::info!(job_id = %id, ?kind, "job started");
+        info
+    }
+
+    /// Returns every retained job, newest first.
+    #[must_use]
+    pub fn list(&self) -> Vec<JobInfo> {
+        let mut jobs: Vec<JobInfo> = self.lock().values().map(|e| e.info.clone()).collect();
+        jobs.sort_by_key(|job| std::cmp::Reverse(job.created_at));
+        jobs
+    }
+
+    /// Returns the job with `id`.
+    #[must_use]
+    pub fn get(&self, id: Uuid) -> Option<JobInfo> {
+        self.lock().get(&id).map(|e| e.info.clone())
+    }
+
+    /// Requests cancellation of job `id` and returns its state. Finished
+    /// jobs are returned unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::JobNotFound`] if no such job is retained.
+    pub fn cancel(&self, id: Uuid) -> Result<JobInfo, GatewayError> {
+        let mut jobs = self.lock();
+        let entry = jobs.get_mut(&id).ok_or(GatewayError::JobNotFound(id))?;
+        if !entry.info.status.is_finished() {
+            entry.cancel.store(true, Ordering::Release);
+            entry.info.cancel_requested = true;
+            tracing::info!(job_id = %id, "job cancellation requested");
+        }
+        Ok(entry.info.clone())
+    }
+
+    fn update(&self, id: Uuid, f: impl FnOnce(&mut JobInfo)) {
+        if let Some(entry) = self.lock().get_mut(&id) {
+            f(&mut entry.info);
+        }
+    }
+
+    fn finish(&self, id: Uuid, outcome: Result<serde_json::Value, GatewayError>) {
+        let mut jobs = self.lock();
+        if let Some(entry) = jobs.get_mut(&id) {
+            let info = &mut entry.info;
+            info.finished_at = Some(Utc::now());
+            match outcome {
+                Ok(result) => {
+                    info.status = JobStatus::Completed;
+                    info.result = Some(result);
+                }
+                Err(_) if entry.cancel.load(Ordering::Acquire) => {
+                    info.status = JobStatus::Cancelled;
+                }
+                Err(e) => {
+                    info.status = JobStatus::Failed;
+                    info.error = Some(e.to_string());
+                }
+            }
+            tracing::info!(job_id = %id, status = ?info.status, "job finished");
+        }
+        prune(&mut jobs);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Uuid, JobEntry>> {
+        self.jobs.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Drops the oldest finished jobs beyond [`MAX_FINISHED_JOBS`].
+fn prune(jobs: &mut HashMap<Uuid, JobEntry>) {
+    let mut finished: Vec<(DateTime<Utc>, Uuid)> = jobs
+        .values()
+        .filter_map(|e| e.info.finished_at.map(|at| (at, e.info.id)))
+        .collect();
+    if finished.len() <= MAX_FINISHED_JOBS {
+        return;
+    }
+    finished.sort_unstable();
+    let excess = finished.len().saturating_sub(MAX_FINISHED_JOBS);
+    for (_, id) in finished.into_iter().take(excess) {
+        jobs.remove(&id);
+    }
+}
+
+/// Handle a running job uses to report progress and observe cancellation.
+#[derive(Debug, Clone)]
+pub struct JobProgress {
+    id: Uuid,
+    registry: JobRegistry,
+    cancel: Arc<AtomicBool>,
+}
+
+impl JobProgress {
+    /// Records the total number of steps.
+    pub fn set_total(&self, total: u64) {
+        self.registry
+            .update(self.id, |info| info.total = Some(total));
+    }
+
+    /// Records one more completed step.
+    pub fn advance(&self) {
+        self.registry.update(self.id, |info| {
+            info.completed = info.completed.saturating_add(1)
+        });
+    }
+
+    /// Returns `true` once cancellation was requested.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Acquire)
+    }
+
+    /// Stops the job if cancellation was requested.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::Internal`] when the job was cancelled; the
+    /// job should propagate it so it is recorded as cancelled.
+    pub fn checkpoint(&self) -> Result<(), GatewayError> {
+        if self.is_cancelled() {
+            return Err(GatewayError::Internal("job cancelled".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn wait_finished(jobs: &JobRegistry, id: Uuid) -> JobInfo {
+        for _ in 0..200 {
+            if let Some(info) = jobs.get(id)
+                && info.status.is_finished()
+            {
+                return info;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!("job {id} did not finish");
+    }
+
+    #[tokio::test]
+    async fn jobs_report_progress_results_and_cancellation() {
+        let jobs = JobRegistry::new();
+        let done = jobs.spawn(JobKind::SnapshotAll, |progress| async move {
+            progress.set_total(2);
+            progress.advance();
+            progress.advance();
+            Ok(serde_json::json!({ "snapshots": 2 }))
+        });
+        let info = wait_finished(&jobs, done.id).await;
+        assert_eq!(info.status, JobStatus::Completed);
+        assert_eq!((info.completed, info.total), (2, Some(2)));
+        assert_eq!(info.result, Some(serde_json::json!({ "snapshots": 2 })));
+
+        let failed = jobs.spawn(JobKind::Replay, |_| async {
+            Err(GatewayError::PersistenceUnavailable)
+        });
+        let info = wait_finished(&jobs, failed.id).await;
+        assert_eq!(info.status, JobStatus::Failed);
+        assert!(info.error.is_some());
+
+        let (release, wait) = tokio::sync::oneshot::channel::<()>();
+        let slow = jobs.spawn(JobKind::BulkDelete, |progress| async move {
+            let _ = wait.await;
+            progress.checkpoint()?;
+            Ok(serde_json::Value::Null)
+        });
+        let Ok(info) = jobs.cancel(slow.id) else {
+            panic!("running job not found");
+        };
+        assert!(info.cancel_requested);
+        let _ = release.send(());
+        assert_eq!(
+            wait_finished(&jobs, slow.id).await.status,
+            JobStatus::Cancelled
+        );
+
+        assert_eq!(jobs.list().len(), 3);
+        assert!(jobs.cancel(Uuid::new_v4()).is_err());
+    }
+}
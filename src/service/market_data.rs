@@ -0,0 +1,392 @@
+//! Market data derived from pool state: best bid and offer, slippage
+//! curves, and depth charts.
+//!
+//! Curves and charts run on pools rebuilt from a [`PoolSeed`], so they
+//! never mutate live state. Depth is found by bisecting swap sizes
+//! against the pool's own pricing, which covers every pool type
+//! uniformly: curve pools are walked along their invariant and CLMM pools
+//! across their initialized ticks. The best bid and offer is read straight
+//! from the live entry, cheap enough to poll at high frequency.
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::domain::pool_entry::{OperationOutcome, PoolEntry, pro_rata};
+use crate::domain::pool_operation::{PoolOperation, SwapKind, TokenSide};
+use crate::error::GatewayError;
+
+use super::routing::{PoolSeed, RouteHop, quote_hop};
+
+/// Default number of points on a slippage curve.
+pub const DEFAULT_CURVE_POINTS: u32 = 20;
+
+/// Maximum number of points on a slippage curve.
+pub const MAX_CURVE_POINTS: u32 = 100;
+
+/// Default depth-chart half-width in basis points.
+pub const DEFAULT_DEPTH_RANGE_BPS: u32 = 500;
+
+/// Maximum depth-chart half-width in basis points.
+pub const MAX_DEPTH_RANGE_BPS: u32 = 5_000;
+
+/// Default number of levels per depth-chart side.
+pub const DEFAULT_DEPTH_LEVELS: u32 = 20;
+
+/// Maximum number of levels per depth-chart side.
+pub const MAX_DEPTH_LEVELS: u32 = 50;
+
+/// Doubling steps allowed while bracketing a depth level.
+const MAX_BRACKET_STEPS: u32 = 128;
+
+/// Where a [`Bbo`] comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BboSource {
+    /// Best resting orders of an order-book pool.
+    OrderBook,
+    /// Marginal prices of an AMM curve, net of the pool fee.
+    Curve,
+}
+
+/// Best bid and offer of a pool, in quote units per base unit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bbo {
+    /// How the prices were obtained.
+    pub source: BboSource,
+    /// Side of the pair priced.
+    pub base: TokenSide,
+    /// Highest price the pool buys base at (`None` if it cannot).
+    pub best_bid: Option<f64>,
+    /// Lowest price the pool sells base at (`None` if it cannot).
+    pub best_ask: Option<f64>,
+}
+
+impl Bbo {
+    /// Reads the best bid and offer of `entry`.
+    ///
+    /// Order-book pools report their best resting orders, priced in token
+    /// B per token A. Other pools report a synthetic quote from the slope
+    /// of their curve at the current point: selling an infinitesimal
+    /// amount of base fetches the spot price less the fee, and buying one
+    /// costs the spot price grossed up by it.
+    #[must_use]
+    pub fn of(entry: &PoolEntry) -> Self {
+        if entry.pool_type == "orderbook" {
+            return Self {
+                source: BboSource::OrderBook,
+                base: TokenSide::First,
+                best_bid: entry.orders.best_bid().map(|p| p as f64),
+                best_ask: entry.orders.best_ask().map(|p| p as f64),
+            };
+        }
+        let spot = entry.spot_price().filter(|p| *p > 0.0);
+        let keep = 1.0 - f64::from(entry.fee_bps) / 10_000.0;
+        Self {
+            source: BboSource::Curve,
+            base: entry.price_base,
+            best_bid: spot.map(|p| p * keep.max(0.0)),
+            best_ask: spot.filter(|_| keep > 0.0).map(|p| p / keep),
+        }
+    }
+
+    /// Midpoint of the bid and ask, when both exist.
+    #[must_use]
+    pub fn mid_price(&self) -> Option<f64> {
+        Some((self.best_bid? + self.best_ask?) / 2.0)
+    }
+
+    /// Ask minus bid as a share of the mid price, in basis points.
+    #[must_use]
+    pub fn spread_bps(&self) -> Option<f64> {
+        let mid = self.mid_price().filter(|m| *m > 0.0)?;
+        Some((self.best_ask? - self.best_bid?) / mid * 10_000.0)
+    }
+}
+
+/// One cumulative level of a depth chart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthLevel {
+    /// Distance from the mid price in basis points.
+    pub offset_bps: u32,
+    /// Price of the base token at this level.
+    pub price: f64,
+    /// Cumulative base-token amount tradeable up to this level.
+    pub base_amount: u128,
+    /// Cumulative quote-token amount on the other side of those trades.
+    pub quote_amount: u128,
+}
+
+/// Cumulative buy and sell liquidity around the current price.
+///
+/// The base token follows the pool's price convention; prices are in
+/// units of the other token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthChart {
+    /// Side of the pair priced by the chart.
+    pub base: TokenSide,
+    /// Spot price before any trade.
+    pub mid_price: f64,
+    /// Selling base into the pool, moving the price down.
+    pub bids: Vec<DepthLevel>,
+    /// Buying base from the pool, moving the price up.
+    pub asks: Vec<DepthLevel>,
+}
+
+/// Builds a depth chart with `levels` evenly spaced levels per side out
+/// to `range_bps` from the mid price.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] if the pool cannot currently
+/// price, or a [`GatewayError`] if the sandbox cannot be rebuilt.
+pub fn depth_chart(
+    seed: &PoolSeed,
+    range_bps: u32,
+    levels: u32,
+) -> Result<DepthChart, GatewayError> {
+    let sandbox = seed.build()?;
+    let base = sandbox.price_base;
+    let mid_price = sandbox
+        .spot_price()
+        .filter(|p| *p > 0.0)
+        .ok_or_else(|| GatewayError::InvalidRequest("pool cannot price".to_string()))?;
+    let range_bps = range_bps.clamp(1, MAX_DEPTH_RANGE_BPS);
+    let levels = levels.clamp(1, MAX_DEPTH_LEVELS);
+
+    let mut bids = Vec::with_capacity(levels as usize);
+    let mut asks = Vec::with_capacity(levels as usize);
+    let (mut bid_floor, mut ask_floor) = (0u128, 0u128);
+    for i in 1..=levels {
+        let offset_bps = range_bps.saturating_mul(i) / levels;
+        let shift = f64::from(offset_bps) / 10_000.0;
+
+        // Bids: base goes in, price falls to the level.
+        let bid_price = mid_price * (1.0 - shift);
+        let (amount_in, amount_out) = max_fill(seed, base, bid_floor, |p| p >= bid_price);
+        bid_floor = amount_in;
+        bids.push(DepthLevel {
+            offset_bps,
+            price: bid_price,
+            base_amount: amount_in,
+            quote_amount: amount_out,
+        });
+
+        // Asks: quote goes in, price rises to the level.
+        let ask_price = mid_price * (1.0 + shift);
+        let (amount_in, amount_out) = max_fill(seed, base.other(), ask_floor, |p| p <= ask_price);
+        ask_floor = amount_in;
+        asks.push(DepthLevel {
+            offset_bps,
+            price: ask_price,
+            base_amount: amount_out,
+            quote_amount: amount_in,
+        });
+    }
+
+    Ok(DepthChart {
+        base,
+        mid_price,
+        bids,
+        asks,
+    })
+}
+
+/// Outcome of one sizing probe.
+enum Fill {
+    /// The swap fills and stays within the level; carries the output.
+    Fits(u128),
+    /// The swap fills but moves the price past the level.
+    Beyond,
+    /// The pool rejects the swap (too small to produce output, or too
+    /// large for its liquidity).
+    Rejected,
+}
+
+/// Finds (to within 1 bp of size) the largest exact-in swap of the
+/// `side` token, at least `floor`, whose post-trade spot price satisfies
+/// `within`. Returns `(amount_in, amount_out)`.
+fn max_fill(
+    seed: &PoolSeed,
+    side: TokenSide,
+    floor: u128,
+    within: impl Fn(f64) -> bool,
+) -> (u128, u128) {
+    let fill = |amount: u128| match probe(seed, side, amount) {
+        Some((out, price)) if within(price) => Fill::Fits(out),
+        Some(_) => Fill::Beyond,
+        None => Fill::Rejected,
+    };
+
+    let mut lo = (floor, 0);
+    if floor > 0
+        && let Fill::Fits(out) = fill(floor)
+    {
+        lo.1 = out;
+    }
+
+    // Bracket by doubling. Rejections below the first fill are dust
+    // sizes; past it they mean the pool ran out of liquidity.
+    let mut hi = floor.max(1);
+    let mut filled = floor > 0;
+    for _ in 0..MAX_BRACKET_STEPS {
+        match fill(hi) {
+            Fill::Fits(out) => {
+                lo = (hi, out);
+                filled = true;
+            }
+            Fill::Beyond => break,
+            Fill::Rejected if filled => break,
+            Fill::Rejected => {}
+        }
+        if hi == u128::MAX {
+            return lo;
+        }
+        hi = hi.saturating_mul(2);
+    }
+
+    while hi - lo.0 > (lo.0 / 10_000).max(1) {
+        let mid = lo.0 + (hi - lo.0) / 2;
+        match fill(mid) {
+            Fill::Fits(out) => lo = (mid, out),
+            Fill::Beyond | Fill::Rejected => hi = mid,
+        }
+    }
+    lo
+}
+
+/// Swaps `amount` of the `side` token on a fresh sandbox, returning the
+/// output and the post-trade spot price of the first token.
+fn probe(seed: &PoolSeed, side: TokenSide, amount: u128) -> Option<(u128, f64)> {
+    let mut sandbox = seed.build().ok()?;
+    let op = PoolOperation::Swap {
+        token_in: side,
+        kind: SwapKind::ExactIn,
+        amount: amount.to_string(),
+    };
+    let OperationOutcome::Swap(result) = sandbox.apply(&op).ok()? else {
+        return None;
+    };
+    Some((result.amount_out().get(), sandbox.spot_price()?))
+}
+
+/// Quotes exact-in swaps of the `side` token at `points` evenly spaced
+/// sizes up to `max_amount`.
+///
+/// The curve stops at the first size the pool cannot fill, so the result
+/// may have fewer than `points` entries.
+#[must_use]
+pub fn slippage_curve(
+    seed: &PoolSeed,
+    side: TokenSide,
+    points: u32,
+    max_amount: u128,
+) -> Vec<RouteHop> {
+    let points = points.clamp(1, MAX_CURVE_POINTS);
+    let mut curve = Vec::with_capacity(points as usize);
+    for i in 1..=points {
+        let amount = pro_rata(max_amount, u128::from(i), u128::from(points));
+        if amount == 0 {
+            continue;
+        }
+        let Ok(hop) = quote_hop(seed, side, amount) else {
+            break;
+        };
+        curve.push(hop);
+    }
+    curve
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::PoolId;
+    use crate::service::pool_service::build_entry;
+
+    fn cp_seed_config() -> serde_json::Value {
+        serde_json::json!({
+            "token_a": {"address": "0xaaa", "decimals": 6},
+            "token_b": {"address": "0xbbb", "decimals": 6},
+            "fee_bps": 30,
+            "reserve_a": "1000000",
+            "reserve_b": "1000000",
+        })
+    }
+
+    fn cp_seed() -> PoolSeed {
+        let Ok(entry) = build_entry(PoolId::new(), "constant_product", cp_seed_config()) else {
+            panic!("entry build failed");
+        };
+        let Some(seed) = PoolSeed::of(&entry) else {
+            panic!("entry has no config");
+        };
+        seed
+    }
+
+    #[test]
+    fn bbo_straddles_spot_by_the_fee() {
+        let Ok(mut entry) = build_entry(PoolId::new(), "constant_product", cp_seed_config()) else {
+            panic!("entry build failed");
+        };
+        let bbo = Bbo::of(&entry);
+        assert_eq!(bbo.source, BboSource::Curve);
+        let (Some(bid), Some(ask), Some(mid), Some(spread)) = (
+            bbo.best_bid,
+            bbo.best_ask,
+            bbo.mid_price(),
+            bbo.spread_bps(),
+        ) else {
+            panic!("curve pool without a quote");
+        };
+        assert!((bid - 0.997).abs() < 1e-9);
+        assert!(bid < 1.0 && 1.0 < ask && bid < mid && mid < ask);
+        // Two fees wide, plus the gross-up on the ask
+        assert!((spread - 60.09).abs() < 0.01);
+
+        entry.fee_bps = 10_000;
+        let bbo = Bbo::of(&entry);
+        assert_eq!((bbo.best_ask, bbo.mid_price()), (None, None));
+    }
+
+    #[test]
+    fn impact_worsens_with_size() {
+        let seed = cp_seed();
+        let curve = slippage_curve(&seed, TokenSide::First, 10, 500_000);
+        assert_eq!(curve.len(), 10);
+        assert_eq!(curve.last().map(|p| p.amount_in), Some(500_000));
+        assert!(
+            curve
+                .windows(2)
+                .all(|w| matches!(w, [a, b] if b.price_impact_bps <= a.price_impact_bps))
+        );
+    }
+
+    #[test]
+    fn depth_is_cumulative_and_matches_curve() {
+        let seed = cp_seed();
+        let Ok(chart) = depth_chart(&seed, 400, 4) else {
+            panic!("depth chart failed");
+        };
+        assert!((chart.mid_price - 1.0).abs() < f64::EPSILON);
+        assert_eq!(chart.bids.len(), 4);
+        assert!(
+            chart
+                .bids
+                .windows(2)
+                .all(|w| matches!(w, [a, b] if b.base_amount > a.base_amount))
+        );
+        assert!(
+            chart
+                .asks
+                .windows(2)
+                .all(|w| matches!(w, [a, b] if b.quote_amount > a.quote_amount))
+        );
+
+        // x*y=k with a 30 bps fee: a -4% move needs roughly
+        // 1e6 * (1/sqrt(0.96) - 1) / 0.997 ≈ 20_700 base in.
+        let Some(last) = chart.bids.last() else {
+            panic!("no bid levels");
+        };
+        assert!((20_000..21_500).contains(&last.base_amount));
+    }
+}
@@ -0,0 +1,309 @@
+//! JSON Schema validation of pool metadata.
+//!
+//! Pools carry free-form `metadata`, set at creation and replaced with
+//! `PATCH /api/v1/pools/{id}`. So that downstream indexers can rely on its
+//! shape, operators register JSON Schemas per tenant (the caller's client
+//! id) and per pool type. Metadata must satisfy every schema that applies:
+//! the caller's tenant schema and the pool type's schema. Violations are
+//! reported per field, as JSON pointers into the metadata. `null`
+//! metadata (none, or cleared) is never validated.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use chrono::{DateTime, Utc};
+use jsonschema::Validator;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::{FieldError, GatewayError};
+use crate::persistence::models::MetadataSchemaRecord;
+
+/// What a metadata schema applies to.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaScope {
+    /// Pools created or updated by one client id.
+    Tenant,
+    /// Pools of one pool type.
+    PoolType,
+}
+
+impl SchemaScope {
+    /// Scope name as used in paths and the database.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Tenant => "tenant",
+            Self::PoolType => "pool_type",
+        }
+    }
+}
+
+impl fmt::Display for SchemaScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SchemaScope {
+    type Err = GatewayError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tenant" => Ok(Self::Tenant),
+            "pool_type" => Ok(Self::PoolType),
+            other => Err(GatewayError::InvalidRequest(format!(
+                "unknown schema scope '{other}' (expected tenant or pool_type)"
+            ))),
+        }
+    }
+}
+
+/// A registered schema and its compiled validator.
+#[derive(Debug, Clone)]
+struct Registered {
+    schema: serde_json::Value,
+    validator: Arc<Validator>,
+    updated_at: DateTime<Utc>,
+}
+
+/// Shared table of metadata schemas by scope and name.
+#[derive(Debug, Clone, Default)]
+pub struct MetadataSchemas {
+    schemas: Arc<RwLock<HashMap<(SchemaScope, String), Registered>>>,
+}
+
+impl MetadataSchemas {
+    /// Creates an empty table.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Installs persisted schemas. Rows with an unknown scope or a schema
+    /// that no longer compiles are skipped and returned as warnings.
+    pub fn load(&self, records: &[MetadataSchemaRecord]) -> Vec<String> {
+        let mut skipped = Vec::new();
+        for record in records {
+            let result = record.scope.parse().and_then(|scope| {
+                self.set(
+                    scope,
+                    &record.name,
+                    record.schema.clone(),
+                    record.updated_at,
+                )
+            });
+            if let Err(e) = result {
+                skipped.push(format!("{}:{}: {e}", record.scope, record.name));
+            }
+        }
+        skipped
+    }
+
+    /// Compiles `schema` and registers it for `scope`/`name`, replacing
+    /// any previous schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InvalidRequest`] if `schema` is not a valid
+    /// JSON Schema.
+    pub fn set(
+        &self,
+        scope: SchemaScope,
+        name: &str,
+        schema: serde_json::Value,
+        updated_at: DateTime<Utc>,
+    ) -> Result<(), GatewayError> {
+        let validator = compile(&schema)?;
+        self.schemas
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                (scope, name.to_string()),
+                Registered {
+                    schema,
+                    validator: Arc::new(validator),
+                    updated_at,
+                },
+            );
+        Ok(())
+    }
+
+    /// Removes the schema of `scope`/`name`. Returns `true` if it existed.
+    pub fn remove(&self, scope: SchemaScope, name: &str) -> bool {
+        self.schemas
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&(scope, name.to_string()))
+            .is_some()
+    }
+
+    /// Returns every schema, by scope then name.
+    #[must_use]
+    pub fn list(&self) -> Vec<MetadataSchemaRecord> {
+        let schemas = self.schemas.read().unwrap_or_else(PoisonError::into_inner);
+        schemas
+            .iter()
+            .map(|((scope, name), registered)| ((*scope, name.clone()), registered))
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .map(|((scope, name), registered)| MetadataSchemaRecord {
+                scope: scope.to_string(),
+                name,
+                schema: registered.schema.clone(),
+                updated_at: registered.updated_at,
+            })
+            .collect()
+    }
+
+    /// Checks `metadata` against the schema of `tenant` (if any) and of
+    /// `pool_type`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InvalidMetadata`] listing every violation
+    /// of every applicable schema.
+    pub fn validate(
+        &self,
+        tenant: Option<&str>,
+        pool_type: &str,
+        metadata: &serde_json::Value,
+    ) -> Result<(), GatewayError> {
+        if metadata.is_null() {
+            return Ok(());
+        }
+        let applicable = {
+            let schemas = self.schemas.read().unwrap_or_else(PoisonError::into_inner);
+            let tenant = tenant.and_then(|name| {
+                let key = (SchemaScope::Tenant, name.to_string());
+                schemas.get(&key).map(|r| (key, Arc::clone(&r.validator)))
+            });
+            let key = (SchemaScope::PoolType, pool_type.to_string());
+            let pool_type = schemas.get(&key).map(|r| (key, Arc::clone(&r.validator)));
+            tenant.into_iter().chain(pool_type).collect::<Vec<_>>()
+        };
+
+        let errors: Vec<FieldError> = applicable
+            .iter()
+            .flat_map(|((scope, name), validator)| {
+                validator.iter_errors(metadata).map(move |e| FieldError {
+                    field: e.instance_path.to_string(),
+                    message: e.to_string(),
+                    schema: format!("{scope}:{name}"),
+                })
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(GatewayError::InvalidMetadata(errors))
+        }
+    }
+}
+
+/// Compiles `schema` into a validator.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] if `schema` is not a valid
+/// JSON Schema.
+pub fn compile(schema: &serde_json::Value) -> Result<Validator, GatewayError> {
+    jsonschema::validator_for(schema)
+        .map_err(|e| GatewayError::InvalidRequest(format!("invalid JSON Schema: {e}")))
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_must_satisfy_tenant_and_pool_type_schemas() {
+        let schemas = MetadataSchemas::new();
+        let now = Utc::now();
+        let pool_type_schema = serde_json::json!({
+            "type": "object",
+            "required": ["desk"],
+            "properties": { "desk": { "type": "string" } },
+        });
+        let tenant_schema = serde_json::json!({
+            "type": "object",
+            "properties": { "tags": { "type": "array", "items": { "type": "string" } } },
+        });
+        let Ok(()) = schemas.set(
+            SchemaScope::PoolType,
+            "constant_product",
+            pool_type_schema,
+            now,
+        ) else {
+            panic!("schema rejected");
+        };
+        let Ok(()) = schemas.set(SchemaScope::Tenant, "acme", tenant_schema, now) else {
+            panic!("schema rejected");
+        };
+        assert!(
+            schemas
+                .set(
+                    SchemaScope::Tenant,
+                    "bad",
+                    serde_json::json!({"type": 5}),
+                    now
+                )
+                .is_err()
+        );
+
+        let valid = serde_json::json!({ "desk": "rates", "tags": ["otc"] });
+        assert!(
+            schemas
+                .validate(Some("acme"), "constant_product", &valid)
+                .is_ok()
+        );
+        assert!(
+            schemas
+                .validate(None, "stable_swap", &serde_json::json!(42))
+                .is_ok()
+        );
+        assert!(
+            schemas
+                .validate(Some("acme"), "constant_product", &serde_json::Value::Null)
+                .is_ok()
+        );
+
+        let invalid = serde_json::json!({ "tags": ["otc", 7] });
+        let Err(GatewayError::InvalidMetadata(errors)) =
+            schemas.validate(Some("acme"), "constant_product", &invalid)
+        else {
+            panic!("invalid metadata accepted");
+        };
+        assert_eq!(errors.len(), 2);
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.field == "/tags/1" && e.schema == "tenant:acme")
+        );
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.field.is_empty() && e.schema == "pool_type:constant_product")
+        );
+
+        // A persisted row survives a reload; the list is ordered by scope
+        let reloaded = MetadataSchemas::new();
+        assert!(reloaded.load(&schemas.list()).is_empty());
+        assert_eq!(
+            reloaded
+                .list()
+                .iter()
+                .map(|r| r.scope.as_str())
+                .collect::<Vec<_>>(),
+            vec!["tenant", "pool_type"]
+        );
+        assert!(reloaded.remove(SchemaScope::Tenant, "acme"));
+        assert!(!reloaded.remove(SchemaScope::Tenant, "acme"));
+    }
+}
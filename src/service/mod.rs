@@ -3,6 +3,44 @@
 //! [`PoolService`] coordinates pool operations, delegates computation
 //! to hydra-amm, and emits events through the [`super::domain::EventBus`].
 
+pub mod attestation;
+pub mod backtest;
+pub mod candles;
+pub mod concurrency;
+pub mod contention;
+pub mod event_hook;
+pub mod event_import;
+pub mod event_log;
+pub mod event_tail;
+pub mod fee_program;
+pub mod idempotency;
+pub mod jobs;
+pub mod market_data;
+pub mod metadata_schema;
+pub mod ownership;
+pub mod pool_defaults;
 pub mod pool_service;
+pub mod pool_stats;
+pub mod pricing;
+pub mod quota;
+pub mod quote_audit;
+pub mod quote_runtime;
+pub mod replay;
+pub mod replica;
+pub mod rewards;
+pub mod risk;
+pub mod route_limits;
+pub mod routing;
+pub mod sandbox;
+pub mod snapshot;
+pub mod snapshot_policy;
+pub mod snapshot_scheduler;
+pub mod stale_pools;
+pub mod timing;
+pub mod trade_tape;
+pub mod usage;
+pub mod volatility;
+pub mod webhooks;
 
+pub use event_hook::EventHook;
 pub use pool_service::PoolService;
@@ -0,0 +1,102 @@
+//! Pool ownership and who may manage a pool.
+//!
+//! The client (`X-Client-Id`) that creates a pool becomes its owner.
+//! Managing it — updating its metadata, deleting it, or transferring it —
+//! is then reserved to the owner and to the admin clients listed in
+//! `ADMIN_CLIENT_IDS`. Pools created without a client id have no owner
+//! and stay open to any caller, as before ownership existed, except that
+//! only an admin may hand them an owner.
+
+use std::collections::BTreeSet;
+
+use crate::api::client_id::parse_client_id;
+
+/// Who is asking to manage a pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Manager<'a> {
+    /// The gateway itself: admin endpoints and background jobs.
+    Operator,
+    /// A client request, with its client id if it sent one.
+    Client(Option<&'a str>),
+}
+
+impl<'a> Manager<'a> {
+    /// Client id recorded as the actor of the resulting event.
+    #[must_use]
+    pub const fn actor(self) -> Option<&'a str> {
+        match self {
+            Self::Operator => None,
+            Self::Client(client) => client,
+        }
+    }
+}
+
+/// Parses a comma-separated list of admin client ids.
+///
+/// Entries that are not valid client ids are skipped.
+#[must_use]
+pub fn parse_admins(spec: &str) -> BTreeSet<String> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .filter_map(|id| parse_client_id(id).ok())
+        .collect()
+}
+
+/// Returns `true` if `manager` is the gateway itself or one of `admins`.
+#[must_use]
+pub fn is_admin(manager: Manager<'_>, admins: &BTreeSet<String>) -> bool {
+    match manager {
+        Manager::Operator => true,
+        Manager::Client(client) => client.is_some_and(|id| admins.contains(id)),
+    }
+}
+
+/// Returns `true` if `manager` may manage a pool owned by `owner`.
+///
+/// Unowned pools may be managed by anyone unless `transfer` is set:
+/// assigning an owner to one is reserved to admins.
+#[must_use]
+pub fn may_manage(
+    owner: Option<&str>,
+    manager: Manager<'_>,
+    admins: &BTreeSet<String>,
+    transfer: bool,
+) -> bool {
+    if is_admin(manager, admins) {
+        return true;
+    }
+    let client = manager.actor();
+    match owner {
+        Some(owner) => client == Some(owner),
+        None => !transfer,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn owner_and_admins_manage_owned_pools() {
+        let admins = parse_admins(" root , ops-1,,not valid");
+        assert_eq!(
+            admins.iter().map(String::as_str).collect::<Vec<_>>(),
+            vec!["ops-1", "root"]
+        );
+
+        let client =
+            |owner, id, transfer| may_manage(owner, Manager::Client(id), &admins, transfer);
+
+        let owned = Some("alice");
+        assert!(client(owned, Some("alice"), true));
+        assert!(client(owned, Some("root"), false));
+        assert!(may_manage(owned, Manager::Operator, &admins, true));
+        assert!(!client(owned, Some("bob"), false));
+        assert!(!client(owned, None, false));
+
+        assert!(client(None, None, false));
+        assert!(!client(None, Some("bob"), true));
+        assert!(client(None, Some("ops-1"), true));
+    }
+}
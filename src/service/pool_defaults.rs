@@ -0,0 +1,86 @@
+//! Deployment-wide defaults for pool creation configs.
+//!
+//! Operators can set a default fee tier, CLMM tick spacing, and hybrid
+//! amplification for their environment, so standard pool launches need
+//! not repeat them. A default fills a field only when the create request
+//! leaves it out; an explicit value always wins. Defaults are applied
+//! before the config is stored, so replay never depends on the settings
+//! of the instance that rebuilds a pool.
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Default config fields for new pools; `None` leaves a field required.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct PoolDefaults {
+    /// Fee tier in basis points, for every pool type.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_bps: Option<u32>,
+    /// Tick spacing of `clmm` pools.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tick_spacing: Option<u32>,
+    /// Amplification of `hybrid` pools.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amplification: Option<u32>,
+}
+
+impl PoolDefaults {
+    /// The defaults that apply to `pool_type`.
+    #[must_use]
+    pub fn for_type(&self, pool_type: &str) -> Self {
+        Self {
+            fee_bps: self.fee_bps,
+            tick_spacing: self.tick_spacing.filter(|_| pool_type == "clmm"),
+            amplification: self.amplification.filter(|_| pool_type == "hybrid"),
+        }
+    }
+
+    /// Fills the fields of `config` that `pool_type` takes and the request
+    /// left out. Configs that are not objects are returned unchanged.
+    #[must_use]
+    pub fn apply(&self, pool_type: &str, mut config: serde_json::Value) -> serde_json::Value {
+        let defaults = self.for_type(pool_type);
+        if let Some(fields) = config.as_object_mut() {
+            for (key, value) in [
+                ("fee_bps", defaults.fee_bps),
+                ("tick_spacing", defaults.tick_spacing),
+                ("amplification", defaults.amplification),
+            ] {
+                if let Some(value) = value {
+                    fields
+                        .entry(key)
+                        .or_insert_with(|| serde_json::json!(value));
+                }
+            }
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_fill_only_missing_fields_of_the_type() {
+        let defaults = PoolDefaults {
+            fee_bps: Some(30),
+            tick_spacing: Some(60),
+            amplification: Some(100),
+        };
+        let clmm = defaults.apply("clmm", serde_json::json!({"fee_bps": 5}));
+        assert_eq!(clmm, serde_json::json!({"fee_bps": 5, "tick_spacing": 60}));
+
+        let hybrid = defaults.apply("hybrid", serde_json::json!({}));
+        assert_eq!(
+            hybrid,
+            serde_json::json!({"fee_bps": 30, "amplification": 100})
+        );
+
+        assert_eq!(
+            PoolDefaults::default().apply("constant_product", serde_json::json!({})),
+            serde_json::json!({})
+        );
+    }
+}
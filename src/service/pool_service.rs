@@ -1,17 +1,156 @@
 //! Pool service: orchestrates pool operations and emits events.
 
+use std::collections::BTreeSet;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use hydra_amm::config::AmmConfig;
-use hydra_amm::domain::{LiquidityChange, Position, SwapResult, SwapSpec, Token};
+use hydra_amm::domain::{Amount, Liquidity, SwapResult, SwapSpec, Token};
 use hydra_amm::factory::DefaultPoolFactory;
 use hydra_amm::traits::{LiquidityPool, SwapPool};
-
-use crate::domain::pool_entry::{PoolEntry, PoolSummary};
+use tokio::sync::RwLock;
+
+use super::attestation::{self, EventSigner};
+use super::contention::{ContentionTracker, TrackedWriteGuard};
+use super::event_hook::EventHook;
+use super::event_log::EventLogSink;
+use super::fee_program::{FeeProgram, FeeRebate};
+use super::market_data::Bbo;
+use super::ownership::{self, Manager};
+use super::risk::{self, RiskInputs};
+use super::routing::{FULL_CANARY_WEIGHT_BPS, PoolSeed, RoutePlan};
+use super::sandbox::SandboxStock;
+use super::timing::{self, Phase};
+use super::{snapshot, stale_pools};
+use crate::api::config_parser::{self, ConfigIssue};
+use crate::domain::order_book::{BookDepth, LimitOrder, OrderSide};
+use crate::domain::pool_entry::{
+    KnownToken, LiquidityPosition, OperationOutcome, PoolEntry, PoolStatus, PoolSummary,
+    known_tokens,
+};
 use crate::domain::pool_event::{LiquidityChangeType, PoolEvent, PriceChangeReason};
-use crate::domain::{EventBus, PoolId, PoolRegistry};
+use crate::domain::pool_operation::{PoolOperation, SwapKind, TickRange, TokenSide};
+use crate::domain::{
+    EventBus, Journal, LpLedger, OrderId, PoolFilter, PoolId, PoolRegistry, PositionId, Tombstone,
+};
 use crate::error::GatewayError;
+use crate::persistence::models::PoolSnapshot;
+use crate::persistence::postgres::PostgresPersistence;
+
+/// Upper bounds on what clients can make the gateway hold in memory;
+/// `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CapacityLimits {
+    /// Pools in the registry.
+    pub max_pools: Option<usize>,
+    /// Liquidity positions tracked per pool.
+    pub max_positions_per_pool: Option<usize>,
+}
+
+/// Current registry usage against [`CapacityLimits`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CapacityUsage {
+    /// Configured limits.
+    pub limits: CapacityLimits,
+    /// Pools in the registry.
+    pub pools: usize,
+    /// Positions tracked across all pools.
+    pub positions: usize,
+    /// Positions tracked by the pool holding the most.
+    pub max_positions_in_a_pool: usize,
+    /// Mutations rejected because a pool lock timed out, since startup.
+    pub lock_timeouts: u64,
+}
+
+/// What [`PoolService::close_position`] paid out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClosedPosition {
+    /// Fees collected per token, in pool order.
+    pub fees: [u128; 2],
+    /// LP units burned; 0 if the position was already empty.
+    pub liquidity_burned: u128,
+    /// Tokens withdrawn in pool order, for pools with tracked reserves
+    /// (`None` otherwise, or if nothing was burned).
+    pub withdrawn: Option<[u128; 2]>,
+}
+
+/// Result of [`PoolService::set_oracle_price`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OracleUpdate {
+    /// Oracle price before the update.
+    pub previous_price: f64,
+    /// Spot price after the update, per the pool's price convention.
+    pub spot_price: Option<f64>,
+}
+
+/// Conditions a swap must meet to execute; unset ones are not checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SwapConditions {
+    /// Execute only if the pool is still at this
+    /// [version](PoolEntry::version).
+    pub expected_version: Option<u64>,
+    /// Least output accepted.
+    pub min_amount_out: Option<u128>,
+    /// Most input accepted.
+    pub max_amount_in: Option<u128>,
+    /// Execute only until this time.
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+impl SwapConditions {
+    /// Returns `true` if a slippage bound is set.
+    #[must_use]
+    pub const fn has_slippage_bounds(&self) -> bool {
+        self.min_amount_out.is_some() || self.max_amount_in.is_some()
+    }
+
+    /// Checks a quoted fill against the slippage bounds.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::SlippageExceeded`] if the output is below
+    /// `min_amount_out` or the input above `max_amount_in`.
+    pub fn check_fill(&self, quoted: &SwapResult) -> Result<(), GatewayError> {
+        let amount_out = quoted.amount_out().get();
+        if let Some(min) = self.min_amount_out
+            && amount_out < min
+        {
+            return Err(GatewayError::SlippageExceeded {
+                bound: "min_amount_out",
+                limit: min.to_string(),
+                quoted: amount_out.to_string(),
+            });
+        }
+        let amount_in = quoted.amount_in().get();
+        if let Some(max) = self.max_amount_in
+            && amount_in > max
+        {
+            return Err(GatewayError::SlippageExceeded {
+                bound: "max_amount_in",
+                limit: max.to_string(),
+                quoted: amount_in.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Rejects an operation whose `deadline` is before `now`.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::DeadlineExpired`] if the deadline has passed.
+pub fn check_deadline(
+    deadline: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<(), GatewayError> {
+    match deadline {
+        Some(deadline) if deadline < now => Err(GatewayError::DeadlineExpired { deadline }),
+        _ => Ok(()),
+    }
+}
 
 /// Orchestration layer for all pool operations.
 ///
@@ -23,6 +162,17 @@ use crate::error::GatewayError;
 pub struct PoolService {
     registry: Arc<PoolRegistry>,
     event_bus: EventBus,
+    event_signer: Option<Arc<EventSigner>>,
+    read_only: bool,
+    limits: CapacityLimits,
+    fee_program: Option<Arc<FeeProgram>>,
+    lock_timeout: Option<Duration>,
+    lock_timeouts: Arc<AtomicU64>,
+    contention: ContentionTracker,
+    admins: Arc<BTreeSet<String>>,
+    hooks: Arc<[Arc<dyn EventHook>]>,
+    sandboxes: SandboxStock,
+    event_log: Option<EventLogSink>,
 }
 
 impl PoolService {
@@ -32,7 +182,246 @@ impl PoolService {
         Self {
             registry,
             event_bus,
+            event_signer: None,
+            read_only: false,
+            limits: CapacityLimits::default(),
+            fee_program: None,
+            lock_timeout: None,
+            lock_timeouts: Arc::new(AtomicU64::new(0)),
+            contention: ContentionTracker::new(),
+            admins: Arc::default(),
+            hooks: Arc::new([]),
+            sandboxes: SandboxStock::new(),
+            event_log: None,
+        }
+    }
+
+    /// Attaches Ed25519 attestations to serialized events.
+    #[must_use]
+    pub fn with_event_signer(mut self, signer: EventSigner) -> Self {
+        self.event_signer = Some(Arc::new(signer));
+        self
+    }
+
+    /// Refuses every mutation with [`GatewayError::ReadOnlyReplica`]; pool
+    /// state then changes only through replication.
+    #[must_use]
+    pub fn with_read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Rejects pool creations and new positions beyond `limits` with
+    /// [`GatewayError::CapacityExceeded`].
+    #[must_use]
+    pub const fn with_capacity_limits(mut self, limits: CapacityLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Rebates part of the fee on swaps by accounts in a volume tier of
+    /// `program`, and counts their volume toward it.
+    #[must_use]
+    pub fn with_fee_program(mut self, program: Arc<FeeProgram>) -> Self {
+        self.fee_program = Some(program);
+        self
+    }
+
+    /// Rejects mutations that wait longer than `timeout` for a pool's
+    /// write lock with [`GatewayError::LockTimeout`], so one stuck pool
+    /// cannot pile up requests.
+    #[must_use]
+    pub const fn with_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = Some(timeout);
+        self
+    }
+
+    /// Lets the clients in `admins` manage every pool, whoever owns it.
+    #[must_use]
+    pub fn with_admins(mut self, admins: BTreeSet<String>) -> Self {
+        self.admins = Arc::new(admins);
+        self
+    }
+
+    /// Calls `hook` with every event, after any hooks already
+    /// registered and before the event is published.
+    #[must_use]
+    pub fn with_event_hook(mut self, hook: Arc<dyn EventHook>) -> Self {
+        let mut hooks = self.hooks.to_vec();
+        hooks.push(hook);
+        self.hooks = hooks.into();
+        self
+    }
+
+    /// Hands every emitted event to the event log writer through `sink`,
+    /// and refuses mutations with [`GatewayError::EventLogBacklog`] while
+    /// the writer is too far behind.
+    #[must_use]
+    pub fn with_event_log(mut self, sink: EventLogSink) -> Self {
+        self.event_log = Some(sink);
+        self
+    }
+
+    /// Returns the fee-tier program, if one is configured.
+    #[must_use]
+    pub fn fee_program(&self) -> Option<&FeeProgram> {
+        self.fee_program.as_deref()
+    }
+
+    /// Returns the per-pool lock contention statistics.
+    #[must_use]
+    pub const fn contention(&self) -> &ContentionTracker {
+        &self.contention
+    }
+
+    /// Returns the configured capacity limits.
+    #[must_use]
+    pub const fn capacity_limits(&self) -> CapacityLimits {
+        self.limits
+    }
+
+    /// Counts pools and positions held in the registry.
+    pub async fn capacity_usage(&self) -> CapacityUsage {
+        let entries = self.registry.entries().await;
+        let mut usage = CapacityUsage {
+            limits: self.limits,
+            pools: entries.len(),
+            lock_timeouts: self.lock_timeouts.load(Ordering::Relaxed),
+            ..CapacityUsage::default()
+        };
+        for entry_lock in entries {
+            let positions = entry_lock.read().await.positions.len();
+            usage.positions = usage.positions.saturating_add(positions);
+            usage.max_positions_in_a_pool = usage.max_positions_in_a_pool.max(positions);
         }
+        usage
+    }
+
+    /// Returns `true` if this service refuses mutations.
+    #[must_use]
+    pub const fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn ensure_writable(&self) -> Result<(), GatewayError> {
+        if self.read_only {
+            return Err(GatewayError::ReadOnlyReplica);
+        }
+        if let Some(sink) = &self.event_log {
+            sink.ensure_room()?;
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if `manager` is the gateway itself or an admin
+    /// client.
+    #[must_use]
+    pub fn is_admin(&self, manager: Manager<'_>) -> bool {
+        ownership::is_admin(manager, &self.admins)
+    }
+
+    /// Rejects `manager` unless it may `operation` the pool in `entry`.
+    fn authorize(
+        &self,
+        entry: &PoolEntry,
+        manager: Manager<'_>,
+        operation: &str,
+        transfer: bool,
+    ) -> Result<(), GatewayError> {
+        if ownership::may_manage(entry.owner.as_deref(), manager, &self.admins, transfer) {
+            return Ok(());
+        }
+        Err(GatewayError::Forbidden(match &entry.owner {
+            Some(owner) => format!(
+                "only the owner ({owner}) or an admin may {operation} pool {}",
+                entry.pool_id
+            ),
+            None => format!(
+                "only an admin may {operation} unowned pool {}",
+                entry.pool_id
+            ),
+        }))
+    }
+
+    /// Takes the write lock of `pool_id` for `operation`, timed as
+    /// [`Phase::Lock`], bounded by the lock timeout, and tracked for
+    /// contention statistics. Timeouts are counted and logged. A pool
+    /// removed while the operation waited is reported as not found.
+    async fn write_entry<'a>(
+        &self,
+        pool_id: PoolId,
+        lock: &'a RwLock<PoolEntry>,
+        operation: &'static str,
+    ) -> Result<TrackedWriteGuard<'a>, GatewayError> {
+        let ticket = self.contention.enqueue(pool_id, operation);
+        let acquire = timing::measure(Phase::Lock, lock.write());
+        let Some(timeout) = self.lock_timeout else {
+            return live(pool_id, ticket.acquired(acquire.await));
+        };
+        match tokio::time::timeout(timeout, acquire).await {
+            Ok(guard) => live(pool_id, ticket.acquired(guard)),
+            Err(_) => {
+                ticket.timed_out();
+                let total = self
+                    .lock_timeouts
+                    .fetch_add(1, Ordering::Relaxed)
+                    .saturating_add(1);
+                let retry_after_ms = u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX);
+                tracing::warn!(%pool_id, operation, timeout_ms = retry_after_ms, total, "pool write lock timed out");
+                Err(GatewayError::LockTimeout {
+                    pool_id: *pool_id.as_uuid(),
+                    retry_after_ms,
+                })
+            }
+        }
+    }
+
+    /// Runs the event hooks on `event`, logs it, and publishes it on the
+    /// bus.
+    ///
+    /// For events of changes made outside the service, such as those a
+    /// replica replays or reward claims; the service's own mutations emit
+    /// theirs.
+    pub fn publish(&self, event: PoolEvent) {
+        self.emit(event);
+    }
+
+    /// Emits `event`, which leaves its pool's version unchanged.
+    fn emit(&self, event: PoolEvent) {
+        self.dispatch(event, None);
+    }
+
+    /// Emits `event`, which left its pool at `version`. Called with the
+    /// pool's write lock held, so the event log receives each pool's
+    /// versions in order.
+    fn emit_versioned(&self, event: PoolEvent, version: u64) {
+        self.dispatch(event, Some(version));
+    }
+
+    /// Runs the event hooks on `event`, queues it for the event log, and
+    /// publishes it on the bus, timed as [`Phase::Publish`].
+    fn dispatch(&self, event: PoolEvent, version: Option<u64>) {
+        timing::time(Phase::Publish, || {
+            for hook in self.hooks.iter() {
+                hook.on_event(&event);
+            }
+            if let Some(sink) = &self.event_log {
+                sink.push(&event, version);
+            }
+            let _ = self.event_bus.publish(event);
+        });
+    }
+
+    /// Returns the event signer, if attestations are enabled.
+    #[must_use]
+    pub fn event_signer(&self) -> Option<&EventSigner> {
+        self.event_signer.as_deref()
+    }
+
+    /// Serializes `event` to JSON, attested when a signer is configured.
+    #[must_use]
+    pub fn event_payload(&self, event: &PoolEvent) -> serde_json::Value {
+        attestation::event_payload(event, self.event_signer())
     }
 
     /// Returns a reference to the inner [`EventBus`].
@@ -49,34 +438,79 @@ impl PoolService {
 
     /// Creates a new pool from the given configuration.
     ///
+    /// Pools created this way carry no creation config, so they cannot be
+    /// rebuilt by replay; prefer [`Self::create_pool_from_config`].
+    ///
     /// # Errors
     ///
     /// Returns a [`GatewayError`] if the configuration is invalid or
-    /// pool creation fails.
+    /// pool creation fails, and [`GatewayError::CapacityExceeded`] if the
+    /// pool limit is reached.
     pub async fn create_pool(
         &self,
         config: &AmmConfig,
         pool_type: &str,
         fee_bps: u32,
     ) -> Result<PoolId, GatewayError> {
-        let pool_box = DefaultPoolFactory::create(config)?;
-        let pool_id = PoolId::new();
-
-        let pair = pool_box.token_pair();
-        let token_a = format!("{:?}", pair.first().address());
-        let token_b = format!("{:?}", pair.second().address());
+        self.ensure_writable()?;
+        let pool_box = timing::time(Phase::Amm, || DefaultPoolFactory::create(config))?;
+        let entry = PoolEntry::new(PoolId::new(), pool_box, pool_type.to_string(), fee_bps);
+        self.register(entry).await
+    }
 
-        let entry = PoolEntry::new(pool_id, pool_box, pool_type.to_string(), fee_bps);
-        self.registry.insert(entry).await?;
+    /// Creates a new pool under a caller-chosen identifier from a
+    /// type-specific JSON config, with optional `metadata` (`null` for
+    /// none) that the caller has already validated. `owner` is the
+    /// creating client, if known.
+    ///
+    /// The config is kept on the entry (and in the `PoolCreated` event)
+    /// so the pool can be rebuilt by replay. A caller-chosen ID is used in
+    /// clustered mode, where it must hash to the local instance.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError`] if the configuration is invalid, its
+    /// initial reserves are implausibly far apart, pool creation fails,
+    /// or the ID is already registered, and
+    /// [`GatewayError::CapacityExceeded`] if the pool limit is reached.
+    pub async fn create_pool_from_config(
+        &self,
+        pool_id: PoolId,
+        pool_type: &str,
+        config_json: serde_json::Value,
+        metadata: serde_json::Value,
+        owner: Option<&str>,
+    ) -> Result<PoolId, GatewayError> {
+        self.ensure_writable()?;
+        let mut entry = timing::time(Phase::Amm, || build_entry(pool_id, pool_type, config_json))?;
+        check_spread(&entry)?;
+        entry.metadata = metadata;
+        entry.owner = owner.map(str::to_string);
+        self.register(entry).await
+    }
 
-        let _ = self.event_bus.publish(PoolEvent::PoolCreated {
+    /// Inserts a freshly built entry and announces it.
+    async fn register(&self, entry: PoolEntry) -> Result<PoolId, GatewayError> {
+        let pool_id = entry.pool_id;
+        let version = entry.version();
+        let pool_type = entry.pool_type.clone();
+        let event = PoolEvent::PoolCreated {
             pool_id,
-            pool_type: pool_type.to_string(),
-            token_a,
-            token_b,
-            fee_tier: fee_bps,
+            pool_type: pool_type.clone(),
+            token_a: entry.token_label(TokenSide::First),
+            token_b: entry.token_label(TokenSide::Second),
+            fee_tier: entry.fee_bps,
+            config: entry.config_json.clone(),
+            metadata: entry.metadata.clone(),
+            owner: entry.owner.clone(),
             timestamp: Utc::now(),
-        });
+        };
+        match self.limits.max_pools {
+            Some(max_pools) => self.registry.insert_within(entry, max_pools).await?,
+            None => self.registry.insert(entry).await?,
+        };
+
+        self.emit_versioned(event, version);
 
         tracing::info!(%pool_id, pool_type, "pool created");
         Ok(pool_id)
@@ -84,60 +518,135 @@ impl PoolService {
 
     /// Executes a swap on the specified pool.
     ///
+    /// `actor` identifies the client that issued the command and is
+    /// recorded on the emitted events. The swap only executes if it meets
+    /// `conditions`, checked once the pool's lock is held: the deadline
+    /// has not passed, the pool is still at the expected
+    /// [version](PoolEntry::version), and the fill, quoted on a sandbox,
+    /// respects the slippage bounds.
+    ///
     /// # Errors
     ///
-    /// Returns a [`GatewayError`] if the pool is not found or the swap fails.
+    /// Returns a [`GatewayError`] if the pool is not found, `token_in` is
+    /// not part of the pool, or the swap fails,
+    /// [`GatewayError::PoolPaused`] if the pool is paused or draining,
+    /// [`GatewayError::DeadlineExpired`] if the deadline has passed,
+    /// [`GatewayError::VersionMismatch`] if the pool has moved past the
+    /// expected version, [`GatewayError::SlippageExceeded`] if a slippage
+    /// bound is violated, and [`GatewayError::InvalidRequest`] if bounds
+    /// are set on a pool without a creation config to quote against.
+    #[allow(clippy::too_many_arguments)]
     pub async fn execute_swap(
         &self,
         pool_id: PoolId,
-        spec: SwapSpec,
+        kind: SwapKind,
+        amount: Amount,
         token_in: Token,
+        conditions: SwapConditions,
         command_id: &str,
-    ) -> Result<SwapResult, GatewayError> {
+        actor: Option<&str>,
+    ) -> Result<(SwapResult, FeeRebate), GatewayError> {
+        self.ensure_writable()?;
         let entry_lock = self.registry.get(pool_id).await?;
-        let mut entry = entry_lock.write().await;
+        let mut entry = self.write_entry(pool_id, &entry_lock, "swap").await?;
+        entry.ensure_tradable()?;
+
+        check_deadline(conditions.deadline, Utc::now())?;
+        if let Some(expected) = conditions.expected_version
+            && entry.version() != expected
+        {
+            return Err(GatewayError::VersionMismatch {
+                expected,
+                actual: entry.version(),
+            });
+        }
 
-        // Capture price before swap
-        let pair = *entry.pool_box.token_pair();
-        let base = pair.first();
-        let quote = pair.second();
-        let price_before = entry
-            .pool_box
-            .spot_price(&base, &quote)
-            .map(|p| p.get())
-            .unwrap_or(0.0);
+        let side = TokenSide::of(entry.pool_box.token_pair(), token_in).ok_or_else(|| {
+            GatewayError::InvalidRequest(format!("token_in not found in pool {pool_id}"))
+        })?;
+
+        if conditions.has_slippage_bounds() {
+            let seed = PoolSeed::of(&entry).ok_or_else(|| {
+                GatewayError::InvalidRequest(format!(
+                    "pool {pool_id} has no creation config to check slippage bounds against"
+                ))
+            })?;
+            let quoted = timing::time(Phase::Amm, || -> Result<SwapResult, GatewayError> {
+                let mut sandbox = seed.build()?;
+                Ok(sandbox.pool_box.swap(kind.spec(amount)?, token_in)?)
+            })?;
+            conditions.check_fill(&quoted)?;
+        }
+
+        self.swap_locked(&mut entry, side, kind, amount, command_id, actor)
+    }
 
-        let result = entry.pool_box.swap(spec, token_in)?;
+    /// Swaps on an entry whose write lock the caller already holds, then
+    /// emits the swap and price events. The actor's fee-tier rebate is
+    /// computed from its volume before this swap.
+    fn swap_locked(
+        &self,
+        entry: &mut PoolEntry,
+        side: TokenSide,
+        kind: SwapKind,
+        amount: Amount,
+        command_id: &str,
+        actor: Option<&str>,
+    ) -> Result<(SwapResult, FeeRebate), GatewayError> {
+        let pool_id = entry.pool_id;
+
+        // Capture price before swap
+        let price_before = entry.spot_price().unwrap_or(0.0);
 
-        // Update metadata
-        entry.swap_count = entry.swap_count.saturating_add(1);
-        entry.total_volume = entry.total_volume.saturating_add(result.amount_in().get());
-        entry.last_modified_at = Utc::now();
+        let op = PoolOperation::Swap {
+            token_in: side,
+            kind,
+            amount: amount.get().to_string(),
+        };
+        let OperationOutcome::Swap(result) = timing::time(Phase::Amm, || entry.apply(&op))? else {
+            return Err(GatewayError::Internal(
+                "swap produced no result".to_string(),
+            ));
+        };
 
         // Capture price after swap
-        let price_after = entry
-            .pool_box
-            .spot_price(&base, &quote)
-            .map(|p| p.get())
-            .unwrap_or(0.0);
+        let price_after = entry.spot_price().unwrap_or(0.0);
+        let token_label = entry.token_label(side);
 
         let price_change_bps = compute_price_change_bps(price_before, price_after);
 
-        drop(entry);
+        let now = Utc::now();
+        let rebate = match self.fee_program.as_deref() {
+            Some(program) => {
+                let rebate = program.rebate(actor, result.fee().get(), entry.fee_bps, now);
+                if let Some(actor) = actor {
+                    program.record(actor, result.amount_in().get(), now);
+                }
+                rebate
+            }
+            None => FeeRebate::default(),
+        };
 
         // Emit events
-        let _ = self.event_bus.publish(PoolEvent::SwapExecuted {
-            pool_id,
-            command_id: command_id.to_string(),
-            amount_in: result.amount_in().get().to_string(),
-            amount_out: result.amount_out().get().to_string(),
-            fee: result.fee().get().to_string(),
-            new_price: format!("{price_after}"),
-            price_change_bps,
-            timestamp: Utc::now(),
-        });
-
-        let _ = self.event_bus.publish(PoolEvent::PriceUpdated {
+        self.emit_versioned(
+            PoolEvent::SwapExecuted {
+                pool_id,
+                command_id: command_id.to_string(),
+                amount_in: result.amount_in().get().to_string(),
+                amount_out: result.amount_out().get().to_string(),
+                fee: result.fee().get().to_string(),
+                fee_rebate: rebate.amount.to_string(),
+                new_price: format!("{price_after}"),
+                price_change_bps,
+                token_in: token_label,
+                swap_kind: kind,
+                actor: actor.map(str::to_string),
+                timestamp: Utc::now(),
+            },
+            entry.version(),
+        );
+
+        self.emit(PoolEvent::PriceUpdated {
             pool_id,
             old_price: format!("{price_before}"),
             new_price: format!("{price_after}"),
@@ -146,102 +655,315 @@ impl PoolService {
             timestamp: Utc::now(),
         });
 
-        Ok(result)
+        Ok((result, rebate))
+    }
+
+    /// Captures routing seeds for every active pool that has a creation
+    /// config.
+    pub async fn route_seeds(&self) -> Vec<PoolSeed> {
+        let mut seeds = Vec::new();
+        for entry_lock in self.registry.entries().await {
+            let entry = entry_lock.read().await;
+            if entry.status != PoolStatus::Active {
+                continue;
+            }
+            if let Some(seed) = PoolSeed::of(&entry) {
+                seeds.push(seed);
+            }
+        }
+        seeds
+    }
+
+    /// Captures the routing seed of a single pool together with its
+    /// tracked reserves.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PoolNotFound`] if the pool does not exist
+    /// and [`GatewayError::InvalidRequest`] if it has no creation config
+    /// to sandbox from.
+    pub async fn pool_seed(
+        &self,
+        pool_id: PoolId,
+    ) -> Result<(PoolSeed, Option<Vec<u128>>), GatewayError> {
+        let entry_lock = self.registry.get(pool_id).await?;
+        let entry = entry_lock.read().await;
+        let seed = PoolSeed::of(&entry).ok_or_else(|| {
+            GatewayError::InvalidRequest(format!(
+                "pool {pool_id} has no creation config to quote against"
+            ))
+        })?;
+        Ok((seed, entry.reserves.clone()))
+    }
+
+    /// Measures the risk factors read from live pool state as of `now`:
+    /// the concentration of open positions for CLMM pools and the age of
+    /// the oracle price for dynamic pools (counted from creation until
+    /// the first update). Depth and volatility are left unset.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PoolNotFound`] if the pool does not exist.
+    pub async fn risk_inputs(
+        &self,
+        pool_id: PoolId,
+        now: DateTime<Utc>,
+    ) -> Result<RiskInputs, GatewayError> {
+        let entry_lock = self.registry.get(pool_id).await?;
+        let entry = entry_lock.read().await;
+        let concentration = (entry.pool_type == "clmm")
+            .then(|| risk::concentration(entry.positions.values().map(|p| p.liquidity)))
+            .flatten();
+        let oracle_age_secs = (entry.pool_type == "dynamic").then(|| {
+            let set_at = entry.oracle_updated_at.unwrap_or(entry.created_at);
+            (now - set_at).num_milliseconds().max(0) as f64 / 1_000.0
+        });
+        Ok(RiskInputs {
+            concentration,
+            oracle_age_secs,
+            ..RiskInputs::default()
+        })
+    }
+
+    /// Executes a routed swap atomically.
+    ///
+    /// Write locks on every pool in the plan are taken in pool-id order,
+    /// the plan is re-quoted against the locked state, and the hops are
+    /// applied only if the re-quoted output still meets
+    /// `min_amount_out`. Returns the plan as executed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::SlippageExceeded`] if the re-quote falls
+    /// below `min_amount_out`, or a [`GatewayError`] if a pool vanished or
+    /// a hop fails.
+    pub async fn execute_route(
+        &self,
+        plan: &RoutePlan,
+        min_amount_out: u128,
+        command_id: &str,
+        actor: Option<&str>,
+    ) -> Result<RoutePlan, GatewayError> {
+        self.ensure_writable()?;
+        let pool_ids: BTreeSet<PoolId> = plan
+            .legs
+            .iter()
+            .flat_map(|leg| leg.hops.iter().map(|hop| hop.pool_id))
+            .collect();
+        let mut locks = Vec::with_capacity(pool_ids.len());
+        for pool_id in &pool_ids {
+            locks.push(self.registry.get(*pool_id).await?);
+        }
+        let mut guards = Vec::with_capacity(locks.len());
+        for (pool_id, lock) in pool_ids.iter().zip(&locks) {
+            let guard = self.write_entry(*pool_id, lock, "route_swap").await?;
+            guard.ensure_tradable()?;
+            guards.push(guard);
+        }
+
+        let seeds: Vec<PoolSeed> = guards.iter().filter_map(|g| PoolSeed::of(g)).collect();
+        let quoted = timing::time(Phase::Amm, || plan.requote(&seeds))?;
+        if quoted.amount_out < min_amount_out {
+            return Err(GatewayError::SlippageExceeded {
+                bound: "min_amount_out",
+                limit: min_amount_out.to_string(),
+                quoted: quoted.amount_out.to_string(),
+            });
+        }
+
+        for hop in quoted.legs.iter().flat_map(|leg| leg.hops.iter()) {
+            let entry = guards
+                .iter_mut()
+                .find(|g| g.pool_id == hop.pool_id)
+                .ok_or(GatewayError::PoolNotFound(*hop.pool_id.as_uuid()))?;
+            let side = entry.side_of_label(&hop.token_in).ok_or_else(|| {
+                GatewayError::Internal(format!("token {} left pool {}", hop.token_in, hop.pool_id))
+            })?;
+            self.swap_locked(
+                entry,
+                side,
+                SwapKind::ExactIn,
+                Amount::new(hop.amount_in),
+                command_id,
+                actor,
+            )?;
+        }
+
+        tracing::info!(
+            legs = quoted.legs.len(),
+            amount_out = %quoted.amount_out,
+            "route executed"
+        );
+        Ok(quoted)
     }
 
-    /// Dry-run swap: clones pool state to compute a quote without mutation.
+    /// Dry-run swap: computes a quote without touching the live pool.
+    ///
+    /// The swap runs on a sandbox copy of the pool: the one kept ready in
+    /// the [`SandboxStock`], brought up to date under a read lock, or one
+    /// rebuilt from the pool's config and journal if none is ready. Quotes
+    /// never change pool state and do not block swaps while they compute.
+    /// Programmatic pools, which have no config to rebuild from, are
+    /// quoted in place (see [`Self::quote_in_place`]).
     ///
     /// # Errors
     ///
-    /// Returns a [`GatewayError`] if the pool is not found or the quote
-    /// computation fails.
+    /// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+    /// or a [`GatewayError`] if the swap would fail.
     pub async fn quote_swap(
         &self,
         pool_id: PoolId,
-        spec: SwapSpec,
+        kind: SwapKind,
+        amount: Amount,
         token_in: Token,
     ) -> Result<SwapResult, GatewayError> {
-        // PoolBox doesn't implement Clone, so we acquire a write lock
-        // and perform the swap, then reverse it. For a true quote we
-        // accept the write lock cost — this is simpler than rebuilding
-        // the pool from config.
         let entry_lock = self.registry.get(pool_id).await?;
-        let mut entry = entry_lock.write().await;
-        let result = entry.pool_box.swap(spec, token_in)?;
+        let (ready, seed) = {
+            let entry = entry_lock.read().await;
+            let Some(seed) = PoolSeed::of(&entry) else {
+                drop(entry);
+                return self.quote_in_place(pool_id, kind, amount, token_in).await;
+            };
+            (self.sandboxes.take(&entry), seed)
+        };
+        let mut sandbox = match ready {
+            Some(sandbox) => sandbox,
+            None => seed.build()?,
+        };
+        self.sandboxes.restock(seed);
+        Ok(sandbox.pool_box.swap(kind.spec(amount)?, token_in)?)
+    }
 
-        // Reverse the swap to restore original state: swap the output
-        // amount back using the output token.
+    /// Quotes a programmatic pool by swapping on the live pool under its
+    /// write lock and swapping the output back. The reversal is best
+    /// effort: fees and rounding can leave the reserves slightly off.
+    async fn quote_in_place(
+        &self,
+        pool_id: PoolId,
+        kind: SwapKind,
+        amount: Amount,
+        token_in: Token,
+    ) -> Result<SwapResult, GatewayError> {
+        let entry_lock = self.registry.get(pool_id).await?;
+        let mut entry = self.write_entry(pool_id, &entry_lock, "quote").await?;
+        let result = entry.pool_box.swap(kind.spec(amount)?, token_in)?;
         let pair = *entry.pool_box.token_pair();
         let reverse_token = if token_in == pair.first() {
             pair.second()
         } else {
             pair.first()
         };
-        // Best-effort reversal — if it fails, state may drift slightly
-        // but this is acceptable for a quote endpoint.
         if let Ok(reverse_spec) = SwapSpec::exact_in(result.amount_out()) {
             let _ = entry.pool_box.swap(reverse_spec, reverse_token);
         }
-
         Ok(result)
     }
 
     /// Adds liquidity to the specified pool.
     ///
+    /// Tops up `position` when given; otherwise opens a new position
+    /// over `range` (full range when `None`), owned by `owner` or, when
+    /// `None`, by `actor`. Returns the position and the LP units minted,
+    /// which are credited to the position's owner.
+    ///
     /// # Errors
     ///
-    /// Returns a [`GatewayError`] if the pool is not found or the
-    /// liquidity operation fails.
+    /// Returns a [`GatewayError`] if the pool or position is not found,
+    /// a range is given for an existing position, or the liquidity
+    /// operation fails, and [`GatewayError::CapacityExceeded`] if a new
+    /// position would exceed the per-pool position limit.
+    #[allow(clippy::too_many_arguments)]
     pub async fn add_liquidity(
         &self,
         pool_id: PoolId,
-        change: &LiquidityChange,
-    ) -> Result<hydra_amm::domain::Amount, GatewayError> {
-        let entry_lock = self.registry.get(pool_id).await?;
-        let mut entry = entry_lock.write().await;
+        amount_a: Amount,
+        amount_b: Amount,
+        position: Option<PositionId>,
+        range: Option<TickRange>,
+        owner: Option<&str>,
+        actor: Option<&str>,
+    ) -> Result<(PositionId, Amount), GatewayError> {
+        self.ensure_writable()?;
+        if let Some(r) = range
+            && (r.lower >= r.upper
+                || r.lower < TickRange::FULL.lower
+                || r.upper > TickRange::FULL.upper)
+        {
+            return Err(GatewayError::InvalidRequest(format!(
+                "invalid tick range [{}, {})",
+                r.lower, r.upper
+            )));
+        }
 
-        let pair = *entry.pool_box.token_pair();
-        let base = pair.first();
-        let quote_tok = pair.second();
-        let price_before = entry
-            .pool_box
-            .spot_price(&base, &quote_tok)
-            .map(|p| p.get())
-            .unwrap_or(0.0);
+        let entry_lock = self.registry.get(pool_id).await?;
+        let mut entry = self
+            .write_entry(pool_id, &entry_lock, "add_liquidity")
+            .await?;
 
-        let minted = entry.pool_box.add_liquidity(change)?;
+        let position_id = match position {
+            Some(id) if !entry.positions.contains(&id) => {
+                return Err(GatewayError::PositionNotFound(*pool_id.as_uuid()));
+            }
+            Some(_) if range.is_some() => {
+                return Err(GatewayError::InvalidRequest(
+                    "tick range is fixed when a position is opened".to_string(),
+                ));
+            }
+            Some(id) => id,
+            None => {
+                if let Some(max) = self.limits.max_positions_per_pool
+                    && entry.positions.len() >= max
+                {
+                    return Err(GatewayError::CapacityExceeded(format!(
+                        "pool {pool_id} already tracks {max} positions"
+                    )));
+                }
+                PositionId::new()
+            }
+        };
 
-        entry.last_modified_at = Utc::now();
+        let price_before = entry.spot_price().unwrap_or(0.0);
+
+        let op = PoolOperation::AddLiquidity {
+            amount_a: amount_a.get().to_string(),
+            amount_b: amount_b.get().to_string(),
+            position_id: Some(position_id),
+            range: if position.is_none() { range } else { None },
+            owner: if position.is_none() {
+                owner.or(actor).map(str::to_string)
+            } else {
+                None
+            },
+        };
+        let OperationOutcome::LiquidityAdded(minted) =
+            timing::time(Phase::Amm, || entry.apply(&op))?
+        else {
+            return Err(GatewayError::Internal("add produced no result".to_string()));
+        };
 
         let total_liq = entry.pool_box.total_liquidity();
-        let price_after = entry
-            .pool_box
-            .spot_price(&base, &quote_tok)
-            .map(|p| p.get())
-            .unwrap_or(0.0);
+        let price_after = entry.spot_price().unwrap_or(0.0);
 
         let price_change_bps = compute_price_change_bps(price_before, price_after);
 
-        // Extract amounts from the change for the event
-        let (amount_a, amount_b) = match change {
-            LiquidityChange::Add { amount_a, amount_b } => {
-                (amount_a.get().to_string(), amount_b.get().to_string())
-            }
-            _ => ("0".to_string(), "0".to_string()),
-        };
-
+        self.emit_versioned(
+            PoolEvent::LiquidityChanged {
+                pool_id,
+                change_type: LiquidityChangeType::Add,
+                amount_a: amount_a.get().to_string(),
+                amount_b: amount_b.get().to_string(),
+                new_total_liquidity: total_liq.get().to_string(),
+                liquidity_delta: minted.get().to_string(),
+                position_id: Some(position_id),
+                range: if position.is_none() { range } else { None },
+                actor: actor.map(str::to_string),
+                timestamp: Utc::now(),
+            },
+            entry.version(),
+        );
         drop(entry);
 
-        let _ = self.event_bus.publish(PoolEvent::LiquidityChanged {
-            pool_id,
-            change_type: LiquidityChangeType::Add,
-            amount_a,
-            amount_b,
-            new_total_liquidity: total_liq.get().to_string(),
-            timestamp: Utc::now(),
-        });
-
-        let _ = self.event_bus.publish(PoolEvent::PriceUpdated {
+        self.emit(PoolEvent::PriceUpdated {
             pool_id,
             old_price: format!("{price_before}"),
             new_price: format!("{price_after}"),
@@ -250,57 +972,88 @@ impl PoolService {
             timestamp: Utc::now(),
         });
 
-        Ok(minted)
+        Ok((position_id, minted))
     }
 
     /// Removes liquidity from the specified pool.
     ///
+    /// Burns from `position` when given; otherwise only liquidity no
+    /// position owns can be burned. A position with an owner can only be
+    /// withdrawn by that owner, given as `owner` or, when `None`, as
+    /// `actor`, and only up to the LP units the owner holds. Returns
+    /// hydra-amm's returned amount and, for pools with tracked reserves,
+    /// the tokens withdrawn in pool order.
+    ///
     /// # Errors
     ///
-    /// Returns a [`GatewayError`] if the pool is not found or the
-    /// liquidity operation fails.
+    /// Returns [`GatewayError::Forbidden`] if the position belongs to
+    /// another provider, and a [`GatewayError`] if the pool or position
+    /// is not found, the position or its owner holds less than
+    /// `liquidity`, or the liquidity operation fails.
     pub async fn remove_liquidity(
         &self,
         pool_id: PoolId,
-        change: &LiquidityChange,
-    ) -> Result<hydra_amm::domain::Amount, GatewayError> {
+        position: Option<PositionId>,
+        liquidity: Liquidity,
+        owner: Option<&str>,
+        actor: Option<&str>,
+    ) -> Result<(Amount, Option<[u128; 2]>), GatewayError> {
+        self.ensure_writable()?;
         let entry_lock = self.registry.get(pool_id).await?;
-        let mut entry = entry_lock.write().await;
-
-        let pair = *entry.pool_box.token_pair();
-        let base = pair.first();
-        let quote_tok = pair.second();
-        let price_before = entry
-            .pool_box
-            .spot_price(&base, &quote_tok)
-            .map(|p| p.get())
-            .unwrap_or(0.0);
+        let mut entry = self
+            .write_entry(pool_id, &entry_lock, "remove_liquidity")
+            .await?;
+
+        let caller = owner.or(actor);
+        if let Some(holder) = position
+            .and_then(|id| entry.positions.get(&id))
+            .and_then(|held| held.owner.as_deref())
+        {
+            if caller != Some(holder) {
+                return Err(GatewayError::Forbidden(format!(
+                    "position belongs to provider {holder}"
+                )));
+            }
+            entry.lp_ledger.ensure_holds(holder, liquidity.get())?;
+        }
 
-        let returned = entry.pool_box.remove_liquidity(change)?;
+        let price_before = entry.spot_price().unwrap_or(0.0);
 
-        entry.last_modified_at = Utc::now();
+        let op = PoolOperation::RemoveLiquidity {
+            liquidity: liquidity.get().to_string(),
+            position_id: position,
+        };
+        let OperationOutcome::LiquidityRemoved { returned, amounts } =
+            timing::time(Phase::Amm, || entry.apply(&op))?
+        else {
+            return Err(GatewayError::Internal(
+                "remove produced no result".to_string(),
+            ));
+        };
 
         let total_liq = entry.pool_box.total_liquidity();
-        let price_after = entry
-            .pool_box
-            .spot_price(&base, &quote_tok)
-            .map(|p| p.get())
-            .unwrap_or(0.0);
+        let price_after = entry.spot_price().unwrap_or(0.0);
 
         let price_change_bps = compute_price_change_bps(price_before, price_after);
 
+        self.emit_versioned(
+            PoolEvent::LiquidityChanged {
+                pool_id,
+                change_type: LiquidityChangeType::Remove,
+                amount_a: amounts.map_or(returned.get(), |[a, _]| a).to_string(),
+                amount_b: amounts.map_or(0, |[_, b]| b).to_string(),
+                new_total_liquidity: total_liq.get().to_string(),
+                liquidity_delta: liquidity.get().to_string(),
+                position_id: position,
+                range: None,
+                actor: actor.map(str::to_string),
+                timestamp: Utc::now(),
+            },
+            entry.version(),
+        );
         drop(entry);
 
-        let _ = self.event_bus.publish(PoolEvent::LiquidityChanged {
-            pool_id,
-            change_type: LiquidityChangeType::Remove,
-            amount_a: returned.get().to_string(),
-            amount_b: "0".to_string(),
-            new_total_liquidity: total_liq.get().to_string(),
-            timestamp: Utc::now(),
-        });
-
-        let _ = self.event_bus.publish(PoolEvent::PriceUpdated {
+        self.emit(PoolEvent::PriceUpdated {
             pool_id,
             old_price: format!("{price_before}"),
             new_price: format!("{price_after}"),
@@ -309,168 +1062,2324 @@ impl PoolService {
             timestamp: Utc::now(),
         });
 
-        Ok(returned)
+        Ok((returned, amounts))
     }
 
-    /// Collects accrued fees for a position.
+    /// Returns a position held in the specified pool.
     ///
     /// # Errors
     ///
-    /// Returns a [`GatewayError`] if the pool is not found or fee
-    /// collection fails.
-    pub async fn collect_fees(
+    /// Returns a [`GatewayError`] if the pool or position is not found.
+    pub async fn get_position(
         &self,
         pool_id: PoolId,
-        position: &Position,
-    ) -> Result<hydra_amm::domain::Amount, GatewayError> {
+        position_id: PositionId,
+    ) -> Result<LiquidityPosition, GatewayError> {
         let entry_lock = self.registry.get(pool_id).await?;
-        let mut entry = entry_lock.write().await;
-
-        let fees = entry.pool_box.collect_fees(position)?;
-        entry.last_modified_at = Utc::now();
-
-        drop(entry);
-
-        let _ = self.event_bus.publish(PoolEvent::FeesCollected {
-            pool_id,
-            fee_token_a: fees.get().to_string(),
-            fee_token_b: "0".to_string(),
-            timestamp: Utc::now(),
-        });
+        let entry = entry_lock.read().await;
+        entry
+            .positions
+            .get(&position_id)
+            .cloned()
+            .ok_or(GatewayError::PositionNotFound(*pool_id.as_uuid()))
+    }
 
-        Ok(fees)
+    /// Finds the open position of `owner` over `range`, for clients that
+    /// identify positions by tick range rather than by id. Positions
+    /// opened without a range count as full range; closed ones are
+    /// skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PositionNotFound`] if `owner` has no open
+    /// position over `range`, or [`GatewayError::InvalidRequest`] if it
+    /// has several, which only their ids tell apart.
+    pub async fn find_position(
+        &self,
+        pool_id: PoolId,
+        range: TickRange,
+        owner: Option<&str>,
+    ) -> Result<PositionId, GatewayError> {
+        let entry_lock = self.registry.get(pool_id).await?;
+        let entry = entry_lock.read().await;
+        let matching: Vec<PositionId> = entry
+            .positions
+            .iter()
+            .filter(|(_, p)| p.is_open() && p.owner.as_deref() == owner)
+            .filter(|(_, p)| p.range.unwrap_or(TickRange::FULL) == range)
+            .map(|(id, _)| *id)
+            .collect();
+        match matching.as_slice() {
+            [id] => Ok(*id),
+            [] => Err(GatewayError::PositionNotFound(*pool_id.as_uuid())),
+            _ => Err(GatewayError::InvalidRequest(format!(
+                "{} positions over [{}, {}); pass position_id",
+                matching.len(),
+                range.lower,
+                range.upper
+            ))),
+        }
     }
 
-    /// Removes a pool from the registry.
+    /// Lists the positions of the specified pool in id order, optionally
+    /// only those opened by `owner`. Closed positions are included.
     ///
     /// # Errors
     ///
     /// Returns a [`GatewayError`] if the pool is not found.
-    pub async fn remove_pool(&self, pool_id: PoolId) -> Result<(), GatewayError> {
-        let _entry = self.registry.remove(pool_id).await?;
-
-        let _ = self.event_bus.publish(PoolEvent::PoolRemoved {
-            pool_id,
-            timestamp: Utc::now(),
-        });
-
-        tracing::info!(%pool_id, "pool removed");
-        Ok(())
+    pub async fn list_positions(
+        &self,
+        pool_id: PoolId,
+        owner: Option<&str>,
+    ) -> Result<Vec<(PositionId, LiquidityPosition)>, GatewayError> {
+        let entry_lock = self.registry.get(pool_id).await?;
+        let entry = entry_lock.read().await;
+        Ok(entry
+            .positions
+            .iter()
+            .filter(|(_, p)| owner.is_none_or(|o| p.owner.as_deref() == Some(o)))
+            .map(|(id, p)| (*id, p.clone()))
+            .collect())
     }
 
-    /// Returns summaries of all pools, optionally filtered by type.
-    pub async fn list_pools(&self, pool_type_filter: Option<&str>) -> Vec<PoolSummary> {
-        self.registry.list(pool_type_filter).await
+    /// Returns a copy of the pool's LP ledger and its total liquidity.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PoolNotFound`] if the pool does not exist.
+    pub async fn lp_holdings(&self, pool_id: PoolId) -> Result<(LpLedger, u128), GatewayError> {
+        let entry_lock = self.registry.get(pool_id).await?;
+        let entry = entry_lock.read().await;
+        Ok((
+            entry.lp_ledger.clone(),
+            entry.pool_box.total_liquidity().get(),
+        ))
+    }
+
+    /// Closes a position: collects its fees, then withdraws all the
+    /// liquidity it still holds. The position stays listed, empty.
+    /// Returns the fees per token, the LP units burned, and, for pools
+    /// with tracked reserves, the tokens withdrawn in pool order.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError`] if the pool or position is not found
+    /// or either operation fails; fees collected before a failed
+    /// withdrawal stay collected.
+    pub async fn close_position(
+        &self,
+        pool_id: PoolId,
+        position_id: PositionId,
+        actor: Option<&str>,
+    ) -> Result<ClosedPosition, GatewayError> {
+        let (_, fees) = self.collect_fees(pool_id, position_id, actor).await?;
+        let held = self.get_position(pool_id, position_id).await?.liquidity;
+        let withdrawn = if held > 0 {
+            let (_, amounts) = self
+                .remove_liquidity(
+                    pool_id,
+                    Some(position_id),
+                    Liquidity::new(held),
+                    None,
+                    actor,
+                )
+                .await?;
+            amounts
+        } else {
+            None
+        };
+        Ok(ClosedPosition {
+            fees,
+            liquidity_burned: held,
+            withdrawn,
+        })
+    }
+
+    /// Collects accrued fees for a position.
+    ///
+    /// The tick range and liquidity are taken from the position record,
+    /// so callers only name the position. Returns hydra-amm's collected
+    /// amount and the fees per token in pool order: the position's
+    /// current share of the swap fees charged since it last collected.
+    /// Fees of a position with an owner are collected only by that owner,
+    /// given as `actor`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::Forbidden`] if the position belongs to
+    /// another provider, and a [`GatewayError`] if the pool or position
+    /// is not found or fee collection fails.
+    pub async fn collect_fees(
+        &self,
+        pool_id: PoolId,
+        position_id: PositionId,
+        actor: Option<&str>,
+    ) -> Result<(Amount, [u128; 2]), GatewayError> {
+        self.ensure_writable()?;
+        let entry_lock = self.registry.get(pool_id).await?;
+        let mut entry = self
+            .write_entry(pool_id, &entry_lock, "collect_fees")
+            .await?;
+
+        if let Some(holder) = entry
+            .positions
+            .get(&position_id)
+            .and_then(|held| held.owner.as_deref())
+            && actor != Some(holder)
+        {
+            return Err(GatewayError::Forbidden(format!(
+                "position belongs to provider {holder}"
+            )));
+        }
+
+        let op = PoolOperation::CollectFees { position_id };
+        let OperationOutcome::FeesCollected { collected, amounts } =
+            timing::time(Phase::Amm, || entry.apply(&op))?
+        else {
+            return Err(GatewayError::Internal(
+                "collection produced no result".to_string(),
+            ));
+        };
+
+        let [fee_a, fee_b] = amounts;
+        self.emit_versioned(
+            PoolEvent::FeesCollected {
+                pool_id,
+                fee_token_a: fee_a.to_string(),
+                fee_token_b: fee_b.to_string(),
+                position_id: Some(position_id),
+                actor: actor.map(str::to_string),
+                timestamp: Utc::now(),
+            },
+            entry.version(),
+        );
+        drop(entry);
+
+        Ok((collected, amounts))
+    }
+
+    /// Rests a limit order on an order-book pool and emits
+    /// `OrderPlaced`. Orders are post-only: one that would cross the
+    /// opposite side of the book is rejected.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError`] if the pool is not found, is not an
+    /// order-book pool, or rejects the order (zero amounts, off-tick
+    /// price, off-lot quantity, or a crossing price).
+    pub async fn place_order(
+        &self,
+        pool_id: PoolId,
+        side: OrderSide,
+        price: u128,
+        quantity: u128,
+        actor: Option<&str>,
+    ) -> Result<(OrderId, LimitOrder), GatewayError> {
+        self.ensure_writable()?;
+        let entry_lock = self.registry.get(pool_id).await?;
+        let mut entry = self
+            .write_entry(pool_id, &entry_lock, "place_order")
+            .await?;
+
+        let order_id = OrderId::new();
+        let op = PoolOperation::PlaceOrder {
+            order_id,
+            side,
+            price: price.to_string(),
+            quantity: quantity.to_string(),
+            owner: actor.map(str::to_string),
+        };
+        let OperationOutcome::OrderPlaced(order) = entry.apply(&op)? else {
+            return Err(GatewayError::Internal(
+                "order placement produced no result".to_string(),
+            ));
+        };
+
+        self.emit_versioned(
+            PoolEvent::OrderPlaced {
+                pool_id,
+                order_id,
+                side,
+                price: order.price.to_string(),
+                quantity: order.quantity.to_string(),
+                actor: actor.map(str::to_string),
+                timestamp: Utc::now(),
+            },
+            entry.version(),
+        );
+        drop(entry);
+
+        Ok((order_id, order))
+    }
+
+    /// Cancels a resting limit order and emits `OrderCancelled`. Returns
+    /// the order removed from the book.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError`] if the pool or order is not found.
+    pub async fn cancel_order(
+        &self,
+        pool_id: PoolId,
+        order_id: OrderId,
+        actor: Option<&str>,
+    ) -> Result<LimitOrder, GatewayError> {
+        self.ensure_writable()?;
+        let entry_lock = self.registry.get(pool_id).await?;
+        let mut entry = self
+            .write_entry(pool_id, &entry_lock, "cancel_order")
+            .await?;
+
+        let op = PoolOperation::CancelOrder { order_id };
+        let OperationOutcome::OrderCancelled(order) = entry.apply(&op)? else {
+            return Err(GatewayError::Internal(
+                "order cancellation produced no result".to_string(),
+            ));
+        };
+
+        self.emit_versioned(
+            PoolEvent::OrderCancelled {
+                pool_id,
+                order_id,
+                side: order.side,
+                price: order.price.to_string(),
+                quantity: order.quantity.to_string(),
+                actor: actor.map(str::to_string),
+                timestamp: Utc::now(),
+            },
+            entry.version(),
+        );
+        drop(entry);
+
+        Ok(order)
+    }
+
+    /// Pushes a new oracle price to a dynamic pool and emits
+    /// `OraclePriceUpdated` and `PriceUpdated`.
+    ///
+    /// Prices outside the pool's oracle bounds are rejected and logged;
+    /// the pool keeps quoting around its previous price.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+    /// [`GatewayError::Forbidden`] if `manager` is neither its owner nor
+    /// an admin, [`GatewayError::InvalidRequest`] if it is not a dynamic
+    /// pool or the price is not positive,
+    /// [`GatewayError::OraclePriceRejected`] if the price is out of
+    /// bounds, or [`GatewayError::ReadOnlyReplica`] on a replica.
+    pub async fn set_oracle_price(
+        &self,
+        pool_id: PoolId,
+        price: f64,
+        manager: Manager<'_>,
+    ) -> Result<OracleUpdate, GatewayError> {
+        self.ensure_writable()?;
+        let entry_lock = self.registry.get(pool_id).await?;
+        let mut entry = self
+            .write_entry(pool_id, &entry_lock, "set_oracle_price")
+            .await?;
+        self.authorize(&entry, manager, "reprice", false)?;
+
+        let price_before = entry.spot_price().unwrap_or(0.0);
+        let op = PoolOperation::SetOraclePrice {
+            price: format!("{price}"),
+        };
+        let previous_price = match timing::time(Phase::Amm, || entry.apply(&op)) {
+            Ok(OperationOutcome::OracleUpdated(previous)) => previous,
+            Ok(_) => {
+                return Err(GatewayError::Internal(
+                    "oracle update produced no result".to_string(),
+                ));
+            }
+            Err(e) => {
+                if let GatewayError::OraclePriceRejected(reason) = &e {
+                    tracing::warn!(%pool_id, price, reason, "oracle price rejected");
+                }
+                return Err(e);
+            }
+        };
+        let spot_price = entry.spot_price();
+        let price_after = spot_price.unwrap_or(0.0);
+
+        self.emit_versioned(
+            PoolEvent::OraclePriceUpdated {
+                pool_id,
+                old_price: format!("{previous_price}"),
+                new_price: format!("{price}"),
+                actor: manager.actor().map(str::to_string),
+                timestamp: Utc::now(),
+            },
+            entry.version(),
+        );
+        drop(entry);
+
+        self.emit(PoolEvent::PriceUpdated {
+            pool_id,
+            old_price: format!("{price_before}"),
+            new_price: format!("{price_after}"),
+            price_change_bps: compute_price_change_bps(price_before, price_after),
+            reason: PriceChangeReason::OracleUpdated,
+            timestamp: Utc::now(),
+        });
+
+        tracing::info!(%pool_id, previous_price, price, "oracle price updated");
+        Ok(OracleUpdate {
+            previous_price,
+            spot_price,
+        })
+    }
+
+    /// Returns the best bid and offer of a pool (see [`Bbo::of`]) with its
+    /// token labels, base first, and state version.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PoolNotFound`] if the pool does not exist.
+    pub async fn bbo(&self, pool_id: PoolId) -> Result<(Bbo, [String; 2], u64), GatewayError> {
+        let entry_lock = self.registry.get(pool_id).await?;
+        let entry = entry_lock.read().await;
+        let bbo = Bbo::of(&entry);
+        let labels = [
+            entry.token_label(bbo.base),
+            entry.token_label(bbo.base.other()),
+        ];
+        Ok((bbo, labels, entry.version()))
+    }
+
+    /// Returns the resting orders of an order-book pool aggregated by
+    /// price, up to `levels` levels per side.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+    /// or [`GatewayError::InvalidRequest`] if it is not an order-book
+    /// pool.
+    pub async fn order_book(
+        &self,
+        pool_id: PoolId,
+        levels: usize,
+    ) -> Result<BookDepth, GatewayError> {
+        let entry_lock = self.registry.get(pool_id).await?;
+        let entry = entry_lock.read().await;
+        if entry.pool_type != "orderbook" {
+            return Err(GatewayError::InvalidRequest(format!(
+                "{} pools have no order book",
+                entry.pool_type
+            )));
+        }
+        Ok(entry.orders.snapshot(levels))
+    }
+
+    /// Removes a pool from the registry.
+    ///
+    /// Succeeds even while other operations hold the pool: they are
+    /// refused from then on, and `PoolRemoved` is published once the last
+    /// of them lets go (see [`Self::publish_removal`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError`] if the pool is not found.
+    pub async fn remove_pool(&self, pool_id: PoolId) -> Result<(), GatewayError> {
+        self.ensure_writable()?;
+        let tombstone = self.registry.remove(pool_id).await?;
+        self.contention.forget(pool_id);
+        self.sandboxes.forget(pool_id);
+        self.publish_removal(tombstone);
+
+        tracing::info!(%pool_id, "pool removed");
+        Ok(())
+    }
+
+    /// Publishes `PoolRemoved` once `tombstone` is reaped: at once if
+    /// nothing else holds the pool, otherwise from a task that waits for
+    /// the last handle to be dropped.
+    fn publish_removal(&self, tombstone: Tombstone) {
+        let pool_id = tombstone.pool_id();
+        let removed = move || PoolEvent::PoolRemoved {
+            pool_id,
+            timestamp: Utc::now(),
+        };
+        match tombstone.try_reap() {
+            Ok(_) => self.emit(removed()),
+            Err(tombstone) => {
+                tracing::debug!(%pool_id, "pool removal waits for in-flight operations");
+                let service = self.clone();
+                tokio::spawn(async move {
+                    let _ = tombstone.reap().await;
+                    service.emit(removed());
+                });
+            }
+        }
+    }
+
+    /// Archives a pool that is still stale at `cutoff` (see
+    /// [`stale_pools::is_stale`]) and emits `PoolArchived`. Returns
+    /// `false`, leaving the pool untouched, if it gained liquidity or
+    /// changed since `cutoff`, or is already archived.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+    /// or [`GatewayError::ReadOnlyReplica`] on a replica.
+    pub async fn archive_pool(
+        &self,
+        pool_id: PoolId,
+        cutoff: DateTime<Utc>,
+    ) -> Result<bool, GatewayError> {
+        self.ensure_writable()?;
+        let entry_lock = self.registry.get(pool_id).await?;
+        let mut entry = self.write_entry(pool_id, &entry_lock, "archive").await?;
+        if !stale_pools::is_stale(&entry, cutoff) {
+            return Ok(false);
+        }
+        let now = Utc::now();
+        entry.status = PoolStatus::Archived;
+        entry.archived_at = Some(now);
+        let idle_since = entry.last_modified_at;
+        drop(entry);
+
+        self.emit(PoolEvent::PoolArchived {
+            pool_id,
+            idle_since,
+            timestamp: now,
+        });
+
+        tracing::info!(%pool_id, %idle_since, "pool archived");
+        Ok(true)
+    }
+
+    /// Moves a pool to `status` (active, paused, or draining) and emits
+    /// `PoolStatusChanged`. Returns the previous status; nothing is
+    /// emitted if the pool was already in `status`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+    /// [`GatewayError::InvalidRequest`] if `status` is `archived` (pools
+    /// are only archived for inactivity), [`GatewayError::PoolArchived`]
+    /// if it is archived, or [`GatewayError::ReadOnlyReplica`] on a
+    /// replica.
+    pub async fn set_pool_status(
+        &self,
+        pool_id: PoolId,
+        status: PoolStatus,
+        actor: Option<&str>,
+    ) -> Result<PoolStatus, GatewayError> {
+        self.ensure_writable()?;
+        if status == PoolStatus::Archived {
+            return Err(GatewayError::InvalidRequest(
+                "pools are archived only for inactivity".to_string(),
+            ));
+        }
+        let entry_lock = self.registry.get(pool_id).await?;
+        let mut entry = self.write_entry(pool_id, &entry_lock, "set_status").await?;
+        let previous_status = entry.status;
+        if previous_status == PoolStatus::Archived {
+            return Err(GatewayError::PoolArchived(*pool_id.as_uuid()));
+        }
+        if previous_status == status {
+            return Ok(previous_status);
+        }
+        entry.status = status;
+        drop(entry);
+
+        self.emit(PoolEvent::PoolStatusChanged {
+            pool_id,
+            previous_status,
+            status,
+            actor: actor.map(str::to_string),
+            timestamp: Utc::now(),
+        });
+
+        tracing::info!(%pool_id, %previous_status, %status, actor, "pool status changed");
+        Ok(previous_status)
+    }
+
+    /// Replaces the metadata of a pool and emits `PoolMetadataUpdated`.
+    /// `metadata` must already be validated; `null` clears it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+    /// [`GatewayError::Forbidden`] if `manager` is neither its owner nor
+    /// an admin, [`GatewayError::PoolArchived`] if it is archived, or
+    /// [`GatewayError::ReadOnlyReplica`] on a replica.
+    pub async fn update_metadata(
+        &self,
+        pool_id: PoolId,
+        metadata: serde_json::Value,
+        manager: Manager<'_>,
+    ) -> Result<(), GatewayError> {
+        self.ensure_writable()?;
+        let entry_lock = self.registry.get(pool_id).await?;
+        let mut entry = self
+            .write_entry(pool_id, &entry_lock, "update_metadata")
+            .await?;
+        self.authorize(&entry, manager, "update", false)?;
+        if entry.archived_at.is_some() {
+            return Err(GatewayError::PoolArchived(*pool_id.as_uuid()));
+        }
+        entry.metadata = metadata.clone();
+        drop(entry);
+
+        self.emit(PoolEvent::PoolMetadataUpdated {
+            pool_id,
+            metadata,
+            actor: manager.actor().map(str::to_string),
+            timestamp: Utc::now(),
+        });
+
+        tracing::info!(%pool_id, "pool metadata updated");
+        Ok(())
+    }
+
+    /// Makes `new_owner` the owner of a pool and emits
+    /// `PoolOwnershipTransferred`. Returns the previous owner.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+    /// [`GatewayError::Forbidden`] if `manager` is neither its owner nor
+    /// an admin (only admins may transfer an unowned pool),
+    /// [`GatewayError::PoolArchived`] if it is archived, or
+    /// [`GatewayError::ReadOnlyReplica`] on a replica.
+    pub async fn transfer_ownership(
+        &self,
+        pool_id: PoolId,
+        new_owner: String,
+        manager: Manager<'_>,
+    ) -> Result<Option<String>, GatewayError> {
+        self.ensure_writable()?;
+        let entry_lock = self.registry.get(pool_id).await?;
+        let mut entry = self
+            .write_entry(pool_id, &entry_lock, "transfer_ownership")
+            .await?;
+        self.authorize(&entry, manager, "transfer", true)?;
+        if entry.archived_at.is_some() {
+            return Err(GatewayError::PoolArchived(*pool_id.as_uuid()));
+        }
+        let previous_owner = entry.owner.replace(new_owner.clone());
+        drop(entry);
+
+        self.emit(PoolEvent::PoolOwnershipTransferred {
+            pool_id,
+            previous_owner: previous_owner.clone(),
+            new_owner: new_owner.clone(),
+            actor: manager.actor().map(str::to_string),
+            timestamp: Utc::now(),
+        });
+
+        tracing::info!(%pool_id, ?previous_owner, new_owner, "pool ownership transferred");
+        Ok(previous_owner)
+    }
+
+    /// Marks a pool as a canary receiving about `weight_bps` of routed
+    /// flow, or promotes it to full routing with `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+    /// [`GatewayError::InvalidRequest`] if the weight exceeds 10 000 bps
+    /// or the pool has no creation config (so is never routed), or
+    /// [`GatewayError::ReadOnlyReplica`] on a replica.
+    pub async fn set_canary_weight(
+        &self,
+        pool_id: PoolId,
+        weight_bps: Option<u32>,
+    ) -> Result<(), GatewayError> {
+        self.ensure_writable()?;
+        if weight_bps.is_some_and(|weight| weight > FULL_CANARY_WEIGHT_BPS) {
+            return Err(GatewayError::InvalidRequest(format!(
+                "weight_bps must be at most {FULL_CANARY_WEIGHT_BPS}"
+            )));
+        }
+        let entry_lock = self.registry.get(pool_id).await?;
+        let mut entry = self.write_entry(pool_id, &entry_lock, "set_canary").await?;
+        if entry.config_json.is_null() {
+            return Err(GatewayError::InvalidRequest(format!(
+                "pool {pool_id} has no creation config and is never routed"
+            )));
+        }
+        entry.canary_weight_bps = weight_bps;
+        drop(entry);
+
+        match weight_bps {
+            Some(weight_bps) => tracing::info!(%pool_id, weight_bps, "pool marked as canary"),
+            None => tracing::info!(%pool_id, "canary pool promoted"),
+        }
+        Ok(())
+    }
+
+    /// Deletes a pool, returning its final state.
+    ///
+    /// The pool is detached from the registry first so no new operation
+    /// can reach it, then its write lock is taken to wait out in-flight
+    /// ones. Unless `force` is set, deletion is refused while providers
+    /// hold open positions. When `archive` is given and the pool has a
+    /// creation config, a final snapshot is saved before the removal; if
+    /// that fails the pool is restored. Operations still holding the
+    /// pool are refused from then on, and `PoolRemoved` is published once
+    /// the last of them lets go.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PoolNotFound`] if the pool does not exist,
+    /// [`GatewayError::Forbidden`] if `manager` is neither its owner nor
+    /// an admin, [`GatewayError::PoolInUse`] if it is in use and `force`
+    /// is not set, or a [`GatewayError::PersistenceError`] if archiving
+    /// fails.
+    pub async fn delete_pool(
+        &self,
+        pool_id: PoolId,
+        force: bool,
+        archive: Option<&PostgresPersistence>,
+        manager: Manager<'_>,
+    ) -> Result<DeletedPool, GatewayError> {
+        self.ensure_writable()?;
+        let entry_lock = self.registry.detach(pool_id).await?;
+        let mut entry = match self.write_entry(pool_id, &entry_lock, "delete").await {
+            Ok(entry) => entry,
+            Err(e) => {
+                self.registry
+                    .reattach(pool_id, Arc::clone(&entry_lock))
+                    .await;
+                return Err(e);
+            }
+        };
+        if let Err(e) = self.authorize(&entry, manager, "delete", false) {
+            drop(entry);
+            self.registry.reattach(pool_id, entry_lock).await;
+            return Err(e);
+        }
+
+        if entry.provided_liquidity > 0 && !force {
+            let reason = format!(
+                "{} LP units still provided to pool {pool_id}",
+                entry.provided_liquidity
+            );
+            drop(entry);
+            self.registry.reattach(pool_id, entry_lock).await;
+            return Err(GatewayError::PoolInUse(reason));
+        }
+
+        let mut archived_snapshot_id = None;
+        if let Some(db) = archive
+            && let Ok(parts) = snapshot::encode(&entry)
+        {
+            let saved = timing::measure(
+                Phase::Persist,
+                db.save_snapshot(
+                    *pool_id.as_uuid(),
+                    &entry.pool_type,
+                    &parts.config_json,
+                    &parts.state_json,
+                    &parts.metadata_json,
+                ),
+            )
+            .await;
+            match saved {
+                Ok(id) => archived_snapshot_id = Some(id),
+                Err(e) => {
+                    drop(entry);
+                    self.registry.reattach(pool_id, entry_lock).await;
+                    return Err(e);
+                }
+            }
+        }
+
+        let deleted = DeletedPool {
+            summary: PoolSummary::from(&*entry),
+            total_volume: entry.total_volume,
+            config_json: entry.config_json.clone(),
+            journal: entry.journal.clone(),
+            archived_snapshot_id,
+            forced: force,
+        };
+        entry.removed = true;
+        drop(entry);
+
+        self.contention.forget(pool_id);
+        self.publish_removal(Tombstone::new(pool_id, entry_lock));
+        tracing::info!(%pool_id, force, archived = archived_snapshot_id.is_some(), "pool deleted");
+        Ok(deleted)
+    }
+
+    /// Replaces a pool's state with the one stored in `snapshot` and
+    /// publishes `PoolRestored`.
+    ///
+    /// The current state is archived as a snapshot in `db` first, and the
+    /// restored state is saved again as the pool's latest snapshot so
+    /// recovery and replay start from it; if either save fails the pool is
+    /// left untouched. Positions, resting orders, and counters are rewound
+    /// with the pool. Only the operator and admin clients may restore.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::Forbidden`] if `manager` is not an admin,
+    /// [`GatewayError::PoolNotFound`] if the pool does not exist,
+    /// [`GatewayError::InvalidRequest`] if the snapshot belongs to another
+    /// pool or the pool cannot be snapshotted, or a
+    /// [`GatewayError::PersistenceError`] if the snapshot is malformed or
+    /// cannot be saved.
+    pub async fn restore_pool(
+        &self,
+        pool_id: PoolId,
+        snapshot: &PoolSnapshot,
+        db: &PostgresPersistence,
+        manager: Manager<'_>,
+    ) -> Result<RestoredPool, GatewayError> {
+        self.ensure_writable()?;
+        if !self.is_admin(manager) {
+            return Err(GatewayError::Forbidden(format!(
+                "only an admin may restore pool {pool_id}"
+            )));
+        }
+        if snapshot.pool_id != *pool_id.as_uuid() {
+            return Err(GatewayError::InvalidRequest(format!(
+                "snapshot {} belongs to pool {}, not {pool_id}",
+                snapshot.id, snapshot.pool_id
+            )));
+        }
+        let mut restored = snapshot::decode(snapshot)?;
+
+        let entry_lock = self.registry.get(pool_id).await?;
+        let mut entry = self.write_entry(pool_id, &entry_lock, "restore").await?;
+        // The restore is itself a version, so versions never repeat
+        restored.version_base = entry
+            .version()
+            .saturating_add(1)
+            .saturating_sub(u64::try_from(restored.journal.len()).unwrap_or(u64::MAX));
+        let restored_parts = snapshot::encode(&restored)?;
+        let current = snapshot::encode(&entry)?;
+        let archived_snapshot_id = timing::measure(
+            Phase::Persist,
+            db.save_snapshot(
+                *pool_id.as_uuid(),
+                &entry.pool_type,
+                &current.config_json,
+                &current.state_json,
+                &current.metadata_json,
+            ),
+        )
+        .await?;
+        // Taken before the restored state is saved, so replay can tell
+        // that snapshot already includes the restore
+        let timestamp = Utc::now();
+        timing::measure(
+            Phase::Persist,
+            db.save_snapshot(
+                *pool_id.as_uuid(),
+                &restored.pool_type,
+                &restored_parts.config_json,
+                &restored_parts.state_json,
+                &restored_parts.metadata_json,
+            ),
+        )
+        .await?;
+
+        *entry = restored;
+        let summary = PoolSummary::from(&*entry);
+        self.emit_versioned(
+            PoolEvent::PoolRestored {
+                pool_id,
+                snapshot_id: snapshot.id,
+                snapshot_at: snapshot.snapshot_at,
+                archived_snapshot_id,
+                actor: manager.actor().map(str::to_string),
+                timestamp,
+            },
+            entry.version(),
+        );
+        drop(entry);
+        tracing::info!(
+            %pool_id,
+            snapshot_id = snapshot.id,
+            archived_snapshot_id,
+            actor = manager.actor(),
+            "pool restored from snapshot"
+        );
+        Ok(RestoredPool {
+            summary,
+            snapshot_id: snapshot.id,
+            snapshot_at: snapshot.snapshot_at,
+            archived_snapshot_id,
+        })
+    }
+
+    /// Returns summaries of the pools matching `filter`, in its order.
+    pub async fn list_pools(&self, filter: &PoolFilter) -> Vec<PoolSummary> {
+        self.registry.list(filter).await
+    }
+
+    /// Returns every distinct token across registered pools, optionally
+    /// restricted to symbols starting with `symbol_prefix`
+    /// (case-insensitive).
+    pub async fn known_tokens(&self, symbol_prefix: Option<&str>) -> Vec<KnownToken> {
+        let pools = self.registry.list(&PoolFilter::default()).await;
+        let mut tokens = known_tokens(&pools);
+        if let Some(prefix) = symbol_prefix {
+            let prefix = prefix.to_ascii_lowercase();
+            tokens.retain(|t| t.symbol.to_ascii_lowercase().starts_with(&prefix));
+        }
+        tokens
+    }
+
+    /// Returns summaries of every pool holding the token at `address`,
+    /// ordered by total liquidity (deepest first).
+    pub async fn pools_with_token(&self, address: &str) -> Vec<PoolSummary> {
+        let mut pools: Vec<PoolSummary> = self
+            .registry
+            .list(&PoolFilter::default())
+            .await
+            .into_iter()
+            .filter(|p| p.has_token(address))
+            .collect();
+        pools.sort_by_key(|p| std::cmp::Reverse(p.total_liquidity));
+        pools
+    }
+}
+
+/// Final state of a deleted pool.
+#[derive(Debug, Clone)]
+pub struct DeletedPool {
+    /// Summary at the moment of deletion.
+    pub summary: PoolSummary,
+    /// Cumulative swap volume.
+    pub total_volume: u128,
+    /// Creation config (`Null` for programmatic pools).
+    pub config_json: serde_json::Value,
+    /// Operation journal; with the config it rebuilds the final state.
+    pub journal: Journal,
+    /// Id of the archived final snapshot, if one was saved.
+    pub archived_snapshot_id: Option<i64>,
+    /// Whether deletion was forced.
+    pub forced: bool,
+}
+
+/// Outcome of [`PoolService::restore_pool`].
+#[derive(Debug, Clone)]
+pub struct RestoredPool {
+    /// Summary of the restored pool.
+    pub summary: PoolSummary,
+    /// Snapshot the pool was restored from.
+    pub snapshot_id: i64,
+    /// When that snapshot was taken.
+    pub snapshot_at: DateTime<Utc>,
+    /// Snapshot holding the state that was replaced.
+    pub archived_snapshot_id: i64,
+}
+
+/// Builds a pool entry from a type-specific JSON config.
+///
+/// Reserves are first converted to smallest units per the config's
+/// `reserve_units` (see [`config_parser::resolve_reserve_units`]), then an
+/// `initial_price` is expanded into the fields it determines (see
+/// [`config_parser::resolve_initial_price`]); the entry keeps the expanded
+/// config.
+///
+/// # Errors
+///
+/// Returns a [`GatewayError`] if the config is invalid or hydra-amm
+/// rejects it.
+pub fn build_entry(
+    pool_id: PoolId,
+    pool_type: &str,
+    config_json: serde_json::Value,
+) -> Result<PoolEntry, GatewayError> {
+    let config_json = config_parser::resolve_reserve_units(pool_type, config_json)?;
+    let config_json = config_parser::resolve_initial_price(pool_type, config_json)?;
+    let (config, fee_bps) = config_parser::parse_pool_config(pool_type, &config_json)?;
+    let pool_box = DefaultPoolFactory::create(&config)?;
+    let tokens = config_parser::token_infos(pool_type, &config_json);
+    let reserves = config_parser::initial_reserves(pool_type, &config_json);
+    let price_base = config_parser::price_base(&tokens, &config_json)?;
+    let mut entry = PoolEntry::new(pool_id, pool_box, pool_type.to_string(), fee_bps)
+        .with_definition(config_json, tokens, reserves);
+    entry.price_base = price_base;
+    Ok(entry)
+}
+
+/// Rejects a new pool whose initial reserves are absurdly far apart (see
+/// [`config_parser::check_reserve_spread`]). Only applied on creation:
+/// pools rebuilt from the log keep whatever reserves they were created
+/// with.
+fn check_spread(entry: &PoolEntry) -> Result<(), GatewayError> {
+    entry.reserves.as_deref().map_or(Ok(()), |reserves| {
+        config_parser::check_reserve_spread(&entry.tokens, reserves)
+    })
+}
+
+/// Dry-runs pool creation from a JSON config without registering
+/// anything.
+///
+/// Reports every field-level problem at once; when there are none, the
+/// pool is built (and discarded) so hydra-amm validates the config as a
+/// whole. An empty result means creation would succeed.
+#[must_use]
+pub fn validate_config(pool_type: &str, config_json: serde_json::Value) -> Vec<ConfigIssue> {
+    let config_json = match config_parser::resolve_reserve_units(pool_type, config_json) {
+        Ok(config) => config,
+        Err(e) => return vec![ConfigIssue::new("reserve_units", &e)],
+    };
+    let config_json = match config_parser::resolve_initial_price(pool_type, config_json) {
+        Ok(config) => config,
+        Err(e) => return vec![ConfigIssue::new("initial_price", &e)],
+    };
+    let issues = config_parser::config_issues(pool_type, &config_json);
+    if !issues.is_empty() {
+        return issues;
+    }
+    match build_entry(PoolId::new(), pool_type, config_json).and_then(|entry| check_spread(&entry))
+    {
+        Ok(()) => Vec::new(),
+        Err(e) => vec![ConfigIssue::new("config", &e)],
+    }
+}
+
+/// Computes the price change in basis points between two price values.
+fn compute_price_change_bps(old: f64, new: f64) -> i32 {
+    if old == 0.0 {
+        return 0;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let bps = ((new - old) / old * 10_000.0) as i32;
+    bps
+}
+
+/// Passes `guard` through unless its pool was removed while the caller
+/// waited for it.
+fn live(
+    pool_id: PoolId,
+    guard: TrackedWriteGuard<'_>,
+) -> Result<TrackedWriteGuard<'_>, GatewayError> {
+    if guard.removed {
+        return Err(GatewayError::PoolNotFound(*pool_id.as_uuid()));
+    }
+    Ok(guard)
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::service::routing::{self, PerHopCost, RouteOptions};
+    use hydra_amm::config::ConstantProductConfig;
+    use hydra_amm::domain::{BasisPoints, Decimals, FeeTier, TokenAddress, TokenPair};
+
+    fn make_config() -> (AmmConfig, Token, Token) {
+        let Ok(d6) = Decimals::new(6) else {
+            panic!("valid decimals");
+        };
+        let Ok(d18) = Decimals::new(18) else {
+            panic!("valid decimals");
+        };
+        let tok_a = Token::new(TokenAddress::from_bytes([1u8; 32]), d6);
+        let tok_b = Token::new(TokenAddress::from_bytes([2u8; 32]), d18);
+        let Ok(pair) = TokenPair::new(tok_a, tok_b) else {
+            panic!("valid pair");
+        };
+        let fee = FeeTier::new(BasisPoints::new(30));
+        let Ok(cfg) =
+            ConstantProductConfig::new(pair, fee, Amount::new(1_000_000), Amount::new(1_000_000))
+        else {
+            panic!("valid config");
+        };
+        (AmmConfig::ConstantProduct(cfg), tok_a, tok_b)
+    }
+
+    fn make_service() -> PoolService {
+        let registry = Arc::new(PoolRegistry::new());
+        let event_bus = EventBus::new(1000);
+        PoolService::new(registry, event_bus)
+    }
+
+    #[tokio::test]
+    async fn create_pool_emits_event() {
+        let service = make_service();
+        let mut rx = service.event_bus().subscribe();
+        let (config, _, _) = make_config();
+
+        let result = service.create_pool(&config, "constant_product", 30).await;
+        assert!(result.is_ok());
+
+        let event = rx.recv().await;
+        let Ok(event) = event else {
+            panic!("expected event");
+        };
+        assert_eq!(event.event_type_str(), "pool_created");
+    }
+
+    #[derive(Debug)]
+    struct RecordingHook {
+        bus: std::sync::Mutex<tokio::sync::broadcast::Receiver<PoolEvent>>,
+        seen: std::sync::Mutex<Vec<(&'static str, bool)>>,
+    }
+
+    impl EventHook for RecordingHook {
+        fn on_event(&self, event: &PoolEvent) {
+            let Ok(mut bus) = self.bus.lock() else {
+                return;
+            };
+            // The bus may hold earlier events, but not this one yet
+            let mut published = false;
+            while let Ok(earlier) = bus.try_recv() {
+                published |= earlier.event_type_str() == event.event_type_str();
+            }
+            if let Ok(mut seen) = self.seen.lock() {
+                seen.push((event.event_type_str(), published));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn event_hooks_run_before_publish() {
+        let service = make_service();
+        let hook = Arc::new(RecordingHook {
+            bus: std::sync::Mutex::new(service.event_bus().subscribe()),
+            seen: std::sync::Mutex::default(),
+        });
+        let service = service.with_event_hook(Arc::<RecordingHook>::clone(&hook));
+        let (config, _, _) = make_config();
+
+        let Ok(pool_id) = service.create_pool(&config, "constant_product", 30).await else {
+            panic!("pool creation failed");
+        };
+        assert!(service.remove_pool(pool_id).await.is_ok());
+
+        let Ok(seen) = hook.seen.lock() else {
+            panic!("hook poisoned");
+        };
+        assert_eq!(*seen, [("pool_created", false), ("pool_removed", false)]);
+    }
+
+    #[tokio::test]
+    async fn stuck_pool_lock_times_out_with_retryable_error() {
+        let service = make_service().with_lock_timeout(Duration::from_millis(20));
+        let (config, tok_a, _) = make_config();
+        let Ok(pool_id) = service.create_pool(&config, "constant_product", 30).await else {
+            panic!("pool creation failed");
+        };
+        let Ok(entry_lock) = service.registry().get(pool_id).await else {
+            panic!("pool not found");
+        };
+        let held = entry_lock.read().await;
+
+        let result = service
+            .execute_swap(
+                pool_id,
+                SwapKind::ExactIn,
+                Amount::new(1000),
+                tok_a,
+                SwapConditions::default(),
+                "cmd-1",
+                None,
+            )
+            .await;
+        let Err(error @ GatewayError::LockTimeout { retry_after_ms, .. }) = result else {
+            panic!("expected lock timeout");
+        };
+        assert_eq!(retry_after_ms, 20);
+        assert!(error.is_retryable());
+        assert_eq!(service.capacity_usage().await.lock_timeouts, 1);
+
+        drop(held);
+        let Ok(_) = service
+            .delete_pool(pool_id, true, None, Manager::Operator)
+            .await
+        else {
+            panic!("pool not deleted once the lock is free");
+        };
+    }
+
+    #[tokio::test]
+    async fn execute_swap_updates_state() {
+        let service = make_service();
+        let (config, tok_a, _) = make_config();
+
+        let Ok(pool_id) = service.create_pool(&config, "constant_product", 30).await else {
+            panic!("pool creation failed");
+        };
+
+        let result = service
+            .execute_swap(
+                pool_id,
+                SwapKind::ExactIn,
+                Amount::new(1000),
+                tok_a,
+                SwapConditions::default(),
+                "cmd-1",
+                Some("bot-1"),
+            )
+            .await;
+        assert!(result.is_ok());
+
+        let entry_lock = service.registry().get(pool_id).await;
+        let Ok(entry_lock) = entry_lock else {
+            panic!("pool not found");
+        };
+        let entry = entry_lock.read().await;
+        assert_eq!(entry.swap_count, 1);
+        assert!(entry.total_volume > 0);
+    }
+
+    #[tokio::test]
+    async fn stale_expected_version_rejects_swap() {
+        let service = make_service();
+        let (config, tok_a, _) = make_config();
+        let Ok(pool_id) = service.create_pool(&config, "constant_product", 30).await else {
+            panic!("pool creation failed");
+        };
+        let swap = |expected_version| {
+            service.execute_swap(
+                pool_id,
+                SwapKind::ExactIn,
+                Amount::new(1000),
+                tok_a,
+                SwapConditions {
+                    expected_version,
+                    ..SwapConditions::default()
+                },
+                "cmd",
+                None,
+            )
+        };
+
+        assert!(swap(Some(0)).await.is_ok());
+        let Err(GatewayError::VersionMismatch { expected, actual }) = swap(Some(0)).await else {
+            panic!("stale version accepted");
+        };
+        assert_eq!((expected, actual), (0, 1));
+        assert!(swap(Some(1)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn paused_pools_reject_swaps_until_resumed() {
+        let service = make_service();
+        let (config, tok_a, _) = make_config();
+        let Ok(pool_id) = service.create_pool(&config, "constant_product", 30).await else {
+            panic!("pool creation failed");
+        };
+        let mut rx = service.event_bus().subscribe();
+        let swap = || {
+            service.execute_swap(
+                pool_id,
+                SwapKind::ExactIn,
+                Amount::new(1000),
+                tok_a,
+                SwapConditions::default(),
+                "cmd",
+                None,
+            )
+        };
+
+        let paused = service
+            .set_pool_status(pool_id, PoolStatus::Paused, Some("ops"))
+            .await;
+        assert_eq!(paused.ok(), Some(PoolStatus::Active));
+        let Ok(PoolEvent::PoolStatusChanged {
+            previous_status,
+            status,
+            actor,
+            ..
+        }) = rx.recv().await
+        else {
+            panic!("expected a status change event");
+        };
+        assert_eq!(
+            (previous_status, status, actor.as_deref()),
+            (PoolStatus::Active, PoolStatus::Paused, Some("ops"))
+        );
+        let Err(
+            error @ GatewayError::PoolPaused {
+                status: "paused", ..
+            },
+        ) = swap().await
+        else {
+            panic!("paused pool accepted a swap");
+        };
+        assert_eq!(error.status_code(), axum::http::StatusCode::CONFLICT);
+
+        // Draining pools still reject swaps
+        let drained = service
+            .set_pool_status(pool_id, PoolStatus::Draining, None)
+            .await;
+        assert_eq!(drained.ok(), Some(PoolStatus::Paused));
+        assert!(matches!(
+            swap().await,
+            Err(GatewayError::PoolPaused {
+                status: "draining",
+                ..
+            })
+        ));
+        assert!(matches!(
+            service
+                .set_pool_status(pool_id, PoolStatus::Archived, None)
+                .await,
+            Err(GatewayError::InvalidRequest(_))
+        ));
+
+        let resumed = service
+            .set_pool_status(pool_id, PoolStatus::Active, None)
+            .await;
+        assert_eq!(resumed.ok(), Some(PoolStatus::Draining));
+        assert!(swap().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn programmatic_pools_are_quoted_in_place() {
+        let service = make_service();
+        let (config, tok_a, _) = make_config();
+        let Ok(pool_id) = service.create_pool(&config, "constant_product", 30).await else {
+            panic!("pool creation failed");
+        };
+
+        let Ok(quote) = service
+            .quote_swap(pool_id, SwapKind::ExactIn, Amount::new(1000), tok_a)
+            .await
+        else {
+            panic!("programmatic pool quote failed");
+        };
+        assert!(quote.amount_out().get() > 0);
+        let Ok(entry_lock) = service.registry().get(pool_id).await else {
+            panic!("pool not found");
+        };
+        let entry = entry_lock.read().await;
+        assert_eq!(entry.swap_count, 0);
+        assert_eq!(entry.version(), 0);
+    }
+
+    #[tokio::test]
+    async fn quote_swap_does_not_mutate() {
+        let service = make_service();
+        let config = serde_json::json!({
+            "token_a": {"address": "0xaaa", "decimals": 6},
+            "token_b": {"address": "0xbbb", "decimals": 18},
+            "fee_bps": 30,
+            "reserve_a": "1000000",
+            "reserve_b": "1000000",
+        });
+        let Ok(pool_id) = service
+            .create_pool_from_config(
+                PoolId::new(),
+                "constant_product",
+                config,
+                serde_json::Value::Null,
+                None,
+            )
+            .await
+        else {
+            panic!("pool creation failed");
+        };
+        let Ok(entry_lock) = service.registry().get(pool_id).await else {
+            panic!("pool not found");
+        };
+        let tok_a = entry_lock.read().await.pool_box.token_pair().first();
+
+        let Ok(quote) = service
+            .quote_swap(pool_id, SwapKind::ExactIn, Amount::new(1000), tok_a)
+            .await
+        else {
+            panic!("quote failed");
+        };
+        {
+            let entry = entry_lock.read().await;
+            assert_eq!(entry.swap_count, 0);
+            assert_eq!(entry.version(), 0);
+            assert_eq!(entry.reserves, Some(vec![1_000_000, 1_000_000]));
+        }
+
+        // The quote matches what the same swap then executes at
+        let Ok((executed, _)) = service
+            .execute_swap(
+                pool_id,
+                SwapKind::ExactIn,
+                Amount::new(1000),
+                tok_a,
+                SwapConditions::default(),
+                "cmd-1",
+                None,
+            )
+            .await
+        else {
+            panic!("swap failed");
+        };
+        assert_eq!(quote.amount_out(), executed.amount_out());
     }
-}
 
-/// Computes the price change in basis points between two price values.
-fn compute_price_change_bps(old: f64, new: f64) -> i32 {
-    if old == 0.0 {
-        return 0;
+    #[tokio::test]
+    async fn slippage_bounds_reject_swaps_without_mutating() {
+        let service = make_service();
+        let config = serde_json::json!({
+            "token_a": {"address": "0xaaa", "decimals": 6},
+            "token_b": {"address": "0xbbb", "decimals": 6},
+            "fee_bps": 30,
+            "reserve_a": "1000000",
+            "reserve_b": "1000000",
+        });
+        let Ok(pool_id) = service
+            .create_pool_from_config(
+                PoolId::new(),
+                "constant_product",
+                config,
+                serde_json::Value::Null,
+                None,
+            )
+            .await
+        else {
+            panic!("pool creation failed");
+        };
+        let Ok(entry_lock) = service.registry().get(pool_id).await else {
+            panic!("pool not found");
+        };
+        let tok_a = entry_lock.read().await.pool_box.token_pair().first();
+        let swap = |kind, conditions| {
+            service.execute_swap(
+                pool_id,
+                kind,
+                Amount::new(1000),
+                tok_a,
+                conditions,
+                "cmd",
+                None,
+            )
+        };
+
+        let too_greedy = SwapConditions {
+            min_amount_out: Some(1000),
+            ..SwapConditions::default()
+        };
+        let Err(GatewayError::SlippageExceeded { bound, .. }) =
+            swap(SwapKind::ExactIn, too_greedy).await
+        else {
+            panic!("output below min_amount_out accepted");
+        };
+        assert_eq!(bound, "min_amount_out");
+        let too_cheap = SwapConditions {
+            max_amount_in: Some(1000),
+            ..SwapConditions::default()
+        };
+        let Err(GatewayError::SlippageExceeded { bound, .. }) =
+            swap(SwapKind::ExactOut, too_cheap).await
+        else {
+            panic!("input above max_amount_in accepted");
+        };
+        assert_eq!(bound, "max_amount_in");
+        assert_eq!(entry_lock.read().await.version(), 0);
+
+        let fair = SwapConditions {
+            min_amount_out: Some(900),
+            max_amount_in: Some(1000),
+            ..SwapConditions::default()
+        };
+        assert!(swap(SwapKind::ExactIn, fair).await.is_ok());
+        assert_eq!(entry_lock.read().await.version(), 1);
     }
-    #[allow(clippy::cast_possible_truncation)]
-    let bps = ((new - old) / old * 10_000.0) as i32;
-    bps
-}
 
-#[cfg(test)]
-#[allow(clippy::panic)]
-mod tests {
-    use super::*;
-    use hydra_amm::config::ConstantProductConfig;
-    use hydra_amm::domain::{Amount, BasisPoints, Decimals, FeeTier, TokenAddress, TokenPair};
+    #[tokio::test]
+    async fn expired_deadline_rejects_swap() {
+        let service = make_service();
+        let (config, tok_a, _) = make_config();
+        let Ok(pool_id) = service.create_pool(&config, "constant_product", 30).await else {
+            panic!("pool creation failed");
+        };
+        let now = Utc::now();
+        assert!(check_deadline(None, now).is_ok());
+        assert!(check_deadline(Some(now), now).is_ok());
 
-    fn make_config() -> (AmmConfig, Token, Token) {
-        let Ok(d6) = Decimals::new(6) else {
-            panic!("valid decimals");
+        let expired = SwapConditions {
+            deadline: Some(now - chrono::Duration::seconds(1)),
+            ..SwapConditions::default()
         };
-        let Ok(d18) = Decimals::new(18) else {
-            panic!("valid decimals");
+        let result = service
+            .execute_swap(
+                pool_id,
+                SwapKind::ExactIn,
+                Amount::new(1000),
+                tok_a,
+                expired,
+                "cmd-1",
+                None,
+            )
+            .await;
+        assert!(matches!(result, Err(GatewayError::DeadlineExpired { .. })));
+        let Ok(entry_lock) = service.registry().get(pool_id).await else {
+            panic!("pool not found");
         };
-        let tok_a = Token::new(TokenAddress::from_bytes([1u8; 32]), d6);
-        let tok_b = Token::new(TokenAddress::from_bytes([2u8; 32]), d18);
-        let Ok(pair) = TokenPair::new(tok_a, tok_b) else {
-            panic!("valid pair");
+        assert_eq!(entry_lock.read().await.swap_count, 0);
+    }
+
+    #[tokio::test]
+    async fn pools_with_token_matches_config_addresses() {
+        let service = make_service();
+        let config = serde_json::json!({
+            "token_a": {"address": "0xAbC", "decimals": 6, "symbol": "USDC"},
+            "token_b": {"address": "0xdef", "decimals": 18},
+            "fee_bps": 30,
+            "reserve_a": "1000000",
+            "reserve_b": "1000000",
+        });
+        let Ok(pool_id) = service
+            .create_pool_from_config(
+                PoolId::new(),
+                "constant_product",
+                config,
+                serde_json::Value::Null,
+                None,
+            )
+            .await
+        else {
+            panic!("pool creation failed");
         };
-        let fee = FeeTier::new(BasisPoints::new(30));
-        let Ok(cfg) =
-            ConstantProductConfig::new(pair, fee, Amount::new(1_000_000), Amount::new(1_000_000))
+
+        let found = service.pools_with_token("0xabc").await;
+        assert_eq!(found.first().map(|p| p.pool_id), Some(pool_id));
+        assert!(service.pools_with_token("0x123").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn execute_route_applies_hops_and_enforces_minimum() {
+        let service = make_service();
+        let config = serde_json::json!({
+            "token_a": {"address": "0xaaa", "decimals": 6},
+            "token_b": {"address": "0xbbb", "decimals": 6},
+            "fee_bps": 30,
+            "reserve_a": "1000000",
+            "reserve_b": "1000000",
+        });
+        let Ok(pool_id) = service
+            .create_pool_from_config(
+                PoolId::new(),
+                "constant_product",
+                config,
+                serde_json::Value::Null,
+                None,
+            )
+            .await
         else {
-            panic!("valid config");
+            panic!("pool creation failed");
         };
-        (AmmConfig::ConstantProduct(cfg), tok_a, tok_b)
+        let seeds = service.route_seeds().await;
+        let Ok(plan) = routing::best_route(
+            &seeds,
+            "0xaaa",
+            "0xbbb",
+            1_000,
+            RouteOptions::default(),
+            &PerHopCost::default(),
+        ) else {
+            panic!("no route");
+        };
+
+        let too_high = plan.amount_out + 1;
+        assert!(matches!(
+            service.execute_route(&plan, too_high, "cmd", None).await,
+            Err(GatewayError::SlippageExceeded { .. })
+        ));
+
+        let Ok(executed) = service
+            .execute_route(&plan, plan.amount_out, "cmd", None)
+            .await
+        else {
+            panic!("route execution failed");
+        };
+        assert_eq!(executed, plan);
+        let Ok(lock) = service.registry().get(pool_id).await else {
+            panic!("pool missing");
+        };
+        assert_eq!(lock.read().await.swap_count, 1);
     }
 
-    fn make_service() -> PoolService {
-        let registry = Arc::new(PoolRegistry::new());
-        let event_bus = EventBus::new(1000);
-        PoolService::new(registry, event_bus)
+    #[tokio::test]
+    async fn only_owner_or_admin_manages_an_owned_pool() {
+        let service = make_service().with_admins(BTreeSet::from(["root".to_string()]));
+        let config = serde_json::json!({
+            "token_a": {"address": "0xaaa", "decimals": 6},
+            "token_b": {"address": "0xbbb", "decimals": 6},
+            "fee_bps": 30,
+            "reserve_a": "1000000",
+            "reserve_b": "1000000",
+        });
+        let Ok(pool_id) = service
+            .create_pool_from_config(
+                PoolId::new(),
+                "constant_product",
+                config,
+                serde_json::Value::Null,
+                Some("alice"),
+            )
+            .await
+        else {
+            panic!("pool creation failed");
+        };
+        let metadata = || serde_json::json!({"label": "x"});
+
+        for manager in [Manager::Client(Some("bob")), Manager::Client(None)] {
+            assert!(matches!(
+                service.update_metadata(pool_id, metadata(), manager).await,
+                Err(GatewayError::Forbidden(_))
+            ));
+        }
+        assert!(matches!(
+            service
+                .delete_pool(pool_id, false, None, Manager::Client(Some("bob")))
+                .await,
+            Err(GatewayError::Forbidden(_))
+        ));
+        assert!(
+            service
+                .update_metadata(pool_id, metadata(), Manager::Client(Some("root")))
+                .await
+                .is_ok()
+        );
+
+        let mut rx = service.event_bus().subscribe();
+        let Ok(previous) = service
+            .transfer_ownership(pool_id, "bob".to_string(), Manager::Client(Some("alice")))
+            .await
+        else {
+            panic!("owner could not transfer");
+        };
+        assert_eq!(previous.as_deref(), Some("alice"));
+        let Ok(PoolEvent::PoolOwnershipTransferred { new_owner, .. }) = rx.try_recv() else {
+            panic!("expected PoolOwnershipTransferred");
+        };
+        assert_eq!(new_owner, "bob");
+        assert!(matches!(
+            service
+                .transfer_ownership(pool_id, "alice".to_string(), Manager::Client(Some("alice")))
+                .await,
+            Err(GatewayError::Forbidden(_))
+        ));
+        assert!(
+            service
+                .delete_pool(pool_id, false, None, Manager::Client(Some("bob")))
+                .await
+                .is_ok()
+        );
     }
 
     #[tokio::test]
-    async fn create_pool_emits_event() {
+    async fn only_admins_restore_pools() {
+        let service = make_service().with_admins(BTreeSet::from(["root".to_string()]));
+        let (config, _, _) = make_config();
+        let Ok(pool_id) = service.create_pool(&config, "constant_product", 30).await else {
+            panic!("pool creation failed");
+        };
+        let stored = PoolSnapshot {
+            id: 1,
+            pool_id: *pool_id.as_uuid(),
+            pool_type: "constant_product".to_string(),
+            config_json: serde_json::Value::Null,
+            state_json: serde_json::Value::Null,
+            metadata_json: serde_json::Value::Null,
+            snapshot_at: Utc::now(),
+        };
+        // Never connected: the refusal comes before any query
+        let Ok(pool) =
+            sqlx::postgres::PgPoolOptions::new().connect_lazy("postgres://localhost/none")
+        else {
+            panic!("lazy pool");
+        };
+        let db = PostgresPersistence::new(pool);
+
+        for manager in [Manager::Client(Some("desk-1")), Manager::Client(None)] {
+            assert!(matches!(
+                service.restore_pool(pool_id, &stored, &db, manager).await,
+                Err(GatewayError::Forbidden(_))
+            ));
+        }
+        assert!(service.is_admin(Manager::Client(Some("root"))));
+        assert!(service.is_admin(Manager::Operator));
+    }
+
+    #[tokio::test]
+    async fn oracle_updates_outside_bounds_are_rejected() {
         let service = make_service();
+        let config = serde_json::json!({
+            "token_a": {"address": "0xaaa", "decimals": 6},
+            "token_b": {"address": "0xbbb", "decimals": 6},
+            "fee_bps": 30,
+            "oracle_price": 1.0,
+            "slippage_coefficient": 0.5,
+            "reserve_a": "1000000",
+            "reserve_b": "1000000",
+            "oracle_bounds": {"min_price": 0.5, "max_price": 2.0, "max_step_pct": 10.0},
+        });
+        let Ok(pool_id) = service
+            .create_pool_from_config(
+                PoolId::new(),
+                "dynamic",
+                config,
+                serde_json::Value::Null,
+                None,
+            )
+            .await
+        else {
+            panic!("pool creation failed");
+        };
         let mut rx = service.event_bus().subscribe();
+
+        let Ok(update) = service
+            .set_oracle_price(pool_id, 1.05, Manager::Operator)
+            .await
+        else {
+            panic!("in-bounds price rejected");
+        };
+        assert!((update.previous_price - 1.0).abs() < f64::EPSILON);
+        let Ok(PoolEvent::OraclePriceUpdated { new_price, .. }) = rx.try_recv() else {
+            panic!("expected OraclePriceUpdated");
+        };
+        assert_eq!(new_price, "1.05");
+
+        for price in [1.3, 2.5, -1.0] {
+            assert!(
+                service
+                    .set_oracle_price(pool_id, price, Manager::Operator)
+                    .await
+                    .is_err()
+            );
+        }
+        let Ok(lock) = service.registry().get(pool_id).await else {
+            panic!("pool missing");
+        };
+        let entry = lock.read().await;
+        assert_eq!(entry.current_oracle_price(), Some(1.05));
+        assert_eq!(entry.version(), 1);
+    }
+
+    #[tokio::test]
+    async fn delete_pool_refuses_open_liquidity_unless_forced() {
+        let service = make_service();
         let (config, _, _) = make_config();
+        let Ok(pool_id) = service.create_pool(&config, "constant_product", 30).await else {
+            panic!("pool creation failed");
+        };
+        let Ok(_) = service
+            .add_liquidity(
+                pool_id,
+                Amount::new(1_000),
+                Amount::new(1_000),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+        else {
+            panic!("add liquidity failed");
+        };
 
-        let result = service.create_pool(&config, "constant_product", 30).await;
-        assert!(result.is_ok());
+        assert!(matches!(
+            service
+                .delete_pool(pool_id, false, None, Manager::Operator)
+                .await,
+            Err(GatewayError::PoolInUse(_))
+        ));
+        assert!(service.registry().get(pool_id).await.is_ok());
+
+        let Ok(deleted) = service
+            .delete_pool(pool_id, true, None, Manager::Operator)
+            .await
+        else {
+            panic!("forced delete failed");
+        };
+        assert!(deleted.forced);
+        assert_eq!(deleted.journal.len(), 1);
+        assert!(service.registry().get(pool_id).await.is_err());
+    }
 
-        let event = rx.recv().await;
-        let Ok(event) = event else {
-            panic!("expected event");
+    #[tokio::test]
+    async fn capacity_limits_reject_new_pools_and_positions() {
+        let service = make_service().with_capacity_limits(CapacityLimits {
+            max_pools: Some(1),
+            max_positions_per_pool: Some(1),
+        });
+        let (config, _, _) = make_config();
+        let Ok(pool_id) = service.create_pool(&config, "constant_product", 30).await else {
+            panic!("pool creation failed");
         };
-        assert_eq!(event.event_type_str(), "pool_created");
+        assert!(matches!(
+            service.create_pool(&config, "constant_product", 30).await,
+            Err(GatewayError::CapacityExceeded(_))
+        ));
+
+        let Ok((position_id, _)) = service
+            .add_liquidity(
+                pool_id,
+                Amount::new(1_000),
+                Amount::new(1_000),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+        else {
+            panic!("add liquidity failed");
+        };
+        assert!(matches!(
+            service
+                .add_liquidity(
+                    pool_id,
+                    Amount::new(1_000),
+                    Amount::new(1_000),
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await,
+            Err(GatewayError::CapacityExceeded(_))
+        ));
+        assert!(
+            service
+                .add_liquidity(
+                    pool_id,
+                    Amount::new(1_000),
+                    Amount::new(1_000),
+                    Some(position_id),
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .is_ok(),
+            "existing positions can still be topped up"
+        );
+
+        let usage = service.capacity_usage().await;
+        assert_eq!((usage.pools, usage.positions), (1, 1));
+        assert_eq!(usage.limits.max_pools, Some(1));
     }
 
     #[tokio::test]
-    async fn execute_swap_updates_state() {
+    async fn positions_are_referenced_by_id() {
         let service = make_service();
-        let (config, tok_a, _) = make_config();
+        let (config, _, _) = make_config();
+        let Ok(pool_id) = service.create_pool(&config, "constant_product", 30).await else {
+            panic!("pool creation failed");
+        };
+        let mut rx = service.event_bus().subscribe();
 
+        let Ok((position_id, minted)) = service
+            .add_liquidity(
+                pool_id,
+                Amount::new(1_000),
+                Amount::new(1_000),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+        else {
+            panic!("add liquidity failed");
+        };
+        let Ok(PoolEvent::LiquidityChanged {
+            position_id: Some(emitted),
+            ..
+        }) = rx.recv().await
+        else {
+            panic!("expected LiquidityChanged with a position");
+        };
+        assert_eq!(emitted, position_id);
+
+        let Ok((topped_up, second)) = service
+            .add_liquidity(
+                pool_id,
+                Amount::new(1_000),
+                Amount::new(1_000),
+                Some(position_id),
+                None,
+                None,
+                None,
+            )
+            .await
+        else {
+            panic!("top-up failed");
+        };
+        assert_eq!(topped_up, position_id);
+
+        let Ok(_) = service
+            .remove_liquidity(
+                pool_id,
+                Some(position_id),
+                Liquidity::new(minted.get()),
+                None,
+                None,
+            )
+            .await
+        else {
+            panic!("remove failed");
+        };
+        let Ok(position) = service.get_position(pool_id, position_id).await else {
+            panic!("position missing");
+        };
+        assert_eq!(position.liquidity, second.get());
+        assert!(matches!(
+            service.collect_fees(pool_id, PositionId::new(), None).await,
+            Err(GatewayError::PositionNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn closed_positions_stay_listed_for_their_owner() {
+        let service = make_service();
+        let (config, _, _) = make_config();
         let Ok(pool_id) = service.create_pool(&config, "constant_product", 30).await else {
             panic!("pool creation failed");
         };
+        let open = |owner| {
+            service.add_liquidity(
+                pool_id,
+                Amount::new(1_000),
+                Amount::new(1_000),
+                None,
+                None,
+                None,
+                owner,
+            )
+        };
+        let (Ok((mine, minted)), Ok(_)) = (open(Some("desk-1")).await, open(None).await) else {
+            panic!("add liquidity failed");
+        };
 
-        let Ok(spec) = SwapSpec::exact_in(Amount::new(1000)) else {
-            panic!("invalid spec");
+        let Ok(closed) = service.close_position(pool_id, mine, Some("desk-1")).await else {
+            panic!("close failed");
         };
+        assert_eq!(closed.liquidity_burned, minted.get());
+        let Ok(listed) = service.list_positions(pool_id, Some("desk-1")).await else {
+            panic!("list failed");
+        };
+        let [(id, position)] = listed.as_slice() else {
+            panic!("expected one position for desk-1");
+        };
+        assert_eq!(*id, mine);
+        assert!(!position.is_open());
+        assert_eq!(
+            service
+                .list_positions(pool_id, None)
+                .await
+                .map(|p| p.len())
+                .ok(),
+            Some(2)
+        );
+    }
 
-        let result = service.execute_swap(pool_id, spec, tok_a, "cmd-1").await;
-        assert!(result.is_ok());
+    #[tokio::test]
+    async fn positions_are_found_by_owner_and_range() {
+        let service = make_service();
+        let (config, _, _) = make_config();
+        let Ok(pool_id) = service.create_pool(&config, "constant_product", 30).await else {
+            panic!("pool creation failed");
+        };
+        let open = || {
+            service.add_liquidity(
+                pool_id,
+                Amount::new(1_000),
+                Amount::new(1_000),
+                None,
+                None,
+                None,
+                Some("desk-1"),
+            )
+        };
+        let Ok((first, _)) = open().await else {
+            panic!("add liquidity failed");
+        };
 
-        let entry_lock = service.registry().get(pool_id).await;
-        let Ok(entry_lock) = entry_lock else {
-            panic!("pool not found");
+        let find = |owner| service.find_position(pool_id, TickRange::FULL, owner);
+        assert!(matches!(find(Some("desk-1")).await, Ok(id) if id == first));
+        assert!(matches!(
+            find(Some("desk-2")).await,
+            Err(GatewayError::PositionNotFound(_))
+        ));
+        assert!(matches!(
+            service
+                .find_position(
+                    pool_id,
+                    TickRange {
+                        lower: -60,
+                        upper: 60
+                    },
+                    Some("desk-1")
+                )
+                .await,
+            Err(GatewayError::PositionNotFound(_))
+        ));
+
+        let Ok(_) = open().await else {
+            panic!("add liquidity failed");
         };
-        let entry = entry_lock.read().await;
-        assert_eq!(entry.swap_count, 1);
-        assert!(entry.total_volume > 0);
+        assert!(matches!(
+            find(Some("desk-1")).await,
+            Err(GatewayError::InvalidRequest(_))
+        ));
     }
 
     #[tokio::test]
-    async fn quote_swap_does_not_mutate() {
+    async fn closed_positions_are_not_found_by_range() {
         let service = make_service();
-        let (config, tok_a, _) = make_config();
+        let (config, _, _) = make_config();
+        let Ok(pool_id) = service.create_pool(&config, "constant_product", 30).await else {
+            panic!("pool creation failed");
+        };
+        let open = || {
+            service.add_liquidity(
+                pool_id,
+                Amount::new(1_000),
+                Amount::new(1_000),
+                None,
+                None,
+                None,
+                Some("desk-1"),
+            )
+        };
+        let find = || service.find_position(pool_id, TickRange::FULL, Some("desk-1"));
+        let Ok((first, _)) = open().await else {
+            panic!("add liquidity failed");
+        };
+        let Ok(_) = service.close_position(pool_id, first, Some("desk-1")).await else {
+            panic!("close failed");
+        };
+        assert!(matches!(
+            find().await,
+            Err(GatewayError::PositionNotFound(_))
+        ));
+
+        let Ok((reopened, _)) = open().await else {
+            panic!("add liquidity failed");
+        };
+        assert!(matches!(find().await, Ok(id) if id == reopened));
+    }
 
+    #[tokio::test]
+    async fn owned_positions_are_withdrawn_only_by_their_provider() {
+        let service = make_service();
+        let (config, _, _) = make_config();
         let Ok(pool_id) = service.create_pool(&config, "constant_product", 30).await else {
             panic!("pool creation failed");
         };
+        let Ok((position_id, minted)) = service
+            .add_liquidity(
+                pool_id,
+                Amount::new(1_000),
+                Amount::new(1_000),
+                None,
+                None,
+                Some("fund-1"),
+                Some("desk-1"),
+            )
+            .await
+        else {
+            panic!("add liquidity failed");
+        };
+        let Ok((ledger, _)) = service.lp_holdings(pool_id).await else {
+            panic!("holdings missing");
+        };
+        assert_eq!(ledger.balance("fund-1"), minted.get());
+        assert_eq!(ledger.balance("desk-1"), 0, "the depositor is not credited");
+
+        let withdraw = |liquidity: u128, actor| {
+            service.remove_liquidity(
+                pool_id,
+                Some(position_id),
+                Liquidity::new(liquidity),
+                None,
+                actor,
+            )
+        };
+        assert!(matches!(
+            withdraw(1, Some("desk-1")).await,
+            Err(GatewayError::Forbidden(_))
+        ));
+        assert!(matches!(
+            withdraw(1, None).await,
+            Err(GatewayError::Forbidden(_))
+        ));
+        for actor in [Some("desk-1"), None] {
+            assert!(matches!(
+                service.collect_fees(pool_id, position_id, actor).await,
+                Err(GatewayError::Forbidden(_))
+            ));
+        }
+        assert!(
+            service
+                .collect_fees(pool_id, position_id, Some("fund-1"))
+                .await
+                .is_ok()
+        );
+        assert!(matches!(
+            withdraw(minted.get() + 1, Some("fund-1")).await,
+            Err(GatewayError::InsufficientLiquidity)
+        ));
+        assert!(withdraw(minted.get(), Some("fund-1")).await.is_ok());
+        let Ok((ledger, _)) = service.lp_holdings(pool_id).await else {
+            panic!("holdings missing");
+        };
+        assert!(ledger.is_empty());
+    }
 
-        let Ok(spec) = SwapSpec::exact_in(Amount::new(1000)) else {
-            panic!("invalid spec");
+    #[test]
+    fn price_convention_selects_reported_side() {
+        let config = |convention: serde_json::Value| {
+            serde_json::json!({
+                "token_a": {"address": "0xaaa", "decimals": 6},
+                "token_b": {"address": "0xbbb", "decimals": 6},
+                "fee_bps": 30,
+                "reserve_a": "1000000",
+                "reserve_b": "4000000",
+                "price_convention": convention,
+            })
         };
+        let Ok(default) = build_entry(
+            PoolId::new(),
+            "constant_product",
+            config(serde_json::json!({})),
+        ) else {
+            panic!("entry build failed");
+        };
+        let Ok(inverted) = build_entry(
+            PoolId::new(),
+            "constant_product",
+            config(serde_json::json!({"base": "0xBBB", "quote": "0xaaa"})),
+        ) else {
+            panic!("entry build failed");
+        };
+        assert_eq!(inverted.price_base, TokenSide::Second);
+        let (Some(forward), Some(inverse)) = (default.spot_price(), inverted.spot_price()) else {
+            panic!("pools should price");
+        };
+        assert!((forward * inverse - 1.0).abs() < 1e-9);
+
+        let same = build_entry(
+            PoolId::new(),
+            "constant_product",
+            config(serde_json::json!({"base": "0xaaa", "quote": "0xaaa"})),
+        );
+        assert!(matches!(same, Err(GatewayError::InvalidRequest(_))));
+        let foreign = build_entry(
+            PoolId::new(),
+            "constant_product",
+            config(serde_json::json!({"base": "0xccc"})),
+        );
+        assert!(matches!(foreign, Err(GatewayError::InvalidRequest(_))));
+    }
 
-        let result = service.quote_swap(pool_id, spec, tok_a).await;
-        assert!(result.is_ok());
+    #[test]
+    fn initial_price_derives_missing_reserve() {
+        let config = |extra: serde_json::Value| {
+            let mut config = serde_json::json!({
+                "token_a": {"address": "0xaaa", "decimals": 6},
+                "token_b": {"address": "0xbbb", "decimals": 6},
+                "fee_bps": 30,
+                "reserve_a": "1000000",
+            });
+            if let (Some(fields), Some(extra)) = (config.as_object_mut(), extra.as_object()) {
+                fields.extend(extra.clone());
+            }
+            config
+        };
+        let Ok(entry) = build_entry(
+            PoolId::new(),
+            "constant_product",
+            config(serde_json::json!({"initial_price": "2.5"})),
+        ) else {
+            panic!("entry build failed");
+        };
+        assert_eq!(entry.reserves, Some(vec![1_000_000, 2_500_000]));
+        assert!(entry.config_json.get("initial_price").is_none());
+        assert!(entry.spot_price().is_some_and(|p| (p - 2.5).abs() < 1e-9));
+
+        // Quoted per the price convention: 4 A per B means 0.25 B per A.
+        let Ok(inverted) = build_entry(
+            PoolId::new(),
+            "constant_product",
+            config(serde_json::json!({
+                "initial_price": 4,
+                "price_convention": {"base": "0xbbb"},
+            })),
+        ) else {
+            panic!("entry build failed");
+        };
+        assert_eq!(inverted.reserves, Some(vec![1_000_000, 250_000]));
+
+        let both = build_entry(
+            PoolId::new(),
+            "constant_product",
+            config(serde_json::json!({"initial_price": 2, "reserve_b": "5"})),
+        );
+        assert!(matches!(both, Err(GatewayError::InvalidRequest(_))));
+    }
 
-        let entry_lock = service.registry().get(pool_id).await;
-        let Ok(entry_lock) = entry_lock else {
-            panic!("pool not found");
+    #[test]
+    fn reserve_units_normalize_and_skewed_reserves_are_rejected() {
+        let config = |reserve_a: serde_json::Value, extra: serde_json::Value| {
+            let mut config = serde_json::json!({
+                "token_a": {"address": "0xaaa", "decimals": 18},
+                "token_b": {"address": "0xbbb", "decimals": 6},
+                "fee_bps": 30,
+                "reserve_a": reserve_a,
+                "reserve_b": "2500000000",
+            });
+            if let (Some(fields), Some(extra)) = (config.as_object_mut(), extra.as_object()) {
+                fields.extend(extra.clone());
+            }
+            config
         };
-        let entry = entry_lock.read().await;
-        assert_eq!(entry.swap_count, 0);
+        let Ok(human) = build_entry(
+            PoolId::new(),
+            "constant_product",
+            config(
+                serde_json::json!("1.5"),
+                serde_json::json!({"reserve_units": "human"}),
+            ),
+        ) else {
+            panic!("entry build failed");
+        };
+        assert_eq!(
+            human.reserves,
+            Some(vec![1_500_000_000_000_000_000, 2_500_000_000_000_000])
+        );
+        assert!(human.config_json.get("reserve_units").is_none());
+
+        // Auto reads "2" as whole tokens but keeps reserve_b raw.
+        let Ok(auto) = build_entry(
+            PoolId::new(),
+            "constant_product",
+            config(
+                serde_json::json!(2),
+                serde_json::json!({"reserve_units": "auto"}),
+            ),
+        ) else {
+            panic!("entry build failed");
+        };
+        assert_eq!(
+            auto.reserves,
+            Some(vec![2_000_000_000_000_000_000, 2_500_000_000])
+        );
+
+        let unmarked = build_entry(
+            PoolId::new(),
+            "constant_product",
+            config(serde_json::json!("1.5"), serde_json::json!({})),
+        );
+        assert!(matches!(unmarked, Err(GatewayError::InvalidRequest(_))));
+
+        // 1 wei against 2,500 whole tokens.
+        let issues = validate_config(
+            "constant_product",
+            config(serde_json::json!("1"), serde_json::json!({})),
+        );
+        assert!(
+            issues
+                .iter()
+                .any(|i| i.message.contains("orders of magnitude"))
+        );
+    }
+
+    #[test]
+    fn validate_config_aggregates_field_errors() {
+        let issues = validate_config(
+            "constant_product",
+            serde_json::json!({
+                "token_a": {"address": "0xaaa"},
+                "fee_bps": 30,
+                "reserve_a": "lots",
+            }),
+        );
+        let fields: Vec<&str> = issues.iter().map(|i| i.field.as_str()).collect();
+        assert_eq!(fields, ["token_a", "token_b", "reserve_a", "reserve_b"]);
+
+        let valid = serde_json::json!({
+            "token_a": {"address": "0xaaa", "decimals": 6},
+            "token_b": {"address": "0xbbb", "decimals": 18},
+            "fee_bps": 30,
+            "reserve_a": "1000000",
+            "reserve_b": "1000000",
+        });
+        assert!(validate_config("constant_product", valid.clone()).is_empty());
+        let unknown = validate_config("bonding_curve", valid);
+        assert_eq!(unknown.len(), 1);
+        assert!(unknown.iter().all(|i| i.field == "pool_type"));
+    }
+
+    #[tokio::test]
+    async fn orders_rest_on_orderbook_pools_and_cancel() {
+        let service = make_service();
+        let config = serde_json::json!({
+            "token_a": {"address": "0xaaa", "decimals": 6},
+            "token_b": {"address": "0xbbb", "decimals": 6},
+            "fee_bps": 30,
+            "tick_size": "10",
+            "lot_size": "100",
+        });
+        let Ok(pool_id) = service
+            .create_pool_from_config(
+                PoolId::new(),
+                "orderbook",
+                config,
+                serde_json::Value::Null,
+                None,
+            )
+            .await
+        else {
+            panic!("pool creation failed");
+        };
+        let mut rx = service.event_bus().subscribe();
+
+        let Ok((bid, _)) = service
+            .place_order(pool_id, OrderSide::Bid, 990, 500, Some("alice"))
+            .await
+        else {
+            panic!("bid rejected");
+        };
+        let Ok(PoolEvent::OrderPlaced {
+            order_id, actor, ..
+        }) = rx.recv().await
+        else {
+            panic!("expected OrderPlaced");
+        };
+        assert_eq!((order_id, actor.as_deref()), (bid, Some("alice")));
+        assert!(
+            service
+                .place_order(pool_id, OrderSide::Ask, 995, 100, None)
+                .await
+                .is_err(),
+            "off-tick price"
+        );
+        assert!(
+            service
+                .place_order(pool_id, OrderSide::Ask, 980, 100, None)
+                .await
+                .is_err(),
+            "crossing ask"
+        );
+        let Ok(_) = service
+            .place_order(pool_id, OrderSide::Ask, 1010, 200, None)
+            .await
+        else {
+            panic!("ask rejected");
+        };
+        let Ok(depth) = service.order_book(pool_id, 10).await else {
+            panic!("book unavailable");
+        };
+        assert_eq!(depth.resting_orders, 2);
+        assert_eq!(depth.bids.first().map(|l| l.price), Some(990));
+
+        let Ok(cancelled) = service.cancel_order(pool_id, bid, Some("alice")).await else {
+            panic!("cancel failed");
+        };
+        assert_eq!(cancelled.quantity, 500);
+        assert!(matches!(
+            service.cancel_order(pool_id, bid, None).await,
+            Err(GatewayError::OrderNotFound(_))
+        ));
+        let Ok(depth) = service.order_book(pool_id, 10).await else {
+            panic!("book unavailable");
+        };
+        assert!(depth.bids.is_empty());
     }
 
     #[tokio::test]
@@ -494,4 +3403,30 @@ mod tests {
         };
         assert_eq!(event.event_type_str(), "pool_removed");
     }
+
+    #[tokio::test]
+    async fn removal_waits_for_in_flight_holders_before_publishing() {
+        let service = make_service();
+        let (config, _, _) = make_config();
+        let Ok(pool_id) = service.create_pool(&config, "constant_product", 30).await else {
+            panic!("pool creation failed");
+        };
+        let mut rx = service.event_bus().subscribe();
+        let Ok(in_flight) = service.registry().get(pool_id).await else {
+            panic!("pool not found");
+        };
+
+        assert!(service.remove_pool(pool_id).await.is_ok());
+        assert!(matches!(
+            service.write_entry(pool_id, &in_flight, "swap").await,
+            Err(GatewayError::PoolNotFound(_))
+        ));
+        assert!(rx.try_recv().is_err(), "published before reaping");
+
+        drop(in_flight);
+        let Ok(Ok(event)) = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await else {
+            panic!("expected PoolRemoved after reaping");
+        };
+        assert_eq!(event.event_type_str(), "pool_removed");
+    }
 }
@@ -0,0 +1,424 @@
+//! Rolling TVL and activity statistics per pool.
+//!
+//! [`PoolStatsBook`] folds every `swap_executed` event into hourly UTC
+//! buckets per pool, keeping [`WINDOW_DAYS`] of them, and answers
+//! trailing 24-hour and 7-day totals. Volumes and fees are in raw units
+//! of the pool's quote token per its price convention: volume is the
+//! quote side of each trade, and fees are taken net of rebates and
+//! converted from the input token at the trade's execution price.
+//! Windows are whole hours ending with the current one, so a 24-hour
+//! figure covers between 23 and 24 hours. Anonymous swaps count toward
+//! volume but not toward unique traders.
+//!
+//! The book can be backfilled from the persisted `swap_executed` events
+//! of the window at startup, and from the hourly swap buckets of
+//! compaction checkpoints; imported history is left out.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use chrono::{DateTime, Duration, Utc};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use super::PoolService;
+use super::candles::Timeframe;
+use super::pricing::{self, TradeDecimals};
+use super::trade_tape::Trade;
+use crate::domain::pool_entry::PoolEntry;
+use crate::domain::pool_operation::TokenSide;
+use crate::domain::{PoolEvent, PoolId, PoolRegistry};
+use crate::persistence::compaction::EventCheckpoint;
+use crate::persistence::models::StoredEvent;
+
+/// Days of hourly buckets kept per pool.
+pub const WINDOW_DAYS: i64 = 7;
+
+/// Hours in the short window.
+const DAY_HOURS: i64 = 24;
+
+/// One swap as counted by the book.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapActivity {
+    /// Pool the swap executed on.
+    pub pool_id: PoolId,
+    /// Quote token traded, in raw units.
+    pub quote_volume: u128,
+    /// Fee net of rebates, in raw quote units.
+    pub quote_fee: u128,
+    /// Client that swapped, if known.
+    pub actor: Option<String>,
+    /// Execution time.
+    pub timestamp: DateTime<Utc>,
+}
+
+impl SwapActivity {
+    /// Builds the activity of a `swap_executed` event on `entry`'s pool.
+    ///
+    /// Returns `None` for other events, unparseable amounts, or an input
+    /// token that is not on the pool.
+    #[must_use]
+    pub fn from_event(event: &PoolEvent, entry: &PoolEntry) -> Option<Self> {
+        let trade = Trade::from_event(event, entry)?;
+        let PoolEvent::SwapExecuted {
+            fee,
+            fee_rebate,
+            token_in,
+            actor,
+            ..
+        } = event
+        else {
+            return None;
+        };
+        let fee: u128 = fee.parse().ok()?;
+        let net_fee = fee.saturating_sub(fee_rebate.parse().unwrap_or(0));
+        let quote_fee = if entry.side_of_label(token_in)? == entry.price_base {
+            (net_fee as f64 * trade.price) as u128
+        } else {
+            net_fee
+        };
+        Some(Self {
+            pool_id: trade.pool_id,
+            quote_volume: trade.quote_size,
+            quote_fee,
+            actor: actor.clone(),
+            timestamp: trade.timestamp,
+        })
+    }
+}
+
+/// Trailing activity of one pool.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoolActivity {
+    /// Quote volume over the last 24 hours, in raw units.
+    pub volume_24h: u128,
+    /// Quote volume over the last 7 days, in raw units.
+    pub volume_7d: u128,
+    /// Fees net of rebates over the last 24 hours, in raw quote units.
+    pub fees_24h: u128,
+    /// Swaps over the last 24 hours.
+    pub swaps_24h: u64,
+    /// Distinct clients that swapped over the last 24 hours.
+    pub unique_traders_24h: usize,
+}
+
+/// Activity of one pool in one UTC hour.
+#[derive(Debug, Default)]
+struct Hour {
+    volume: u128,
+    fees: u128,
+    swaps: u64,
+    traders: HashSet<String>,
+}
+
+/// Hourly activity of one pool by hour start.
+type Hours = BTreeMap<DateTime<Utc>, Hour>;
+
+/// Shared rolling statistics of every pool.
+#[derive(Debug, Clone, Default)]
+pub struct PoolStatsBook {
+    pools: Arc<Mutex<HashMap<PoolId, Hours>>>,
+}
+
+impl PoolStatsBook {
+    /// Creates an empty book. Call [`Self::spawn`] to start feeding it.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Folds `swap` into its pool's hour, dropping hours that fall out
+    /// of the window.
+    pub fn record(&self, swap: &SwapActivity) {
+        self.record_swaps(swap, 1);
+    }
+
+    /// Folds `swaps` swaps whose summed activity is `swap`.
+    fn record_swaps(&self, swap: &SwapActivity, swaps: u64) {
+        let hour = Timeframe::OneHour.bucket_start(swap.timestamp);
+        let mut pools = self.pools.lock().unwrap_or_else(PoisonError::into_inner);
+        let hours = pools.entry(swap.pool_id).or_default();
+        let bucket = hours.entry(hour).or_default();
+        bucket.volume = bucket.volume.saturating_add(swap.quote_volume);
+        bucket.fees = bucket.fees.saturating_add(swap.quote_fee);
+        bucket.swaps = bucket.swaps.saturating_add(swaps);
+        if let Some(actor) = &swap.actor {
+            bucket.traders.insert(actor.clone());
+        }
+        if let Some(newest) = hours.keys().next_back().copied() {
+            let cutoff = window_start(newest, WINDOW_DAYS * DAY_HOURS);
+            hours.retain(|start, _| *start >= cutoff);
+        }
+    }
+
+    /// Drops the statistics of `pool_id`.
+    pub fn forget(&self, pool_id: PoolId) {
+        self.pools
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&pool_id);
+    }
+
+    /// Returns the trailing activity of `pool_id` as of `now`.
+    #[must_use]
+    pub fn activity(&self, pool_id: PoolId, now: DateTime<Utc>) -> PoolActivity {
+        let current = Timeframe::OneHour.bucket_start(now);
+        let day_from = window_start(current, DAY_HOURS);
+        let week_from = window_start(current, WINDOW_DAYS * DAY_HOURS);
+        let pools = self.pools.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut activity = PoolActivity::default();
+        let Some(hours) = pools.get(&pool_id) else {
+            return activity;
+        };
+        let mut traders = HashSet::new();
+        for (start, hour) in hours.range(week_from..=current) {
+            activity.volume_7d = activity.volume_7d.saturating_add(hour.volume);
+            if *start >= day_from {
+                activity.volume_24h = activity.volume_24h.saturating_add(hour.volume);
+                activity.fees_24h = activity.fees_24h.saturating_add(hour.fees);
+                activity.swaps_24h = activity.swaps_24h.saturating_add(hour.swaps);
+                traders.extend(hour.traders.iter().map(String::as_str));
+            }
+        }
+        activity.unique_traders_24h = traders.len();
+        activity
+    }
+
+    /// Folds persisted `swap_executed` events into the book, skipping
+    /// imported ones and those of pools not in `registry`. Returns how
+    /// many were counted.
+    pub async fn backfill(&self, registry: &PoolRegistry, events: &[StoredEvent]) -> usize {
+        let mut counted = 0;
+        for stored in events.iter().filter(|e| !e.imported) {
+            let Ok(event) = stored.to_pool_event() else {
+                continue;
+            };
+            let Ok(entry) = registry.get(event.pool_id()).await else {
+                continue;
+            };
+            if let Some(swap) = SwapActivity::from_event(&event, &*entry.read().await) {
+                self.record(&swap);
+                counted += 1;
+            }
+        }
+        counted
+    }
+
+    /// Folds the swap buckets of compaction `checkpoints` from `since`
+    /// on into the book, skipping pools not in `registry`. A bucket's
+    /// fees are converted at its volume-weighted price. Returns how many
+    /// swaps were counted.
+    pub async fn backfill_checkpoints(
+        &self,
+        registry: &PoolRegistry,
+        checkpoints: &[EventCheckpoint],
+        since: DateTime<Utc>,
+    ) -> u64 {
+        let mut counted = 0;
+        for checkpoint in checkpoints.iter().filter(|c| c.to >= since) {
+            let pool_id = PoolId::from_uuid(checkpoint.pool_id);
+            let Ok(entry) = registry.get(pool_id).await else {
+                continue;
+            };
+            let entry = entry.read().await;
+            for bucket in checkpoint.summary.swaps.iter().filter(|b| b.hour >= since) {
+                let event = bucket.to_event(pool_id);
+                if let Some(swap) = SwapActivity::from_event(&event, &entry) {
+                    self.record_swaps(&swap, bucket.swaps);
+                    counted += bucket.swaps;
+                }
+            }
+        }
+        counted
+    }
+
+    /// Starts a task that records every swap published by
+    /// `pool_service` and forgets removed pools. The task ends when the
+    /// event bus closes.
+    pub fn spawn(&self, pool_service: Arc<PoolService>) -> JoinHandle<()> {
+        let book = self.clone();
+        let mut events = pool_service.event_bus().subscribe();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event @ PoolEvent::SwapExecuted { .. }) => {
+                        let Ok(entry) = pool_service.registry().get(event.pool_id()).await else {
+                            continue;
+                        };
+                        let swap = SwapActivity::from_event(&event, &*entry.read().await);
+                        if let Some(swap) = swap {
+                            book.record(&swap);
+                        }
+                    }
+                    Ok(PoolEvent::PoolRemoved { pool_id, .. }) => book.forget(pool_id),
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(lagged = n, "pool stats lagged behind event bus");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+/// Start of the first of `hours` whole hours ending with `current`.
+fn window_start(current: DateTime<Utc>, hours: i64) -> DateTime<Utc> {
+    current - Duration::hours(hours - 1)
+}
+
+/// Total value locked in `entry`, in raw units of its quote token: the
+/// quote reserve plus the base reserve at the spot price.
+///
+/// `None` for pools whose reserves the gateway does not track, pools
+/// holding other than two tokens, and pools that cannot price.
+#[must_use]
+pub fn tvl(entry: &PoolEntry) -> Option<u128> {
+    let reserves = entry.reserves.as_deref()?;
+    let [first, second] = reserves else {
+        return None;
+    };
+    let base = entry.price_base;
+    let (base_reserve, quote_reserve) = match base {
+        TokenSide::First => (*first, *second),
+        TokenSide::Second => (*second, *first),
+    };
+    let price = entry.spot_price()?;
+    let decimals = TradeDecimals {
+        input: entry.decimals_of(base),
+        output: entry.decimals_of(base.other()),
+    };
+    let base_value = pricing::amount_at_price(base_reserve, price, decimals);
+    Some(quote_reserve.saturating_add(base_value as u128))
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::pool_operation::SwapKind;
+    use crate::service::pool_service::build_entry;
+
+    #[test]
+    fn windows_sum_quote_volume_fees_and_traders() {
+        let Ok(entry) = build_entry(
+            PoolId::new(),
+            "constant_product",
+            serde_json::json!({
+                "token_a": {"address": "usdc", "decimals": 6},
+                "token_b": {"address": "weth", "decimals": 6},
+                "fee_bps": 30,
+                "reserve_a": "4000000",
+                "reserve_b": "1000000",
+                "price_convention": {"base": "weth"},
+            }),
+        ) else {
+            panic!("entry build failed");
+        };
+        let now = Utc::now();
+        let swap = |token_in: &str, amount_in: &str, amount_out: &str, actor: &str, hours_ago| {
+            PoolEvent::SwapExecuted {
+                pool_id: entry.pool_id,
+                command_id: "cmd".to_string(),
+                amount_in: amount_in.to_string(),
+                amount_out: amount_out.to_string(),
+                fee: "10".to_string(),
+                fee_rebate: "2".to_string(),
+                new_price: "4".to_string(),
+                price_change_bps: 0,
+                token_in: token_in.to_string(),
+                swap_kind: SwapKind::ExactIn,
+                actor: Some(actor.to_string()),
+                timestamp: now - Duration::hours(hours_ago),
+            }
+        };
+
+        let book = PoolStatsBook::new();
+        for event in [
+            swap("weth", "100", "400", "desk-1", 0),
+            swap("usdc", "800", "200", "desk-2", 1),
+            swap("usdc", "400", "100", "desk-1", 2),
+            swap("usdc", "1000", "250", "desk-3", 48),
+            swap("usdc", "5000", "1250", "desk-4", 24 * 8),
+        ] {
+            let Some(activity) = SwapActivity::from_event(&event, &entry) else {
+                panic!("expected a swap");
+            };
+            book.record(&activity);
+        }
+
+        let activity = book.activity(entry.pool_id, now);
+        assert_eq!(activity.swaps_24h, 3);
+        assert_eq!(activity.volume_24h, 1_600);
+        assert_eq!(activity.volume_7d, 2_600);
+        // 8 weth at 4 usdc, then 8 usdc twice
+        assert_eq!(activity.fees_24h, 32 + 8 + 8);
+        assert_eq!(activity.unique_traders_24h, 2);
+        assert_eq!(book.activity(PoolId::new(), now), PoolActivity::default());
+
+        assert_eq!(tvl(&entry), Some(4_000_000 + 4_000_000));
+        book.forget(entry.pool_id);
+        assert_eq!(book.activity(entry.pool_id, now).swaps_24h, 0);
+    }
+
+    #[tokio::test]
+    async fn checkpoint_buckets_count_their_swaps() {
+        let Ok(entry) = build_entry(
+            PoolId::new(),
+            "constant_product",
+            serde_json::json!({
+                "token_a": {"address": "usdc", "decimals": 6},
+                "token_b": {"address": "weth", "decimals": 6},
+                "fee_bps": 30,
+                "reserve_a": "4000000",
+                "reserve_b": "1000000",
+                "price_convention": {"base": "weth"},
+            }),
+        ) else {
+            panic!("entry build failed");
+        };
+        let pool_id = entry.pool_id;
+        let registry = PoolRegistry::new();
+        let _ = registry.insert(entry).await;
+
+        let now = Utc::now();
+        let hour = Timeframe::OneHour.bucket_start(now);
+        let bucket = |hour, actor: &str| crate::persistence::compaction::SwapBucket {
+            hour,
+            token_in: "weth".to_string(),
+            actor: Some(actor.to_string()),
+            swaps: 3,
+            amount_in: "100".to_string(),
+            amount_out: "400".to_string(),
+            fee: "10".to_string(),
+            fee_rebate: "2".to_string(),
+        };
+        let checkpoint = EventCheckpoint {
+            id: 1,
+            pool_id: *pool_id.as_uuid(),
+            from: hour - Duration::days(30),
+            to: hour,
+            summary: crate::persistence::compaction::CompactionSummary {
+                swaps: vec![
+                    bucket(hour - Duration::days(30), "desk-1"),
+                    bucket(hour, "desk-2"),
+                ],
+                ..Default::default()
+            },
+        };
+
+        let book = PoolStatsBook::new();
+        let since = now - Duration::days(WINDOW_DAYS);
+        let counted = book
+            .backfill_checkpoints(&registry, &[checkpoint], since)
+            .await;
+        assert_eq!(counted, 3, "buckets before the window are left out");
+
+        let activity = book.activity(pool_id, now);
+        assert_eq!(activity.swaps_24h, 3);
+        assert_eq!(activity.volume_24h, 400);
+        // 8 weth of net fees at the bucket's 4 usdc price
+        assert_eq!(activity.fees_24h, 32);
+        assert_eq!(activity.unique_traders_24h, 1);
+    }
+}
@@ -0,0 +1,113 @@
+//! Decimals-aware price math shared by swaps, quotes, routing, and market
+//! data.
+//!
+//! hydra-amm quotes spot prices per whole token, while swap amounts are
+//! raw integers in each token's smallest unit. A raw `amount_out /
+//! amount_in` ratio is therefore off from spot by a factor of
+//! `10^(decimals_out - decimals_in)` whenever the two tokens' decimals
+//! differ; everything here converts to whole tokens first.
+//!
+//! Price impact compares the execution price against the pre-trade mid
+//! price (the spot price in the trade's direction). The fee is taken out
+//! of the input because it is reported separately, so the impact measures
+//! only how far the trade moved along the curve. Both are computed from
+//! the amounts actually exchanged, so exact-in and exact-out swaps are
+//! measured the same way. Negative impact means less output than the mid
+//! price would give.
+
+/// Decimals of a trade's input and output tokens. Unknown decimals are
+/// treated as equal to the other side's.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TradeDecimals {
+    /// Decimals of the input token.
+    pub input: Option<u8>,
+    /// Decimals of the output token.
+    pub output: Option<u8>,
+}
+
+impl TradeDecimals {
+    /// Multiplier converting a raw `out / in` ratio into whole output
+    /// tokens per whole input token.
+    #[must_use]
+    pub fn scale(self) -> f64 {
+        match (self.input, self.output) {
+            (Some(input), Some(output)) => 10f64.powi(i32::from(input) - i32::from(output)),
+            _ => 1.0,
+        }
+    }
+}
+
+/// Whole output tokens received per whole input token, or `None` for an
+/// empty input.
+#[must_use]
+pub fn execution_price(amount_in: u128, amount_out: u128, decimals: TradeDecimals) -> Option<f64> {
+    (amount_in > 0).then(|| amount_out as f64 / amount_in as f64 * decimals.scale())
+}
+
+/// Raw output that `amount_in` buys at `price` (whole output tokens per
+/// whole input token).
+#[must_use]
+pub fn amount_at_price(amount_in: u128, price: f64, decimals: TradeDecimals) -> f64 {
+    amount_in as f64 * price / decimals.scale()
+}
+
+/// Impact of a fill against the pre-trade `mid_price`, in basis points,
+/// with `fee` (in input units) excluded. Zero when the pool could not
+/// price before the trade.
+#[must_use]
+pub fn price_impact_bps(
+    mid_price: Option<f64>,
+    amount_in: u128,
+    fee: u128,
+    amount_out: u128,
+    decimals: TradeDecimals,
+) -> i32 {
+    let net_in = amount_in.saturating_sub(fee);
+    match (mid_price, execution_price(net_in, amount_out, decimals)) {
+        (Some(mid), Some(execution)) if mid > 0.0 => ratio_bps(execution, mid),
+        _ => 0,
+    }
+}
+
+/// Relative difference of `value` from `reference` in basis points,
+/// rounded to the nearest basis point.
+#[must_use]
+pub fn ratio_bps(value: f64, reference: f64) -> i32 {
+    #[allow(clippy::cast_possible_truncation)]
+    let bps = ((value - reference) / reference * 10_000.0).round() as i32;
+    bps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impact_is_measured_in_whole_tokens() {
+        // 1 whole token A (6 decimals) buys 2 whole token B (18 decimals)
+        // at mid; the fill returns 1% less after a 0.3% fee.
+        let decimals = TradeDecimals {
+            input: Some(6),
+            output: Some(18),
+        };
+        let amount_in = 1_000_000_u128;
+        let fee = 3_000_u128;
+        let amount_out = 1_974_060_000_000_000_000_u128;
+        assert_eq!(
+            price_impact_bps(Some(2.0), amount_in, fee, amount_out, decimals),
+            -100
+        );
+        assert!(
+            execution_price(amount_in, amount_out, decimals)
+                .is_some_and(|p| (p - 1.97406).abs() < 1e-9)
+        );
+        assert!((amount_at_price(amount_in, 2.0, decimals) - 2e18).abs() < 1.0);
+
+        // Without decimals the raw ratio is used, as before.
+        assert_eq!(
+            price_impact_bps(Some(1.0), 1_000, 0, 990, TradeDecimals::default()),
+            -100
+        );
+        assert_eq!(price_impact_bps(None, 1_000, 0, 990, decimals), 0);
+    }
+}
@@ -0,0 +1,540 @@
+//! Per-API-key request, swap, WebSocket, and pool creation quotas.
+//!
+//! The API key is the caller's `X-Client-Id`: the gateway trusts the
+//! authenticating proxy in front of it to set that header. Keys without a
+//! configured quota are unlimited. Requests, swaps, and pool creations are
+//! counted in fixed UTC windows (the current minute and the current day);
+//! WebSocket connections are counted while they stay open, and pools
+//! created while the key has a quota are counted until they are removed.
+//!
+//! Counters are per instance. In a cluster each instance enforces the
+//! full quota on the traffic it serves.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::domain::{EventBus, PoolEvent, PoolId};
+use crate::error::GatewayError;
+use crate::persistence::models::RateLimitRecord;
+
+/// Suggested retry delay when a key is at its WebSocket connection limit.
+const WS_RETRY_AFTER_MS: u64 = 1_000;
+
+/// Limits for one API key; `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuotaLimits {
+    /// Requests per UTC minute.
+    pub requests_per_minute: Option<u32>,
+    /// Swap executions per UTC day.
+    pub swaps_per_day: Option<u32>,
+    /// Concurrently open WebSocket connections.
+    pub ws_connections: Option<u32>,
+    /// Live pools created by the key.
+    pub max_pools: Option<u32>,
+    /// Pool creations per UTC day.
+    pub pool_creations_per_day: Option<u32>,
+}
+
+impl From<&RateLimitRecord> for QuotaLimits {
+    fn from(record: &RateLimitRecord) -> Self {
+        Self {
+            requests_per_minute: record.requests_per_minute,
+            swaps_per_day: record.swaps_per_day,
+            ws_connections: record.ws_connections,
+            max_pools: record.max_pools,
+            pool_creations_per_day: record.pool_creations_per_day,
+        }
+    }
+}
+
+/// Current usage of one API key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuotaUsage {
+    /// Requests admitted in the current minute.
+    pub requests_this_minute: u32,
+    /// Swaps admitted in the current day.
+    pub swaps_today: u32,
+    /// Open WebSocket connections.
+    pub ws_connections: u32,
+    /// Live pools created by the key.
+    pub pools: u32,
+    /// Pool creations admitted in the current day.
+    pub pool_creations_today: u32,
+}
+
+/// Limits and usage of one API key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyQuota {
+    /// API key (client id).
+    pub api_key: String,
+    /// Configured limits.
+    pub limits: QuotaLimits,
+    /// Usage at the time of the report.
+    pub usage: QuotaUsage,
+}
+
+/// Counter over a fixed window identified by its index.
+#[derive(Debug, Default)]
+struct Window {
+    index: i64,
+    count: u32,
+}
+
+impl Window {
+    /// Returns the count in window `index`.
+    fn count_in(&self, index: i64) -> u32 {
+        if self.index == index { self.count } else { 0 }
+    }
+
+    /// Admits one unit if window `index` is below `limit`, starting a
+    /// fresh count when the window has rolled over.
+    fn admit(&mut self, index: i64, limit: Option<u32>) -> bool {
+        let count = self.count_in(index);
+        if limit.is_some_and(|max| count >= max) {
+            return false;
+        }
+        self.index = index;
+        self.count = count.saturating_add(1);
+        true
+    }
+
+    /// Gives back one unit admitted in window `index`.
+    fn release(&mut self, index: i64) {
+        if self.index == index {
+            self.count = self.count.saturating_sub(1);
+        }
+    }
+}
+
+/// Limits and counters of one key.
+#[derive(Debug, Default)]
+struct KeyState {
+    limits: QuotaLimits,
+    requests: Window,
+    swaps: Window,
+    ws_open: u32,
+    pool_creations: Window,
+    /// Live pools created while the key had a quota.
+    pools: HashSet<PoolId>,
+    /// Creations admitted but not yet completed.
+    pools_pending: u32,
+}
+
+impl KeyState {
+    fn pools_in_use(&self) -> u32 {
+        u32::try_from(self.pools.len())
+            .unwrap_or(u32::MAX)
+            .saturating_add(self.pools_pending)
+    }
+}
+
+/// Shared quota table.
+#[derive(Debug, Clone, Default)]
+pub struct QuotaRegistry {
+    keys: Arc<Mutex<HashMap<String, KeyState>>>,
+}
+
+impl QuotaRegistry {
+    /// Creates an empty registry: every key is unlimited.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Installs persisted quotas, replacing the limits of the keys they
+    /// name.
+    pub fn load(&self, records: &[RateLimitRecord]) {
+        for record in records {
+            self.set_limits(&record.api_key, record.into());
+        }
+    }
+
+    /// Sets the limits of `api_key`, keeping its current usage.
+    pub fn set_limits(&self, api_key: &str, limits: QuotaLimits) {
+        let mut keys = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        keys.entry(api_key.to_string()).or_default().limits = limits;
+    }
+
+    /// Returns limits and usage of every configured key, ordered by key.
+    #[must_use]
+    pub fn report(&self, now: DateTime<Utc>) -> Vec<KeyQuota> {
+        let keys = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut report: Vec<KeyQuota> = keys
+            .iter()
+            .map(|(api_key, state)| key_quota(api_key, state, now))
+            .collect();
+        report.sort_by(|a, b| a.api_key.cmp(&b.api_key));
+        report
+    }
+
+    /// Returns limits and usage of `api_key`, if it has a quota.
+    #[must_use]
+    pub fn report_for(&self, api_key: &str, now: DateTime<Utc>) -> Option<KeyQuota> {
+        let keys = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        keys.get(api_key)
+            .map(|state| key_quota(api_key, state, now))
+    }
+
+    /// Counts one request by `api_key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::RateLimited`] if the key has used up its
+    /// requests for the current minute.
+    pub fn check_request(
+        &self,
+        api_key: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<(), GatewayError> {
+        self.admit(api_key, now, 60, |state| {
+            (&mut state.requests, state.limits.requests_per_minute)
+        })
+    }
+
+    /// Counts one swap execution by `api_key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::RateLimited`] if the key has used up its
+    /// swaps for the current day.
+    pub fn check_swap(
+        &self,
+        api_key: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<(), GatewayError> {
+        self.admit(api_key, now, 86_400, |state| {
+            (&mut state.swaps, state.limits.swaps_per_day)
+        })
+    }
+
+    /// Registers an open WebSocket connection for `api_key`. The
+    /// connection is counted until the returned permit is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::RateLimited`] if the key already has its
+    /// maximum number of connections open.
+    pub fn open_ws(&self, api_key: Option<&str>) -> Result<WsPermit, GatewayError> {
+        let Some(api_key) = api_key else {
+            return Ok(WsPermit::default());
+        };
+        let mut keys = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(state) = keys.get_mut(api_key) else {
+            return Ok(WsPermit::default());
+        };
+        if state
+            .limits
+            .ws_connections
+            .is_some_and(|max| state.ws_open >= max)
+        {
+            return Err(GatewayError::RateLimited {
+                retry_after_ms: WS_RETRY_AFTER_MS,
+            });
+        }
+        state.ws_open = state.ws_open.saturating_add(1);
+        Ok(WsPermit {
+            held: Some((self.clone(), api_key.to_string())),
+        })
+    }
+
+    /// Admits one pool creation by `api_key`. The creation is counted
+    /// against both pool quotas until the returned permit is dropped,
+    /// unless it is [committed](PoolCreationPermit::commit) with the
+    /// created pool.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::PoolQuotaExceeded`] if the key already has
+    /// its maximum number of live pools or has used up its creations for
+    /// the current day.
+    pub fn reserve_pool_creation(
+        &self,
+        api_key: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<PoolCreationPermit, GatewayError> {
+        let Some(api_key) = api_key else {
+            return Ok(PoolCreationPermit::default());
+        };
+        let mut keys = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(state) = keys.get_mut(api_key) else {
+            return Ok(PoolCreationPermit::default());
+        };
+        if let Some(max) = state.limits.max_pools
+            && state.pools_in_use() >= max
+        {
+            return Err(GatewayError::PoolQuotaExceeded(format!(
+                "{api_key} may hold {max} pools; remove one first"
+            )));
+        }
+        let day = window_index(now, 86_400);
+        if !state
+            .pool_creations
+            .admit(day, state.limits.pool_creations_per_day)
+        {
+            let elapsed_ms = now.timestamp_millis().rem_euclid(86_400_000);
+            return Err(GatewayError::PoolQuotaExceeded(format!(
+                "{api_key} may create {} pools per day; retry after {} ms",
+                state.limits.pool_creations_per_day.unwrap_or_default(),
+                86_400_000 - elapsed_ms
+            )));
+        }
+        state.pools_pending = state.pools_pending.saturating_add(1);
+        Ok(PoolCreationPermit {
+            held: Some((self.clone(), api_key.to_string(), day)),
+        })
+    }
+
+    /// Stops counting `pool_id` against the key that created it.
+    pub fn forget_pool(&self, pool_id: PoolId) {
+        let mut keys = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        for state in keys.values_mut() {
+            if state.pools.remove(&pool_id) {
+                break;
+            }
+        }
+    }
+
+    /// Starts a task that stops counting pools once they are removed,
+    /// however the removal happened. The task ends when the event bus
+    /// closes.
+    pub fn track_pool_removals(&self, event_bus: &EventBus) -> JoinHandle<()> {
+        let mut events = event_bus.subscribe();
+        let quotas = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(PoolEvent::PoolRemoved { pool_id, .. }) => quotas.forget_pool(pool_id),
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(lagged = n, "pool quota tracker lagged behind event bus");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Settles a pool creation reserved in window `day`: counts `pool_id`
+    /// as live, or gives the reservation back when the creation failed.
+    fn settle_pool_creation(&self, api_key: &str, day: i64, pool_id: Option<PoolId>) {
+        let mut keys = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(state) = keys.get_mut(api_key) {
+            state.pools_pending = state.pools_pending.saturating_sub(1);
+            match pool_id {
+                Some(pool_id) => {
+                    state.pools.insert(pool_id);
+                }
+                None => state.pool_creations.release(day),
+            }
+        }
+    }
+
+    /// Admits one unit against the window selected by `select`, whose
+    /// length is `window_secs`.
+    fn admit(
+        &self,
+        api_key: Option<&str>,
+        now: DateTime<Utc>,
+        window_secs: i64,
+        select: impl FnOnce(&mut KeyState) -> (&mut Window, Option<u32>),
+    ) -> Result<(), GatewayError> {
+        let Some(api_key) = api_key else {
+            return Ok(());
+        };
+        let mut keys = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(state) = keys.get_mut(api_key) else {
+            return Ok(());
+        };
+        let (window, limit) = select(state);
+        if window.admit(window_index(now, window_secs), limit) {
+            return Ok(());
+        }
+        let elapsed_ms = now.timestamp_millis().rem_euclid(window_secs * 1_000);
+        Err(GatewayError::RateLimited {
+            retry_after_ms: u64::try_from(window_secs * 1_000 - elapsed_ms).unwrap_or(0),
+        })
+    }
+
+    /// Releases a WebSocket connection slot.
+    fn close_ws(&self, api_key: &str) {
+        let mut keys = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(state) = keys.get_mut(api_key) {
+            state.ws_open = state.ws_open.saturating_sub(1);
+        }
+    }
+}
+
+/// Keeps a WebSocket connection counted against its key while alive.
+#[derive(Debug, Default)]
+pub struct WsPermit {
+    held: Option<(QuotaRegistry, String)>,
+}
+
+impl Drop for WsPermit {
+    fn drop(&mut self) {
+        if let Some((registry, api_key)) = self.held.take() {
+            registry.close_ws(&api_key);
+        }
+    }
+}
+
+/// Holds a pool creation reserved against its key's quotas. Dropping it
+/// without [`Self::commit`] gives the reservation back.
+#[derive(Debug, Default)]
+pub struct PoolCreationPermit {
+    held: Option<(QuotaRegistry, String, i64)>,
+}
+
+impl PoolCreationPermit {
+    /// Counts `pool_id` as a live pool of the key.
+    pub fn commit(mut self, pool_id: PoolId) {
+        if let Some((registry, api_key, day)) = self.held.take() {
+            registry.settle_pool_creation(&api_key, day, Some(pool_id));
+        }
+    }
+}
+
+impl Drop for PoolCreationPermit {
+    fn drop(&mut self) {
+        if let Some((registry, api_key, day)) = self.held.take() {
+            registry.settle_pool_creation(&api_key, day, None);
+        }
+    }
+}
+
+/// Index of the `window_secs`-long UTC window containing `now`.
+fn window_index(now: DateTime<Utc>, window_secs: i64) -> i64 {
+    now.timestamp().div_euclid(window_secs)
+}
+
+fn key_quota(api_key: &str, state: &KeyState, now: DateTime<Utc>) -> KeyQuota {
+    KeyQuota {
+        api_key: api_key.to_string(),
+        limits: state.limits,
+        usage: QuotaUsage {
+            requests_this_minute: state.requests.count_in(window_index(now, 60)),
+            swaps_today: state.swaps.count_in(window_index(now, 86_400)),
+            ws_connections: state.ws_open,
+            pools: state.pools_in_use(),
+            pool_creations_today: state.pool_creations.count_in(window_index(now, 86_400)),
+        },
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_requests_swaps_and_connections_per_key() {
+        let quotas = QuotaRegistry::new();
+        quotas.set_limits(
+            "bot",
+            QuotaLimits {
+                requests_per_minute: Some(2),
+                swaps_per_day: Some(1),
+                ws_connections: Some(1),
+                ..QuotaLimits::default()
+            },
+        );
+        let Some(now) = DateTime::from_timestamp(1_700_000_010, 0) else {
+            panic!("bad timestamp");
+        };
+
+        assert!(quotas.check_request(Some("bot"), now).is_ok());
+        assert!(quotas.check_request(Some("bot"), now).is_ok());
+        let Err(GatewayError::RateLimited { retry_after_ms }) =
+            quotas.check_request(Some("bot"), now)
+        else {
+            panic!("expected rate limit");
+        };
+        assert_eq!(retry_after_ms, 30_000);
+        assert!(
+            quotas
+                .check_request(Some("bot"), now + chrono::Duration::seconds(30))
+                .is_ok()
+        );
+        assert!(quotas.check_request(Some("other"), now).is_ok());
+        assert!(quotas.check_request(None, now).is_ok());
+
+        assert!(quotas.check_swap(Some("bot"), now).is_ok());
+        assert!(quotas.check_swap(Some("bot"), now).is_err());
+
+        let Ok(permit) = quotas.open_ws(Some("bot")) else {
+            panic!("first connection refused");
+        };
+        assert!(quotas.open_ws(Some("bot")).is_err());
+        let later = now + chrono::Duration::seconds(30);
+        let usage = quotas.report_for("bot", later).map(|q| q.usage);
+        assert_eq!(
+            usage,
+            Some(QuotaUsage {
+                requests_this_minute: 1,
+                swaps_today: 1,
+                ws_connections: 1,
+                ..QuotaUsage::default()
+            })
+        );
+        drop(permit);
+        assert!(quotas.open_ws(Some("bot")).is_ok());
+    }
+
+    #[test]
+    fn limits_live_pools_and_daily_creations_per_key() {
+        let quotas = QuotaRegistry::new();
+        quotas.set_limits(
+            "bot",
+            QuotaLimits {
+                max_pools: Some(2),
+                pool_creations_per_day: Some(3),
+                ..QuotaLimits::default()
+            },
+        );
+        let Some(now) = DateTime::from_timestamp(1_700_000_010, 0) else {
+            panic!("bad timestamp");
+        };
+
+        // A failed creation gives its reservation back
+        let Ok(permit) = quotas.reserve_pool_creation(Some("bot"), now) else {
+            panic!("creation refused");
+        };
+        drop(permit);
+
+        let first = PoolId::new();
+        for pool_id in [first, PoolId::new()] {
+            let Ok(permit) = quotas.reserve_pool_creation(Some("bot"), now) else {
+                panic!("creation refused");
+            };
+            permit.commit(pool_id);
+        }
+        assert!(matches!(
+            quotas.reserve_pool_creation(Some("bot"), now),
+            Err(GatewayError::PoolQuotaExceeded(_))
+        ));
+
+        quotas.forget_pool(first);
+        let Ok(permit) = quotas.reserve_pool_creation(Some("bot"), now) else {
+            panic!("creation refused after removal");
+        };
+        let second = PoolId::new();
+        permit.commit(second);
+        let usage = quotas.report_for("bot", now).map(|q| q.usage);
+        assert_eq!(
+            usage.map(|u| (u.pools, u.pool_creations_today)),
+            Some((2, 3))
+        );
+
+        // Room for another pool, but the day's creations are used up
+        quotas.forget_pool(second);
+        assert!(matches!(
+            quotas.reserve_pool_creation(Some("bot"), now),
+            Err(GatewayError::PoolQuotaExceeded(_))
+        ));
+        assert!(quotas.reserve_pool_creation(None, now).is_ok());
+    }
+}
@@ -0,0 +1,150 @@
+//! Quote audit trail.
+//!
+//! With `PERSISTENCE_QUOTE_AUDIT_ENABLED`, quotes served by
+//! `POST /api/v1/pools/{id}/quote` are recorded in the `quotes` table with
+//! the client they were served to, so best-execution audits can compare
+//! each client's quoted prices against the `swap_executed` events of its
+//! swaps. A [`QuoteSampler`] picks which quotes are kept: a share of all
+//! quotes in basis points, optionally restricted to a list of clients.
+//!
+//! Recording never holds up a quote. Sampled quotes go into a bounded
+//! buffer and a single writer inserts them in batches; once the buffer is
+//! full, further quotes are dropped from the trail and counted in a
+//! warning.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::persistence::models::QuoteRecord;
+use crate::persistence::postgres::PostgresPersistence;
+
+/// Quotes buffered while the writer is behind.
+const BUFFER: usize = 10_000;
+
+/// Most quotes inserted per transaction.
+const BATCH_SIZE: usize = 500;
+
+/// Basis points in a whole; a sample rate of this keeps every quote.
+pub const FULL_SAMPLE_BPS: u32 = 10_000;
+
+/// Which quotes are recorded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuoteSampler {
+    /// Share of quotes kept, in basis points.
+    rate_bps: u32,
+    /// Only quotes served to these clients; empty means every client.
+    clients: BTreeSet<String>,
+}
+
+impl QuoteSampler {
+    /// Keeps `rate_bps` of the quotes served to the comma-separated
+    /// `clients` (every client when empty). Rates above
+    /// [`FULL_SAMPLE_BPS`] keep every quote.
+    #[must_use]
+    pub fn new(rate_bps: u32, clients: &str) -> Self {
+        Self {
+            rate_bps: rate_bps.min(FULL_SAMPLE_BPS),
+            clients: clients
+                .split(',')
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    /// Returns `true` if a quote served to `client_id` is kept, given a
+    /// uniform draw `roll_bps` in `0..10_000`.
+    #[must_use]
+    pub fn admits(&self, client_id: Option<&str>, roll_bps: u32) -> bool {
+        let listed = self.clients.is_empty()
+            || client_id.is_some_and(|client| self.clients.contains(client));
+        listed && roll_bps < self.rate_bps
+    }
+}
+
+/// Handle for recording served quotes.
+#[derive(Debug, Clone)]
+pub struct QuoteAudit {
+    sampler: Arc<QuoteSampler>,
+    tx: mpsc::Sender<QuoteRecord>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl QuoteAudit {
+    /// Starts the writer saving sampled quotes to `db`. The task ends once
+    /// every handle is dropped and the buffer is written.
+    #[must_use]
+    pub fn spawn(db: PostgresPersistence, sampler: QuoteSampler) -> (Self, JoinHandle<()>) {
+        let (tx, rx) = mpsc::channel(BUFFER);
+        let audit = Self {
+            sampler: Arc::new(sampler),
+            tx,
+            dropped: Arc::default(),
+        };
+        let writer = tokio::spawn(write(db, rx, Arc::clone(&audit.dropped)));
+        (audit, writer)
+    }
+
+    /// Records `quote` if the sampler keeps it, without waiting on the
+    /// database.
+    pub fn record(&self, quote: QuoteRecord) {
+        let roll_bps =
+            u32::try_from(Uuid::new_v4().as_u128() % u128::from(FULL_SAMPLE_BPS)).unwrap_or(0);
+        if !self.sampler.admits(quote.client_id.as_deref(), roll_bps) {
+            return;
+        }
+        if self.tx.try_send(quote).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Writes buffered quotes in batches until every handle is dropped.
+async fn write(
+    db: PostgresPersistence,
+    mut rx: mpsc::Receiver<QuoteRecord>,
+    dropped: Arc<AtomicU64>,
+) {
+    let mut quotes = Vec::with_capacity(BATCH_SIZE);
+    while rx.recv_many(&mut quotes, BATCH_SIZE).await > 0 {
+        if let Err(e) = db.save_quotes(&quotes).await {
+            tracing::warn!(quotes = quotes.len(), error = %e, "quote audit write failed; quotes were not recorded");
+        }
+        quotes.clear();
+        let lost = dropped.swap(0, Ordering::Relaxed);
+        if lost > 0 {
+            tracing::warn!(
+                dropped = lost,
+                "quote audit buffer full; quotes were not recorded"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampler_keeps_rate_of_listed_clients() {
+        let everyone = QuoteSampler::new(2_500, "");
+        assert!(everyone.admits(None, 0));
+        assert!(everyone.admits(Some("desk"), 2_499));
+        assert!(!everyone.admits(Some("desk"), 2_500));
+
+        let listed = QuoteSampler::new(FULL_SAMPLE_BPS, " desk-a, desk-b ");
+        assert!(listed.admits(Some("desk-b"), 9_999));
+        assert!(!listed.admits(Some("desk-c"), 0));
+        assert!(!listed.admits(None, 0));
+
+        assert!(!QuoteSampler::new(0, "").admits(Some("desk"), 0));
+        assert!(QuoteSampler::new(50_000, "").admits(Some("desk"), 9_999));
+    }
+}
@@ -0,0 +1,159 @@
+//! Optional dedicated runtime for quote and simulation work.
+//!
+//! Quotes, route searches, slippage curves, and depth charts are pure
+//! computation that clients may poll aggressively. With a dedicated
+//! runtime they run on their own bounded set of worker threads, so a quote
+//! storm queues up there instead of starving the runtime that serves
+//! swaps and WebSocket connections. Without one, they run on the shared
+//! runtime as before.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::runtime::{Builder, Handle, Runtime};
+
+use super::timing;
+use crate::error::GatewayError;
+
+/// Worker thread name prefix of the dedicated runtime.
+const THREAD_NAME: &str = "hydra-quote";
+
+/// Where quote and simulation work runs: a dedicated runtime, or the
+/// caller's (shared) runtime.
+#[derive(Debug, Clone, Default)]
+pub struct QuoteRuntime {
+    dedicated: Option<Arc<Dedicated>>,
+}
+
+/// Owns the dedicated runtime; shut down without blocking when the last
+/// handle is dropped, which may happen on an async thread.
+#[derive(Debug)]
+struct Dedicated {
+    handle: Handle,
+    runtime: Option<Runtime>,
+}
+
+impl Drop for Dedicated {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+impl QuoteRuntime {
+    /// Runs quote work on the caller's runtime.
+    #[must_use]
+    pub fn shared() -> Self {
+        Self::default()
+    }
+
+    /// Starts a dedicated runtime with `threads` worker threads; `0` means
+    /// [`Self::shared`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the runtime's threads cannot be spawned.
+    pub fn dedicated(threads: usize) -> std::io::Result<Self> {
+        if threads == 0 {
+            return Ok(Self::shared());
+        }
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(threads)
+            .max_blocking_threads(threads)
+            .thread_name(THREAD_NAME)
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            dedicated: Some(Arc::new(Dedicated {
+                handle: runtime.handle().clone(),
+                runtime: Some(runtime),
+            })),
+        })
+    }
+
+    /// Whether quote work runs on its own runtime.
+    #[must_use]
+    pub fn is_dedicated(&self) -> bool {
+        self.dedicated.is_some()
+    }
+
+    /// Runs `fut` on the dedicated runtime, or awaits it in place. Phase
+    /// timings it records still count toward the calling request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::Internal`] if the task panics or the
+    /// runtime shuts down while it runs.
+    pub async fn run<F>(&self, fut: F) -> Result<F::Output, GatewayError>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let Some(dedicated) = &self.dedicated else {
+            return Ok(fut.await);
+        };
+        let task = match timing::current() {
+            Some(timings) => dedicated.handle.spawn(timing::scope(timings, fut)),
+            None => dedicated.handle.spawn(fut),
+        };
+        task.await
+            .map_err(|e| GatewayError::Internal(e.to_string()))
+    }
+
+    /// Runs CPU-bound `f` on a dedicated runtime worker, or on the shared
+    /// runtime's blocking pool, keeping it off the shared reactor either
+    /// way.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::Internal`] if `f` panics or the runtime
+    /// shuts down while it runs.
+    pub async fn compute<T, F>(&self, f: F) -> Result<T, GatewayError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let task = match &self.dedicated {
+            Some(dedicated) => dedicated.handle.spawn(async move { f() }),
+            None => tokio::task::spawn_blocking(f),
+        };
+        task.await
+            .map_err(|e| GatewayError::Internal(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn thread_name() -> String {
+        std::thread::current()
+            .name()
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn dedicated_runtime_runs_work_on_its_own_threads() {
+        let Ok(quotes) = QuoteRuntime::dedicated(1) else {
+            panic!("runtime not started");
+        };
+        assert!(quotes.is_dedicated());
+        let Ok(name) = quotes.run(async { thread_name() }).await else {
+            panic!("task failed");
+        };
+        assert_eq!(name, THREAD_NAME);
+        let Ok(name) = quotes.compute(thread_name).await else {
+            panic!("task failed");
+        };
+        assert_eq!(name, THREAD_NAME);
+
+        let shared = QuoteRuntime::shared();
+        let Ok(name) = shared.run(async { thread_name() }).await else {
+            panic!("task failed");
+        };
+        assert_ne!(name, THREAD_NAME);
+    }
+}
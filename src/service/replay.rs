@@ -0,0 +1,995 @@
+//! Rebuilding pool state from snapshots and the event log.
+//!
+//! A [`Replayer`] is seeded with snapshots, fed stored events in log
+//! order, and finally inserts the rebuilt pools into a registry. It never
+//! touches the live registry unless the caller hands it in, so the same
+//! machinery serves disaster-recovery rehearsals and startup recovery
+//! ([`recover`]), whose outcome is kept as a [`StartupReport`].
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use hydra_amm::traits::LiquidityPool;
+
+use super::pool_service::build_entry;
+use super::snapshot;
+use crate::domain::pool_entry::{OperationOutcome, PoolEntry, PoolStatus};
+use crate::domain::pool_event::LiquidityChangeType;
+use crate::domain::pool_operation::{PoolOperation, SwapKind};
+use crate::domain::{PoolEvent, PoolId, PoolRegistry};
+use crate::error::GatewayError;
+use crate::persistence::models::{PoolSnapshot, StoredEvent};
+use crate::persistence::postgres::PostgresPersistence;
+
+/// Per-pool replay progress.
+#[derive(Debug, Default)]
+struct PoolProgress {
+    base_snapshot_at: Option<DateTime<Utc>>,
+    operations_applied: u64,
+    events_skipped: u64,
+    divergences: u64,
+    removed: bool,
+    error: Option<String>,
+}
+
+/// Final state of one replayed pool.
+#[derive(Debug, Clone)]
+pub struct ReplayedPool {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Pool type, when the pool could be rebuilt.
+    pub pool_type: Option<String>,
+    /// Snapshot the pool was seeded from, if any.
+    pub base_snapshot_at: Option<DateTime<Utc>>,
+    /// Operations re-applied from the event log.
+    pub operations_applied: u64,
+    /// Events for this pool that could not be replayed.
+    pub events_skipped: u64,
+    /// Swaps whose replayed output differed from the recorded one.
+    pub divergences: u64,
+    /// `"active"`, `"removed"`, or `"failed"`.
+    pub status: &'static str,
+    /// Spot price of token A in token B after replay.
+    pub spot_price: Option<f64>,
+    /// Total liquidity after replay.
+    pub total_liquidity: Option<u128>,
+    /// Tracked reserves keyed by token address.
+    pub reserves: BTreeMap<String, u128>,
+    /// Swaps executed, including those before the base snapshot.
+    pub swap_count: u64,
+    /// First error that stopped this pool from replaying.
+    pub error: Option<String>,
+}
+
+/// Summary of a replay run.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayReport {
+    /// Stored events examined.
+    pub events_scanned: u64,
+    /// Events that changed pool state.
+    pub events_applied: u64,
+    /// Events that could not be replayed.
+    pub events_skipped: u64,
+    /// Per-pool results, ordered by pool ID.
+    pub pools: Vec<ReplayedPool>,
+}
+
+/// Outcome of startup recovery, kept so operators can confirm it was
+/// complete.
+#[derive(Debug, Clone)]
+pub struct StartupReport {
+    /// Whether pools were recovered; `false` when persistence or
+    /// recovery is disabled.
+    pub recovered: bool,
+    /// When recovery started.
+    pub started_at: DateTime<Utc>,
+    /// When the recovered pools were registered.
+    pub completed_at: DateTime<Utc>,
+    /// Wall-clock time spent recovering.
+    pub duration: Duration,
+    /// Snapshots pools were seeded from.
+    pub snapshots_loaded: u64,
+    /// Id of the last event log row read (0 if the log is empty).
+    pub last_event_id: i64,
+    /// Replay totals and per-pool results.
+    pub replay: ReplayReport,
+}
+
+impl StartupReport {
+    /// Report of a start without recovery, at `at`.
+    #[must_use]
+    pub fn skipped(at: DateTime<Utc>) -> Self {
+        Self {
+            recovered: false,
+            started_at: at,
+            completed_at: at,
+            duration: Duration::ZERO,
+            snapshots_loaded: 0,
+            last_event_id: 0,
+            replay: ReplayReport::default(),
+        }
+    }
+
+    /// Number of pools that ended replay with `status` (`"active"`,
+    /// `"removed"`, or `"failed"`).
+    #[must_use]
+    pub fn pools_with_status(&self, status: &str) -> usize {
+        self.replay
+            .pools
+            .iter()
+            .filter(|p| p.status == status)
+            .count()
+    }
+
+    /// Logs every pool that failed to recover and a summary.
+    pub fn log(&self) {
+        for pool in self.replay.pools.iter().filter(|p| p.status == "failed") {
+            tracing::warn!(
+                pool_id = %pool.pool_id,
+                error = pool.error.as_deref().unwrap_or_default(),
+                "pool not recovered"
+            );
+        }
+        tracing::info!(
+            pools = self.pools_with_status("active"),
+            failed = self.pools_with_status("failed"),
+            snapshots = self.snapshots_loaded,
+            events = self.replay.events_scanned,
+            replayed = self.replay.events_applied,
+            skipped = self.replay.events_skipped,
+            last_event_id = self.last_event_id,
+            duration_ms = u64::try_from(self.duration.as_millis()).unwrap_or(u64::MAX),
+            "pools recovered"
+        );
+    }
+}
+
+/// Rebuilds pools from snapshots and stored events.
+#[derive(Debug, Default)]
+pub struct Replayer {
+    entries: BTreeMap<PoolId, PoolEntry>,
+    progress: BTreeMap<PoolId, PoolProgress>,
+    events_scanned: u64,
+    events_applied: u64,
+    events_skipped: u64,
+}
+
+impl Replayer {
+    /// Creates an empty replayer.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seeds a pool from a snapshot. Events that changed the pool are
+    /// ignored up to the version the snapshot holds; events logged
+    /// without a version, at or before the snapshot time.
+    pub fn seed(&mut self, snapshot: &PoolSnapshot) {
+        let pool_id = PoolId::from_uuid(snapshot.pool_id);
+        let progress = self.progress.entry(pool_id).or_default();
+        progress.base_snapshot_at = Some(snapshot.snapshot_at);
+        match snapshot::decode(snapshot) {
+            Ok(entry) => {
+                self.entries.insert(pool_id, entry);
+            }
+            Err(e) => progress.error = Some(e.to_string()),
+        }
+    }
+
+    /// Records that the pool's events up to `to` were compacted away.
+    /// Unless the pool is seeded from a snapshot taken at or after `to`,
+    /// it cannot be rebuilt and fails. Call after seeding.
+    pub fn compacted(&mut self, pool_id: PoolId, to: DateTime<Utc>) {
+        let progress = self.progress.entry(pool_id).or_default();
+        if progress.base_snapshot_at.is_some_and(|at| to <= at) || progress.error.is_some() {
+            return;
+        }
+        progress.error = Some(format!(
+            "events up to {to} were compacted; replay from a later snapshot"
+        ));
+    }
+
+    /// Applies one stored event (already upcast to the current schema).
+    /// Imported history is scanned but never applied, and an event whose
+    /// pool version the pool already reached (because the base snapshot
+    /// holds it, or the event was logged twice) is ignored.
+    pub fn apply(&mut self, stored: &StoredEvent) {
+        self.events_scanned = self.events_scanned.saturating_add(1);
+        if stored.imported {
+            return;
+        }
+        let pool_id = PoolId::from_uuid(stored.pool_id);
+        let progress = self.progress.entry(pool_id).or_default();
+        let version = stored.pool_version.and_then(|v| u64::try_from(v).ok());
+        // Events logged before versions were recorded fall back to time
+        if version.is_none()
+            && progress
+                .base_snapshot_at
+                .is_some_and(|at| stored.created_at <= at)
+            || progress.error.is_some()
+        {
+            return;
+        }
+
+        let event = match stored.to_pool_event() {
+            Ok(event) => event,
+            Err(e) => {
+                progress.events_skipped = progress.events_skipped.saturating_add(1);
+                self.events_skipped = self.events_skipped.saturating_add(1);
+                tracing::warn!(event_id = stored.id, error = %e, "skipping unreadable event");
+                return;
+            }
+        };
+
+        match step(&mut self.entries, progress, pool_id, &event, version) {
+            Step::Applied => self.events_applied = self.events_applied.saturating_add(1),
+            Step::Skipped(reason) => {
+                progress.events_skipped = progress.events_skipped.saturating_add(1);
+                self.events_skipped = self.events_skipped.saturating_add(1);
+                tracing::debug!(event_id = stored.id, %pool_id, reason, "event not replayed");
+            }
+            Step::Ignored => {}
+        }
+    }
+
+    /// Summarizes the current replay state.
+    #[must_use]
+    pub fn report(&self) -> ReplayReport {
+        let pools = self
+            .progress
+            .iter()
+            .map(|(pool_id, progress)| {
+                let entry = self.entries.get(pool_id);
+                let status = if progress.error.is_some() {
+                    "failed"
+                } else if progress.removed {
+                    "removed"
+                } else if entry.is_some() {
+                    "active"
+                } else {
+                    "failed"
+                };
+                let error = progress.error.clone().or_else(|| {
+                    (entry.is_none() && !progress.removed)
+                        .then(|| "pool creation is not replayable".to_string())
+                });
+                ReplayedPool {
+                    pool_id: *pool_id,
+                    pool_type: entry.map(|e| e.pool_type.clone()),
+                    base_snapshot_at: progress.base_snapshot_at,
+                    operations_applied: progress.operations_applied,
+                    events_skipped: progress.events_skipped,
+                    divergences: progress.divergences,
+                    status,
+                    spot_price: entry.and_then(PoolEntry::spot_price),
+                    total_liquidity: entry.map(|e| e.pool_box.total_liquidity().get()),
+                    reserves: entry.map(reserves_by_token).unwrap_or_default(),
+                    swap_count: entry.map_or(0, |e| e.swap_count),
+                    error,
+                }
+            })
+            .collect();
+        ReplayReport {
+            events_scanned: self.events_scanned,
+            events_applied: self.events_applied,
+            events_skipped: self.events_skipped,
+            pools,
+        }
+    }
+
+    /// Inserts every rebuilt pool into `registry`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError`] if a pool ID is already registered.
+    pub async fn finish_into(self, registry: &PoolRegistry) -> Result<(), GatewayError> {
+        for entry in self.entries.into_values() {
+            registry.insert(entry).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Rebuilds pools into a fresh staging registry and reports the result.
+///
+/// When `from` is given, each pool is seeded from its latest snapshot at
+/// or before `from`; pools without one are rebuilt from their creation
+/// event. Events up to and including `to` are then applied. Live pools
+/// are never touched.
+///
+/// # Errors
+///
+/// Returns a [`GatewayError::PersistenceError`] if snapshots or events
+/// cannot be loaded.
+pub async fn replay_into_staging(
+    persistence: &PostgresPersistence,
+    from: Option<DateTime<Utc>>,
+    to: DateTime<Utc>,
+    pool_id: Option<PoolId>,
+) -> Result<(PoolRegistry, ReplayReport), GatewayError> {
+    let pool_filter = pool_id.map(|id| *id.as_uuid());
+    let mut replayer = Replayer::new();
+    if let Some(from) = from {
+        for snapshot in persistence.load_snapshots_at(from, pool_filter).await? {
+            replayer.seed(&snapshot);
+        }
+    }
+    for checkpoint in persistence.load_checkpoints(pool_filter).await? {
+        if checkpoint.to <= to {
+            replayer.compacted(PoolId::from_uuid(checkpoint.pool_id), checkpoint.to);
+        }
+    }
+    for event in persistence.load_events_until(to, pool_filter).await? {
+        replayer.apply(&event);
+    }
+
+    let report = replayer.report();
+    let staging = PoolRegistry::new();
+    replayer.finish_into(&staging).await?;
+    Ok((staging, report))
+}
+
+/// Events loaded per query while recovering.
+const RECOVERY_BATCH_SIZE: i64 = 1_000;
+
+/// Rebuilds every pool from the latest snapshots and the whole event log
+/// into `registry`, which should be empty. Used at startup, so a restart
+/// keeps the pools it had.
+///
+/// # Errors
+///
+/// Returns a [`GatewayError::PersistenceError`] if snapshots or events
+/// cannot be loaded, or a [`GatewayError`] if a rebuilt pool is already
+/// registered.
+pub async fn recover(
+    persistence: &PostgresPersistence,
+    registry: &PoolRegistry,
+) -> Result<StartupReport, GatewayError> {
+    let started_at = Utc::now();
+    let started = Instant::now();
+    let mut replayer = Replayer::new();
+    let snapshots = persistence.load_latest_snapshots().await?;
+    for snapshot in &snapshots {
+        replayer.seed(snapshot);
+    }
+    for checkpoint in persistence.load_checkpoints(None).await? {
+        replayer.compacted(PoolId::from_uuid(checkpoint.pool_id), checkpoint.to);
+    }
+    let mut last_event_id = 0;
+    loop {
+        let batch = persistence
+            .load_events_after_id(last_event_id, RECOVERY_BATCH_SIZE)
+            .await?;
+        let Some(last) = batch.last() else {
+            break;
+        };
+        last_event_id = last.id;
+        for event in &batch {
+            replayer.apply(event);
+        }
+    }
+
+    let replay = replayer.report();
+    replayer.finish_into(registry).await?;
+    Ok(StartupReport {
+        recovered: true,
+        started_at,
+        completed_at: Utc::now(),
+        duration: started.elapsed(),
+        snapshots_loaded: u64::try_from(snapshots.len()).unwrap_or(u64::MAX),
+        last_event_id,
+        replay,
+    })
+}
+
+/// Outcome of replaying a single event.
+enum Step {
+    Applied,
+    Skipped(&'static str),
+    Ignored,
+}
+
+/// Replays `event`, which left its pool at `version` if one was logged.
+fn step(
+    entries: &mut BTreeMap<PoolId, PoolEntry>,
+    progress: &mut PoolProgress,
+    pool_id: PoolId,
+    event: &PoolEvent,
+    version: Option<u64>,
+) -> Step {
+    let op = match event {
+        PoolEvent::PoolCreated {
+            pool_type,
+            config,
+            metadata,
+            owner,
+            timestamp,
+            ..
+        } => {
+            if entries.contains_key(&pool_id) {
+                return Step::Ignored;
+            }
+            if config.is_null() {
+                return Step::Skipped("creation config not recorded");
+            }
+            return match build_entry(pool_id, pool_type, config.clone()) {
+                Ok(mut entry) => {
+                    entry.created_at = *timestamp;
+                    entry.last_modified_at = *timestamp;
+                    entry.metadata = metadata.clone();
+                    entry.owner = owner.clone();
+                    entries.insert(pool_id, entry);
+                    progress.removed = false;
+                    Step::Applied
+                }
+                Err(e) => {
+                    progress.error = Some(e.to_string());
+                    Step::Skipped("creation config rejected")
+                }
+            };
+        }
+        PoolEvent::PoolRemoved { .. } => {
+            return if entries.remove(&pool_id).is_some() {
+                progress.removed = true;
+                Step::Applied
+            } else {
+                Step::Skipped("unknown pool")
+            };
+        }
+        PoolEvent::PoolArchived { timestamp, .. } => {
+            return match entries.get_mut(&pool_id) {
+                Some(entry) => {
+                    entry.status = PoolStatus::Archived;
+                    entry.archived_at = Some(*timestamp);
+                    Step::Applied
+                }
+                None => Step::Skipped("unknown pool"),
+            };
+        }
+        PoolEvent::PoolMetadataUpdated { metadata, .. } => {
+            return match entries.get_mut(&pool_id) {
+                Some(entry) => {
+                    entry.metadata = metadata.clone();
+                    Step::Applied
+                }
+                None => Step::Skipped("unknown pool"),
+            };
+        }
+        PoolEvent::PoolStatusChanged { status, .. } => {
+            return match entries.get_mut(&pool_id) {
+                Some(entry) => {
+                    entry.status = *status;
+                    Step::Applied
+                }
+                None => Step::Skipped("unknown pool"),
+            };
+        }
+        PoolEvent::PoolOwnershipTransferred { new_owner, .. } => {
+            return match entries.get_mut(&pool_id) {
+                Some(entry) => {
+                    entry.owner = Some(new_owner.clone());
+                    Step::Applied
+                }
+                None => Step::Skipped("unknown pool"),
+            };
+        }
+        PoolEvent::PoolRestored {
+            snapshot_id,
+            timestamp,
+            ..
+        } => {
+            // The restored state is snapshotted before the event is
+            // published, so a base snapshot from after it already holds it
+            let held = match version {
+                Some(version) => entries
+                    .get(&pool_id)
+                    .is_some_and(|e| e.version() >= version),
+                None => progress.base_snapshot_at.is_some_and(|at| at >= *timestamp),
+            };
+            if held {
+                return Step::Ignored;
+            }
+            progress.error = Some(format!(
+                "pool was restored from snapshot {snapshot_id} at {timestamp}; \
+                 replay from a later snapshot"
+            ));
+            return Step::Skipped("pool restored from a snapshot");
+        }
+        PoolEvent::PriceUpdated { .. } | PoolEvent::RewardsClaimed { .. } => {
+            return Step::Ignored;
+        }
+        PoolEvent::SwapExecuted { .. }
+        | PoolEvent::LiquidityChanged { .. }
+        | PoolEvent::FeesCollected { .. }
+        | PoolEvent::OrderPlaced { .. }
+        | PoolEvent::OrderCancelled { .. }
+        | PoolEvent::OraclePriceUpdated { .. } => {
+            let Some(entry) = entries.get(&pool_id) else {
+                return Step::Skipped("unknown pool");
+            };
+            if let Some(version) = version {
+                let current = entry.version();
+                if version <= current {
+                    return Step::Ignored;
+                }
+                if version > current.saturating_add(1) {
+                    progress.error = Some(format!(
+                        "events after version {current} are missing before version {version}; \
+                         replay from a later snapshot"
+                    ));
+                    return Step::Skipped("events missing");
+                }
+            }
+            match event_operation(entry, event) {
+                Ok(Some(op)) => op,
+                Ok(None) => return Step::Ignored,
+                Err(reason) => return Step::Skipped(reason),
+            }
+        }
+    };
+
+    let Some(entry) = entries.get_mut(&pool_id) else {
+        return Step::Skipped("unknown pool");
+    };
+    match entry.apply(&op) {
+        Ok(outcome) => {
+            entry.last_modified_at = event_timestamp(event);
+            if let (
+                OperationOutcome::Swap(result),
+                PoolEvent::SwapExecuted {
+                    amount_in,
+                    amount_out,
+                    ..
+                },
+            ) = (&outcome, event)
+                && (result.amount_in().get().to_string() != *amount_in
+                    || result.amount_out().get().to_string() != *amount_out)
+            {
+                progress.divergences = progress.divergences.saturating_add(1);
+            }
+            progress.operations_applied = progress.operations_applied.saturating_add(1);
+            Step::Applied
+        }
+        Err(e) => {
+            progress.error = Some(e.to_string());
+            Step::Skipped("operation rejected")
+        }
+    }
+}
+
+/// Translates a swap or liquidity event into the operation that
+/// reproduces it on `entry`. Returns `Ok(None)` for events that change no
+/// pool state, or the reason the event cannot be reproduced.
+pub(crate) fn event_operation(
+    entry: &PoolEntry,
+    event: &PoolEvent,
+) -> Result<Option<PoolOperation>, &'static str> {
+    Ok(Some(match event {
+        PoolEvent::SwapExecuted {
+            token_in,
+            swap_kind,
+            amount_in,
+            amount_out,
+            ..
+        } => {
+            let side = entry
+                .side_of_label(token_in)
+                .ok_or("swap direction not recorded")?;
+            PoolOperation::Swap {
+                token_in: side,
+                kind: *swap_kind,
+                amount: match swap_kind {
+                    SwapKind::ExactIn => amount_in.clone(),
+                    SwapKind::ExactOut => amount_out.clone(),
+                },
+            }
+        }
+        PoolEvent::LiquidityChanged {
+            change_type,
+            amount_a,
+            amount_b,
+            liquidity_delta,
+            position_id,
+            range,
+            actor,
+            ..
+        } => match change_type {
+            // The actor is only kept as owner when the deposit opens the
+            // position; later top-ups leave it unchanged.
+            LiquidityChangeType::Add => PoolOperation::AddLiquidity {
+                amount_a: amount_a.clone(),
+                amount_b: amount_b.clone(),
+                position_id: *position_id,
+                range: *range,
+                owner: actor.clone(),
+            },
+            LiquidityChangeType::Remove if liquidity_delta == "0" => {
+                return Err("burned liquidity not recorded");
+            }
+            LiquidityChangeType::Remove => PoolOperation::RemoveLiquidity {
+                liquidity: liquidity_delta.clone(),
+                position_id: *position_id,
+            },
+        },
+        PoolEvent::FeesCollected {
+            position_id: Some(position_id),
+            ..
+        } => PoolOperation::CollectFees {
+            position_id: *position_id,
+        },
+        PoolEvent::OrderPlaced {
+            order_id,
+            side,
+            price,
+            quantity,
+            actor,
+            ..
+        } => PoolOperation::PlaceOrder {
+            order_id: *order_id,
+            side: *side,
+            price: price.clone(),
+            quantity: quantity.clone(),
+            owner: actor.clone(),
+        },
+        PoolEvent::OrderCancelled { order_id, .. } => PoolOperation::CancelOrder {
+            order_id: *order_id,
+        },
+        PoolEvent::OraclePriceUpdated { new_price, .. } => PoolOperation::SetOraclePrice {
+            price: new_price.clone(),
+        },
+        PoolEvent::PoolCreated { .. }
+        | PoolEvent::PoolRemoved { .. }
+        | PoolEvent::PoolArchived { .. }
+        | PoolEvent::PoolRestored { .. }
+        | PoolEvent::PoolStatusChanged { .. }
+        | PoolEvent::PoolMetadataUpdated { .. }
+        | PoolEvent::PoolOwnershipTransferred { .. }
+        | PoolEvent::FeesCollected {
+            position_id: None, ..
+        }
+        | PoolEvent::RewardsClaimed { .. }
+        | PoolEvent::PriceUpdated { .. } => return Ok(None),
+    }))
+}
+
+pub(crate) fn event_timestamp(event: &PoolEvent) -> DateTime<Utc> {
+    match event {
+        PoolEvent::PoolCreated { timestamp, .. }
+        | PoolEvent::PoolRemoved { timestamp, .. }
+        | PoolEvent::PoolArchived { timestamp, .. }
+        | PoolEvent::PoolRestored { timestamp, .. }
+        | PoolEvent::PoolStatusChanged { timestamp, .. }
+        | PoolEvent::PoolMetadataUpdated { timestamp, .. }
+        | PoolEvent::PoolOwnershipTransferred { timestamp, .. }
+        | PoolEvent::SwapExecuted { timestamp, .. }
+        | PoolEvent::LiquidityChanged { timestamp, .. }
+        | PoolEvent::FeesCollected { timestamp, .. }
+        | PoolEvent::RewardsClaimed { timestamp, .. }
+        | PoolEvent::OrderPlaced { timestamp, .. }
+        | PoolEvent::OrderCancelled { timestamp, .. }
+        | PoolEvent::OraclePriceUpdated { timestamp, .. }
+        | PoolEvent::PriceUpdated { timestamp, .. } => *timestamp,
+    }
+}
+
+/// Pairs tracked reserves with their token addresses.
+fn reserves_by_token(entry: &PoolEntry) -> BTreeMap<String, u128> {
+    entry
+        .reserves
+        .iter()
+        .flatten()
+        .zip(&entry.tokens)
+        .map(|(reserve, token)| (token.address.clone(), *reserve))
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::pool_event::EVENT_SCHEMA_VERSION;
+    use crate::persistence::models::NewEvent;
+
+    fn stored(id: i64, event: &PoolEvent) -> StoredEvent {
+        let Ok(payload) = serde_json::to_value(event) else {
+            panic!("serialize failed");
+        };
+        StoredEvent {
+            id,
+            pool_id: *event.pool_id().as_uuid(),
+            event_type: event.event_type_str().to_string(),
+            schema_version: i32::try_from(EVENT_SCHEMA_VERSION).unwrap_or(i32::MAX),
+            payload,
+            created_at: Utc::now(),
+            imported: false,
+            pool_version: None,
+        }
+    }
+
+    fn created(pool_id: PoolId) -> PoolEvent {
+        PoolEvent::PoolCreated {
+            pool_id,
+            pool_type: "constant_product".to_string(),
+            token_a: "0xaaa".to_string(),
+            token_b: "0xbbb".to_string(),
+            fee_tier: 30,
+            config: serde_json::json!({
+                "token_a": {"address": "0xaaa", "decimals": 6},
+                "token_b": {"address": "0xbbb", "decimals": 6},
+                "fee_bps": 30,
+                "reserve_a": "1000000",
+                "reserve_b": "1000000",
+            }),
+            metadata: serde_json::Value::Null,
+            owner: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn swap(pool_id: PoolId, token_in: &str, amount_in: &str, amount_out: &str) -> PoolEvent {
+        PoolEvent::SwapExecuted {
+            pool_id,
+            command_id: "cmd".to_string(),
+            amount_in: amount_in.to_string(),
+            amount_out: amount_out.to_string(),
+            fee: "0".to_string(),
+            fee_rebate: "0".to_string(),
+            new_price: "1".to_string(),
+            price_change_bps: 0,
+            token_in: token_in.to_string(),
+            swap_kind: SwapKind::ExactIn,
+            actor: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn replays_creation_and_swaps() {
+        let pool_id = PoolId::new();
+        let mut replayer = Replayer::new();
+        replayer.apply(&stored(1, &created(pool_id)));
+        replayer.apply(&stored(2, &swap(pool_id, "0xaaa", "1000", "0")));
+
+        let report = replayer.report();
+        assert_eq!(report.events_applied, 2);
+        let Some(pool) = report.pools.first() else {
+            panic!("expected a pool");
+        };
+        assert_eq!(pool.status, "active");
+        assert_eq!(pool.swap_count, 1);
+        assert_eq!(pool.reserves.get("0xaaa"), Some(&1_001_000));
+        // Recorded amount_out of 0 does not match the replayed output.
+        assert_eq!(pool.divergences, 1);
+    }
+
+    #[test]
+    fn unknown_direction_is_skipped() {
+        let pool_id = PoolId::new();
+        let mut replayer = Replayer::new();
+        replayer.apply(&stored(1, &created(pool_id)));
+        replayer.apply(&stored(2, &swap(pool_id, "", "1000", "0")));
+
+        let report = replayer.report();
+        assert_eq!(report.events_skipped, 1);
+        assert_eq!(report.pools.first().map(|p| p.swap_count), Some(0));
+    }
+
+    #[test]
+    fn compacted_history_fails_the_pool() {
+        let pool_id = PoolId::new();
+        let mut replayer = Replayer::new();
+        replayer.compacted(pool_id, Utc::now());
+        replayer.apply(&stored(1, &created(pool_id)));
+
+        let report = replayer.report();
+        assert_eq!(report.pools.first().map(|p| p.status), Some("failed"));
+
+        let mut startup = StartupReport::skipped(Utc::now());
+        startup.replay = report;
+        assert_eq!(
+            (
+                startup.pools_with_status("active"),
+                startup.pools_with_status("failed")
+            ),
+            (0, 1)
+        );
+    }
+
+    #[test]
+    fn restores_need_a_later_base_snapshot() {
+        let pool_id = PoolId::new();
+        let restore_at = Utc::now();
+        let restored = PoolEvent::PoolRestored {
+            pool_id,
+            snapshot_id: 1,
+            snapshot_at: restore_at,
+            archived_snapshot_id: 2,
+            actor: None,
+            timestamp: restore_at,
+        };
+
+        let mut replayer = Replayer::new();
+        replayer.apply(&stored(1, &created(pool_id)));
+        replayer.apply(&stored(2, &restored));
+        assert_eq!(
+            replayer.report().pools.first().map(|p| p.status),
+            Some("failed")
+        );
+
+        // The restored state is snapshotted just after the event's own
+        // timestamp but may be logged before the event row.
+        let Ok(entry) = build_entry(
+            pool_id,
+            "constant_product",
+            serde_json::json!({
+                "token_a": {"address": "0xaaa", "decimals": 6},
+                "token_b": {"address": "0xbbb", "decimals": 6},
+                "fee_bps": 30,
+                "reserve_a": "1000000",
+                "reserve_b": "1000000",
+            }),
+        ) else {
+            panic!("entry build failed");
+        };
+        let Ok(parts) = snapshot::encode(&entry) else {
+            panic!("encode failed");
+        };
+        let base_at = restore_at + chrono::Duration::milliseconds(5);
+        let mut replayer = Replayer::new();
+        replayer.seed(&PoolSnapshot {
+            id: 3,
+            pool_id: *pool_id.as_uuid(),
+            pool_type: entry.pool_type.clone(),
+            config_json: parts.config_json,
+            state_json: parts.state_json,
+            metadata_json: parts.metadata_json,
+            snapshot_at: base_at,
+        });
+        let mut event = stored(4, &restored);
+        event.created_at = base_at + chrono::Duration::milliseconds(5);
+        replayer.apply(&event);
+        assert_eq!(
+            replayer.report().pools.first().map(|p| p.status),
+            Some("active")
+        );
+    }
+
+    #[test]
+    fn logged_versions_apply_each_mutation_once() {
+        let pool_id = PoolId::new();
+        let Ok(mut entry) = build_entry(
+            pool_id,
+            "constant_product",
+            serde_json::json!({
+                "token_a": {"address": "0xaaa", "decimals": 6},
+                "token_b": {"address": "0xbbb", "decimals": 6},
+                "fee_bps": 30,
+                "reserve_a": "1000000",
+                "reserve_b": "1000000",
+            }),
+        ) else {
+            panic!("entry build failed");
+        };
+        let op = PoolOperation::Swap {
+            token_in: crate::domain::pool_operation::TokenSide::First,
+            kind: SwapKind::ExactIn,
+            amount: "1000".to_string(),
+        };
+        let Ok(_) = entry.apply(&op) else {
+            panic!("swap failed");
+        };
+        let Ok(parts) = snapshot::encode(&entry) else {
+            panic!("encode failed");
+        };
+        let mut replayer = Replayer::new();
+        replayer.seed(&PoolSnapshot {
+            id: 1,
+            pool_id: *pool_id.as_uuid(),
+            pool_type: entry.pool_type.clone(),
+            config_json: parts.config_json,
+            state_json: parts.state_json,
+            metadata_json: parts.metadata_json,
+            snapshot_at: Utc::now() - chrono::Duration::minutes(1),
+        });
+        // Rows are stamped when written, so the first swap's row comes
+        // after the snapshot that already holds it
+        let versioned = |id: i64, version: i64| {
+            let mut event = stored(id, &swap(pool_id, "0xaaa", "1000", "0"));
+            event.pool_version = Some(version);
+            event
+        };
+        replayer.apply(&versioned(2, 1));
+        replayer.apply(&versioned(3, 2));
+        replayer.apply(&versioned(4, 2));
+        let report = replayer.report();
+        assert_eq!(report.events_applied, 1);
+        let Some(pool) = report.pools.first() else {
+            panic!("expected a pool");
+        };
+        assert_eq!((pool.status, pool.swap_count), ("active", 2));
+
+        replayer.apply(&versioned(5, 4));
+        let Some(pool) = replayer.report().pools.first().cloned() else {
+            panic!("expected a pool");
+        };
+        assert_eq!(pool.status, "failed", "version 3 was never logged");
+        assert!(pool.error.is_some_and(|e| e.contains("missing")));
+    }
+
+    #[tokio::test]
+    async fn removed_pools_are_not_registered() {
+        let pool_id = PoolId::new();
+        let mut replayer = Replayer::new();
+        replayer.apply(&stored(1, &created(pool_id)));
+        replayer.apply(&stored(
+            2,
+            &PoolEvent::PoolRemoved {
+                pool_id,
+                timestamp: Utc::now(),
+            },
+        ));
+        assert_eq!(
+            replayer.report().pools.first().map(|p| p.status),
+            Some("removed")
+        );
+
+        let registry = PoolRegistry::new();
+        let Ok(()) = replayer.finish_into(&registry).await else {
+            panic!("finish failed");
+        };
+        assert_eq!(registry.len().await, 0);
+    }
+
+    #[tokio::test]
+    async fn startup_recovery_reports_what_it_restored() {
+        let Some(db) = crate::persistence::postgres::testing::persistence().await else {
+            return;
+        };
+        let (kept, removed) = (PoolId::new(), PoolId::new());
+        let events = [
+            created(kept),
+            swap(kept, "0xaaa", "1000", "0"),
+            created(removed),
+            PoolEvent::PoolRemoved {
+                pool_id: removed,
+                timestamp: Utc::now(),
+            },
+        ];
+        let rows: Vec<NewEvent> = events
+            .iter()
+            .map(|event| NewEvent {
+                pool_id: *event.pool_id().as_uuid(),
+                event_type: event.event_type_str(),
+                payload: serde_json::to_value(event).unwrap_or_default(),
+                pool_version: None,
+            })
+            .collect();
+        let Ok(()) = db.save_events(&rows).await else {
+            panic!("events not saved");
+        };
+
+        let registry = PoolRegistry::new();
+        let Ok(report) = recover(&db, &registry).await else {
+            panic!("recovery failed");
+        };
+        assert!(report.recovered);
+        assert!(report.completed_at >= report.started_at);
+        assert!(report.last_event_id > 0);
+        assert!(report.replay.events_scanned >= 4);
+        let status = |pool_id| {
+            report
+                .replay
+                .pools
+                .iter()
+                .find(|p| p.pool_id == pool_id)
+                .map(|p| p.status)
+        };
+        assert_eq!(status(kept), Some("active"));
+        assert_eq!(status(removed), Some("removed"));
+        assert!(report.pools_with_status("active") >= 1);
+        assert!(report.pools_with_status("removed") >= 1);
+        assert!(registry.get(kept).await.is_ok());
+        assert!(registry.get(removed).await.is_err());
+    }
+}
@@ -0,0 +1,382 @@
+//! Warm-standby replica: read-only pool copies fed from the event log.
+//!
+//! A replica never accepts mutations (see [`PoolService::with_read_only`]).
+//! It rebuilds its pools from the latest snapshots and the event log at
+//! startup, then tails the log by row id, applying each new event to its
+//! registry and republishing it on the local event bus so quotes, reads,
+//! and WebSocket subscriptions served by the replica follow the primary.
+//!
+//! The log is written by the primary's single event-log task, so row ids
+//! grow in commit order and tailing by id misses nothing. Replication lag
+//! is bounded by the event-log write latency plus the poll interval.
+//!
+//! In a cluster the same follower keeps copies of the pools owned by the
+//! other instances (see [`ReplicaFollower::peer_pools_only`]), which
+//! non-owners serve reads from.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use super::PoolService;
+use super::pool_service::build_entry;
+use super::replay::{self, StartupReport, event_operation, event_timestamp};
+use super::snapshot;
+use crate::cluster::ClusterMembership;
+use crate::domain::pool_entry::PoolStatus;
+use crate::domain::{PoolEvent, PoolId};
+use crate::error::GatewayError;
+use crate::persistence::models::StoredEvent;
+use crate::persistence::postgres::PostgresPersistence;
+
+/// Events fetched per poll.
+const BATCH_SIZE: i64 = 1_000;
+
+/// Outcome of applying one replicated event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Replicated {
+    /// The event changed the local copy.
+    Applied,
+    /// The event carries no state change (or was already reflected).
+    Ignored,
+    /// The event could not be reproduced locally.
+    Skipped(&'static str),
+}
+
+/// Follows the event log into a read-only [`PoolService`].
+#[derive(Debug)]
+pub struct ReplicaFollower {
+    pool_service: Arc<PoolService>,
+    db: PostgresPersistence,
+    poll_interval: Duration,
+    last_event_id: i64,
+    peers_of: Option<Arc<ClusterMembership>>,
+}
+
+impl ReplicaFollower {
+    /// Creates a follower that polls the log every `poll_interval`.
+    #[must_use]
+    pub fn new(
+        pool_service: Arc<PoolService>,
+        db: PostgresPersistence,
+        poll_interval: Duration,
+    ) -> Self {
+        Self {
+            pool_service,
+            db,
+            poll_interval,
+            last_event_id: 0,
+            peers_of: None,
+        }
+    }
+
+    /// Follows only the pools `cluster` assigns to other instances,
+    /// ignoring the ones this instance owns and writes itself.
+    #[must_use]
+    pub fn peer_pools_only(mut self, cluster: Arc<ClusterMembership>) -> Self {
+        self.peers_of = Some(cluster);
+        self
+    }
+
+    fn follows(&self, pool_id: PoolId) -> bool {
+        self.peers_of
+            .as_ref()
+            .is_none_or(|cluster| !cluster.is_local(pool_id))
+    }
+
+    /// Id of the last event log row consumed.
+    #[must_use]
+    pub const fn last_event_id(&self) -> i64 {
+        self.last_event_id
+    }
+
+    /// Rebuilds every pool from the latest snapshots and the full event
+    /// log into the (empty) local registry, returning what was restored.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] if snapshots or
+    /// events cannot be loaded.
+    pub async fn bootstrap(&mut self) -> Result<StartupReport, GatewayError> {
+        let report = replay::recover(&self.db, self.pool_service.registry()).await?;
+        self.last_event_id = report.last_event_id;
+        self.pool_service
+            .registry()
+            .retain(|pool_id| self.follows(pool_id))
+            .await;
+        Ok(report)
+    }
+
+    /// Applies every event written since the last poll. Returns the
+    /// number of events consumed.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError::PersistenceError`] if events cannot be
+    /// loaded.
+    pub async fn poll(&mut self) -> Result<usize, GatewayError> {
+        let batch = self
+            .db
+            .load_events_after_id(self.last_event_id, BATCH_SIZE)
+            .await?;
+        for stored in &batch {
+            self.last_event_id = stored.id;
+            self.consume(stored).await;
+        }
+        Ok(batch.len())
+    }
+
+    /// Starts the tailing loop. The task runs until the process exits.
+    pub fn spawn(mut self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match self.poll().await {
+                    Ok(n) if n as i64 >= BATCH_SIZE => continue,
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!(error = %e, "replica poll failed"),
+                }
+                tokio::time::sleep(self.poll_interval).await;
+            }
+        })
+    }
+
+    async fn consume(&self, stored: &StoredEvent) {
+        // Imported history describes the pool's past on another venue.
+        if stored.imported
+            || !self.follows(PoolId::from_uuid(stored.pool_id))
+            || self.already_applied(stored).await
+        {
+            return;
+        }
+        let event = match stored.to_pool_event() {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::warn!(event_id = stored.id, error = %e, "replica skipped unreadable event");
+                return;
+            }
+        };
+        let outcome = match &event {
+            PoolEvent::PoolRestored { snapshot_id, .. } => {
+                let version = stored.pool_version.and_then(|v| u64::try_from(v).ok());
+                self.restore(event.pool_id(), *snapshot_id, version).await
+            }
+            _ => apply_replicated(&self.pool_service, &event).await,
+        };
+        match outcome {
+            Replicated::Applied | Replicated::Ignored => {
+                self.pool_service.publish(event);
+            }
+            Replicated::Skipped(reason) => {
+                tracing::warn!(
+                    event_id = stored.id,
+                    pool_id = %event.pool_id(),
+                    reason,
+                    "replica could not apply event"
+                );
+            }
+        }
+    }
+
+    /// Returns `true` if the local copy already reached the version
+    /// `stored` left its pool at, as when a retried batch logged it twice.
+    async fn already_applied(&self, stored: &StoredEvent) -> bool {
+        let Some(version) = stored.pool_version.and_then(|v| u64::try_from(v).ok()) else {
+            return false;
+        };
+        let pool_id = PoolId::from_uuid(stored.pool_id);
+        let Ok(entry_lock) = self.pool_service.registry().get(pool_id).await else {
+            return false;
+        };
+        entry_lock.read().await.version() >= version
+    }
+
+    /// Replaces the local copy of a pool restored on the primary with the
+    /// snapshot it was restored from, at the version the restore logged.
+    async fn restore(&self, pool_id: PoolId, snapshot_id: i64, version: Option<u64>) -> Replicated {
+        let Ok(entry_lock) = self.pool_service.registry().get(pool_id).await else {
+            return Replicated::Skipped("unknown pool");
+        };
+        let snapshot = match self.db.load_snapshot(snapshot_id).await {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => return Replicated::Skipped("restored snapshot not found"),
+            Err(_) => return Replicated::Skipped("restored snapshot unreadable"),
+        };
+        match snapshot::decode(&snapshot) {
+            Ok(mut restored) => {
+                if let Some(version) = version {
+                    let journaled = u64::try_from(restored.journal.len()).unwrap_or(u64::MAX);
+                    restored.version_base = version.saturating_sub(journaled);
+                }
+                *entry_lock.write().await = restored;
+                Replicated::Applied
+            }
+            Err(_) => Replicated::Skipped("restored snapshot rejected"),
+        }
+    }
+}
+
+/// Applies one event from the primary to the local registry of
+/// `pool_service`, bypassing its read-only guard. Pool restores need the
+/// snapshot store and are applied by [`ReplicaFollower`] instead.
+pub async fn apply_replicated(pool_service: &PoolService, event: &PoolEvent) -> Replicated {
+    let registry = pool_service.registry();
+    let pool_id: PoolId = event.pool_id();
+    match event {
+        PoolEvent::PoolCreated {
+            pool_type,
+            config,
+            metadata,
+            owner,
+            timestamp,
+            ..
+        } => {
+            if registry.get(pool_id).await.is_ok() {
+                return Replicated::Ignored;
+            }
+            if config.is_null() {
+                return Replicated::Skipped("creation config not recorded");
+            }
+            let Ok(mut entry) = build_entry(pool_id, pool_type, config.clone()) else {
+                return Replicated::Skipped("creation config rejected");
+            };
+            entry.created_at = *timestamp;
+            entry.last_modified_at = *timestamp;
+            entry.metadata = metadata.clone();
+            entry.owner = owner.clone();
+            match registry.insert(entry).await {
+                Ok(_) => Replicated::Applied,
+                Err(_) => Replicated::Ignored,
+            }
+        }
+        PoolEvent::PoolRemoved { .. } => match registry.remove(pool_id).await {
+            Ok(_) => Replicated::Applied,
+            Err(_) => Replicated::Ignored,
+        },
+        PoolEvent::PoolArchived { timestamp, .. } => {
+            let Ok(entry_lock) = registry.get(pool_id).await else {
+                return Replicated::Skipped("unknown pool");
+            };
+            let mut entry = entry_lock.write().await;
+            entry.status = PoolStatus::Archived;
+            entry.archived_at = Some(*timestamp);
+            Replicated::Applied
+        }
+        PoolEvent::PoolStatusChanged { status, .. } => {
+            let Ok(entry_lock) = registry.get(pool_id).await else {
+                return Replicated::Skipped("unknown pool");
+            };
+            entry_lock.write().await.status = *status;
+            Replicated::Applied
+        }
+        PoolEvent::PoolMetadataUpdated { metadata, .. } => {
+            let Ok(entry_lock) = registry.get(pool_id).await else {
+                return Replicated::Skipped("unknown pool");
+            };
+            entry_lock.write().await.metadata = metadata.clone();
+            Replicated::Applied
+        }
+        PoolEvent::PoolOwnershipTransferred { new_owner, .. } => {
+            let Ok(entry_lock) = registry.get(pool_id).await else {
+                return Replicated::Skipped("unknown pool");
+            };
+            entry_lock.write().await.owner = Some(new_owner.clone());
+            Replicated::Applied
+        }
+        PoolEvent::PoolRestored { .. } => Replicated::Skipped("snapshot store required"),
+        _ => {
+            let Ok(entry_lock) = registry.get(pool_id).await else {
+                return Replicated::Skipped("unknown pool");
+            };
+            let mut entry = entry_lock.write().await;
+            let op = match event_operation(&entry, event) {
+                Ok(Some(op)) => op,
+                Ok(None) => return Replicated::Ignored,
+                Err(reason) => return Replicated::Skipped(reason),
+            };
+            match entry.apply(&op) {
+                Ok(_) => {
+                    entry.last_modified_at = event_timestamp(event);
+                    Replicated::Applied
+                }
+                Err(_) => Replicated::Skipped("operation rejected"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::pool_operation::SwapKind;
+    use crate::domain::{EventBus, PoolRegistry};
+    use chrono::Utc;
+
+    #[tokio::test]
+    async fn replicated_events_update_a_read_only_service() {
+        let service =
+            PoolService::new(Arc::new(PoolRegistry::new()), EventBus::new(16)).with_read_only();
+        let pool_id = PoolId::new();
+        let created = PoolEvent::PoolCreated {
+            pool_id,
+            pool_type: "constant_product".to_string(),
+            token_a: "0xaaa".to_string(),
+            token_b: "0xbbb".to_string(),
+            fee_tier: 30,
+            config: serde_json::json!({
+                "token_a": {"address": "0xaaa", "decimals": 6},
+                "token_b": {"address": "0xbbb", "decimals": 6},
+                "fee_bps": 30,
+                "reserve_a": "1000000",
+                "reserve_b": "1000000",
+            }),
+            metadata: serde_json::Value::Null,
+            owner: None,
+            timestamp: Utc::now(),
+        };
+        assert_eq!(
+            apply_replicated(&service, &created).await,
+            Replicated::Applied
+        );
+        assert_eq!(
+            apply_replicated(&service, &created).await,
+            Replicated::Ignored
+        );
+
+        let swap = PoolEvent::SwapExecuted {
+            pool_id,
+            command_id: "cmd".to_string(),
+            amount_in: "1000".to_string(),
+            amount_out: "0".to_string(),
+            fee: "0".to_string(),
+            fee_rebate: "0".to_string(),
+            new_price: "1".to_string(),
+            price_change_bps: 0,
+            token_in: "0xaaa".to_string(),
+            swap_kind: SwapKind::ExactIn,
+            actor: None,
+            timestamp: Utc::now(),
+        };
+        assert_eq!(apply_replicated(&service, &swap).await, Replicated::Applied);
+        let Ok(entry) = service.registry().get(pool_id).await else {
+            panic!("replicated pool missing");
+        };
+        assert_eq!(entry.read().await.swap_count, 1);
+
+        assert!(matches!(
+            service.remove_pool(pool_id).await,
+            Err(GatewayError::ReadOnlyReplica)
+        ));
+
+        let removed = PoolEvent::PoolRemoved {
+            pool_id,
+            timestamp: Utc::now(),
+        };
+        assert_eq!(
+            apply_replicated(&service, &removed).await,
+            Replicated::Applied
+        );
+        assert!(service.registry().get(pool_id).await.is_err());
+    }
+}
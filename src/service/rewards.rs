@@ -0,0 +1,447 @@
+//! Liquidity-mining rewards.
+//!
+//! Admins give a pool an [`EmissionSchedule`]: a reward token emitted at a
+//! fixed rate over a time window. Emissions are shared between the pool's
+//! positions in proportion to the liquidity each held over time, using a
+//! reward-per-liquidity accumulator that advances whenever liquidity
+//! changes. The tracker is seeded from the position registry at startup
+//! and then follows `liquidity_changed` events; rewards are credited to
+//! the client that opened each position.
+//!
+//! Schedules and unclaimed balances live in memory. Claiming pays out
+//! everything accrued to a client and emits `RewardsClaimed` per pool.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use super::PoolService;
+use crate::domain::pool_event::LiquidityChangeType;
+use crate::domain::{PoolEvent, PoolId, PositionId};
+
+/// Fixed-point scale of the reward-per-liquidity accumulator.
+const ACC_SCALE: u128 = 1_000_000_000_000;
+
+/// Rewards emitted to a pool's liquidity providers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmissionSchedule {
+    /// Address of the reward token.
+    pub reward_token: String,
+    /// Reward units emitted per second, shared by all positions.
+    pub rate_per_second: u128,
+    /// Start of emissions.
+    pub starts_at: DateTime<Utc>,
+    /// End of emissions; `None` emits until the schedule is replaced.
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+impl EmissionSchedule {
+    /// Rewards emitted between `from` and `to`, counted in milliseconds.
+    #[must_use]
+    pub fn emitted(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> u128 {
+        let from = from.max(self.starts_at);
+        let to = self.ends_at.map_or(to, |end| to.min(end));
+        let millis = u128::try_from((to - from).num_milliseconds()).unwrap_or(0);
+        self.rate_per_second.saturating_mul(millis) / 1_000
+    }
+}
+
+/// Rewards accrued to one client in one pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccruedReward {
+    /// Pool the rewards were earned in.
+    pub pool_id: PoolId,
+    /// Address of the reward token.
+    pub reward_token: String,
+    /// Unclaimed reward units.
+    pub amount: u128,
+    /// Positions of the client in the pool.
+    pub positions: Vec<PositionId>,
+}
+
+/// A position's share of a pool's emissions.
+#[derive(Debug, Clone, Default)]
+struct Stake {
+    owner: Option<String>,
+    liquidity: u128,
+    /// Accumulator value the position was last settled at.
+    checkpoint: u128,
+    /// Settled, unclaimed rewards by reward token.
+    pending: BTreeMap<String, u128>,
+}
+
+/// Reward state of one pool.
+#[derive(Debug, Clone)]
+struct PoolRewards {
+    schedule: Option<EmissionSchedule>,
+    /// Rewards per unit of liquidity since the schedule was set, scaled
+    /// by [`ACC_SCALE`].
+    acc_per_liquidity: u128,
+    updated_at: DateTime<Utc>,
+    stakes: BTreeMap<PositionId, Stake>,
+}
+
+impl PoolRewards {
+    fn new(at: DateTime<Utc>) -> Self {
+        Self {
+            schedule: None,
+            acc_per_liquidity: 0,
+            updated_at: at,
+            stakes: BTreeMap::new(),
+        }
+    }
+
+    /// Accumulator value at `now`, without recording it.
+    fn acc_at(&self, now: DateTime<Utc>) -> u128 {
+        let total = self
+            .stakes
+            .values()
+            .fold(0u128, |acc, s| acc.saturating_add(s.liquidity));
+        match &self.schedule {
+            Some(schedule) if total > 0 && now > self.updated_at => {
+                let emitted = schedule.emitted(self.updated_at, now);
+                self.acc_per_liquidity
+                    .saturating_add(emitted.saturating_mul(ACC_SCALE) / total)
+            }
+            _ => self.acc_per_liquidity,
+        }
+    }
+
+    /// Advances the accumulator to `now`.
+    fn advance(&mut self, now: DateTime<Utc>) {
+        self.acc_per_liquidity = self.acc_at(now);
+        self.updated_at = self.updated_at.max(now);
+    }
+
+    /// Rewards `stake` earned since its checkpoint, at accumulator `acc`.
+    fn earned(stake: &Stake, acc: u128) -> u128 {
+        stake
+            .liquidity
+            .saturating_mul(acc.saturating_sub(stake.checkpoint))
+            / ACC_SCALE
+    }
+
+    /// Moves what every stake earned into its pending balance. Call after
+    /// [`Self::advance`].
+    fn settle_all(&mut self) {
+        let Some(token) = self.schedule.as_ref().map(|s| s.reward_token.clone()) else {
+            return;
+        };
+        let acc = self.acc_per_liquidity;
+        for stake in self.stakes.values_mut() {
+            let earned = Self::earned(stake, acc);
+            if earned > 0 {
+                let pending = stake.pending.entry(token.clone()).or_default();
+                *pending = pending.saturating_add(earned);
+            }
+            stake.checkpoint = acc;
+        }
+    }
+
+    /// Rewards of `owner` at `now` by token, with their positions.
+    fn accrued(
+        &self,
+        owner: &str,
+        now: DateTime<Utc>,
+    ) -> (BTreeMap<String, u128>, Vec<PositionId>) {
+        let acc = self.acc_at(now);
+        let token = self.schedule.as_ref().map(|s| s.reward_token.as_str());
+        let mut totals: BTreeMap<String, u128> = BTreeMap::new();
+        let mut positions = Vec::new();
+        for (id, stake) in &self.stakes {
+            if stake.owner.as_deref() != Some(owner) {
+                continue;
+            }
+            positions.push(*id);
+            for (reward_token, amount) in &stake.pending {
+                let total = totals.entry(reward_token.clone()).or_default();
+                *total = total.saturating_add(*amount);
+            }
+            if let Some(token) = token {
+                let total = totals.entry(token.to_string()).or_default();
+                *total = total.saturating_add(Self::earned(stake, acc));
+            }
+        }
+        totals.retain(|_, amount| *amount > 0);
+        (totals, positions)
+    }
+}
+
+/// Shared handle to the rewards state of every pool.
+#[derive(Debug, Clone, Default)]
+pub struct RewardsTracker {
+    pools: Arc<Mutex<BTreeMap<PoolId, PoolRewards>>>,
+}
+
+impl RewardsTracker {
+    /// Creates an empty tracker. Call [`Self::spawn`] to start feeding it.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seeds the tracker with the positions in `pool_service`'s registry,
+    /// then starts a task that follows their liquidity changes. Call once
+    /// the registry is recovered. The task ends when the event bus closes.
+    pub fn spawn(&self, pool_service: Arc<PoolService>) -> JoinHandle<()> {
+        let tracker = self.clone();
+        let mut events = pool_service.event_bus().subscribe();
+        tokio::spawn(async move {
+            let now = Utc::now();
+            for entry_lock in pool_service.registry().entries().await {
+                let entry = entry_lock.read().await;
+                for (id, position) in entry.positions.iter() {
+                    tracker.stake(
+                        entry.pool_id,
+                        *id,
+                        position.owner.clone(),
+                        position.liquidity,
+                        now,
+                    );
+                }
+            }
+            loop {
+                match events.recv().await {
+                    Ok(event) => tracker.record(&event),
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(lagged = n, "rewards tracker lagged behind event bus");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Applies a position's liquidity change or a pool's removal.
+    pub fn record(&self, event: &PoolEvent) {
+        match event {
+            PoolEvent::LiquidityChanged {
+                pool_id,
+                change_type,
+                liquidity_delta,
+                position_id: Some(position_id),
+                actor,
+                timestamp,
+                ..
+            } => {
+                let Ok(delta) = liquidity_delta.parse::<u128>() else {
+                    return;
+                };
+                let mut pools = self.lock();
+                let pool = pools
+                    .entry(*pool_id)
+                    .or_insert_with(|| PoolRewards::new(*timestamp));
+                pool.advance(*timestamp);
+                pool.settle_all();
+                let checkpoint = pool.acc_per_liquidity;
+                let stake = pool.stakes.entry(*position_id).or_insert_with(|| Stake {
+                    owner: actor.clone(),
+                    checkpoint,
+                    ..Stake::default()
+                });
+                stake.liquidity = match change_type {
+                    LiquidityChangeType::Add => stake.liquidity.saturating_add(delta),
+                    LiquidityChangeType::Remove => stake.liquidity.saturating_sub(delta),
+                };
+            }
+            PoolEvent::PoolRemoved { pool_id, .. } => {
+                self.lock().remove(pool_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Tracks `liquidity` held by a position from `at`, replacing what
+    /// was tracked for it.
+    pub fn stake(
+        &self,
+        pool_id: PoolId,
+        position_id: PositionId,
+        owner: Option<String>,
+        liquidity: u128,
+        at: DateTime<Utc>,
+    ) {
+        let mut pools = self.lock();
+        let pool = pools.entry(pool_id).or_insert_with(|| PoolRewards::new(at));
+        pool.advance(at);
+        pool.settle_all();
+        let checkpoint = pool.acc_per_liquidity;
+        let stake = pool.stakes.entry(position_id).or_default();
+        stake.owner = owner;
+        stake.liquidity = liquidity;
+        stake.checkpoint = checkpoint;
+    }
+
+    /// Sets or clears the emission schedule of `pool_id` from `now`.
+    /// Rewards accrued under the previous schedule stay claimable.
+    pub fn set_schedule(
+        &self,
+        pool_id: PoolId,
+        schedule: Option<EmissionSchedule>,
+        now: DateTime<Utc>,
+    ) {
+        let mut pools = self.lock();
+        let pool = pools
+            .entry(pool_id)
+            .or_insert_with(|| PoolRewards::new(now));
+        pool.advance(now);
+        pool.settle_all();
+        pool.schedule = schedule;
+        pool.acc_per_liquidity = 0;
+        for stake in pool.stakes.values_mut() {
+            stake.checkpoint = 0;
+        }
+    }
+
+    /// Returns every configured schedule, by pool.
+    #[must_use]
+    pub fn schedules(&self) -> Vec<(PoolId, EmissionSchedule)> {
+        self.lock()
+            .iter()
+            .filter_map(|(id, pool)| pool.schedule.clone().map(|s| (*id, s)))
+            .collect()
+    }
+
+    /// Returns the rewards accrued to `owner` at `now`, by pool and
+    /// reward token.
+    #[must_use]
+    pub fn accrued(&self, owner: &str, now: DateTime<Utc>) -> Vec<AccruedReward> {
+        let pools = self.lock();
+        let mut rewards = Vec::new();
+        for (pool_id, pool) in pools.iter() {
+            let (totals, positions) = pool.accrued(owner, now);
+            for (reward_token, amount) in totals {
+                rewards.push(AccruedReward {
+                    pool_id: *pool_id,
+                    reward_token,
+                    amount,
+                    positions: positions.clone(),
+                });
+            }
+        }
+        rewards
+    }
+
+    /// Pays out everything accrued to `owner` at `now`, publishing a
+    /// `RewardsClaimed` event through `pool_service` per pool and reward
+    /// token, and returns it.
+    pub fn claim(
+        &self,
+        owner: &str,
+        now: DateTime<Utc>,
+        pool_service: &PoolService,
+    ) -> Vec<AccruedReward> {
+        let mut pools = self.lock();
+        let mut claimed = Vec::new();
+        for (pool_id, pool) in pools.iter_mut() {
+            pool.advance(now);
+            pool.settle_all();
+            let (totals, positions) = pool.accrued(owner, now);
+            for stake in pool.stakes.values_mut() {
+                if stake.owner.as_deref() == Some(owner) {
+                    stake.pending.clear();
+                }
+            }
+            for (reward_token, amount) in totals {
+                claimed.push(AccruedReward {
+                    pool_id: *pool_id,
+                    reward_token,
+                    amount,
+                    positions: positions.clone(),
+                });
+            }
+        }
+        drop(pools);
+        for reward in &claimed {
+            pool_service.publish(PoolEvent::RewardsClaimed {
+                pool_id: reward.pool_id,
+                reward_token: reward.reward_token.clone(),
+                amount: reward.amount.to_string(),
+                position_ids: reward.positions.clone(),
+                actor: Some(owner.to_string()),
+                timestamp: now,
+            });
+        }
+        claimed
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<PoolId, PoolRewards>> {
+        self.pools.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::{EventBus, PoolRegistry};
+    use chrono::TimeDelta;
+
+    #[test]
+    fn emissions_follow_time_weighted_liquidity() {
+        let tracker = RewardsTracker::new();
+        let (pool_id, alice, bob) = (PoolId::new(), PositionId::new(), PositionId::new());
+        let t0 = Utc::now();
+        let at = |secs: i64| t0 + TimeDelta::seconds(secs);
+        let changed = |position_id, owner: &str, secs| PoolEvent::LiquidityChanged {
+            pool_id,
+            change_type: LiquidityChangeType::Add,
+            amount_a: "0".to_string(),
+            amount_b: "0".to_string(),
+            new_total_liquidity: "0".to_string(),
+            liquidity_delta: "100".to_string(),
+            position_id: Some(position_id),
+            range: None,
+            actor: Some(owner.to_string()),
+            timestamp: at(secs),
+        };
+
+        tracker.record(&changed(alice, "alice", 0));
+        tracker.set_schedule(
+            pool_id,
+            Some(EmissionSchedule {
+                reward_token: "0xrwd".to_string(),
+                rate_per_second: 10,
+                starts_at: t0,
+                ends_at: Some(at(30)),
+            }),
+            t0,
+        );
+        // Alice alone for 10s, then both equally until emissions end.
+        tracker.record(&changed(bob, "bob", 10));
+        let amount = |owner: &str| {
+            tracker
+                .accrued(owner, at(60))
+                .iter()
+                .map(|r| r.amount)
+                .sum::<u128>()
+        };
+        assert_eq!(amount("alice"), 200);
+        assert_eq!(amount("bob"), 100);
+
+        let pool_service = PoolService::new(Arc::new(PoolRegistry::new()), EventBus::new(16));
+        let mut events = pool_service.event_bus().subscribe();
+        let claimed = tracker.claim("alice", at(60), &pool_service);
+        let [reward] = claimed.as_slice() else {
+            panic!("expected one claimed reward");
+        };
+        assert_eq!(
+            (reward.amount, reward.reward_token.as_str()),
+            (200, "0xrwd")
+        );
+        let Ok(PoolEvent::RewardsClaimed {
+            amount: paid,
+            actor,
+            ..
+        }) = events.try_recv()
+        else {
+            panic!("expected a RewardsClaimed event");
+        };
+        assert_eq!((paid.as_str(), actor.as_deref()), ("200", Some("alice")));
+        assert_eq!(amount("alice"), 0);
+        assert_eq!(amount("bob"), 100);
+    }
+}
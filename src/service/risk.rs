@@ -0,0 +1,262 @@
+//! Composite risk scores for routing decisions.
+//!
+//! A pool's score combines up to four factors, each scored from 0 (safe)
+//! to 100 (risky):
+//!
+//! - liquidity depth: whole base tokens tradeable within ±2% of the
+//!   current price on the thinner side, on a log scale from 10^6 tokens
+//!   (0) down to one token (100);
+//! - volatility: annualized realized volatility of the last 24 hours of
+//!   one-minute closes, linear up to 200% (100);
+//! - concentration (CLMM): Herfindahl index of the open positions'
+//!   liquidity, so a pool held by a single provider scores 100;
+//! - oracle staleness (dynamic): time since the oracle price was last
+//!   set, linear up to one hour (100).
+//!
+//! The composite is the weighted mean of the factors that apply and could
+//! be measured, with the weights rescaled to sum to one. The thresholds
+//! are heuristics meant for ranking and gating pools, not a guarantee.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::market_data::DepthChart;
+use super::volatility;
+use crate::persistence::models::CandleRecord;
+
+/// Half-width of the price band depth is measured in, in basis points.
+pub const DEPTH_RANGE_BPS: u32 = 200;
+
+/// Window of the volatility factor.
+pub const VOLATILITY_WINDOW_HOURS: i64 = 24;
+
+/// Depth, in whole base tokens, that scores 0.
+const DEPTH_SAFE_TOKENS: f64 = 1e6;
+
+/// Depth, in whole base tokens, that scores 100.
+const DEPTH_THIN_TOKENS: f64 = 1.0;
+
+/// Annualized volatility that scores 100.
+const VOLATILITY_HIGH: f64 = 2.0;
+
+/// Oracle price age, in seconds, that scores 100.
+const ORACLE_STALE_SECS: f64 = 3_600.0;
+
+/// Composite scores below this are [`RiskLevel::Low`].
+const MEDIUM_FROM: f64 = 33.0;
+
+/// Composite scores from this on are [`RiskLevel::High`].
+const HIGH_FROM: f64 = 66.0;
+
+/// A factor of the risk score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskFactor {
+    /// Liquidity near the current price.
+    LiquidityDepth,
+    /// Recent realized volatility.
+    Volatility,
+    /// How few providers hold the liquidity (CLMM).
+    Concentration,
+    /// Age of the oracle price (dynamic).
+    OracleStaleness,
+}
+
+impl RiskFactor {
+    /// Weight of the factor before rescaling.
+    const fn weight(self) -> f64 {
+        match self {
+            Self::LiquidityDepth => 0.35,
+            Self::Volatility => 0.30,
+            Self::Concentration => 0.15,
+            Self::OracleStaleness => 0.20,
+        }
+    }
+}
+
+/// Coarse band of a composite score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    /// Below 33.
+    Low,
+    /// From 33 to below 66.
+    Medium,
+    /// 66 and above.
+    High,
+}
+
+/// Measured inputs of a score; `None` leaves a factor out.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RiskInputs {
+    /// Whole base tokens tradeable within [`DEPTH_RANGE_BPS`] of the
+    /// price on the thinner side.
+    pub depth_tokens: Option<f64>,
+    /// Annualized realized volatility.
+    pub volatility: Option<f64>,
+    /// Herfindahl index of open position liquidity, from 0 to 1.
+    pub concentration: Option<f64>,
+    /// Seconds since the oracle price was set.
+    pub oracle_age_secs: Option<f64>,
+}
+
+/// One factor's part of a score.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RiskComponent {
+    /// The factor.
+    pub factor: RiskFactor,
+    /// Measured input, in the units of its [`RiskInputs`] field.
+    pub value: f64,
+    /// Factor score from 0 to 100.
+    pub score: f64,
+    /// Share of the composite, after rescaling.
+    pub weight: f64,
+}
+
+/// A composite score with its breakdown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskScore {
+    /// Weighted mean of the component scores, from 0 to 100 (0 without
+    /// components).
+    pub score: f64,
+    /// Band of `score`.
+    pub level: RiskLevel,
+    /// Factors that contributed.
+    pub components: Vec<RiskComponent>,
+}
+
+impl RiskScore {
+    /// Scores `inputs`.
+    #[must_use]
+    pub fn of(inputs: &RiskInputs) -> Self {
+        let measured = [
+            (RiskFactor::LiquidityDepth, inputs.depth_tokens),
+            (RiskFactor::Volatility, inputs.volatility),
+            (RiskFactor::Concentration, inputs.concentration),
+            (RiskFactor::OracleStaleness, inputs.oracle_age_secs),
+        ];
+        let total_weight: f64 = measured
+            .iter()
+            .filter(|(_, value)| value.is_some())
+            .map(|(factor, _)| factor.weight())
+            .sum();
+        let components: Vec<RiskComponent> = measured
+            .into_iter()
+            .filter_map(|(factor, value)| {
+                let value = value?;
+                let score = match factor {
+                    RiskFactor::LiquidityDepth => depth_score(value),
+                    RiskFactor::Volatility => ramp(value / VOLATILITY_HIGH),
+                    RiskFactor::Concentration => ramp(value),
+                    RiskFactor::OracleStaleness => ramp(value / ORACLE_STALE_SECS),
+                };
+                Some(RiskComponent {
+                    factor,
+                    value,
+                    score,
+                    weight: factor.weight() / total_weight,
+                })
+            })
+            .collect();
+        let score = components.iter().map(|c| c.score * c.weight).sum::<f64>();
+        let level = if score >= HIGH_FROM {
+            RiskLevel::High
+        } else if score >= MEDIUM_FROM {
+            RiskLevel::Medium
+        } else {
+            RiskLevel::Low
+        };
+        Self {
+            score,
+            level,
+            components,
+        }
+    }
+}
+
+/// Maps a fraction of the risky threshold to a 0–100 score.
+fn ramp(fraction: f64) -> f64 {
+    if fraction.is_nan() {
+        return 100.0;
+    }
+    (fraction * 100.0).clamp(0.0, 100.0)
+}
+
+/// Scores depth on a log scale between the thin and safe thresholds.
+fn depth_score(tokens: f64) -> f64 {
+    if tokens <= DEPTH_THIN_TOKENS {
+        return 100.0;
+    }
+    let span = DEPTH_SAFE_TOKENS.log10() - DEPTH_THIN_TOKENS.log10();
+    ramp((DEPTH_SAFE_TOKENS.log10() - tokens.log10()) / span)
+}
+
+/// Whole base tokens tradeable within the chart's range on its thinner
+/// side; `base_decimals` of `None` counts raw units.
+#[must_use]
+pub fn depth_tokens(chart: &DepthChart, base_decimals: Option<u8>) -> f64 {
+    let reach = |levels: &[super::market_data::DepthLevel]| {
+        levels.last().map_or(0, |level| level.base_amount)
+    };
+    let thinner = reach(&chart.bids).min(reach(&chart.asks));
+    thinner as f64 / 10f64.powi(i32::from(base_decimals.unwrap_or(0)))
+}
+
+/// Annualized volatility of `candles` (oldest first) over the factor's
+/// window, from their closes.
+#[must_use]
+pub fn candle_volatility(candles: &[CandleRecord]) -> Option<f64> {
+    let series: Vec<(DateTime<Utc>, f64)> =
+        candles.iter().map(|c| (c.bucket_start, c.close)).collect();
+    volatility::realized(&series, Duration::hours(VOLATILITY_WINDOW_HOURS)).map(|s| s.annualized)
+}
+
+/// Herfindahl index of `liquidity` across positions: the sum of squared
+/// shares, 1 for a single holder. `None` without liquidity.
+#[must_use]
+pub fn concentration(liquidity: impl IntoIterator<Item = u128>) -> Option<f64> {
+    let amounts: Vec<f64> = liquidity
+        .into_iter()
+        .filter(|l| *l > 0)
+        .map(|l| l as f64)
+        .collect();
+    let total: f64 = amounts.iter().sum();
+    (total > 0.0).then(|| amounts.iter().map(|a| (a / total).powi(2)).sum())
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composite_weighs_only_measured_factors() {
+        let calm = RiskScore::of(&RiskInputs {
+            depth_tokens: Some(1e7),
+            volatility: Some(0.0),
+            ..RiskInputs::default()
+        });
+        assert_eq!(calm.level, RiskLevel::Low);
+        assert_eq!(calm.components.len(), 2);
+        let weights: f64 = calm.components.iter().map(|c| c.weight).sum();
+        assert!((weights - 1.0).abs() < 1e-9);
+
+        let thin = RiskScore::of(&RiskInputs {
+            depth_tokens: Some(0.5),
+            concentration: concentration([100, 0]),
+            oracle_age_secs: Some(7_200.0),
+            ..RiskInputs::default()
+        });
+        assert_eq!(thin.level, RiskLevel::High);
+        assert!((thin.score - 100.0).abs() < 1e-9);
+
+        let Some(half) = concentration([50, 50]) else {
+            panic!("liquidity ignored");
+        };
+        assert!((half - 0.5).abs() < 1e-9);
+        assert!(concentration([0]).is_none());
+        assert!((depth_score(1_000.0) - 50.0).abs() < 1e-9);
+        assert_eq!(RiskScore::of(&RiskInputs::default()).score, 0.0);
+    }
+}
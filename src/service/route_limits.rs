@@ -0,0 +1,243 @@
+//! Token-bucket rate limits per route and caller.
+//!
+//! The per-key quotas in [`super::quota`] cap a client's total requests
+//! per minute. These limits shape traffic on individual routes instead:
+//! each caller gets its own bucket per route, holding up to the route's
+//! limit in tokens and refilling at that rate, so a swap route can be held
+//! to a few requests per second while quotes stay generous. Callers are
+//! keyed by `X-Client-Id`, or by peer IP when they send none. A request
+//! finding its bucket empty is answered with
+//! [`GatewayError::RateLimited`] (429) and the time until the next token.
+//!
+//! Limits are per instance.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::error::GatewayError;
+
+/// Buckets retained before full (idle) ones are dropped.
+const MAX_BUCKETS: usize = 10_000;
+
+/// Allowance of one route: `requests` per `period`, all of which may be
+/// spent at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteLimit {
+    /// Bucket capacity, refilled over `period`.
+    pub requests: u32,
+    /// Time to refill an empty bucket.
+    pub period: Duration,
+}
+
+impl RouteLimit {
+    /// Parses `N/s`, `N/m`, or `N/h`; a bare `N` is per second.
+    fn parse(spec: &str) -> Option<Self> {
+        let (requests, unit) = spec.split_once('/').unwrap_or((spec, "s"));
+        let period = match unit.trim() {
+            "s" => Duration::from_secs(1),
+            "m" => Duration::from_secs(60),
+            "h" => Duration::from_secs(3_600),
+            _ => return None,
+        };
+        let requests = requests.trim().parse::<u32>().ok().filter(|n| *n > 0)?;
+        Some(Self { requests, period })
+    }
+
+    fn tokens_per_sec(self) -> f64 {
+        f64::from(self.requests) / self.period.as_secs_f64()
+    }
+}
+
+/// Who a bucket belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Caller {
+    /// A client identified by `X-Client-Id`.
+    Key(String),
+    /// An anonymous client, by peer address.
+    Ip(IpAddr),
+    /// An anonymous client whose address is unknown.
+    Unknown,
+}
+
+impl Caller {
+    /// Keys by `api_key` when given, else by `ip`.
+    #[must_use]
+    pub fn new(api_key: Option<&str>, ip: Option<IpAddr>) -> Self {
+        match (api_key, ip) {
+            (Some(key), _) => Self::Key(key.to_string()),
+            (None, Some(ip)) => Self::Ip(ip),
+            (None, None) => Self::Unknown,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Refills the bucket up to `now`.
+    fn refill(&mut self, limit: RouteLimit, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * limit.tokens_per_sec()).min(f64::from(limit.requests));
+        self.updated = now;
+    }
+}
+
+/// Buckets keyed by route template and caller.
+type Buckets = HashMap<(String, Caller), Bucket>;
+
+/// Route limits and the buckets of every caller.
+#[derive(Debug, Clone, Default)]
+pub struct RouteRateLimiter {
+    /// Limit for routes without their own rule.
+    default_limit: Option<RouteLimit>,
+    /// Rules by route suffix, e.g. `/swap`.
+    rules: Arc<Vec<(String, RouteLimit)>>,
+    buckets: Arc<Mutex<Buckets>>,
+}
+
+impl RouteRateLimiter {
+    /// Parses limits of the form `/swap=10/s,/quote=100/s,*=1200/m`.
+    ///
+    /// A rule applies to every route whose path ends with it, so `/swap`
+    /// covers `/api/v1/pools/{id}/swap`; the longest matching rule wins
+    /// and `*` covers the rest. The leading `/` may be omitted. An empty
+    /// spec disables the limiter.
+    ///
+    /// Malformed entries are skipped.
+    #[must_use]
+    pub fn parse(spec: &str) -> Self {
+        let mut default_limit = None;
+        let mut rules = Vec::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let Some((route, limit)) = entry.split_once('=') else {
+                continue;
+            };
+            let Some(limit) = RouteLimit::parse(limit) else {
+                continue;
+            };
+            match route.trim() {
+                "*" => default_limit = Some(limit),
+                "" => {}
+                route => {
+                    let route = if route.starts_with('/') {
+                        route.to_string()
+                    } else {
+                        format!("/{route}")
+                    };
+                    rules.push((route, limit));
+                }
+            }
+        }
+        Self {
+            default_limit,
+            rules: Arc::new(rules),
+            buckets: Arc::default(),
+        }
+    }
+
+    /// Returns `true` if no route is limited.
+    #[must_use]
+    pub fn is_disabled(&self) -> bool {
+        self.default_limit.is_none() && self.rules.is_empty()
+    }
+
+    /// Returns the limit of the route with path template `route`.
+    #[must_use]
+    pub fn limit_for(&self, route: &str) -> Option<RouteLimit> {
+        self.rules
+            .iter()
+            .filter(|(suffix, _)| route.ends_with(suffix.as_str()))
+            .max_by_key(|(suffix, _)| suffix.len())
+            .map(|(_, limit)| *limit)
+            .or(self.default_limit)
+    }
+
+    /// Takes one token from `caller`'s bucket on `route`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::RateLimited`] with the time until the next
+    /// token if the bucket is empty.
+    pub fn check(&self, route: &str, caller: Caller, now: Instant) -> Result<(), GatewayError> {
+        let Some(limit) = self.limit_for(route) else {
+            return Ok(());
+        };
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        if buckets.len() >= MAX_BUCKETS {
+            self.prune(&mut buckets, now);
+        }
+        let bucket = buckets
+            .entry((route.to_string(), caller))
+            .or_insert_with(|| Bucket {
+                tokens: f64::from(limit.requests),
+                updated: now,
+            });
+        bucket.refill(limit, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        let wait_secs = (1.0 - bucket.tokens) / limit.tokens_per_sec();
+        let retry_after = Duration::try_from_secs_f64(wait_secs).unwrap_or(limit.period);
+        Err(GatewayError::RateLimited {
+            retry_after_ms: u64::try_from(retry_after.as_millis())
+                .unwrap_or(u64::MAX)
+                .max(1),
+        })
+    }
+
+    /// Drops buckets that have refilled completely; a full bucket behaves
+    /// like a missing one.
+    fn prune(&self, buckets: &mut Buckets, now: Instant) {
+        buckets.retain(|(route, _), bucket| {
+            self.limit_for(route).is_some_and(|limit| {
+                bucket.refill(limit, now);
+                bucket.tokens < f64::from(limit.requests)
+            })
+        });
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    const SWAP: &str = "/api/v1/pools/{id}/swap";
+    const QUOTE: &str = "/api/v1/pools/{id}/quote";
+
+    #[test]
+    fn buckets_are_per_route_and_caller_and_refill() {
+        let limiter = RouteRateLimiter::parse("swap=2/s, /quote=100/m, *=50/h, /bad=x/s");
+        assert_eq!(limiter.limit_for(SWAP).map(|l| l.requests), Some(2));
+        assert_eq!(limiter.limit_for(QUOTE).map(|l| l.requests), Some(100));
+        assert_eq!(limiter.limit_for("/health").map(|l| l.requests), Some(50));
+        assert!(RouteRateLimiter::parse("").is_disabled());
+
+        let start = Instant::now();
+        let alice = || Caller::new(Some("alice"), None);
+        assert!(limiter.check(SWAP, alice(), start).is_ok());
+        assert!(limiter.check(SWAP, alice(), start).is_ok());
+        let Err(GatewayError::RateLimited { retry_after_ms }) = limiter.check(SWAP, alice(), start)
+        else {
+            panic!("third swap admitted");
+        };
+        assert_eq!(retry_after_ms, 500);
+
+        // Other routes and callers have their own buckets.
+        assert!(limiter.check(QUOTE, alice(), start).is_ok());
+        let ip = "10.0.0.1".parse().ok();
+        assert!(limiter.check(SWAP, Caller::new(None, ip), start).is_ok());
+
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.check(SWAP, alice(), later).is_ok());
+        assert!(limiter.check(SWAP, alice(), later).is_err());
+    }
+}
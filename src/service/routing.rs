@@ -0,0 +1,874 @@
+//! Best-route search across pools.
+//!
+//! Routes are quoted against sandbox pools rebuilt from each pool's
+//! creation config and journal ([`PoolSeed`]), so searching never touches
+//! live state. The search enumerates simple paths between the two tokens
+//! (up to a hop limit), keeps the path with the best output, and — when
+//! splitting is allowed — greedily spreads the input across up to
+//! [`MAX_SPLIT_LEGS`] pool-disjoint paths in [`SPLIT_STEPS`] chunks,
+//! adopting the split only if it beats the best single path.
+//!
+//! Every hop records the fee it charged and its price impact, and
+//! [`RoutePlan::totals`] decomposes the route's loss against spot into a
+//! fee part and an impact part.
+//!
+//! Candidates are ranked by output net of a [`CostModel`] charge, so a
+//! route with more hops only wins when its extra output covers the extra
+//! execution overhead.
+//!
+//! Canary pools carry a routing weight; [`admit_canaries`] leaves one
+//! out of a search unless a per-search draw falls below its weight, so a
+//! new pool configuration only sees a limited share of routed flow until
+//! it is promoted.
+
+use crate::domain::{Journal, PoolId};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::domain::pool_entry::{OperationOutcome, PoolEntry, pro_rata};
+use crate::domain::pool_operation::{PoolOperation, SwapKind, TokenSide};
+use crate::error::GatewayError;
+
+use super::pool_service::build_entry;
+use super::pricing::{self, TradeDecimals, ratio_bps};
+
+/// Upper bound accepted for `max_hops`.
+pub const MAX_HOPS_LIMIT: usize = 4;
+
+/// Hop limit used when the caller does not specify one.
+pub const DEFAULT_MAX_HOPS: usize = 3;
+
+/// Maximum number of paths considered per search.
+const MAX_CANDIDATE_PATHS: usize = 64;
+
+/// Maximum number of legs in a split route.
+pub const MAX_SPLIT_LEGS: usize = 3;
+
+/// Number of chunks the input is divided into when splitting.
+pub const SPLIT_STEPS: u128 = 10;
+
+/// Canary weight admitting every route, in basis points.
+pub const FULL_CANARY_WEIGHT_BPS: u32 = 10_000;
+
+/// Everything needed to rebuild a pool outside the registry.
+#[derive(Debug, Clone)]
+pub struct PoolSeed {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    pool_type: String,
+    config_json: serde_json::Value,
+    journal: Journal,
+    version_base: u64,
+    labels: [String; 2],
+    decimals: [Option<u8>; 2],
+    /// Canary routing weight in basis points (`None` if promoted).
+    pub canary_weight_bps: Option<u32>,
+}
+
+impl PoolSeed {
+    /// Captures `entry`, or returns `None` if it has no creation config
+    /// (programmatic pools cannot be sandboxed).
+    #[must_use]
+    pub fn of(entry: &PoolEntry) -> Option<Self> {
+        if entry.config_json.is_null() {
+            return None;
+        }
+        Some(Self {
+            pool_id: entry.pool_id,
+            pool_type: entry.pool_type.clone(),
+            config_json: entry.config_json.clone(),
+            journal: entry.journal.clone(),
+            version_base: entry.version_base,
+            labels: [
+                entry.token_label(TokenSide::First),
+                entry.token_label(TokenSide::Second),
+            ],
+            decimals: [
+                entry.decimals_of(TokenSide::First),
+                entry.decimals_of(TokenSide::Second),
+            ],
+            canary_weight_bps: entry.canary_weight_bps,
+        })
+    }
+
+    /// Rebuilds the pool in its current state.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError`] if the config or journal no longer
+    /// applies.
+    pub fn build(&self) -> Result<PoolEntry, GatewayError> {
+        let mut entry = build_entry(self.pool_id, &self.pool_type, self.config_json.clone())?;
+        for op in self.journal.iter() {
+            entry.apply(op)?;
+        }
+        entry.version_base = self.version_base;
+        Ok(entry)
+    }
+
+    /// Address label of one side of the pair.
+    #[must_use]
+    pub fn label(&self, side: TokenSide) -> &str {
+        let [first, second] = &self.labels;
+        match side {
+            TokenSide::First => first,
+            TokenSide::Second => second,
+        }
+    }
+
+    /// Decimals of a trade that sells the `side` token.
+    #[must_use]
+    pub fn trade_decimals(&self, side: TokenSide) -> TradeDecimals {
+        let [first, second] = self.decimals;
+        match side {
+            TokenSide::First => TradeDecimals {
+                input: first,
+                output: second,
+            },
+            TokenSide::Second => TradeDecimals {
+                input: second,
+                output: first,
+            },
+        }
+    }
+
+    /// Side whose label matches `address` (case-insensitive).
+    #[must_use]
+    pub fn side_of(&self, address: &str) -> Option<TokenSide> {
+        [TokenSide::First, TokenSide::Second]
+            .into_iter()
+            .find(|side| self.label(*side).eq_ignore_ascii_case(address))
+    }
+}
+
+/// Search options.
+#[derive(Debug, Clone, Copy)]
+pub struct RouteOptions {
+    /// Maximum number of hops per leg.
+    pub max_hops: usize,
+    /// Whether the input may be split across several paths.
+    pub allow_split: bool,
+}
+
+impl Default for RouteOptions {
+    fn default() -> Self {
+        Self {
+            max_hops: DEFAULT_MAX_HOPS,
+            allow_split: true,
+        }
+    }
+}
+
+/// Estimates the execution overhead of a route, in units of its output
+/// token, so routes can be compared on net output.
+pub trait CostModel: fmt::Debug + Send + Sync {
+    /// Overhead of executing `leg`.
+    fn leg_cost(&self, leg: &RouteLeg) -> u128;
+}
+
+/// Charges a fixed amount for every hop. The default charges nothing,
+/// which ranks routes on raw output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerHopCost {
+    /// Cost of one hop, in output-token units.
+    pub per_hop: u128,
+}
+
+impl CostModel for PerHopCost {
+    fn leg_cost(&self, leg: &RouteLeg) -> u128 {
+        self.per_hop.saturating_mul(leg.hops.len() as u128)
+    }
+}
+
+/// One swap along a route.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteHop {
+    /// Pool the hop trades on.
+    pub pool_id: PoolId,
+    /// Input token address.
+    pub token_in: String,
+    /// Output token address.
+    pub token_out: String,
+    /// Amount sent into the pool.
+    pub amount_in: u128,
+    /// Amount received from the pool.
+    pub amount_out: u128,
+    /// Fee charged, in `token_in` units.
+    pub fee: u128,
+    /// Spot price of `token_in` in `token_out` before the hop, per whole
+    /// token, if the pool can price.
+    pub spot_price: Option<f64>,
+    /// Decimals of `token_in` and `token_out`.
+    pub decimals: TradeDecimals,
+    /// Deviation of the fee-exclusive execution price from spot, in
+    /// basis points (negative means less output than spot).
+    pub price_impact_bps: i32,
+}
+
+impl RouteHop {
+    /// Whole `token_out` received per whole `token_in`.
+    #[must_use]
+    pub fn execution_price(&self) -> Option<f64> {
+        pricing::execution_price(self.amount_in, self.amount_out, self.decimals)
+    }
+
+    /// Raw output at spot price, ignoring fee and impact.
+    fn spot_out(&self) -> Option<f64> {
+        self.spot_price
+            .map(|p| pricing::amount_at_price(self.amount_in, p, self.decimals))
+    }
+
+    /// Raw output at spot price after the fee, ignoring impact.
+    fn fee_only_out(&self) -> Option<f64> {
+        self.spot_price.map(|p| {
+            pricing::amount_at_price(self.amount_in.saturating_sub(self.fee), p, self.decimals)
+        })
+    }
+}
+
+/// A sequence of hops carrying part (or all) of the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteLeg {
+    /// Hops in execution order.
+    pub hops: Vec<RouteHop>,
+    /// Input routed through this leg.
+    pub amount_in: u128,
+    /// Output of the final hop.
+    pub amount_out: u128,
+}
+
+/// A complete route: one leg, or several pool-disjoint legs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoutePlan {
+    /// Input token address.
+    pub token_in: String,
+    /// Output token address.
+    pub token_out: String,
+    /// Total input.
+    pub amount_in: u128,
+    /// Total output across legs.
+    pub amount_out: u128,
+    /// Execution overhead charged by the cost model, in output-token
+    /// units.
+    pub execution_cost: u128,
+    /// Legs of the route.
+    pub legs: Vec<RouteLeg>,
+}
+
+/// Aggregate cost of a route relative to spot prices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteTotals {
+    /// Number of hops across all legs.
+    pub hop_count: usize,
+    /// Fees charged per token address.
+    pub fees: BTreeMap<String, u128>,
+    /// Output the route would give at pre-trade spot prices with no fees
+    /// or impact (`None` if a hop cannot price).
+    pub spot_amount_out: Option<u128>,
+    /// Share of spot value paid in fees, in basis points.
+    pub fee_bps: i32,
+    /// Loss from price impact after fees, in basis points (negative means
+    /// less output than spot).
+    pub price_impact_bps: i32,
+}
+
+impl RoutePlan {
+    /// Aggregates per-hop fees and decomposes the loss against spot into
+    /// fee and impact components.
+    #[must_use]
+    pub fn totals(&self) -> RouteTotals {
+        let mut fees: BTreeMap<String, u128> = BTreeMap::new();
+        let mut hop_count = 0;
+        let (mut spot_out, mut fee_only_out) = (Some(0.0), Some(0.0));
+        for leg in &self.legs {
+            // Chain spot ratios along the leg: output per unit of input.
+            let (mut spot_ratio, mut fee_ratio) = (Some(1.0), Some(1.0));
+            for hop in &leg.hops {
+                hop_count += 1;
+                let fee = fees.entry(hop.token_in.clone()).or_insert(0);
+                *fee = fee.saturating_add(hop.fee);
+                let input = hop.amount_in as f64;
+                spot_ratio = spot_ratio.zip(hop.spot_out()).map(|(r, o)| r * o / input);
+                fee_ratio = fee_ratio
+                    .zip(hop.fee_only_out())
+                    .map(|(r, o)| r * o / input);
+            }
+            let leg_in = leg.amount_in as f64;
+            spot_out = spot_out.zip(spot_ratio).map(|(t, r)| t + r * leg_in);
+            fee_only_out = fee_only_out.zip(fee_ratio).map(|(t, r)| t + r * leg_in);
+        }
+
+        let (fee_bps, price_impact_bps) = match (spot_out, fee_only_out) {
+            (Some(spot), Some(fee_only)) if spot > 0.0 && fee_only > 0.0 => (
+                -ratio_bps(fee_only, spot),
+                ratio_bps(self.amount_out as f64, fee_only),
+            ),
+            _ => (0, 0),
+        };
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let spot_amount_out = spot_out.map(|v| v as u128);
+        RouteTotals {
+            hop_count,
+            fees,
+            spot_amount_out,
+            fee_bps,
+            price_impact_bps,
+        }
+    }
+
+    /// Output after subtracting the execution cost.
+    #[must_use]
+    pub fn net_amount_out(&self) -> u128 {
+        self.amount_out.saturating_sub(self.execution_cost)
+    }
+
+    /// Returns `true` if the input is split across more than one leg.
+    #[must_use]
+    pub fn is_split(&self) -> bool {
+        self.legs.len() > 1
+    }
+
+    /// Re-quotes every leg at its current amount against `seeds`. The
+    /// hops are unchanged, so the execution cost carries over.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError`] if a hop's pool is missing from `seeds`
+    /// or no longer fills.
+    pub fn requote(&self, seeds: &[PoolSeed]) -> Result<Self, GatewayError> {
+        let mut legs = Vec::with_capacity(self.legs.len());
+        for leg in &self.legs {
+            let path = leg
+                .hops
+                .iter()
+                .map(|hop| {
+                    let pool = seeds
+                        .iter()
+                        .position(|s| s.pool_id == hop.pool_id)
+                        .ok_or(GatewayError::PoolNotFound(*hop.pool_id.as_uuid()))?;
+                    let side = seeds
+                        .get(pool)
+                        .and_then(|s| s.side_of(&hop.token_in))
+                        .ok_or_else(|| {
+                            GatewayError::Internal(format!(
+                                "token {} left pool {}",
+                                hop.token_in, hop.pool_id
+                            ))
+                        })?;
+                    Ok(Step { pool, side })
+                })
+                .collect::<Result<Vec<_>, GatewayError>>()?;
+            legs.push(quote_path(seeds, &path, leg.amount_in)?);
+        }
+        let mut requoted = plan(&self.token_in, &self.token_out, self.amount_in, legs);
+        requoted.execution_cost = self.execution_cost;
+        Ok(requoted)
+    }
+}
+
+/// A hop before quoting: pool index into the seed list and input side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Step {
+    pool: usize,
+    side: TokenSide,
+}
+
+/// Removes the canary pools not admitted to this search. `roll_bps` is a
+/// uniform draw below [`FULL_CANARY_WEIGHT_BPS`], taken once per search;
+/// a canary is admitted when the draw falls below its weight, so it sees
+/// about that share of routed flow.
+pub fn admit_canaries(seeds: &mut Vec<PoolSeed>, roll_bps: u32) {
+    seeds.retain(|seed| {
+        seed.canary_weight_bps
+            .is_none_or(|weight| roll_bps < weight)
+    });
+}
+
+/// Finds the route from `token_in` to `token_out` that yields the most
+/// output for `amount_in`, net of the overhead `cost` assigns to it.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] if the tokens are identical
+/// or no path connects them, and [`GatewayError::InsufficientLiquidity`]
+/// if paths exist but none can fill the amount.
+pub fn best_route(
+    seeds: &[PoolSeed],
+    token_in: &str,
+    token_out: &str,
+    amount_in: u128,
+    options: RouteOptions,
+    cost: &dyn CostModel,
+) -> Result<RoutePlan, GatewayError> {
+    if token_in.eq_ignore_ascii_case(token_out) {
+        return Err(GatewayError::InvalidRequest(
+            "token_in and token_out must differ".to_string(),
+        ));
+    }
+    let max_hops = options.max_hops.clamp(1, MAX_HOPS_LIMIT);
+    let paths = candidate_paths(seeds, token_in, token_out, max_hops);
+    if paths.is_empty() {
+        return Err(GatewayError::InvalidRequest(format!(
+            "no route from {token_in} to {token_out}"
+        )));
+    }
+
+    // Quote every path at the full amount, best net output first.
+    let mut quoted: Vec<(Vec<Step>, RouteLeg)> = paths
+        .into_iter()
+        .filter_map(|path| {
+            let leg = quote_path(seeds, &path, amount_in).ok()?;
+            Some((path, leg))
+        })
+        .collect();
+    quoted.sort_by_key(|(_, leg)| {
+        std::cmp::Reverse(leg.amount_out.saturating_sub(cost.leg_cost(leg)))
+    });
+    let Some((_, best)) = quoted.first() else {
+        return Err(GatewayError::InsufficientLiquidity);
+    };
+    let mut single = plan(token_in, token_out, amount_in, vec![best.clone()]);
+    single.execution_cost = cost.leg_cost(best);
+
+    if options.allow_split
+        && let Some(split) = split_route(seeds, &quoted, token_in, token_out, amount_in, cost)
+        && split.net_amount_out() > single.net_amount_out()
+    {
+        return Ok(split);
+    }
+    Ok(single)
+}
+
+/// Enumerates simple paths (no repeated pool or token) of at most
+/// `max_hops` hops.
+fn candidate_paths(
+    seeds: &[PoolSeed],
+    token_in: &str,
+    token_out: &str,
+    max_hops: usize,
+) -> Vec<Vec<Step>> {
+    let mut paths = Vec::new();
+    let mut visited = vec![token_in.to_ascii_lowercase()];
+    let mut path = Vec::new();
+    walk(
+        seeds,
+        token_in,
+        token_out,
+        max_hops,
+        &mut visited,
+        &mut path,
+        &mut paths,
+    );
+    paths
+}
+
+fn walk(
+    seeds: &[PoolSeed],
+    current: &str,
+    target: &str,
+    max_hops: usize,
+    visited: &mut Vec<String>,
+    path: &mut Vec<Step>,
+    paths: &mut Vec<Vec<Step>>,
+) {
+    if path.len() >= max_hops {
+        return;
+    }
+    for (pool, seed) in seeds.iter().enumerate() {
+        if paths.len() >= MAX_CANDIDATE_PATHS {
+            return;
+        }
+        if path.iter().any(|s| s.pool == pool) {
+            continue;
+        }
+        let Some(side) = seed.side_of(current) else {
+            continue;
+        };
+        let next = seed.label(side.other());
+        path.push(Step { pool, side });
+        if next.eq_ignore_ascii_case(target) {
+            paths.push(path.clone());
+        } else if !visited.iter().any(|v| v.eq_ignore_ascii_case(next)) {
+            visited.push(next.to_ascii_lowercase());
+            walk(seeds, next, target, max_hops, visited, path, paths);
+            visited.pop();
+        }
+        path.pop();
+    }
+}
+
+/// Quotes `amount_in` along `path` on fresh sandboxes.
+fn quote_path(
+    seeds: &[PoolSeed],
+    path: &[Step],
+    amount_in: u128,
+) -> Result<RouteLeg, GatewayError> {
+    let mut hops = Vec::with_capacity(path.len());
+    let mut amount = amount_in;
+    for step in path {
+        let seed = seeds
+            .get(step.pool)
+            .ok_or_else(|| GatewayError::Internal("route step out of range".to_string()))?;
+        let hop = quote_hop(seed, step.side, amount)?;
+        amount = hop.amount_out;
+        hops.push(hop);
+    }
+    Ok(RouteLeg {
+        hops,
+        amount_in,
+        amount_out: amount,
+    })
+}
+
+/// Quotes an exact-in swap of `amount_in` of the `side` token on a fresh
+/// sandbox of `seed`.
+///
+/// # Errors
+///
+/// Returns a [`GatewayError`] if the sandbox cannot be rebuilt or the
+/// swap does not fill.
+pub fn quote_hop(
+    seed: &PoolSeed,
+    side: TokenSide,
+    amount_in: u128,
+) -> Result<RouteHop, GatewayError> {
+    let mut sandbox = seed.build()?;
+    let spot_price = sandbox.spot_price_of(side);
+    let op = PoolOperation::Swap {
+        token_in: side,
+        kind: SwapKind::ExactIn,
+        amount: amount_in.to_string(),
+    };
+    let OperationOutcome::Swap(result) = sandbox.apply(&op)? else {
+        return Err(GatewayError::Internal(
+            "swap produced no result".to_string(),
+        ));
+    };
+    let amount_out = result.amount_out().get();
+    let fee = result.fee().get();
+    let decimals = seed.trade_decimals(side);
+    let price_impact_bps =
+        pricing::price_impact_bps(spot_price, amount_in, fee, amount_out, decimals);
+    Ok(RouteHop {
+        pool_id: seed.pool_id,
+        token_in: seed.label(side).to_string(),
+        token_out: seed.label(side.other()).to_string(),
+        amount_in,
+        amount_out,
+        fee,
+        spot_price,
+        decimals,
+        price_impact_bps,
+    })
+}
+
+/// Greedily allocates [`SPLIT_STEPS`] chunks of the input across the
+/// best pool-disjoint paths, always giving the next chunk to the path
+/// with the largest marginal output. Opening a path costs its
+/// [`CostModel`] charge. Returns `None` when fewer than two disjoint
+/// paths exist or the allocation collapses onto one path.
+fn split_route(
+    seeds: &[PoolSeed],
+    quoted: &[(Vec<Step>, RouteLeg)],
+    token_in: &str,
+    token_out: &str,
+    amount_in: u128,
+    cost: &dyn CostModel,
+) -> Option<RoutePlan> {
+    let mut disjoint: Vec<&Vec<Step>> = Vec::new();
+    let mut opening_costs: Vec<u128> = Vec::new();
+    for (path, leg) in quoted {
+        if disjoint.len() >= MAX_SPLIT_LEGS {
+            break;
+        }
+        let overlaps = disjoint
+            .iter()
+            .any(|d| d.iter().any(|a| path.iter().any(|b| a.pool == b.pool)));
+        if !overlaps {
+            disjoint.push(path);
+            opening_costs.push(cost.leg_cost(leg));
+        }
+    }
+    if disjoint.len() < 2 {
+        return None;
+    }
+
+    // Output of each path at k/SPLIT_STEPS of the input, k = 0..=STEPS.
+    let curves: Vec<Vec<Option<u128>>> = disjoint
+        .iter()
+        .map(|path| {
+            (0..=SPLIT_STEPS)
+                .map(|k| {
+                    let amount = pro_rata(amount_in, k, SPLIT_STEPS);
+                    if amount == 0 {
+                        Some(0)
+                    } else {
+                        quote_path(seeds, path, amount).ok().map(|l| l.amount_out)
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut chunks = vec![0usize; disjoint.len()];
+    for _ in 0..SPLIT_STEPS {
+        let mut best: Option<(usize, i128)> = None;
+        for (i, curve) in curves.iter().enumerate() {
+            let taken = chunks.get(i).copied().unwrap_or(0);
+            let (Some(Some(now)), Some(Some(next))) = (curve.get(taken), curve.get(taken + 1))
+            else {
+                continue;
+            };
+            let opening = if taken == 0 {
+                opening_costs.get(i).copied().unwrap_or(0)
+            } else {
+                0
+            };
+            let gain = signed(next.saturating_sub(*now)).saturating_sub(signed(opening));
+            if best.is_none_or(|(_, g)| gain > g) {
+                best = Some((i, gain));
+            }
+        }
+        let (i, _) = best?;
+        if let Some(c) = chunks.get_mut(i) {
+            *c += 1;
+        }
+    }
+
+    // Turn chunk counts into exact amounts; the last used leg absorbs
+    // the rounding remainder so legs sum to the input.
+    let used: Vec<(usize, usize)> = chunks
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|(_, c)| *c > 0)
+        .collect();
+    if used.len() < 2 {
+        return None;
+    }
+    let mut legs = Vec::with_capacity(used.len());
+    let mut remaining = amount_in;
+    for (n, (i, c)) in used.iter().enumerate() {
+        let amount = if n + 1 == used.len() {
+            remaining
+        } else {
+            pro_rata(amount_in, *c as u128, SPLIT_STEPS)
+        };
+        remaining = remaining.saturating_sub(amount);
+        legs.push(quote_path(seeds, disjoint.get(*i)?, amount).ok()?);
+    }
+    let mut split = plan(token_in, token_out, amount_in, legs);
+    split.execution_cost = split
+        .legs
+        .iter()
+        .fold(0u128, |acc, l| acc.saturating_add(cost.leg_cost(l)));
+    Some(split)
+}
+
+/// Converts an amount to `i128` for signed comparisons, saturating.
+fn signed(value: u128) -> i128 {
+    i128::try_from(value).unwrap_or(i128::MAX)
+}
+
+fn plan(token_in: &str, token_out: &str, amount_in: u128, legs: Vec<RouteLeg>) -> RoutePlan {
+    RoutePlan {
+        token_in: token_in.to_string(),
+        token_out: token_out.to_string(),
+        amount_in,
+        amount_out: legs
+            .iter()
+            .fold(0u128, |acc, l| acc.saturating_add(l.amount_out)),
+        execution_cost: 0,
+        legs,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn seed(token_a: &str, token_b: &str, reserve_a: u128, reserve_b: u128) -> PoolSeed {
+        let config = serde_json::json!({
+            "token_a": {"address": token_a, "decimals": 6},
+            "token_b": {"address": token_b, "decimals": 6},
+            "fee_bps": 30,
+            "reserve_a": reserve_a.to_string(),
+            "reserve_b": reserve_b.to_string(),
+        });
+        let Ok(entry) = build_entry(PoolId::new(), "constant_product", config) else {
+            panic!("entry build failed");
+        };
+        let Some(seed) = PoolSeed::of(&entry) else {
+            panic!("entry has no config");
+        };
+        seed
+    }
+
+    #[test]
+    fn canaries_only_join_searches_below_their_weight() {
+        let stable = seed("0xa", "0xb", 1_000_000, 1_000_000);
+        let mut canary = seed("0xa", "0xb", 10_000_000, 10_000_000);
+        canary.canary_weight_bps = Some(2_500);
+        let canary_id = canary.pool_id;
+        let options = RouteOptions::default();
+
+        for (roll, routed_to_canary) in [(0, true), (2_499, true), (2_500, false), (9_999, false)] {
+            let mut seeds = vec![stable.clone(), canary.clone()];
+            admit_canaries(&mut seeds, roll);
+            let Ok(plan) = best_route(
+                &seeds,
+                "0xa",
+                "0xb",
+                10_000,
+                options,
+                &PerHopCost::default(),
+            ) else {
+                panic!("no route");
+            };
+            let uses_canary = plan
+                .legs
+                .iter()
+                .any(|leg| leg.hops.iter().any(|hop| hop.pool_id == canary_id));
+            assert_eq!(uses_canary, routed_to_canary, "roll {roll}");
+        }
+    }
+
+    #[test]
+    fn prefers_multi_hop_when_direct_pool_is_shallow() {
+        let seeds = vec![
+            seed("0xa", "0xc", 1_000, 1_000),
+            seed("0xa", "0xb", 10_000_000, 10_000_000),
+            seed("0xb", "0xc", 10_000_000, 10_000_000),
+        ];
+        let options = RouteOptions {
+            allow_split: false,
+            ..RouteOptions::default()
+        };
+        let Ok(plan) = best_route(
+            &seeds,
+            "0xA",
+            "0xc",
+            10_000,
+            options,
+            &PerHopCost::default(),
+        ) else {
+            panic!("no route");
+        };
+        let Some(leg) = plan.legs.first() else {
+            panic!("empty plan");
+        };
+        assert_eq!(leg.hops.len(), 2);
+        assert_eq!(leg.hops.first().map(|h| h.token_out.as_str()), Some("0xb"));
+        assert_eq!(plan.amount_out, leg.amount_out);
+    }
+
+    #[test]
+    fn hop_cost_favours_shorter_routes() {
+        let seeds = vec![
+            seed("0xa", "0xc", 100_000, 100_000),
+            seed("0xa", "0xb", 10_000_000, 10_000_000),
+            seed("0xb", "0xc", 10_000_000, 10_000_000),
+        ];
+        let options = RouteOptions {
+            allow_split: false,
+            ..RouteOptions::default()
+        };
+        let hops = |cost: &PerHopCost| {
+            let Ok(plan) = best_route(&seeds, "0xa", "0xc", 10_000, options, cost) else {
+                panic!("no route");
+            };
+            plan.legs.first().map_or(0, |l| l.hops.len())
+        };
+        assert_eq!(hops(&PerHopCost::default()), 2);
+
+        let cost = PerHopCost { per_hop: 1_000 };
+        let Ok(plan) = best_route(&seeds, "0xa", "0xc", 10_000, options, &cost) else {
+            panic!("no route");
+        };
+        assert_eq!(plan.legs.first().map(|l| l.hops.len()), Some(1));
+        assert_eq!(plan.execution_cost, 1_000);
+        assert_eq!(plan.net_amount_out(), plan.amount_out - 1_000);
+    }
+
+    #[test]
+    fn totals_split_loss_into_fees_and_impact() {
+        let seeds = vec![
+            seed("0xa", "0xb", 1_000_000, 1_000_000),
+            seed("0xb", "0xc", 1_000_000, 1_000_000),
+        ];
+        let Ok(plan) = best_route(
+            &seeds,
+            "0xa",
+            "0xc",
+            10_000,
+            RouteOptions::default(),
+            &PerHopCost::default(),
+        ) else {
+            panic!("no route");
+        };
+        let totals = plan.totals();
+        assert_eq!(totals.hop_count, 2);
+        assert_eq!(totals.fees.get("0xa"), Some(&30));
+        assert_eq!(totals.fees.get("0xb").map(|f| *f > 0), Some(true));
+        assert_eq!(totals.spot_amount_out, Some(10_000));
+        // Two 30 bps fees compound to just under 60 bps.
+        assert!((59..=60).contains(&totals.fee_bps));
+        assert!(totals.price_impact_bps < 0);
+        for hop in plan.legs.iter().flat_map(|l| l.hops.iter()) {
+            assert!(hop.price_impact_bps <= 0);
+        }
+    }
+
+    #[test]
+    fn splits_across_parallel_pools() {
+        let seeds = vec![
+            seed("0xa", "0xb", 100_000, 100_000),
+            seed("0xa", "0xb", 100_000, 100_000),
+        ];
+        let Ok(plan) = best_route(
+            &seeds,
+            "0xa",
+            "0xb",
+            50_000,
+            RouteOptions::default(),
+            &PerHopCost::default(),
+        ) else {
+            panic!("no route");
+        };
+        assert!(plan.is_split());
+        let routed: u128 = plan.legs.iter().map(|l| l.amount_in).sum();
+        assert_eq!(routed, 50_000);
+
+        let Ok(single) = best_route(
+            &seeds,
+            "0xa",
+            "0xb",
+            50_000,
+            RouteOptions {
+                allow_split: false,
+                ..RouteOptions::default()
+            },
+            &PerHopCost::default(),
+        ) else {
+            panic!("no route");
+        };
+        assert!(plan.amount_out > single.amount_out);
+    }
+
+    #[test]
+    fn unconnected_tokens_have_no_route() {
+        let seeds = vec![seed("0xa", "0xb", 1_000, 1_000)];
+        assert!(matches!(
+            best_route(
+                &seeds,
+                "0xa",
+                "0xz",
+                10,
+                RouteOptions::default(),
+                &PerHopCost::default()
+            ),
+            Err(GatewayError::InvalidRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,169 @@
+//! Ready-built sandboxes for quoting.
+//!
+//! A quote is a real swap, and hydra-amm pools cannot be cloned, so every
+//! quote runs on a throwaway copy of the pool rebuilt from its creation
+//! config and journal. [`SandboxStock`] keeps one such copy per pool built
+//! ahead of time: a quote takes it and brings it up to date by applying
+//! only the operations journaled since it was built, and a replacement is
+//! rebuilt in the background. A quote that finds no copy ready builds its
+//! own.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use super::routing::PoolSeed;
+use crate::domain::PoolId;
+use crate::domain::pool_entry::PoolEntry;
+
+/// Sandboxes built ahead of time, one per pool at most.
+#[derive(Debug, Clone, Default)]
+pub struct SandboxStock {
+    inner: Arc<Mutex<Stock>>,
+}
+
+#[derive(Debug, Default)]
+struct Stock {
+    ready: HashMap<PoolId, PoolEntry>,
+    building: BTreeSet<PoolId>,
+}
+
+impl SandboxStock {
+    /// Creates an empty stock.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes the ready sandbox of `entry`'s pool, brought up to its
+    /// current version, or `None` if there is none or it no longer
+    /// matches the pool.
+    #[must_use]
+    pub fn take(&self, entry: &PoolEntry) -> Option<PoolEntry> {
+        let mut sandbox = self.lock().ready.remove(&entry.pool_id)?;
+        // A restore replaces the journal the sandbox was built from
+        if sandbox.pool_type != entry.pool_type
+            || sandbox.config_json != entry.config_json
+            || sandbox.version_base != entry.version_base
+        {
+            return None;
+        }
+        let built = sandbox.journal.len();
+        if built > entry.journal.len() {
+            return None;
+        }
+        for op in entry.journal.since(built) {
+            sandbox.apply(op).ok()?;
+        }
+        Some(sandbox)
+    }
+
+    /// Rebuilds a sandbox from `seed` in the background, unless one is
+    /// already ready or being built for its pool.
+    pub fn restock(&self, seed: PoolSeed) {
+        let pool_id = seed.pool_id;
+        {
+            let mut stock = self.lock();
+            if stock.ready.contains_key(&pool_id) || !stock.building.insert(pool_id) {
+                return;
+            }
+        }
+        let stock = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let built = seed.build();
+            let mut inner = stock.lock();
+            inner.building.remove(&pool_id);
+            match built {
+                Ok(sandbox) => {
+                    inner.ready.insert(pool_id, sandbox);
+                }
+                Err(e) => tracing::debug!(%pool_id, error = %e, "quote sandbox rebuild failed"),
+            }
+        });
+    }
+
+    /// Drops the ready sandbox of a removed pool.
+    pub fn forget(&self, pool_id: PoolId) {
+        self.lock().ready.remove(&pool_id);
+    }
+
+    /// Returns `true` if a sandbox is ready for `pool_id`.
+    #[must_use]
+    pub fn is_ready(&self, pool_id: PoolId) -> bool {
+        self.lock().ready.contains_key(&pool_id)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Stock> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::PoolOperation;
+    use crate::domain::pool_operation::{SwapKind, TokenSide};
+    use crate::service::pool_service::build_entry;
+
+    fn entry() -> PoolEntry {
+        let Ok(entry) = build_entry(
+            PoolId::new(),
+            "constant_product",
+            serde_json::json!({
+                "token_a": {"address": "usdc", "decimals": 6},
+                "token_b": {"address": "weth", "decimals": 6},
+                "fee_bps": 30,
+                "reserve_a": "4000000",
+                "reserve_b": "1000000",
+            }),
+        ) else {
+            panic!("pool build failed");
+        };
+        entry
+    }
+
+    async fn restocked(stock: &SandboxStock, pool_id: PoolId) {
+        for _ in 0..200 {
+            if stock.is_ready(pool_id) {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        panic!("sandbox never rebuilt");
+    }
+
+    #[tokio::test]
+    async fn taken_sandboxes_catch_up_with_the_journal() {
+        let mut live = entry();
+        let stock = SandboxStock::new();
+        assert!(stock.take(&live).is_none());
+        let Some(seed) = PoolSeed::of(&live) else {
+            panic!("no seed");
+        };
+        stock.restock(seed);
+        restocked(&stock, live.pool_id).await;
+
+        let swap = PoolOperation::Swap {
+            token_in: TokenSide::Second,
+            kind: SwapKind::ExactIn,
+            amount: "1000".to_string(),
+        };
+        assert!(live.apply(&swap).is_ok());
+        let Some(sandbox) = stock.take(&live) else {
+            panic!("no sandbox ready");
+        };
+        assert_eq!(sandbox.version(), live.version());
+        assert_eq!(sandbox.reserves, live.reserves);
+        assert!(stock.take(&live).is_none(), "each sandbox serves one quote");
+
+        let Some(seed) = PoolSeed::of(&live) else {
+            panic!("no seed");
+        };
+        stock.restock(seed);
+        restocked(&stock, live.pool_id).await;
+        let mut stale = entry();
+        stale.pool_id = live.pool_id;
+        stale.config_json = serde_json::json!({"replaced": true});
+        assert!(stock.take(&stale).is_none(), "a replaced pool is rebuilt");
+    }
+}
@@ -0,0 +1,192 @@
+//! Mutation-count snapshot policy.
+//!
+//! Time-based snapshots bound how stale a snapshot can get, but a hot
+//! pool can still accumulate a long tail of events between them. This
+//! policy also snapshots a pool once it has seen N mutations (swaps,
+//! liquidity changes, fee collections) since its last snapshot, with N
+//! configurable per pool type.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use super::PoolService;
+use super::snapshot;
+use crate::domain::{PoolEvent, PoolId};
+use crate::persistence::postgres::PostgresPersistence;
+
+/// Mutation thresholds by pool type.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotPolicy {
+    /// Threshold for pool types without their own entry (`None` = off).
+    default_every: Option<u64>,
+    /// Per-pool-type thresholds; `0` disables the policy for that type.
+    per_type: HashMap<String, u64>,
+}
+
+impl SnapshotPolicy {
+    /// Parses a policy of the form `clmm=500,constant_product=1000,*=5000`.
+    /// A bare number is shorthand for `*=N`; an empty spec disables the
+    /// policy.
+    ///
+    /// Malformed entries are skipped.
+    #[must_use]
+    pub fn parse(spec: &str) -> Self {
+        let mut policy = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (pool_type, count) = entry.split_once('=').unwrap_or(("*", entry));
+            let Ok(count) = count.trim().parse::<u64>() else {
+                continue;
+            };
+            match pool_type.trim() {
+                "*" => policy.default_every = Some(count).filter(|n| *n > 0),
+                "" => {}
+                pool_type => {
+                    policy.per_type.insert(pool_type.to_string(), count);
+                }
+            }
+        }
+        policy
+    }
+
+    /// Returns the mutation threshold for `pool_type`, or `None` when the
+    /// policy does not apply to it.
+    #[must_use]
+    pub fn threshold(&self, pool_type: &str) -> Option<u64> {
+        match self.per_type.get(pool_type) {
+            Some(0) => None,
+            Some(n) => Some(*n),
+            None => self.default_every,
+        }
+    }
+
+    /// Returns `true` if no pool type is covered.
+    #[must_use]
+    pub fn is_disabled(&self) -> bool {
+        self.default_every.is_none() && self.per_type.values().all(|n| *n == 0)
+    }
+
+    /// Starts a task that snapshots pools into `db` as they cross their
+    /// threshold. The task ends when the event bus closes.
+    pub fn spawn(self, pool_service: Arc<PoolService>, db: PostgresPersistence) -> JoinHandle<()> {
+        let mut events = pool_service.event_bus().subscribe();
+        tokio::spawn(async move {
+            let mut counter = MutationCounter::default();
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        if matches!(event, PoolEvent::PoolRemoved { .. }) {
+                            counter.forget(event.pool_id());
+                            continue;
+                        }
+                        if !is_mutation(&event) {
+                            continue;
+                        }
+                        let pool_id = event.pool_id();
+                        let Ok(entry_lock) = pool_service.registry().get(pool_id).await else {
+                            continue;
+                        };
+                        let entry = entry_lock.read().await;
+                        let Some(every) = self.threshold(&entry.pool_type) else {
+                            continue;
+                        };
+                        if !counter.record(pool_id, every) {
+                            continue;
+                        }
+                        let Ok(parts) = snapshot::encode(&entry) else {
+                            continue;
+                        };
+                        let pool_type = entry.pool_type.clone();
+                        drop(entry);
+                        let saved = db
+                            .save_snapshot(
+                                *pool_id.as_uuid(),
+                                &pool_type,
+                                &parts.config_json,
+                                &parts.state_json,
+                                &parts.metadata_json,
+                            )
+                            .await;
+                        match saved {
+                            Ok(id) => tracing::debug!(%pool_id, id, "policy snapshot saved"),
+                            Err(e) => {
+                                tracing::warn!(%pool_id, error = %e, "policy snapshot failed")
+                            }
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(lagged = n, "snapshot policy lagged behind event bus");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+/// Returns `true` for events recording a change to pool state.
+fn is_mutation(event: &PoolEvent) -> bool {
+    matches!(
+        event,
+        PoolEvent::SwapExecuted { .. }
+            | PoolEvent::LiquidityChanged { .. }
+            | PoolEvent::FeesCollected { .. }
+            | PoolEvent::OrderPlaced { .. }
+            | PoolEvent::OrderCancelled { .. }
+            | PoolEvent::OraclePriceUpdated { .. }
+    )
+}
+
+/// Per-pool mutation counts since the last snapshot.
+#[derive(Debug, Default)]
+struct MutationCounter {
+    counts: HashMap<PoolId, u64>,
+}
+
+impl MutationCounter {
+    /// Counts one mutation on `pool_id`; returns `true` (and resets the
+    /// count) once `every` mutations have accumulated.
+    fn record(&mut self, pool_id: PoolId, every: u64) -> bool {
+        let count = self.counts.entry(pool_id).or_insert(0);
+        *count = count.saturating_add(1);
+        if *count >= every {
+            *count = 0;
+            return true;
+        }
+        false
+    }
+
+    /// Drops the count of a removed pool.
+    fn forget(&mut self, pool_id: PoolId) {
+        self.counts.remove(&pool_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_per_type_and_default_thresholds() {
+        let policy = SnapshotPolicy::parse("clmm=500, weighted=0, *=5000, bogus=x");
+        assert_eq!(policy.threshold("clmm"), Some(500));
+        assert_eq!(policy.threshold("weighted"), None);
+        assert_eq!(policy.threshold("constant_product"), Some(5000));
+        assert_eq!(SnapshotPolicy::parse("250").threshold("hybrid"), Some(250));
+        assert!(SnapshotPolicy::parse("").is_disabled());
+        assert!(SnapshotPolicy::parse("clmm=0").is_disabled());
+    }
+
+    #[test]
+    fn counter_fires_every_n_mutations() {
+        let mut counter = MutationCounter::default();
+        let pool = PoolId::new();
+        let fired: Vec<bool> = (0..6).map(|_| counter.record(pool, 3)).collect();
+        assert_eq!(fired, [false, false, true, false, false, true]);
+        counter.record(pool, 3);
+        counter.forget(pool);
+        assert!(!counter.record(pool, 2));
+    }
+}
@@ -0,0 +1,139 @@
+//! Periodic pool snapshots.
+//!
+//! Every `PERSISTENCE_SNAPSHOT_INTERVAL_SECS` the scheduler snapshots each
+//! registered pool (config, state, and metadata), bounding how much of the
+//! event log startup recovery has to replay. Rounds start after a random
+//! delay of up to a tenth of the interval, so instances started together
+//! do not hit the database at the same moment. Pools are snapshotted one
+//! at a time and independently: a pool that cannot be encoded or saved is
+//! logged and the round carries on with the next one.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use super::PoolService;
+use super::snapshot;
+use crate::domain::PoolId;
+use crate::persistence::postgres::PostgresPersistence;
+
+/// Fraction of the interval used as the largest jitter.
+const JITTER_DIVISOR: u32 = 10;
+
+/// Outcome of one snapshot round.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotRound {
+    /// Snapshots written.
+    pub saved: usize,
+    /// Pools without a creation config, which cannot be snapshotted.
+    pub skipped: usize,
+    /// Pools whose snapshot failed.
+    pub failed: Vec<PoolId>,
+}
+
+/// Snapshots every pool on a fixed interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotScheduler {
+    interval: Duration,
+}
+
+impl SnapshotScheduler {
+    /// Creates a scheduler running every `interval`.
+    #[must_use]
+    pub const fn new(interval: Duration) -> Self {
+        Self { interval }
+    }
+
+    /// Returns the delay before the next round: the interval plus up to a
+    /// tenth of it, drawn from `seed`.
+    #[must_use]
+    pub fn next_delay(&self, seed: u128) -> Duration {
+        let max_jitter = self.interval / JITTER_DIVISOR;
+        let max_ms = max_jitter.as_millis();
+        let jitter_ms = seed.checked_rem(max_ms.saturating_add(1)).unwrap_or(0);
+        self.interval
+            .saturating_add(Duration::from_millis(u64::try_from(jitter_ms).unwrap_or(0)))
+    }
+
+    /// Snapshots every registered pool into `db` once.
+    pub async fn run_once(pool_service: &PoolService, db: &PostgresPersistence) -> SnapshotRound {
+        let mut round = SnapshotRound::default();
+        for entry_lock in pool_service.registry().entries().await {
+            let entry = entry_lock.read().await;
+            let pool_id = entry.pool_id;
+            let Ok(parts) = snapshot::encode(&entry) else {
+                round.skipped = round.skipped.saturating_add(1);
+                continue;
+            };
+            let pool_type = entry.pool_type.clone();
+            drop(entry);
+            let saved = db
+                .save_snapshot(
+                    *pool_id.as_uuid(),
+                    &pool_type,
+                    &parts.config_json,
+                    &parts.state_json,
+                    &parts.metadata_json,
+                )
+                .await;
+            match saved {
+                Ok(_) => {
+                    // Seal what was just saved: captures share it from now on
+                    entry_lock.write().await.journal.checkpoint();
+                    round.saved = round.saved.saturating_add(1);
+                }
+                Err(e) => {
+                    tracing::warn!(%pool_id, error = %e, "scheduled snapshot failed");
+                    round.failed.push(pool_id);
+                }
+            }
+        }
+        round
+    }
+
+    /// Starts the task running a round every interval. Does nothing when
+    /// the interval is zero.
+    pub fn spawn(
+        self,
+        pool_service: Arc<PoolService>,
+        db: PostgresPersistence,
+    ) -> Option<JoinHandle<()>> {
+        if self.interval.is_zero() {
+            return None;
+        }
+        Some(tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(self.next_delay(Uuid::new_v4().as_u128())).await;
+                let round = Self::run_once(&pool_service, &db).await;
+                tracing::debug!(
+                    saved = round.saved,
+                    skipped = round.skipped,
+                    failed = round.failed.len(),
+                    "snapshot round finished"
+                );
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_stays_within_a_tenth_of_the_interval() {
+        let scheduler = SnapshotScheduler::new(Duration::from_secs(60));
+        assert_eq!(scheduler.next_delay(0), Duration::from_secs(60));
+        for seed in [1, 5_999, 6_000, 6_001, u128::MAX] {
+            let delay = scheduler.next_delay(seed);
+            assert!(delay >= Duration::from_secs(60));
+            assert!(delay <= Duration::from_secs(66));
+        }
+        assert_eq!(
+            SnapshotScheduler::new(Duration::ZERO).next_delay(42),
+            Duration::ZERO
+        );
+    }
+}
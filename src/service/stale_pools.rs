@@ -0,0 +1,155 @@
+//! Detection and archiving of stale pools.
+//!
+//! A pool is stale once it holds no liquidity and has not changed for a
+//! configurable number of days. Operators review the candidates through
+//! `GET /admin/pools/stale`; when auto-archiving is enabled, a periodic
+//! sweep also archives them. Archiving is not deletion: an archived pool
+//! keeps its state and history and stays readable, but rejects every
+//! further operation.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hydra_amm::traits::LiquidityPool;
+use tokio::task::JoinHandle;
+
+use super::PoolService;
+use crate::domain::PoolId;
+use crate::domain::pool_entry::PoolEntry;
+
+/// How often the auto-archive sweep runs.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(3_600);
+
+/// When pools count as stale and whether they are archived automatically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StalePoolPolicy {
+    /// Days without a state change after which an empty pool is stale.
+    pub idle_days: u32,
+    /// Whether the sweep archives stale pools without operator action.
+    pub auto_archive: bool,
+}
+
+/// A pool that qualifies for archiving.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StalePool {
+    /// Pool identifier.
+    pub pool_id: PoolId,
+    /// Pool type string.
+    pub pool_type: String,
+    /// Last state change (creation if none).
+    pub idle_since: DateTime<Utc>,
+    /// Whole days since `idle_since`.
+    pub idle_days: i64,
+}
+
+/// Returns the last-change cutoff for pools idle `idle_days` at `now`.
+#[must_use]
+pub fn idle_cutoff(now: DateTime<Utc>, idle_days: u32) -> DateTime<Utc> {
+    now - chrono::Duration::days(i64::from(idle_days))
+}
+
+/// Returns `true` if `entry` is unarchived, holds no liquidity, and has
+/// not changed since `cutoff`.
+#[must_use]
+pub fn is_stale(entry: &PoolEntry, cutoff: DateTime<Utc>) -> bool {
+    entry.archived_at.is_none()
+        && entry.pool_box.total_liquidity().get() == 0
+        && entry.last_modified_at <= cutoff
+}
+
+impl StalePoolPolicy {
+    /// Lists pools stale for at least `idle_days` at `now`, idle longest
+    /// first.
+    pub async fn candidates(
+        pool_service: &PoolService,
+        now: DateTime<Utc>,
+        idle_days: u32,
+    ) -> Vec<StalePool> {
+        let cutoff = idle_cutoff(now, idle_days);
+        let mut stale = Vec::new();
+        for entry_lock in pool_service.registry().entries().await {
+            let entry = entry_lock.read().await;
+            if is_stale(&entry, cutoff) {
+                stale.push(StalePool {
+                    pool_id: entry.pool_id,
+                    pool_type: entry.pool_type.clone(),
+                    idle_since: entry.last_modified_at,
+                    idle_days: (now - entry.last_modified_at).num_days(),
+                });
+            }
+        }
+        stale.sort_by_key(|p| (p.idle_since, p.pool_id));
+        stale
+    }
+
+    /// Starts a task that archives stale pools every hour. Does nothing
+    /// unless auto-archiving is enabled.
+    pub fn spawn(self, pool_service: Arc<PoolService>) -> Option<JoinHandle<()>> {
+        if !self.auto_archive {
+            return None;
+        }
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(SWEEP_INTERVAL);
+            loop {
+                ticker.tick().await;
+                let now = Utc::now();
+                let cutoff = idle_cutoff(now, self.idle_days);
+                for pool in Self::candidates(&pool_service, now, self.idle_days).await {
+                    match pool_service.archive_pool(pool.pool_id, cutoff).await {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            tracing::debug!(pool_id = %pool.pool_id, "pool no longer stale");
+                        }
+                        Err(e) => {
+                            tracing::warn!(pool_id = %pool.pool_id, error = %e, "archiving failed");
+                        }
+                    }
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::PoolOperation;
+    use crate::domain::pool_entry::PoolStatus;
+    use crate::domain::pool_operation::{SwapKind, TokenSide};
+    use crate::error::GatewayError;
+    use crate::service::pool_service::build_entry;
+
+    #[test]
+    fn only_empty_idle_pools_are_stale_and_archived_pools_reject_operations() {
+        let config = serde_json::json!({
+            "token_a": {"address": "0xaaa", "decimals": 6},
+            "token_b": {"address": "0xbbb", "decimals": 18},
+            "fee_bps": 30,
+            "reserve_a": "1000000",
+            "reserve_b": "1000000",
+        });
+        let Ok(mut entry) = build_entry(PoolId::new(), "constant_product", config) else {
+            panic!("entry build failed");
+        };
+        let now = Utc::now();
+        entry.last_modified_at = now - chrono::Duration::days(45);
+        assert!(idle_cutoff(now, 30) > entry.last_modified_at);
+        // Idle long enough, but still funded
+        assert!(!is_stale(&entry, idle_cutoff(now, 30)));
+
+        entry.status = PoolStatus::Archived;
+        entry.archived_at = Some(now);
+        let swap = PoolOperation::Swap {
+            token_in: TokenSide::First,
+            kind: SwapKind::ExactIn,
+            amount: "1000".to_string(),
+        };
+        assert!(matches!(
+            entry.apply(&swap),
+            Err(GatewayError::PoolArchived(_))
+        ));
+        assert_eq!(entry.version(), 0);
+    }
+}
@@ -0,0 +1,176 @@
+//! Per-request phase timings for the `Server-Timing` response header.
+//!
+//! The API middleware runs each mutation inside [`scope`]; code on the
+//! request path reports how long it spent in each [`Phase`] through
+//! [`measure`] and [`time`]. Outside a scope (background tasks, replay,
+//! replication) recording is a no-op, so the service layer can report
+//! unconditionally.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A stage of a mutation reported in `Server-Timing`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Waiting for pool write locks.
+    Lock,
+    /// AMM computation in hydra-amm.
+    Amm,
+    /// Publishing events on the event bus.
+    Publish,
+    /// Database writes made before responding.
+    Persist,
+}
+
+impl Phase {
+    /// Every phase, in header order.
+    pub const ALL: [Self; 4] = [Self::Lock, Self::Amm, Self::Publish, Self::Persist];
+
+    /// Metric name used in the header.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Lock => "lock",
+            Self::Amm => "amm",
+            Self::Publish => "publish",
+            Self::Persist => "persist",
+        }
+    }
+
+    /// Human-readable description used in the header.
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Lock => "Pool lock acquisition",
+            Self::Amm => "AMM computation",
+            Self::Publish => "Event publish",
+            Self::Persist => "Persistence",
+        }
+    }
+
+    const fn index(self) -> usize {
+        match self {
+            Self::Lock => 0,
+            Self::Amm => 1,
+            Self::Publish => 2,
+            Self::Persist => 3,
+        }
+    }
+}
+
+/// Time spent per phase by one request; phases never entered stay `None`.
+#[derive(Debug, Default)]
+pub struct RequestTimings {
+    phases: Mutex<[Option<Duration>; 4]>,
+}
+
+impl RequestTimings {
+    /// Adds `elapsed` to `phase`.
+    pub fn add(&self, phase: Phase, elapsed: Duration) {
+        let Ok(mut phases) = self.phases.lock() else {
+            return;
+        };
+        if let Some(slot) = phases.get_mut(phase.index()) {
+            *slot = Some(slot.unwrap_or_default() + elapsed);
+        }
+    }
+
+    /// Returns the time recorded for `phase`, if it was entered.
+    #[must_use]
+    pub fn get(&self, phase: Phase) -> Option<Duration> {
+        self.phases
+            .lock()
+            .ok()
+            .and_then(|phases| phases.get(phase.index()).copied().flatten())
+    }
+
+    /// Renders the recorded phases followed by `total` as a
+    /// `Server-Timing` header value, durations in milliseconds.
+    #[must_use]
+    pub fn header_value(&self, total: Duration) -> String {
+        let mut metrics: Vec<String> = Phase::ALL
+            .iter()
+            .filter_map(|&phase| {
+                self.get(phase).map(|elapsed| {
+                    format!(
+                        "{};dur={:.3};desc=\"{}\"",
+                        phase.as_str(),
+                        millis(elapsed),
+                        phase.description()
+                    )
+                })
+            })
+            .collect();
+        metrics.push(format!("total;dur={:.3}", millis(total)));
+        metrics.join(", ")
+    }
+}
+
+fn millis(elapsed: Duration) -> f64 {
+    elapsed.as_secs_f64() * 1_000.0
+}
+
+tokio::task_local! {
+    static CURRENT: Arc<RequestTimings>;
+}
+
+/// Runs `fut` with `timings` collecting the phases it reports.
+pub async fn scope<F: Future>(timings: Arc<RequestTimings>, fut: F) -> F::Output {
+    CURRENT.scope(timings, fut).await
+}
+
+/// Returns the timings of the current request, if any, so work handed to
+/// another task can keep reporting into them.
+#[must_use]
+pub fn current() -> Option<Arc<RequestTimings>> {
+    CURRENT.try_with(Arc::clone).ok()
+}
+
+/// Adds `elapsed` to `phase` of the current request, if any.
+pub fn record(phase: Phase, elapsed: Duration) {
+    let _ = CURRENT.try_with(|timings| timings.add(phase, elapsed));
+}
+
+/// Awaits `fut`, recording its duration under `phase`.
+pub async fn measure<F: Future>(phase: Phase, fut: F) -> F::Output {
+    let started = Instant::now();
+    let output = fut.await;
+    record(phase, started.elapsed());
+    output
+}
+
+/// Runs `f`, recording its duration under `phase`.
+pub fn time<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let output = f();
+    record(phase, started.elapsed());
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn phases_are_recorded_only_inside_a_scope() {
+        record(Phase::Amm, Duration::from_millis(5));
+
+        let timings = Arc::new(RequestTimings::default());
+        scope(Arc::clone(&timings), async {
+            record(Phase::Lock, Duration::from_micros(1_500));
+            record(Phase::Lock, Duration::from_micros(500));
+            time(Phase::Publish, || ());
+        })
+        .await;
+
+        assert_eq!(timings.get(Phase::Lock), Some(Duration::from_millis(2)));
+        assert!(timings.get(Phase::Publish).is_some());
+        assert_eq!(timings.get(Phase::Amm), None);
+
+        let header = timings.header_value(Duration::from_millis(3));
+        assert!(header.starts_with("lock;dur=2.000;desc=\"Pool lock acquisition\", publish;dur="));
+        assert!(header.ends_with("total;dur=3.000"));
+        assert!(!header.contains("amm") && !header.contains("persist"));
+    }
+}
@@ -0,0 +1,246 @@
+//! Recent-trades tape across all pools.
+//!
+//! A background task turns `swap_executed` events into exchange-style
+//! trades — price, size, and side relative to each pool's base token —
+//! and keeps the most recent ones in memory. New trades are also
+//! rebroadcast for the WebSocket `trades` channel.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use utoipa::ToSchema;
+
+use super::PoolService;
+use crate::domain::pool_entry::PoolEntry;
+use crate::domain::{PoolEvent, PoolId};
+
+/// Number of trades retained by the tape.
+pub const TAPE_CAPACITY: usize = 1_000;
+
+/// Direction of a trade from the taker's point of view, relative to the
+/// pool's base token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeSide {
+    /// The taker bought base (paid quote into the pool).
+    Buy,
+    /// The taker sold base into the pool.
+    Sell,
+}
+
+/// One executed swap, expressed as a trade.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trade {
+    /// Command id of the swap.
+    pub trade_id: String,
+    /// Pool the swap executed on.
+    pub pool_id: PoolId,
+    /// Base token address per the pool's price convention.
+    pub base_token: String,
+    /// Quote token address.
+    pub quote_token: String,
+    /// Whether the taker bought or sold base.
+    pub side: TradeSide,
+    /// Execution price in quote per base.
+    pub price: f64,
+    /// Base amount traded.
+    pub size: u128,
+    /// Quote amount traded.
+    pub quote_size: u128,
+    /// Execution time.
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Trade {
+    /// Builds a trade from a `swap_executed` event on `entry`'s pool.
+    ///
+    /// Returns `None` for other events, unparseable amounts, or an input
+    /// token that is not on the pool.
+    #[must_use]
+    pub fn from_event(event: &PoolEvent, entry: &PoolEntry) -> Option<Self> {
+        let PoolEvent::SwapExecuted {
+            pool_id,
+            command_id,
+            amount_in,
+            amount_out,
+            token_in,
+            timestamp,
+            ..
+        } = event
+        else {
+            return None;
+        };
+        let amount_in: u128 = amount_in.parse().ok()?;
+        let amount_out: u128 = amount_out.parse().ok()?;
+        let side_in = entry.side_of_label(token_in)?;
+        let base = entry.price_base;
+        let (side, size, quote_size) = if side_in == base {
+            (TradeSide::Sell, amount_in, amount_out)
+        } else {
+            (TradeSide::Buy, amount_out, amount_in)
+        };
+        let price = if size == 0 {
+            0.0
+        } else {
+            quote_size as f64 / size as f64
+        };
+        Some(Self {
+            trade_id: command_id.clone(),
+            pool_id: *pool_id,
+            base_token: entry.token_label(base),
+            quote_token: entry.token_label(base.other()),
+            side,
+            price,
+            size,
+            quote_size,
+            timestamp: *timestamp,
+        })
+    }
+}
+
+/// Shared handle to the trade tape.
+#[derive(Debug, Clone)]
+pub struct TradeTape {
+    recent: Arc<Mutex<VecDeque<Trade>>>,
+    live: broadcast::Sender<Trade>,
+}
+
+impl Default for TradeTape {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TradeTape {
+    /// Creates an empty tape. Call [`Self::spawn`] to start feeding it.
+    #[must_use]
+    pub fn new() -> Self {
+        let (live, _) = broadcast::channel(TAPE_CAPACITY);
+        Self {
+            recent: Arc::new(Mutex::new(VecDeque::with_capacity(TAPE_CAPACITY))),
+            live,
+        }
+    }
+
+    /// Starts a task that records every swap published by
+    /// `pool_service`. The task ends when the event bus closes.
+    pub fn spawn(&self, pool_service: Arc<PoolService>) -> JoinHandle<()> {
+        let tape = self.clone();
+        let mut events = pool_service.event_bus().subscribe();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event @ PoolEvent::SwapExecuted { .. }) => {
+                        let Ok(entry) = pool_service.registry().get(event.pool_id()).await else {
+                            continue;
+                        };
+                        let trade = Trade::from_event(&event, &*entry.read().await);
+                        if let Some(trade) = trade {
+                            tape.record(trade);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(lagged = n, "trade tape lagged behind event bus");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Appends a trade, evicting the oldest beyond [`TAPE_CAPACITY`].
+    pub fn record(&self, trade: Trade) {
+        let mut recent = self.recent.lock().unwrap_or_else(PoisonError::into_inner);
+        if recent.len() == TAPE_CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(trade.clone());
+        drop(recent);
+        let _ = self.live.send(trade);
+    }
+
+    /// Returns up to `limit` most recent trades, newest first, optionally
+    /// restricted to one pool.
+    #[must_use]
+    pub fn recent(&self, limit: usize, pool_id: Option<PoolId>) -> Vec<Trade> {
+        let recent = self.recent.lock().unwrap_or_else(PoisonError::into_inner);
+        recent
+            .iter()
+            .rev()
+            .filter(|t| pool_id.is_none_or(|id| t.pool_id == id))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Subscribes to trades as they are recorded.
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<Trade> {
+        self.live.subscribe()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::pool_operation::SwapKind;
+    use crate::service::pool_service::build_entry;
+
+    #[test]
+    fn swaps_become_trades_relative_to_base() {
+        let Ok(entry) = build_entry(
+            PoolId::new(),
+            "constant_product",
+            serde_json::json!({
+                "token_a": {"address": "usdc", "decimals": 6},
+                "token_b": {"address": "weth", "decimals": 18},
+                "fee_bps": 30,
+                "reserve_a": "4000000",
+                "reserve_b": "1000000",
+                "price_convention": {"base": "weth"},
+            }),
+        ) else {
+            panic!("entry build failed");
+        };
+        let swap = |token_in: &str, amount_in: &str, amount_out: &str| PoolEvent::SwapExecuted {
+            pool_id: entry.pool_id,
+            command_id: "cmd".to_string(),
+            amount_in: amount_in.to_string(),
+            amount_out: amount_out.to_string(),
+            fee: "0".to_string(),
+            fee_rebate: "0".to_string(),
+            new_price: "4".to_string(),
+            price_change_bps: 0,
+            token_in: token_in.to_string(),
+            swap_kind: SwapKind::ExactIn,
+            actor: None,
+            timestamp: Utc::now(),
+        };
+
+        let Some(sell) = Trade::from_event(&swap("weth", "10", "38"), &entry) else {
+            panic!("expected a trade");
+        };
+        assert_eq!(sell.side, TradeSide::Sell);
+        assert_eq!((sell.size, sell.quote_size), (10, 38));
+        assert_eq!(sell.base_token, "weth");
+
+        let Some(buy) = Trade::from_event(&swap("usdc", "42", "10"), &entry) else {
+            panic!("expected a trade");
+        };
+        assert_eq!(buy.side, TradeSide::Buy);
+        assert!((buy.price - 4.2).abs() < 1e-9);
+
+        let tape = TradeTape::new();
+        tape.record(sell);
+        tape.record(buy);
+        let newest = tape.recent(1, None);
+        assert_eq!(newest.first().map(|t| t.side), Some(TradeSide::Buy));
+        assert!(tape.recent(10, Some(PoolId::new())).is_empty());
+    }
+}
@@ -0,0 +1,269 @@
+//! Per-API-key usage ledger.
+//!
+//! The API key is the caller's `X-Client-Id`, as for quotas. The ledger
+//! keeps hourly UTC buckets per key of:
+//!
+//! - requests admitted by the HTTP API;
+//! - swaps executed, with their input volume per token;
+//! - WebSocket bytes sent to and received from the key's connections
+//!   (text and binary payloads).
+//!
+//! Buckets older than the retention are dropped as new ones start.
+//! Counters are per instance, like quotas: in a cluster each instance
+//! reports the traffic it served.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::domain::{EventBus, PoolEvent};
+
+/// Usage counted over some span of time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UsageTotals {
+    /// HTTP requests admitted.
+    pub requests: u64,
+    /// Swaps executed.
+    pub swaps: u64,
+    /// Swap input volume by input token address, in raw units.
+    pub swap_volume: BTreeMap<String, u128>,
+    /// WebSocket payload bytes sent to the key.
+    pub ws_bytes_sent: u64,
+    /// WebSocket payload bytes received from the key.
+    pub ws_bytes_received: u64,
+}
+
+impl UsageTotals {
+    /// Adds `other` into these totals.
+    fn add(&mut self, other: &Self) {
+        self.requests = self.requests.saturating_add(other.requests);
+        self.swaps = self.swaps.saturating_add(other.swaps);
+        for (token, amount) in &other.swap_volume {
+            let total = self.swap_volume.entry(token.clone()).or_default();
+            *total = total.saturating_add(*amount);
+        }
+        self.ws_bytes_sent = self.ws_bytes_sent.saturating_add(other.ws_bytes_sent);
+        self.ws_bytes_received = self
+            .ws_bytes_received
+            .saturating_add(other.ws_bytes_received);
+    }
+}
+
+/// Usage of one key in one UTC hour.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageBucket {
+    /// Start of the hour.
+    pub hour_start: DateTime<Utc>,
+    /// Usage within the hour.
+    pub totals: UsageTotals,
+}
+
+/// Usage of one key over a time range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageReport {
+    /// API key (client id).
+    pub api_key: String,
+    /// Sum of `hourly`.
+    pub totals: UsageTotals,
+    /// Hours with usage, oldest first.
+    pub hourly: Vec<UsageBucket>,
+}
+
+/// Usage of one key by hour start.
+type Hours = BTreeMap<DateTime<Utc>, UsageTotals>;
+
+/// Shared usage ledger.
+#[derive(Debug, Clone)]
+pub struct UsageLedger {
+    keys: Arc<Mutex<HashMap<String, Hours>>>,
+    retention: Duration,
+}
+
+impl UsageLedger {
+    /// Creates an empty ledger keeping `retention_days` of buckets.
+    #[must_use]
+    pub fn new(retention_days: u32) -> Self {
+        Self {
+            keys: Arc::new(Mutex::new(HashMap::new())),
+            retention: Duration::days(i64::from(retention_days.max(1))),
+        }
+    }
+
+    /// Counts one request by `api_key`.
+    pub fn record_request(&self, api_key: &str, now: DateTime<Utc>) {
+        self.record(api_key, now, |totals| {
+            totals.requests = totals.requests.saturating_add(1);
+        });
+    }
+
+    /// Counts one swap by `api_key` spending `amount_in` of `token_in`.
+    pub fn record_swap(&self, api_key: &str, token_in: &str, amount_in: u128, now: DateTime<Utc>) {
+        self.record(api_key, now, |totals| {
+            totals.swaps = totals.swaps.saturating_add(1);
+            let volume = totals.swap_volume.entry(token_in.to_string()).or_default();
+            *volume = volume.saturating_add(amount_in);
+        });
+    }
+
+    /// Counts WebSocket bytes sent to and received from `api_key`.
+    pub fn record_ws(&self, api_key: &str, sent: u64, received: u64, now: DateTime<Utc>) {
+        self.record(api_key, now, |totals| {
+            totals.ws_bytes_sent = totals.ws_bytes_sent.saturating_add(sent);
+            totals.ws_bytes_received = totals.ws_bytes_received.saturating_add(received);
+        });
+    }
+
+    /// Returns the usage of `api_key` in the hours starting within
+    /// `[from, to)`.
+    #[must_use]
+    pub fn report(&self, api_key: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> UsageReport {
+        let keys = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        let hours = keys.get(api_key);
+        key_report(api_key, hours, from, to)
+    }
+
+    /// Returns the usage of every key with usage in the hours starting
+    /// within `[from, to)`, ordered by key.
+    #[must_use]
+    pub fn report_all(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<UsageReport> {
+        let keys = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut reports: Vec<UsageReport> = keys
+            .iter()
+            .map(|(api_key, hours)| key_report(api_key, Some(hours), from, to))
+            .filter(|report| !report.hourly.is_empty())
+            .collect();
+        reports.sort_by(|a, b| a.api_key.cmp(&b.api_key));
+        reports
+    }
+
+    /// Starts a task counting swaps by their actor. The task ends when
+    /// the event bus closes.
+    pub fn track_swaps(&self, event_bus: &EventBus) -> JoinHandle<()> {
+        let mut events = event_bus.subscribe();
+        let usage = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(PoolEvent::SwapExecuted {
+                        actor: Some(actor),
+                        token_in,
+                        amount_in,
+                        timestamp,
+                        ..
+                    }) => {
+                        let amount = amount_in.parse().unwrap_or_default();
+                        usage.record_swap(&actor, &token_in, amount, timestamp);
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(lagged = n, "usage ledger lagged behind event bus");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Applies `update` to the bucket of `api_key` holding `now`,
+    /// dropping the key's expired buckets when a new one starts.
+    fn record(&self, api_key: &str, now: DateTime<Utc>, update: impl FnOnce(&mut UsageTotals)) {
+        let hour = hour_start(now);
+        let mut keys = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        let hours = keys.entry(api_key.to_string()).or_default();
+        if !hours.contains_key(&hour) {
+            let cutoff = hour - self.retention;
+            hours.retain(|start, _| *start >= cutoff);
+        }
+        update(hours.entry(hour).or_default());
+    }
+}
+
+/// Start of the UTC hour holding `at`.
+fn hour_start(at: DateTime<Utc>) -> DateTime<Utc> {
+    at.duration_trunc(Duration::hours(1)).unwrap_or(at)
+}
+
+fn key_report(
+    api_key: &str,
+    hours: Option<&Hours>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> UsageReport {
+    let mut totals = UsageTotals::default();
+    let hourly: Vec<UsageBucket> = hours
+        .into_iter()
+        .flat_map(|hours| hours.range(from..to))
+        .map(|(start, usage)| {
+            totals.add(usage);
+            UsageBucket {
+                hour_start: *start,
+                totals: usage.clone(),
+            }
+        })
+        .collect();
+    UsageReport {
+        api_key: api_key.to_string(),
+        totals,
+        hourly,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn usage_is_bucketed_hourly_and_reported_by_range() {
+        let ledger = UsageLedger::new(1);
+        let Some(start) = Utc.with_ymd_and_hms(2026, 3, 1, 10, 15, 0).single() else {
+            panic!("valid time");
+        };
+        ledger.record_request("desk-1", start);
+        ledger.record_request("desk-1", start + Duration::minutes(10));
+        ledger.record_swap("desk-1", "0xA", 500, start);
+        ledger.record_swap("desk-1", "0xA", 250, start + Duration::hours(1));
+        ledger.record_ws("desk-1", 100, 20, start + Duration::hours(1));
+        ledger.record_request("desk-2", start);
+
+        let report = ledger.report(
+            "desk-1",
+            start - Duration::hours(1),
+            start + Duration::hours(2),
+        );
+        assert_eq!(report.hourly.len(), 2);
+        assert_eq!(report.totals.requests, 2);
+        assert_eq!(report.totals.swaps, 2);
+        assert_eq!(report.totals.swap_volume.get("0xA"), Some(&750));
+        assert_eq!(report.totals.ws_bytes_sent, 100);
+        assert_eq!(report.totals.ws_bytes_received, 20);
+        let Some(first) = report.hourly.first() else {
+            panic!("missing bucket");
+        };
+        assert_eq!(first.hour_start, hour_start(start));
+        assert_eq!(first.totals.requests, 2);
+
+        // The range selects whole hours by their start
+        let later = ledger.report("desk-1", start, start + Duration::hours(2));
+        assert_eq!(later.totals.swaps, 1);
+        assert!(ledger.report("nobody", start, start).hourly.is_empty());
+        let all = ledger.report_all(start - Duration::hours(1), start);
+        assert_eq!(
+            all.iter().map(|r| r.api_key.as_str()).collect::<Vec<_>>(),
+            ["desk-1", "desk-2"]
+        );
+
+        // Buckets past the retention go once a new hour starts
+        ledger.record_request("desk-1", start + Duration::days(2));
+        let kept = ledger.report(
+            "desk-1",
+            start - Duration::days(1),
+            start + Duration::days(3),
+        );
+        assert_eq!(kept.hourly.len(), 1);
+    }
+}
@@ -0,0 +1,135 @@
+//! Realized volatility from a pool's persisted price series.
+//!
+//! Returns are log returns between consecutive `price_updated` events.
+//! Because updates arrive irregularly, the per-return standard deviation
+//! is annualized by the observed return frequency over the window.
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::error::GatewayError;
+
+/// Window used when the caller does not specify one.
+pub const DEFAULT_WINDOW: &str = "24h";
+
+/// Longest accepted window.
+const MAX_WINDOW_DAYS: i64 = 366;
+
+/// Seconds in a 365-day year.
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 3600.0;
+
+/// Volatility statistics over a window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolatilityStats {
+    /// Number of price observations used.
+    pub samples: usize,
+    /// Standard deviation of log returns between observations.
+    pub return_stddev: f64,
+    /// `return_stddev` scaled to one year at the observed frequency.
+    pub annualized: f64,
+}
+
+/// Parses a window such as `30m`, `24h`, `7d`, or `2w`.
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] for an unknown unit, a
+/// non-positive length, or a window longer than a year.
+pub fn parse_window(window: &str) -> Result<Duration, GatewayError> {
+    let invalid = || GatewayError::InvalidRequest(format!("invalid window: {window}"));
+    let split = window.len().checked_sub(1).ok_or_else(invalid)?;
+    let (count, unit) = window.split_at(split);
+    let count: i64 = count.parse().map_err(|_| invalid())?;
+    if count <= 0 {
+        return Err(invalid());
+    }
+    let duration = match unit {
+        "m" => Duration::try_minutes(count),
+        "h" => Duration::try_hours(count),
+        "d" => Duration::try_days(count),
+        "w" => Duration::try_weeks(count),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+    if duration > Duration::days(MAX_WINDOW_DAYS) {
+        return Err(GatewayError::InvalidRequest(format!(
+            "window must not exceed {MAX_WINDOW_DAYS} days"
+        )));
+    }
+    Ok(duration)
+}
+
+/// Computes realized volatility of `series` (time-ordered
+/// `(timestamp, price)` pairs) observed over `window`.
+///
+/// Non-positive prices are skipped. Returns `None` with fewer than two
+/// returns.
+#[must_use]
+pub fn realized(series: &[(DateTime<Utc>, f64)], window: Duration) -> Option<VolatilityStats> {
+    let prices: Vec<f64> = series
+        .iter()
+        .map(|(_, p)| *p)
+        .filter(|p| p.is_finite() && *p > 0.0)
+        .collect();
+    let returns: Vec<f64> = prices
+        .windows(2)
+        .filter_map(|w| match w {
+            [a, b] => Some((b / a).ln()),
+            _ => None,
+        })
+        .collect();
+    if returns.len() < 2 {
+        return None;
+    }
+
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let return_stddev = variance.sqrt();
+
+    let window_secs = window.num_seconds().max(1) as f64;
+    let returns_per_year = n * SECONDS_PER_YEAR / window_secs;
+    Some(VolatilityStats {
+        samples: prices.len(),
+        return_stddev,
+        annualized: return_stddev * returns_per_year.sqrt(),
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_windows() {
+        assert_eq!(parse_window("24h").ok(), Some(Duration::hours(24)));
+        assert_eq!(parse_window("7d").ok(), Some(Duration::days(7)));
+        assert!(parse_window("0h").is_err());
+        assert!(parse_window("5y").is_err());
+        assert!(parse_window("").is_err());
+        assert!(parse_window("400d").is_err());
+    }
+
+    #[test]
+    fn alternating_prices_have_known_volatility() {
+        let now = Utc::now();
+        // Log returns alternate between +ln(1.01) and -ln(1.01).
+        let series: Vec<(DateTime<Utc>, f64)> = (0..5)
+            .map(|i| (now, if i % 2 == 0 { 100.0 } else { 101.0 }))
+            .collect();
+        let Some(stats) = realized(&series, Duration::days(365)) else {
+            panic!("expected stats");
+        };
+        assert_eq!(stats.samples, 5);
+        let r = 1.01f64.ln();
+        // Four returns, mean 0, sample variance 4r²/3.
+        let expected = (4.0 * r * r / 3.0).sqrt();
+        assert!((stats.return_stddev - expected).abs() < 1e-12);
+        // Four returns per year: annualized = stddev * 2.
+        assert!((stats.annualized - expected * 2.0).abs() < 1e-9);
+        let Some(head) = series.get(..2) else {
+            panic!("series too short");
+        };
+        assert!(realized(head, Duration::days(1)).is_none());
+    }
+}
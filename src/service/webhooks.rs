@@ -0,0 +1,482 @@
+//! Webhooks attached to individual pools.
+//!
+//! A pool may carry up to [`MAX_WEBHOOKS_PER_POOL`] webhooks, each with a
+//! URL, an optional event-type filter, an optional size threshold, and
+//! its own signing secret. A background task matches every event on the
+//! bus against the webhooks of its pool and POSTs the event, in the shape
+//! WebSocket clients receive, to each match.
+//!
+//! Each delivery carries `X-Hydra-Timestamp` and `X-Hydra-Signature`: the
+//! hex HMAC-SHA256 of `"{timestamp}\n{body}"` under the webhook's secret.
+//! Failed deliveries (transport errors and non-2xx responses) are retried
+//! with backoff up to [`MAX_ATTEMPTS`] times. The outcome of each is kept
+//! in a per-webhook delivery log of the last [`DELIVERY_LOG_CAPACITY`]
+//! deliveries, in memory on the instance that sent them.
+//!
+//! Webhooks are removed with their pool. Only the primary delivers.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use super::PoolService;
+use crate::api::signing::{self, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+use crate::domain::{PoolEvent, PoolId};
+use crate::error::GatewayError;
+use crate::persistence::models::WebhookRecord;
+use crate::persistence::postgres::PostgresPersistence;
+
+/// Webhooks one pool may carry.
+pub const MAX_WEBHOOKS_PER_POOL: usize = 10;
+
+/// Deliveries kept per webhook.
+pub const DELIVERY_LOG_CAPACITY: usize = 50;
+
+/// Delivery attempts before giving up.
+pub const MAX_ATTEMPTS: u32 = 3;
+
+/// Timeout of one delivery attempt.
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Delay before the first retry; doubled for each further retry.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Event types a webhook may filter on.
+pub const EVENT_TYPES: [&str; 15] = [
+    "pool_created",
+    "pool_removed",
+    "pool_archived",
+    "pool_restored",
+    "pool_status_changed",
+    "pool_metadata_updated",
+    "pool_ownership_transferred",
+    "swap_executed",
+    "liquidity_changed",
+    "fees_collected",
+    "rewards_claimed",
+    "order_placed",
+    "order_cancelled",
+    "oracle_price_updated",
+    "price_updated",
+];
+
+/// A webhook attached to a pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Webhook {
+    /// Webhook identifier.
+    pub id: Uuid,
+    /// Pool the webhook is attached to.
+    pub pool_id: PoolId,
+    /// Delivery URL.
+    pub url: String,
+    /// Event types delivered; empty means every type.
+    pub event_types: Vec<String>,
+    /// Smallest `amount_in` of a delivered swap and smallest
+    /// `liquidity_delta` of a delivered liquidity change.
+    pub min_amount: Option<u128>,
+    /// HMAC signing secret.
+    pub secret: String,
+    /// Creation time.
+    pub created_at: DateTime<Utc>,
+}
+
+impl Webhook {
+    /// Returns `true` if `event` should be delivered to this webhook.
+    /// Amounts that cannot be parsed pass the threshold.
+    #[must_use]
+    pub fn matches(&self, event: &PoolEvent) -> bool {
+        if event.pool_id() != self.pool_id {
+            return false;
+        }
+        let event_type = event.event_type_str();
+        if !self.event_types.is_empty() && !self.event_types.iter().any(|t| t == event_type) {
+            return false;
+        }
+        let amount = match event {
+            PoolEvent::SwapExecuted { amount_in, .. } => amount_in,
+            PoolEvent::LiquidityChanged {
+                liquidity_delta, ..
+            } => liquidity_delta,
+            _ => return true,
+        };
+        match (self.min_amount, amount.parse::<u128>()) {
+            (Some(min), Ok(amount)) => amount >= min,
+            _ => true,
+        }
+    }
+
+    /// Signs a delivery `body` sent at `timestamp` (Unix seconds).
+    #[must_use]
+    pub fn sign(&self, timestamp: i64, body: &str) -> String {
+        signing::sign(self.secret.as_bytes(), &format!("{timestamp}\n{body}"))
+    }
+
+    /// Converts to the persisted row.
+    #[must_use]
+    pub fn to_record(&self) -> WebhookRecord {
+        WebhookRecord {
+            id: self.id,
+            pool_id: *self.pool_id.as_uuid(),
+            url: self.url.clone(),
+            event_types: self.event_types.clone(),
+            min_amount: self.min_amount.map(|m| m.to_string()),
+            secret: self.secret.clone(),
+            created_at: self.created_at,
+        }
+    }
+}
+
+impl From<&WebhookRecord> for Webhook {
+    fn from(record: &WebhookRecord) -> Self {
+        Self {
+            id: record.id,
+            pool_id: PoolId::from_uuid(record.pool_id),
+            url: record.url.clone(),
+            event_types: record.event_types.clone(),
+            min_amount: record.min_amount.as_deref().and_then(|m| m.parse().ok()),
+            secret: record.secret.clone(),
+            created_at: record.created_at,
+        }
+    }
+}
+
+/// Outcome of one delivery.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryRecord {
+    /// Event type delivered.
+    pub event_type: String,
+    /// Attempts made.
+    pub attempts: u32,
+    /// HTTP status of the last attempt, if a response arrived.
+    pub status: Option<u16>,
+    /// Error of the last attempt, if it failed.
+    pub error: Option<String>,
+    /// Whether an attempt got a 2xx response.
+    pub delivered: bool,
+    /// When the last attempt finished.
+    pub finished_at: DateTime<Utc>,
+}
+
+/// Generates a 256-bit hex secret.
+#[must_use]
+pub fn generate_secret() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Shared table of pool webhooks and their delivery logs.
+#[derive(Debug, Clone, Default)]
+pub struct WebhookRegistry {
+    hooks: Arc<Mutex<HashMap<PoolId, Vec<Webhook>>>>,
+    deliveries: Arc<Mutex<HashMap<Uuid, VecDeque<DeliveryRecord>>>>,
+    client: reqwest::Client,
+}
+
+impl WebhookRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Installs persisted webhooks.
+    pub fn load(&self, records: &[WebhookRecord]) {
+        let mut hooks = self.hooks.lock().unwrap_or_else(PoisonError::into_inner);
+        for record in records {
+            let webhook = Webhook::from(record);
+            hooks.entry(webhook.pool_id).or_default().push(webhook);
+        }
+    }
+
+    /// Attaches `webhook` to its pool.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::CapacityExceeded`] if the pool already has
+    /// [`MAX_WEBHOOKS_PER_POOL`] webhooks.
+    pub fn add(&self, webhook: Webhook) -> Result<(), GatewayError> {
+        let mut hooks = self.hooks.lock().unwrap_or_else(PoisonError::into_inner);
+        let pool_hooks = hooks.entry(webhook.pool_id).or_default();
+        if pool_hooks.len() >= MAX_WEBHOOKS_PER_POOL {
+            return Err(GatewayError::CapacityExceeded(format!(
+                "pool {} already has {MAX_WEBHOOKS_PER_POOL} webhooks",
+                webhook.pool_id
+            )));
+        }
+        pool_hooks.push(webhook);
+        Ok(())
+    }
+
+    /// Returns the webhooks of `pool_id`, oldest first.
+    #[must_use]
+    pub fn list(&self, pool_id: PoolId) -> Vec<Webhook> {
+        let hooks = self.hooks.lock().unwrap_or_else(PoisonError::into_inner);
+        hooks.get(&pool_id).cloned().unwrap_or_default()
+    }
+
+    /// Detaches webhook `id` from `pool_id`, returning it if it existed.
+    pub fn remove(&self, pool_id: PoolId, id: Uuid) -> Option<Webhook> {
+        let mut hooks = self.hooks.lock().unwrap_or_else(PoisonError::into_inner);
+        let pool_hooks = hooks.get_mut(&pool_id)?;
+        let index = pool_hooks.iter().position(|h| h.id == id)?;
+        let removed = pool_hooks.remove(index);
+        if pool_hooks.is_empty() {
+            hooks.remove(&pool_id);
+        }
+        drop(hooks);
+        self.lock_deliveries().remove(&id);
+        Some(removed)
+    }
+
+    /// Detaches every webhook of `pool_id`, returning how many there were.
+    pub fn remove_pool(&self, pool_id: PoolId) -> usize {
+        let removed = self
+            .hooks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&pool_id)
+            .unwrap_or_default();
+        let mut deliveries = self.lock_deliveries();
+        for webhook in &removed {
+            deliveries.remove(&webhook.id);
+        }
+        removed.len()
+    }
+
+    /// Returns the webhooks `event` should be delivered to.
+    #[must_use]
+    pub fn matching(&self, event: &PoolEvent) -> Vec<Webhook> {
+        let hooks = self.hooks.lock().unwrap_or_else(PoisonError::into_inner);
+        hooks
+            .get(&event.pool_id())
+            .map(|pool_hooks| {
+                pool_hooks
+                    .iter()
+                    .filter(|h| h.matches(event))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the delivery log of webhook `id` on `pool_id`, newest
+    /// first, or `None` if the pool has no such webhook.
+    #[must_use]
+    pub fn deliveries(&self, pool_id: PoolId, id: Uuid) -> Option<Vec<DeliveryRecord>> {
+        if !self.list(pool_id).iter().any(|h| h.id == id) {
+            return None;
+        }
+        Some(
+            self.lock_deliveries()
+                .get(&id)
+                .map(|log| log.iter().rev().cloned().collect())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Appends to the delivery log of webhook `id`, evicting the oldest
+    /// entry beyond [`DELIVERY_LOG_CAPACITY`].
+    pub fn record_delivery(&self, id: Uuid, record: DeliveryRecord) {
+        let mut deliveries = self.lock_deliveries();
+        let log = deliveries.entry(id).or_default();
+        if log.len() == DELIVERY_LOG_CAPACITY {
+            log.pop_front();
+        }
+        log.push_back(record);
+    }
+
+    fn lock_deliveries(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<Uuid, VecDeque<DeliveryRecord>>> {
+        self.deliveries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Starts a task that delivers every event published by
+    /// `pool_service` to the matching webhooks, and drops the webhooks of
+    /// removed pools (from `db` too, when given). The task ends when the
+    /// event bus closes.
+    pub fn spawn(
+        &self,
+        pool_service: Arc<PoolService>,
+        db: Option<PostgresPersistence>,
+    ) -> JoinHandle<()> {
+        let registry = self.clone();
+        let mut events = pool_service.event_bus().subscribe();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        let matching = registry.matching(&event);
+                        if !matching.is_empty() {
+                            let body = pool_service.event_payload(&event).to_string();
+                            for webhook in matching {
+                                let registry = registry.clone();
+                                let body = body.clone();
+                                let event_type = event.event_type_str();
+                                tokio::spawn(async move {
+                                    registry.deliver(&webhook, event_type, &body).await;
+                                });
+                            }
+                        }
+                        if let PoolEvent::PoolRemoved { pool_id, .. } = event {
+                            registry.remove_pool(pool_id);
+                            if let Some(db) = db.as_ref()
+                                && let Err(e) = db.delete_webhooks(*pool_id.as_uuid(), None).await
+                            {
+                                tracing::warn!(%pool_id, error = %e, "webhook cleanup failed");
+                            }
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(lagged = n, "webhook dispatcher lagged behind event bus");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// POSTs `body` to `webhook`, retrying failures, and logs the outcome.
+    async fn deliver(&self, webhook: &Webhook, event_type: &str, body: &str) {
+        let mut record = DeliveryRecord {
+            event_type: event_type.to_string(),
+            attempts: 0,
+            status: None,
+            error: None,
+            delivered: false,
+            finished_at: Utc::now(),
+        };
+        let mut delay = RETRY_BASE_DELAY;
+        while record.attempts < MAX_ATTEMPTS && !record.delivered {
+            if record.attempts > 0 {
+                tokio::time::sleep(delay).await;
+                delay = delay.saturating_mul(2);
+            }
+            record.attempts += 1;
+            let timestamp = Utc::now().timestamp();
+            let sent = self
+                .client
+                .post(&webhook.url)
+                .timeout(ATTEMPT_TIMEOUT)
+                .header("content-type", "application/json")
+                .header("x-hydra-webhook-id", webhook.id.to_string())
+                .header("x-hydra-event", event_type)
+                .header(TIMESTAMP_HEADER, timestamp.to_string())
+                .header(SIGNATURE_HEADER, webhook.sign(timestamp, body))
+                .body(body.to_string())
+                .send()
+                .await;
+            match sent {
+                Ok(response) => {
+                    record.status = Some(response.status().as_u16());
+                    record.delivered = response.status().is_success();
+                    record.error = (!record.delivered)
+                        .then(|| format!("unexpected status {}", response.status()));
+                }
+                Err(e) => {
+                    record.status = None;
+                    record.error = Some(e.to_string());
+                }
+            }
+        }
+        record.finished_at = Utc::now();
+        if !record.delivered {
+            tracing::warn!(
+                webhook = %webhook.id,
+                pool_id = %webhook.pool_id,
+                attempts = record.attempts,
+                error = record.error.as_deref().unwrap_or_default(),
+                "webhook delivery failed"
+            );
+        }
+        self.record_delivery(webhook.id, record);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::pool_operation::SwapKind;
+
+    fn webhook(pool_id: PoolId, event_types: &[&str], min_amount: Option<u128>) -> Webhook {
+        Webhook {
+            id: Uuid::new_v4(),
+            pool_id,
+            url: "http://127.0.0.1:9/hook".to_string(),
+            event_types: event_types.iter().map(|t| (*t).to_string()).collect(),
+            min_amount,
+            secret: "secret".to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn swap(pool_id: PoolId, amount_in: &str) -> PoolEvent {
+        PoolEvent::SwapExecuted {
+            pool_id,
+            command_id: "cmd".to_string(),
+            amount_in: amount_in.to_string(),
+            amount_out: "1".to_string(),
+            fee: "0".to_string(),
+            fee_rebate: "0".to_string(),
+            new_price: "1".to_string(),
+            price_change_bps: 0,
+            token_in: "usdc".to_string(),
+            swap_kind: SwapKind::ExactIn,
+            actor: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn webhooks_match_their_pool_types_and_threshold() {
+        let (pool_id, other) = (PoolId::new(), PoolId::new());
+        let registry = WebhookRegistry::new();
+        let whale = webhook(pool_id, &["swap_executed"], Some(1_000));
+        let everything = webhook(pool_id, &[], None);
+        assert!(registry.add(whale.clone()).is_ok());
+        assert!(registry.add(everything.clone()).is_ok());
+
+        let ids = |event: &PoolEvent| -> Vec<Uuid> {
+            registry.matching(event).iter().map(|h| h.id).collect()
+        };
+        assert_eq!(ids(&swap(pool_id, "5000")), vec![whale.id, everything.id]);
+        assert_eq!(ids(&swap(pool_id, "999")), vec![everything.id]);
+        assert!(ids(&swap(other, "5000")).is_empty());
+        let removed = PoolEvent::PoolRemoved {
+            pool_id,
+            timestamp: Utc::now(),
+        };
+        assert_eq!(ids(&removed), vec![everything.id]);
+
+        assert_eq!(registry.deliveries(pool_id, whale.id), Some(Vec::new()));
+        assert_eq!(registry.deliveries(other, whale.id), None);
+        assert!(registry.remove(pool_id, whale.id).is_some());
+        assert_eq!(registry.remove_pool(pool_id), 1);
+        assert!(registry.list(pool_id).is_empty());
+    }
+
+    #[test]
+    fn pools_are_capped_and_records_round_trip() {
+        let pool_id = PoolId::new();
+        let registry = WebhookRegistry::new();
+        for _ in 0..MAX_WEBHOOKS_PER_POOL {
+            assert!(registry.add(webhook(pool_id, &[], None)).is_ok());
+        }
+        assert!(matches!(
+            registry.add(webhook(pool_id, &[], None)),
+            Err(GatewayError::CapacityExceeded(_))
+        ));
+
+        let hook = webhook(pool_id, &["liquidity_changed"], Some(7));
+        assert_eq!(Webhook::from(&hook.to_record()), hook);
+        assert_eq!(hook.sign(1, "{}").len(), 64);
+        assert_eq!(generate_secret().len(), 64);
+    }
+}
@@ -0,0 +1,304 @@
+//! AsyncAPI description of the WebSocket protocol.
+//!
+//! The document is built from the same Rust types the connection loop
+//! serializes ([`WsMessage`], [`WsCommand`], [`PoolEvent`], [`TradeDto`]),
+//! so it cannot drift from the wire format. It is served at
+//! `GET /api-docs/asyncapi.json` next to the OpenAPI document.
+
+use serde_json::{Map, Value, json};
+use utoipa::ToSchema;
+
+use super::messages::{WsCommand, WsMessage, WsMessageType};
+use crate::api::dto::TradeDto;
+use crate::domain::PoolEvent;
+
+/// AsyncAPI specification version the document follows.
+pub const ASYNCAPI_VERSION: &str = "2.6.0";
+
+/// Error codes carried by `error` frames, with their meaning. Swap,
+/// quote, and state commands, job commands, and failed job steps report
+/// the REST error codes instead (e.g. `1001` invalid request, `2001` pool
+/// not found).
+pub const WS_ERROR_CODES: &[(u32, &str)] = &[
+    (400, "Malformed JSON envelope"),
+    (401, "Account mode requested without a client id"),
+    (404, "Unknown command"),
+    (429, "Rate limited: job slots or swap quota exhausted"),
+];
+
+/// Builds the AsyncAPI document for the `/ws` endpoint.
+#[must_use]
+pub fn document() -> Value {
+    let mut schemas = Map::new();
+    collect::<WsMessage>(&mut schemas);
+    collect::<WsMessageType>(&mut schemas);
+    collect::<WsCommand>(&mut schemas);
+    collect::<PoolEvent>(&mut schemas);
+    collect::<TradeDto>(&mut schemas);
+    schemas.insert(
+        "TradeFrame".to_string(),
+        json!({
+            "type": "object",
+            "description": "Payload of a trade tape event.",
+            "required": ["channel", "trade"],
+            "properties": {
+                "channel": { "type": "string", "enum": ["trades"] },
+                "trade": { "$ref": "#/components/schemas/TradeDto" },
+            },
+        }),
+    );
+    schemas.insert(
+        "QuoteFrame".to_string(),
+        json!({
+            "type": "object",
+            "description": "Payload of a quote stream frame: the `response` to `subscribe_quotes` and each `event` pushed after a price change carry `quote`, the same object the `quote` command returns. A quote that fails is pushed as `error`, and `ended` reports why the stream was closed.",
+            "required": ["channel", "subscription_id"],
+            "properties": {
+                "channel": { "type": "string", "enum": ["quotes"] },
+                "subscription_id": { "type": "string", "format": "uuid" },
+                "quote": { "type": "object" },
+                "error": { "$ref": "#/components/schemas/WsErrorPayload" },
+                "ended": { "type": "string", "enum": ["pool_removed"] },
+            },
+        }),
+    );
+    schemas.insert("WsErrorPayload".to_string(), error_payload_schema());
+    schemas.insert(
+        "SessionFrame".to_string(),
+        json!({
+            "type": "object",
+            "description": "Payload of the `session` frame.",
+            "required": ["session_token", "resumed", "last_sequence"],
+            "properties": {
+                "session_token": { "type": "string" },
+                "resumed": { "type": "boolean" },
+                "last_sequence": { "type": "integer", "minimum": 0 },
+            },
+        }),
+    );
+    schemas.insert(
+        "NoticeFrame".to_string(),
+        json!({
+            "type": "object",
+            "description": "Payload of `notice` frames. `events_dropped` reports events dropped because the client read slower than they were published and its outbound queue was full. `server_draining` announces that the server stops serving at `deadline`, `drain_in_secs` from now; clients should reconnect, to `alternate_host` when given, before then.",
+            "required": ["notice"],
+            "properties": {
+                "notice": { "type": "string", "enum": ["events_dropped", "server_draining"] },
+                "dropped_count": { "type": "integer", "minimum": 1 },
+                "drain_in_secs": { "type": "integer", "minimum": 0 },
+                "deadline": { "type": "string", "format": "date-time" },
+                "alternate_host": { "type": ["string", "null"] },
+            },
+        }),
+    );
+    schemas.insert(
+        "JobFrame".to_string(),
+        json!({
+            "type": "object",
+            "description": "Payload of `accepted` and `progress` frames, and of the final `response` of a job.",
+            "required": ["job_id"],
+            "properties": {
+                "job_id": { "type": "string", "format": "uuid" },
+                "command": { "type": "string" },
+                "completed": { "type": "integer", "minimum": 0 },
+                "total": { "type": "integer", "minimum": 0 },
+                "status": { "type": "string" },
+                "result": { "type": "object" },
+                "results": { "type": "array", "items": { "type": "object" } },
+            },
+        }),
+    );
+
+    json!({
+        "asyncapi": ASYNCAPI_VERSION,
+        "info": {
+            "title": "hydra-gateway WebSocket",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Real-time pool events, trade tape, quote streams, and commands. Every frame is a JSON envelope (`WsMessage`) whose `type` selects the payload; pool events may instead be sent as protobuf binary frames when subscribed with `\"encoding\": \"protobuf\"`, or as bare `PoolEvent` JSON with `\"encoding\": \"raw\"`.",
+        },
+        "defaultContentType": "application/json",
+        "channels": {
+            "/ws": {
+                "description": "Bidirectional connection. Browsers pass their client id as `?client_id=`; other clients use the `X-Client-Id` header. `?session=new` opens a resumable session and `?session=<token>` resumes one, with its subscriptions and event sequence numbers, on this or a replacement process. `?numbers=number` or `?numbers=object` changes how amounts are written in JSON event frames.",
+                "bindings": {
+                    "ws": {
+                        "query": {
+                            "type": "object",
+                            "properties": {
+                                "client_id": { "type": "string" },
+                                "session": { "type": "string" },
+                                "numbers": { "type": "string", "enum": ["string", "number", "object"] },
+                            },
+                        },
+                        "headers": {
+                            "type": "object",
+                            "properties": { "X-Client-Id": { "type": "string" } },
+                        },
+                    },
+                },
+                "publish": {
+                    "operationId": "sendCommand",
+                    "summary": "Commands sent by the client.",
+                    "message": { "$ref": "#/components/messages/Command" },
+                },
+                "subscribe": {
+                    "operationId": "receive",
+                    "summary": "Frames sent by the gateway.",
+                    "message": {
+                        "oneOf": [
+                            { "$ref": "#/components/messages/Response" },
+                            { "$ref": "#/components/messages/Event" },
+                            { "$ref": "#/components/messages/Error" },
+                            { "$ref": "#/components/messages/Accepted" },
+                            { "$ref": "#/components/messages/Progress" },
+                            { "$ref": "#/components/messages/Session" },
+                            { "$ref": "#/components/messages/Notice" },
+                        ],
+                    },
+                },
+            },
+        },
+        "components": {
+            "messages": {
+                "Command": message(
+                    WsMessageType::Command,
+                    "Client command; `id` is echoed in every frame answering it.",
+                    json!({ "$ref": "#/components/schemas/WsCommand" }),
+                ),
+                "Response": message(
+                    WsMessageType::Response,
+                    "Result of a command, or the final result of a job.",
+                    json!({ "type": "object" }),
+                ),
+                "Event": message(
+                    WsMessageType::Event,
+                    "Pool event or trade matching the connection's subscriptions, or a fresh quote of a quote stream.",
+                    json!({ "oneOf": [
+                        { "$ref": "#/components/schemas/PoolEvent" },
+                        { "$ref": "#/components/schemas/TradeFrame" },
+                        { "$ref": "#/components/schemas/QuoteFrame" },
+                    ] }),
+                ),
+                "Error": message(
+                    WsMessageType::Error,
+                    "Command failure.",
+                    json!({ "$ref": "#/components/schemas/WsErrorPayload" }),
+                ),
+                "Accepted": message(
+                    WsMessageType::Accepted,
+                    "A job command was started.",
+                    json!({ "$ref": "#/components/schemas/JobFrame" }),
+                ),
+                "Progress": message(
+                    WsMessageType::Progress,
+                    "Progress of a running job.",
+                    json!({ "$ref": "#/components/schemas/JobFrame" }),
+                ),
+                "Session": message(
+                    WsMessageType::Session,
+                    "First frame of a connection opened with `?session=`. Event frames then carry a `seq` that continues across resumes.",
+                    json!({ "$ref": "#/components/schemas/SessionFrame" }),
+                ),
+                "Notice": message(
+                    WsMessageType::Notice,
+                    "Out-of-band notice about the connection.",
+                    json!({ "$ref": "#/components/schemas/NoticeFrame" }),
+                ),
+            },
+            "schemas": schemas,
+        },
+    })
+}
+
+/// Adds `T` and every schema it references to `schemas`.
+fn collect<T: ToSchema>(schemas: &mut Map<String, Value>) {
+    let mut nested = Vec::new();
+    T::schemas(&mut nested);
+    let own = (T::name().into_owned(), T::schema());
+    for (name, schema) in nested.into_iter().chain(std::iter::once(own)) {
+        schemas.insert(name, serde_json::to_value(schema).unwrap_or_default());
+    }
+}
+
+/// A message whose envelope has type `msg_type` and carries `payload`.
+fn message(msg_type: WsMessageType, summary: &str, payload: Value) -> Value {
+    let name = serde_json::to_value(&msg_type).unwrap_or_default();
+    json!({
+        "name": name,
+        "summary": summary,
+        "payload": {
+            "allOf": [
+                { "$ref": "#/components/schemas/WsMessage" },
+                {
+                    "type": "object",
+                    "properties": {
+                        "type": { "type": "string", "enum": [name] },
+                        "payload": payload,
+                    },
+                },
+            ],
+        },
+    })
+}
+
+fn error_payload_schema() -> Value {
+    let codes: Vec<u32> = WS_ERROR_CODES.iter().map(|(code, _)| *code).collect();
+    let described: Vec<String> = WS_ERROR_CODES
+        .iter()
+        .map(|(code, meaning)| format!("`{code}`: {meaning}"))
+        .collect();
+    json!({
+        "type": "object",
+        "required": ["code", "message"],
+        "properties": {
+            "code": {
+                "type": "integer",
+                "description": format!(
+                    "{}. Pool and job commands use the REST error codes.",
+                    described.join("; ")
+                ),
+                "examples": codes,
+            },
+            "message": { "type": "string" },
+            "retryable": {
+                "type": "boolean",
+                "description": "Pool and job commands only: whether the same command may succeed if sent again later.",
+            },
+            "retry_after_ms": {
+                "type": "integer",
+                "description": "Pool and job commands only: how long to wait before retrying, when known.",
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document_describes_commands_events_and_their_types() {
+        let doc = document();
+        assert_eq!(
+            doc.get("asyncapi").and_then(Value::as_str),
+            Some(ASYNCAPI_VERSION)
+        );
+        for name in ["WsMessage", "WsCommand", "PoolEvent", "PoolId", "TradeDto"] {
+            assert!(
+                doc.pointer(&format!("/components/schemas/{name}"))
+                    .is_some(),
+                "missing schema {name}"
+            );
+        }
+        assert_eq!(
+            doc.pointer("/components/messages/Progress/name")
+                .and_then(Value::as_str),
+            Some("progress")
+        );
+        assert!(
+            doc.pointer("/components/schemas/WsCommand")
+                .is_some_and(|s| s.to_string().contains("batch_swap")),
+            "commands are generated from WsCommand"
+        );
+    }
+}
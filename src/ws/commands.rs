@@ -0,0 +1,265 @@
+//! Request/response WebSocket commands: `swap`, `quote`, and `get_state`.
+//!
+//! Each command is answered with a single `response` (or `error`) frame
+//! echoing the command's `id`. Swaps go through the same quota checks and
+//! event emission as `POST /pools/{id}/swap`; quotes never change pool
+//! state. The swap `spec` holds exactly one of `amount_in` / `amount_out`
+//! (string-encoded u128) and, for swaps, optional `expected_version`,
+//! `min_amount_out`, `max_amount_in`, and `deadline` conditions.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use hydra_amm::domain::{Amount, Token};
+use hydra_amm::traits::SwapPool;
+use serde_json::{Value, json};
+
+use super::jobs::{error_payload, frame};
+use super::messages::{WsCommand, WsMessageType};
+use crate::api::dto::PoolDetailResponse;
+use crate::api::numeric::NumericMode;
+use crate::domain::PoolId;
+use crate::domain::pool_operation::SwapKind;
+use crate::error::GatewayError;
+use crate::service::PoolService;
+use crate::service::pool_service::SwapConditions;
+use crate::service::quota::QuotaRegistry;
+
+/// Names of the commands handled here.
+pub const COMMANDS: [&str; 3] = ["swap", "quote", "get_state"];
+
+/// Executes request/response commands for one connection.
+#[derive(Debug, Clone)]
+pub struct CommandExecutor {
+    pool_service: Arc<PoolService>,
+    quotas: QuotaRegistry,
+    client_id: Option<String>,
+    numbers: NumericMode,
+}
+
+impl CommandExecutor {
+    /// Creates an executor acting for `client_id`, writing amounts in the
+    /// `numbers` mode.
+    #[must_use]
+    pub fn new(
+        pool_service: Arc<PoolService>,
+        quotas: QuotaRegistry,
+        client_id: Option<String>,
+        numbers: NumericMode,
+    ) -> Self {
+        Self {
+            pool_service,
+            quotas,
+            client_id,
+            numbers,
+        }
+    }
+
+    /// Runs the command in `payload` and returns its `response` frame, or
+    /// an `error` frame if it is malformed or fails.
+    pub async fn execute(&self, request_id: String, payload: &Value) -> Option<String> {
+        let result = match serde_json::from_value::<WsCommand>(payload.clone()) {
+            Ok(WsCommand::Swap {
+                pool_id,
+                token_in,
+                spec,
+            }) => self.swap(&pool_id, &token_in, &spec).await,
+            Ok(WsCommand::Quote {
+                pool_id,
+                token_in,
+                spec,
+            }) => self.quote(&pool_id, &token_in, &spec).await,
+            Ok(WsCommand::GetState { pool_id }) => self.get_state(&pool_id).await,
+            Ok(_) => Err(GatewayError::InvalidRequest(
+                "not a request/response command".to_string(),
+            )),
+            Err(e) => Err(GatewayError::InvalidRequest(e.to_string())),
+        };
+        self.answer(request_id, result)
+    }
+
+    /// Builds the `response` frame of a successful command, with amounts
+    /// in the connection's `numbers` mode, or the `error` frame of a
+    /// failed one.
+    pub(super) fn answer(
+        &self,
+        request_id: String,
+        result: Result<Value, GatewayError>,
+    ) -> Option<String> {
+        match result {
+            Ok(mut response) => {
+                self.numbers.apply(&mut response);
+                frame(request_id, WsMessageType::Response, response)
+            }
+            Err(e) => frame(request_id, WsMessageType::Error, error_payload(&e)),
+        }
+    }
+
+    async fn swap(
+        &self,
+        pool_id: &str,
+        token_in: &str,
+        spec: &Value,
+    ) -> Result<Value, GatewayError> {
+        let pool_id = parse_pool_id(pool_id)?;
+        let (kind, amount) = parse_spec(spec).map_err(GatewayError::InvalidRequest)?;
+        let conditions = SwapConditions {
+            expected_version: spec.get("expected_version").and_then(Value::as_u64),
+            min_amount_out: parse_bound(spec, "min_amount_out")?,
+            max_amount_in: parse_bound(spec, "max_amount_in")?,
+            deadline: spec
+                .get("deadline")
+                .map(|raw| serde_json::from_value(raw.clone()))
+                .transpose()
+                .map_err(|e| GatewayError::InvalidRequest(format!("invalid deadline: {e}")))?,
+        };
+        let token = self.resolve(pool_id, token_in).await?;
+        self.quotas
+            .check_swap(self.client_id.as_deref(), Utc::now())?;
+
+        let swap_id = uuid::Uuid::new_v4().to_string();
+        let (result, rebate) = self
+            .pool_service
+            .execute_swap(
+                pool_id,
+                kind,
+                Amount::new(amount),
+                token,
+                conditions,
+                &swap_id,
+                self.client_id.as_deref(),
+            )
+            .await?;
+        Ok(json!({
+            "swap_id": swap_id,
+            "pool_id": pool_id,
+            "token_in": token_in,
+            "amount_in": result.amount_in().get().to_string(),
+            "amount_out": result.amount_out().get().to_string(),
+            "fee_charged": result.fee().get().to_string(),
+            "fee_rebate": rebate.amount.to_string(),
+        }))
+    }
+
+    async fn quote(
+        &self,
+        pool_id: &str,
+        token_in: &str,
+        spec: &Value,
+    ) -> Result<Value, GatewayError> {
+        let pool_id = parse_pool_id(pool_id)?;
+        let (kind, amount) = parse_spec(spec).map_err(GatewayError::InvalidRequest)?;
+        self.quote_amount(pool_id, token_in, kind, amount).await
+    }
+
+    /// Prices a swap of `amount` of `token_in` on `pool_id`, as the
+    /// `quote` command does.
+    pub(super) async fn quote_amount(
+        &self,
+        pool_id: PoolId,
+        token_in: &str,
+        kind: SwapKind,
+        amount: u128,
+    ) -> Result<Value, GatewayError> {
+        let token = self.resolve(pool_id, token_in).await?;
+        let (spot_price, pool_version) = {
+            let entry_lock = self.pool_service.registry().get(pool_id).await?;
+            let entry = entry_lock.read().await;
+            (entry.spot_price(), entry.version())
+        };
+
+        let result = self
+            .pool_service
+            .quote_swap(pool_id, kind, Amount::new(amount), token)
+            .await?;
+        Ok(json!({
+            "pool_id": pool_id,
+            "token_in": token_in,
+            "amount_in": result.amount_in().get().to_string(),
+            "amount_out": result.amount_out().get().to_string(),
+            "fee_charged": result.fee().get().to_string(),
+            "spot_price": spot_price.map(|p| format!("{p}")),
+            "pool_version": pool_version,
+        }))
+    }
+
+    async fn get_state(&self, pool_id: &str) -> Result<Value, GatewayError> {
+        let pool_id = parse_pool_id(pool_id)?;
+        let entry_lock = self.pool_service.registry().get(pool_id).await?;
+        let entry = entry_lock.read().await;
+        let mut state = serde_json::to_value(PoolDetailResponse::from(&*entry))
+            .map_err(|e| GatewayError::Internal(e.to_string()))?;
+        if let Some(fields) = state.as_object_mut() {
+            fields.insert("pool_version".to_string(), json!(entry.version()));
+        }
+        Ok(state)
+    }
+
+    /// Resolves `token_in`, an address label, to one of the pool's tokens.
+    async fn resolve(&self, pool_id: PoolId, token_in: &str) -> Result<Token, GatewayError> {
+        let entry_lock = self.pool_service.registry().get(pool_id).await?;
+        let entry = entry_lock.read().await;
+        entry
+            .side_of_label(token_in)
+            .map(|side| side.token(entry.pool_box.token_pair()))
+            .ok_or_else(|| {
+                GatewayError::InvalidRequest(format!("token_in {token_in} not found in pool"))
+            })
+    }
+}
+
+/// Reads an optional slippage bound (string-encoded u128) from `spec`.
+fn parse_bound(spec: &Value, field: &str) -> Result<Option<u128>, GatewayError> {
+    spec.get(field)
+        .and_then(Value::as_str)
+        .map(|raw| {
+            raw.parse::<u128>()
+                .map_err(|_| GatewayError::InvalidRequest(format!("invalid {field}: {raw}")))
+        })
+        .transpose()
+}
+
+pub(super) fn parse_pool_id(pool_id: &str) -> Result<PoolId, GatewayError> {
+    pool_id
+        .parse::<uuid::Uuid>()
+        .map(PoolId::from_uuid)
+        .map_err(|_| GatewayError::InvalidRequest(format!("invalid pool_id {pool_id}")))
+}
+
+/// Reads the direction and amount of a swap from `item`, which holds
+/// exactly one of `amount_in` / `amount_out` (a positive string-encoded
+/// u128). Errors are plain messages, for the caller to put in context.
+pub(super) fn parse_spec(item: &Value) -> Result<(SwapKind, u128), String> {
+    let field = |key: &str| item.get(key).and_then(Value::as_str);
+    let (kind, raw) = match (field("amount_in"), field("amount_out")) {
+        (Some(raw), None) => (SwapKind::ExactIn, raw),
+        (None, Some(raw)) => (SwapKind::ExactOut, raw),
+        _ => return Err("specify exactly one of amount_in or amount_out".to_string()),
+    };
+    let amount = raw
+        .parse::<u128>()
+        .ok()
+        .filter(|a| *a > 0)
+        .ok_or_else(|| format!("invalid amount {raw}"))?;
+    Ok((kind, amount))
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spec_takes_exactly_one_positive_amount() {
+        let Ok((kind, amount)) = parse_spec(&json!({ "amount_out": "25" })) else {
+            panic!("valid spec rejected");
+        };
+        assert!(matches!(kind, SwapKind::ExactOut));
+        assert_eq!(amount, 25);
+        assert!(parse_spec(&json!({})).is_err());
+        assert!(parse_spec(&json!({ "amount_in": "1", "amount_out": "1" })).is_err());
+        assert!(parse_spec(&json!({ "amount_in": "0" })).is_err());
+        assert!(parse_spec(&json!({ "amount_in": "-5" })).is_err());
+        assert!(parse_pool_id("not-a-uuid").is_err());
+    }
+}
@@ -3,79 +3,394 @@
 //! Handles the read/write loop for a single WebSocket connection,
 //! dispatching incoming commands and forwarding filtered events.
 
+use std::sync::PoisonError;
+
 use axum::extract::ws::{Message, WebSocket};
 use futures_util::{SinkExt, StreamExt};
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc, watch};
 
+use super::commands::{self, CommandExecutor};
+use super::drain::{self, DrainNotice};
+use super::heartbeat::{self, Heartbeat};
+use super::jobs::JobRunner;
+use super::limits::{self, FrameLimits};
 use super::messages::{WsMessage, WsMessageType};
-use super::subscription::SubscriptionManager;
-use crate::domain::{PoolEvent, PoolId};
+use super::outbound::{ConnectionHandle, EventClass, EventPriority, OutboundQueue};
+use super::quote_stream::{self, QuoteOutput, QuoteWorker};
+use super::raw::{RawEvent, RawEventFeed};
+use super::session::{Closing, SessionRegistry, SharedSession};
+use super::subscription::{CatalogFilter, EventEncoding, SubscriptionManager};
+use crate::api::dto::TradeDto;
+use crate::api::numeric::NumericMode;
+use crate::domain::{EventBus, PoolFilter, PoolId};
 use crate::service::PoolService;
+use crate::service::quota::QuotaRegistry;
+use crate::service::trade_tape::{Trade, TradeTape};
+use crate::service::webhooks::EVENT_TYPES;
 
 /// Runs the read/write loop for a single WebSocket connection.
 ///
 /// - Reads commands from the client and dispatches them.
-/// - Forwards matching events from the [`broadcast::Receiver`] to the client.
+/// - Forwards matching events from `event_bus` to the client.
+/// - Streams the trade tape while the `trades` channel is subscribed.
+/// - Takes events from the shared, pre-serialized `raw_feed` instead of
+///   `event_bus` while the `raw` encoding is selected.
+/// - Executes `swap`, `quote`, and `get_state` commands, answering each
+///   with a `response` carrying the command's `id`.
+/// - Pushes a fresh quote for each quote stream opened with
+///   `subscribe_quotes` whenever its pool's price changes; quotes are
+///   priced on the connection's [`QuoteWorker`], off this loop.
+/// - Forwards progress of background jobs started by the client.
+/// - With a `session`, restores its subscriptions, numbers event frames,
+///   and keeps the session up to date for a later resume.
+/// - Sends the `server_draining` notice announced on `draining`.
+/// - Closes with the frame of `closing` once it is set: `1012 Service
+///   Restart` for a handover, `1001 Going Away` for a shutdown.
+/// - Writes amounts in JSON event frames in the `numbers` mode.
+/// - Closes with `1008 Policy Violation` when the client sends a message
+///   breaking `limits`, or one the transport rejects as oversized.
+/// - Pings the client on the `heartbeat` interval, and closes with
+///   `1001 Going Away` once it has sent nothing for the idle timeout.
+/// - Writes frames through the bounded outbound queue of `connection`,
+///   which drops events or disconnects when the client reads too slowly.
+/// - Counts payload bytes both ways against the client's usage, through
+///   `connection`.
+#[allow(clippy::too_many_arguments)]
 pub async fn run_connection(
     socket: WebSocket,
-    mut event_rx: broadcast::Receiver<PoolEvent>,
-    _pool_service: std::sync::Arc<PoolService>,
+    event_bus: EventBus,
+    raw_feed: RawEventFeed,
+    pool_service: std::sync::Arc<PoolService>,
+    trade_tape: TradeTape,
+    quotas: QuotaRegistry,
+    client_id: Option<String>,
+    session: Option<(SharedSession, bool)>,
+    mut closing: watch::Receiver<Option<Closing>>,
+    mut draining: watch::Receiver<Option<DrainNotice>>,
+    numbers: NumericMode,
+    limits: FrameLimits,
+    heartbeat: Heartbeat,
+    connection: ConnectionHandle,
 ) {
     let (mut ws_tx, mut ws_rx) = socket.split();
-    let mut subs = SubscriptionManager::new();
+    let (job_tx, mut job_rx) = mpsc::unbounded_channel();
+    let executor = CommandExecutor::new(
+        std::sync::Arc::clone(&pool_service),
+        quotas.clone(),
+        client_id.clone(),
+        numbers,
+    );
+    let jobs = JobRunner::new(
+        std::sync::Arc::clone(&pool_service),
+        quotas,
+        client_id.clone(),
+        job_tx,
+    );
+    let mut subs = SubscriptionManager::for_client(client_id.clone());
+    let (quote_tx, mut quote_rx) = mpsc::unbounded_channel();
+    let quotes = QuoteWorker::spawn(executor.clone(), pool_service.event_bus().clone(), quote_tx);
+    let mut seq = None;
+    if let Some((shared, resumed)) = &session {
+        let (token, last_sequence) = {
+            let descriptor = shared.lock().unwrap_or_else(PoisonError::into_inner);
+            subs = SubscriptionManager::restore(client_id, &descriptor.subscriptions);
+            (descriptor.token.clone(), descriptor.last_sequence)
+        };
+        seq = Some(last_sequence);
+        let msg = WsMessage {
+            id: uuid::Uuid::new_v4().to_string(),
+            msg_type: WsMessageType::Session,
+            timestamp: chrono::Utc::now(),
+            seq: None,
+            payload: serde_json::json!({
+                "session_token": token,
+                "resumed": resumed,
+                "last_sequence": last_sequence,
+            }),
+        };
+        let frame = Message::text(serde_json::to_string(&msg).unwrap_or_default());
+        connection.record_sent(&frame);
+        if ws_tx.send(frame).await.is_err() {
+            SessionRegistry::release(shared);
+            return;
+        }
+    }
+    let session = session.map(|(shared, _)| shared);
+    let mut trades_rx: Option<broadcast::Receiver<Trade>> =
+        subs.trades_enabled().then(|| trade_tape.subscribe());
+    let raw = subs.encoding() == EventEncoding::Raw;
+    let mut raw_rx: Option<broadcast::Receiver<std::sync::Arc<RawEvent>>> =
+        raw.then(|| raw_feed.subscribe());
+    let mut event_rx = (!raw).then(|| event_bus.subscribe());
+    let outbound = OutboundQueue::spawn(ws_tx, &connection);
+    let mut close = None;
+    let mut ping = heartbeat.ticker();
+    let mut last_seen = tokio::time::Instant::now();
+    // Connected during a countdown: announce it right away
+    let pending_drain = draining.borrow_and_update().clone();
+    if let Some(notice) = pending_drain {
+        let _ = outbound.push(notice.to_message(chrono::Utc::now()));
+    }
 
     loop {
         tokio::select! {
+            // Handover to a replacement process, or shutdown
+            reason = server_closing(&mut closing) => {
+                close = Some(reason.close_frame());
+                break;
+            }
+            // Shutdown announced: tell the client when and where to go
+            notice = drain::announced(&mut draining) => {
+                if outbound.push(notice.to_message(chrono::Utc::now())).is_err() {
+                    break;
+                }
+            }
+            // Keepalive for the client and any load balancer in between
+            () = heartbeat::next_ping(&mut ping) => {
+                if outbound.push(Message::Ping(axum::body::Bytes::new())).is_err() {
+                    break;
+                }
+            }
+            // Nothing heard from the client, not even a pong
+            () = heartbeat::idle(heartbeat.idle_deadline(last_seen)) => {
+                tracing::debug!("closing idle ws connection");
+                close = Some(heartbeat::idle_close());
+                break;
+            }
             // Incoming message from client
             msg = ws_rx.next() => {
+                if let Some(Ok(message)) = &msg {
+                    last_seen = tokio::time::Instant::now();
+                    connection.record_received(message);
+                }
                 match msg {
                     Some(Ok(Message::Text(text))) => {
-                        let response = handle_text_message(&text, &mut subs);
+                        if let Err(reason) = limits.check(&text) {
+                            tracing::debug!(%reason, "closing ws connection over frame limits");
+                            close = Some(limits::policy_close(&reason));
+                            break;
+                        }
+                        let response = handle_text_message(
+                            &text,
+                            &mut subs,
+                            &quotes,
+                            &pool_service,
+                            &jobs,
+                            &executor,
+                        )
+                        .await;
+                        match (subs.trades_enabled(), trades_rx.is_some()) {
+                            (true, false) => trades_rx = Some(trade_tape.subscribe()),
+                            (false, true) => trades_rx = None,
+                            _ => {}
+                        }
+                        match (subs.encoding() == EventEncoding::Raw, raw_rx.is_some()) {
+                            (true, false) => {
+                                raw_rx = Some(raw_feed.subscribe());
+                                event_rx = None;
+                            }
+                            (false, true) => {
+                                event_rx = Some(event_bus.subscribe());
+                                raw_rx = None;
+                            }
+                            _ => {}
+                        }
+                        if let Some(shared) = &session {
+                            let mut descriptor = shared.lock().unwrap_or_else(PoisonError::into_inner);
+                            descriptor.subscriptions = subs.export();
+                            descriptor.updated_at = chrono::Utc::now();
+                        }
                         if let Some(resp_json) = response
-                            && ws_tx.send(Message::text(resp_json)).await.is_err() {
+                            && outbound.push(Message::text(resp_json)).is_err() {
                                 break;
                             }
                     }
                     Some(Ok(Message::Close(_))) | None => break,
+                    Some(Err(e)) => {
+                        tracing::debug!(error = %e, "ws read failed");
+                        close = Some(limits::policy_close("message rejected"));
+                        break;
+                    }
                     _ => {}
                 }
             }
             // Event from EventBus
-            event = event_rx.recv() => {
+            event = next_on(&mut event_rx) => {
                 match event {
                     Ok(pool_event) => {
-                        if subs.matches(pool_event.pool_id()) {
+                        if subs.wants(&pool_event) {
+                            // Only JSON envelopes carry a sequence number
+                            let frame = match subs.encoding() {
+                                EventEncoding::Json => {
+                                    let mut payload = pool_service.event_payload(&pool_event);
+                                    numbers.apply(&mut payload);
+                                    let msg = WsMessage {
+                                        id: uuid::Uuid::new_v4().to_string(),
+                                        msg_type: WsMessageType::Event,
+                                        timestamp: chrono::Utc::now(),
+                                        seq: next_seq(&mut seq, session.as_ref()),
+                                        payload,
+                                    };
+                                    Message::text(serde_json::to_string(&msg).unwrap_or_default())
+                                }
+                                EventEncoding::Protobuf => {
+                                    Message::binary(crate::proto::encode_event(&pool_event))
+                                }
+                                EventEncoding::Raw => {
+                                    Message::text(serde_json::to_string(&pool_event).unwrap_or_default())
+                                }
+                            };
+                            if outbound.push_event(frame, EventClass::of(&pool_event)).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(lagged = n, "ws client lagged behind event bus");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            // Pre-serialized event for the raw encoding
+            raw = next_on(&mut raw_rx) => {
+                match raw {
+                    Ok(raw) => {
+                        if subs.wants(&raw.event)
+                            && outbound
+                                .push_event(Message::Text(raw.frame.clone()), EventClass::of(&raw.event))
+                                .is_err()
+                        {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(lagged = n, "ws client lagged behind raw event feed");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            // Trade from the tape
+            trade = next_on(&mut trades_rx) => {
+                match trade {
+                    Ok(trade) => {
+                        if subs.wants_trade(trade.pool_id) {
+                            let mut payload = serde_json::json!({
+                                "channel": "trades",
+                                "trade": TradeDto::from(&trade),
+                            });
+                            numbers.apply(&mut payload);
                             let msg = WsMessage {
                                 id: uuid::Uuid::new_v4().to_string(),
                                 msg_type: WsMessageType::Event,
                                 timestamp: chrono::Utc::now(),
-                                payload: serde_json::to_value(&pool_event).unwrap_or_default(),
+                                seq: next_seq(&mut seq, session.as_ref()),
+                                payload,
                             };
-                            let json = serde_json::to_string(&msg).unwrap_or_default();
-                            if ws_tx.send(Message::text(json)).await.is_err() {
+                            let text = serde_json::to_string(&msg).unwrap_or_default();
+                            let class = EventClass::new(EventPriority::High);
+                            if outbound.push_event(Message::text(text), class).is_err() {
                                 break;
                             }
                         }
                     }
                     Err(broadcast::error::RecvError::Lagged(n)) => {
-                        tracing::warn!(lagged = n, "ws client lagged behind event bus");
+                        tracing::warn!(lagged = n, "ws client lagged behind trade tape");
                     }
-                    Err(broadcast::error::RecvError::Closed) => break,
+                    Err(broadcast::error::RecvError::Closed) => trades_rx = None,
+                }
+            }
+            // Answer or fresh quote from the quote worker
+            Some(output) = quote_rx.recv() => {
+                let pushed = match output {
+                    QuoteOutput::Response(frame) => outbound.push(Message::text(frame)),
+                    QuoteOutput::Push(mut payload) => {
+                        let class = quote_stream::delivery_class(&payload);
+                        numbers.apply(&mut payload);
+                        let msg = WsMessage {
+                            id: uuid::Uuid::new_v4().to_string(),
+                            msg_type: WsMessageType::Event,
+                            timestamp: chrono::Utc::now(),
+                            seq: next_seq(&mut seq, session.as_ref()),
+                            payload,
+                        };
+                        let text = serde_json::to_string(&msg).unwrap_or_default();
+                        outbound.push_event(Message::text(text), class)
+                    }
+                };
+                if pushed.is_err() {
+                    break;
+                }
+            }
+            // Progress or result of a background job
+            Some(frame) = job_rx.recv() => {
+                if outbound.push(Message::text(frame)).is_err() {
+                    break;
                 }
             }
         }
     }
 
+    outbound.finish(close).await;
+    if let Some(shared) = &session {
+        SessionRegistry::release(shared);
+    }
+    drop(connection);
     tracing::debug!("ws connection closed");
 }
 
+/// Advances the session's sequence number for the next event frame and
+/// records it on the session. `None` on connections without a session.
+fn next_seq(seq: &mut Option<u64>, session: Option<&SharedSession>) -> Option<u64> {
+    let next = seq.as_mut().map(|seq| {
+        *seq += 1;
+        *seq
+    })?;
+    if let Some(shared) = session {
+        shared
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .last_sequence = next;
+    }
+    Some(next)
+}
+
+/// Resolves once `closing` is set, or never if its sender is gone.
+async fn server_closing(closing: &mut watch::Receiver<Option<Closing>>) -> Closing {
+    match closing
+        .wait_for(Option::is_some)
+        .await
+        .map(|reason| *reason)
+    {
+        Ok(Some(reason)) => reason,
+        _ => std::future::pending().await,
+    }
+}
+
+/// Waits for the next value on `rx`, or forever while it is off.
+pub(super) async fn next_on<T: Clone>(
+    rx: &mut Option<broadcast::Receiver<T>>,
+) -> Result<T, broadcast::error::RecvError> {
+    match rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
 /// Handles a text message from the client, returning an optional JSON response.
-fn handle_text_message(text: &str, subs: &mut SubscriptionManager) -> Option<String> {
+async fn handle_text_message(
+    text: &str,
+    subs: &mut SubscriptionManager,
+    quotes: &QuoteWorker,
+    pool_service: &PoolService,
+    jobs: &JobRunner,
+    executor: &CommandExecutor,
+) -> Option<String> {
     let Ok(msg) = serde_json::from_str::<WsMessage>(text) else {
         let err = WsMessage {
             id: String::new(),
             msg_type: WsMessageType::Error,
             timestamp: chrono::Utc::now(),
+            seq: None,
             payload: serde_json::json!({
                 "code": 400,
                 "message": "malformed JSON"
@@ -84,6 +399,145 @@ fn handle_text_message(text: &str, subs: &mut SubscriptionManager) -> Option<Str
         return serde_json::to_string(&err).ok();
     };
 
+    // Batch swap job: `{"command": "batch_swap", "swaps": [..]}`
+    if msg.payload.get("command").and_then(|v| v.as_str()) == Some("batch_swap") {
+        return jobs.batch_swap(msg.id, &msg.payload);
+    }
+
+    // Request/response commands: `swap`, `quote`, `get_state`
+    if msg
+        .payload
+        .get("command")
+        .and_then(|v| v.as_str())
+        .is_some_and(|command| commands::COMMANDS.contains(&command))
+    {
+        return executor.execute(msg.id, &msg.payload).await;
+    }
+
+    // Quote streams: `subscribe_quotes`, `unsubscribe_quotes`
+    if msg
+        .payload
+        .get("command")
+        .and_then(|v| v.as_str())
+        .is_some_and(|command| quote_stream::COMMANDS.contains(&command))
+    {
+        quotes.execute(msg.id, msg.payload);
+        return None;
+    }
+
+    // Pool catalog: `{"command": "subscribe"|"unsubscribe", "channel":
+    // "pool_catalog", "pool_types": [..], "tokens": [..]}`
+    if msg.payload.get("channel").and_then(|v| v.as_str()) == Some("pool_catalog") {
+        let strings = |key: &str| -> Vec<String> {
+            msg.payload
+                .get(key)
+                .and_then(|v| v.as_array())
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        match msg.payload.get("command").and_then(|v| v.as_str()) {
+            Some("subscribe") | None => {
+                let filter = CatalogFilter {
+                    pool_types: strings("pool_types"),
+                    tokens: strings("tokens"),
+                };
+                let existing: Vec<PoolId> = pool_service
+                    .list_pools(&PoolFilter::default())
+                    .await
+                    .into_iter()
+                    .filter(|p| {
+                        filter.matches(&p.pool_type, p.tokens.iter().map(|t| t.address.as_str()))
+                    })
+                    .map(|p| p.pool_id)
+                    .collect();
+                subs.set_catalog(filter, existing);
+            }
+            Some("unsubscribe") => subs.clear_catalog(),
+            Some(_) => return unknown_command(msg.id),
+        }
+        let response = WsMessage {
+            id: msg.id,
+            msg_type: WsMessageType::Response,
+            timestamp: chrono::Utc::now(),
+            seq: None,
+            payload: serde_json::json!({
+                "channel": "pool_catalog",
+                "subscribed": subs.catalog().is_some(),
+                "pool_types": subs.catalog().map(|f| f.pool_types.clone()),
+                "tokens": subs.catalog().map(|f| f.tokens.clone()),
+            }),
+        };
+        return serde_json::to_string(&response).ok();
+    }
+
+    // Trade tape: `{"command": "subscribe"|"unsubscribe", "channel":
+    // "trades", "pool_ids": [..]}`; no ids (or `"*"`) means every pool.
+    if msg.payload.get("channel").and_then(|v| v.as_str()) == Some("trades") {
+        match msg.payload.get("command").and_then(|v| v.as_str()) {
+            Some("subscribe") | None => {
+                let ids: Vec<PoolId> = msg
+                    .payload
+                    .get("pool_ids")
+                    .and_then(|v| v.as_array())
+                    .map(|arr| {
+                        arr.iter()
+                            .filter_map(|v| v.as_str()?.parse::<uuid::Uuid>().ok())
+                            .map(PoolId::from_uuid)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                subs.set_trades(&ids);
+            }
+            Some("unsubscribe") => subs.clear_trades(),
+            Some(_) => return unknown_command(msg.id),
+        }
+        let response = WsMessage {
+            id: msg.id,
+            msg_type: WsMessageType::Response,
+            timestamp: chrono::Utc::now(),
+            seq: None,
+            payload: serde_json::json!({
+                "channel": "trades",
+                "subscribed": subs.trades_enabled(),
+            }),
+        };
+        return serde_json::to_string(&response).ok();
+    }
+
+    // Account mode: `{"command": "subscribe"|"unsubscribe", "mode": "account"}`
+    if msg.payload.get("mode").and_then(|v| v.as_str()) == Some("account") {
+        let enable = match msg.payload.get("command").and_then(|v| v.as_str()) {
+            Some("subscribe") | None => true,
+            Some("unsubscribe") => false,
+            Some(_) => return unknown_command(msg.id),
+        };
+        if !subs.set_account(enable) {
+            let err = WsMessage {
+                id: msg.id,
+                msg_type: WsMessageType::Error,
+                timestamp: chrono::Utc::now(),
+                seq: None,
+                payload: serde_json::json!({
+                    "code": 401,
+                    "message": "account mode requires a client id (X-Client-Id header or client_id query parameter)"
+                }),
+            };
+            return serde_json::to_string(&err).ok();
+        }
+        let response = WsMessage {
+            id: msg.id,
+            msg_type: WsMessageType::Response,
+            timestamp: chrono::Utc::now(),
+            seq: None,
+            payload: serde_json::json!({ "account": subs.is_account() }),
+        };
+        return serde_json::to_string(&response).ok();
+    }
+
     // Try to parse as a command with pool_ids for subscribe/unsubscribe
     if let Some(pool_ids) = msg.payload.get("pool_ids").and_then(|v| v.as_array()) {
         let command = msg
@@ -105,15 +559,89 @@ fn handle_text_message(text: &str, subs: &mut SubscriptionManager) -> Option<Str
                         }
                     }
                 }
+                // `min_liquidity_change`: string-encoded u128 (or a JSON
+                // number); null or 0 clears it, absent keeps it.
+                if let Some(value) = msg.payload.get("min_liquidity_change") {
+                    let min = match value {
+                        serde_json::Value::Null => Some(None),
+                        serde_json::Value::String(s) => s.parse::<u128>().ok().map(Some),
+                        serde_json::Value::Number(n) => n.as_u64().map(|n| Some(u128::from(n))),
+                        _ => None,
+                    };
+                    let Some(min) = min else {
+                        let err = WsMessage {
+                            id: msg.id,
+                            msg_type: WsMessageType::Error,
+                            timestamp: chrono::Utc::now(),
+                            seq: None,
+                            payload: serde_json::json!({
+                                "code": 400,
+                                "message": "min_liquidity_change must be a non-negative integer"
+                            }),
+                        };
+                        return serde_json::to_string(&err).ok();
+                    };
+                    subs.set_min_liquidity_change(min);
+                }
+                // `event_types`: event type names; null or [] clears the
+                // filter, absent keeps it.
+                if let Some(value) = msg.payload.get("event_types") {
+                    let types = match value {
+                        serde_json::Value::Null => Some(None),
+                        serde_json::Value::Array(types) => types
+                            .iter()
+                            .map(|t| {
+                                t.as_str()
+                                    .filter(|t| EVENT_TYPES.contains(t))
+                                    .map(str::to_string)
+                            })
+                            .collect::<Option<Vec<_>>>()
+                            .map(Some),
+                        _ => None,
+                    };
+                    let Some(types) = types else {
+                        let err = WsMessage {
+                            id: msg.id,
+                            msg_type: WsMessageType::Error,
+                            timestamp: chrono::Utc::now(),
+                            seq: None,
+                            payload: serde_json::json!({
+                                "code": 400,
+                                "message": format!(
+                                    "event_types must be a list of: {}",
+                                    EVENT_TYPES.join(", ")
+                                )
+                            }),
+                        };
+                        return serde_json::to_string(&err).ok();
+                    };
+                    subs.set_event_types(types);
+                }
                 subs.subscribe(&ids, wildcard);
+                if let Some(encoding) = msg
+                    .payload
+                    .get("encoding")
+                    .and_then(|v| v.as_str())
+                    .and_then(EventEncoding::parse)
+                {
+                    subs.set_encoding(encoding);
+                }
                 let response = WsMessage {
                     id: msg.id,
                     msg_type: WsMessageType::Response,
                     timestamp: chrono::Utc::now(),
+                    seq: None,
                     payload: serde_json::json!({
                         "subscribed": ids.iter().map(|id| id.to_string()).collect::<Vec<_>>(),
                         "count": subs.count(),
                         "wildcard": subs.is_subscribed_all(),
+                        "min_liquidity_change": subs.min_liquidity_change().map(|m| m.to_string()),
+                        "event_types": subs.event_types(),
+                        "encoding": match subs.encoding() {
+                            EventEncoding::Json => "json",
+                            EventEncoding::Protobuf => "protobuf",
+                            EventEncoding::Raw => "raw",
+                        },
                     }),
                 };
                 return serde_json::to_string(&response).ok();
@@ -132,6 +660,7 @@ fn handle_text_message(text: &str, subs: &mut SubscriptionManager) -> Option<Str
                     id: msg.id,
                     msg_type: WsMessageType::Response,
                     timestamp: chrono::Utc::now(),
+                    seq: None,
                     payload: serde_json::json!({
                         "unsubscribed": ids.iter().map(|id| id.to_string()).collect::<Vec<_>>(),
                         "remaining_count": subs.count(),
@@ -143,11 +672,16 @@ fn handle_text_message(text: &str, subs: &mut SubscriptionManager) -> Option<Str
         }
     }
 
-    // Unknown command
+    unknown_command(msg.id)
+}
+
+/// Builds the error response for an unrecognised command.
+fn unknown_command(id: String) -> Option<String> {
     let err = WsMessage {
-        id: msg.id,
+        id,
         msg_type: WsMessageType::Error,
         timestamp: chrono::Utc::now(),
+        seq: None,
         payload: serde_json::json!({
             "code": 404,
             "message": "unknown command"
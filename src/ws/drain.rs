@@ -0,0 +1,143 @@
+//! `server_draining` announcements ahead of a shutdown.
+//!
+//! With `WS_DRAIN_NOTICE_SECS` set, a shutdown signal first sends every
+//! WebSocket client a `notice` frame saying when the server will stop
+//! and, if `WS_DRAIN_ALTERNATE_HOST` is set, where to reconnect. The
+//! server keeps serving through the countdown so algorithmic clients can
+//! move their subscriptions on their own schedule; connections opened
+//! meanwhile get the notice as soon as they connect. The usual graceful
+//! shutdown (and session handover) follows once the countdown ends.
+
+use std::time::Duration;
+
+use axum::extract::ws::Message;
+use chrono::{DateTime, Utc};
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use super::messages::{WsMessage, WsMessageType};
+
+/// A pending shutdown, as announced to clients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrainNotice {
+    /// When the server stops serving.
+    pub deadline: DateTime<Utc>,
+    /// Where clients should reconnect, if configured.
+    pub alternate_host: Option<String>,
+}
+
+impl DrainNotice {
+    /// Builds the `notice` frame, counting down from `now`.
+    #[must_use]
+    pub fn to_message(&self, now: DateTime<Utc>) -> Message {
+        let remaining = (self.deadline - now).num_milliseconds().max(0);
+        let msg = WsMessage {
+            id: Uuid::new_v4().to_string(),
+            msg_type: WsMessageType::Notice,
+            timestamp: now,
+            seq: None,
+            payload: serde_json::json!({
+                "notice": "server_draining",
+                "drain_in_secs": (remaining + 999) / 1_000,
+                "deadline": self.deadline,
+                "alternate_host": self.alternate_host,
+            }),
+        };
+        Message::text(serde_json::to_string(&msg).unwrap_or_default())
+    }
+}
+
+/// Publishes the drain notice to every connection.
+#[derive(Debug, Clone)]
+pub struct DrainAnnouncer {
+    notice: watch::Sender<Option<DrainNotice>>,
+}
+
+impl Default for DrainAnnouncer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DrainAnnouncer {
+    /// Creates an announcer with nothing announced.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            notice: watch::Sender::new(None),
+        }
+    }
+
+    /// Announces that the server stops in `countdown`, returning the
+    /// notice sent.
+    pub fn announce(&self, countdown: Duration, alternate_host: Option<String>) -> DrainNotice {
+        let deadline = chrono::Duration::from_std(countdown)
+            .ok()
+            .and_then(|countdown| Utc::now().checked_add_signed(countdown))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        let notice = DrainNotice {
+            deadline,
+            alternate_host,
+        };
+        self.notice.send_replace(Some(notice.clone()));
+        notice
+    }
+
+    /// Receiver a connection watches for the announcement.
+    #[must_use]
+    pub fn subscribe(&self) -> watch::Receiver<Option<DrainNotice>> {
+        self.notice.subscribe()
+    }
+}
+
+/// Waits for the next announcement, or forever if none comes.
+pub async fn announced(rx: &mut watch::Receiver<Option<DrainNotice>>) -> DrainNotice {
+    loop {
+        if rx.changed().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+        if let Some(notice) = rx.borrow_and_update().clone() {
+            return notice;
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn announcement_reaches_connections_with_countdown() {
+        let announcer = DrainAnnouncer::new();
+        let mut rx = announcer.subscribe();
+        assert!(rx.borrow_and_update().is_none());
+
+        let sent = announcer.announce(
+            Duration::from_secs(30),
+            Some("wss://gw-2.example.com/ws".to_string()),
+        );
+        let notice = announced(&mut rx).await;
+        assert_eq!(notice, sent);
+
+        // A connection opened during the countdown sees it right away.
+        assert_eq!(*announcer.subscribe().borrow(), Some(sent));
+
+        let Message::Text(text) =
+            notice.to_message(notice.deadline - chrono::Duration::seconds(12))
+        else {
+            panic!("expected a text frame");
+        };
+        let Ok(frame) = serde_json::from_str::<WsMessage>(&text) else {
+            panic!("invalid frame");
+        };
+        assert_eq!(frame.msg_type, WsMessageType::Notice);
+        let field = |name| frame.payload.get(name);
+        assert_eq!(field("notice"), Some(&serde_json::json!("server_draining")));
+        assert_eq!(field("drain_in_secs"), Some(&serde_json::json!(12)));
+        assert_eq!(
+            field("alternate_host"),
+            Some(&serde_json::json!("wss://gw-2.example.com/ws"))
+        );
+    }
+}
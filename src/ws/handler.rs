@@ -1,16 +1,107 @@
 //! Axum WebSocket upgrade handler.
 
-use axum::extract::State;
 use axum::extract::ws::WebSocketUpgrade;
+use axum::extract::{Query, State};
 use axum::response::IntoResponse;
+use serde::Deserialize;
 
 use super::connection::run_connection;
+use super::session::SessionRegistry;
+use crate::api::client_id::{ClientId, parse_client_id};
+use crate::api::numeric::NumericMode;
 use crate::app_state::AppState;
+use crate::error::GatewayError;
+
+/// Query parameters accepted on the upgrade request.
+#[derive(Debug, Default, Deserialize)]
+pub struct WsParams {
+    /// Client id for browsers, which cannot set `X-Client-Id` on a
+    /// WebSocket handshake. The header wins when both are present.
+    #[serde(default)]
+    pub client_id: Option<String>,
+    /// `new` to open a resumable session, or a session token to resume.
+    #[serde(default)]
+    pub session: Option<String>,
+    /// Numeric serialization mode of event frames (`string`, `number`,
+    /// or `object`); see [`NumericMode`].
+    #[serde(default)]
+    pub numbers: Option<NumericMode>,
+}
 
 /// `GET /ws` — Upgrade HTTP connection to WebSocket.
-pub async fn ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> impl IntoResponse {
-    let event_rx = state.event_bus.subscribe();
+///
+/// # Errors
+///
+/// Returns [`GatewayError::InvalidRequest`] if the client id is malformed,
+/// [`GatewayError::Unauthorized`] if the session belongs to another
+/// client id, or [`GatewayError::RateLimited`] if the client already has
+/// its maximum number of WebSocket connections open.
+pub async fn ws_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    client: ClientId,
+    Query(params): Query<WsParams>,
+) -> Result<impl IntoResponse, GatewayError> {
+    let numbers = params.numbers.unwrap_or_default();
+    let client_id = match (client.0, params.client_id) {
+        (Some(id), _) => Some(id),
+        (None, Some(raw)) => Some(parse_client_id(&raw)?),
+        (None, None) => None,
+    };
+    let session = match params.session.as_deref() {
+        Some(token) => Some(state.ws_sessions.open(token, client_id.clone()).await?),
+        None => None,
+    };
+    // A resumed session keeps the client id it was opened with
+    let client_id = match &session {
+        Some((shared, _)) => shared
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .client_id
+            .clone(),
+        None => client_id,
+    };
+    let permit = match state.quotas.open_ws(client_id.as_deref()) {
+        Ok(permit) => permit,
+        Err(e) => {
+            if let Some((shared, _)) = &session {
+                SessionRegistry::release(shared);
+            }
+            return Err(e);
+        }
+    };
+    let event_bus = state.event_bus.clone();
+    let raw_feed = state.ws_raw_feed.clone();
     let pool_service = std::sync::Arc::clone(&state.pool_service);
+    let trade_tape = state.trade_tape.clone();
+    let quotas = state.quotas.clone();
+    let closing = state.ws_sessions.closing();
+    let draining = state.ws_drain.subscribe();
+    let limits = state.ws_limits;
+    let heartbeat = state.ws_heartbeat;
+    let connection = state.ws_connections.register(client_id.clone());
 
-    ws.on_upgrade(move |socket| run_connection(socket, event_rx, pool_service))
+    Ok(ws
+        .max_message_size(limits.max_message_bytes)
+        .max_frame_size(limits.max_message_bytes)
+        .on_upgrade(move |socket| async move {
+            run_connection(
+                socket,
+                event_bus,
+                raw_feed,
+                pool_service,
+                trade_tape,
+                quotas,
+                client_id,
+                session,
+                closing,
+                draining,
+                numbers,
+                limits,
+                heartbeat,
+                connection,
+            )
+            .await;
+            drop(permit);
+        }))
 }
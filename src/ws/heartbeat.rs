@@ -0,0 +1,102 @@
+//! Keepalive pings and idle timeouts.
+//!
+//! A quiet connection carries no traffic, and load balancers close sockets
+//! they have seen nothing on for a while. With a ping interval, the server
+//! pings every connection on that interval, keeping it busy and giving the
+//! client a liveness probe. With an idle timeout, a connection on which the
+//! client has sent nothing — not even a pong — for that long is closed
+//! with `1001 Going Away`, so connections to vanished clients are reaped
+//! instead of holding subscriptions forever. Browsers answer pings on
+//! their own, so a live client does not time out while pings are on.
+
+use std::time::Duration;
+
+use axum::extract::ws::{CloseFrame, Message, close_code};
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+
+/// Ping interval and idle timeout of WebSocket connections.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Heartbeat {
+    /// How often the server pings the client; `None` sends no pings.
+    pub ping_interval: Option<Duration>,
+    /// How long the client may stay silent before it is disconnected;
+    /// `None` never disconnects it.
+    pub idle_timeout: Option<Duration>,
+}
+
+impl Heartbeat {
+    /// Builds the settings from seconds, `0` turning either off.
+    #[must_use]
+    pub fn from_secs(ping_interval_secs: u64, idle_timeout_secs: u64) -> Self {
+        let secs = |s: u64| (s > 0).then(|| Duration::from_secs(s));
+        Self {
+            ping_interval: secs(ping_interval_secs),
+            idle_timeout: secs(idle_timeout_secs),
+        }
+    }
+
+    /// Ticker firing once per ping interval, first one interval from now.
+    #[must_use]
+    pub fn ticker(&self) -> Option<Interval> {
+        self.ping_interval.map(|period| {
+            let mut ticker = tokio::time::interval_at(Instant::now() + period, period);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            ticker
+        })
+    }
+
+    /// When a client last heard from at `last_seen` becomes idle.
+    #[must_use]
+    pub fn idle_deadline(&self, last_seen: Instant) -> Option<Instant> {
+        self.idle_timeout.map(|timeout| last_seen + timeout)
+    }
+}
+
+/// Waits for the next ping, or forever while pings are off.
+pub async fn next_ping(ticker: &mut Option<Interval>) {
+    match ticker {
+        Some(ticker) => {
+            ticker.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Waits until `deadline`, or forever without one.
+pub async fn idle(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Builds the `1001 Going Away` close frame sent to an idle client.
+#[must_use]
+pub fn idle_close() -> Message {
+    Message::Close(Some(CloseFrame {
+        code: close_code::AWAY,
+        reason: "idle timeout".into(),
+    }))
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_turns_heartbeat_off() {
+        let heartbeat = Heartbeat::from_secs(30, 90);
+        assert_eq!(heartbeat.ping_interval, Some(Duration::from_secs(30)));
+        let now = Instant::now();
+        assert_eq!(
+            heartbeat.idle_deadline(now),
+            Some(now + Duration::from_secs(90))
+        );
+
+        let off = Heartbeat::from_secs(0, 0);
+        assert_eq!(off, Heartbeat::default());
+        assert!(off.ticker().is_none());
+        assert!(off.idle_deadline(now).is_none());
+    }
+}
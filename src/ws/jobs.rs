@@ -0,0 +1,310 @@
+//! Long-running WebSocket commands executed as background jobs.
+//!
+//! A job command is answered at once with an `accepted` message carrying
+//! a `job_id`, then runs on its own task so the connection keeps serving
+//! events and other commands. The job streams `progress` messages and
+//! finishes with a `response` holding its result. All three echo the
+//! command's `id`. Jobs stop early if the connection closes.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use chrono::Utc;
+use hydra_amm::domain::Amount;
+use hydra_amm::traits::SwapPool;
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+
+use super::commands::parse_spec;
+use super::messages::{WsMessage, WsMessageType};
+use crate::domain::PoolId;
+use crate::domain::pool_operation::SwapKind;
+use crate::error::GatewayError;
+use crate::service::PoolService;
+use crate::service::pool_service::SwapConditions;
+use crate::service::quota::QuotaRegistry;
+
+/// Most jobs a single connection may run at once.
+pub const MAX_JOBS_PER_CONNECTION: usize = 4;
+
+/// Most swaps accepted in one `batch_swap` command.
+pub const MAX_BATCH_SWAPS: usize = 100;
+
+/// Starts jobs for one connection and feeds their frames back to it.
+#[derive(Debug, Clone)]
+pub struct JobRunner {
+    pool_service: Arc<PoolService>,
+    quotas: QuotaRegistry,
+    client_id: Option<String>,
+    frames: mpsc::UnboundedSender<String>,
+    running: Arc<AtomicUsize>,
+}
+
+/// One swap of a batch, validated.
+#[derive(Debug, Clone)]
+struct BatchSwap {
+    pool_id: PoolId,
+    token_in: String,
+    kind: SwapKind,
+    amount: u128,
+}
+
+impl JobRunner {
+    /// Creates a runner whose job frames are sent to `frames`.
+    #[must_use]
+    pub fn new(
+        pool_service: Arc<PoolService>,
+        quotas: QuotaRegistry,
+        client_id: Option<String>,
+        frames: mpsc::UnboundedSender<String>,
+    ) -> Self {
+        Self {
+            pool_service,
+            quotas,
+            client_id,
+            frames,
+            running: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Handles `{"command": "batch_swap", "swaps": [{"pool_id", "token_in",
+    /// "amount_in" | "amount_out"}, ..]}`: validates the batch, then runs
+    /// the swaps in order on a background task. A failed swap is reported
+    /// and the batch continues.
+    ///
+    /// Returns the `accepted` frame, or an error frame if the batch is
+    /// malformed or too many jobs are running.
+    #[must_use]
+    pub fn batch_swap(&self, request_id: String, payload: &Value) -> Option<String> {
+        let swaps = match parse_batch(payload) {
+            Ok(swaps) => swaps,
+            Err(e) => return frame(request_id, WsMessageType::Error, error_payload(&e)),
+        };
+        let Some(slot) = self.acquire_slot() else {
+            let e = GatewayError::RateLimited {
+                retry_after_ms: 1_000,
+            };
+            return frame(request_id, WsMessageType::Error, error_payload(&e));
+        };
+
+        let job_id = uuid::Uuid::new_v4().to_string();
+        let accepted = frame(
+            request_id.clone(),
+            WsMessageType::Accepted,
+            json!({ "job_id": job_id, "command": "batch_swap", "total": swaps.len() }),
+        );
+        let runner = self.clone();
+        tokio::spawn(async move {
+            let _slot = slot;
+            runner.run_batch(request_id, job_id, swaps).await;
+        });
+        accepted
+    }
+
+    async fn run_batch(&self, request_id: String, job_id: String, swaps: Vec<BatchSwap>) {
+        let total = swaps.len();
+        let mut results = Vec::with_capacity(total);
+        let mut failed = 0usize;
+        for (index, swap) in swaps.into_iter().enumerate() {
+            let result = match self.execute(&job_id, index, &swap).await {
+                Ok(result) => result,
+                Err(e) => {
+                    failed = failed.saturating_add(1);
+                    json!({
+                        "index": index,
+                        "pool_id": swap.pool_id,
+                        "error": error_payload(&e),
+                    })
+                }
+            };
+            let progress = json!({
+                "job_id": job_id,
+                "completed": index.saturating_add(1),
+                "total": total,
+                "result": result,
+            });
+            if !self.send(request_id.clone(), WsMessageType::Progress, progress) {
+                return;
+            }
+            results.push(result);
+        }
+        self.send(
+            request_id,
+            WsMessageType::Response,
+            json!({
+                "job_id": job_id,
+                "status": "completed",
+                "succeeded": total.saturating_sub(failed),
+                "failed": failed,
+                "results": results,
+            }),
+        );
+    }
+
+    async fn execute(
+        &self,
+        job_id: &str,
+        index: usize,
+        swap: &BatchSwap,
+    ) -> Result<Value, GatewayError> {
+        let entry_lock = self.pool_service.registry().get(swap.pool_id).await?;
+        let entry = entry_lock.read().await;
+        let token_in = entry
+            .side_of_label(&swap.token_in)
+            .map(|side| side.token(entry.pool_box.token_pair()))
+            .ok_or_else(|| {
+                GatewayError::InvalidRequest(format!(
+                    "token_in {} not found in pool",
+                    swap.token_in
+                ))
+            })?;
+        drop(entry);
+
+        self.quotas
+            .check_swap(self.client_id.as_deref(), Utc::now())?;
+        let command_id = format!("{job_id}:{index}");
+        let (result, rebate) = self
+            .pool_service
+            .execute_swap(
+                swap.pool_id,
+                swap.kind,
+                Amount::new(swap.amount),
+                token_in,
+                SwapConditions::default(),
+                &command_id,
+                self.client_id.as_deref(),
+            )
+            .await?;
+        Ok(json!({
+            "index": index,
+            "pool_id": swap.pool_id,
+            "swap_id": command_id,
+            "amount_in": result.amount_in().get().to_string(),
+            "amount_out": result.amount_out().get().to_string(),
+            "fee_charged": result.fee().get().to_string(),
+            "fee_rebate": rebate.amount.to_string(),
+        }))
+    }
+
+    /// Reserves a job slot, released when the returned guard drops.
+    fn acquire_slot(&self) -> Option<JobSlot> {
+        self.running
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < MAX_JOBS_PER_CONNECTION).then_some(n.saturating_add(1))
+            })
+            .ok()
+            .map(|_| JobSlot(Arc::clone(&self.running)))
+    }
+
+    /// Sends a job frame; returns `false` once the connection is gone.
+    fn send(&self, request_id: String, msg_type: WsMessageType, payload: Value) -> bool {
+        frame(request_id, msg_type, payload).is_some_and(|text| self.frames.send(text).is_ok())
+    }
+}
+
+/// Counts a running job until dropped.
+#[derive(Debug)]
+struct JobSlot(Arc<AtomicUsize>);
+
+impl Drop for JobSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+fn parse_batch(payload: &Value) -> Result<Vec<BatchSwap>, GatewayError> {
+    let invalid = |msg: String| GatewayError::InvalidRequest(msg);
+    let items = payload
+        .get("swaps")
+        .and_then(Value::as_array)
+        .filter(|items| !items.is_empty())
+        .ok_or_else(|| invalid("swaps must be a non-empty array".to_string()))?;
+    if items.len() > MAX_BATCH_SWAPS {
+        return Err(invalid(format!(
+            "at most {MAX_BATCH_SWAPS} swaps per batch"
+        )));
+    }
+    items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let field = |key: &str| item.get(key).and_then(Value::as_str);
+            let pool_id = field("pool_id")
+                .and_then(|s| s.parse::<uuid::Uuid>().ok())
+                .map(PoolId::from_uuid)
+                .ok_or_else(|| invalid(format!("swaps[{index}]: invalid pool_id")))?;
+            let token_in = field("token_in")
+                .ok_or_else(|| invalid(format!("swaps[{index}]: missing token_in")))?
+                .to_string();
+            let (kind, amount) =
+                parse_spec(item).map_err(|e| invalid(format!("swaps[{index}]: {e}")))?;
+            Ok(BatchSwap {
+                pool_id,
+                token_in,
+                kind,
+                amount,
+            })
+        })
+        .collect()
+}
+
+/// Error frame payload: code, message, and retry hints.
+pub(super) fn error_payload(e: &GatewayError) -> Value {
+    let mut payload = json!({
+        "code": e.error_code(),
+        "message": e.to_string(),
+        "retryable": e.is_retryable(),
+    });
+    if let (Some(retry_after_ms), Some(fields)) = (e.retry_after_ms(), payload.as_object_mut()) {
+        fields.insert("retry_after_ms".to_string(), json!(retry_after_ms));
+    }
+    payload
+}
+
+/// Serializes a frame answering request `id`.
+pub(super) fn frame(id: String, msg_type: WsMessageType, payload: Value) -> Option<String> {
+    serde_json::to_string(&WsMessage {
+        id,
+        msg_type,
+        timestamp: Utc::now(),
+        payload,
+        seq: None,
+    })
+    .ok()
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_parsing_validates_every_swap() {
+        let pool = uuid::Uuid::new_v4().to_string();
+        let Ok(swaps) = parse_batch(&json!({"swaps": [
+            {"pool_id": pool, "token_in": "usdc", "amount_in": "100"},
+            {"pool_id": pool, "token_in": "weth", "amount_out": "5"},
+        ]})) else {
+            panic!("valid batch rejected");
+        };
+        assert_eq!(swaps.len(), 2);
+        assert!(matches!(
+            swaps.get(1).map(|s| s.kind),
+            Some(SwapKind::ExactOut)
+        ));
+
+        assert!(parse_batch(&json!({"swaps": []})).is_err());
+        assert!(
+            parse_batch(&json!({"swaps": [
+                {"pool_id": pool, "token_in": "usdc", "amount_in": "1", "amount_out": "1"}
+            ]}))
+            .is_err()
+        );
+        assert!(
+            parse_batch(&json!({"swaps": [
+                {"pool_id": "nope", "token_in": "usdc", "amount_in": "1"}
+            ]}))
+            .is_err()
+        );
+    }
+}
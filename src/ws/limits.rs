@@ -0,0 +1,97 @@
+//! Inbound WebSocket frame limits.
+//!
+//! The upgrade caps the size of a client message, so an oversized one is
+//! rejected while it is still being read rather than buffered whole. Text
+//! messages are also scanned for their JSON nesting depth before they are
+//! parsed, keeping deeply nested payloads away from the parser. A client
+//! breaking either limit is disconnected with `1008 Policy Violation`.
+
+use axum::extract::ws::{CloseFrame, Message, close_code};
+
+/// Size and nesting limits applied to client messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameLimits {
+    /// Largest message accepted, in bytes.
+    pub max_message_bytes: usize,
+    /// Deepest nesting of JSON objects and arrays accepted.
+    pub max_json_depth: usize,
+}
+
+impl FrameLimits {
+    /// Checks the nesting depth of `text`, returning the reason to close
+    /// the connection with if it is too deep.
+    ///
+    /// # Errors
+    ///
+    /// Returns the close reason when `text` nests objects or arrays deeper
+    /// than `max_json_depth`.
+    pub fn check(&self, text: &str) -> Result<(), String> {
+        if exceeds_depth(text, self.max_json_depth) {
+            return Err(format!("JSON nested deeper than {}", self.max_json_depth));
+        }
+        Ok(())
+    }
+}
+
+/// Builds the `1008 Policy Violation` close frame sent to a client that
+/// broke a limit.
+#[must_use]
+pub fn policy_close(reason: &str) -> Message {
+    Message::Close(Some(CloseFrame {
+        code: close_code::POLICY,
+        reason: reason.into(),
+    }))
+}
+
+/// Returns whether `text` nests brackets deeper than `max_depth`, ignoring
+/// brackets inside strings. Stops at the first bracket past the limit.
+fn exceeds_depth(text: &str, max_depth: usize) -> bool {
+    let mut depth: usize = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for byte in text.bytes() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth = depth.saturating_add(1);
+                if depth > max_depth {
+                    return true;
+                }
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn depth_counts_brackets_outside_strings() {
+        let limits = FrameLimits {
+            max_message_bytes: 1024,
+            max_json_depth: 4,
+        };
+        assert!(limits.check(r#"{"payload": {"swaps": [{}]}}"#).is_ok());
+        assert!(limits.check(r#"{"payload": {"swaps": [[{}]]}}"#).is_err());
+        assert!(
+            limits
+                .check(r#"{"memo": "[[[[[[{{{{", "x": "\"[[[["}"#)
+                .is_ok()
+        );
+        assert!(limits.check(&"[".repeat(100_000)).is_err());
+        assert!(limits.check("not json").is_ok());
+    }
+}
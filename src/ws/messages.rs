@@ -2,9 +2,10 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// Top-level WebSocket message envelope.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct WsMessage {
     /// Client-provided ID for requests; server-generated for events.
     pub id: String,
@@ -14,11 +15,16 @@ pub struct WsMessage {
     /// ISO-8601 timestamp.
     pub timestamp: DateTime<Utc>,
     /// Variant-specific payload.
+    #[schema(value_type = Object)]
     pub payload: serde_json::Value,
+    /// Per-session sequence number of an event frame; only set on
+    /// connections with a session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
 }
 
 /// Discriminator for WebSocket message types.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum WsMessageType {
     /// Client → Server command.
@@ -29,21 +35,72 @@ pub enum WsMessageType {
     Event,
     /// Server → Client error.
     Error,
+    /// Server → Client acknowledgement that a job command was started.
+    Accepted,
+    /// Server → Client progress update of a running job.
+    Progress,
+    /// Server → Client session token, sent first on connections opened
+    /// with `?session=`.
+    Session,
+    /// Server → Client notice, e.g. that event frames were dropped
+    /// because the client read too slowly.
+    Notice,
 }
 
 /// Commands that a client can send over WebSocket.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ToSchema)]
 #[serde(tag = "command", rename_all = "snake_case")]
 pub enum WsCommand {
     /// Subscribe to events for specific pools.
     Subscribe {
         /// Pool IDs to subscribe to. Use `["*"]` for all pools.
+        #[serde(default)]
         pool_ids: Vec<String>,
+        /// Event encoding: `"json"` (default), `"protobuf"` for binary
+        /// frames using `proto/pool_events.proto`, or `"raw"` for bare
+        /// `PoolEvent` JSON without the envelope.
+        #[serde(default)]
+        encoding: Option<String>,
+        /// `"account"` delivers every event caused by this connection's
+        /// client id, in any pool; `pool_ids` may then be empty.
+        #[serde(default)]
+        mode: Option<String>,
+        /// `"pool_catalog"` announces `pool_created`/`pool_removed` events
+        /// for pools passing `pool_types` and `tokens`, instead of
+        /// subscribing to pools. `"trades"` streams the trade tape for
+        /// `pool_ids` (every pool when empty).
+        #[serde(default)]
+        channel: Option<String>,
+        /// Catalog filter: pool types to announce (all when empty).
+        #[serde(default)]
+        pool_types: Vec<String>,
+        /// Catalog filter: token addresses, either side of the pair (all
+        /// when empty).
+        #[serde(default)]
+        tokens: Vec<String>,
+        /// Smallest `liquidity_delta` (string-encoded u128) of delivered
+        /// `liquidity_changed` events from subscribed pools. Applies to
+        /// the whole connection; `null` or `"0"` clears it.
+        #[serde(default)]
+        min_liquidity_change: Option<String>,
+        /// Event types delivered from subscribed pools, e.g.
+        /// `["swap_executed"]`. Applies to the whole connection; `null` or
+        /// `[]` delivers every type.
+        #[serde(default)]
+        event_types: Option<Vec<String>>,
     },
     /// Unsubscribe from events for specific pools.
     Unsubscribe {
         /// Pool IDs to unsubscribe from.
+        #[serde(default)]
         pool_ids: Vec<String>,
+        /// `"account"` turns account mode off.
+        #[serde(default)]
+        mode: Option<String>,
+        /// `"pool_catalog"` stops catalog announcements; `"trades"` stops
+        /// the trade tape.
+        #[serde(default)]
+        channel: Option<String>,
     },
     /// Execute a swap via WebSocket.
     Swap {
@@ -51,7 +108,10 @@ pub enum WsCommand {
         pool_id: String,
         /// Input token address.
         token_in: String,
-        /// Swap specification (exact_in or exact_out).
+        /// Swap specification: one of `amount_in` / `amount_out`
+        /// (string-encoded u128), plus optional `expected_version`,
+        /// `min_amount_out`, `max_amount_in`, and `deadline` conditions.
+        #[schema(value_type = Object)]
         spec: serde_json::Value,
     },
     /// Get a swap quote (read-only).
@@ -60,9 +120,37 @@ pub enum WsCommand {
         pool_id: String,
         /// Input token address.
         token_in: String,
-        /// Swap specification.
+        /// Swap specification: one of `amount_in` / `amount_out`
+        /// (string-encoded u128).
+        #[schema(value_type = Object)]
         spec: serde_json::Value,
     },
+    /// Stream quotes for a swap: answered with the current quote and a
+    /// `subscription_id`, then a fresh quote is pushed as an `event`
+    /// every time the pool's price changes.
+    SubscribeQuotes {
+        /// Target pool ID.
+        pool_id: String,
+        /// Input token address.
+        token_in: String,
+        /// Swap specification: one of `amount_in` / `amount_out`
+        /// (string-encoded u128).
+        #[schema(value_type = Object)]
+        spec: serde_json::Value,
+    },
+    /// Stop a quote stream.
+    UnsubscribeQuotes {
+        /// Stream to stop, as returned by `subscribe_quotes`.
+        subscription_id: String,
+    },
+    /// Execute several swaps in order as a background job. Answered with
+    /// `accepted`, one `progress` per swap, and a final `response`.
+    BatchSwap {
+        /// Swaps to execute: `pool_id`, `token_in`, and one of
+        /// `amount_in` / `amount_out`.
+        #[schema(value_type = Vec<Object>)]
+        swaps: Vec<serde_json::Value>,
+    },
     /// Get full pool state.
     GetState {
         /// Target pool ID.
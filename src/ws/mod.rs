@@ -3,7 +3,17 @@
 //! The WebSocket endpoint at `/ws` provides bidirectional communication
 //! for real-time event subscriptions and command execution.
 
+pub mod asyncapi;
+pub mod commands;
 pub mod connection;
+pub mod drain;
 pub mod handler;
+pub mod heartbeat;
+pub mod jobs;
+pub mod limits;
 pub mod messages;
+pub mod outbound;
+pub mod quote_stream;
+pub mod raw;
+pub mod session;
 pub mod subscription;
@@ -0,0 +1,695 @@
+//! Bounded outbound queue of a WebSocket connection.
+//!
+//! Frames for a client are queued and written by a separate task, so a
+//! slow reader never holds up its connection loop. Event frames count
+//! against the queue's capacity. When it is full, either the least
+//! important queued event is dropped and the client is sent a `notice`
+//! frame with the `dropped_count`, or the connection is closed with
+//! `1008 Policy Violation`. Responses, errors, and job frames are never
+//! dropped.
+//!
+//! Each event frame carries an [`EventClass`]: a priority, and for events
+//! that only report the latest value of something, a coalescing key. A
+//! full queue first drops the queued event a new one supersedes (same
+//! key), then the oldest event of the lowest priority queued; a new event
+//! ranking below everything queued is dropped itself. Swaps and pool
+//! status changes thus keep flowing to a lagging client while price ticks
+//! and quotes give way.
+//!
+//! Every live connection is listed in the [`ConnectionRegistry`] with its
+//! queue depth and drop counter, served at `GET /admin/ws/connections`.
+//! Payload bytes written to and read from connections with a client id
+//! are counted in the registry's usage ledger, when it has one.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use axum::extract::ws::Message;
+use chrono::{DateTime, Utc};
+use futures_util::{Sink, SinkExt};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use super::limits;
+use super::messages::{WsMessage, WsMessageType};
+use crate::domain::PoolEvent;
+use crate::service::usage::UsageLedger;
+
+/// How long a finished connection may spend flushing its queue.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often [`ConnectionRegistry::wait_closed`] checks for connections.
+const CLOSE_POLL: Duration = Duration::from_millis(50);
+
+/// What to do with an event when the outbound queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drop the oldest queued event and notify the client.
+    DropOldest,
+    /// Close the connection with `1008 Policy Violation`.
+    Disconnect,
+}
+
+impl OverflowPolicy {
+    /// Returns the policy name, as reported by the admin endpoint.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::DropOldest => "drop_oldest",
+            Self::Disconnect => "disconnect",
+        }
+    }
+}
+
+/// Capacity and overflow policy of outbound queues.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutboundOptions {
+    /// Event frames queued before the policy applies.
+    pub capacity: usize,
+    /// What happens to events past the capacity.
+    pub policy: OverflowPolicy,
+}
+
+/// Delivery priority of an event frame, lowest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EventPriority {
+    /// Superseded by the next event of its kind, e.g. price ticks.
+    Low,
+    /// Everything else.
+    Normal,
+    /// Trade-critical: swaps and pool status changes.
+    High,
+}
+
+/// How an event frame is treated when the outbound queue is full.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventClass {
+    /// Priority of the frame.
+    pub priority: EventPriority,
+    /// Frames with the same key report the latest value of one thing; a
+    /// newer frame replaces a queued one when the queue is full.
+    pub key: Option<String>,
+}
+
+impl EventClass {
+    /// Class of a frame with `priority` that coalesces with none.
+    #[must_use]
+    pub const fn new(priority: EventPriority) -> Self {
+        Self {
+            priority,
+            key: None,
+        }
+    }
+
+    /// Makes the frame coalesce with queued frames keyed `key`.
+    #[must_use]
+    pub fn coalescing(mut self, key: String) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Class of a frame carrying `event`: swaps, status changes, and
+    /// pool removals are high priority; price and oracle updates are low
+    /// and coalesce per pool.
+    #[must_use]
+    pub fn of(event: &PoolEvent) -> Self {
+        match event {
+            PoolEvent::SwapExecuted { .. }
+            | PoolEvent::PoolStatusChanged { .. }
+            | PoolEvent::PoolRemoved { .. }
+            | PoolEvent::PoolArchived { .. } => Self::new(EventPriority::High),
+            PoolEvent::PriceUpdated { pool_id, .. }
+            | PoolEvent::OraclePriceUpdated { pool_id, .. } => Self::new(EventPriority::Low)
+                .coalescing(format!("{}:{pool_id}", event.event_type_str())),
+            _ => Self::new(EventPriority::Normal),
+        }
+    }
+}
+
+/// Why a frame was not queued.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushError {
+    /// The writer stopped, because the client went away.
+    Closed,
+    /// The queue was full and the connection is being closed.
+    Overflow,
+}
+
+/// Counters of one connection, shared with the registry.
+#[derive(Debug)]
+struct ConnectionCounters {
+    id: Uuid,
+    client_id: Option<String>,
+    connected_at: DateTime<Utc>,
+    options: OutboundOptions,
+    queued: AtomicUsize,
+    dropped: AtomicU64,
+    usage: Option<UsageLedger>,
+}
+
+impl ConnectionCounters {
+    /// Counts WebSocket payload bytes against the connection's client.
+    fn record_usage(&self, sent: usize, received: usize) {
+        if let (Some(usage), Some(client_id)) = (&self.usage, &self.client_id) {
+            usage.record_ws(
+                client_id,
+                u64::try_from(sent).unwrap_or(u64::MAX),
+                u64::try_from(received).unwrap_or(u64::MAX),
+                Utc::now(),
+            );
+        }
+    }
+}
+
+/// Point-in-time view of one connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    /// Connection identifier, unique within this process.
+    pub id: Uuid,
+    /// Client id of the connection, if any.
+    pub client_id: Option<String>,
+    /// When the connection was opened.
+    pub connected_at: DateTime<Utc>,
+    /// Event frames waiting to be written.
+    pub queued_events: usize,
+    /// Outbound queue capacity.
+    pub capacity: usize,
+    /// Overflow policy of the queue.
+    pub policy: OverflowPolicy,
+    /// Events dropped since the connection opened.
+    pub dropped_events: u64,
+}
+
+/// Live WebSocket connections of this process.
+#[derive(Debug, Clone)]
+pub struct ConnectionRegistry {
+    connections: Arc<Mutex<HashMap<Uuid, Arc<ConnectionCounters>>>>,
+    options: OutboundOptions,
+    usage: Option<UsageLedger>,
+}
+
+impl ConnectionRegistry {
+    /// Creates an empty registry whose connections queue with `options`.
+    #[must_use]
+    pub fn new(options: OutboundOptions) -> Self {
+        Self {
+            connections: Arc::new(Mutex::new(HashMap::new())),
+            options,
+            usage: None,
+        }
+    }
+
+    /// Counts the WebSocket bytes of connections with a client id in
+    /// `usage`.
+    #[must_use]
+    pub fn with_usage(mut self, usage: UsageLedger) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    /// Registers a connection by `client_id`. It stays listed until the
+    /// returned handle is dropped.
+    #[must_use]
+    pub fn register(&self, client_id: Option<String>) -> ConnectionHandle {
+        let counters = Arc::new(ConnectionCounters {
+            id: Uuid::new_v4(),
+            client_id,
+            connected_at: Utc::now(),
+            options: self.options,
+            queued: AtomicUsize::new(0),
+            dropped: AtomicU64::new(0),
+            usage: self.usage.clone(),
+        });
+        self.connections
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(counters.id, Arc::clone(&counters));
+        ConnectionHandle {
+            registry: self.clone(),
+            counters,
+        }
+    }
+
+    /// Returns every live connection, oldest first.
+    #[must_use]
+    pub fn list(&self) -> Vec<ConnectionInfo> {
+        let mut infos: Vec<ConnectionInfo> = self
+            .connections
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .map(|counters| ConnectionInfo {
+                id: counters.id,
+                client_id: counters.client_id.clone(),
+                connected_at: counters.connected_at,
+                queued_events: counters.queued.load(Ordering::Relaxed),
+                capacity: counters.options.capacity,
+                policy: counters.options.policy,
+                dropped_events: counters.dropped.load(Ordering::Relaxed),
+            })
+            .collect();
+        infos.sort_by_key(|info| info.connected_at);
+        infos
+    }
+
+    /// Waits for every connection to close, at most as long as one may
+    /// spend flushing its queue. Returns how many are still open.
+    pub async fn wait_closed(&self) -> usize {
+        let deadline = tokio::time::Instant::now() + FLUSH_TIMEOUT + CLOSE_POLL;
+        loop {
+            let open = self.len();
+            if open == 0 || tokio::time::Instant::now() >= deadline {
+                return open;
+            }
+            tokio::time::sleep(CLOSE_POLL).await;
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.connections
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+}
+
+/// A registered connection; unregisters it when dropped.
+#[derive(Debug)]
+pub struct ConnectionHandle {
+    registry: ConnectionRegistry,
+    counters: Arc<ConnectionCounters>,
+}
+
+impl ConnectionHandle {
+    /// Counts a message read from the client against its usage.
+    pub fn record_received(&self, message: &Message) {
+        self.counters.record_usage(0, payload_len(message));
+    }
+
+    /// Counts a message written to the client outside the outbound queue
+    /// against its usage.
+    pub fn record_sent(&self, message: &Message) {
+        self.counters.record_usage(payload_len(message), 0);
+    }
+}
+
+impl Drop for ConnectionHandle {
+    fn drop(&mut self) {
+        self.registry
+            .connections
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.counters.id);
+    }
+}
+
+#[derive(Debug)]
+struct QueuedFrame {
+    message: Message,
+    /// Class of an event frame; `None` for frames never dropped.
+    class: Option<EventClass>,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    frames: VecDeque<QueuedFrame>,
+    events: usize,
+    unreported_drops: u64,
+    finishing: bool,
+    closed: bool,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<QueueState>,
+    ready: Notify,
+    counters: Arc<ConnectionCounters>,
+}
+
+impl Shared {
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Outbound queue of one connection, feeding its writer task.
+#[derive(Debug)]
+pub struct OutboundQueue {
+    shared: Arc<Shared>,
+    writer: JoinHandle<()>,
+}
+
+impl OutboundQueue {
+    /// Starts the task writing queued frames to `sink`, the connection's
+    /// write half, counting against the connection behind `handle`.
+    #[must_use]
+    pub fn spawn<S>(sink: S, handle: &ConnectionHandle) -> Self
+    where
+        S: Sink<Message> + Unpin + Send + 'static,
+    {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(QueueState::default()),
+            ready: Notify::new(),
+            counters: Arc::clone(&handle.counters),
+        });
+        let writer = tokio::spawn(write(sink, Arc::clone(&shared)));
+        Self { shared, writer }
+    }
+
+    /// Queues a frame that is never dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PushError::Closed`] if the writer has stopped.
+    pub fn push(&self, message: Message) -> Result<(), PushError> {
+        let mut state = self.shared.lock();
+        if state.closed || state.finishing {
+            return Err(PushError::Closed);
+        }
+        state.frames.push_back(QueuedFrame {
+            message,
+            class: None,
+        });
+        drop(state);
+        self.shared.ready.notify_one();
+        Ok(())
+    }
+
+    /// Queues an event frame of `class`, applying the overflow policy
+    /// when the queue already holds its capacity of events.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PushError::Closed`] if the writer has stopped, or
+    /// [`PushError::Overflow`] if the queue was full under
+    /// [`OverflowPolicy::Disconnect`]; the connection is then closing.
+    pub fn push_event(&self, message: Message, class: EventClass) -> Result<(), PushError> {
+        let counters = &self.shared.counters;
+        let mut state = self.shared.lock();
+        if state.closed || state.finishing {
+            return Err(PushError::Closed);
+        }
+        if state.events >= counters.options.capacity {
+            match counters.options.policy {
+                OverflowPolicy::DropOldest => {
+                    state.unreported_drops = state.unreported_drops.saturating_add(1);
+                    counters.dropped.fetch_add(1, Ordering::Relaxed);
+                    let Some(victim) = victim(&state.frames, &class) else {
+                        return Ok(());
+                    };
+                    state.frames.remove(victim);
+                    state.events = state.events.saturating_sub(1);
+                }
+                OverflowPolicy::Disconnect => {
+                    state.frames.clear();
+                    state.events = 0;
+                    state.frames.push_back(QueuedFrame {
+                        message: limits::policy_close("outbound queue full"),
+                        class: None,
+                    });
+                    state.finishing = true;
+                    counters.queued.store(0, Ordering::Relaxed);
+                    drop(state);
+                    self.shared.ready.notify_one();
+                    return Err(PushError::Overflow);
+                }
+            }
+        }
+        state.frames.push_back(QueuedFrame {
+            message,
+            class: Some(class),
+        });
+        state.events = state.events.saturating_add(1);
+        counters.queued.store(state.events, Ordering::Relaxed);
+        drop(state);
+        self.shared.ready.notify_one();
+        Ok(())
+    }
+
+    /// Queues `close` as the last frame and waits, for a bounded time,
+    /// for the queue to be written. Without a close frame the queue is
+    /// just flushed.
+    pub async fn finish(self, close: Option<Message>) {
+        {
+            let mut state = self.shared.lock();
+            if let Some(close) = close
+                && !state.finishing
+            {
+                state.frames.push_back(QueuedFrame {
+                    message: close,
+                    class: None,
+                });
+            }
+            state.finishing = true;
+        }
+        self.shared.ready.notify_one();
+        let mut writer = self.writer;
+        if tokio::time::timeout(FLUSH_TIMEOUT, &mut writer)
+            .await
+            .is_err()
+        {
+            writer.abort();
+        }
+    }
+}
+
+/// Writes queued frames until the queue is finished and empty or the
+/// client goes away. Drops are announced before the next frame.
+async fn write<S>(mut sink: S, shared: Arc<Shared>)
+where
+    S: Sink<Message> + Unpin,
+{
+    loop {
+        let next = {
+            let mut state = shared.lock();
+            if state.unreported_drops > 0 {
+                let dropped = std::mem::take(&mut state.unreported_drops);
+                Some(drop_notice(dropped))
+            } else if let Some(frame) = state.frames.pop_front() {
+                if frame.class.is_some() {
+                    state.events = state.events.saturating_sub(1);
+                    shared
+                        .counters
+                        .queued
+                        .store(state.events, Ordering::Relaxed);
+                }
+                Some(frame.message)
+            } else if state.finishing {
+                break;
+            } else {
+                None
+            }
+        };
+        match next {
+            Some(message) => {
+                let bytes = payload_len(&message);
+                if sink.send(message).await.is_err() {
+                    shared.lock().closed = true;
+                    break;
+                }
+                shared.counters.record_usage(bytes, 0);
+            }
+            None => shared.ready.notified().await,
+        }
+    }
+}
+
+/// Index of the queued event to drop so `incoming` fits: the oldest one
+/// it supersedes, else the oldest of the lowest priority queued. `None`
+/// if `incoming` ranks below every queued event (or none is queued), in
+/// which case it is dropped instead.
+fn victim(frames: &VecDeque<QueuedFrame>, incoming: &EventClass) -> Option<usize> {
+    let events = frames
+        .iter()
+        .enumerate()
+        .filter_map(|(i, frame)| frame.class.as_ref().map(|class| (i, class)));
+    if let Some(key) = &incoming.key
+        && let Some((i, _)) = events
+            .clone()
+            .find(|(_, class)| class.key.as_ref() == Some(key))
+    {
+        return Some(i);
+    }
+    let (i, lowest) = events.min_by_key(|(_, class)| class.priority)?;
+    (lowest.priority <= incoming.priority).then_some(i)
+}
+
+/// Bytes of a text or binary payload; control frames count as none.
+fn payload_len(message: &Message) -> usize {
+    match message {
+        Message::Text(text) => text.len(),
+        Message::Binary(bytes) => bytes.len(),
+        _ => 0,
+    }
+}
+
+/// Builds the `notice` frame telling the client `dropped` events were
+/// dropped.
+fn drop_notice(dropped: u64) -> Message {
+    let msg = WsMessage {
+        id: Uuid::new_v4().to_string(),
+        msg_type: WsMessageType::Notice,
+        timestamp: Utc::now(),
+        seq: None,
+        payload: serde_json::json!({
+            "notice": "events_dropped",
+            "dropped_count": dropped,
+        }),
+    };
+    Message::text(serde_json::to_string(&msg).unwrap_or_default())
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn full_queue_drops_oldest_events_and_notifies() {
+        let registry = ConnectionRegistry::new(OutboundOptions {
+            capacity: 2,
+            policy: OverflowPolicy::DropOldest,
+        });
+        let handle = registry.register(Some("desk-1".to_string()));
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let sink = Box::pin(futures_util::sink::unfold(
+            Arc::clone(&sent),
+            |sent, message: Message| async move {
+                sent.lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .push(message);
+                Ok::<_, std::convert::Infallible>(sent)
+            },
+        ));
+        let queue = OutboundQueue::spawn(sink, &handle);
+
+        // The writer only runs once the test yields
+        for n in 0..4 {
+            assert_eq!(
+                queue.push_event(
+                    Message::text(format!("event-{n}")),
+                    EventClass::new(EventPriority::Normal)
+                ),
+                Ok(())
+            );
+        }
+        assert_eq!(queue.push(Message::text("response")), Ok(()));
+        let [info] = registry
+            .list()
+            .try_into()
+            .unwrap_or_else(|_| panic!("one connection"));
+        assert_eq!(info.queued_events, 2);
+        assert_eq!(info.dropped_events, 2);
+
+        queue.finish(None).await;
+        let sent = sent.lock().unwrap_or_else(PoisonError::into_inner);
+        let texts: Vec<String> = sent
+            .iter()
+            .filter_map(|m| m.to_text().ok().map(str::to_string))
+            .collect();
+        assert_eq!(texts.len(), 4);
+        assert!(
+            texts
+                .first()
+                .is_some_and(|t| t.contains("\"dropped_count\":2"))
+        );
+        assert_eq!(
+            texts.get(1..),
+            Some(
+                &[
+                    "event-2".to_string(),
+                    "event-3".to_string(),
+                    "response".to_string()
+                ][..]
+            )
+        );
+
+        drop(handle);
+        assert!(registry.list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn full_queue_drops_low_priority_events_first() {
+        let registry = ConnectionRegistry::new(OutboundOptions {
+            capacity: 3,
+            policy: OverflowPolicy::DropOldest,
+        });
+        let handle = registry.register(None);
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let sink = Box::pin(futures_util::sink::unfold(
+            Arc::clone(&sent),
+            |sent, message: Message| async move {
+                sent.lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .push(message);
+                Ok::<_, std::convert::Infallible>(sent)
+            },
+        ));
+        let queue = OutboundQueue::spawn(sink, &handle);
+        let tick = || EventClass::new(EventPriority::Low).coalescing("price".to_string());
+
+        for (text, class) in [
+            ("tick-1", tick()),
+            ("liquidity-1", EventClass::new(EventPriority::Normal)),
+            ("swap-1", EventClass::new(EventPriority::High)),
+            // Full from here: supersedes tick-1
+            ("tick-2", tick()),
+            // Displaces tick-2, the lowest priority queued
+            ("swap-2", EventClass::new(EventPriority::High)),
+            // Ranks below everything queued, so is dropped itself
+            ("tick-3", tick()),
+            // Displaces the oldest normal event
+            ("liquidity-2", EventClass::new(EventPriority::Normal)),
+        ] {
+            assert_eq!(queue.push_event(Message::text(text), class), Ok(()));
+        }
+
+        queue.finish(None).await;
+        let sent = sent.lock().unwrap_or_else(PoisonError::into_inner);
+        let texts: Vec<String> = sent
+            .iter()
+            .filter_map(|m| m.to_text().ok().map(str::to_string))
+            .collect();
+        assert!(
+            texts
+                .first()
+                .is_some_and(|t| t.contains("\"dropped_count\":4"))
+        );
+        assert_eq!(
+            texts.get(1..),
+            Some(
+                &[
+                    "swap-1".to_string(),
+                    "swap-2".to_string(),
+                    "liquidity-2".to_string()
+                ][..]
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn disconnect_policy_closes_when_full() {
+        let registry = ConnectionRegistry::new(OutboundOptions {
+            capacity: 1,
+            policy: OverflowPolicy::Disconnect,
+        });
+        let handle = registry.register(None);
+        let queue = OutboundQueue::spawn(futures_util::sink::drain::<Message>(), &handle);
+        assert_eq!(
+            queue.push_event(Message::text("a"), EventClass::new(EventPriority::Low)),
+            Ok(())
+        );
+        assert_eq!(
+            queue.push_event(Message::text("b"), EventClass::new(EventPriority::High)),
+            Err(PushError::Overflow)
+        );
+        assert_eq!(queue.push(Message::text("c")), Err(PushError::Closed));
+
+        queue.finish(None).await;
+        drop(handle);
+        assert_eq!(registry.wait_closed().await, 0);
+    }
+}
@@ -0,0 +1,535 @@
+//! Streaming quotes: `subscribe_quotes` and `unsubscribe_quotes`.
+//!
+//! A quote stream prices one swap (pool, input token, and one of
+//! `amount_in` / `amount_out`) again every time the pool publishes a
+//! `price_updated` event, and pushes the result to the connection as an
+//! `event` frame on the `quotes` channel. Quotes never change pool
+//! state. Quote frames are low-priority events: a client that reads too
+//! slowly gets the latest quote of each stream rather than every one.
+//! Streams belong to the connection: they end when it closes or
+//! the pool is removed, and a resumed session does not get them back.
+//!
+//! Pricing a stream rebuilds the pool's swap sandbox, so the streams of a
+//! connection live on their own [`QuoteWorker`] task: the connection loop
+//! hands it quote commands and gets back finished frames and payloads,
+//! and keeps answering pings and delivering events while quotes are
+//! recomputed. Price updates that pile up while the worker is busy are
+//! priced once, against the latest state.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{Value, json};
+use tokio::sync::{broadcast, mpsc};
+
+use super::commands::{self, CommandExecutor};
+use super::jobs::error_payload;
+use super::messages::WsCommand;
+use super::outbound::{EventClass, EventPriority};
+use crate::domain::pool_operation::SwapKind;
+use crate::domain::{EventBus, PoolEvent, PoolId};
+use crate::error::GatewayError;
+
+/// Names of the commands handled here.
+pub const COMMANDS: [&str; 2] = ["subscribe_quotes", "unsubscribe_quotes"];
+
+/// Most quote streams open at once on one connection.
+pub const MAX_QUOTE_STREAMS: usize = 32;
+
+/// The swap priced by one quote stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuoteSubscription {
+    /// Pool quoted.
+    pub pool_id: PoolId,
+    /// Input token address.
+    pub token_in: String,
+    /// Whether `amount` is the input or the output.
+    pub kind: SwapKind,
+    /// Amount quoted, in raw units.
+    pub amount: u128,
+}
+
+/// Quote streams of one connection, by subscription id.
+#[derive(Debug, Default)]
+pub struct QuoteStreams {
+    streams: BTreeMap<String, QuoteSubscription>,
+}
+
+impl QuoteStreams {
+    /// Creates a connection's empty set of streams.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if no stream is open.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.streams.is_empty()
+    }
+
+    /// Opens a stream for `subscription` and returns its id.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InvalidRequest`] if [`MAX_QUOTE_STREAMS`]
+    /// are already open.
+    pub fn add(&mut self, subscription: QuoteSubscription) -> Result<String, GatewayError> {
+        if self.streams.len() >= MAX_QUOTE_STREAMS {
+            return Err(GatewayError::InvalidRequest(format!(
+                "at most {MAX_QUOTE_STREAMS} quote streams per connection"
+            )));
+        }
+        let id = uuid::Uuid::new_v4().to_string();
+        self.streams.insert(id.clone(), subscription);
+        Ok(id)
+    }
+
+    /// Closes the stream `id`; returns `false` if it was not open.
+    pub fn remove(&mut self, id: &str) -> bool {
+        self.streams.remove(id).is_some()
+    }
+
+    /// Runs a `subscribe_quotes` or `unsubscribe_quotes` command and
+    /// returns its `response` frame, or an `error` frame if it is
+    /// malformed or fails. A new stream answers with its first quote.
+    pub async fn execute(
+        &mut self,
+        request_id: String,
+        payload: &Value,
+        executor: &CommandExecutor,
+    ) -> Option<String> {
+        let result = match serde_json::from_value::<WsCommand>(payload.clone()) {
+            Ok(WsCommand::SubscribeQuotes {
+                pool_id,
+                token_in,
+                spec,
+            }) => self.subscribe(&pool_id, token_in, &spec, executor).await,
+            Ok(WsCommand::UnsubscribeQuotes { subscription_id }) => {
+                if self.remove(&subscription_id) {
+                    Ok(json!({
+                        "channel": "quotes",
+                        "subscription_id": subscription_id,
+                        "unsubscribed": true,
+                    }))
+                } else {
+                    Err(GatewayError::InvalidRequest(format!(
+                        "no quote stream {subscription_id}"
+                    )))
+                }
+            }
+            Ok(_) => Err(GatewayError::InvalidRequest(
+                "not a quote stream command".to_string(),
+            )),
+            Err(e) => Err(GatewayError::InvalidRequest(e.to_string())),
+        };
+        executor.answer(request_id, result)
+    }
+
+    /// Returns the payloads to push for `event`: a fresh quote for every
+    /// stream on the pool of a `price_updated` event, and a final
+    /// `ended` payload for every stream on a removed pool, which is
+    /// closed. A quote that fails is pushed as its `error`.
+    pub async fn on_event(&mut self, event: &PoolEvent, executor: &CommandExecutor) -> Vec<Value> {
+        match event {
+            PoolEvent::PriceUpdated { pool_id, .. } => {
+                let mut payloads = Vec::new();
+                for (id, sub) in self.streams.iter().filter(|(_, s)| s.pool_id == *pool_id) {
+                    let quote = executor
+                        .quote_amount(sub.pool_id, &sub.token_in, sub.kind, sub.amount)
+                        .await;
+                    payloads.push(match quote {
+                        Ok(quote) => stream_payload(id, "quote", quote),
+                        Err(e) => stream_payload(id, "error", error_payload(&e)),
+                    });
+                }
+                payloads
+            }
+            PoolEvent::PoolRemoved { pool_id, .. } => {
+                let mut payloads = Vec::new();
+                self.streams.retain(|id, sub| {
+                    let removed = sub.pool_id == *pool_id;
+                    if removed {
+                        payloads.push(stream_payload(id, "ended", json!("pool_removed")));
+                    }
+                    !removed
+                });
+                payloads
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    async fn subscribe(
+        &mut self,
+        pool_id: &str,
+        token_in: String,
+        spec: &Value,
+        executor: &CommandExecutor,
+    ) -> Result<Value, GatewayError> {
+        let pool_id = commands::parse_pool_id(pool_id)?;
+        let (kind, amount) = commands::parse_spec(spec).map_err(GatewayError::InvalidRequest)?;
+        let quote = executor
+            .quote_amount(pool_id, &token_in, kind, amount)
+            .await?;
+        let id = self.add(QuoteSubscription {
+            pool_id,
+            token_in,
+            kind,
+            amount,
+        })?;
+        Ok(stream_payload(&id, "quote", quote))
+    }
+}
+
+/// What a [`QuoteWorker`] sends back to its connection.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuoteOutput {
+    /// `response` or `error` frame answering a quote command.
+    Response(String),
+    /// `quotes` channel payload to push as an event.
+    Push(Value),
+}
+
+/// Handle to the task running the quote streams of one connection.
+///
+/// The task owns the connection's [`QuoteStreams`], listens to the event
+/// bus while any stream is open, and ends when the handle is dropped or
+/// the connection stops reading its outputs.
+#[derive(Debug)]
+pub struct QuoteWorker {
+    commands: mpsc::UnboundedSender<(String, Value)>,
+}
+
+impl QuoteWorker {
+    /// Spawns the worker; its frames and payloads are sent to `outputs`.
+    #[must_use]
+    pub fn spawn(
+        executor: CommandExecutor,
+        event_bus: EventBus,
+        outputs: mpsc::UnboundedSender<QuoteOutput>,
+    ) -> Self {
+        let (commands, rx) = mpsc::unbounded_channel();
+        tokio::spawn(run_worker(executor, event_bus, rx, outputs));
+        Self { commands }
+    }
+
+    /// Queues a `subscribe_quotes` or `unsubscribe_quotes` command; its
+    /// answer comes back as a [`QuoteOutput::Response`].
+    pub fn execute(&self, request_id: String, payload: Value) {
+        let _ = self.commands.send((request_id, payload));
+    }
+}
+
+async fn run_worker(
+    executor: CommandExecutor,
+    event_bus: EventBus,
+    mut commands: mpsc::UnboundedReceiver<(String, Value)>,
+    outputs: mpsc::UnboundedSender<QuoteOutput>,
+) {
+    let mut streams = QuoteStreams::new();
+    let mut events: Option<broadcast::Receiver<PoolEvent>> = None;
+    loop {
+        let sent = tokio::select! {
+            command = commands.recv() => {
+                let Some((request_id, payload)) = command else {
+                    return;
+                };
+                match streams.execute(request_id, &payload, &executor).await {
+                    Some(frame) => outputs.send(QuoteOutput::Response(frame)).is_ok(),
+                    None => true,
+                }
+            }
+            event = super::connection::next_on(&mut events) => match event {
+                Ok(event) => {
+                    let mut batch = vec![event];
+                    if let Some(rx) = events.as_mut() {
+                        while let Ok(event) = rx.try_recv() {
+                            batch.push(event);
+                        }
+                    }
+                    let mut sent = true;
+                    for event in latest_prices(batch) {
+                        for payload in streams.on_event(&event, &executor).await {
+                            sent &= outputs.send(QuoteOutput::Push(payload)).is_ok();
+                        }
+                    }
+                    sent
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!(lagged = n, "ws quote streams lagged behind event bus");
+                    true
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    events = None;
+                    true
+                }
+            },
+        };
+        if !sent {
+            return;
+        }
+        match (!streams.is_empty(), events.is_some()) {
+            (true, false) => events = Some(event_bus.subscribe()),
+            (false, true) => events = None,
+            _ => {}
+        }
+    }
+}
+
+/// Keeps the last `price_updated` event of each pool in `batch`, in
+/// order with the other events: one requote against the latest state
+/// replaces every earlier one.
+fn latest_prices(batch: Vec<PoolEvent>) -> Vec<PoolEvent> {
+    let mut seen = BTreeSet::new();
+    let mut kept: Vec<PoolEvent> = batch
+        .into_iter()
+        .rev()
+        .filter(|event| match event {
+            PoolEvent::PriceUpdated { pool_id, .. } => seen.insert(*pool_id),
+            _ => true,
+        })
+        .collect();
+    kept.reverse();
+    kept
+}
+
+/// Delivery class of a `quotes` channel payload: quotes and quote
+/// errors are low priority and coalesce per stream, since each replaces
+/// the last; a stream's `ended` payload is not.
+#[must_use]
+pub fn delivery_class(payload: &Value) -> EventClass {
+    match payload.get("subscription_id").and_then(Value::as_str) {
+        Some(id) if payload.get("ended").is_none() => {
+            EventClass::new(EventPriority::Low).coalescing(format!("quotes:{id}"))
+        }
+        _ => EventClass::new(EventPriority::Normal),
+    }
+}
+
+/// Payload of a `quotes` channel frame of stream `id`.
+fn stream_payload(id: &str, key: &str, value: Value) -> Value {
+    json!({
+        "channel": "quotes",
+        "subscription_id": id,
+        key: value,
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::api::numeric::NumericMode;
+    use crate::domain::{EventBus, PoolRegistry};
+    use crate::service::PoolService;
+    use crate::service::quota::QuotaRegistry;
+
+    #[tokio::test]
+    async fn streams_requote_on_price_updates_and_end_with_the_pool() {
+        let service = Arc::new(PoolService::new(
+            Arc::new(PoolRegistry::new()),
+            EventBus::new(16),
+        ));
+        let pool_id = PoolId::new();
+        let created = service
+            .create_pool_from_config(
+                pool_id,
+                "constant_product",
+                json!({
+                    "token_a": {"address": "usdc", "decimals": 6},
+                    "token_b": {"address": "weth", "decimals": 6},
+                    "fee_bps": 30,
+                    "reserve_a": "4000000",
+                    "reserve_b": "1000000",
+                }),
+                Value::Null,
+                None,
+            )
+            .await;
+        assert!(created.is_ok());
+        let executor = CommandExecutor::new(
+            Arc::clone(&service),
+            QuotaRegistry::new(),
+            None,
+            NumericMode::default(),
+        );
+        let command = |spec: Value| {
+            json!({
+                "command": "subscribe_quotes",
+                "pool_id": pool_id.to_string(),
+                "token_in": "weth",
+                "spec": spec,
+            })
+        };
+
+        let mut streams = QuoteStreams::new();
+        let Some(frame) = streams
+            .execute(
+                "r1".to_string(),
+                &command(json!({"amount_in": "1000"})),
+                &executor,
+            )
+            .await
+        else {
+            panic!("no response");
+        };
+        assert!(frame.contains("subscription_id"), "{frame}");
+        assert!(!streams.is_empty());
+        let Some(error) = streams
+            .execute("r2".to_string(), &command(json!({})), &executor)
+            .await
+        else {
+            panic!("no response");
+        };
+        assert!(error.contains("\"error\""), "{error}");
+
+        let price_updated = PoolEvent::PriceUpdated {
+            pool_id,
+            old_price: "4".to_string(),
+            new_price: "4".to_string(),
+            price_change_bps: 0,
+            reason: crate::domain::pool_event::PriceChangeReason::SwapExecuted,
+            timestamp: chrono::Utc::now(),
+        };
+        let pushed = streams.on_event(&price_updated, &executor).await;
+        assert_eq!(pushed.len(), 1);
+        assert!(pushed.iter().all(|p| p.get("quote").is_some()));
+        assert!(
+            pushed
+                .iter()
+                .all(|p| delivery_class(p).priority == EventPriority::Low)
+        );
+
+        let removed = PoolEvent::PoolRemoved {
+            pool_id,
+            timestamp: chrono::Utc::now(),
+        };
+        let ended = streams.on_event(&removed, &executor).await;
+        assert_eq!(ended.len(), 1);
+        assert!(
+            ended
+                .iter()
+                .all(|p| delivery_class(p) == EventClass::new(EventPriority::Normal))
+        );
+        assert!(streams.is_empty());
+    }
+
+    #[tokio::test]
+    async fn worker_answers_commands_and_pushes_requotes() {
+        let service = Arc::new(PoolService::new(
+            Arc::new(PoolRegistry::new()),
+            EventBus::new(16),
+        ));
+        let pool_id = PoolId::new();
+        let created = service
+            .create_pool_from_config(
+                pool_id,
+                "constant_product",
+                json!({
+                    "token_a": {"address": "usdc", "decimals": 6},
+                    "token_b": {"address": "weth", "decimals": 6},
+                    "fee_bps": 30,
+                    "reserve_a": "4000000",
+                    "reserve_b": "1000000",
+                }),
+                Value::Null,
+                None,
+            )
+            .await;
+        assert!(created.is_ok());
+        let executor = CommandExecutor::new(
+            Arc::clone(&service),
+            QuotaRegistry::new(),
+            None,
+            NumericMode::default(),
+        );
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let worker = QuoteWorker::spawn(executor, service.event_bus().clone(), tx);
+        worker.execute(
+            "r1".to_string(),
+            json!({
+                "command": "subscribe_quotes",
+                "pool_id": pool_id.to_string(),
+                "token_in": "weth",
+                "spec": {"amount_in": "1000"},
+            }),
+        );
+        let Some(QuoteOutput::Response(frame)) = rx.recv().await else {
+            panic!("no response");
+        };
+        assert!(frame.contains("subscription_id"), "{frame}");
+
+        let price_updated = || PoolEvent::PriceUpdated {
+            pool_id,
+            old_price: "4".to_string(),
+            new_price: "4".to_string(),
+            price_change_bps: 0,
+            reason: crate::domain::pool_event::PriceChangeReason::SwapExecuted,
+            timestamp: chrono::Utc::now(),
+        };
+        service.event_bus().publish(price_updated());
+        let Some(QuoteOutput::Push(payload)) = rx.recv().await else {
+            panic!("no requote");
+        };
+        assert!(payload.get("quote").is_some(), "{payload}");
+
+        drop(worker);
+        assert!(rx.recv().await.is_none(), "worker ends with its handle");
+    }
+
+    #[test]
+    fn piled_up_price_updates_are_priced_once_per_pool() {
+        let (a, b) = (PoolId::new(), PoolId::new());
+        let now = chrono::Utc::now();
+        let price = |pool_id, new_price: &str| PoolEvent::PriceUpdated {
+            pool_id,
+            old_price: "1".to_string(),
+            new_price: new_price.to_string(),
+            price_change_bps: 0,
+            reason: crate::domain::pool_event::PriceChangeReason::SwapExecuted,
+            timestamp: now,
+        };
+        let removed = PoolEvent::PoolRemoved {
+            pool_id: b,
+            timestamp: now,
+        };
+        let kept = latest_prices(vec![
+            price(a, "2"),
+            price(b, "2"),
+            price(a, "3"),
+            removed.clone(),
+            price(a, "4"),
+        ]);
+        assert_eq!(
+            serde_json::to_value(kept).ok(),
+            serde_json::to_value(vec![price(b, "2"), removed, price(a, "4")]).ok()
+        );
+    }
+
+    #[test]
+    fn streams_are_capped_per_connection() {
+        let subscription = QuoteSubscription {
+            pool_id: PoolId::new(),
+            token_in: "weth".to_string(),
+            kind: SwapKind::ExactIn,
+            amount: 1_000,
+        };
+        let mut streams = QuoteStreams::new();
+        let mut ids = Vec::new();
+        for _ in 0..MAX_QUOTE_STREAMS {
+            let Ok(id) = streams.add(subscription.clone()) else {
+                panic!("stream refused below the cap");
+            };
+            ids.push(id);
+        }
+        assert!(streams.add(subscription.clone()).is_err());
+        let Some(first) = ids.first() else {
+            panic!("no streams");
+        };
+        assert!(streams.remove(first));
+        assert!(!streams.remove(first));
+        assert!(streams.add(subscription).is_ok());
+    }
+}
@@ -0,0 +1,112 @@
+//! Pre-serialized event feed for the `raw` WebSocket encoding.
+//!
+//! A connection subscribed with `"encoding": "raw"` receives each pool
+//! event as its bare JSON serialization, without the `WsMessage`
+//! envelope. A single task serializes every event once and shares the
+//! bytes with all raw connections, so the per-connection cost of an event
+//! is a filter check and a reference-count bump: no id generation, no
+//! envelope, and no re-serialization. Nothing is serialized while no
+//! connection uses the encoding.
+
+use std::sync::Arc;
+
+use axum::extract::ws::Utf8Bytes;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::domain::{EventBus, PoolEvent};
+
+/// An event with its JSON text frame.
+#[derive(Debug)]
+pub struct RawEvent {
+    /// The event, for filtering.
+    pub event: PoolEvent,
+    /// `serde_json` serialization of `event`.
+    pub frame: Utf8Bytes,
+}
+
+impl RawEvent {
+    /// Serializes `event`.
+    #[must_use]
+    pub fn new(event: PoolEvent) -> Self {
+        let frame = serde_json::to_string(&event).unwrap_or_default().into();
+        Self { event, frame }
+    }
+}
+
+/// Shared handle to the pre-serialized feed.
+#[derive(Debug, Clone)]
+pub struct RawEventFeed {
+    sender: broadcast::Sender<Arc<RawEvent>>,
+}
+
+impl RawEventFeed {
+    /// Creates a feed buffering up to `capacity` events per lagging
+    /// connection. Call [`Self::spawn`] to start feeding it.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Starts a task serializing every event published on `bus`. The
+    /// task ends when the bus closes.
+    pub fn spawn(&self, bus: &EventBus) -> JoinHandle<()> {
+        let sender = self.sender.clone();
+        let mut events = bus.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        if sender.receiver_count() > 0 {
+                            let _ = sender.send(Arc::new(RawEvent::new(event)));
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(lagged = n, "raw event feed lagged behind event bus");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Creates a receiver for a connection switching to the raw encoding.
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<RawEvent>> {
+        self.sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::domain::PoolId;
+
+    #[tokio::test]
+    async fn connections_share_one_serialization() {
+        let bus = EventBus::new(16);
+        let feed = RawEventFeed::new(16);
+        let _task = feed.spawn(&bus);
+        let mut first = feed.subscribe();
+        let mut second = feed.subscribe();
+
+        let pool_id = PoolId::new();
+        bus.publish(PoolEvent::PoolRemoved {
+            pool_id,
+            timestamp: Utc::now(),
+        });
+
+        let (Ok(a), Ok(b)) = (first.recv().await, second.recv().await) else {
+            panic!("raw event not delivered");
+        };
+        assert!(Arc::ptr_eq(&a, &b));
+        let Ok(decoded) = serde_json::from_str::<PoolEvent>(a.frame.as_str()) else {
+            panic!("raw frame is not a pool event");
+        };
+        assert_eq!(decoded.pool_id(), pool_id);
+    }
+}
@@ -0,0 +1,412 @@
+//! Resumable WebSocket sessions and their handover between processes.
+//!
+//! A client that connects with `?session=new` gets a session token. The
+//! session keeps the connection's subscriptions, client id, and the
+//! sequence number of the last event frame it was sent, so a client that
+//! reconnects with `?session=<token>` gets its subscriptions back and
+//! sequence numbers that continue where they stopped. Disconnected
+//! sessions are kept for a configurable time.
+//!
+//! During a blue-green handover the draining process closes its
+//! connections with `1012 Service Restart` and writes every session to a
+//! handover file; the replacement process imports the file, so clients
+//! reconnecting with their token resume on the new process. Events
+//! published while a client was away are not replayed. On a plain
+//! shutdown, connections are closed with `1001 Going Away` instead and no
+//! file is written.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use axum::extract::ws::{CloseFrame, Message, close_code};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use super::subscription::SubscriptionState;
+use crate::error::GatewayError;
+
+/// `session` query value asking for a new session.
+pub const NEW_SESSION: &str = "new";
+
+/// How long the draining process lets connections close before writing
+/// the handover file.
+const HANDOVER_SETTLE: Duration = Duration::from_millis(250);
+
+/// How often the replacement process looks for the handover file.
+const HANDOVER_POLL: Duration = Duration::from_millis(100);
+
+/// Why the server is closing every connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Closing {
+    /// Sessions are handed to a replacement process.
+    Handover,
+    /// The server is shutting down.
+    Shutdown,
+}
+
+impl Closing {
+    /// Builds the close frame sent to clients: `1012 Service Restart` for
+    /// a handover, `1001 Going Away` for a shutdown.
+    #[must_use]
+    pub fn close_frame(self) -> Message {
+        let (code, reason) = match self {
+            Self::Handover => (close_code::RESTART, "handover"),
+            Self::Shutdown => (close_code::AWAY, "server shutting down"),
+        };
+        Message::Close(Some(CloseFrame {
+            code,
+            reason: reason.into(),
+        }))
+    }
+}
+
+/// Everything needed to resume a WebSocket session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionDescriptor {
+    /// Token the client presents to resume.
+    pub token: String,
+    /// Client id the session was opened with.
+    pub client_id: Option<String>,
+    /// Subscriptions at the last change.
+    pub subscriptions: SubscriptionState,
+    /// Sequence number of the last event frame sent.
+    pub last_sequence: u64,
+    /// Whether a connection is currently using the session.
+    #[serde(skip)]
+    pub connected: bool,
+    /// Last change, or when the session was disconnected.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A session shared between its connection and the registry.
+pub type SharedSession = Arc<Mutex<SessionDescriptor>>;
+
+/// Contents of the handover file.
+#[derive(Debug, Serialize, Deserialize)]
+struct HandoverFile {
+    exported_at: DateTime<Utc>,
+    sessions: Vec<SessionDescriptor>,
+}
+
+/// Sessions known to this process.
+#[derive(Debug, Clone)]
+pub struct SessionRegistry {
+    sessions: Arc<Mutex<HashMap<String, SharedSession>>>,
+    ttl: Duration,
+    /// Set to close every connection, for a handover or a shutdown.
+    closing: watch::Sender<Option<Closing>>,
+    /// `true` while sessions from the previous process are expected.
+    importing: watch::Sender<bool>,
+}
+
+impl SessionRegistry {
+    /// Creates a registry keeping disconnected sessions for `ttl`.
+    #[must_use]
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            ttl,
+            closing: watch::Sender::new(None),
+            importing: watch::Sender::new(false),
+        }
+    }
+
+    /// Opens a session for a connection by `client_id`. `token` is a
+    /// previously issued token, or [`NEW_SESSION`]. An unknown, expired,
+    /// or already connected token starts a new session. While sessions
+    /// of a previous process are being imported, an unknown token waits
+    /// for the import first.
+    ///
+    /// Returns the session and whether it was resumed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::Unauthorized`] if the session belongs to a
+    /// different client id.
+    pub async fn open(
+        &self,
+        token: &str,
+        client_id: Option<String>,
+    ) -> Result<(SharedSession, bool), GatewayError> {
+        if token != NEW_SESSION && !self.contains(token) {
+            self.wait_for_import().await;
+        }
+        let now = Utc::now();
+        let mut sessions = self.lock();
+        self.prune(&mut sessions, now);
+
+        if let Some(shared) = sessions.get(token) {
+            let mut session = shared.lock().unwrap_or_else(PoisonError::into_inner);
+            if !session.connected {
+                if client_id.is_some() && session.client_id != client_id {
+                    return Err(GatewayError::Unauthorized(
+                        "session belongs to another client id".to_string(),
+                    ));
+                }
+                session.connected = true;
+                session.updated_at = now;
+                drop(session);
+                return Ok((Arc::clone(shared), true));
+            }
+        }
+
+        let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let shared = Arc::new(Mutex::new(SessionDescriptor {
+            token: token.clone(),
+            client_id,
+            subscriptions: SubscriptionState::default(),
+            last_sequence: 0,
+            connected: true,
+            updated_at: now,
+        }));
+        sessions.insert(token, Arc::clone(&shared));
+        Ok((shared, false))
+    }
+
+    /// Marks `session` disconnected; it stays resumable for the TTL.
+    pub fn release(session: &SharedSession) {
+        let mut session = session.lock().unwrap_or_else(PoisonError::into_inner);
+        session.connected = false;
+        session.updated_at = Utc::now();
+    }
+
+    /// Returns a receiver that is set when connections must close.
+    #[must_use]
+    pub fn closing(&self) -> watch::Receiver<Option<Closing>> {
+        self.closing.subscribe()
+    }
+
+    /// Closes every connection for a shutdown, without handing sessions
+    /// over.
+    pub fn close_all(&self) {
+        self.closing.send_replace(Some(Closing::Shutdown));
+    }
+
+    /// Closes every connection and writes all resumable sessions to
+    /// `path` for the replacement process.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the file cannot be written.
+    pub async fn hand_over(&self, path: &Path) -> std::io::Result<usize> {
+        self.closing.send_replace(Some(Closing::Handover));
+        tokio::time::sleep(HANDOVER_SETTLE).await;
+
+        let now = Utc::now();
+        let exported = self.export(now);
+        let count = exported.len();
+        let file = HandoverFile {
+            exported_at: now,
+            sessions: exported,
+        };
+        let json = serde_json::to_vec(&file).map_err(std::io::Error::other)?;
+        // Write then rename so the new process never reads a partial file
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, json).await?;
+        tokio::fs::rename(&tmp, path).await?;
+        Ok(count)
+    }
+
+    /// Removes a stale handover file at `path`, then starts a task that
+    /// imports the sessions the previous process writes there, giving up
+    /// after `timeout`. Call before signalling the previous process.
+    pub fn expect_handover(&self, path: PathBuf, timeout: Duration) -> JoinHandle<()> {
+        let _ = std::fs::remove_file(&path);
+        self.importing.send_replace(true);
+        let registry = self.clone();
+        tokio::spawn(async move {
+            let deadline = tokio::time::Instant::now() + timeout;
+            loop {
+                match tokio::fs::read(&path).await {
+                    Ok(bytes) => {
+                        match registry.import(&bytes) {
+                            Ok(count) => tracing::info!(sessions = count, "ws sessions imported"),
+                            Err(e) => {
+                                tracing::warn!(error = %e, "invalid ws session handover file")
+                            }
+                        }
+                        let _ = tokio::fs::remove_file(&path).await;
+                        break;
+                    }
+                    Err(_) if tokio::time::Instant::now() >= deadline => {
+                        tracing::warn!("no ws session handover from previous process");
+                        break;
+                    }
+                    Err(_) => tokio::time::sleep(HANDOVER_POLL).await,
+                }
+            }
+            registry.importing.send_replace(false);
+        })
+    }
+
+    /// Copies every resumable session, stamped disconnected at `now`.
+    fn export(&self, now: DateTime<Utc>) -> Vec<SessionDescriptor> {
+        let mut sessions = self.lock();
+        self.prune(&mut sessions, now);
+        sessions
+            .values()
+            .map(|shared| {
+                let mut session = shared
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .clone();
+                session.connected = false;
+                session.updated_at = now;
+                session
+            })
+            .collect()
+    }
+
+    /// Adds the sessions of a handover file; existing tokens are kept.
+    fn import(&self, bytes: &[u8]) -> Result<usize, serde_json::Error> {
+        let file: HandoverFile = serde_json::from_slice(bytes)?;
+        let now = Utc::now();
+        let mut sessions = self.lock();
+        let mut imported = 0;
+        for session in file.sessions {
+            if now - session.updated_at > self.ttl_delta() {
+                continue;
+            }
+            sessions
+                .entry(session.token.clone())
+                .or_insert_with(|| Arc::new(Mutex::new(session)));
+            imported += 1;
+        }
+        Ok(imported)
+    }
+
+    /// Waits while an import is in progress.
+    async fn wait_for_import(&self) {
+        let mut importing = self.importing.subscribe();
+        let _ = importing.wait_for(|importing| !*importing).await;
+    }
+
+    fn contains(&self, token: &str) -> bool {
+        self.lock().contains_key(token)
+    }
+
+    /// Drops disconnected sessions idle for longer than the TTL.
+    fn prune(&self, sessions: &mut HashMap<String, SharedSession>, now: DateTime<Utc>) {
+        let ttl = self.ttl_delta();
+        sessions.retain(|_, shared| {
+            let session = shared.lock().unwrap_or_else(PoisonError::into_inner);
+            session.connected || now - session.updated_at <= ttl
+        });
+    }
+
+    fn ttl_delta(&self) -> chrono::Duration {
+        chrono::Duration::from_std(self.ttl).unwrap_or(chrono::Duration::MAX)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, SharedSession>> {
+        self.sessions.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::domain::PoolId;
+
+    #[tokio::test]
+    async fn sessions_resume_only_when_disconnected_and_owned() {
+        let registry = SessionRegistry::new(Duration::from_secs(60));
+        let Ok((session, resumed)) = registry.open(NEW_SESSION, Some("bot-1".into())).await else {
+            panic!("session not opened");
+        };
+        assert!(!resumed);
+        let token = session
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .token
+            .clone();
+
+        // Still connected: a second connection gets a fresh session
+        let Ok((other, resumed)) = registry.open(&token, Some("bot-1".into())).await else {
+            panic!("session not opened");
+        };
+        assert!(!resumed);
+        SessionRegistry::release(&other);
+
+        SessionRegistry::release(&session);
+        assert!(matches!(
+            registry.open(&token, Some("bot-2".into())).await,
+            Err(GatewayError::Unauthorized(_))
+        ));
+        let Ok((_, resumed)) = registry.open(&token, None).await else {
+            panic!("session not resumed");
+        };
+        assert!(resumed);
+    }
+
+    #[tokio::test]
+    async fn handover_file_carries_sessions_to_a_new_registry() {
+        let old = SessionRegistry::new(Duration::from_secs(60));
+        let Ok((session, _)) = old.open(NEW_SESSION, None).await else {
+            panic!("session not opened");
+        };
+        let pool = PoolId::new();
+        let token = {
+            let mut session = session.lock().unwrap_or_else(PoisonError::into_inner);
+            session.subscriptions.pool_ids = vec![pool];
+            session.last_sequence = 42;
+            session.token.clone()
+        };
+        let mut closing = old.closing();
+
+        let path = std::env::temp_dir().join(format!("ws-handover-{}.json", Uuid::new_v4()));
+        let Ok(count) = old.hand_over(&path).await else {
+            panic!("handover not written");
+        };
+        assert_eq!(count, 1);
+        assert_eq!(*closing.borrow_and_update(), Some(Closing::Handover));
+
+        let new = SessionRegistry::new(Duration::from_secs(60));
+        let importer = new.expect_handover(path.clone(), Duration::from_secs(1));
+        // The stale-file cleanup ran first, so write the export again
+        let Ok(_) = old.hand_over(&path).await else {
+            panic!("handover not written");
+        };
+        let Ok((resumed_session, resumed)) = new.open(&token, None).await else {
+            panic!("session not resumed");
+        };
+        assert!(resumed);
+        let resumed_session = resumed_session
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        assert_eq!(resumed_session.last_sequence, 42);
+        assert_eq!(resumed_session.subscriptions.pool_ids, vec![pool]);
+        let _ = importer.await;
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn shutdown_closes_connections_going_away() {
+        let registry = SessionRegistry::new(Duration::from_secs(60));
+        let mut closing = registry.closing();
+        assert_eq!(*closing.borrow_and_update(), None);
+
+        registry.close_all();
+        assert!(closing.has_changed().unwrap_or(false));
+        let Some(reason) = *closing.borrow_and_update() else {
+            panic!("connections not told to close");
+        };
+        assert_eq!(reason, Closing::Shutdown);
+
+        let Message::Close(Some(frame)) = reason.close_frame() else {
+            panic!("not a close frame");
+        };
+        assert_eq!(frame.code, close_code::AWAY);
+        let Message::Close(Some(frame)) = Closing::Handover.close_frame() else {
+            panic!("not a close frame");
+        };
+        assert_eq!(frame.code, close_code::RESTART);
+    }
+}
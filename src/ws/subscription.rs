@@ -1,11 +1,107 @@
 //! Per-connection subscription manager.
 //!
 //! Tracks which pool IDs a WebSocket client is subscribed to and
-//! provides server-side event filtering.
+//! provides server-side event filtering. In account mode the client also
+//! receives every event its own commands caused, in any pool. The pool
+//! catalog channel announces pool creations and removals matching a
+//! pool-type/token filter, and the trades channel streams the trade tape.
+//! A liquidity threshold drops small `liquidity_changed` events from
+//! subscribed pools, and an event-type filter keeps only the listed types
+//! from them. The whole set can be exported as a
+//! [`SubscriptionState`] so a resumed session gets it back.
 
 use std::collections::HashSet;
 
-use crate::domain::PoolId;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{PoolEvent, PoolId};
+
+/// Wire encoding used to deliver events to a connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventEncoding {
+    /// JSON text frames wrapped in the [`super::messages::WsMessage`] envelope.
+    #[default]
+    Json,
+    /// Binary frames carrying `hydra.gateway.events.v1.PoolEvent`.
+    Protobuf,
+    /// Bare JSON `PoolEvent` text frames shared by every raw connection:
+    /// no envelope, sequence number, signature, or numbers mode.
+    Raw,
+}
+
+impl EventEncoding {
+    /// Parses an encoding name (`"json"`, `"protobuf"`, or `"raw"`).
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "json" => Some(Self::Json),
+            "protobuf" | "proto" => Some(Self::Protobuf),
+            "raw" => Some(Self::Raw),
+            _ => None,
+        }
+    }
+}
+
+/// Filter of the `pool_catalog` channel. An empty list places no
+/// constraint; tokens are compared case-insensitively and match either
+/// side of the pair.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatalogFilter {
+    /// Accepted pool types.
+    pub pool_types: Vec<String>,
+    /// Accepted token addresses.
+    pub tokens: Vec<String>,
+}
+
+impl CatalogFilter {
+    /// Returns `true` if a pool with this type and tokens passes.
+    #[must_use]
+    pub fn matches<'a>(&self, pool_type: &str, tokens: impl IntoIterator<Item = &'a str>) -> bool {
+        let type_ok = self.pool_types.is_empty() || self.pool_types.iter().any(|t| t == pool_type);
+        type_ok
+            && (self.tokens.is_empty()
+                || tokens.into_iter().any(|addr| {
+                    self.tokens
+                        .iter()
+                        .any(|wanted| wanted.eq_ignore_ascii_case(addr))
+                }))
+    }
+}
+
+/// Serializable copy of a connection's subscriptions, kept with its
+/// session so they survive a reconnect or a process handover.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriptionState {
+    /// Explicitly subscribed pools.
+    #[serde(default)]
+    pub pool_ids: Vec<PoolId>,
+    /// Whether the wildcard subscription is active.
+    #[serde(default)]
+    pub subscribe_all: bool,
+    /// Event delivery encoding.
+    #[serde(default)]
+    pub encoding: EventEncoding,
+    /// Whether account mode is active.
+    #[serde(default)]
+    pub account: bool,
+    /// Pool catalog filter, when the channel is subscribed.
+    #[serde(default)]
+    pub catalog: Option<CatalogFilter>,
+    /// Live pools passing the catalog filter.
+    #[serde(default)]
+    pub catalog_pools: Vec<PoolId>,
+    /// Pools whose trades are streamed (all when empty), when the trades
+    /// channel is subscribed.
+    #[serde(default)]
+    pub trades: Option<Vec<PoolId>>,
+    /// Liquidity threshold (string-encoded u128), if set.
+    #[serde(default)]
+    pub min_liquidity_change: Option<String>,
+    /// Event types delivered from subscribed pools (all when `None`).
+    #[serde(default)]
+    pub event_types: Option<Vec<String>>,
+}
 
 /// Manages the set of pool subscriptions for a single WebSocket connection.
 #[derive(Debug, Default)]
@@ -14,6 +110,25 @@ pub struct SubscriptionManager {
     pool_ids: HashSet<PoolId>,
     /// Whether the client subscribes to all pools (wildcard `"*"`).
     subscribe_all: bool,
+    /// Encoding for delivered events.
+    encoding: EventEncoding,
+    /// Client id of the connection; required for account mode.
+    client_id: Option<String>,
+    /// Whether events caused by `client_id` are delivered.
+    account: bool,
+    /// Pool catalog filter, when the channel is subscribed.
+    catalog: Option<CatalogFilter>,
+    /// Live pools passing the catalog filter, so their removal (which
+    /// carries no type or tokens) can be matched.
+    catalog_pools: HashSet<PoolId>,
+    /// Pools whose trades are streamed (all when empty), when the trades
+    /// channel is subscribed.
+    trades: Option<HashSet<PoolId>>,
+    /// Smallest `liquidity_delta` of a delivered `liquidity_changed` event
+    /// from a subscribed pool.
+    min_liquidity_change: Option<u128>,
+    /// Event types delivered from subscribed pools (all when `None`).
+    event_types: Option<HashSet<String>>,
 }
 
 impl SubscriptionManager {
@@ -23,6 +138,157 @@ impl SubscriptionManager {
         Self::default()
     }
 
+    /// Creates a manager for a connection opened by `client_id`.
+    #[must_use]
+    pub fn for_client(client_id: Option<String>) -> Self {
+        Self {
+            client_id,
+            ..Self::default()
+        }
+    }
+
+    /// Rebuilds a manager for `client_id` from exported subscriptions.
+    /// Account mode is only restored when the connection has a client id.
+    #[must_use]
+    pub fn restore(client_id: Option<String>, state: &SubscriptionState) -> Self {
+        Self {
+            pool_ids: state.pool_ids.iter().copied().collect(),
+            subscribe_all: state.subscribe_all,
+            encoding: state.encoding,
+            account: state.account && client_id.is_some(),
+            client_id,
+            catalog: state.catalog.clone(),
+            catalog_pools: state.catalog_pools.iter().copied().collect(),
+            trades: state
+                .trades
+                .as_ref()
+                .map(|pools| pools.iter().copied().collect()),
+            min_liquidity_change: state
+                .min_liquidity_change
+                .as_deref()
+                .and_then(|m| m.parse().ok())
+                .filter(|m| *m > 0),
+            event_types: state
+                .event_types
+                .as_ref()
+                .map(|types| types.iter().cloned().collect()),
+        }
+    }
+
+    /// Exports the subscriptions for a session descriptor.
+    #[must_use]
+    pub fn export(&self) -> SubscriptionState {
+        SubscriptionState {
+            pool_ids: sorted(&self.pool_ids),
+            subscribe_all: self.subscribe_all,
+            encoding: self.encoding,
+            account: self.account,
+            catalog: self.catalog.clone(),
+            catalog_pools: sorted(&self.catalog_pools),
+            trades: self.trades.as_ref().map(sorted),
+            min_liquidity_change: self.min_liquidity_change.map(|m| m.to_string()),
+            event_types: self.event_types(),
+        }
+    }
+
+    /// Enables or disables account mode.
+    ///
+    /// Returns `false` (leaving the mode off) when enabling on a
+    /// connection without a client id.
+    pub fn set_account(&mut self, enabled: bool) -> bool {
+        if enabled && self.client_id.is_none() {
+            return false;
+        }
+        self.account = enabled;
+        true
+    }
+
+    /// Returns `true` if account mode is active.
+    #[must_use]
+    pub fn is_account(&self) -> bool {
+        self.account
+    }
+
+    /// Subscribes the pool catalog channel with `filter`, replacing any
+    /// previous filter. `existing` lists pools already registered that
+    /// pass it, whose removal will be announced.
+    pub fn set_catalog(
+        &mut self,
+        filter: CatalogFilter,
+        existing: impl IntoIterator<Item = PoolId>,
+    ) {
+        self.catalog = Some(filter);
+        self.catalog_pools = existing.into_iter().collect();
+    }
+
+    /// Unsubscribes the pool catalog channel.
+    pub fn clear_catalog(&mut self) {
+        self.catalog = None;
+        self.catalog_pools.clear();
+    }
+
+    /// Returns the catalog filter, if the channel is subscribed.
+    #[must_use]
+    pub fn catalog(&self) -> Option<&CatalogFilter> {
+        self.catalog.as_ref()
+    }
+
+    /// Subscribes the trades channel for `pools`, or for every pool when
+    /// empty. Replaces any previous trades filter.
+    pub fn set_trades(&mut self, pools: &[PoolId]) {
+        self.trades = Some(pools.iter().copied().collect());
+    }
+
+    /// Unsubscribes the trades channel.
+    pub fn clear_trades(&mut self) {
+        self.trades = None;
+    }
+
+    /// Returns `true` if the trades channel is subscribed.
+    #[must_use]
+    pub fn trades_enabled(&self) -> bool {
+        self.trades.is_some()
+    }
+
+    /// Returns `true` if a trade on `pool_id` should be streamed.
+    #[must_use]
+    pub fn wants_trade(&self, pool_id: PoolId) -> bool {
+        self.trades
+            .as_ref()
+            .is_some_and(|pools| pools.is_empty() || pools.contains(&pool_id))
+    }
+
+    /// Sets the liquidity threshold of subscribed pools; `None` or zero
+    /// delivers every liquidity change.
+    pub fn set_min_liquidity_change(&mut self, min: Option<u128>) {
+        self.min_liquidity_change = min.filter(|m| *m > 0);
+    }
+
+    /// Returns the liquidity threshold, if set.
+    #[must_use]
+    pub fn min_liquidity_change(&self) -> Option<u128> {
+        self.min_liquidity_change
+    }
+
+    /// Restricts events from subscribed pools to `types`; `None` or an
+    /// empty list delivers every type.
+    pub fn set_event_types(&mut self, types: Option<Vec<String>>) {
+        self.event_types = types
+            .filter(|types| !types.is_empty())
+            .map(|types| types.into_iter().collect());
+    }
+
+    /// Returns the event types delivered from subscribed pools, sorted,
+    /// or `None` when every type is.
+    #[must_use]
+    pub fn event_types(&self) -> Option<Vec<String>> {
+        self.event_types.as_ref().map(|types| {
+            let mut types: Vec<String> = types.iter().cloned().collect();
+            types.sort();
+            types
+        })
+    }
+
     /// Adds pool IDs to the subscription set. `"*"` enables the wildcard.
     pub fn subscribe(&mut self, ids: &[PoolId], wildcard: bool) {
         if wildcard {
@@ -46,6 +312,71 @@ impl SubscriptionManager {
         self.subscribe_all || self.pool_ids.contains(&pool_id)
     }
 
+    /// Returns `true` if `event` should be delivered: its pool is
+    /// subscribed and it passes the type filter and the liquidity
+    /// threshold, account mode is on and this client caused it, or it is
+    /// a catalog announcement passing the filter.
+    ///
+    /// Takes `&mut self` because catalog announcements update the set of
+    /// catalogued pools.
+    pub fn wants(&mut self, event: &PoolEvent) -> bool {
+        let catalogued = self.catalog_wants(event);
+        catalogued
+            || (self.matches(event.pool_id())
+                && self.type_wanted(event)
+                && self.clears_threshold(event))
+            || (self.account
+                && event.actor().is_some()
+                && event.actor() == self.client_id.as_deref())
+    }
+
+    /// Applies the event-type filter.
+    fn type_wanted(&self, event: &PoolEvent) -> bool {
+        self.event_types
+            .as_ref()
+            .is_none_or(|types| types.contains(event.event_type_str()))
+    }
+
+    /// Applies the liquidity threshold. Other events, and changes whose
+    /// delta cannot be parsed, always pass.
+    fn clears_threshold(&self, event: &PoolEvent) -> bool {
+        match (self.min_liquidity_change, event) {
+            (
+                Some(min),
+                PoolEvent::LiquidityChanged {
+                    liquidity_delta, ..
+                },
+            ) => liquidity_delta
+                .parse::<u128>()
+                .map_or(true, |delta| delta >= min),
+            _ => true,
+        }
+    }
+
+    /// Applies the catalog filter to lifecycle events.
+    fn catalog_wants(&mut self, event: &PoolEvent) -> bool {
+        let Some(filter) = &self.catalog else {
+            return false;
+        };
+        match event {
+            PoolEvent::PoolCreated {
+                pool_id,
+                pool_type,
+                token_a,
+                token_b,
+                ..
+            } => {
+                let pass = filter.matches(pool_type, [token_a.as_str(), token_b.as_str()]);
+                if pass {
+                    self.catalog_pools.insert(*pool_id);
+                }
+                pass
+            }
+            PoolEvent::PoolRemoved { pool_id, .. } => self.catalog_pools.remove(pool_id),
+            _ => false,
+        }
+    }
+
     /// Returns the number of explicitly subscribed pool IDs.
     #[must_use]
     pub fn count(&self) -> usize {
@@ -57,6 +388,24 @@ impl SubscriptionManager {
     pub fn is_subscribed_all(&self) -> bool {
         self.subscribe_all
     }
+
+    /// Sets the event delivery encoding for this connection.
+    pub fn set_encoding(&mut self, encoding: EventEncoding) {
+        self.encoding = encoding;
+    }
+
+    /// Returns the event delivery encoding for this connection.
+    #[must_use]
+    pub fn encoding(&self) -> EventEncoding {
+        self.encoding
+    }
+}
+
+/// Lists `pools` in a stable order.
+fn sorted(pools: &HashSet<PoolId>) -> Vec<PoolId> {
+    let mut pools: Vec<PoolId> = pools.iter().copied().collect();
+    pools.sort();
+    pools
 }
 
 #[cfg(test)]
@@ -97,6 +446,185 @@ mod tests {
         assert!(!mgr.matches(id));
     }
 
+    #[test]
+    fn encoding_defaults_to_json() {
+        let mut mgr = SubscriptionManager::new();
+        assert_eq!(mgr.encoding(), EventEncoding::Json);
+        mgr.set_encoding(EventEncoding::Protobuf);
+        assert_eq!(mgr.encoding(), EventEncoding::Protobuf);
+        assert_eq!(EventEncoding::parse("xml"), None);
+    }
+
+    #[test]
+    fn account_mode_delivers_only_own_events() {
+        let swap = |actor: Option<&str>| PoolEvent::SwapExecuted {
+            pool_id: PoolId::new(),
+            command_id: "cmd".to_string(),
+            amount_in: "1".to_string(),
+            amount_out: "1".to_string(),
+            fee: "0".to_string(),
+            fee_rebate: "0".to_string(),
+            new_price: "1".to_string(),
+            price_change_bps: 0,
+            token_in: "0xaaa".to_string(),
+            swap_kind: crate::domain::pool_operation::SwapKind::ExactIn,
+            actor: actor.map(str::to_string),
+            timestamp: chrono::Utc::now(),
+        };
+
+        let mut anonymous = SubscriptionManager::new();
+        assert!(!anonymous.set_account(true));
+
+        let mut mgr = SubscriptionManager::for_client(Some("bot-1".to_string()));
+        assert!(!mgr.wants(&swap(Some("bot-1"))));
+        assert!(mgr.set_account(true));
+        assert!(mgr.wants(&swap(Some("bot-1"))));
+        assert!(!mgr.wants(&swap(Some("bot-2"))));
+        assert!(!mgr.wants(&swap(None)));
+    }
+
+    #[test]
+    fn catalog_announces_matching_pools() {
+        let created = |pool_id, pool_type: &str, token_b: &str| PoolEvent::PoolCreated {
+            pool_id,
+            pool_type: pool_type.to_string(),
+            token_a: "usdc".to_string(),
+            token_b: token_b.to_string(),
+            fee_tier: 30,
+            config: serde_json::Value::Null,
+            metadata: serde_json::Value::Null,
+            owner: None,
+            timestamp: chrono::Utc::now(),
+        };
+        let removed = |pool_id| PoolEvent::PoolRemoved {
+            pool_id,
+            timestamp: chrono::Utc::now(),
+        };
+
+        let existing = PoolId::new();
+        let mut mgr = SubscriptionManager::new();
+        mgr.set_catalog(
+            CatalogFilter {
+                pool_types: vec!["clmm".to_string()],
+                tokens: vec!["WETH".to_string()],
+            },
+            [existing],
+        );
+        let (wanted, other_type, other_token) = (PoolId::new(), PoolId::new(), PoolId::new());
+        assert!(mgr.wants(&created(wanted, "clmm", "weth")));
+        assert!(!mgr.wants(&created(other_type, "weighted", "weth")));
+        assert!(!mgr.wants(&created(other_token, "clmm", "wbtc")));
+
+        assert!(mgr.wants(&removed(wanted)));
+        assert!(mgr.wants(&removed(existing)));
+        assert!(!mgr.wants(&removed(other_type)));
+        assert!(!mgr.wants(&removed(wanted)));
+
+        mgr.clear_catalog();
+        assert!(!mgr.wants(&created(PoolId::new(), "clmm", "weth")));
+    }
+
+    #[test]
+    fn trades_channel_filters_by_pool() {
+        let (wanted, other) = (PoolId::new(), PoolId::new());
+        let mut mgr = SubscriptionManager::new();
+        assert!(!mgr.wants_trade(wanted));
+        mgr.set_trades(&[wanted]);
+        assert!(mgr.wants_trade(wanted));
+        assert!(!mgr.wants_trade(other));
+        mgr.set_trades(&[]);
+        assert!(mgr.wants_trade(other));
+        mgr.clear_trades();
+        assert!(!mgr.trades_enabled());
+    }
+
+    #[test]
+    fn liquidity_threshold_drops_small_changes() {
+        let pool_id = PoolId::new();
+        let change = |delta: &str, actor: Option<&str>| PoolEvent::LiquidityChanged {
+            pool_id,
+            change_type: crate::domain::pool_event::LiquidityChangeType::Add,
+            amount_a: "1".to_string(),
+            amount_b: "1".to_string(),
+            new_total_liquidity: "1000000".to_string(),
+            liquidity_delta: delta.to_string(),
+            position_id: None,
+            range: None,
+            actor: actor.map(str::to_string),
+            timestamp: chrono::Utc::now(),
+        };
+
+        let mut mgr = SubscriptionManager::for_client(Some("treasury".to_string()));
+        mgr.subscribe(&[pool_id], false);
+        mgr.set_min_liquidity_change(Some(1_000));
+        assert!(mgr.wants(&change("1000", None)));
+        assert!(!mgr.wants(&change("999", None)));
+
+        // Own changes still arrive in account mode.
+        assert!(mgr.set_account(true));
+        assert!(mgr.wants(&change("1", Some("treasury"))));
+
+        mgr.set_min_liquidity_change(Some(0));
+        assert_eq!(mgr.min_liquidity_change(), None);
+        assert!(mgr.wants(&change("1", None)));
+    }
+
+    #[test]
+    fn event_types_filter_subscribed_pools() {
+        let pool_id = PoolId::new();
+        let price = |actor: Option<&str>| PoolEvent::OraclePriceUpdated {
+            pool_id,
+            old_price: "1".to_string(),
+            new_price: "2".to_string(),
+            actor: actor.map(str::to_string),
+            timestamp: chrono::Utc::now(),
+        };
+        let removed = PoolEvent::PoolRemoved {
+            pool_id,
+            timestamp: chrono::Utc::now(),
+        };
+
+        let mut mgr = SubscriptionManager::for_client(Some("desk".to_string()));
+        mgr.subscribe(&[pool_id], false);
+        mgr.set_event_types(Some(vec!["pool_removed".to_string()]));
+        assert!(mgr.wants(&removed));
+        assert!(!mgr.wants(&price(None)));
+
+        // Own events still arrive in account mode.
+        assert!(mgr.set_account(true));
+        assert!(mgr.wants(&price(Some("desk"))));
+
+        mgr.set_event_types(Some(Vec::new()));
+        assert_eq!(mgr.event_types(), None);
+        assert!(mgr.wants(&price(None)));
+    }
+
+    #[test]
+    fn export_and_restore_round_trip() {
+        let pool = PoolId::new();
+        let mut mgr = SubscriptionManager::for_client(Some("bot-1".to_string()));
+        mgr.subscribe(&[pool], false);
+        mgr.set_encoding(EventEncoding::Protobuf);
+        mgr.set_account(true);
+        mgr.set_trades(&[pool]);
+        mgr.set_min_liquidity_change(Some(500));
+        mgr.set_event_types(Some(vec!["swap_executed".to_string()]));
+
+        let state = mgr.export();
+        let Ok(json) = serde_json::to_string(&state) else {
+            panic!("state not serializable");
+        };
+        let Ok(decoded) = serde_json::from_str::<SubscriptionState>(&json) else {
+            panic!("state not deserializable");
+        };
+        let restored = SubscriptionManager::restore(Some("bot-1".to_string()), &decoded);
+        assert_eq!(restored.export(), state);
+        assert!(restored.matches(pool) && restored.is_account());
+
+        let anonymous = SubscriptionManager::restore(None, &decoded);
+        assert!(!anonymous.is_account());
+    }
+
     #[test]
     fn count_tracks_explicit() {
         let mut mgr = SubscriptionManager::new();